@@ -1,10 +1,18 @@
 #[repr(transparent)]
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct NVDIMMDeviceHandle {
     handle: u32,
 }
 
 impl NVDIMMDeviceHandle {
+    pub const fn new(handle: u32) -> Self {
+        Self { handle }
+    }
+
+    pub const fn raw(&self) -> u32 {
+        self.handle
+    }
+
     pub fn dimm_number(&self) -> u8 {
         (self.handle & 15) as u8
     }
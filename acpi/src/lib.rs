@@ -3,3 +3,4 @@
 #![forbid(warnings)]
 
 pub mod devices;
+pub mod tables;
@@ -0,0 +1,5 @@
+mod nfit;
+mod rsdp;
+mod sdt;
+
+pub use {nfit::*, rsdp::*, sdt::*};
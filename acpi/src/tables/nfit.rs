@@ -0,0 +1,237 @@
+use {
+    super::sdt::SdtHeader,
+    crate::devices::acpi_defined::NVDIMMDeviceHandle,
+    core::{convert::TryInto, iter::FusedIterator},
+};
+
+/// Byte representation of the persistent memory address range type GUID, `66F0D379-B4F3-4074-AC43-0D3318B78CDB`.
+pub const NFIT_PERSISTENT_MEMORY_REGION: [u8; 16] = [
+    0x79, 0xD3, 0xF0, 0x66, 0xF3, 0xB4, 0x74, 0x40, 0xAC, 0x43, 0x0D, 0x33, 0x18, 0xB7, 0x8C, 0xDB,
+];
+
+/* Structure types */
+const SPA_RANGE: u16 = 0;
+const REGION_MAPPING: u16 = 1;
+
+const SPA_RANGE_SIZE: usize = 56;
+const REGION_MAPPING_SIZE: usize = 48;
+
+/// NVDIMM Firmware Interface Table, describing the NVDIMMs and the address ranges they're mapped to.
+#[derive(Clone, Copy)]
+pub struct Nfit<'a> {
+    structures: &'a [u8],
+}
+
+impl<'a> Nfit<'a> {
+    pub const SIGNATURE: [u8; 4] = *b"NFIT";
+
+    pub fn parse(table: SdtHeader<'a>) -> Option<Self> {
+        if table.signature() != Self::SIGNATURE {
+            return None;
+        }
+
+        /* Reserved field following the header */
+        Some(Self {
+            structures: table.data().get(4..)?,
+        })
+    }
+
+    /// Returns iterator over the structures, in the order they're listed.
+    pub fn structures(&self) -> NfitStructures<'a> {
+        NfitStructures {
+            structures: self.structures,
+        }
+    }
+
+    /// Returns the system physical address range structure with the index.
+    pub fn spa_range(&self, index: u16) -> Option<SpaRange> {
+        self.structures()
+            .find_map(|structure: NfitStructure| match structure {
+                NfitStructure::SpaRange(range) if range.index() == index => Some(range),
+                _ => None,
+            })
+    }
+
+    /// Returns iterator over the region mappings of the NVDIMM.
+    pub fn region_mappings(
+        &self,
+        device_handle: NVDIMMDeviceHandle,
+    ) -> impl Iterator<Item = RegionMapping> + 'a {
+        self.structures()
+            .filter_map(move |structure: NfitStructure| match structure {
+                NfitStructure::RegionMapping(mapping)
+                    if mapping.device_handle() == device_handle =>
+                {
+                    Some(mapping)
+                }
+                _ => None,
+            })
+    }
+}
+
+/// Structure of the NFIT.
+#[derive(Clone, Copy)]
+pub enum NfitStructure {
+    SpaRange(SpaRange),
+    RegionMapping(RegionMapping),
+    /// Structure of another type, identified by it's type.
+    Other(u16),
+}
+
+/// Iterator over the structures of an NFIT.
+pub struct NfitStructures<'a> {
+    structures: &'a [u8],
+}
+
+impl Iterator for NfitStructures<'_> {
+    type Item = NfitStructure;
+
+    fn next(&mut self) -> Option<<Self as Iterator>::Item> {
+        let header: &[u8] = self.structures.get(..4)?;
+        let structure_type: u16 = u16::from_le_bytes([header[0], header[1]]);
+        let length: usize = usize::from(u16::from_le_bytes([header[2], header[3]]));
+
+        /* Stops at malformed structures */
+        if length < 4 || self.structures.len() < length {
+            self.structures = &[];
+
+            return None;
+        }
+
+        let (structure, rest): (&[u8], &[u8]) = self.structures.split_at(length);
+
+        self.structures = rest;
+
+        Some(match structure_type {
+            SPA_RANGE if length >= SPA_RANGE_SIZE => NfitStructure::SpaRange(SpaRange {
+                raw: structure[..SPA_RANGE_SIZE].try_into().ok()?,
+            }),
+            REGION_MAPPING if length >= REGION_MAPPING_SIZE => {
+                NfitStructure::RegionMapping(RegionMapping {
+                    raw: structure[..REGION_MAPPING_SIZE].try_into().ok()?,
+                })
+            }
+            _ => NfitStructure::Other(structure_type),
+        })
+    }
+}
+
+impl FusedIterator for NfitStructures<'_> {}
+
+/// System Physical Address (SPA) Range structure, describing an address range and it's type.
+#[derive(Clone, Copy)]
+pub struct SpaRange {
+    raw: [u8; SPA_RANGE_SIZE],
+}
+
+impl SpaRange {
+    /// Returns the index the region mappings refer to the range by.
+    pub fn index(&self) -> u16 {
+        read_u16(&self.raw, 4)
+    }
+
+    pub fn flags(&self) -> u16 {
+        read_u16(&self.raw, 6)
+    }
+
+    pub fn proximity_domain(&self) -> u32 {
+        read_u32(&self.raw, 12)
+    }
+
+    /// Returns the byte representation of the address range type GUID.
+    pub fn address_range_type(&self) -> [u8; 16] {
+        let mut guid: [u8; 16] = [0; 16];
+
+        guid.copy_from_slice(&self.raw[16..32]);
+
+        guid
+    }
+
+    pub fn is_persistent_memory(&self) -> bool {
+        self.raw[16..32] == NFIT_PERSISTENT_MEMORY_REGION
+    }
+
+    pub fn base(&self) -> u64 {
+        read_u64(&self.raw, 32)
+    }
+
+    pub fn length(&self) -> u64 {
+        read_u64(&self.raw, 40)
+    }
+
+    pub fn memory_mapping_attributes(&self) -> u64 {
+        read_u64(&self.raw, 48)
+    }
+}
+
+/// NVDIMM Region Mapping structure, describing the part of an SPA range an NVDIMM provides.
+#[derive(Clone, Copy)]
+pub struct RegionMapping {
+    raw: [u8; REGION_MAPPING_SIZE],
+}
+
+impl RegionMapping {
+    pub fn device_handle(&self) -> NVDIMMDeviceHandle {
+        NVDIMMDeviceHandle::new(read_u32(&self.raw, 4))
+    }
+
+    pub fn physical_id(&self) -> u16 {
+        read_u16(&self.raw, 8)
+    }
+
+    pub fn region_id(&self) -> u16 {
+        read_u16(&self.raw, 10)
+    }
+
+    /// Returns the index of the SPA range the region is mapped to, or zero if it isn't mapped.
+    pub fn spa_range_index(&self) -> u16 {
+        read_u16(&self.raw, 12)
+    }
+
+    pub fn control_region_index(&self) -> u16 {
+        read_u16(&self.raw, 14)
+    }
+
+    pub fn region_size(&self) -> u64 {
+        read_u64(&self.raw, 16)
+    }
+
+    /// Returns the offset of the region's first byte within the SPA range.
+    pub fn region_offset(&self) -> u64 {
+        read_u64(&self.raw, 24)
+    }
+
+    /// Returns the NVDIMM physical address the region starts at.
+    pub fn physical_address_region_base(&self) -> u64 {
+        read_u64(&self.raw, 32)
+    }
+
+    pub fn interleave_index(&self) -> u16 {
+        read_u16(&self.raw, 40)
+    }
+
+    pub fn interleave_ways(&self) -> u16 {
+        read_u16(&self.raw, 42)
+    }
+
+    pub fn state_flags(&self) -> u16 {
+        read_u16(&self.raw, 44)
+    }
+}
+
+fn read_u16(data: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([data[offset], data[offset + 1]])
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([
+        data[offset],
+        data[offset + 1],
+        data[offset + 2],
+        data[offset + 3],
+    ])
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from(read_u32(data, offset)) | u64::from(read_u32(data, offset + 4)) << 32
+}
@@ -0,0 +1,89 @@
+use {
+    super::sdt::{checksum, SdtHeader},
+    core::{convert::TryInto, slice::from_raw_parts},
+};
+
+/// "RSD PTR "
+pub const RSDP_SIGNATURE: [u8; 8] = *b"RSD PTR ";
+
+/// Size of the structure defined by ACPI 1.0, which the checksum covers.
+const RSDP_V1_SIZE: usize = 20;
+
+/// Root System Description Pointer, which locates the other system description tables.
+#[repr(C, packed)]
+pub struct Rsdp {
+    signature: [u8; 8],
+    checksum: u8,
+    oem_id: [u8; 6],
+    revision: u8,
+    rsdt_address: u32,
+    length: u32,
+    xsdt_address: u64,
+    extended_checksum: u8,
+    reserved: [u8; 3],
+}
+
+impl Rsdp {
+    /// Verifies the signature and the checksums which apply to the structure's revision.
+    pub fn is_valid(&self) -> bool {
+        let bytes: &[u8] = unsafe {
+            from_raw_parts(
+                self as *const Self as *const u8,
+                core::mem::size_of::<Self>(),
+            )
+        };
+
+        self.signature == RSDP_SIGNATURE
+            && checksum(&bytes[..RSDP_V1_SIZE]) == 0
+            && (self.revision < 2 || checksum(bytes) == 0)
+    }
+
+    pub fn revision(&self) -> u8 {
+        self.revision
+    }
+
+    pub fn oem_id(&self) -> [u8; 6] {
+        self.oem_id
+    }
+
+    /// Finds the first table with the signature, through the XSDT when available and the RSDT otherwise.
+    /// # Safety
+    /// The structure must be valid and the tables it references must remain mapped for the rest of the program.
+    pub unsafe fn find_table(&self, signature: [u8; 4]) -> Option<SdtHeader<'static>> {
+        let (root, entry_size): (usize, usize) = if self.revision >= 2 && self.xsdt_address != 0 {
+            (self.xsdt_address as usize, 8)
+        } else {
+            (self.rsdt_address as usize, 4)
+        };
+
+        let root: SdtHeader<'static> = SdtHeader::from_address(root)?;
+
+        root.data()
+            .chunks(entry_size)
+            .filter_map(|entry: &[u8]| {
+                let address: usize = match *entry {
+                    [a, b, c, d] => u32::from_le_bytes([a, b, c, d]) as usize,
+                    _ => u64::from_le_bytes(entry.try_into().ok()?) as usize,
+                };
+
+                SdtHeader::from_address(address)
+            })
+            .find(|table: &SdtHeader<'static>| table.signature() == signature)
+    }
+}
+
+#[cfg(feature = "efi_interops")]
+use efi_interops::{traits::EfiConfigurationTable, types::EfiGuid};
+
+/// Located through the ACPI 2.0 table GUID, which is provided for every revision since.
+#[cfg(feature = "efi_interops")]
+unsafe impl EfiConfigurationTable for Rsdp {
+    fn guid() -> EfiGuid {
+        (
+            0x8868_E871,
+            0xE4F1,
+            0x11D3,
+            [0xBC, 0x22, 0x00, 0x80, 0xC7, 0x3C, 0x88, 0x81],
+        )
+    }
+}
@@ -0,0 +1,74 @@
+use core::{convert::TryInto, slice::from_raw_parts};
+
+/// Size of the header all system description tables start with.
+pub const SDT_HEADER_SIZE: usize = 36;
+
+/// Header of a system description table.
+#[derive(Clone, Copy)]
+pub struct SdtHeader<'a> {
+    table: &'a [u8],
+}
+
+impl<'a> SdtHeader<'a> {
+    /// Parses the header and verifies the table's checksum, returning `None` if the table is shorter than it's length.
+    pub fn parse(table: &'a [u8]) -> Option<Self> {
+        let length: usize = u32::from_le_bytes(table.get(4..8)?.try_into().ok()?) as usize;
+
+        if length < SDT_HEADER_SIZE || table.len() < length || checksum(&table[..length]) != 0 {
+            return None;
+        }
+
+        Some(Self {
+            table: &table[..length],
+        })
+    }
+
+    /// Parses the table at the address, reading as much memory as the table's length.
+    /// # Safety
+    /// The address must point to a system description table which remains mapped for the rest of the program.
+    pub unsafe fn from_address(address: usize) -> Option<SdtHeader<'static>> {
+        if address == 0 {
+            return None;
+        }
+
+        let header: &[u8] = from_raw_parts(address as *const u8, SDT_HEADER_SIZE);
+        let length: usize = u32::from_le_bytes(header[4..8].try_into().ok()?) as usize;
+
+        SdtHeader::parse(from_raw_parts(
+            address as *const u8,
+            length.max(SDT_HEADER_SIZE),
+        ))
+    }
+
+    pub fn signature(&self) -> [u8; 4] {
+        [self.table[0], self.table[1], self.table[2], self.table[3]]
+    }
+
+    pub fn revision(&self) -> u8 {
+        self.table[8]
+    }
+
+    pub fn oem_id(&self) -> &'a [u8] {
+        &self.table[10..16]
+    }
+
+    pub fn oem_table_id(&self) -> &'a [u8] {
+        &self.table[16..24]
+    }
+
+    /// Returns the whole table, including the header.
+    pub fn table(&self) -> &'a [u8] {
+        self.table
+    }
+
+    /// Returns the table's contents following the header.
+    pub fn data(&self) -> &'a [u8] {
+        &self.table[SDT_HEADER_SIZE..]
+    }
+}
+
+/// Returns the sum of the bytes, which is zero for intact tables.
+pub(crate) fn checksum(data: &[u8]) -> u8 {
+    data.iter()
+        .fold(0, |sum: u8, &byte: &u8| sum.wrapping_add(byte))
+}
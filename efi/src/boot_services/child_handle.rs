@@ -0,0 +1,170 @@
+use {
+    super::{
+        types::protocol_handler::{EfiInterfaceType, EfiOpenProtocolAttributes},
+        EfiBootServices, EfiBootServices1x0,
+    },
+    crate::{
+        guid::EfiGuid,
+        protocols::{
+            device_path::{EfiDevicePathNode, EfiDevicePathProtocolRaw},
+            EfiProtocol, ParseResult,
+        },
+        status::{EfiStatusEnum, EfiStatusError},
+        types::{EfiFirmwareFault, EfiHandle},
+    },
+    alloc::{boxed::Box, vec::Vec},
+    core::{mem::size_of, ptr::NonNull, slice::from_raw_parts},
+};
+
+/// Child handle created by a bus driver, with a device path installed on it.
+///
+/// The handle has to be released with [`destroy`](ChildHandle::destroy) from the driver's `stop` function.
+/// Dropping it leaves the handle and it's protocols installed, leaking the device path.
+pub struct ChildHandle {
+    handle: EfiHandle,
+    device_path: Box<[u8]>,
+    interfaces: Vec<(EfiGuid, &'static [u8])>,
+    parents: Vec<(EfiHandle, EfiGuid, EfiHandle)>,
+}
+
+impl ChildHandle {
+    /// Creates a new handle with the parent controller's device path, extended with the node.
+    pub fn new(
+        boot_services: &EfiBootServices1x0,
+        parent_device_path: &EfiDevicePathProtocolRaw,
+        node: &EfiDevicePathNode<'_>,
+    ) -> EfiStatusEnum<Self> {
+        let device_path: Box<[u8]> = match unsafe { parent_device_path.append_node(node) } {
+            Some(device_path) => device_path.into_boxed_slice(),
+            None => return EfiStatusEnum::Error(EfiStatusError::EfiInvalidParameter, ()),
+        };
+
+        let mut handle: EfiHandle = 0 as _;
+
+        boot_services
+            .install_protocol_interface(
+                &mut handle,
+                &EfiDevicePathProtocolRaw::guid(),
+                EfiInterfaceType::NativeInterface,
+                Some(&device_path),
+            )
+            .map(|()| Self {
+                handle,
+                device_path,
+                interfaces: Vec::new(),
+                parents: Vec::new(),
+            })
+    }
+
+    pub fn handle(&self) -> EfiHandle {
+        self.handle
+    }
+
+    pub fn device_path(&self) -> EfiDevicePathProtocolRaw {
+        EfiDevicePathProtocolRaw::new(NonNull::from(&self.device_path[0]).cast())
+    }
+
+    /// Installs the protocol interface on the child handle.
+    pub fn install_interface<T>(
+        &mut self,
+        boot_services: &EfiBootServices1x0,
+        interface: &'static T,
+    ) -> EfiStatusEnum
+    where
+        T: EfiProtocol<Parsed = &'static T>,
+    {
+        let interface: &'static [u8] =
+            unsafe { from_raw_parts(interface as *const T as *const u8, size_of::<T>()) };
+
+        boot_services
+            .install_protocol_interface(
+                &mut self.handle,
+                &T::guid(),
+                EfiInterfaceType::NativeInterface,
+                Some(interface),
+            )
+            .map(|()| self.interfaces.push((T::guid(), interface)))
+    }
+
+    /// Opens the parent controller's protocol on behalf of the child, as required by the driver model.
+    ///
+    /// `agent_handle` is the driver binding handle of the driver which created the child.
+    /// Requires boot services revision 1.1 or newer.
+    pub fn open_parent<T>(
+        &mut self,
+        boot_services: &EfiBootServices,
+        controller_handle: EfiHandle,
+        agent_handle: EfiHandle,
+    ) -> Result<EfiStatusEnum<<T as ParseResult>::Result>, EfiFirmwareFault>
+    where
+        T: EfiProtocol
+            + ParseResult<
+                Result = core::result::Result<
+                    <T as EfiProtocol>::Parsed,
+                    <T as EfiProtocol>::Error,
+                >,
+            > + ?Sized,
+    {
+        let result: Result<EfiStatusEnum<<T as ParseResult>::Result>, EfiFirmwareFault> =
+            match boot_services.revision_1_1() {
+                Some(library_services) => library_services.open_protocol::<T>(
+                    controller_handle,
+                    agent_handle,
+                    Some(self.handle),
+                    EfiOpenProtocolAttributes::ByChildController,
+                ),
+                None => return Ok(EfiStatusEnum::Error(EfiStatusError::EfiUnsupported, ())),
+            };
+
+        if let Ok(EfiStatusEnum::Success(_)) | Ok(EfiStatusEnum::Warning(_, _)) = result {
+            self.parents
+                .push((controller_handle, T::guid(), agent_handle));
+        }
+
+        result
+    }
+
+    /// Closes the parents' protocols, uninstalls the protocol interfaces and the device path, releasing the handle.
+    ///
+    /// On failure the child is returned back, with the steps which succeeded already undone.
+    pub fn destroy(mut self, boot_services: &EfiBootServices) -> EfiStatusEnum<(), Self> {
+        if let Some(library_services) = boot_services.revision_1_1() {
+            while let Some(&(controller_handle, protocol, agent_handle)) = self.parents.last() {
+                if let EfiStatusEnum::Error(error, ()) = library_services.close_protocol(
+                    controller_handle,
+                    &protocol,
+                    agent_handle,
+                    Some(self.handle),
+                ) {
+                    return EfiStatusEnum::Error(error, self);
+                }
+
+                self.parents.pop();
+            }
+        }
+
+        while let Some(&(protocol, interface)) = self.interfaces.last() {
+            if let EfiStatusEnum::Error(error, ()) = boot_services.uninstall_protocol_interface(
+                &mut self.handle,
+                &protocol,
+                Some(interface),
+            ) {
+                return EfiStatusEnum::Error(error, self);
+            }
+
+            self.interfaces.pop();
+        }
+
+        let mut handle: EfiHandle = self.handle;
+
+        match boot_services.uninstall_protocol_interface(
+            &mut handle,
+            &EfiDevicePathProtocolRaw::guid(),
+            Some(&self.device_path),
+        ) {
+            EfiStatusEnum::Success(()) => EfiStatusEnum::Success(()),
+            EfiStatusEnum::Warning(warning, ()) => EfiStatusEnum::Warning(warning, ()),
+            EfiStatusEnum::Error(error, ()) => EfiStatusEnum::Error(error, self),
+        }
+    }
+}
@@ -0,0 +1,323 @@
+use {
+    super::{
+        types::{
+            event_and_timer::{EfiEventType, EfiTimerDelay},
+            task_priority::EfiTaskPriorityLevel,
+        },
+        EfiBootServices1x0,
+    },
+    crate::{
+        status::{EfiStatusEnum, EfiStatusError},
+        types::{EfiEvent, VoidPtr},
+    },
+    alloc::{boxed::Box, rc::Rc, sync::Arc, task::Wake},
+    core::{
+        cell::UnsafeCell,
+        future::Future,
+        pin::Pin,
+        sync::atomic::{AtomicBool, Ordering},
+        task::{Context, Poll, Waker},
+        time::Duration,
+    },
+};
+
+type EventNotify = Box<dyn FnMut()>;
+
+/// Owned event which is closed when dropped.
+///
+/// Notify functions are Rust closures, which are kept alive for as long as the event exists.
+pub struct Event<'a> {
+    boot_services: &'a EfiBootServices1x0,
+    event: EfiEvent,
+    notify: Option<Box<EventNotify>>,
+}
+
+impl<'a> Event<'a> {
+    /// Creates new event without a notify function.
+    pub fn new(
+        boot_services: &'a EfiBootServices1x0,
+        event_type: EfiEventType,
+    ) -> EfiStatusEnum<Self> {
+        boot_services
+            .create_event(event_type, EfiTaskPriorityLevel::APPLICATION, None)
+            .map(|event: EfiEvent| Self {
+                boot_services,
+                event,
+                notify: None,
+            })
+    }
+
+    /// Creates new event which calls the closure at the given task priority level when notified.
+    pub fn with_notify<F>(
+        boot_services: &'a EfiBootServices1x0,
+        event_type: EfiEventType,
+        tpl: EfiTaskPriorityLevel,
+        notify: F,
+    ) -> EfiStatusEnum<Self>
+    where
+        F: FnMut() + 'static,
+    {
+        /* Boxed twice, so the context is a thin pointer */
+        let mut notify: Box<EventNotify> = Box::new(Box::new(notify));
+
+        let context: VoidPtr = &mut *notify as *mut EventNotify as VoidPtr;
+
+        boot_services
+            .create_event(event_type, tpl, Some((event_notify, context)))
+            .map(|event: EfiEvent| Self {
+                boot_services,
+                event,
+                notify: Some(notify),
+            })
+    }
+
+    /// Creates new timer event without a notify function.
+    pub fn timer(boot_services: &'a EfiBootServices1x0) -> EfiStatusEnum<Self> {
+        Self::new(boot_services, EfiEventType::Timer)
+    }
+
+    /// Creates new timer event which calls the closure at the given task priority level when the timer expires.
+    pub fn timer_with_notify<F>(
+        boot_services: &'a EfiBootServices1x0,
+        tpl: EfiTaskPriorityLevel,
+        notify: F,
+    ) -> EfiStatusEnum<Self>
+    where
+        F: FnMut() + 'static,
+    {
+        Self::with_notify(boot_services, EfiEventType::TimerNotifySignal, tpl, notify)
+    }
+
+    /// Takes ownership over an already created event.
+    /// # Safety
+    /// The event must be valid and must not be closed by anything else.
+    pub unsafe fn from_raw(boot_services: &'a EfiBootServices1x0, event: EfiEvent) -> Self {
+        Self {
+            boot_services,
+            event,
+            notify: None,
+        }
+    }
+
+    pub fn raw(&self) -> EfiEvent {
+        self.event
+    }
+
+    pub fn boot_services(&self) -> &'a EfiBootServices1x0 {
+        self.boot_services
+    }
+
+    pub fn signal(&self) -> EfiStatusEnum {
+        self.boot_services.signal_event(self.event)
+    }
+
+    /// Returns whether the event is signalled and resets it if it is.
+    pub fn check(&self) -> EfiStatusEnum<bool> {
+        match self.boot_services.check_event(self.event) {
+            EfiStatusEnum::Success(()) => EfiStatusEnum::Success(true),
+            EfiStatusEnum::Warning(warning, ()) => EfiStatusEnum::Warning(warning, true),
+            EfiStatusEnum::Error(EfiStatusError::EfiNotReady, ()) => EfiStatusEnum::Success(false),
+            EfiStatusEnum::Error(error, ()) => EfiStatusEnum::Error(error, ()),
+        }
+    }
+
+    /// Blocks until the event is signalled. Can only be called at `TPL_APPLICATION`.
+    pub fn wait(&self) -> EfiStatusEnum {
+        self.boot_services
+            .wait_for_event(&[self.event])
+            .map(|_: usize| ())
+    }
+
+    /// Sets the timer to expire every `period`.
+    pub fn set_periodic(&self, period: Duration) -> EfiStatusEnum {
+        self.boot_services
+            .set_timer(self.event, EfiTimerDelay::Pediodic, timer_units(period))
+    }
+
+    /// Sets the timer to expire once after `delay`.
+    pub fn set_relative(&self, delay: Duration) -> EfiStatusEnum {
+        self.boot_services
+            .set_timer(self.event, EfiTimerDelay::Relative, timer_units(delay))
+    }
+
+    pub fn cancel_timer(&self) -> EfiStatusEnum {
+        self.boot_services
+            .set_timer(self.event, EfiTimerDelay::Cancel, 0)
+    }
+}
+
+impl Drop for Event<'_> {
+    fn drop(&mut self) {
+        /* The notify function is dropped after the event is closed, so the firmware never calls into freed memory */
+        let _ = self.boot_services.close_event(self.event);
+    }
+}
+
+extern "efiapi" fn event_notify(_: EfiEvent, context: VoidPtr) {
+    (unsafe { &mut *(context as *mut EventNotify) })();
+}
+
+/// Converts the duration into the timer's 100 nanosecond units.
+fn timer_units(duration: Duration) -> u64 {
+    let units: u128 = duration.as_nanos() / 100;
+
+    if units > u128::from(u64::MAX) {
+        u64::MAX
+    } else {
+        units as u64
+    }
+}
+
+struct EventFutureState {
+    signalled: AtomicBool,
+    registering: AtomicBool,
+    waker: UnsafeCell<Option<Waker>>,
+}
+
+impl EventFutureState {
+    fn notify(&self) {
+        self.signalled.store(true, Ordering::Release);
+
+        /* When the notify function interrupts the registration, the future checks the flag once it's done */
+        if !self.registering.swap(true, Ordering::SeqCst) {
+            if let Some(waker) = unsafe { &*self.waker.get() } {
+                waker.wake_by_ref();
+            }
+
+            self.registering.store(false, Ordering::SeqCst);
+        }
+    }
+}
+
+/// Future which completes when the event is signalled.
+///
+/// The future can be awaited again by reference (e.g.: `(&mut future).await`), which makes it usable for periodic timers.
+/// The waker passed by the executor is woken from the event's notify function, so executors using [`EventWaker`] sleep until something happens.
+pub struct EventFuture<'a> {
+    event: Event<'a>,
+    state: Rc<EventFutureState>,
+}
+
+impl<'a> EventFuture<'a> {
+    /// Task priority level of the event's notify function.
+    pub const NOTIFY_LEVEL: EfiTaskPriorityLevel = EfiTaskPriorityLevel::CALLBACK;
+
+    /// Creates new event of the given type (which must contain `EVT_NOTIFY_SIGNAL`).
+    pub fn new(
+        boot_services: &'a EfiBootServices1x0,
+        event_type: EfiEventType,
+    ) -> EfiStatusEnum<Self> {
+        let state: Rc<EventFutureState> = Rc::new(EventFutureState {
+            signalled: AtomicBool::new(false),
+            registering: AtomicBool::new(false),
+            waker: UnsafeCell::new(None),
+        });
+
+        let notify_state: Rc<EventFutureState> = state.clone();
+
+        Event::with_notify(boot_services, event_type, Self::NOTIFY_LEVEL, move || {
+            notify_state.notify()
+        })
+        .map(|event: Event<'a>| Self { event, state })
+    }
+
+    /// Creates future which completes after `delay`.
+    pub fn timer(boot_services: &'a EfiBootServices1x0, delay: Duration) -> EfiStatusEnum<Self> {
+        let future: Self = match Self::new(boot_services, EfiEventType::TimerNotifySignal) {
+            EfiStatusEnum::Success(future) | EfiStatusEnum::Warning(_, future) => future,
+            EfiStatusEnum::Error(error, ()) => return EfiStatusEnum::Error(error, ()),
+        };
+
+        future.event.set_relative(delay).map(|()| future)
+    }
+
+    /// Creates future which completes every `period`.
+    pub fn periodic(
+        boot_services: &'a EfiBootServices1x0,
+        period: Duration,
+    ) -> EfiStatusEnum<Self> {
+        let future: Self = match Self::new(boot_services, EfiEventType::TimerNotifySignal) {
+            EfiStatusEnum::Success(future) | EfiStatusEnum::Warning(_, future) => future,
+            EfiStatusEnum::Error(error, ()) => return EfiStatusEnum::Error(error, ()),
+        };
+
+        future.event.set_periodic(period).map(|()| future)
+    }
+
+    pub fn event(&self) -> &Event<'a> {
+        &self.event
+    }
+
+    /// Returns whether the event was signalled since the future last completed, without completing it.
+    pub fn is_signalled(&self) -> bool {
+        self.state.signalled.load(Ordering::Acquire)
+    }
+}
+
+impl Future for EventFuture<'_> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<()> {
+        let state: &EventFutureState = &self.state;
+
+        if state.signalled.swap(false, Ordering::Acquire) {
+            return Poll::Ready(());
+        }
+
+        /* The notify function only runs at a higher TPL, so it can't be interrupted by the registration */
+        state.registering.store(true, Ordering::SeqCst);
+
+        unsafe {
+            match &mut *state.waker.get() {
+                Some(waker) if waker.will_wake(context.waker()) => (),
+                waker => *waker = Some(context.waker().clone()),
+            }
+        }
+
+        state.registering.store(false, Ordering::SeqCst);
+
+        if state.signalled.swap(false, Ordering::Acquire) {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+/// Waker which signals an event, allowing executors to block on it instead of polling continuously.
+///
+/// Executors poll the future and call [`park`](Self::park) when it's pending, e.g.:
+/// `nautilos_async::block_on_with_park(&mut Context::from_waker(&waker.waker()), future, || { let _ = waker.park(); })`.
+pub struct EventWaker {
+    event: Event<'static>,
+}
+
+/* Boot services are used from a single processor and event functions can be called up to TPL_NOTIFY */
+unsafe impl Send for EventWaker {}
+unsafe impl Sync for EventWaker {}
+
+impl EventWaker {
+    pub fn new(boot_services: &'static EfiBootServices1x0) -> EfiStatusEnum<Arc<Self>> {
+        Event::new(boot_services, EfiEventType::None)
+            .map(|event: Event<'static>| Arc::new(Self { event }))
+    }
+
+    pub fn waker(self: &Arc<Self>) -> Waker {
+        Waker::from(self.clone())
+    }
+
+    /// Blocks until the waker is woken. Can only be called at `TPL_APPLICATION`.
+    pub fn park(&self) -> EfiStatusEnum {
+        self.event.wait()
+    }
+}
+
+impl Wake for EventWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        let _ = self.event.signal();
+    }
+}
@@ -0,0 +1,375 @@
+use {
+    super::{
+        types::protocol_handler::EfiLocateSearchType, EfiBootServices, EfiBootServices1x0,
+        EfiBootServices1x1,
+    },
+    crate::{
+        guid::EfiGuid,
+        guids,
+        protocols::{
+            device_path::{EfiDevicePathProtocolRaw, EfiDevicePathToTextProtocol},
+            driver::EfiComponentName2Protocol,
+        },
+        status::{EfiStatusEnum, EfiStatusError},
+        types::{EfiHandle, VoidPtr},
+        utilities::string_length,
+    },
+    alloc::{string::String, vec, vec::Vec},
+    core::{
+        char::{decode_utf16, REPLACEMENT_CHARACTER},
+        fmt::{Display, Formatter, Result as FmtResult, Write},
+        mem::size_of,
+        slice::from_raw_parts,
+    },
+};
+
+/* EFI_OPEN_PROTOCOL_BY_CHILD_CONTROLLER */
+const BY_CHILD_CONTROLLER: u32 = 0x08;
+/* EFI_OPEN_PROTOCOL_BY_DRIVER */
+const BY_DRIVER: u32 = 0x10;
+
+/// Information gathered about a single handle.
+pub struct HandleInfo {
+    handle: EfiHandle,
+    protocols: Vec<EfiGuid>,
+    device_path: Option<String>,
+    driver_name: Option<String>,
+    controller_name: Option<String>,
+    drivers: Vec<EfiHandle>,
+    children: Vec<EfiHandle>,
+}
+
+impl HandleInfo {
+    pub fn handle(&self) -> EfiHandle {
+        self.handle
+    }
+
+    /// Returns the GUIDs of the protocols installed on the handle.
+    pub fn protocols(&self) -> &[EfiGuid] {
+        &self.protocols
+    }
+
+    /// Returns the handle's device path in text form.
+    pub fn device_path(&self) -> Option<&str> {
+        self.device_path.as_deref()
+    }
+
+    /// Returns the driver's name, when the handle has `EFI_COMPONENT_NAME2_PROTOCOL` installed.
+    pub fn driver_name(&self) -> Option<&str> {
+        self.driver_name.as_deref()
+    }
+
+    /// Returns the controller's name, as reported by the drivers managing it.
+    pub fn controller_name(&self) -> Option<&str> {
+        self.controller_name.as_deref()
+    }
+
+    /// Returns the handles of the drivers managing the controller.
+    pub fn drivers(&self) -> &[EfiHandle] {
+        &self.drivers
+    }
+
+    /// Returns the handles of the controller's children.
+    pub fn children(&self) -> &[EfiHandle] {
+        &self.children
+    }
+}
+
+/// Snapshot of the handle database, similar to the output of the shell's `dh -v` command.
+///
+/// The report is printed through it's [`Display`] implementation.
+pub struct HandleDatabase {
+    handles: Vec<HandleInfo>,
+}
+
+impl HandleDatabase {
+    /// Walks all handles, collecting their protocols, device paths and names.
+    ///
+    /// Names are queried in the supported language that best matches `language` (e.g.: `"en"`).
+    /// Requires boot services revision 1.1 or newer.
+    pub fn inspect(boot_services: &EfiBootServices, language: &str) -> EfiStatusEnum<Self> {
+        let library_services: &EfiBootServices1x1 = match boot_services.revision_1_1() {
+            Some(library_services) => library_services,
+            None => return EfiStatusEnum::Error(EfiStatusError::EfiUnsupported, ()),
+        };
+
+        let handles: Vec<EfiHandle> = match all_handles(boot_services) {
+            EfiStatusEnum::Success(handles) | EfiStatusEnum::Warning(_, handles) => handles,
+            EfiStatusEnum::Error(error, ()) => return EfiStatusEnum::Error(error, ()),
+        };
+
+        let mut database: Self = Self {
+            handles: handles
+                .iter()
+                .map(|&handle: &EfiHandle| HandleInfo {
+                    handle,
+                    protocols: protocols(boot_services, library_services, handle),
+                    device_path: None,
+                    driver_name: None,
+                    controller_name: None,
+                    drivers: Vec::new(),
+                    children: Vec::new(),
+                })
+                .collect(),
+        };
+
+        let to_text: Option<&EfiDevicePathToTextProtocol> = database
+            .handles
+            .iter()
+            .filter(|info: &&HandleInfo| {
+                info.protocols
+                    .contains(&guids::EFI_DEVICE_PATH_TO_TEXT_PROTOCOL)
+            })
+            .find_map(|info: &HandleInfo| {
+                match boot_services.handle_protocol::<EfiDevicePathToTextProtocol>(info.handle) {
+                    Ok(EfiStatusEnum::Success(Ok(to_text))) => Some(to_text),
+                    _ => None,
+                }
+            });
+
+        for info in &mut database.handles {
+            for protocol in &info.protocols {
+                if let EfiStatusEnum::Success(entries) =
+                    library_services.open_protocol_information(info.handle, protocol)
+                {
+                    for entry in entries {
+                        if entry.attributes() & BY_DRIVER != 0
+                            && !info.drivers.contains(&entry.agent_handle())
+                        {
+                            info.drivers.push(entry.agent_handle());
+                        }
+
+                        if entry.attributes() & BY_CHILD_CONTROLLER != 0
+                            && !info.children.contains(&entry.controller_handle())
+                        {
+                            info.children.push(entry.controller_handle());
+                        }
+                    }
+
+                    if !entries.is_empty() {
+                        let _ = boot_services.free_pool(entries.as_ptr() as VoidPtr);
+                    }
+                }
+            }
+
+            if info.protocols.contains(&guids::EFI_DEVICE_PATH_PROTOCOL) {
+                info.device_path = device_path(boot_services, to_text, info.handle);
+            }
+
+            if let Some(component_name) = component_name(boot_services, info.handle) {
+                info.driver_name = component_name
+                    .preferred_language(language)
+                    .and_then(|language: &str| {
+                        component_name.get_driver_name(language).unfold().ok()
+                    })
+                    .map(|(_, name): (_, &[u16])| to_string(name));
+            }
+
+            let handle: EfiHandle = info.handle;
+
+            info.controller_name = info.drivers.iter().find_map(|&driver: &EfiHandle| {
+                let component_name: &EfiComponentName2Protocol =
+                    component_name(boot_services, driver)?;
+
+                component_name
+                    .get_controller_name(handle, None, component_name.preferred_language(language)?)
+                    .unfold()
+                    .ok()
+                    .map(|(_, name): (_, &[u16])| to_string(name))
+            });
+        }
+
+        EfiStatusEnum::Success(database)
+    }
+
+    pub fn handles(&self) -> &[HandleInfo] {
+        &self.handles
+    }
+
+    pub fn find(&self, handle: EfiHandle) -> Option<&HandleInfo> {
+        self.handles
+            .iter()
+            .find(|info: &&HandleInfo| info.handle == handle)
+    }
+}
+
+impl Display for HandleDatabase {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        for info in &self.handles {
+            writeln!(f, "Handle {}", HandleAddress(info.handle))?;
+
+            for protocol in &info.protocols {
+                match guids::name(protocol) {
+                    Some(name) => writeln!(f, "  {}", name)?,
+                    None => writeln!(f, "  {}", protocol)?,
+                }
+            }
+
+            if let Some(device_path) = &info.device_path {
+                writeln!(f, "  Device path: {}", device_path)?;
+            }
+
+            if let Some(driver_name) = &info.driver_name {
+                writeln!(f, "  Driver name: {}", driver_name)?;
+            }
+
+            if let Some(controller_name) = &info.controller_name {
+                writeln!(f, "  Controller name: {}", controller_name)?;
+            }
+
+            write_handles(f, "Managed by", &info.drivers)?;
+            write_handles(f, "Children", &info.children)?;
+        }
+
+        Ok(())
+    }
+}
+
+struct HandleAddress(EfiHandle);
+
+impl Display for HandleAddress {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "0x{:0>width$X}",
+            self.0 as usize,
+            width = size_of::<usize>() * 2
+        )
+    }
+}
+
+fn write_handles(f: &mut Formatter<'_>, label: &str, handles: &[EfiHandle]) -> FmtResult {
+    if handles.is_empty() {
+        return Ok(());
+    }
+
+    write!(f, "  {}:", label)?;
+
+    for &handle in handles {
+        write!(f, " {}", HandleAddress(handle))?;
+    }
+
+    writeln!(f)
+}
+
+fn all_handles(boot_services: &EfiBootServices1x0) -> EfiStatusEnum<Vec<EfiHandle>> {
+    let mut handles: Vec<EfiHandle> = Vec::new();
+
+    loop {
+        match boot_services.locate_handle(EfiLocateSearchType::AllHandles, None, None, &mut handles)
+        {
+            EfiStatusEnum::Success(found) => {
+                let count: usize = found.len();
+
+                handles.truncate(count);
+
+                return EfiStatusEnum::Success(handles);
+            }
+            EfiStatusEnum::Warning(warning, found) => {
+                let count: usize = found.len();
+
+                handles.truncate(count);
+
+                return EfiStatusEnum::Warning(warning, handles);
+            }
+            /* Handles may get installed between the calls */
+            EfiStatusEnum::Error(EfiStatusError::EfiBufferTooSmall, size) => {
+                handles = vec![0 as _; size / size_of::<EfiHandle>()]
+            }
+            EfiStatusEnum::Error(error, _) => return EfiStatusEnum::Error(error, ()),
+        }
+    }
+}
+
+fn protocols(
+    boot_services: &EfiBootServices1x0,
+    library_services: &EfiBootServices1x1,
+    handle: EfiHandle,
+) -> Vec<EfiGuid> {
+    match library_services.protocols_per_handle(handle) {
+        EfiStatusEnum::Success(protocols) | EfiStatusEnum::Warning(_, protocols) => {
+            let guids: Vec<EfiGuid> = protocols
+                .iter()
+                .map(|&protocol: &&EfiGuid| *protocol)
+                .collect();
+
+            if !protocols.is_empty() {
+                let _ = boot_services.free_pool(protocols.as_ptr() as VoidPtr);
+            }
+
+            guids
+        }
+        EfiStatusEnum::Error(..) => Vec::new(),
+    }
+}
+
+fn component_name(
+    boot_services: &EfiBootServices1x0,
+    handle: EfiHandle,
+) -> Option<&'static EfiComponentName2Protocol> {
+    match boot_services.handle_protocol::<EfiComponentName2Protocol>(handle) {
+        Ok(EfiStatusEnum::Success(Ok(component_name))) => Some(component_name),
+        _ => None,
+    }
+}
+
+fn device_path(
+    boot_services: &EfiBootServices1x0,
+    to_text: Option<&EfiDevicePathToTextProtocol>,
+    handle: EfiHandle,
+) -> Option<String> {
+    let device_path: EfiDevicePathProtocolRaw =
+        match boot_services.handle_protocol::<EfiDevicePathProtocolRaw>(handle) {
+            Ok(EfiStatusEnum::Success(Ok(device_path))) => device_path,
+            _ => return None,
+        };
+
+    if let Some(text) =
+        to_text.and_then(|to_text| to_text.convert_device_path_to_text(&device_path, false, false))
+    {
+        let string: String = unsafe {
+            to_string(from_raw_parts(
+                text.as_ptr(),
+                string_length(text.as_ptr()).unwrap_or(0),
+            ))
+        };
+
+        let _ = boot_services.free_pool(text.as_ptr() as VoidPtr);
+
+        return Some(string);
+    }
+
+    /* Generic text form, used when the firmware can't convert device paths */
+    let mut string: String = String::new();
+    let mut separator: Option<char> = None;
+
+    for node in unsafe { device_path.nodes() } {
+        if node.is_end_of_instance() {
+            separator = Some(',');
+
+            continue;
+        }
+
+        if let Some(separator) = separator {
+            string.push(separator);
+        }
+
+        separator = Some('/');
+
+        let _ = write!(string, "Path({},{},", node.node_type(), node.sub_type());
+
+        for byte in node.data() {
+            let _ = write!(string, "{:02X}", byte);
+        }
+
+        string.push(')');
+    }
+
+    Some(string)
+}
+
+fn to_string(string: &[u16]) -> String {
+    decode_utf16(string.iter().copied())
+        .map(|character| character.unwrap_or(REPLACEMENT_CHARACTER))
+        .collect()
+}
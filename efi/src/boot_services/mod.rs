@@ -1,10 +1,16 @@
+pub mod child_handle;
+pub mod event;
+pub mod handle_database;
+pub mod protocol_notify;
+pub mod sync;
 pub mod types;
 
 use {
     crate::{
         protocols::{device_path::EfiDevicePathProtocolRaw, EfiProtocol, ParseResult},
         types::{EfiFirmwareFault, NonNullVoidPtr, Void, VoidMutPtr, VoidPtr},
-        EfiEvent, EfiGuid, EfiHandle, EfiPhysicalAddress, EfiStatus, EfiStatusEnum, EfiTableHeader,
+        EfiEvent, EfiGuid, EfiHandle, EfiPhysicalAddress, EfiRevision, EfiStatus, EfiStatusEnum,
+        EfiTableHeader,
     },
     core::{
         mem::size_of,
@@ -18,7 +24,10 @@ use {
             EfiAllocateType, EfiGetMemoryMapResult, EfiMemoryDescriptor, EfiMemoryDescriptorsMut,
             EfiMemoryType,
         },
-        protocol_handler::{EfiInterfaceType, EfiLocateSearchType},
+        protocol_handler::{
+            EfiInterfaceType, EfiLocateSearchType, EfiOpenProtocolAttributes,
+            EfiOpenProtocolInformationEntry,
+        },
         task_priority::EfiTaskPriorityLevel,
     },
 };
@@ -27,6 +36,8 @@ use {
 pub struct EfiBootServices {
     table_header: EfiTableHeader,
     v1_0: EfiBootServices1x0,
+    v1_1: EfiBootServices1x1,
+    v2_0: EfiBootServices2x0,
 }
 
 impl EfiBootServices {
@@ -37,6 +48,24 @@ impl EfiBootServices {
     pub fn header_mut(&mut self) -> &mut EfiTableHeader {
         &mut self.table_header
     }
+
+    /// Returns the services introduced with EFI 1.1, if the table's revision provides them.
+    pub fn revision_1_1(&self) -> Option<&EfiBootServices1x1> {
+        if self.table_header.revision() >= EfiRevision::EFI_1_10 {
+            Some(&self.v1_1)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the services introduced with UEFI 2.0, if the table's revision provides them.
+    pub fn revision_2_0(&self) -> Option<&EfiBootServices2x0> {
+        if self.table_header.revision() >= EfiRevision::EFI_2_00 {
+            Some(&self.v2_0)
+        } else {
+            None
+        }
+    }
 }
 
 impl Deref for EfiBootServices {
@@ -55,7 +84,7 @@ impl DerefMut for EfiBootServices {
 
 #[repr(C)]
 pub struct EfiBootServices1x0 {
-    raise_tpl: extern "efiapi" fn(EfiTaskPriorityLevel) -> EfiStatus,
+    raise_tpl: extern "efiapi" fn(EfiTaskPriorityLevel) -> EfiTaskPriorityLevel,
     restore_tpl: extern "efiapi" fn(EfiTaskPriorityLevel) -> Void,
 
     /*~~~~~~~~~~*/
@@ -94,9 +123,9 @@ pub struct EfiBootServices1x0 {
     install_protocol_interface:
         extern "efiapi" fn(*mut EfiHandle, *const EfiGuid, EfiInterfaceType, VoidPtr) -> EfiStatus,
     reinstall_protocol_interface:
-        extern "efiapi" fn(*mut EfiHandle, *const EfiGuid, VoidPtr, VoidPtr) -> EfiStatus,
+        extern "efiapi" fn(EfiHandle, *const EfiGuid, VoidPtr, VoidPtr) -> EfiStatus,
     uninstall_protocol_interface:
-        extern "efiapi" fn(*mut EfiHandle, *const EfiGuid, VoidPtr) -> EfiStatus,
+        extern "efiapi" fn(EfiHandle, *const EfiGuid, VoidPtr) -> EfiStatus,
     handle_protocol: extern "efiapi" fn(EfiHandle, *const EfiGuid, *mut VoidMutPtr) -> EfiStatus,
     _reserved: VoidPtr,
     register_protocol_notify: extern "efiapi" fn(&EfiGuid, EfiEvent, *mut VoidPtr) -> EfiStatus,
@@ -127,21 +156,49 @@ pub struct EfiBootServices1x0 {
     exit: extern "efiapi" fn(EfiHandle, EfiStatus, usize, *const u16) -> EfiStatus,
     unload_image: extern "efiapi" fn(EfiHandle) -> EfiStatus,
     exit_boot_services: extern "efiapi" fn(EfiHandle, usize) -> EfiStatus,
+
+    /*~~~~~~~~~~*/
+    get_next_monotonic_count: extern "efiapi" fn(*mut u64) -> EfiStatus,
+    stall: extern "efiapi" fn(usize) -> EfiStatus,
+    set_watchdog_timer: extern "efiapi" fn(usize, u64, usize, *const u16) -> EfiStatus,
 }
 
 impl EfiBootServices1x0 {
     /* TASK PRIORITY */
 
+    /// Raises the task priority level and returns the previous one.
+    /// # Safety
+    /// The new level must not be lower than the current one and the previous level must be restored afterwards.
+    /// [`TplGuard`](sync::TplGuard) does both.
     #[inline(always)]
-    pub fn raise_priority_level(&self, new_priority_level: EfiTaskPriorityLevel) -> EfiStatusEnum {
-        (self.raise_tpl)(new_priority_level).into_enum()
+    pub unsafe fn raise_priority_level(
+        &self,
+        new_priority_level: EfiTaskPriorityLevel,
+    ) -> EfiTaskPriorityLevel {
+        (self.raise_tpl)(new_priority_level)
     }
 
+    /// Restores the task priority level returned by [`raise_priority_level`](Self::raise_priority_level).
+    /// # Safety
+    /// The level must not be higher than the current one.
     #[inline(always)]
-    pub fn restore_priority_level(&self, old_priority_level: EfiTaskPriorityLevel) {
+    pub unsafe fn restore_priority_level(&self, old_priority_level: EfiTaskPriorityLevel) {
         (self.restore_tpl)(old_priority_level);
     }
 
+    /// Returns the current task priority level.
+    pub fn current_priority_level(&self) -> EfiTaskPriorityLevel {
+        /* Raising to the highest level is always valid and returns the current level */
+        unsafe {
+            let level: EfiTaskPriorityLevel =
+                self.raise_priority_level(EfiTaskPriorityLevel::HIGH_LEVEL);
+
+            self.restore_priority_level(level);
+
+            level
+        }
+    }
+
     /* MEMORY */
 
     #[inline(always)]
@@ -189,13 +246,19 @@ impl EfiBootServices1x0 {
             &mut descriptor_version,
         );
 
-        let return_data: EfiGetMemoryMapResult = EfiGetMemoryMapResult::new(
-            memory_map_key,
-            descriptor_version,
-            EfiMemoryDescriptorsMut::new(&mut memory_map[..allocation_size], descriptor_size),
-        );
-
-        result.into_enum_data_error(|| return_data, || allocation_size)
+        result.into_enum_data_error(
+            move || {
+                EfiGetMemoryMapResult::new(
+                    memory_map_key,
+                    descriptor_version,
+                    EfiMemoryDescriptorsMut::new(
+                        &mut memory_map[..allocation_size],
+                        descriptor_size,
+                    ),
+                )
+            },
+            || allocation_size,
+        )
     }
 
     pub fn allocate_pool(
@@ -315,7 +378,7 @@ impl EfiBootServices1x0 {
         };
 
         (self.reinstall_protocol_interface)(
-            handle,
+            *handle,
             protocol_guid,
             old_interface as _,
             new_interface as _,
@@ -335,7 +398,7 @@ impl EfiBootServices1x0 {
             0 as _
         };
 
-        (self.uninstall_protocol_interface)(handle, protocol_guid, interface as _).into_enum()
+        (self.uninstall_protocol_interface)(*handle, protocol_guid, interface as _).into_enum()
     }
 
     #[inline(always)]
@@ -532,4 +595,338 @@ impl EfiBootServices1x0 {
     ) -> EfiStatusEnum {
         (self.exit_boot_services)(image_handle, memory_map_key).into_enum()
     }
+
+    /* Miscellaneous */
+
+    pub fn get_next_monotonic_count(&self) -> EfiStatusEnum<u64> {
+        let mut count: u64 = 0;
+
+        (self.get_next_monotonic_count)(&mut count).into_enum_data(|| count)
+    }
+
+    #[inline(always)]
+    pub fn stall(&self, microseconds: usize) -> EfiStatusEnum {
+        (self.stall)(microseconds).into_enum()
+    }
+
+    /// Sets the watchdog timer's timeout in seconds. Passing zero as a timeout disables the timer.
+    pub fn set_watchdog_timer(
+        &self,
+        timeout: usize,
+        watchdog_code: u64,
+        watchdog_data: Option<&[u16]>,
+    ) -> EfiStatusEnum {
+        let (watchdog_data_ptr, watchdog_data_len): (*const u16, usize) =
+            if let Some(watchdog_data) = watchdog_data {
+                (watchdog_data.as_ptr(), watchdog_data.len() * 2)
+            } else {
+                (0 as _, 0)
+            };
+
+        (self.set_watchdog_timer)(timeout, watchdog_code, watchdog_data_len, watchdog_data_ptr)
+            .into_enum()
+    }
+}
+
+#[repr(C)]
+pub struct EfiBootServices1x1 {
+    connect_controller: extern "efiapi" fn(EfiHandle, *const EfiHandle, VoidPtr, bool) -> EfiStatus,
+    disconnect_controller: extern "efiapi" fn(EfiHandle, EfiHandle, EfiHandle) -> EfiStatus,
+
+    /*~~~~~~~~~~*/
+    open_protocol: extern "efiapi" fn(
+        EfiHandle,
+        *const EfiGuid,
+        *mut VoidMutPtr,
+        EfiHandle,
+        EfiHandle,
+        EfiOpenProtocolAttributes,
+    ) -> EfiStatus,
+    close_protocol:
+        extern "efiapi" fn(EfiHandle, *const EfiGuid, EfiHandle, EfiHandle) -> EfiStatus,
+    open_protocol_information: extern "efiapi" fn(
+        EfiHandle,
+        *const EfiGuid,
+        *mut *const EfiOpenProtocolInformationEntry,
+        *mut usize,
+    ) -> EfiStatus,
+
+    /*~~~~~~~~~~*/
+    protocols_per_handle:
+        extern "efiapi" fn(EfiHandle, *mut *const *const EfiGuid, *mut usize) -> EfiStatus,
+    locate_handle_buffer: extern "efiapi" fn(
+        EfiLocateSearchType,
+        *const EfiGuid,
+        VoidPtr,
+        *mut usize,
+        *mut *const EfiHandle,
+    ) -> EfiStatus,
+    locate_protocol: extern "efiapi" fn(*const EfiGuid, VoidPtr, *mut VoidMutPtr) -> EfiStatus,
+    /* Variadic functions */
+    install_multiple_protocol_interfaces: VoidPtr,
+    uninstall_multiple_protocol_interfaces: VoidPtr,
+
+    /*~~~~~~~~~~*/
+    calculate_crc32: extern "efiapi" fn(VoidPtr, usize, *mut u32) -> EfiStatus,
+
+    /*~~~~~~~~~~*/
+    copy_mem: extern "efiapi" fn(VoidMutPtr, VoidPtr, usize) -> Void,
+    set_mem: extern "efiapi" fn(VoidMutPtr, usize, u8) -> Void,
+}
+
+impl EfiBootServices1x1 {
+    /* Driver Support */
+
+    /// Connects drivers to a controller.
+    ///
+    /// When passed, the list of driver image handles has to be terminated by a null handle.
+    pub fn connect_controller(
+        &self,
+        controller_handle: EfiHandle,
+        driver_image_handles: Option<&[EfiHandle]>,
+        remaining_device_path: Option<&EfiDevicePathProtocolRaw>,
+        recursive: bool,
+    ) -> EfiStatusEnum {
+        (self.connect_controller)(
+            controller_handle,
+            driver_image_handles.map_or(0 as _, |handles: &[EfiHandle]| handles.as_ptr()),
+            remaining_device_path.map_or(0 as _, |device_path: &EfiDevicePathProtocolRaw| {
+                device_path.as_ptr() as VoidPtr
+            }),
+            recursive,
+        )
+        .into_enum()
+    }
+
+    pub fn disconnect_controller(
+        &self,
+        controller_handle: EfiHandle,
+        driver_image_handle: Option<EfiHandle>,
+        child_handle: Option<EfiHandle>,
+    ) -> EfiStatusEnum {
+        (self.disconnect_controller)(
+            controller_handle,
+            driver_image_handle.unwrap_or(0 as _),
+            child_handle.unwrap_or(0 as _),
+        )
+        .into_enum()
+    }
+
+    /* Open and Close Protocol */
+
+    /// Opens the protocol on the handle.
+    ///
+    /// Failures are returned as the status, while a successful call which returns no interface is a firmware fault.
+    pub fn open_protocol<T>(
+        &self,
+        handle: EfiHandle,
+        agent_handle: EfiHandle,
+        controller_handle: Option<EfiHandle>,
+        attributes: EfiOpenProtocolAttributes,
+    ) -> Result<EfiStatusEnum<<T as ParseResult>::Result>, EfiFirmwareFault>
+    where
+        T: EfiProtocol
+            + ParseResult<
+                Result = core::result::Result<
+                    <T as EfiProtocol>::Parsed,
+                    <T as EfiProtocol>::Error,
+                >,
+            > + ?Sized,
+    {
+        let mut interface: VoidMutPtr = null_mut();
+
+        let result: EfiStatus = (self.open_protocol)(
+            handle,
+            &T::guid(),
+            &mut interface,
+            agent_handle,
+            controller_handle.unwrap_or(0 as _),
+            attributes,
+        );
+
+        if let Some(error) = result.get_error() {
+            return Ok(EfiStatusEnum::Error(error, ()));
+        }
+
+        NonNullVoidPtr::new(interface)
+            .ok_or(EfiFirmwareFault)
+            .map(|interface: NonNullVoidPtr| {
+                result.into_enum_data(|| unsafe { T::parse(interface) })
+            })
+    }
+
+    /// Tests whether the protocol is installed on the handle without opening it.
+    pub fn test_protocol(
+        &self,
+        handle: EfiHandle,
+        protocol_guid: &EfiGuid,
+        agent_handle: EfiHandle,
+        controller_handle: Option<EfiHandle>,
+    ) -> EfiStatusEnum {
+        (self.open_protocol)(
+            handle,
+            protocol_guid,
+            null_mut(),
+            agent_handle,
+            controller_handle.unwrap_or(0 as _),
+            EfiOpenProtocolAttributes::TestProtocol,
+        )
+        .into_enum()
+    }
+
+    pub fn close_protocol(
+        &self,
+        handle: EfiHandle,
+        protocol_guid: &EfiGuid,
+        agent_handle: EfiHandle,
+        controller_handle: Option<EfiHandle>,
+    ) -> EfiStatusEnum {
+        (self.close_protocol)(
+            handle,
+            protocol_guid,
+            agent_handle,
+            controller_handle.unwrap_or(0 as _),
+        )
+        .into_enum()
+    }
+
+    /// Retrieves the list of agents that currently have the protocol opened.
+    ///
+    /// The returned buffer is allocated from pool memory and has to be released with [`free_pool`](EfiBootServices1x0::free_pool).
+    pub fn open_protocol_information(
+        &self,
+        handle: EfiHandle,
+        protocol_guid: &EfiGuid,
+    ) -> EfiStatusEnum<&'static [EfiOpenProtocolInformationEntry]> {
+        let (mut entries, mut entries_count): (*const EfiOpenProtocolInformationEntry, usize) =
+            (0 as _, 0);
+
+        (self.open_protocol_information)(handle, protocol_guid, &mut entries, &mut entries_count)
+            .into_enum_data(|| unsafe { from_raw_parts(entries, entries_count) })
+    }
+
+    /* Library Services */
+
+    /// Retrieves the list of protocols installed on the handle.
+    ///
+    /// The returned buffer is allocated from pool memory and has to be released with [`free_pool`](EfiBootServices1x0::free_pool).
+    pub fn protocols_per_handle(
+        &self,
+        handle: EfiHandle,
+    ) -> EfiStatusEnum<&'static [&'static EfiGuid]> {
+        let (mut protocols, mut protocols_count): (*const *const EfiGuid, usize) = (0 as _, 0);
+
+        (self.protocols_per_handle)(handle, &mut protocols, &mut protocols_count).into_enum_data(
+            || unsafe { from_raw_parts(protocols as *const &'static EfiGuid, protocols_count) },
+        )
+    }
+
+    /// Returns the handles matching the search criteria.
+    ///
+    /// The returned buffer is allocated from pool memory and has to be released with [`free_pool`](EfiBootServices1x0::free_pool).
+    pub fn locate_handle_buffer(
+        &self,
+        search_type: EfiLocateSearchType,
+        protocol_guid: Option<&EfiGuid>,
+        search_key: Option<VoidPtr>,
+    ) -> EfiStatusEnum<&'static [EfiHandle]> {
+        let (mut handles, mut handles_count): (*const EfiHandle, usize) = (0 as _, 0);
+
+        (self.locate_handle_buffer)(
+            search_type,
+            protocol_guid.map_or(0 as _, |protocol_guid: &EfiGuid| protocol_guid),
+            search_key.unwrap_or(0 as _),
+            &mut handles_count,
+            &mut handles,
+        )
+        .into_enum_data(|| unsafe { from_raw_parts(handles, handles_count) })
+    }
+
+    /// Returns the first protocol instance that matches the given protocol.
+    ///
+    /// Failures are returned as the status, while a successful call which returns no interface is a firmware fault.
+    pub fn locate_protocol<T>(
+        &self,
+        registration: Option<VoidPtr>,
+    ) -> Result<EfiStatusEnum<<T as ParseResult>::Result>, EfiFirmwareFault>
+    where
+        T: EfiProtocol
+            + ParseResult<
+                Result = core::result::Result<
+                    <T as EfiProtocol>::Parsed,
+                    <T as EfiProtocol>::Error,
+                >,
+            > + ?Sized,
+    {
+        let mut interface: VoidMutPtr = null_mut();
+
+        let result: EfiStatus =
+            (self.locate_protocol)(&T::guid(), registration.unwrap_or(0 as _), &mut interface);
+
+        if let Some(error) = result.get_error() {
+            return Ok(EfiStatusEnum::Error(error, ()));
+        }
+
+        NonNullVoidPtr::new(interface)
+            .ok_or(EfiFirmwareFault)
+            .map(|interface: NonNullVoidPtr| {
+                result.into_enum_data(|| unsafe { T::parse(interface) })
+            })
+    }
+
+    /* 32-bit CRC Services */
+
+    pub fn calculate_crc32(&self, data: &[u8]) -> EfiStatusEnum<u32> {
+        let mut crc32: u32 = 0;
+
+        (self.calculate_crc32)(data.as_ptr() as VoidPtr, data.len(), &mut crc32)
+            .into_enum_data(|| crc32)
+    }
+}
+
+#[repr(C)]
+pub struct EfiBootServices2x0 {
+    create_event_ex: extern "efiapi" fn(
+        EfiEventType,
+        EfiTaskPriorityLevel,
+        EfiEventNotifyCallback,
+        VoidPtr,
+        *const EfiGuid,
+        *mut EfiEvent,
+    ) -> EfiStatus,
+}
+
+impl EfiBootServices2x0 {
+    /* Event and Timer */
+
+    /// Creates an event in the given event group.
+    pub fn create_event_ex(
+        &self,
+        event_type: EfiEventType,
+        tpl: EfiTaskPriorityLevel,
+        notify: Option<(EfiEventNotifyCallback, VoidPtr)>,
+        event_group: Option<&EfiGuid>,
+    ) -> EfiStatusEnum<EfiEvent> {
+        let mut event: EfiEvent = 0 as EfiEvent;
+
+        let (notify_function, notify_context): (EfiEventNotifyCallback, VoidPtr) = {
+            match notify {
+                None => (
+                    unsafe { *(&0usize as *const usize as *const EfiEventNotifyCallback) },
+                    0 as VoidPtr,
+                ),
+                Some((notify_function, notify_context)) => (notify_function, notify_context),
+            }
+        };
+
+        (self.create_event_ex)(
+            event_type,
+            tpl,
+            notify_function,
+            notify_context,
+            event_group.map_or(0 as _, |event_group: &EfiGuid| event_group),
+            &mut event,
+        )
+        .into_enum_data(|| event)
+    }
 }
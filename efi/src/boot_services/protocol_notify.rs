@@ -0,0 +1,120 @@
+use {
+    super::{
+        event::EventFuture,
+        types::{event_and_timer::EfiEventType, protocol_handler::EfiLocateSearchType},
+        EfiBootServices1x0,
+    },
+    crate::{
+        protocols::EfiProtocol,
+        status::{EfiStatusEnum, EfiStatusError},
+        types::{EfiHandle, VoidPtr},
+    },
+    core::{
+        future::Future,
+        marker::PhantomData,
+        pin::Pin,
+        task::{Context, Poll},
+    },
+};
+
+/// Stream of handles on which the protocol gets installed.
+///
+/// Only installations made after the stream is created are reported.
+/// Handles are reported once for every installation, so reinstalling the protocol reports the handle again.
+pub struct ProtocolNotify<'a, T>
+where
+    T: EfiProtocol + ?Sized,
+{
+    boot_services: &'a EfiBootServices1x0,
+    future: EventFuture<'a>,
+    registration: VoidPtr,
+    _protocol: PhantomData<fn() -> *const T>,
+}
+
+impl<'a, T> ProtocolNotify<'a, T>
+where
+    T: EfiProtocol + ?Sized,
+{
+    pub fn new(boot_services: &'a EfiBootServices1x0) -> EfiStatusEnum<Self> {
+        let future: EventFuture<'a> =
+            match EventFuture::new(boot_services, EfiEventType::NotifySignal) {
+                EfiStatusEnum::Success(future) | EfiStatusEnum::Warning(_, future) => future,
+                EfiStatusEnum::Error(error, ()) => return EfiStatusEnum::Error(error, ()),
+            };
+
+        boot_services
+            .register_protocol_notify(&T::guid(), future.event().raw())
+            .map(|registration: VoidPtr| Self {
+                boot_services,
+                future,
+                registration,
+                _protocol: PhantomData,
+            })
+    }
+
+    /// Returns the next handle on which the protocol was installed, if there is one, without waiting.
+    pub fn try_next(&self) -> EfiStatusEnum<Option<EfiHandle>> {
+        let mut handles: [EfiHandle; 1] = [0 as _];
+
+        match self.boot_services.locate_handle(
+            EfiLocateSearchType::ByRegisterNotify,
+            None,
+            Some(self.registration),
+            &mut handles,
+        ) {
+            EfiStatusEnum::Success(handles) => EfiStatusEnum::Success(handles.first().copied()),
+            EfiStatusEnum::Warning(warning, handles) => {
+                EfiStatusEnum::Warning(warning, handles.first().copied())
+            }
+            EfiStatusEnum::Error(EfiStatusError::EfiNotFound, _) => EfiStatusEnum::Success(None),
+            EfiStatusEnum::Error(error, _) => EfiStatusEnum::Error(error, ()),
+        }
+    }
+
+    /// Polls for the next handle, waking the context's waker when the protocol gets installed.
+    pub fn poll_next(&mut self, context: &mut Context<'_>) -> Poll<EfiStatusEnum<EfiHandle>> {
+        loop {
+            match self.try_next() {
+                EfiStatusEnum::Success(Some(handle)) => {
+                    return Poll::Ready(EfiStatusEnum::Success(handle))
+                }
+                EfiStatusEnum::Warning(warning, Some(handle)) => {
+                    return Poll::Ready(EfiStatusEnum::Warning(warning, handle))
+                }
+                EfiStatusEnum::Success(None) | EfiStatusEnum::Warning(_, None) => (),
+                EfiStatusEnum::Error(error, ()) => {
+                    return Poll::Ready(EfiStatusEnum::Error(error, ()))
+                }
+            }
+
+            /* The event may have been signalled between the lookup and the registration of the waker */
+            if Pin::new(&mut self.future).poll(context).is_pending() {
+                return Poll::Pending;
+            }
+        }
+    }
+
+    /// Returns future which completes with the next handle on which the protocol gets installed.
+    pub fn next_handle(&mut self) -> ProtocolNotifyNext<'_, 'a, T> {
+        ProtocolNotifyNext { notify: self }
+    }
+}
+
+/// Future returned by [`ProtocolNotify::next_handle`].
+pub struct ProtocolNotifyNext<'b, 'a, T>
+where
+    T: EfiProtocol + ?Sized,
+{
+    notify: &'b mut ProtocolNotify<'a, T>,
+}
+
+impl<T> Future for ProtocolNotifyNext<'_, '_, T>
+where
+    T: EfiProtocol + ?Sized,
+{
+    type Output = EfiStatusEnum<EfiHandle>;
+
+    fn poll(mut self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<<Self as Future>::Output> {
+        self.notify.poll_next(context)
+    }
+}
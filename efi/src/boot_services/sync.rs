@@ -0,0 +1,161 @@
+use {
+    super::{types::task_priority::EfiTaskPriorityLevel, EfiBootServices1x0},
+    core::{
+        cell::UnsafeCell,
+        ops::{Deref, DerefMut},
+        sync::atomic::{AtomicBool, Ordering},
+    },
+};
+
+/// Raised task priority level which is restored when dropped.
+///
+/// While the guard exists, notify functions registered at the same or a lower task priority level can't run.
+pub struct TplGuard<'a> {
+    boot_services: &'a EfiBootServices1x0,
+    old_level: EfiTaskPriorityLevel,
+}
+
+impl<'a> TplGuard<'a> {
+    /// Raises the task priority level to `level`.
+    ///
+    /// When the current level is already higher (e.g.: inside a notify function), it is kept instead, as lowering it isn't allowed.
+    pub fn raise(boot_services: &'a EfiBootServices1x0, level: EfiTaskPriorityLevel) -> Self {
+        /* Raising to the highest level is always valid and returns the current level */
+        let old_level: EfiTaskPriorityLevel =
+            unsafe { boot_services.raise_priority_level(EfiTaskPriorityLevel::HIGH_LEVEL) };
+
+        unsafe {
+            boot_services.restore_priority_level(old_level.max(level));
+        }
+
+        Self {
+            boot_services,
+            old_level,
+        }
+    }
+
+    /// Raises the task priority level to `TPL_CALLBACK`.
+    pub fn callback(boot_services: &'a EfiBootServices1x0) -> Self {
+        Self::raise(boot_services, EfiTaskPriorityLevel::CALLBACK)
+    }
+
+    /// Raises the task priority level to `TPL_NOTIFY`.
+    pub fn notify(boot_services: &'a EfiBootServices1x0) -> Self {
+        Self::raise(boot_services, EfiTaskPriorityLevel::NOTIFY)
+    }
+
+    /// Returns the level which is restored when the guard is dropped.
+    pub fn old_level(&self) -> EfiTaskPriorityLevel {
+        self.old_level
+    }
+}
+
+impl Drop for TplGuard<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            self.boot_services.restore_priority_level(self.old_level);
+        }
+    }
+}
+
+/// Mutual exclusion lock for data shared with notify functions.
+///
+/// Locking raises the task priority level to the mutex's level, so notify functions at that level or lower can't interrupt the lock's owner.
+/// As there is only a single processor while boot services are active, locking an already locked mutex can only happen recursively and panics instead of deadlocking.
+///
+/// After boot services are exited, the data can only be accessed through [`get_mut`](Self::get_mut) or [`into_inner`](Self::into_inner).
+pub struct Mutex<T> {
+    level: EfiTaskPriorityLevel,
+    locked: AtomicBool,
+    value: UnsafeCell<T>,
+}
+
+unsafe impl<T> Send for Mutex<T> where T: Send {}
+
+unsafe impl<T> Sync for Mutex<T> where T: Send {}
+
+impl<T> Mutex<T> {
+    /// Creates new mutex which raises the task priority level to `level` while locked.
+    /// The level must be at least as high as the levels of the notify functions sharing the data.
+    pub const fn new(level: EfiTaskPriorityLevel, value: T) -> Self {
+        Self {
+            level,
+            locked: AtomicBool::new(false),
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    pub fn level(&self) -> EfiTaskPriorityLevel {
+        self.level
+    }
+
+    #[track_caller]
+    pub fn lock<'a>(&'a self, boot_services: &'a EfiBootServices1x0) -> MutexGuard<'a, T> {
+        match self.try_lock(boot_services) {
+            Some(guard) => guard,
+            None => panic!("Mutex locked recursively!"),
+        }
+    }
+
+    /// Locks the mutex, unless it's already locked.
+    pub fn try_lock<'a>(
+        &'a self,
+        boot_services: &'a EfiBootServices1x0,
+    ) -> Option<MutexGuard<'a, T>> {
+        let tpl_guard: TplGuard<'a> = TplGuard::raise(boot_services, self.level);
+
+        if self.locked.swap(true, Ordering::Acquire) {
+            None
+        } else {
+            Some(MutexGuard {
+                mutex: self,
+                _tpl_guard: tpl_guard,
+            })
+        }
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        self.value.get_mut()
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+}
+
+impl<T> Default for Mutex<T>
+where
+    T: Default,
+{
+    /// Creates new mutex at `TPL_NOTIFY`.
+    fn default() -> Self {
+        Self::new(EfiTaskPriorityLevel::NOTIFY, T::default())
+    }
+}
+
+/// Locked [`Mutex`]. The mutex is unlocked and the task priority level is restored when dropped.
+pub struct MutexGuard<'a, T> {
+    mutex: &'a Mutex<T>,
+    /* Dropped after the mutex is unlocked */
+    _tpl_guard: TplGuard<'a>,
+}
+
+impl<T> Drop for MutexGuard<'_, T> {
+    fn drop(&mut self) {
+        self.mutex.locked.store(false, Ordering::Release);
+    }
+}
+
+impl<T> Deref for MutexGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &<Self as Deref>::Target {
+        unsafe { &*self.mutex.value.get() }
+    }
+}
+
+impl<T> DerefMut for MutexGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut <Self as Deref>::Target {
+        unsafe { &mut *self.mutex.value.get() }
+    }
+}
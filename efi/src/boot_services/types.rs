@@ -1,5 +1,24 @@
 pub mod task_priority {
-    pub type EfiTaskPriorityLevel = usize;
+    #[repr(transparent)]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub struct EfiTaskPriorityLevel {
+        level: usize,
+    }
+
+    impl EfiTaskPriorityLevel {
+        pub const APPLICATION: Self = Self::from_raw(4);
+        pub const CALLBACK: Self = Self::from_raw(8);
+        pub const NOTIFY: Self = Self::from_raw(16);
+        pub const HIGH_LEVEL: Self = Self::from_raw(31);
+
+        pub const fn from_raw(level: usize) -> Self {
+            Self { level }
+        }
+
+        pub const fn raw(&self) -> usize {
+            self.level
+        }
+    }
 }
 
 pub mod memory {
@@ -317,8 +336,12 @@ pub mod event_and_timer {
     #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
     #[non_exhaustive]
     pub enum EfiEventType {
+        /* Can only be signalled, checked and waited on */
+        None = 0x00000000,
         // #[allow(overflowing_literals)]
         Timer = 0x80000000,
+        TimerNotifyWait = 0x80000100,
+        TimerNotifySignal = 0x80000200,
         Runtime = 0x40000000,
         NotifyWait = 0x100,
         NotifySignal = 0x200,
@@ -339,6 +362,8 @@ pub mod event_and_timer {
 }
 
 pub mod protocol_handler {
+    use crate::EfiHandle;
+
     #[repr(C)]
     #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
     #[non_exhaustive]
@@ -354,4 +379,44 @@ pub mod protocol_handler {
         ByRegisterNotify,
         ByProtocol,
     }
+
+    #[repr(u32)]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    #[non_exhaustive]
+    pub enum EfiOpenProtocolAttributes {
+        ByHandleProtocol = 0x01,
+        GetProtocol = 0x02,
+        TestProtocol = 0x04,
+        ByChildController = 0x08,
+        ByDriver = 0x10,
+        Exclusive = 0x20,
+        ByDriverExclusive = 0x30,
+    }
+
+    #[repr(C)]
+    #[derive(Debug, Clone, Copy)]
+    pub struct EfiOpenProtocolInformationEntry {
+        agent_handle: EfiHandle,
+        controller_handle: EfiHandle,
+        attributes: u32,
+        open_count: u32,
+    }
+
+    impl EfiOpenProtocolInformationEntry {
+        pub fn agent_handle(&self) -> EfiHandle {
+            self.agent_handle
+        }
+
+        pub fn controller_handle(&self) -> EfiHandle {
+            self.controller_handle
+        }
+
+        pub fn attributes(&self) -> u32 {
+            self.attributes
+        }
+
+        pub fn open_count(&self) -> u32 {
+            self.open_count
+        }
+    }
 }
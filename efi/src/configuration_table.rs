@@ -200,3 +200,167 @@ impl Debug for EfiRTPropertiesTable {
         )
     }
 }
+
+/// Implementation of EFI's `EFI_SYSTEM_RESOURCE_TABLE`, listing the updatable firmware resources.
+///
+/// The entries immediately follow the header.
+#[repr(C)]
+pub struct EfiSystemResourceTable {
+    fw_resource_count: u32,
+    fw_resource_count_max: u32,
+    fw_resource_version: u64,
+}
+
+impl EfiSystemResourceTable {
+    /// Version of the entries' layout this crate implements.
+    pub const RESOURCE_VERSION: u64 = 1;
+
+    pub const fn fw_resource_count(&self) -> u32 {
+        self.fw_resource_count
+    }
+
+    pub const fn fw_resource_count_max(&self) -> u32 {
+        self.fw_resource_count_max
+    }
+
+    pub const fn fw_resource_version(&self) -> u64 {
+        self.fw_resource_version
+    }
+
+    /// Returns the entries, or `None` when their layout's version is unknown.
+    pub fn entries(&self) -> Option<&[EfiSystemResourceEntry]> {
+        if self.fw_resource_version == Self::RESOURCE_VERSION {
+            Some(unsafe {
+                from_raw_parts(
+                    (self as *const Self).add(1).cast(),
+                    self.fw_resource_count as usize,
+                )
+            })
+        } else {
+            None
+        }
+    }
+}
+
+unsafe impl traits::EfiConfigurationTable for EfiSystemResourceTable {
+    fn guid() -> types::EfiGuid {
+        (
+            0xb122a263,
+            0x3661,
+            0x4f68,
+            [0x99, 0x29, 0x78, 0xf8, 0xb0, 0xd6, 0x21, 0x80],
+        )
+    }
+}
+
+impl Debug for EfiSystemResourceTable {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("EfiSystemResourceTable")
+            .field("fw_resource_count", &self.fw_resource_count)
+            .field("fw_resource_count_max", &self.fw_resource_count_max)
+            .field("fw_resource_version", &self.fw_resource_version)
+            .field("entries", &self.entries())
+            .finish()
+    }
+}
+
+/// Firmware resource described by an [`EfiSystemResourceTable`] entry.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EfiSystemResourceEntry {
+    fw_class: EfiGuid,
+    fw_type: u32,
+    fw_version: u32,
+    lowest_supported_fw_version: u32,
+    capsule_flags: u32,
+    last_attempt_version: u32,
+    last_attempt_status: u32,
+}
+
+impl EfiSystemResourceEntry {
+    /// Identifies the firmware resource; Capsules updating it carry the same GUID.
+    pub const fn fw_class(&self) -> EfiGuid {
+        self.fw_class
+    }
+
+    pub const fn fw_type(&self) -> EfiFirmwareResourceType {
+        EfiFirmwareResourceType::from_raw(self.fw_type)
+    }
+
+    pub const fn fw_version(&self) -> u32 {
+        self.fw_version
+    }
+
+    pub const fn lowest_supported_fw_version(&self) -> u32 {
+        self.lowest_supported_fw_version
+    }
+
+    /// Flags which have to be set in the header of capsules updating the resource.
+    pub const fn capsule_flags(&self) -> u32 {
+        self.capsule_flags
+    }
+
+    pub const fn last_attempt_version(&self) -> u32 {
+        self.last_attempt_version
+    }
+
+    pub const fn last_attempt_status(&self) -> EfiLastAttemptStatus {
+        EfiLastAttemptStatus::from_raw(self.last_attempt_status)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EfiFirmwareResourceType {
+    Unknown,
+    SystemFirmware,
+    DeviceFirmware,
+    UefiDriver,
+    Other(u32),
+}
+
+impl EfiFirmwareResourceType {
+    pub const fn from_raw(fw_type: u32) -> Self {
+        match fw_type {
+            0 => Self::Unknown,
+            1 => Self::SystemFirmware,
+            2 => Self::DeviceFirmware,
+            3 => Self::UefiDriver,
+            _ => Self::Other(fw_type),
+        }
+    }
+}
+
+/// Result of the last update attempt, shared by [`EfiSystemResourceEntry`] and the firmware management protocol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EfiLastAttemptStatus {
+    Success,
+    Unsuccessful,
+    InsufficientResources,
+    IncorrectVersion,
+    InvalidFormat,
+    AuthenticationError,
+    PowerInsufficientAc,
+    PowerInsufficientBattery,
+    UnsatisfiedDependencies,
+    /// Values from `0x1000` to `0x3FFF` are reserved for vendors.
+    Vendor(u32),
+    Other(u32),
+}
+
+impl EfiLastAttemptStatus {
+    pub const fn from_raw(status: u32) -> Self {
+        match status {
+            0 => Self::Success,
+            1 => Self::Unsuccessful,
+            2 => Self::InsufficientResources,
+            3 => Self::IncorrectVersion,
+            4 => Self::InvalidFormat,
+            5 => Self::AuthenticationError,
+            6 => Self::PowerInsufficientAc,
+            7 => Self::PowerInsufficientBattery,
+            8 => Self::UnsatisfiedDependencies,
+            0x1000..=0x3FFF => Self::Vendor(status),
+            _ => Self::Other(status),
+        }
+    }
+}
@@ -0,0 +1,136 @@
+//! CRC32 (IEEE 802.3) as used by EFI tables and GPT.
+//!
+//! Enabling the `crc32_slicing_by_8` feature processes eight bytes per step at the cost of 7 KiB of additional lookup tables.
+
+const POLYNOMIAL: u32 = 0xEDB8_8320;
+
+#[cfg(feature = "crc32_slicing_by_8")]
+const TABLES_COUNT: usize = 8;
+#[cfg(not(feature = "crc32_slicing_by_8"))]
+const TABLES_COUNT: usize = 1;
+
+static LOOKUP_TABLES: [[u32; 256]; TABLES_COUNT] = generate_tables();
+
+const fn generate_tables() -> [[u32; 256]; TABLES_COUNT] {
+    let mut tables: [[u32; 256]; TABLES_COUNT] = [[0; 256]; TABLES_COUNT];
+
+    let mut index: usize = 0;
+
+    while index < 256 {
+        let mut crc: u32 = index as u32;
+
+        let mut bit: usize = 0;
+
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ POLYNOMIAL
+            } else {
+                crc >> 1
+            };
+
+            bit += 1;
+        }
+
+        tables[0][index] = crc;
+
+        index += 1;
+    }
+
+    /* Each table advances the previous one's result by another zero byte */
+    let mut table: usize = 1;
+
+    while table < TABLES_COUNT {
+        index = 0;
+
+        while index < 256 {
+            let previous: u32 = tables[table - 1][index];
+
+            tables[table][index] = (previous >> 8) ^ tables[0][(previous & 0xFF) as usize];
+
+            index += 1;
+        }
+
+        table += 1;
+    }
+
+    tables
+}
+
+fn update_bytes(crc: u32, data: &[u8]) -> u32 {
+    data.iter().fold(crc, |crc: u32, &byte: &u8| {
+        LOOKUP_TABLES[0][((crc as u8) ^ byte) as usize] ^ (crc >> 8)
+    })
+}
+
+#[cfg(feature = "crc32_slicing_by_8")]
+fn update(mut crc: u32, data: &[u8]) -> u32 {
+    let table = |table: usize, value: u32, byte: u32| -> u32 {
+        LOOKUP_TABLES[table][((value >> (byte * 8)) & 0xFF) as usize]
+    };
+
+    let (chunks, remainder): (&[[u8; 8]], &[u8]) = data.as_chunks::<8>();
+
+    for &[b0, b1, b2, b3, b4, b5, b6, b7] in chunks {
+        let low: u32 = crc ^ u32::from_le_bytes([b0, b1, b2, b3]);
+        let high: u32 = u32::from_le_bytes([b4, b5, b6, b7]);
+
+        crc = table(7, low, 0)
+            ^ table(6, low, 1)
+            ^ table(5, low, 2)
+            ^ table(4, low, 3)
+            ^ table(3, high, 0)
+            ^ table(2, high, 1)
+            ^ table(1, high, 2)
+            ^ table(0, high, 3);
+    }
+
+    update_bytes(crc, remainder)
+}
+
+#[cfg(not(feature = "crc32_slicing_by_8"))]
+fn update(crc: u32, data: &[u8]) -> u32 {
+    update_bytes(crc, data)
+}
+
+/// Streaming CRC32 calculation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Crc32 {
+    crc: u32,
+}
+
+impl Crc32 {
+    pub const fn new() -> Self {
+        Self { crc: !0 }
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        self.crc = update(self.crc, data);
+    }
+
+    /// Updates the checksum as if `length` zero bytes were passed (e.g.: in place of a checksum field).
+    pub fn update_zeroed(&mut self, length: usize) {
+        for _ in 0..length {
+            self.crc = update_bytes(self.crc, &[0]);
+        }
+    }
+
+    /// Returns the checksum of the data passed so far.
+    pub const fn finish(&self) -> u32 {
+        !self.crc
+    }
+}
+
+impl Default for Crc32 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Calculates the checksum of the data.
+pub fn checksum(data: &[u8]) -> u32 {
+    let mut crc: Crc32 = Crc32::new();
+
+    crc.update(data);
+
+    crc.finish()
+}
@@ -1,4 +1,7 @@
-use crate::types::EfiGuidTuple;
+use {
+    crate::types::EfiGuidTuple,
+    core::fmt::{Debug, Display, Formatter, Result as FmtResult},
+};
 
 #[repr(C)]
 #[derive(PartialEq, Eq, Clone, Copy)]
@@ -27,6 +30,10 @@ impl EfiGuid {
         unsafe { core::mem::transmute(*data) }
     }
 
+    pub fn to_array(&self) -> [u8; 16] {
+        unsafe { core::mem::transmute(*self) }
+    }
+
     pub fn from_slice(data: &[u8]) -> Option<Self> {
         if data.len() < 16 {
             None
@@ -43,6 +50,27 @@ impl EfiGuid {
     }
 }
 
+/// Formats the GUID in registry format (e.g.: `8BE4DF61-93CA-11D2-AA0D-00E098032B8C`).
+impl Display for EfiGuid {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "{:08X}-{:04X}-{:04X}-{:02X}{:02X}-",
+            self.data_1, self.data_2, self.data_3, self.data_4[0], self.data_4[1]
+        )?;
+
+        self.data_4[2..]
+            .iter()
+            .try_for_each(|byte: &u8| write!(f, "{:02X}", byte))
+    }
+}
+
+impl Debug for EfiGuid {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        Display::fmt(self, f)
+    }
+}
+
 impl PartialEq<&EfiGuid> for EfiGuid {
     fn eq(&self, other: &&EfiGuid) -> bool {
         *self == **other
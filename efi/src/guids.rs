@@ -48,3 +48,466 @@ pub const EFI_RT_PROPERTIES_TABLE: EfiGuid = EfiGuid::from_tuple((
     0x402A,
     [0x84, 0x2E, 0x93, 0x1D, 0x21, 0xC3, 0x8A, 0xE9],
 ));
+
+pub const EFI_PXE_BASE_CODE_PROTOCOL: EfiGuid = EfiGuid::from_tuple((
+    0x03C4_E603,
+    0xAC28,
+    0x11D3,
+    [0x9A, 0x2D, 0x00, 0x90, 0x27, 0x3F, 0xC1, 0x4D],
+));
+
+pub const EFI_HTTP_SERVICE_BINDING_PROTOCOL: EfiGuid = EfiGuid::from_tuple((
+    0xBDC8_E6AF,
+    0xD9BC,
+    0x4379,
+    [0xA7, 0x2A, 0xE0, 0xC4, 0xE7, 0x5D, 0xAE, 0x1C],
+));
+
+pub const EFI_HTTP_PROTOCOL: EfiGuid = EfiGuid::from_tuple((
+    0x7A59_B29B,
+    0x910B,
+    0x4171,
+    [0x82, 0x42, 0xA8, 0x5A, 0x0D, 0xF2, 0x5B, 0x5B],
+));
+
+pub const EFI_IP4_CONFIG2_PROTOCOL: EfiGuid = EfiGuid::from_tuple((
+    0x5B44_6ED1,
+    0xE30B,
+    0x4FAA,
+    [0x87, 0x1A, 0x36, 0x54, 0xEC, 0xA3, 0x60, 0x80],
+));
+
+pub const EFI_DNS4_SERVICE_BINDING_PROTOCOL: EfiGuid = EfiGuid::from_tuple((
+    0xB625_B186,
+    0xE063,
+    0x44F7,
+    [0x89, 0x05, 0x6A, 0x74, 0xDC, 0x6F, 0x52, 0xB4],
+));
+
+pub const EFI_DNS4_PROTOCOL: EfiGuid = EfiGuid::from_tuple((
+    0xAE3D_28CC,
+    0xE05B,
+    0x4FA1,
+    [0xA0, 0x11, 0x7E, 0xB5, 0x5A, 0x3F, 0x14, 0x01],
+));
+
+pub const EFI_TCP4_SERVICE_BINDING_PROTOCOL: EfiGuid = EfiGuid::from_tuple((
+    0x0072_0665,
+    0x67EB,
+    0x4A99,
+    [0xBA, 0xF7, 0xD3, 0xC3, 0x3A, 0x1C, 0x7C, 0xC9],
+));
+
+pub const EFI_TCP4_PROTOCOL: EfiGuid = EfiGuid::from_tuple((
+    0x6553_0BC7,
+    0xA359,
+    0x410F,
+    [0xB0, 0x10, 0x5A, 0xAD, 0xC7, 0xEC, 0x2B, 0x62],
+));
+
+pub const EFI_UDP4_SERVICE_BINDING_PROTOCOL: EfiGuid = EfiGuid::from_tuple((
+    0x83F0_1464,
+    0x99BD,
+    0x45E5,
+    [0xB3, 0x83, 0xAF, 0x63, 0x05, 0xD8, 0xE9, 0xE6],
+));
+
+pub const EFI_UDP4_PROTOCOL: EfiGuid = EfiGuid::from_tuple((
+    0x3AD9_DF29,
+    0x4501,
+    0x478D,
+    [0xB1, 0xF8, 0x7F, 0x7F, 0xE7, 0x0E, 0x50, 0xF3],
+));
+
+pub const EFI_RNG_PROTOCOL: EfiGuid = EfiGuid::from_tuple((
+    0x3152_BCA5,
+    0xEADE,
+    0x433D,
+    [0x86, 0x2E, 0xC0, 0x1C, 0xDC, 0x29, 0x1F, 0x44],
+));
+
+pub const EFI_RNG_ALGORITHM_SP800_90_HASH_256: EfiGuid = EfiGuid::from_tuple((
+    0xA7AF_67CB,
+    0x603B,
+    0x4D42,
+    [0xBA, 0x21, 0x70, 0xBF, 0xB6, 0x29, 0x3F, 0x96],
+));
+
+pub const EFI_RNG_ALGORITHM_SP800_90_HMAC_256: EfiGuid = EfiGuid::from_tuple((
+    0xC514_9B43,
+    0xAE85,
+    0x4F53,
+    [0x99, 0x82, 0xB9, 0x43, 0x35, 0xD3, 0xA9, 0xE7],
+));
+
+pub const EFI_RNG_ALGORITHM_SP800_90_CTR_256: EfiGuid = EfiGuid::from_tuple((
+    0x44F0_DE6E,
+    0x4D8C,
+    0x4045,
+    [0xA8, 0xC7, 0x4D, 0xD1, 0x68, 0x85, 0x6B, 0x9E],
+));
+
+pub const EFI_RNG_ALGORITHM_X9_31_3DES: EfiGuid = EfiGuid::from_tuple((
+    0x63C4_785A,
+    0xCA34,
+    0x4012,
+    [0xA3, 0xC8, 0x0B, 0x6A, 0x32, 0x4F, 0x55, 0x46],
+));
+
+pub const EFI_RNG_ALGORITHM_X9_31_AES: EfiGuid = EfiGuid::from_tuple((
+    0xACD0_3321,
+    0x777E,
+    0x4D3D,
+    [0xB1, 0xC8, 0x20, 0xCF, 0xD8, 0x88, 0x20, 0xC9],
+));
+
+pub const EFI_RNG_ALGORITHM_RAW: EfiGuid = EfiGuid::from_tuple((
+    0xE431_76D7,
+    0xB6E8,
+    0x4827,
+    [0xB7, 0x84, 0x7F, 0xFD, 0xC4, 0xB6, 0x85, 0x61],
+));
+
+pub const EFI_SERIAL_IO_PROTOCOL: EfiGuid = EfiGuid::from_tuple((
+    0xBB25_CF6F,
+    0xF1D4,
+    0x11D2,
+    [0x9A, 0x0C, 0x00, 0x90, 0x27, 0x3F, 0xC1, 0xFD],
+));
+
+pub const EFI_BLOCK_IO2_PROTOCOL: EfiGuid = EfiGuid::from_tuple((
+    0xA77B_2472,
+    0xE282,
+    0x4E9F,
+    [0xA2, 0x45, 0xC2, 0xC0, 0xE2, 0x7B, 0xBC, 0xC1],
+));
+
+pub const EFI_DISK_IO2_PROTOCOL: EfiGuid = EfiGuid::from_tuple((
+    0x151C_8EAE,
+    0x7F2C,
+    0x472C,
+    [0x9E, 0x54, 0x98, 0x28, 0x19, 0x4F, 0x6A, 0x88],
+));
+
+pub const EFI_PCI_ROOT_BRIDGE_IO_PROTOCOL: EfiGuid = EfiGuid::from_tuple((
+    0x2F70_7EBB,
+    0x4A1A,
+    0x11D4,
+    [0x9A, 0x38, 0x00, 0x90, 0x27, 0x3F, 0xC1, 0x4D],
+));
+
+pub const EFI_PCI_IO_PROTOCOL: EfiGuid = EfiGuid::from_tuple((
+    0x4CF5_B200,
+    0x68B8,
+    0x4CA5,
+    [0x9E, 0xEC, 0xB2, 0x3E, 0x3F, 0x50, 0x02, 0x9A],
+));
+
+pub const EFI_LOADED_IMAGE_PROTOCOL: EfiGuid = EfiGuid::from_tuple((
+    0x5B1B_31A1,
+    0x9562,
+    0x11D2,
+    [0x8E, 0x3F, 0x00, 0xA0, 0xC9, 0x69, 0x72, 0x3B],
+));
+
+pub const EFI_LOADED_IMAGE_DEVICE_PATH_PROTOCOL: EfiGuid = EfiGuid::from_tuple((
+    0xBC62_157E,
+    0x3E33,
+    0x4FEC,
+    [0x99, 0x20, 0x2D, 0x3B, 0x36, 0xD7, 0x50, 0xDF],
+));
+
+pub const EFI_DRIVER_BINDING_PROTOCOL: EfiGuid = EfiGuid::from_tuple((
+    0x18A0_31AB,
+    0xB443,
+    0x4D1A,
+    [0xA5, 0xC0, 0x0C, 0x09, 0x26, 0x1E, 0x9F, 0x71],
+));
+
+pub const EFI_COMPONENT_NAME2_PROTOCOL: EfiGuid = EfiGuid::from_tuple((
+    0x6A7A_5CFF,
+    0xE8D9,
+    0x4F70,
+    [0xBA, 0xDA, 0x75, 0xAB, 0x30, 0x25, 0xCE, 0x14],
+));
+
+pub const EFI_DEVICE_PATH_TO_TEXT_PROTOCOL: EfiGuid = EfiGuid::from_tuple((
+    0x8B84_3E20,
+    0x8132,
+    0x4852,
+    [0x90, 0xCC, 0x55, 0x1A, 0x4E, 0x4A, 0x7F, 0x1C],
+));
+
+pub const EFI_SIMPLE_FILE_SYSTEM_PROTOCOL: EfiGuid = EfiGuid::from_tuple((
+    0x964E_5B22,
+    0x6459,
+    0x11D2,
+    [0x8E, 0x39, 0x00, 0xA0, 0xC9, 0x69, 0x72, 0x3B],
+));
+
+pub const EFI_GRAPHICS_OUTPUT_PROTOCOL: EfiGuid = EfiGuid::from_tuple((
+    0x9042_A9DE,
+    0x23DC,
+    0x4A38,
+    [0x96, 0xFB, 0x7A, 0xDE, 0xD0, 0x80, 0x51, 0x6A],
+));
+
+pub const EFI_SYSTEM_RESOURCE_TABLE: EfiGuid = EfiGuid::from_tuple((
+    0xB122_A263,
+    0x3661,
+    0x4F68,
+    [0x99, 0x29, 0x78, 0xF8, 0xB0, 0xD6, 0x21, 0x80],
+));
+
+pub const EFI_FIRMWARE_MANAGEMENT_PROTOCOL: EfiGuid = EfiGuid::from_tuple((
+    0x86C7_7A67,
+    0x0B97,
+    0x4633,
+    [0xA1, 0x87, 0x49, 0x10, 0x4D, 0x06, 0x85, 0xC7],
+));
+
+pub const EFI_FIRMWARE_MANAGEMENT_CAPSULE_ID: EfiGuid = EfiGuid::from_tuple((
+    0x6DCB_D5ED,
+    0xE82D,
+    0x4C44,
+    [0xBD, 0xA1, 0x71, 0x94, 0x19, 0x9A, 0xD9, 0x2A],
+));
+
+pub const EFI_EDID_DISCOVERED_PROTOCOL: EfiGuid = EfiGuid::from_tuple((
+    0x1C0C_34F6,
+    0xD380,
+    0x41FA,
+    [0xA0, 0x49, 0x8A, 0xD0, 0x6C, 0x1A, 0x66, 0xAA],
+));
+
+pub const EFI_EDID_ACTIVE_PROTOCOL: EfiGuid = EfiGuid::from_tuple((
+    0xBD8C_1056,
+    0x9F36,
+    0x44EC,
+    [0x92, 0xA8, 0xA6, 0x33, 0x7F, 0x81, 0x79, 0x86],
+));
+
+pub const EFI_EDID_OVERRIDE_PROTOCOL: EfiGuid = EfiGuid::from_tuple((
+    0x48EC_B431,
+    0xFB72,
+    0x45C0,
+    [0xA9, 0x22, 0xF4, 0x58, 0xFE, 0x04, 0x0B, 0xD5],
+));
+
+pub const EFI_SIMPLE_POINTER_PROTOCOL: EfiGuid = EfiGuid::from_tuple((
+    0x3187_8C87,
+    0x0B75,
+    0x11D5,
+    [0x9A, 0x4F, 0x00, 0x90, 0x27, 0x3F, 0xC1, 0x4D],
+));
+
+pub const EFI_ABSOLUTE_POINTER_PROTOCOL: EfiGuid = EfiGuid::from_tuple((
+    0x8D59_D32B,
+    0xC655,
+    0x4AE9,
+    [0x9B, 0x15, 0xF2, 0x59, 0x04, 0x99, 0x2A, 0x43],
+));
+
+pub const EFI_NVM_EXPRESS_PASS_THRU_PROTOCOL: EfiGuid = EfiGuid::from_tuple((
+    0x52C7_8312,
+    0x8EDC,
+    0x4233,
+    [0x98, 0xF2, 0x1A, 0x1A, 0xA5, 0xE3, 0x88, 0xA5],
+));
+
+pub const EFI_EXT_SCSI_PASS_THRU_PROTOCOL: EfiGuid = EfiGuid::from_tuple((
+    0x143B_7632,
+    0xB81B,
+    0x4CB7,
+    [0xAB, 0xD3, 0xB6, 0x25, 0xA5, 0xB9, 0xBF, 0xFE],
+));
+
+pub const EFI_USB_IO_PROTOCOL: EfiGuid = EfiGuid::from_tuple((
+    0x2B2F_68D6,
+    0x0CD2,
+    0x44CF,
+    [0x8E, 0x8B, 0xBB, 0xA2, 0x0B, 0x1B, 0x5B, 0x75],
+));
+
+pub const EFI_USB2_HC_PROTOCOL: EfiGuid = EfiGuid::from_tuple((
+    0x3E74_5226,
+    0x9818,
+    0x45B6,
+    [0xA2, 0xAC, 0xD7, 0xCD, 0x0E, 0x8B, 0xA2, 0xBC],
+));
+
+pub const EFI_NVDIMM_LABEL_PROTOCOL: EfiGuid = EfiGuid::from_tuple((
+    0xD40B_6B80,
+    0x97D5,
+    0x4282,
+    [0xBB, 0x1D, 0x22, 0x3A, 0x16, 0x91, 0x80, 0x58],
+));
+
+pub const EFI_TIMESTAMP_PROTOCOL: EfiGuid = EfiGuid::from_tuple((
+    0xAFBF_DE41,
+    0x2E6E,
+    0x4262,
+    [0xBA, 0x65, 0x62, 0xB9, 0x23, 0x6E, 0x54, 0x95],
+));
+
+pub const EFI_RAM_DISK_PROTOCOL: EfiGuid = EfiGuid::from_tuple((
+    0xAB38_A0DF,
+    0x6873,
+    0x44A9,
+    [0x87, 0xE6, 0xD4, 0xEB, 0x56, 0x14, 0x84, 0x49],
+));
+
+pub const EFI_VIRTUAL_DISK_GUID: EfiGuid = EfiGuid::from_tuple((
+    0x77AB_535A,
+    0x45FC,
+    0x624B,
+    [0x55, 0x60, 0xF7, 0xB2, 0x81, 0xD1, 0xF9, 0x6E],
+));
+
+pub const EFI_VIRTUAL_CD_GUID: EfiGuid = EfiGuid::from_tuple((
+    0x3D5A_BD30,
+    0x4175,
+    0x87CE,
+    [0x6D, 0x64, 0xD2, 0xAD, 0xE5, 0x23, 0xC4, 0xBB],
+));
+
+pub const EFI_PERSISTENT_VIRTUAL_DISK_GUID: EfiGuid = EfiGuid::from_tuple((
+    0x5CEA_02C9,
+    0x4D07,
+    0x69D3,
+    [0x26, 0x9F, 0x44, 0x96, 0xFB, 0xE0, 0x96, 0xF9],
+));
+
+pub const EFI_PERSISTENT_VIRTUAL_CD_GUID: EfiGuid = EfiGuid::from_tuple((
+    0x0801_8188,
+    0x42CD,
+    0xBB48,
+    [0x10, 0x0F, 0x53, 0x87, 0xD5, 0x3D, 0xED, 0x3D],
+));
+
+/// Names of the GUIDs defined in this module, used for diagnostics.
+const NAMES: [(EfiGuid, &str); 55] = [
+    (EFI_GLOBAL_VARIABLE, "EFI_GLOBAL_VARIABLE"),
+    (
+        EFI_SIMPLE_TEXT_INPUT_PROTOCOL,
+        "EFI_SIMPLE_TEXT_INPUT_PROTOCOL",
+    ),
+    (
+        EFI_SIMPLE_TEXT_OUTPUT_PROTOCOL,
+        "EFI_SIMPLE_TEXT_OUTPUT_PROTOCOL",
+    ),
+    (EFI_DEVICE_PATH_PROTOCOL, "EFI_DEVICE_PATH_PROTOCOL"),
+    (EFI_BLOCK_IO_PROTOCOL, "EFI_BLOCK_IO_PROTOCOL"),
+    (EFI_DISK_IO_PROTOCOL, "EFI_DISK_IO_PROTOCOL"),
+    (EFI_RT_PROPERTIES_TABLE, "EFI_RT_PROPERTIES_TABLE"),
+    (EFI_PXE_BASE_CODE_PROTOCOL, "EFI_PXE_BASE_CODE_PROTOCOL"),
+    (
+        EFI_HTTP_SERVICE_BINDING_PROTOCOL,
+        "EFI_HTTP_SERVICE_BINDING_PROTOCOL",
+    ),
+    (EFI_HTTP_PROTOCOL, "EFI_HTTP_PROTOCOL"),
+    (EFI_IP4_CONFIG2_PROTOCOL, "EFI_IP4_CONFIG2_PROTOCOL"),
+    (
+        EFI_DNS4_SERVICE_BINDING_PROTOCOL,
+        "EFI_DNS4_SERVICE_BINDING_PROTOCOL",
+    ),
+    (EFI_DNS4_PROTOCOL, "EFI_DNS4_PROTOCOL"),
+    (
+        EFI_TCP4_SERVICE_BINDING_PROTOCOL,
+        "EFI_TCP4_SERVICE_BINDING_PROTOCOL",
+    ),
+    (EFI_TCP4_PROTOCOL, "EFI_TCP4_PROTOCOL"),
+    (
+        EFI_UDP4_SERVICE_BINDING_PROTOCOL,
+        "EFI_UDP4_SERVICE_BINDING_PROTOCOL",
+    ),
+    (EFI_UDP4_PROTOCOL, "EFI_UDP4_PROTOCOL"),
+    (EFI_RNG_PROTOCOL, "EFI_RNG_PROTOCOL"),
+    (
+        EFI_RNG_ALGORITHM_SP800_90_HASH_256,
+        "EFI_RNG_ALGORITHM_SP800_90_HASH_256",
+    ),
+    (
+        EFI_RNG_ALGORITHM_SP800_90_HMAC_256,
+        "EFI_RNG_ALGORITHM_SP800_90_HMAC_256",
+    ),
+    (
+        EFI_RNG_ALGORITHM_SP800_90_CTR_256,
+        "EFI_RNG_ALGORITHM_SP800_90_CTR_256",
+    ),
+    (EFI_RNG_ALGORITHM_X9_31_3DES, "EFI_RNG_ALGORITHM_X9_31_3DES"),
+    (EFI_RNG_ALGORITHM_X9_31_AES, "EFI_RNG_ALGORITHM_X9_31_AES"),
+    (EFI_RNG_ALGORITHM_RAW, "EFI_RNG_ALGORITHM_RAW"),
+    (EFI_SERIAL_IO_PROTOCOL, "EFI_SERIAL_IO_PROTOCOL"),
+    (EFI_BLOCK_IO2_PROTOCOL, "EFI_BLOCK_IO2_PROTOCOL"),
+    (EFI_DISK_IO2_PROTOCOL, "EFI_DISK_IO2_PROTOCOL"),
+    (
+        EFI_PCI_ROOT_BRIDGE_IO_PROTOCOL,
+        "EFI_PCI_ROOT_BRIDGE_IO_PROTOCOL",
+    ),
+    (EFI_PCI_IO_PROTOCOL, "EFI_PCI_IO_PROTOCOL"),
+    (EFI_LOADED_IMAGE_PROTOCOL, "EFI_LOADED_IMAGE_PROTOCOL"),
+    (
+        EFI_LOADED_IMAGE_DEVICE_PATH_PROTOCOL,
+        "EFI_LOADED_IMAGE_DEVICE_PATH_PROTOCOL",
+    ),
+    (EFI_DRIVER_BINDING_PROTOCOL, "EFI_DRIVER_BINDING_PROTOCOL"),
+    (EFI_COMPONENT_NAME2_PROTOCOL, "EFI_COMPONENT_NAME2_PROTOCOL"),
+    (
+        EFI_DEVICE_PATH_TO_TEXT_PROTOCOL,
+        "EFI_DEVICE_PATH_TO_TEXT_PROTOCOL",
+    ),
+    (
+        EFI_SIMPLE_FILE_SYSTEM_PROTOCOL,
+        "EFI_SIMPLE_FILE_SYSTEM_PROTOCOL",
+    ),
+    (EFI_GRAPHICS_OUTPUT_PROTOCOL, "EFI_GRAPHICS_OUTPUT_PROTOCOL"),
+    (EFI_SYSTEM_RESOURCE_TABLE, "EFI_SYSTEM_RESOURCE_TABLE"),
+    (
+        EFI_FIRMWARE_MANAGEMENT_PROTOCOL,
+        "EFI_FIRMWARE_MANAGEMENT_PROTOCOL",
+    ),
+    (
+        EFI_FIRMWARE_MANAGEMENT_CAPSULE_ID,
+        "EFI_FIRMWARE_MANAGEMENT_CAPSULE_ID",
+    ),
+    (EFI_EDID_DISCOVERED_PROTOCOL, "EFI_EDID_DISCOVERED_PROTOCOL"),
+    (EFI_EDID_ACTIVE_PROTOCOL, "EFI_EDID_ACTIVE_PROTOCOL"),
+    (EFI_EDID_OVERRIDE_PROTOCOL, "EFI_EDID_OVERRIDE_PROTOCOL"),
+    (EFI_SIMPLE_POINTER_PROTOCOL, "EFI_SIMPLE_POINTER_PROTOCOL"),
+    (
+        EFI_ABSOLUTE_POINTER_PROTOCOL,
+        "EFI_ABSOLUTE_POINTER_PROTOCOL",
+    ),
+    (
+        EFI_NVM_EXPRESS_PASS_THRU_PROTOCOL,
+        "EFI_NVM_EXPRESS_PASS_THRU_PROTOCOL",
+    ),
+    (
+        EFI_EXT_SCSI_PASS_THRU_PROTOCOL,
+        "EFI_EXT_SCSI_PASS_THRU_PROTOCOL",
+    ),
+    (EFI_USB_IO_PROTOCOL, "EFI_USB_IO_PROTOCOL"),
+    (EFI_USB2_HC_PROTOCOL, "EFI_USB2_HC_PROTOCOL"),
+    (EFI_NVDIMM_LABEL_PROTOCOL, "EFI_NVDIMM_LABEL_PROTOCOL"),
+    (EFI_TIMESTAMP_PROTOCOL, "EFI_TIMESTAMP_PROTOCOL"),
+    (EFI_RAM_DISK_PROTOCOL, "EFI_RAM_DISK_PROTOCOL"),
+    (EFI_VIRTUAL_DISK_GUID, "EFI_VIRTUAL_DISK_GUID"),
+    (EFI_VIRTUAL_CD_GUID, "EFI_VIRTUAL_CD_GUID"),
+    (
+        EFI_PERSISTENT_VIRTUAL_DISK_GUID,
+        "EFI_PERSISTENT_VIRTUAL_DISK_GUID",
+    ),
+    (
+        EFI_PERSISTENT_VIRTUAL_CD_GUID,
+        "EFI_PERSISTENT_VIRTUAL_CD_GUID",
+    ),
+];
+
+/// Returns the name of a GUID defined in this module.
+pub fn name(guid: &EfiGuid) -> Option<&'static str> {
+    NAMES
+        .iter()
+        .find(|(known, _): &&(EfiGuid, &str)| known == guid)
+        .map(|&(_, name): &(EfiGuid, &'static str)| name)
+}
@@ -11,6 +11,8 @@
 /* Enables "!" (never) type */
 #![feature(never_type)]
 
+extern crate alloc;
+
 pub mod utilities;
 
 mod types;
@@ -32,6 +34,7 @@ mod system_table;
 pub use system_table::*;
 
 pub mod boot_services;
+pub mod crc32;
 pub mod guids;
 pub mod protocols;
 pub mod runtime_services;
@@ -0,0 +1,173 @@
+use crate::{
+    guid::EfiGuid,
+    protocols::EfiProtocol,
+    status::{EfiStatus, EfiStatusEnum},
+    types::{EfiEvent, NonNullVoidPtr},
+};
+
+/// Implementation of EFI's `EFI_ABSOLUTE_POINTER_PROTOCOL`, which reports the position on touch screens, tablets and the like.
+#[repr(C)]
+pub struct EfiAbsolutePointerProtocol {
+    reset: extern "efiapi" fn(*const Self, extended_verification: bool) -> EfiStatus,
+    get_state: extern "efiapi" fn(*const Self, state: *mut EfiAbsolutePointerState) -> EfiStatus,
+    wait_for_input: EfiEvent,
+    mode: *const EfiAbsolutePointerMode,
+}
+
+impl EfiAbsolutePointerProtocol {
+    pub fn reset(&self, extended_verification: bool) -> EfiStatusEnum {
+        (self.reset)(self, extended_verification).into_enum()
+    }
+
+    /// Returns the current position and buttons.
+    ///
+    /// [`EfiNotReady`](crate::status::EfiStatusError::EfiNotReady) is returned when the state didn't change since the last call.
+    pub fn get_state(&self) -> EfiStatusEnum<EfiAbsolutePointerState> {
+        let mut state: EfiAbsolutePointerState = EfiAbsolutePointerState {
+            current_x: 0,
+            current_y: 0,
+            current_z: 0,
+            active_buttons: 0,
+        };
+
+        (self.get_state)(self, &mut state).into_enum_data(|| state)
+    }
+
+    /// Returns the event signaled when the state changes, for use with `WaitForEvent`.
+    pub fn wait_for_input(&self) -> &EfiEvent {
+        &self.wait_for_input
+    }
+
+    pub fn wait_for_input_mut(&mut self) -> &mut EfiEvent {
+        &mut self.wait_for_input
+    }
+
+    pub fn mode(&self) -> &EfiAbsolutePointerMode {
+        unsafe { &*self.mode }
+    }
+}
+
+impl EfiProtocol for EfiAbsolutePointerProtocol {
+    type Parsed = &'static Self;
+    type Error = !;
+
+    fn guid() -> EfiGuid {
+        crate::guids::EFI_ABSOLUTE_POINTER_PROTOCOL
+    }
+
+    unsafe fn parse(
+        ptr: NonNullVoidPtr,
+    ) -> Result<<Self as EfiProtocol>::Parsed, <Self as EfiProtocol>::Error> {
+        Ok(&*ptr.cast().as_ptr())
+    }
+}
+
+fn scale(position: u64, minimum: u64, maximum: u64, size: u64) -> u64 {
+    match maximum.checked_sub(minimum) {
+        Some(range) if range != 0 && size != 0 => {
+            (u128::from(position.clamp(minimum, maximum) - minimum) * u128::from(size - 1)
+                / u128::from(range)) as u64
+        }
+        _ => 0,
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EfiAbsolutePointerMode {
+    absolute_min_x: u64,
+    absolute_min_y: u64,
+    absolute_min_z: u64,
+    absolute_max_x: u64,
+    absolute_max_y: u64,
+    absolute_max_z: u64,
+    attributes: u32,
+}
+
+impl EfiAbsolutePointerMode {
+    /// The device has an alternate button, reported through [`ALT_ACTIVE`](EfiAbsolutePointerState::ALT_ACTIVE).
+    pub const SUPPORTS_ALT_ACTIVE: u32 = 0x01;
+    /// The Z axis reports the pressure instead of a distance.
+    pub const SUPPORTS_PRESSURE_AS_Z: u32 = 0x02;
+
+    pub fn absolute_min_x(&self) -> u64 {
+        self.absolute_min_x
+    }
+
+    pub fn absolute_min_y(&self) -> u64 {
+        self.absolute_min_y
+    }
+
+    pub fn absolute_min_z(&self) -> u64 {
+        self.absolute_min_z
+    }
+
+    /// Returns the maximum X coordinate, which is zero if the axis isn't supported.
+    pub fn absolute_max_x(&self) -> u64 {
+        self.absolute_max_x
+    }
+
+    /// Returns the maximum Y coordinate, which is zero if the axis isn't supported.
+    pub fn absolute_max_y(&self) -> u64 {
+        self.absolute_max_y
+    }
+
+    /// Returns the maximum Z coordinate, which is zero if the axis isn't supported.
+    pub fn absolute_max_z(&self) -> u64 {
+        self.absolute_max_z
+    }
+
+    pub fn attributes(&self) -> u32 {
+        self.attributes
+    }
+
+    pub fn supports(&self, attributes: u32) -> bool {
+        self.attributes & attributes == attributes
+    }
+
+    /// Maps an X coordinate onto `0..width`, e.g. to find the touched column of the screen.
+    pub fn scale_x(&self, x: u64, width: u64) -> u64 {
+        scale(x, self.absolute_min_x, self.absolute_max_x, width)
+    }
+
+    /// Maps a Y coordinate onto `0..height`, e.g. to find the touched row of the screen.
+    pub fn scale_y(&self, y: u64, height: u64) -> u64 {
+        scale(y, self.absolute_min_y, self.absolute_max_y, height)
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EfiAbsolutePointerState {
+    current_x: u64,
+    current_y: u64,
+    current_z: u64,
+    active_buttons: u32,
+}
+
+impl EfiAbsolutePointerState {
+    /// The screen is touched, or the primary button is pressed.
+    pub const TOUCH_ACTIVE: u32 = 0x01;
+    /// The alternate button is pressed.
+    pub const ALT_ACTIVE: u32 = 0x02;
+
+    pub fn current_x(&self) -> u64 {
+        self.current_x
+    }
+
+    pub fn current_y(&self) -> u64 {
+        self.current_y
+    }
+
+    pub fn current_z(&self) -> u64 {
+        self.current_z
+    }
+
+    pub fn active_buttons(&self) -> u32 {
+        self.active_buttons
+    }
+
+    pub fn is_active(&self, buttons: u32) -> bool {
+        self.active_buttons & buttons == buttons
+    }
+}
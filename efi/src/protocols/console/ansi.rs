@@ -0,0 +1,330 @@
+use {
+    super::{
+        EfiBackgroundColor, EfiForegroundColor, EfiSimpleTextOutputMode,
+        EfiSimpleTextOutputProtocol, EfiTextAttribute,
+    },
+    crate::status::EfiStatusEnum,
+    core::fmt::{Error, Write},
+};
+
+const ESCAPE: char = '\u{1B}';
+
+const MAX_PARAMETERS: usize = 8;
+
+/* ANSI color indices mapped to EFI's colors */
+const NORMAL_COLORS: [EfiForegroundColor; 8] = [
+    EfiForegroundColor::Black,
+    EfiForegroundColor::Red,
+    EfiForegroundColor::Green,
+    EfiForegroundColor::Brown,
+    EfiForegroundColor::Blue,
+    EfiForegroundColor::Magenta,
+    EfiForegroundColor::Cyan,
+    EfiForegroundColor::LightGray,
+];
+
+enum EfiAnsiState {
+    Text,
+    Escape,
+    ControlSequence {
+        parameters: [u16; MAX_PARAMETERS],
+        count: usize,
+        private: bool,
+    },
+}
+
+/// Adapter interpreting ANSI escape sequences written to `EFI_SIMPLE_TEXT_OUTPUT_PROTOCOL`.
+///
+/// Supported sequences:
+/// * SGR (`ESC [ ... m`): reset, bold/normal intensity, normal and bright foreground and background colors, default colors. Bright backgrounds fall back to the normal ones.
+/// * Cursor movement: `A`, `B`, `C`, `D` (relative), `G` (column), `H` and `f` (position).
+/// * Erasing: `2J` and `3J` (screen), `K` with `0`, `1` and `2` (line).
+/// * Cursor visibility: `?25h` and `?25l`.
+///
+/// Other sequences are dropped. Sequences may be split across writes.
+pub struct EfiAnsiTextOutput<'a> {
+    output: &'a EfiSimpleTextOutputProtocol,
+    state: EfiAnsiState,
+    default_attribute: EfiTextAttribute,
+    foreground: EfiForegroundColor,
+    background: EfiBackgroundColor,
+    bold: bool,
+}
+
+impl<'a> EfiAnsiTextOutput<'a> {
+    /// Creates new adapter. The console's current attribute is used as the default one.
+    pub fn new(output: &'a EfiSimpleTextOutputProtocol) -> Self {
+        Self::with_default_attribute(output, output.get_mode().attribute())
+    }
+
+    /// Creates new adapter which restores the given attribute on reset.
+    ///
+    /// Colors set through earlier adapters are kept until they are changed (e.g.: when an adapter is created for each write).
+    pub fn with_default_attribute(
+        output: &'a EfiSimpleTextOutputProtocol,
+        default_attribute: EfiTextAttribute,
+    ) -> Self {
+        let attribute: EfiTextAttribute = output.get_mode().attribute();
+
+        Self {
+            output,
+            state: EfiAnsiState::Text,
+            default_attribute,
+            foreground: attribute.foreground(),
+            background: attribute.background(),
+            bold: false,
+        }
+    }
+
+    pub fn output(&self) -> &'a EfiSimpleTextOutputProtocol {
+        self.output
+    }
+
+    /// Processes the string and returns the first error status, if any.
+    pub fn write(&mut self, string: &str) -> EfiStatusEnum {
+        let mut result: EfiStatusEnum = EfiStatusEnum::Success(());
+        let mut text_start: Option<usize> = None;
+
+        for (index, character) in string.char_indices() {
+            if let EfiAnsiState::Text = self.state {
+                if character != ESCAPE {
+                    text_start.get_or_insert(index);
+
+                    continue;
+                }
+
+                if let Some(start) = text_start.take() {
+                    result = Self::merge(result, self.output.output_string(&string[start..index]));
+                }
+            }
+
+            result = Self::merge(result, self.process(character));
+        }
+
+        if let Some(start) = text_start {
+            result = Self::merge(result, self.output.output_string(&string[start..]));
+        }
+
+        result
+    }
+
+    fn merge(result: EfiStatusEnum, status: EfiStatusEnum) -> EfiStatusEnum {
+        if result.is_error() {
+            result
+        } else {
+            status
+        }
+    }
+
+    fn process(&mut self, character: char) -> EfiStatusEnum {
+        match &mut self.state {
+            EfiAnsiState::Text => {
+                self.state = EfiAnsiState::Escape;
+            }
+            EfiAnsiState::Escape => {
+                self.state = if character == '[' {
+                    EfiAnsiState::ControlSequence {
+                        parameters: [0; MAX_PARAMETERS],
+                        count: 0,
+                        private: false,
+                    }
+                } else {
+                    EfiAnsiState::Text
+                };
+            }
+            EfiAnsiState::ControlSequence {
+                parameters,
+                count,
+                private,
+            } => match character {
+                '?' if *count == 0 => *private = true,
+                '0'..='9' => {
+                    let digit: u16 = character as u16 - u16::from(b'0');
+
+                    if *count == 0 {
+                        *count = 1;
+                    }
+
+                    if let Some(parameter) = parameters.get_mut(*count - 1) {
+                        *parameter = parameter.saturating_mul(10).saturating_add(digit);
+                    }
+                }
+                ';' => {
+                    /* An omitted first parameter still counts */
+                    *count = (*count).max(1) + 1;
+                }
+                '\u{40}'..='\u{7E}' => {
+                    let (parameters, count, private): ([u16; MAX_PARAMETERS], usize, bool) =
+                        (*parameters, (*count).min(MAX_PARAMETERS), *private);
+
+                    self.state = EfiAnsiState::Text;
+
+                    return self.execute(character, &parameters[..count], private);
+                }
+                /* Intermediate and other bytes aren't supported, so the sequence is dropped */
+                _ => (),
+            },
+        }
+
+        EfiStatusEnum::Success(())
+    }
+
+    fn execute(&mut self, command: char, parameters: &[u16], private: bool) -> EfiStatusEnum {
+        let parameter = |index: usize, default: u16| -> u16 {
+            match parameters.get(index) {
+                Some(0) | None => default,
+                Some(&parameter) => parameter,
+            }
+        };
+
+        if private {
+            return match (command, parameters) {
+                ('h', [25]) => self.output.enable_cursor(true),
+                ('l', [25]) => self.output.enable_cursor(false),
+                _ => EfiStatusEnum::Success(()),
+            };
+        }
+
+        let mode: EfiSimpleTextOutputMode = self.output.get_mode();
+        let (column, row): (usize, usize) =
+            (mode.cursor_column() as usize, mode.cursor_row() as usize);
+
+        match command {
+            'm' => self.select_graphic_rendition(parameters),
+            'A' => self.move_cursor(column, row.saturating_sub(usize::from(parameter(0, 1)))),
+            'B' => self.move_cursor(column, row + usize::from(parameter(0, 1))),
+            'C' => self.move_cursor(column + usize::from(parameter(0, 1)), row),
+            'D' => self.move_cursor(column.saturating_sub(usize::from(parameter(0, 1))), row),
+            'G' => self.move_cursor(usize::from(parameter(0, 1)) - 1, row),
+            'H' | 'f' => self.move_cursor(
+                usize::from(parameter(1, 1)) - 1,
+                usize::from(parameter(0, 1)) - 1,
+            ),
+            'J' if matches!(parameters, [2] | [3]) => {
+                let result: EfiStatusEnum = self.output.clear_screen();
+
+                /* Unlike "clear_screen", erasing the screen doesn't move the cursor */
+                if result.is_error() {
+                    result
+                } else {
+                    self.move_cursor(column, row)
+                }
+            }
+            'K' => self.erase_line(parameters.first().copied().unwrap_or(0), column, row),
+            _ => EfiStatusEnum::Success(()),
+        }
+    }
+
+    fn select_graphic_rendition(&mut self, parameters: &[u16]) -> EfiStatusEnum {
+        /* No parameters are equivalent to a reset */
+        let parameters: &[u16] = if parameters.is_empty() {
+            &[0]
+        } else {
+            parameters
+        };
+
+        for &parameter in parameters {
+            match parameter {
+                0 => {
+                    self.foreground = self.default_attribute.foreground();
+                    self.background = self.default_attribute.background();
+                    self.bold = false;
+                }
+                1 => self.bold = true,
+                22 => self.bold = false,
+                30..=37 => self.foreground = NORMAL_COLORS[usize::from(parameter - 30)],
+                39 => self.foreground = self.default_attribute.foreground(),
+                40..=47 => {
+                    self.background = EfiBackgroundColor::from_raw(
+                        (NORMAL_COLORS[usize::from(parameter - 40)] as usize) << 4,
+                    )
+                }
+                49 => self.background = self.default_attribute.background(),
+                90..=97 => self.foreground = NORMAL_COLORS[usize::from(parameter - 90)].bright(),
+                100..=107 => {
+                    self.background = EfiBackgroundColor::from_raw(
+                        (NORMAL_COLORS[usize::from(parameter - 100)] as usize) << 4,
+                    )
+                }
+                _ => (),
+            }
+        }
+
+        let foreground: EfiForegroundColor = if self.bold {
+            self.foreground.bright()
+        } else {
+            self.foreground
+        };
+
+        self.output
+            .set_attribute(EfiTextAttribute::new(foreground, self.background))
+    }
+
+    fn dimensions(&self) -> Option<(usize, usize)> {
+        let (mut columns, mut rows): (usize, usize) = (0, 0);
+
+        self.output
+            .query_mode(
+                self.output.get_mode().mode() as usize,
+                &mut columns,
+                &mut rows,
+            )
+            .unfold()
+            .ok()
+            .map(|_| (columns, rows))
+    }
+
+    fn move_cursor(&self, column: usize, row: usize) -> EfiStatusEnum {
+        match self.dimensions() {
+            Some((columns, rows)) if columns != 0 && rows != 0 => self
+                .output
+                .set_cursor_position(column.min(columns - 1), row.min(rows - 1)),
+            _ => self.output.set_cursor_position(column, row),
+        }
+    }
+
+    fn erase_line(&self, mode: u16, column: usize, row: usize) -> EfiStatusEnum {
+        const SPACES: &str = "                                ";
+
+        let (columns, rows): (usize, usize) = match self.dimensions() {
+            Some(dimensions) => dimensions,
+            None => return EfiStatusEnum::Success(()),
+        };
+
+        let (start, mut end): (usize, usize) = match mode {
+            0 => (column, columns),
+            1 => (0, column + 1),
+            2 => (0, columns),
+            _ => return EfiStatusEnum::Success(()),
+        };
+
+        /* Writing the last cell of the last row would scroll the screen */
+        if row + 1 == rows {
+            end = end.min(columns - 1);
+        }
+
+        let mut result: EfiStatusEnum = self.output.set_cursor_position(start, row);
+
+        let mut remaining: usize = end.saturating_sub(start);
+
+        while remaining != 0 && !result.is_error() {
+            let length: usize = remaining.min(SPACES.len());
+
+            result = self.output.output_string(&SPACES[..length]);
+
+            remaining -= length;
+        }
+
+        Self::merge(result, self.output.set_cursor_position(column, row))
+    }
+}
+
+impl Write for EfiAnsiTextOutput<'_> {
+    fn write_str(&mut self, s: &str) -> Result<(), Error> {
+        if self.write(s).is_error() {
+            Err(Error)
+        } else {
+            Ok(())
+        }
+    }
+}
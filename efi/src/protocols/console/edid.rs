@@ -0,0 +1,167 @@
+use {
+    crate::{
+        guid::EfiGuid,
+        protocols::EfiProtocol,
+        status::{EfiStatus, EfiStatusEnum},
+        structures::edid::Edid,
+        types::{EfiHandle, NonNullVoidPtr},
+    },
+    core::slice::from_raw_parts,
+};
+
+unsafe fn edid_data<'a>(size: usize, edid: *const u8) -> Option<&'a [u8]> {
+    if size == 0 || edid.is_null() {
+        None
+    } else {
+        Some(from_raw_parts(edid, size))
+    }
+}
+
+/// Implementation of EFI's `EFI_EDID_DISCOVERED_PROTOCOL`.
+///
+/// Contains the EDID as read from the video output device, unmodified.
+#[repr(C)]
+pub struct EfiEdidDiscoveredProtocol {
+    size_of_edid: u32,
+    edid: *const u8,
+}
+
+impl EfiEdidDiscoveredProtocol {
+    /// Returns the raw EDID, if the device provided one.
+    pub fn edid(&self) -> Option<&[u8]> {
+        unsafe { edid_data(self.size_of_edid as usize, self.edid) }
+    }
+
+    /// Parses the base block of the EDID.
+    pub fn parse_edid(&self) -> Option<Edid<'_>> {
+        self.edid().and_then(Edid::parse)
+    }
+}
+
+impl EfiProtocol for EfiEdidDiscoveredProtocol {
+    type Parsed = &'static Self;
+    type Error = !;
+
+    fn guid() -> EfiGuid {
+        crate::guids::EFI_EDID_DISCOVERED_PROTOCOL
+    }
+
+    unsafe fn parse(
+        ptr: NonNullVoidPtr,
+    ) -> Result<<Self as EfiProtocol>::Parsed, <Self as EfiProtocol>::Error> {
+        Ok(&*ptr.cast().as_ptr())
+    }
+}
+
+/// Implementation of EFI's `EFI_EDID_ACTIVE_PROTOCOL`.
+///
+/// Contains the EDID the video output device is operated with, which is the discovered one unless it was overridden.
+#[repr(C)]
+pub struct EfiEdidActiveProtocol {
+    size_of_edid: u32,
+    edid: *const u8,
+}
+
+impl EfiEdidActiveProtocol {
+    /// Returns the raw EDID, if the device has one.
+    pub fn edid(&self) -> Option<&[u8]> {
+        unsafe { edid_data(self.size_of_edid as usize, self.edid) }
+    }
+
+    /// Parses the base block of the EDID.
+    pub fn parse_edid(&self) -> Option<Edid<'_>> {
+        self.edid().and_then(Edid::parse)
+    }
+}
+
+impl EfiProtocol for EfiEdidActiveProtocol {
+    type Parsed = &'static Self;
+    type Error = !;
+
+    fn guid() -> EfiGuid {
+        crate::guids::EFI_EDID_ACTIVE_PROTOCOL
+    }
+
+    unsafe fn parse(
+        ptr: NonNullVoidPtr,
+    ) -> Result<<Self as EfiProtocol>::Parsed, <Self as EfiProtocol>::Error> {
+        Ok(&*ptr.cast().as_ptr())
+    }
+}
+
+/// Implementation of EFI's `EFI_EDID_OVERRIDE_PROTOCOL`.
+///
+/// Produced by the platform to replace or amend the EDID of video output devices.
+#[repr(C)]
+pub struct EfiEdidOverrideProtocol {
+    get_edid: extern "efiapi" fn(
+        *const Self,
+        *const EfiHandle,
+        *mut u32,
+        *mut usize,
+        *mut *const u8,
+    ) -> EfiStatus,
+}
+
+impl EfiEdidOverrideProtocol {
+    /// Returns the override policy for the video output device's child handle, and the EDID to use, if any.
+    ///
+    /// The EDID buffer is owned by the protocol's producer.
+    /// [`EfiUnsupported`](crate::status::EfiStatusError::EfiUnsupported) is returned when there's no override for the device.
+    pub fn get_edid(
+        &self,
+        child_handle: EfiHandle,
+    ) -> EfiStatusEnum<(EfiEdidOverrideAttributes, Option<&'static [u8]>)> {
+        let mut attributes: u32 = 0;
+        let mut size: usize = 0;
+        let mut edid: *const u8 = 0 as _;
+
+        (self.get_edid)(self, &child_handle, &mut attributes, &mut size, &mut edid).into_enum_data(
+            || {
+                (EfiEdidOverrideAttributes::new(attributes), unsafe {
+                    edid_data(size, edid)
+                })
+            },
+        )
+    }
+}
+
+impl EfiProtocol for EfiEdidOverrideProtocol {
+    type Parsed = &'static Self;
+    type Error = !;
+
+    fn guid() -> EfiGuid {
+        crate::guids::EFI_EDID_OVERRIDE_PROTOCOL
+    }
+
+    unsafe fn parse(
+        ptr: NonNullVoidPtr,
+    ) -> Result<<Self as EfiProtocol>::Parsed, <Self as EfiProtocol>::Error> {
+        Ok(&*ptr.cast().as_ptr())
+    }
+}
+
+/// Policy returned by [`EfiEdidOverrideProtocol::get_edid`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EfiEdidOverrideAttributes {
+    attributes: u32,
+}
+
+impl EfiEdidOverrideAttributes {
+    /// The discovered EDID is used and the returned one is ignored.
+    pub const DONT_OVERRIDE: u32 = 0x01;
+    /// The returned EDID is only used while no display is attached.
+    pub const ENABLE_HOT_PLUG: u32 = 0x02;
+
+    pub const fn new(attributes: u32) -> Self {
+        Self { attributes }
+    }
+
+    pub const fn bits(&self) -> u32 {
+        self.attributes
+    }
+
+    pub const fn contains(&self, attributes: u32) -> bool {
+        self.attributes & attributes == attributes
+    }
+}
@@ -1,5 +1,15 @@
+mod absolute_pointer;
+mod ansi;
+mod edid;
+mod serial_io;
+mod simple_pointer;
 mod simple_text_input_protocol;
 mod simple_text_output_protocol;
 
+pub use absolute_pointer::*;
+pub use ansi::*;
+pub use edid::*;
+pub use serial_io::*;
+pub use simple_pointer::*;
 pub use simple_text_input_protocol::*;
 pub use simple_text_output_protocol::*;
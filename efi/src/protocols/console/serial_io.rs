@@ -0,0 +1,338 @@
+use {
+    crate::{
+        guid::EfiGuid,
+        protocols::EfiProtocol,
+        status::{EfiStatus, EfiStatusEnum, EfiStatusError},
+        types::{NonNullVoidPtr, VoidMutPtr, VoidPtr},
+    },
+    core::{
+        fmt::{Error, Write},
+        ops::Deref,
+    },
+};
+
+/// Implementation of EFI's `EFI_SERIAL_IO_PROTOCOL`.
+#[repr(C)]
+pub struct EfiSerialIoProtocol {
+    revision: u32,
+    reset: extern "efiapi" fn(*const Self) -> EfiStatus,
+    set_attributes: extern "efiapi" fn(
+        *const Self,
+        u64,
+        u32,
+        u32,
+        EfiParityType,
+        u8,
+        EfiStopBitsType,
+    ) -> EfiStatus,
+    set_control: extern "efiapi" fn(*const Self, u32) -> EfiStatus,
+    get_control: extern "efiapi" fn(*const Self, *mut u32) -> EfiStatus,
+    write: extern "efiapi" fn(*const Self, *mut usize, VoidPtr) -> EfiStatus,
+    read: extern "efiapi" fn(*const Self, *mut usize, VoidMutPtr) -> EfiStatus,
+    mode: *const EfiSerialIoMode,
+    device_type_guid: *const EfiGuid,
+}
+
+impl EfiSerialIoProtocol {
+    pub fn revision(&self) -> u32 {
+        self.revision
+    }
+
+    pub fn reset(&self) -> EfiStatusEnum {
+        (self.reset)(self).into_enum()
+    }
+
+    /// Sets the communication attributes. Passing `0` (or the default variants) selects the device's default for that attribute.
+    ///
+    /// The timeout is in microseconds.
+    pub fn set_attributes(
+        &self,
+        baud_rate: u64,
+        receive_fifo_depth: u32,
+        timeout: u32,
+        parity: EfiParityType,
+        data_bits: u8,
+        stop_bits: EfiStopBitsType,
+    ) -> EfiStatusEnum {
+        (self.set_attributes)(
+            self,
+            baud_rate,
+            receive_fifo_depth,
+            timeout,
+            parity,
+            data_bits,
+            stop_bits,
+        )
+        .into_enum()
+    }
+
+    /// Sets the control bits. Only [`EfiSerialControlBits::SETTABLE`] bits can be set.
+    pub fn set_control(&self, control: EfiSerialControlBits) -> EfiStatusEnum {
+        (self.set_control)(self, control.bits).into_enum()
+    }
+
+    pub fn get_control(&self) -> EfiStatusEnum<EfiSerialControlBits> {
+        let mut control: EfiSerialControlBits = EfiSerialControlBits { bits: 0 };
+
+        (self.get_control)(self, &mut control.bits).into_enum_data(|| control)
+    }
+
+    /// Writes the buffer and returns the number of written bytes.
+    ///
+    /// On timeout the number of bytes written before it is returned as error data.
+    pub fn write(&self, buffer: &[u8]) -> EfiStatusEnum<usize, usize> {
+        let mut size: usize = buffer.len();
+
+        (self.write)(self, &mut size, buffer.as_ptr() as VoidPtr)
+            .into_enum_data_error(|| size, || size)
+    }
+
+    /// Reads into the buffer and returns the number of read bytes.
+    ///
+    /// On timeout the number of bytes read before it is returned as error data.
+    pub fn read(&self, buffer: &mut [u8]) -> EfiStatusEnum<usize, usize> {
+        let mut size: usize = buffer.len();
+
+        (self.read)(self, &mut size, buffer.as_mut_ptr() as VoidMutPtr)
+            .into_enum_data_error(|| size, || size)
+    }
+
+    /// Writes the whole buffer, retrying after timeouts as long as progress is made.
+    ///
+    /// Returns `EfiTimeout` when the device accepts no bytes without reporting an error.
+    pub fn write_all(&self, mut buffer: &[u8]) -> EfiStatusEnum {
+        while !buffer.is_empty() {
+            let written: usize = match self.write(buffer) {
+                EfiStatusEnum::Success(written) | EfiStatusEnum::Warning(_, written) => {
+                    if written == 0 {
+                        return EfiStatusEnum::Error(EfiStatusError::EfiTimeout, ());
+                    }
+
+                    written
+                }
+                EfiStatusEnum::Error(status, written) => {
+                    if written == 0 {
+                        return EfiStatusEnum::Error(status, ());
+                    }
+
+                    written
+                }
+            };
+
+            /* Firmware isn't trusted to report at most the buffer's size */
+            buffer = &buffer[written.min(buffer.len())..];
+        }
+
+        EfiStatusEnum::Success(())
+    }
+
+    pub fn mode(&self) -> &EfiSerialIoMode {
+        unsafe { &*self.mode }
+    }
+
+    /// Returns the device type's GUID. Available since revision `0x00010001`.
+    pub fn device_type_guid(&self) -> Option<&EfiGuid> {
+        if self.revision < 0x0001_0001 {
+            None
+        } else {
+            unsafe { self.device_type_guid.as_ref() }
+        }
+    }
+}
+
+/// Terminals expect carriage return before line feed, so `'\n'` is written as `"\r\n"`.
+impl Write for EfiSerialIoProtocol {
+    fn write_str(&mut self, s: &str) -> Result<(), Error> {
+        for (index, line) in s.split('\n').enumerate() {
+            if (index != 0 && self.write_all(b"\r\n").is_error())
+                || self.write_all(line.as_bytes()).is_error()
+            {
+                return Err(Error);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl EfiProtocol for EfiSerialIoProtocol {
+    type Parsed = &'static Self;
+    type Error = !;
+
+    fn guid() -> EfiGuid {
+        crate::guids::EFI_SERIAL_IO_PROTOCOL
+    }
+
+    unsafe fn parse(
+        ptr: NonNullVoidPtr,
+    ) -> Result<<Self as EfiProtocol>::Parsed, <Self as EfiProtocol>::Error> {
+        Ok(&*ptr.cast().as_ptr())
+    }
+}
+
+#[repr(C)]
+pub struct EfiSerialIoMode {
+    control_mask: u32,
+    timeout: u32,
+    baud_rate: u64,
+    receive_fifo_depth: u32,
+    data_bits: u32,
+    parity: u32,
+    stop_bits: u32,
+}
+
+impl EfiSerialIoMode {
+    /// Returns the control bits supported by the device.
+    pub fn control_mask(&self) -> EfiSerialControlBits {
+        EfiSerialControlBits {
+            bits: self.control_mask,
+        }
+    }
+
+    /// Returns the timeout in microseconds.
+    pub fn timeout(&self) -> u32 {
+        self.timeout
+    }
+
+    pub fn baud_rate(&self) -> u64 {
+        self.baud_rate
+    }
+
+    pub fn receive_fifo_depth(&self) -> u32 {
+        self.receive_fifo_depth
+    }
+
+    pub fn data_bits(&self) -> u32 {
+        self.data_bits
+    }
+
+    /// Returns the parity, or `None` when the firmware reported an unknown value.
+    pub fn parity(&self) -> Option<EfiParityType> {
+        use EfiParityType::*;
+
+        Some(match self.parity {
+            0 => Default,
+            1 => NoParity,
+            2 => EvenParity,
+            3 => OddParity,
+            4 => MarkParity,
+            5 => SpaceParity,
+            _ => return None,
+        })
+    }
+
+    /// Returns the number of stop bits, or `None` when the firmware reported an unknown value.
+    pub fn stop_bits(&self) -> Option<EfiStopBitsType> {
+        use EfiStopBitsType::*;
+
+        Some(match self.stop_bits {
+            0 => Default,
+            1 => OneStopBit,
+            2 => OneFiveStopBits,
+            3 => TwoStopBits,
+            _ => return None,
+        })
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum EfiParityType {
+    Default,
+    NoParity,
+    EvenParity,
+    OddParity,
+    MarkParity,
+    SpaceParity,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum EfiStopBitsType {
+    Default,
+    OneStopBit,
+    OneFiveStopBits,
+    TwoStopBits,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EfiSerialControlBits {
+    bits: u32,
+}
+
+impl EfiSerialControlBits {
+    pub const DATA_TERMINAL_READY: u32 = 0x0001;
+    pub const REQUEST_TO_SEND: u32 = 0x0002;
+    pub const CLEAR_TO_SEND: u32 = 0x0010;
+    pub const DATA_SET_READY: u32 = 0x0020;
+    pub const RING_INDICATE: u32 = 0x0040;
+    pub const CARRIER_DETECT: u32 = 0x0080;
+    pub const INPUT_BUFFER_EMPTY: u32 = 0x0100;
+    pub const OUTPUT_BUFFER_EMPTY: u32 = 0x0200;
+    pub const HARDWARE_LOOPBACK_ENABLE: u32 = 0x1000;
+    pub const SOFTWARE_LOOPBACK_ENABLE: u32 = 0x2000;
+    pub const HARDWARE_FLOW_CONTROL_ENABLE: u32 = 0x4000;
+
+    /// Bits which can be changed through [`EfiSerialIoProtocol::set_control`].
+    pub const SETTABLE: u32 = Self::DATA_TERMINAL_READY
+        | Self::REQUEST_TO_SEND
+        | Self::HARDWARE_LOOPBACK_ENABLE
+        | Self::SOFTWARE_LOOPBACK_ENABLE
+        | Self::HARDWARE_FLOW_CONTROL_ENABLE;
+
+    pub const fn new(bits: u32) -> Self {
+        Self { bits }
+    }
+
+    pub fn data_terminal_ready(&self) -> bool {
+        self.bits & Self::DATA_TERMINAL_READY != 0
+    }
+
+    pub fn request_to_send(&self) -> bool {
+        self.bits & Self::REQUEST_TO_SEND != 0
+    }
+
+    pub fn clear_to_send(&self) -> bool {
+        self.bits & Self::CLEAR_TO_SEND != 0
+    }
+
+    pub fn data_set_ready(&self) -> bool {
+        self.bits & Self::DATA_SET_READY != 0
+    }
+
+    pub fn ring_indicate(&self) -> bool {
+        self.bits & Self::RING_INDICATE != 0
+    }
+
+    pub fn carrier_detect(&self) -> bool {
+        self.bits & Self::CARRIER_DETECT != 0
+    }
+
+    pub fn input_buffer_empty(&self) -> bool {
+        self.bits & Self::INPUT_BUFFER_EMPTY != 0
+    }
+
+    pub fn output_buffer_empty(&self) -> bool {
+        self.bits & Self::OUTPUT_BUFFER_EMPTY != 0
+    }
+
+    pub fn hardware_loopback_enable(&self) -> bool {
+        self.bits & Self::HARDWARE_LOOPBACK_ENABLE != 0
+    }
+
+    pub fn software_loopback_enable(&self) -> bool {
+        self.bits & Self::SOFTWARE_LOOPBACK_ENABLE != 0
+    }
+
+    pub fn hardware_flow_control_enable(&self) -> bool {
+        self.bits & Self::HARDWARE_FLOW_CONTROL_ENABLE != 0
+    }
+}
+
+impl Deref for EfiSerialControlBits {
+    type Target = u32;
+
+    fn deref(&self) -> &<Self as Deref>::Target {
+        &self.bits
+    }
+}
@@ -0,0 +1,134 @@
+use crate::{
+    guid::EfiGuid,
+    protocols::EfiProtocol,
+    status::{EfiStatus, EfiStatusEnum},
+    types::{EfiEvent, NonNullVoidPtr},
+};
+
+/// Implementation of EFI's `EFI_SIMPLE_POINTER_PROTOCOL`, which reports relative movement of mice and the like.
+#[repr(C)]
+pub struct EfiSimplePointerProtocol {
+    reset: extern "efiapi" fn(*const Self, extended_verification: bool) -> EfiStatus,
+    get_state: extern "efiapi" fn(*const Self, state: *mut EfiSimplePointerState) -> EfiStatus,
+    wait_for_input: EfiEvent,
+    mode: *const EfiSimplePointerMode,
+}
+
+impl EfiSimplePointerProtocol {
+    pub fn reset(&self, extended_verification: bool) -> EfiStatusEnum {
+        (self.reset)(self, extended_verification).into_enum()
+    }
+
+    /// Returns the movement since the last call.
+    ///
+    /// [`EfiNotReady`](crate::status::EfiStatusError::EfiNotReady) is returned when the state didn't change.
+    pub fn get_state(&self) -> EfiStatusEnum<EfiSimplePointerState> {
+        let mut state: EfiSimplePointerState = EfiSimplePointerState {
+            relative_movement_x: 0,
+            relative_movement_y: 0,
+            relative_movement_z: 0,
+            left_button: false,
+            right_button: false,
+        };
+
+        (self.get_state)(self, &mut state).into_enum_data(|| state)
+    }
+
+    /// Returns the event signaled when the state changes, for use with `WaitForEvent`.
+    pub fn wait_for_input(&self) -> &EfiEvent {
+        &self.wait_for_input
+    }
+
+    pub fn wait_for_input_mut(&mut self) -> &mut EfiEvent {
+        &mut self.wait_for_input
+    }
+
+    pub fn mode(&self) -> &EfiSimplePointerMode {
+        unsafe { &*self.mode }
+    }
+}
+
+impl EfiProtocol for EfiSimplePointerProtocol {
+    type Parsed = &'static Self;
+    type Error = !;
+
+    fn guid() -> EfiGuid {
+        crate::guids::EFI_SIMPLE_POINTER_PROTOCOL
+    }
+
+    unsafe fn parse(
+        ptr: NonNullVoidPtr,
+    ) -> Result<<Self as EfiProtocol>::Parsed, <Self as EfiProtocol>::Error> {
+        Ok(&*ptr.cast().as_ptr())
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EfiSimplePointerMode {
+    resolution_x: u64,
+    resolution_y: u64,
+    resolution_z: u64,
+    left_button: bool,
+    right_button: bool,
+}
+
+impl EfiSimplePointerMode {
+    /// Returns the counts per millimeter along the X axis, or zero if the axis isn't supported.
+    pub fn resolution_x(&self) -> u64 {
+        self.resolution_x
+    }
+
+    /// Returns the counts per millimeter along the Y axis, or zero if the axis isn't supported.
+    pub fn resolution_y(&self) -> u64 {
+        self.resolution_y
+    }
+
+    /// Returns the counts per millimeter along the Z axis (usually the wheel), or zero if the axis isn't supported.
+    pub fn resolution_z(&self) -> u64 {
+        self.resolution_z
+    }
+
+    pub fn has_left_button(&self) -> bool {
+        self.left_button
+    }
+
+    pub fn has_right_button(&self) -> bool {
+        self.right_button
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EfiSimplePointerState {
+    relative_movement_x: i32,
+    relative_movement_y: i32,
+    relative_movement_z: i32,
+    left_button: bool,
+    right_button: bool,
+}
+
+impl EfiSimplePointerState {
+    /// Returns the movement along the X axis, in units of [`resolution_x`](EfiSimplePointerMode::resolution_x).
+    pub fn relative_movement_x(&self) -> i32 {
+        self.relative_movement_x
+    }
+
+    /// Returns the movement along the Y axis, in units of [`resolution_y`](EfiSimplePointerMode::resolution_y).
+    pub fn relative_movement_y(&self) -> i32 {
+        self.relative_movement_y
+    }
+
+    /// Returns the movement along the Z axis, in units of [`resolution_z`](EfiSimplePointerMode::resolution_z).
+    pub fn relative_movement_z(&self) -> i32 {
+        self.relative_movement_z
+    }
+
+    pub fn left_button(&self) -> bool {
+        self.left_button
+    }
+
+    pub fn right_button(&self) -> bool {
+        self.right_button
+    }
+}
@@ -51,7 +51,36 @@ impl EfiProtocol for EfiSimpleTextInputProtocol {
 }
 
 #[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct EfiInputKey {
     scan_code: u16,
     unicode_char: u16,
 }
+
+impl EfiInputKey {
+    pub const SCAN_NULL: u16 = 0x00;
+    pub const SCAN_UP: u16 = 0x01;
+    pub const SCAN_DOWN: u16 = 0x02;
+    pub const SCAN_RIGHT: u16 = 0x03;
+    pub const SCAN_LEFT: u16 = 0x04;
+    pub const SCAN_HOME: u16 = 0x05;
+    pub const SCAN_END: u16 = 0x06;
+    pub const SCAN_INSERT: u16 = 0x07;
+    pub const SCAN_DELETE: u16 = 0x08;
+    pub const SCAN_PAGE_UP: u16 = 0x09;
+    pub const SCAN_PAGE_DOWN: u16 = 0x0A;
+    /// Scan codes of `F2` to `F10` follow consecutively.
+    pub const SCAN_F1: u16 = 0x0B;
+    pub const SCAN_F10: u16 = 0x14;
+    pub const SCAN_ESC: u16 = 0x17;
+
+    /// Returns the scan code of non-printable keys or [`SCAN_NULL`](Self::SCAN_NULL).
+    pub fn scan_code(&self) -> u16 {
+        self.scan_code
+    }
+
+    /// Returns the UTF-16 code unit of printable keys or zero.
+    pub fn unicode_char(&self) -> u16 {
+        self.unicode_char
+    }
+}
@@ -54,7 +54,7 @@ impl EfiSimpleTextOutputProtocol {
 
             let mut buffer: [u16; BUFFER_LEN] = [0; BUFFER_LEN];
 
-            let mut char_count: usize = 0;
+            let mut byte_count: usize = 0;
 
             let mut index: usize = 0;
 
@@ -70,7 +70,7 @@ impl EfiSimpleTextOutputProtocol {
 
                     index += 2;
 
-                    char_count += 1;
+                    byte_count += 1;
 
                     continue;
                 }
@@ -81,7 +81,7 @@ impl EfiSimpleTextOutputProtocol {
                     break;
                 }
 
-                char_count += 1;
+                byte_count += ch.len_utf8();
 
                 ch.encode_utf16(&mut buffer[index..]);
 
@@ -94,9 +94,7 @@ impl EfiSimpleTextOutputProtocol {
                 return status.into_enum();
             }
 
-            // string = string.split_at(index).1;
-
-            string = &string[char_count..];
+            string = &string[byte_count..];
 
             if string.is_empty() {
                 break;
@@ -175,8 +173,8 @@ impl EfiSimpleTextOutputProtocol {
         (self.set_mode)(self, mode).into_enum()
     }
 
-    pub fn set_attribute(&self, attribute: usize) -> EfiStatusEnum {
-        (self.set_attribute)(self, attribute).into_enum()
+    pub fn set_attribute(&self, attribute: EfiTextAttribute) -> EfiStatusEnum {
+        (self.set_attribute)(self, attribute.raw()).into_enum()
     }
 
     pub fn clear_screen(&self) -> EfiStatusEnum {
@@ -240,8 +238,8 @@ impl EfiSimpleTextOutputMode {
         self.mode
     }
 
-    pub fn attribute(&self) -> u32 {
-        self.attribute
+    pub fn attribute(&self) -> EfiTextAttribute {
+        EfiTextAttribute::from_raw(self.attribute as usize)
     }
 
     pub fn cursor_column(&self) -> u32 {
@@ -256,3 +254,126 @@ impl EfiSimpleTextOutputMode {
         self.cursor_visible
     }
 }
+
+/// Foreground colors of the text.
+#[repr(usize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum EfiForegroundColor {
+    Black = 0x00,
+    Blue = 0x01,
+    Green = 0x02,
+    Cyan = 0x03,
+    Red = 0x04,
+    Magenta = 0x05,
+    Brown = 0x06,
+    LightGray = 0x07,
+    DarkGray = 0x08,
+    LightBlue = 0x09,
+    LightGreen = 0x0A,
+    LightCyan = 0x0B,
+    LightRed = 0x0C,
+    LightMagenta = 0x0D,
+    Yellow = 0x0E,
+    White = 0x0F,
+}
+
+impl EfiForegroundColor {
+    /// Returns the color encoded in the lower 4 bits.
+    pub const fn from_raw(color: usize) -> Self {
+        match color & 0x0F {
+            0x00 => Self::Black,
+            0x01 => Self::Blue,
+            0x02 => Self::Green,
+            0x03 => Self::Cyan,
+            0x04 => Self::Red,
+            0x05 => Self::Magenta,
+            0x06 => Self::Brown,
+            0x07 => Self::LightGray,
+            0x08 => Self::DarkGray,
+            0x09 => Self::LightBlue,
+            0x0A => Self::LightGreen,
+            0x0B => Self::LightCyan,
+            0x0C => Self::LightRed,
+            0x0D => Self::LightMagenta,
+            0x0E => Self::Yellow,
+            _ => Self::White,
+        }
+    }
+
+    /// Returns the bright variant of the color (e.g.: `Yellow` for `Brown`).
+    pub const fn bright(self) -> Self {
+        Self::from_raw(self as usize | 0x08)
+    }
+}
+
+/// Background colors of the text. Only the first eight colors are available as background ones.
+#[repr(usize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum EfiBackgroundColor {
+    Black = 0x00,
+    Blue = 0x10,
+    Green = 0x20,
+    Cyan = 0x30,
+    Red = 0x40,
+    Magenta = 0x50,
+    Brown = 0x60,
+    LightGray = 0x70,
+}
+
+impl EfiBackgroundColor {
+    /// Returns the color encoded in bits 4 to 6.
+    pub const fn from_raw(color: usize) -> Self {
+        match color & 0x70 {
+            0x00 => Self::Black,
+            0x10 => Self::Blue,
+            0x20 => Self::Green,
+            0x30 => Self::Cyan,
+            0x40 => Self::Red,
+            0x50 => Self::Magenta,
+            0x60 => Self::Brown,
+            _ => Self::LightGray,
+        }
+    }
+}
+
+/// Text attribute combining foreground and background colors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EfiTextAttribute {
+    foreground: EfiForegroundColor,
+    background: EfiBackgroundColor,
+}
+
+impl EfiTextAttribute {
+    pub const fn new(foreground: EfiForegroundColor, background: EfiBackgroundColor) -> Self {
+        Self {
+            foreground,
+            background,
+        }
+    }
+
+    pub const fn from_raw(attribute: usize) -> Self {
+        Self::new(
+            EfiForegroundColor::from_raw(attribute),
+            EfiBackgroundColor::from_raw(attribute),
+        )
+    }
+
+    pub const fn raw(&self) -> usize {
+        self.foreground as usize | self.background as usize
+    }
+
+    pub const fn foreground(&self) -> EfiForegroundColor {
+        self.foreground
+    }
+
+    pub const fn background(&self) -> EfiBackgroundColor {
+        self.background
+    }
+}
+
+impl Default for EfiTextAttribute {
+    /// Light gray on black, which is the attribute consoles start with.
+    fn default() -> Self {
+        Self::new(EfiForegroundColor::LightGray, EfiBackgroundColor::Black)
+    }
+}
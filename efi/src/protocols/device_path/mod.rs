@@ -1,9 +1,24 @@
-use core::ops::{Deref, DerefMut};
+use alloc::vec::Vec;
+use core::{
+    convert::{TryFrom, TryInto},
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+    slice::from_raw_parts,
+};
 
-use crate::{
-    guid::EfiGuid, guids::EFI_DEVICE_PATH_PROTOCOL, protocols::EfiProtocol, types::NonNullVoidPtr,
+use {
+    crate::{
+        guid::EfiGuid,
+        guids::EFI_DEVICE_PATH_PROTOCOL,
+        protocols::{media::EfiRamDiskDevicePathNode, EfiProtocol},
+        types::NonNullVoidPtr,
+    },
+    efi_interops::{traits::acpi::device::NvdimmDeviceHandle, EfiObject},
 };
 
+mod to_text;
+pub use to_text::*;
+
 #[repr(transparent)]
 pub struct EfiDevicePathProtocolRaw {
     pointer: NonNullVoidPtr,
@@ -13,6 +28,57 @@ impl EfiDevicePathProtocolRaw {
     pub const fn new(pointer: NonNullVoidPtr) -> Self {
         Self { pointer }
     }
+
+    /// Returns iterator over the device path's nodes, excluding the end node.
+    /// # Safety
+    /// The pointer must point to a valid device path, terminated by an end node.
+    pub unsafe fn nodes(&self) -> EfiDevicePathNodes<'_> {
+        EfiDevicePathNodes {
+            node: self.pointer.as_ptr() as *const u8,
+            _device_path: PhantomData,
+        }
+    }
+
+    /// Returns copy of the device path with the node appended to it's last instance.
+    ///
+    /// Returns `None` when the node's data is too big to fit in a single node.
+    /// # Safety
+    /// The pointer must point to a valid device path, terminated by an end node.
+    pub unsafe fn append_node(&self, node: &EfiDevicePathNode<'_>) -> Option<Vec<u8>> {
+        let mut device_path: Vec<u8> = Vec::new();
+
+        for node in self.nodes().chain(Some(*node)) {
+            node.write_to(&mut device_path)?;
+        }
+
+        EfiDevicePathNode::new(0x7F, 0xFF, &[]).write_to(&mut device_path)?;
+
+        Some(device_path)
+    }
+
+    /// Returns the NFIT device handle of the NVDIMM the device path leads to, held by it's last ACPI `_ADR` node.
+    /// # Safety
+    /// The pointer must point to a valid device path, terminated by an end node.
+    pub unsafe fn nvdimm_device_handle<T: NvdimmDeviceHandle>(&self) -> Option<T> {
+        let node: EfiDevicePathNode<'_> = self
+            .nodes()
+            .filter(|node: &EfiDevicePathNode<'_>| node.node_type == 0x02 && node.sub_type == 0x03)
+            .last()?;
+
+        /* The node may hold several "_ADR"s, for devices spanning multiple ones */
+        let address: [u8; 4] = node.data.get(..4)?.try_into().ok()?;
+
+        T::convert(EfiObject::new(&address))
+    }
+
+    /// Returns the last RAM disk node of the device path, which RAM disks' device paths end with.
+    /// # Safety
+    /// The pointer must point to a valid device path, terminated by an end node.
+    pub unsafe fn ram_disk(&self) -> Option<EfiRamDiskDevicePathNode> {
+        self.nodes()
+            .filter_map(|node: EfiDevicePathNode<'_>| EfiRamDiskDevicePathNode::parse(&node))
+            .last()
+    }
 }
 
 impl Deref for EfiDevicePathProtocolRaw {
@@ -43,3 +109,85 @@ impl EfiProtocol for EfiDevicePathProtocolRaw {
         Ok(Self::new(pointer))
     }
 }
+
+/// Single device path node.
+#[derive(Clone, Copy)]
+pub struct EfiDevicePathNode<'a> {
+    node_type: u8,
+    sub_type: u8,
+    data: &'a [u8],
+}
+
+impl<'a> EfiDevicePathNode<'a> {
+    pub const fn new(node_type: u8, sub_type: u8, data: &'a [u8]) -> Self {
+        Self {
+            node_type,
+            sub_type,
+            data,
+        }
+    }
+
+    pub fn node_type(&self) -> u8 {
+        self.node_type
+    }
+
+    pub fn sub_type(&self) -> u8 {
+        self.sub_type
+    }
+
+    /// Returns the node's data, without the header.
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Returns whether the node ends a device path instance (more instances follow it).
+    pub fn is_end_of_instance(&self) -> bool {
+        self.node_type == 0x7F && self.sub_type == 0x01
+    }
+
+    fn write_to(&self, buffer: &mut Vec<u8>) -> Option<()> {
+        let length: u16 = u16::try_from(self.data.len() + 4).ok()?;
+
+        buffer.extend_from_slice(&[self.node_type, self.sub_type]);
+        buffer.extend_from_slice(&length.to_le_bytes());
+        buffer.extend_from_slice(self.data);
+
+        Some(())
+    }
+}
+
+pub struct EfiDevicePathNodes<'a> {
+    node: *const u8,
+    _device_path: PhantomData<&'a EfiDevicePathProtocolRaw>,
+}
+
+impl<'a> Iterator for EfiDevicePathNodes<'a> {
+    type Item = EfiDevicePathNode<'a>;
+
+    fn next(&mut self) -> Option<<Self as Iterator>::Item> {
+        if self.node.is_null() {
+            return None;
+        }
+
+        let header: &[u8] = unsafe { from_raw_parts(self.node, 4) };
+
+        let length: usize = usize::from(u16::from_le_bytes([header[2], header[3]]));
+
+        /* End of the whole device path or a malformed node */
+        if (header[0] == 0x7F && header[1] == 0xFF) || length < 4 {
+            self.node = 0 as _;
+
+            return None;
+        }
+
+        let node: EfiDevicePathNode<'a> = EfiDevicePathNode {
+            node_type: header[0],
+            sub_type: header[1],
+            data: unsafe { from_raw_parts(self.node.add(4), length - 4) },
+        };
+
+        self.node = unsafe { self.node.add(length) };
+
+        Some(node)
+    }
+}
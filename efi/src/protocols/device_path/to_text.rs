@@ -0,0 +1,61 @@
+use {
+    super::EfiDevicePathProtocolRaw,
+    crate::{guid::EfiGuid, protocols::EfiProtocol, types::NonNullVoidPtr, Void},
+    core::ptr::NonNull,
+};
+
+/// Implementation of EFI's `EFI_DEVICE_PATH_TO_TEXT_PROTOCOL`.
+#[repr(C)]
+pub struct EfiDevicePathToTextProtocol {
+    convert_device_node_to_text: extern "efiapi" fn(*const Void, bool, bool) -> *mut u16,
+    convert_device_path_to_text: extern "efiapi" fn(*const Void, bool, bool) -> *mut u16,
+}
+
+impl EfiDevicePathToTextProtocol {
+    /// Converts the first node of the device path to text.
+    ///
+    /// The returned string is null-terminated, allocated from pool memory and has to be released with `free_pool`.
+    pub fn convert_device_node_to_text(
+        &self,
+        device_node: &EfiDevicePathProtocolRaw,
+        display_only: bool,
+        allow_shortcuts: bool,
+    ) -> Option<NonNull<u16>> {
+        NonNull::new((self.convert_device_node_to_text)(
+            device_node.as_ptr(),
+            display_only,
+            allow_shortcuts,
+        ))
+    }
+
+    /// Converts the device path to text.
+    ///
+    /// The returned string is null-terminated, allocated from pool memory and has to be released with `free_pool`.
+    pub fn convert_device_path_to_text(
+        &self,
+        device_path: &EfiDevicePathProtocolRaw,
+        display_only: bool,
+        allow_shortcuts: bool,
+    ) -> Option<NonNull<u16>> {
+        NonNull::new((self.convert_device_path_to_text)(
+            device_path.as_ptr(),
+            display_only,
+            allow_shortcuts,
+        ))
+    }
+}
+
+impl EfiProtocol for EfiDevicePathToTextProtocol {
+    type Parsed = &'static Self;
+    type Error = !;
+
+    fn guid() -> EfiGuid {
+        crate::guids::EFI_DEVICE_PATH_TO_TEXT_PROTOCOL
+    }
+
+    unsafe fn parse(
+        ptr: NonNullVoidPtr,
+    ) -> Result<<Self as EfiProtocol>::Parsed, <Self as EfiProtocol>::Error> {
+        Ok(&*ptr.cast().as_ptr())
+    }
+}
@@ -0,0 +1,322 @@
+use {
+    crate::{
+        boot_services::{types::protocol_handler::EfiInterfaceType, EfiBootServices1x0},
+        guid::EfiGuid,
+        protocols::EfiProtocol,
+        status::{EfiStatus, EfiStatusEnum, EfiStatusError},
+        types::{EfiHandle, NonNullVoidPtr},
+        utilities::string_from_raw,
+    },
+    alloc::boxed::Box,
+    core::{mem::size_of, ptr::NonNull, slice::from_raw_parts, str::from_utf8},
+};
+
+/* Enough for any RFC 4646 language code used by firmware (e.g.: "en-US") */
+const LANGUAGE_BUFFER_SIZE: usize = 36;
+
+/// Implemented by drivers which report human readable names.
+///
+/// Installed with [`EfiComponentName2Protocol::install`].
+pub trait EfiComponentName: 'static {
+    /// Semicolon separated and null-terminated list of the supported RFC 4646 language codes (e.g.: `b"en;bg\0"`).
+    const SUPPORTED_LANGUAGES: &'static [u8];
+
+    /// Returns the null-terminated name of the driver in one of the supported languages.
+    fn driver_name(&self, language: &str) -> Option<&[u16]>;
+
+    /// Returns the null-terminated name of the controller (or of it's child, when one is given) in one of the supported languages.
+    ///
+    /// Only called for controllers managed by the driver.
+    fn controller_name(
+        &self,
+        controller_handle: EfiHandle,
+        child_handle: Option<EfiHandle>,
+        language: &str,
+    ) -> Option<&[u16]>;
+}
+
+/// Implementation of EFI's `EFI_COMPONENT_NAME2_PROTOCOL`.
+#[repr(C)]
+pub struct EfiComponentName2Protocol {
+    get_driver_name: extern "efiapi" fn(*const Self, *const u8, *mut *const u16) -> EfiStatus,
+    get_controller_name: extern "efiapi" fn(
+        *const Self,
+        EfiHandle,
+        EfiHandle,
+        *const u8,
+        *mut *const u16,
+    ) -> EfiStatus,
+    supported_languages: *const u8,
+}
+
+impl EfiComponentName2Protocol {
+    /// Installs the protocol on the handle, usually the driver binding handle.
+    ///
+    /// The component name is moved to heap memory, which is never released, as the protocol is expected to be installed until the image is unloaded.
+    pub fn install<T>(
+        boot_services: &EfiBootServices1x0,
+        handle: EfiHandle,
+        component_name: T,
+    ) -> EfiStatusEnum<&'static Self>
+    where
+        T: EfiComponentName,
+    {
+        if T::SUPPORTED_LANGUAGES.last() != Some(&0) {
+            return EfiStatusEnum::Error(EfiStatusError::EfiInvalidParameter, ());
+        }
+
+        let instance: NonNull<EfiComponentName2<T>> =
+            NonNull::from(Box::leak(Box::new(EfiComponentName2 {
+                protocol: Self {
+                    get_driver_name: get_driver_name::<T>,
+                    get_controller_name: get_controller_name::<T>,
+                    supported_languages: T::SUPPORTED_LANGUAGES.as_ptr(),
+                },
+                component_name,
+            })));
+
+        let mut handle: EfiHandle = handle;
+
+        match boot_services.install_protocol_interface(
+            &mut handle,
+            &Self::guid(),
+            EfiInterfaceType::NativeInterface,
+            Some(unsafe {
+                from_raw_parts(
+                    instance.as_ptr() as *const u8,
+                    size_of::<EfiComponentName2<T>>(),
+                )
+            }),
+        ) {
+            EfiStatusEnum::Error(error, ()) => {
+                drop(unsafe { Box::from_raw(instance.as_ptr()) });
+
+                EfiStatusEnum::Error(error, ())
+            }
+            result => result.map(|()| unsafe { &(*instance.as_ptr()).protocol }),
+        }
+    }
+
+    /// Returns iterator over the supported RFC 4646 language codes.
+    pub fn supported_languages(&self) -> impl Iterator<Item = &str> {
+        let languages: &[u8] = if self.supported_languages.is_null() {
+            &[]
+        } else {
+            unsafe {
+                let mut length: usize = 0;
+
+                while *self.supported_languages.add(length) != 0 {
+                    length += 1;
+                }
+
+                from_raw_parts(self.supported_languages, length)
+            }
+        };
+
+        from_utf8(languages)
+            .unwrap_or("")
+            .split(';')
+            .filter(|language: &&str| !language.is_empty())
+    }
+
+    /// Returns the supported language which best matches the preferred one.
+    ///
+    /// Exact matches are preferred over ones with the same primary language (e.g.: `en-US` for `en`), falling back to the first supported language.
+    pub fn preferred_language(&self, preferred: &str) -> Option<&str> {
+        self.supported_languages()
+            .find(|language: &&str| language.eq_ignore_ascii_case(preferred))
+            .or_else(|| {
+                self.supported_languages().find(|language: &&str| {
+                    primary_language(language).eq_ignore_ascii_case(primary_language(preferred))
+                })
+            })
+            .or_else(|| self.supported_languages().next())
+    }
+
+    /// Returns the driver's name in the given language (without the null terminator).
+    pub fn get_driver_name(&self, language: &str) -> EfiStatusEnum<&[u16]> {
+        let language: [u8; LANGUAGE_BUFFER_SIZE] = match null_terminated(language) {
+            Some(language) => language,
+            None => return EfiStatusEnum::Error(EfiStatusError::EfiUnsupported, ()),
+        };
+
+        let mut name: *const u16 = 0 as _;
+
+        match (self.get_driver_name)(self, language.as_ptr(), &mut name).into_enum() {
+            EfiStatusEnum::Error(error, ()) => EfiStatusEnum::Error(error, ()),
+            result => match unsafe { string_from_raw(name) } {
+                Ok(name) => result.map(|()| name),
+                Err(_) => EfiStatusEnum::Error(EfiStatusError::EfiDeviceError, ()),
+            },
+        }
+    }
+
+    /// Returns the name of the controller (or of it's child, when one is given) in the given language (without the null terminator).
+    pub fn get_controller_name(
+        &self,
+        controller_handle: EfiHandle,
+        child_handle: Option<EfiHandle>,
+        language: &str,
+    ) -> EfiStatusEnum<&[u16]> {
+        let language: [u8; LANGUAGE_BUFFER_SIZE] = match null_terminated(language) {
+            Some(language) => language,
+            None => return EfiStatusEnum::Error(EfiStatusError::EfiUnsupported, ()),
+        };
+
+        let mut name: *const u16 = 0 as _;
+
+        match (self.get_controller_name)(
+            self,
+            controller_handle,
+            child_handle.unwrap_or(0 as _),
+            language.as_ptr(),
+            &mut name,
+        )
+        .into_enum()
+        {
+            EfiStatusEnum::Error(error, ()) => EfiStatusEnum::Error(error, ()),
+            result => match unsafe { string_from_raw(name) } {
+                Ok(name) => result.map(|()| name),
+                Err(_) => EfiStatusEnum::Error(EfiStatusError::EfiDeviceError, ()),
+            },
+        }
+    }
+}
+
+impl EfiProtocol for EfiComponentName2Protocol {
+    type Parsed = &'static Self;
+    type Error = !;
+
+    fn guid() -> EfiGuid {
+        crate::guids::EFI_COMPONENT_NAME2_PROTOCOL
+    }
+
+    unsafe fn parse(
+        ptr: NonNullVoidPtr,
+    ) -> Result<<Self as EfiProtocol>::Parsed, <Self as EfiProtocol>::Error> {
+        Ok(&*ptr.cast().as_ptr())
+    }
+}
+
+fn null_terminated(language: &str) -> Option<[u8; LANGUAGE_BUFFER_SIZE]> {
+    let mut buffer: [u8; LANGUAGE_BUFFER_SIZE] = [0; LANGUAGE_BUFFER_SIZE];
+
+    if language.len() < LANGUAGE_BUFFER_SIZE && !language.bytes().any(|byte: u8| byte == 0) {
+        buffer[..language.len()].copy_from_slice(language.as_bytes());
+
+        Some(buffer)
+    } else {
+        None
+    }
+}
+
+/// Returns the primary language subtag (e.g.: `en` for `en-US`).
+fn primary_language(language: &str) -> &str {
+    language.split('-').next().unwrap_or(language)
+}
+
+#[repr(C)]
+struct EfiComponentName2<T>
+where
+    T: EfiComponentName,
+{
+    protocol: EfiComponentName2Protocol,
+    component_name: T,
+}
+
+impl<T> EfiComponentName2<T>
+where
+    T: EfiComponentName,
+{
+    /* The protocol is the first field of the instance */
+    unsafe fn from_protocol<'a>(this: *const EfiComponentName2Protocol) -> &'a Self {
+        &*(this as *const Self)
+    }
+
+    /// Returns the language, if it's one of the supported ones.
+    unsafe fn language<'b>(&self, language: *const u8) -> Option<&'b str> {
+        if language.is_null() {
+            return None;
+        }
+
+        let mut length: usize = 0;
+
+        while *language.add(length) != 0 {
+            length += 1;
+        }
+
+        let language: &str = from_utf8(from_raw_parts(language, length)).ok()?;
+
+        if self
+            .protocol
+            .supported_languages()
+            .any(|supported: &str| supported == language)
+        {
+            Some(language)
+        } else {
+            None
+        }
+    }
+}
+
+extern "efiapi" fn get_driver_name<T>(
+    this: *const EfiComponentName2Protocol,
+    language: *const u8,
+    driver_name: *mut *const u16,
+) -> EfiStatus
+where
+    T: EfiComponentName,
+{
+    let instance: &EfiComponentName2<T> = unsafe { EfiComponentName2::from_protocol(this) };
+
+    if driver_name.is_null() {
+        return EfiStatusError::EfiInvalidParameter.into();
+    }
+
+    match unsafe { instance.language(language) }
+        .and_then(|language: &str| instance.component_name.driver_name(language))
+    {
+        Some(name) if name.last() == Some(&0) => {
+            unsafe { *driver_name = name.as_ptr() };
+
+            EfiStatus::success()
+        }
+        _ => EfiStatusError::EfiUnsupported.into(),
+    }
+}
+
+extern "efiapi" fn get_controller_name<T>(
+    this: *const EfiComponentName2Protocol,
+    controller_handle: EfiHandle,
+    child_handle: EfiHandle,
+    language: *const u8,
+    controller_name: *mut *const u16,
+) -> EfiStatus
+where
+    T: EfiComponentName,
+{
+    let instance: &EfiComponentName2<T> = unsafe { EfiComponentName2::from_protocol(this) };
+
+    if controller_handle.is_null() || controller_name.is_null() {
+        return EfiStatusError::EfiInvalidParameter.into();
+    }
+
+    let child_handle: Option<EfiHandle> = if child_handle.is_null() {
+        None
+    } else {
+        Some(child_handle)
+    };
+
+    match unsafe { instance.language(language) }.and_then(|language: &str| {
+        instance
+            .component_name
+            .controller_name(controller_handle, child_handle, language)
+    }) {
+        Some(name) if name.last() == Some(&0) => {
+            unsafe { *controller_name = name.as_ptr() };
+
+            EfiStatus::success()
+        }
+        _ => EfiStatusError::EfiUnsupported.into(),
+    }
+}
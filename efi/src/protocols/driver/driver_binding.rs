@@ -0,0 +1,271 @@
+use {
+    crate::{
+        boot_services::{types::protocol_handler::EfiInterfaceType, EfiBootServices1x0},
+        guid::EfiGuid,
+        protocols::{device_path::EfiDevicePathProtocolRaw, EfiProtocol},
+        status::{EfiStatus, EfiStatusEnum},
+        types::{EfiHandle, NonNullVoidPtr, VoidPtr},
+    },
+    alloc::boxed::Box,
+    core::{mem::size_of, ptr::NonNull, slice::from_raw_parts},
+};
+
+/// Implemented by drivers following the UEFI driver model.
+///
+/// Installed with [`EfiDriverBindingProtocol::install`].
+pub trait EfiDriver: 'static {
+    /// Firmware prefers drivers with higher versions when more than one supports a controller.
+    const VERSION: u32;
+
+    /// Tests whether the driver supports the controller.
+    ///
+    /// Must not modify the controller's state, so protocols opened here have to be closed before returning.
+    fn supported(
+        &self,
+        binding: &EfiDriverBindingProtocol,
+        controller_handle: EfiHandle,
+        remaining_device_path: Option<EfiDevicePathProtocolRaw>,
+    ) -> EfiStatusEnum;
+
+    /// Starts managing the controller, creating child handles for the remaining device path, when one is given.
+    fn start(
+        &self,
+        binding: &EfiDriverBindingProtocol,
+        controller_handle: EfiHandle,
+        remaining_device_path: Option<EfiDevicePathProtocolRaw>,
+    ) -> EfiStatusEnum;
+
+    /// Stops managing the controller.
+    ///
+    /// When `children` is empty, the controller itself has to be released, otherwise only the given children have to be destroyed.
+    fn stop(
+        &self,
+        binding: &EfiDriverBindingProtocol,
+        controller_handle: EfiHandle,
+        children: &[EfiHandle],
+    ) -> EfiStatusEnum;
+}
+
+/// Implementation of EFI's `EFI_DRIVER_BINDING_PROTOCOL`.
+#[repr(C)]
+pub struct EfiDriverBindingProtocol {
+    supported: extern "efiapi" fn(*const Self, EfiHandle, VoidPtr) -> EfiStatus,
+    start: extern "efiapi" fn(*const Self, EfiHandle, VoidPtr) -> EfiStatus,
+    stop: extern "efiapi" fn(*const Self, EfiHandle, usize, *const EfiHandle) -> EfiStatus,
+    version: u32,
+    image_handle: EfiHandle,
+    driver_binding_handle: EfiHandle,
+}
+
+impl EfiDriverBindingProtocol {
+    /// Installs the driver's binding protocol on the image handle.
+    ///
+    /// The driver is moved to heap memory, which is never released, as the protocol is expected to be installed until the image is unloaded.
+    pub fn install<T>(
+        boot_services: &EfiBootServices1x0,
+        image_handle: EfiHandle,
+        driver: T,
+    ) -> EfiStatusEnum<&'static Self>
+    where
+        T: EfiDriver,
+    {
+        let instance: NonNull<EfiDriverBinding<T>> =
+            NonNull::from(Box::leak(Box::new(EfiDriverBinding {
+                protocol: Self {
+                    supported: supported::<T>,
+                    start: start::<T>,
+                    stop: stop::<T>,
+                    version: T::VERSION,
+                    image_handle,
+                    driver_binding_handle: image_handle,
+                },
+                driver,
+            })));
+
+        let mut handle: EfiHandle = image_handle;
+
+        match boot_services.install_protocol_interface(
+            &mut handle,
+            &Self::guid(),
+            EfiInterfaceType::NativeInterface,
+            Some(unsafe {
+                from_raw_parts(
+                    instance.as_ptr() as *const u8,
+                    size_of::<EfiDriverBinding<T>>(),
+                )
+            }),
+        ) {
+            EfiStatusEnum::Error(error, ()) => {
+                drop(unsafe { Box::from_raw(instance.as_ptr()) });
+
+                EfiStatusEnum::Error(error, ())
+            }
+            result => result.map(|()| unsafe { &(*instance.as_ptr()).protocol }),
+        }
+    }
+
+    /// Uninstalls the protocol from the driver binding handle.
+    ///
+    /// The driver itself is leaked, as the firmware may still be executing it's functions.
+    pub fn uninstall(&'static self, boot_services: &EfiBootServices1x0) -> EfiStatusEnum {
+        let mut handle: EfiHandle = self.driver_binding_handle;
+
+        boot_services.uninstall_protocol_interface(
+            &mut handle,
+            &Self::guid(),
+            Some(unsafe { from_raw_parts(self as *const Self as *const u8, size_of::<Self>()) }),
+        )
+    }
+
+    pub fn supported(
+        &self,
+        controller_handle: EfiHandle,
+        remaining_device_path: Option<&EfiDevicePathProtocolRaw>,
+    ) -> EfiStatusEnum {
+        (self.supported)(
+            self,
+            controller_handle,
+            remaining_device_path.map_or(0 as _, |device_path: &EfiDevicePathProtocolRaw| {
+                device_path.as_ptr() as VoidPtr
+            }),
+        )
+        .into_enum()
+    }
+
+    pub fn start(
+        &self,
+        controller_handle: EfiHandle,
+        remaining_device_path: Option<&EfiDevicePathProtocolRaw>,
+    ) -> EfiStatusEnum {
+        (self.start)(
+            self,
+            controller_handle,
+            remaining_device_path.map_or(0 as _, |device_path: &EfiDevicePathProtocolRaw| {
+                device_path.as_ptr() as VoidPtr
+            }),
+        )
+        .into_enum()
+    }
+
+    pub fn stop(&self, controller_handle: EfiHandle, children: &[EfiHandle]) -> EfiStatusEnum {
+        (self.stop)(
+            self,
+            controller_handle,
+            children.len(),
+            if children.is_empty() {
+                0 as _
+            } else {
+                children.as_ptr()
+            },
+        )
+        .into_enum()
+    }
+
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Returns the handle of the image which produced the driver.
+    pub fn image_handle(&self) -> EfiHandle {
+        self.image_handle
+    }
+
+    /// Returns the handle on which the protocol is installed.
+    ///
+    /// Used as the agent handle when opening protocols on behalf of the driver.
+    pub fn driver_binding_handle(&self) -> EfiHandle {
+        self.driver_binding_handle
+    }
+}
+
+impl EfiProtocol for EfiDriverBindingProtocol {
+    type Parsed = &'static Self;
+    type Error = !;
+
+    fn guid() -> EfiGuid {
+        crate::guids::EFI_DRIVER_BINDING_PROTOCOL
+    }
+
+    unsafe fn parse(
+        ptr: NonNullVoidPtr,
+    ) -> Result<<Self as EfiProtocol>::Parsed, <Self as EfiProtocol>::Error> {
+        Ok(&*ptr.cast().as_ptr())
+    }
+}
+
+#[repr(C)]
+struct EfiDriverBinding<T>
+where
+    T: EfiDriver,
+{
+    protocol: EfiDriverBindingProtocol,
+    driver: T,
+}
+
+impl<T> EfiDriverBinding<T>
+where
+    T: EfiDriver,
+{
+    /* The protocol is the first field of the instance */
+    unsafe fn from_protocol<'a>(this: *const EfiDriverBindingProtocol) -> &'a Self {
+        &*(this as *const Self)
+    }
+}
+
+extern "efiapi" fn supported<T>(
+    this: *const EfiDriverBindingProtocol,
+    controller_handle: EfiHandle,
+    remaining_device_path: VoidPtr,
+) -> EfiStatus
+where
+    T: EfiDriver,
+{
+    let instance: &EfiDriverBinding<T> = unsafe { EfiDriverBinding::from_protocol(this) };
+
+    EfiStatus::from(&instance.driver.supported(
+        &instance.protocol,
+        controller_handle,
+        NonNullVoidPtr::new(remaining_device_path as _).map(EfiDevicePathProtocolRaw::new),
+    ))
+}
+
+extern "efiapi" fn start<T>(
+    this: *const EfiDriverBindingProtocol,
+    controller_handle: EfiHandle,
+    remaining_device_path: VoidPtr,
+) -> EfiStatus
+where
+    T: EfiDriver,
+{
+    let instance: &EfiDriverBinding<T> = unsafe { EfiDriverBinding::from_protocol(this) };
+
+    EfiStatus::from(&instance.driver.start(
+        &instance.protocol,
+        controller_handle,
+        NonNullVoidPtr::new(remaining_device_path as _).map(EfiDevicePathProtocolRaw::new),
+    ))
+}
+
+extern "efiapi" fn stop<T>(
+    this: *const EfiDriverBindingProtocol,
+    controller_handle: EfiHandle,
+    children_count: usize,
+    children: *const EfiHandle,
+) -> EfiStatus
+where
+    T: EfiDriver,
+{
+    let instance: &EfiDriverBinding<T> = unsafe { EfiDriverBinding::from_protocol(this) };
+
+    let children: &[EfiHandle] = if children.is_null() || children_count == 0 {
+        &[]
+    } else {
+        unsafe { from_raw_parts(children, children_count) }
+    };
+
+    EfiStatus::from(
+        &instance
+            .driver
+            .stop(&instance.protocol, controller_handle, children),
+    )
+}
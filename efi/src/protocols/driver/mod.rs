@@ -0,0 +1,4 @@
+mod component_name2;
+mod driver_binding;
+
+pub use {component_name2::*, driver_binding::*};
@@ -0,0 +1,652 @@
+use {
+    crate::{
+        boot_services::EfiBootServices1x0,
+        configuration_table::EfiLastAttemptStatus,
+        guid::EfiGuid,
+        protocols::EfiProtocol,
+        status::{EfiStatus, EfiStatusEnum, EfiStatusError},
+        types::{NonNullVoidPtr, Void, VoidPtr},
+        utilities::string_from_raw,
+    },
+    alloc::{vec, vec::Vec},
+    core::{
+        marker::PhantomData,
+        mem::{size_of, zeroed},
+        ptr::{copy_nonoverlapping, NonNull},
+        slice::from_raw_parts,
+        sync::atomic::{AtomicPtr, Ordering},
+    },
+};
+
+/* Called by the firmware without a context, so the active closure is published here */
+static PROGRESS: AtomicPtr<()> = AtomicPtr::new(0 as _);
+
+extern "efiapi" fn progress_trampoline(completion: usize) -> EfiStatus {
+    let progress: *mut &mut dyn FnMut(usize) = PROGRESS.load(Ordering::Acquire).cast();
+
+    if let Some(progress) = unsafe { progress.as_mut() } {
+        progress(completion);
+    }
+
+    EfiStatus::success()
+}
+
+/// Implementation of EFI's `EFI_FIRMWARE_MANAGEMENT_PROTOCOL`.
+#[repr(C)]
+pub struct EfiFirmwareManagementProtocol {
+    get_image_info: extern "efiapi" fn(
+        *const Self,
+        *mut usize,
+        *mut Void,
+        *mut u32,
+        *mut u8,
+        *mut usize,
+        *mut u32,
+        *mut *mut u16,
+    ) -> EfiStatus,
+    get_image: extern "efiapi" fn(*const Self, u8, *mut Void, *mut usize) -> EfiStatus,
+    set_image: extern "efiapi" fn(
+        *const Self,
+        u8,
+        VoidPtr,
+        usize,
+        VoidPtr,
+        Option<extern "efiapi" fn(usize) -> EfiStatus>,
+        *mut *mut u16,
+    ) -> EfiStatus,
+    check_image: extern "efiapi" fn(*const Self, u8, VoidPtr, usize, *mut u32) -> EfiStatus,
+    get_package_info: extern "efiapi" fn(
+        *const Self,
+        *mut u32,
+        *mut *mut u16,
+        *mut u32,
+        *mut u64,
+        *mut u64,
+    ) -> EfiStatus,
+    set_package_info:
+        extern "efiapi" fn(*const Self, VoidPtr, usize, VoidPtr, u32, *const u16) -> EfiStatus,
+}
+
+impl EfiFirmwareManagementProtocol {
+    /// Returns the descriptors of the images managed by the device and the package's version.
+    ///
+    /// The package's version name, allocated by the firmware, is copied and released.
+    pub fn get_image_info(
+        &self,
+        boot_services: &EfiBootServices1x0,
+    ) -> EfiStatusEnum<EfiFirmwareImageInfo> {
+        let mut buffer: Vec<u64> = Vec::new();
+
+        loop {
+            let mut size: usize = buffer.len() * size_of::<u64>();
+            let mut descriptor_version: u32 = 0;
+            let mut descriptor_count: u8 = 0;
+            let mut descriptor_size: usize = 0;
+            let mut package_version: u32 = 0;
+            let mut package_version_name: *mut u16 = 0 as _;
+
+            let status: EfiStatus = (self.get_image_info)(
+                self,
+                &mut size,
+                buffer.as_mut_ptr().cast(),
+                &mut descriptor_version,
+                &mut descriptor_count,
+                &mut descriptor_size,
+                &mut package_version,
+                &mut package_version_name,
+            );
+
+            if status.get_error() == Some(EfiStatusError::EfiBufferTooSmall) {
+                buffer = vec![0; (size + size_of::<u64>() - 1) / size_of::<u64>()];
+
+                continue;
+            }
+
+            return status.into_enum_data(|| EfiFirmwareImageInfo {
+                buffer,
+                descriptor_count,
+                descriptor_size,
+                descriptor_version,
+                package_version,
+                package_version_name: unsafe {
+                    take_pool_string(boot_services, package_version_name).unwrap_or_default()
+                },
+            });
+        }
+    }
+
+    /// Returns a copy of the image with the given index.
+    pub fn get_image(&self, image_index: u8) -> EfiStatusEnum<Vec<u8>> {
+        let mut image: Vec<u8> = Vec::new();
+
+        loop {
+            let mut size: usize = image.len();
+
+            let status: EfiStatus =
+                (self.get_image)(self, image_index, image.as_mut_ptr().cast(), &mut size);
+
+            if status.get_error() == Some(EfiStatusError::EfiBufferTooSmall) {
+                image = vec![0; size];
+
+                continue;
+            }
+
+            image.truncate(size);
+
+            return status.into_enum_data(|| image);
+        }
+    }
+
+    /// Updates the image with the given index.
+    ///
+    /// `progress` is called with the completion percentage, from 1 to 100, while the image is written.
+    /// When the update is aborted, the error data may contain the reason reported by the firmware, which gets copied and released.
+    pub fn set_image(
+        &self,
+        boot_services: &EfiBootServices1x0,
+        image_index: u8,
+        image: &[u8],
+        vendor_code: Option<&[u8]>,
+        progress: Option<&mut dyn FnMut(usize)>,
+    ) -> EfiStatusEnum<(), Option<Vec<u16>>> {
+        let mut abort_reason: *mut u16 = 0 as _;
+
+        let status: EfiStatus = match progress {
+            Some(mut progress) => {
+                let previous: *mut () = PROGRESS.swap(
+                    (&mut progress as *mut &mut dyn FnMut(usize)).cast(),
+                    Ordering::AcqRel,
+                );
+
+                let status: EfiStatus = (self.set_image)(
+                    self,
+                    image_index,
+                    image.as_ptr().cast(),
+                    image.len(),
+                    vendor_code.map_or(0 as _, |vendor_code: &[u8]| vendor_code.as_ptr().cast()),
+                    Some(progress_trampoline),
+                    &mut abort_reason,
+                );
+
+                PROGRESS.store(previous, Ordering::Release);
+
+                status
+            }
+            None => (self.set_image)(
+                self,
+                image_index,
+                image.as_ptr().cast(),
+                image.len(),
+                vendor_code.map_or(0 as _, |vendor_code: &[u8]| vendor_code.as_ptr().cast()),
+                None,
+                &mut abort_reason,
+            ),
+        };
+
+        status.into_enum_data_error(
+            || (),
+            || unsafe { take_pool_string(boot_services, abort_reason) },
+        )
+    }
+
+    /// Checks whether the image can be used to update the image with the given index.
+    pub fn check_image(
+        &self,
+        image_index: u8,
+        image: &[u8],
+    ) -> EfiStatusEnum<EfiFirmwareImageUpdatable> {
+        let mut updatable: u32 = 0;
+
+        (self.check_image)(
+            self,
+            image_index,
+            image.as_ptr().cast(),
+            image.len(),
+            &mut updatable,
+        )
+        .into_enum_data(|| EfiFirmwareImageUpdatable::new(updatable))
+    }
+
+    /// Returns the package's version information.
+    ///
+    /// The package's version name, allocated by the firmware, is copied and released.
+    pub fn get_package_info(
+        &self,
+        boot_services: &EfiBootServices1x0,
+    ) -> EfiStatusEnum<EfiFirmwarePackageInfo> {
+        let mut version: u32 = 0;
+        let mut version_name: *mut u16 = 0 as _;
+        let mut version_name_max_length: u32 = 0;
+        let mut attributes_supported: u64 = 0;
+        let mut attributes_setting: u64 = 0;
+
+        (self.get_package_info)(
+            self,
+            &mut version,
+            &mut version_name,
+            &mut version_name_max_length,
+            &mut attributes_supported,
+            &mut attributes_setting,
+        )
+        .into_enum_data(|| EfiFirmwarePackageInfo {
+            version,
+            version_name: unsafe { take_pool_string(boot_services, version_name) }
+                .unwrap_or_default(),
+            version_name_max_length,
+            attributes_supported: EfiFirmwarePackageAttributes::new(attributes_supported),
+            attributes_setting: EfiFirmwarePackageAttributes::new(attributes_setting),
+        })
+    }
+
+    /// Updates the package's version information.
+    ///
+    /// `version_name` has to be null-terminated; Otherwise [`EfiInvalidParameter`](EfiStatusError::EfiInvalidParameter) is returned.
+    /// `image` is used when the firmware requires authentication, by passing a signed image.
+    pub fn set_package_info(
+        &self,
+        image: Option<&[u8]>,
+        vendor_code: Option<&[u8]>,
+        version: u32,
+        version_name: &[u16],
+    ) -> EfiStatusEnum {
+        if version_name.last() != Some(&0) {
+            return EfiStatusEnum::Error(EfiStatusError::EfiInvalidParameter, ());
+        }
+
+        (self.set_package_info)(
+            self,
+            image.map_or(0 as _, |image: &[u8]| image.as_ptr().cast()),
+            image.map_or(0, <[u8]>::len),
+            vendor_code.map_or(0 as _, |vendor_code: &[u8]| vendor_code.as_ptr().cast()),
+            version,
+            version_name.as_ptr(),
+        )
+        .into_enum()
+    }
+}
+
+impl EfiProtocol for EfiFirmwareManagementProtocol {
+    type Parsed = &'static Self;
+    type Error = !;
+
+    fn guid() -> EfiGuid {
+        crate::guids::EFI_FIRMWARE_MANAGEMENT_PROTOCOL
+    }
+
+    unsafe fn parse(
+        ptr: NonNullVoidPtr,
+    ) -> Result<<Self as EfiProtocol>::Parsed, <Self as EfiProtocol>::Error> {
+        Ok(&*ptr.cast().as_ptr())
+    }
+}
+
+/// Copies a string allocated by the firmware and releases it.
+unsafe fn take_pool_string(
+    boot_services: &EfiBootServices1x0,
+    string: *mut u16,
+) -> Option<Vec<u16>> {
+    let string: NonNull<u16> = NonNull::new(string)?;
+
+    let copy: Option<Vec<u16>> = string_from_raw(string.as_ptr()).ok().map(<[u16]>::to_vec);
+
+    let _ = boot_services.free_pool(string.as_ptr() as VoidPtr);
+
+    copy
+}
+
+/// Descriptors returned by [`get_image_info`](EfiFirmwareManagementProtocol::get_image_info).
+pub struct EfiFirmwareImageInfo {
+    /* Kept as "u64"s for the descriptors' alignment */
+    buffer: Vec<u64>,
+    descriptor_count: u8,
+    descriptor_size: usize,
+    descriptor_version: u32,
+    package_version: u32,
+    package_version_name: Vec<u16>,
+}
+
+impl EfiFirmwareImageInfo {
+    pub fn descriptor_version(&self) -> u32 {
+        self.descriptor_version
+    }
+
+    pub fn descriptors(&self) -> impl Iterator<Item = EfiFirmwareImageDescriptor<'_>> + '_ {
+        let base: *const u8 = self.buffer.as_ptr().cast();
+        let length: usize = self.buffer.len() * size_of::<u64>();
+
+        (0..usize::from(self.descriptor_count))
+            .take_while(move |index: &usize| (index + 1) * self.descriptor_size <= length)
+            .map(move |index: usize| unsafe {
+                EfiFirmwareImageDescriptor::read(
+                    base.add(index * self.descriptor_size),
+                    self.descriptor_size,
+                    self.descriptor_version,
+                )
+            })
+    }
+
+    /// Version of the package, or `0xFFFFFFFE` when versions are tracked per image.
+    pub fn package_version(&self) -> u32 {
+        self.package_version
+    }
+
+    /// Version name of the package, without the null-terminator.
+    pub fn package_version_name(&self) -> &[u16] {
+        &self.package_version_name
+    }
+}
+
+#[repr(C)]
+struct EfiFirmwareImageDescriptorRaw {
+    image_index: u8,
+    image_type_id: EfiGuid,
+    image_id: u64,
+    image_id_name: *const u16,
+    version: u32,
+    version_name: *const u16,
+    size: usize,
+    attributes_supported: u64,
+    attributes_setting: u64,
+    compatibilities: u64,
+    /* Version 2 */
+    lowest_supported_image_version: u32,
+    /* Version 3 */
+    last_attempt_version: u32,
+    last_attempt_status: u32,
+    hardware_instance: u64,
+    /* Version 4 */
+    dependencies: *const u8,
+}
+
+/// Implementation of EFI's `EFI_FIRMWARE_IMAGE_DESCRIPTOR`.
+///
+/// Fields introduced by later versions of the descriptor return `None` for older ones.
+pub struct EfiFirmwareImageDescriptor<'a> {
+    raw: EfiFirmwareImageDescriptorRaw,
+    version: u32,
+    _info: PhantomData<&'a EfiFirmwareImageInfo>,
+}
+
+impl<'a> EfiFirmwareImageDescriptor<'a> {
+    /// Copies the descriptor, leaving the fields beyond `size` zeroed.
+    unsafe fn read(descriptor: *const u8, size: usize, version: u32) -> Self {
+        let mut raw: EfiFirmwareImageDescriptorRaw = zeroed();
+
+        copy_nonoverlapping(
+            descriptor,
+            (&mut raw as *mut EfiFirmwareImageDescriptorRaw).cast(),
+            size.min(size_of::<EfiFirmwareImageDescriptorRaw>()),
+        );
+
+        Self {
+            raw,
+            version,
+            _info: PhantomData,
+        }
+    }
+
+    /// Index used by the protocol's functions, starting from 1.
+    pub fn image_index(&self) -> u8 {
+        self.raw.image_index
+    }
+
+    pub fn image_type_id(&self) -> EfiGuid {
+        self.raw.image_type_id
+    }
+
+    pub fn image_id(&self) -> u64 {
+        self.raw.image_id
+    }
+
+    pub fn image_id_name(&self) -> Option<&'a [u16]> {
+        unsafe { string_from_raw(self.raw.image_id_name).ok() }
+    }
+
+    pub fn version(&self) -> u32 {
+        self.raw.version
+    }
+
+    pub fn version_name(&self) -> Option<&'a [u16]> {
+        unsafe { string_from_raw(self.raw.version_name).ok() }
+    }
+
+    pub fn size(&self) -> usize {
+        self.raw.size
+    }
+
+    pub fn attributes_supported(&self) -> EfiFirmwareImageAttributes {
+        EfiFirmwareImageAttributes::new(self.raw.attributes_supported)
+    }
+
+    pub fn attributes_setting(&self) -> EfiFirmwareImageAttributes {
+        EfiFirmwareImageAttributes::new(self.raw.attributes_setting)
+    }
+
+    pub fn compatibilities(&self) -> EfiFirmwareImageCompatibilities {
+        EfiFirmwareImageCompatibilities::new(self.raw.compatibilities)
+    }
+
+    pub fn lowest_supported_image_version(&self) -> Option<u32> {
+        if self.version >= 2 {
+            Some(self.raw.lowest_supported_image_version)
+        } else {
+            None
+        }
+    }
+
+    pub fn last_attempt_version(&self) -> Option<u32> {
+        if self.version >= 3 {
+            Some(self.raw.last_attempt_version)
+        } else {
+            None
+        }
+    }
+
+    pub fn last_attempt_status(&self) -> Option<EfiLastAttemptStatus> {
+        if self.version >= 3 {
+            Some(EfiLastAttemptStatus::from_raw(self.raw.last_attempt_status))
+        } else {
+            None
+        }
+    }
+
+    pub fn hardware_instance(&self) -> Option<u64> {
+        if self.version >= 3 {
+            Some(self.raw.hardware_instance)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the dependency expression, including it's `END` opcode.
+    ///
+    /// Only images with the [`DEPENDENCY`](EfiFirmwareImageAttributes::DEPENDENCY) attribute set carry one.
+    pub fn dependencies(&self) -> Option<&'a [u8]> {
+        if self.version < 4
+            || self.raw.dependencies.is_null()
+            || !self
+                .attributes_setting()
+                .contains(EfiFirmwareImageAttributes::DEPENDENCY)
+        {
+            return None;
+        }
+
+        unsafe {
+            let length: usize = dependencies_length(self.raw.dependencies)?;
+
+            Some(from_raw_parts(self.raw.dependencies, length))
+        }
+    }
+}
+
+/* Opcodes of the dependency expressions */
+const DEPENDENCY_PUSH_GUID: u8 = 0x00;
+const DEPENDENCY_PUSH_VERSION: u8 = 0x01;
+const DEPENDENCY_DECLARE_VERSION_NAME: u8 = 0x02;
+const DEPENDENCY_END: u8 = 0x0D;
+const DEPENDENCY_DECLARE_LENGTH: u8 = 0x0E;
+
+/// Walks the dependency expression until it's `END` opcode and returns it's length.
+unsafe fn dependencies_length(expression: *const u8) -> Option<usize> {
+    let mut offset: usize = 0;
+
+    loop {
+        let opcode: u8 = *expression.add(offset);
+
+        offset += 1;
+
+        match opcode {
+            DEPENDENCY_PUSH_GUID => offset += size_of::<EfiGuid>(),
+            DEPENDENCY_PUSH_VERSION | DEPENDENCY_DECLARE_LENGTH => offset += size_of::<u32>(),
+            DEPENDENCY_DECLARE_VERSION_NAME => {
+                /* Null-terminated UTF-16 string, which isn't aligned */
+                while expression.add(offset).cast::<u16>().read_unaligned() != 0 {
+                    offset += size_of::<u16>();
+                }
+
+                offset += size_of::<u16>();
+            }
+            DEPENDENCY_END => return Some(offset),
+            0x03..=0x0C => (),
+            _ => return None,
+        }
+    }
+}
+
+/// Attributes of images, as found in [`EfiFirmwareImageDescriptor`]s.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EfiFirmwareImageAttributes {
+    attributes: u64,
+}
+
+impl EfiFirmwareImageAttributes {
+    pub const IMAGE_UPDATABLE: u64 = 0x0001;
+    pub const RESET_REQUIRED: u64 = 0x0002;
+    pub const AUTHENTICATION_REQUIRED: u64 = 0x0004;
+    pub const IN_USE: u64 = 0x0008;
+    pub const UEFI_IMAGE: u64 = 0x0010;
+    pub const DEPENDENCY: u64 = 0x0020;
+
+    pub const fn new(attributes: u64) -> Self {
+        Self { attributes }
+    }
+
+    pub const fn bits(&self) -> u64 {
+        self.attributes
+    }
+
+    pub const fn contains(&self, attributes: u64) -> bool {
+        self.attributes & attributes == attributes
+    }
+}
+
+/// Compatibility checks supported by images, as found in [`EfiFirmwareImageDescriptor`]s.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EfiFirmwareImageCompatibilities {
+    compatibilities: u64,
+}
+
+impl EfiFirmwareImageCompatibilities {
+    pub const CHECK_SUPPORTED: u64 = 0x0001;
+
+    pub const fn new(compatibilities: u64) -> Self {
+        Self { compatibilities }
+    }
+
+    pub const fn bits(&self) -> u64 {
+        self.compatibilities
+    }
+
+    pub const fn contains(&self, compatibilities: u64) -> bool {
+        self.compatibilities & compatibilities == compatibilities
+    }
+}
+
+/// Result of [`check_image`](EfiFirmwareManagementProtocol::check_image).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EfiFirmwareImageUpdatable {
+    updatable: u32,
+}
+
+impl EfiFirmwareImageUpdatable {
+    pub const VALID: u32 = 0x0001;
+    pub const INVALID: u32 = 0x0002;
+    pub const INVALID_TYPE: u32 = 0x0004;
+    pub const INVALID_OLD: u32 = 0x0008;
+    pub const VALID_WITH_VENDOR_CODE: u32 = 0x0010;
+
+    pub const fn new(updatable: u32) -> Self {
+        Self { updatable }
+    }
+
+    pub const fn bits(&self) -> u32 {
+        self.updatable
+    }
+
+    pub const fn contains(&self, updatable: u32) -> bool {
+        self.updatable & updatable == updatable
+    }
+
+    pub const fn is_valid(&self) -> bool {
+        self.updatable & (Self::VALID | Self::VALID_WITH_VENDOR_CODE) != 0
+    }
+}
+
+/// Attributes of firmware packages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EfiFirmwarePackageAttributes {
+    attributes: u64,
+}
+
+impl EfiFirmwarePackageAttributes {
+    pub const VERSION_UPDATABLE: u64 = 0x0001;
+    pub const RESET_REQUIRED: u64 = 0x0002;
+    pub const AUTHENTICATION_REQUIRED: u64 = 0x0004;
+
+    pub const fn new(attributes: u64) -> Self {
+        Self { attributes }
+    }
+
+    pub const fn bits(&self) -> u64 {
+        self.attributes
+    }
+
+    pub const fn contains(&self, attributes: u64) -> bool {
+        self.attributes & attributes == attributes
+    }
+}
+
+/// Returned by [`get_package_info`](EfiFirmwareManagementProtocol::get_package_info).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EfiFirmwarePackageInfo {
+    version: u32,
+    version_name: Vec<u16>,
+    version_name_max_length: u32,
+    attributes_supported: EfiFirmwarePackageAttributes,
+    attributes_setting: EfiFirmwarePackageAttributes,
+}
+
+impl EfiFirmwarePackageInfo {
+    /// Version of the package, or `0xFFFFFFFE` when versions are tracked per image.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Version name of the package, without the null-terminator.
+    pub fn version_name(&self) -> &[u16] {
+        &self.version_name
+    }
+
+    /// Maximum number of characters of the version name, including the null-terminator.
+    pub fn version_name_max_length(&self) -> u32 {
+        self.version_name_max_length
+    }
+
+    pub fn attributes_supported(&self) -> EfiFirmwarePackageAttributes {
+        self.attributes_supported
+    }
+
+    pub fn attributes_setting(&self) -> EfiFirmwarePackageAttributes {
+        self.attributes_setting
+    }
+}
@@ -0,0 +1,47 @@
+use {
+    alloc::{vec, vec::Vec},
+    core::ops::{Deref, DerefMut},
+};
+
+/// Heap buffer aligned to a device's `IoAlign` requirement, for pass-through transfers.
+pub struct EfiAlignedBuffer {
+    data: Vec<u8>,
+    offset: usize,
+    length: usize,
+}
+
+impl EfiAlignedBuffer {
+    /// Allocates a zeroed buffer. An alignment of zero or one means no requirement, as in the protocols' modes.
+    ///
+    /// Returns `None` if the alignment isn't a power of two.
+    pub fn new(length: usize, alignment: u32) -> Option<Self> {
+        let alignment: usize = (alignment as usize).max(1);
+
+        if !alignment.is_power_of_two() {
+            return None;
+        }
+
+        let data: Vec<u8> = vec![0; length + alignment - 1];
+        let offset: usize = data.as_ptr().align_offset(alignment);
+
+        Some(Self {
+            data,
+            offset,
+            length,
+        })
+    }
+}
+
+impl Deref for EfiAlignedBuffer {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        &self.data[self.offset..self.offset + self.length]
+    }
+}
+
+impl DerefMut for EfiAlignedBuffer {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.data[self.offset..self.offset + self.length]
+    }
+}
@@ -92,7 +92,7 @@ impl EfiProtocol for EfiBlockIOProtocol {
 }
 
 #[repr(C)]
-struct EfiBlockIOMediaRaw {
+pub(super) struct EfiBlockIOMediaRaw {
     media_id: u32,
     removable_media: bool,
     media_present: bool,
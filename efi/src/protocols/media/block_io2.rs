@@ -0,0 +1,145 @@
+use {
+    super::{
+        block_io::EfiBlockIOMediaRaw,
+        io2_future::{EfiIO2Future, EfiIO2Operation, EfiIO2Token},
+        EfiBlockIOMediaRevision3,
+    },
+    crate::{
+        boot_services::EfiBootServices1x0,
+        guid::EfiGuid,
+        protocols::EfiProtocol,
+        status::{EfiStatus, EfiStatusEnum},
+        types::{EfiLBA, NonNullVoidPtr, VoidMutPtr, VoidPtr},
+    },
+};
+
+/// Implementation of EFI's `EFI_BLOCK_IO2_PROTOCOL`.
+#[repr(C)]
+pub struct EfiBlockIO2Protocol {
+    media: *const EfiBlockIOMediaRaw,
+    reset: extern "efiapi" fn(*const Self, bool) -> EfiStatus,
+    read_blocks_ex: extern "efiapi" fn(
+        *const Self,
+        u32,
+        EfiLBA,
+        *mut EfiIO2Token,
+        usize,
+        VoidMutPtr,
+    ) -> EfiStatus,
+    write_blocks_ex:
+        extern "efiapi" fn(*const Self, u32, EfiLBA, *mut EfiIO2Token, usize, VoidPtr) -> EfiStatus,
+    flush_blocks_ex: extern "efiapi" fn(*const Self, *mut EfiIO2Token) -> EfiStatus,
+}
+
+impl EfiBlockIO2Protocol {
+    /// Returns the media information. The protocol was introduced alongside the third media revision.
+    pub fn media(&self) -> &dyn EfiBlockIOMediaRevision3 {
+        unsafe { &*self.media }
+    }
+
+    /// Resets the device. Outstanding requests are aborted.
+    pub fn reset(&self, extended_verification: bool) -> EfiStatusEnum {
+        (self.reset)(self, extended_verification).into_enum()
+    }
+
+    /// Queues a read request.
+    /// # Safety
+    /// The token and buffer must not be moved or dropped until the token's event is signalled.
+    pub unsafe fn read_blocks_ex(
+        &self,
+        media_id: u32,
+        lba: EfiLBA,
+        token: &mut EfiIO2Token,
+        buffer: &mut [u8],
+    ) -> EfiStatusEnum {
+        (self.read_blocks_ex)(
+            self,
+            media_id,
+            lba,
+            token,
+            buffer.len(),
+            buffer.as_mut_ptr() as VoidMutPtr,
+        )
+        .into_enum()
+    }
+
+    /// Queues a write request.
+    /// # Safety
+    /// The token and buffer must not be moved or dropped until the token's event is signalled.
+    pub unsafe fn write_blocks_ex(
+        &self,
+        media_id: u32,
+        lba: EfiLBA,
+        token: &mut EfiIO2Token,
+        buffer: &[u8],
+    ) -> EfiStatusEnum {
+        (self.write_blocks_ex)(
+            self,
+            media_id,
+            lba,
+            token,
+            buffer.len(),
+            buffer.as_ptr() as VoidPtr,
+        )
+        .into_enum()
+    }
+
+    /// Queues a flush request.
+    /// # Safety
+    /// The token must not be moved or dropped until it's event is signalled.
+    pub unsafe fn flush_blocks_ex(&self, token: &mut EfiIO2Token) -> EfiStatusEnum {
+        (self.flush_blocks_ex)(self, token).into_enum()
+    }
+
+    /// Returns a future reading blocks into the buffer.
+    /// # Safety
+    /// The future must not be leaked (e.g.: with `mem::forget`) before it completes, as the firmware accesses the buffer until then.
+    pub unsafe fn read_blocks<'a>(
+        &'a self,
+        boot_services: &'a EfiBootServices1x0,
+        media_id: u32,
+        lba: EfiLBA,
+        buffer: &'a mut [u8],
+    ) -> EfiIO2Future<'a> {
+        EfiIO2Future::new(
+            boot_services,
+            EfiIO2Operation::ReadBlocks(self, media_id, lba, buffer),
+        )
+    }
+
+    /// Returns a future writing the buffer's blocks.
+    /// # Safety
+    /// The future must not be leaked (e.g.: with `mem::forget`) before it completes, as the firmware accesses the buffer until then.
+    pub unsafe fn write_blocks<'a>(
+        &'a self,
+        boot_services: &'a EfiBootServices1x0,
+        media_id: u32,
+        lba: EfiLBA,
+        buffer: &'a [u8],
+    ) -> EfiIO2Future<'a> {
+        EfiIO2Future::new(
+            boot_services,
+            EfiIO2Operation::WriteBlocks(self, media_id, lba, buffer),
+        )
+    }
+
+    /// Returns a future flushing the device's cached writes.
+    pub fn flush_blocks<'a>(&'a self, boot_services: &'a EfiBootServices1x0) -> EfiIO2Future<'a> {
+        EfiIO2Future::new(boot_services, EfiIO2Operation::FlushBlocks(self))
+    }
+}
+
+impl EfiProtocol for EfiBlockIO2Protocol {
+    type Parsed = &'static Self;
+    type Error = !;
+
+    fn guid() -> EfiGuid {
+        crate::guids::EFI_BLOCK_IO2_PROTOCOL
+    }
+
+    unsafe fn parse(
+        ptr: NonNullVoidPtr,
+    ) -> Result<<Self as EfiProtocol>::Parsed, <Self as EfiProtocol>::Error> {
+        Ok(&*ptr.cast().as_ptr())
+    }
+}
@@ -0,0 +1,134 @@
+use {
+    super::io2_future::{EfiIO2Future, EfiIO2Operation, EfiIO2Token},
+    crate::{
+        boot_services::EfiBootServices1x0,
+        guid::EfiGuid,
+        protocols::EfiProtocol,
+        status::{EfiStatus, EfiStatusEnum},
+        types::{NonNullVoidPtr, VoidMutPtr, VoidPtr},
+    },
+};
+
+/// Implementation of EFI's `EFI_DISK_IO2_PROTOCOL`.
+#[repr(C)]
+pub struct EfiDiskIO2Protocol {
+    revision: u64,
+    cancel: extern "efiapi" fn(*const Self) -> EfiStatus,
+    read_disk_ex:
+        extern "efiapi" fn(*const Self, u32, u64, *mut EfiIO2Token, usize, VoidMutPtr) -> EfiStatus,
+    write_disk_ex:
+        extern "efiapi" fn(*const Self, u32, u64, *mut EfiIO2Token, usize, VoidPtr) -> EfiStatus,
+    flush_disk_ex: extern "efiapi" fn(*const Self, *mut EfiIO2Token) -> EfiStatus,
+}
+
+impl EfiDiskIO2Protocol {
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Aborts all outstanding requests. Their tokens' events are signalled with `EFI_ABORTED`.
+    pub fn cancel(&self) -> EfiStatusEnum {
+        (self.cancel)(self).into_enum()
+    }
+
+    /// Queues a read request.
+    /// # Safety
+    /// The token and buffer must not be moved or dropped until the token's event is signalled.
+    pub unsafe fn read_disk_ex(
+        &self,
+        media_id: u32,
+        offset: u64,
+        token: &mut EfiIO2Token,
+        buffer: &mut [u8],
+    ) -> EfiStatusEnum {
+        (self.read_disk_ex)(
+            self,
+            media_id,
+            offset,
+            token,
+            buffer.len(),
+            buffer.as_mut_ptr() as VoidMutPtr,
+        )
+        .into_enum()
+    }
+
+    /// Queues a write request.
+    /// # Safety
+    /// The token and buffer must not be moved or dropped until the token's event is signalled.
+    pub unsafe fn write_disk_ex(
+        &self,
+        media_id: u32,
+        offset: u64,
+        token: &mut EfiIO2Token,
+        buffer: &[u8],
+    ) -> EfiStatusEnum {
+        (self.write_disk_ex)(
+            self,
+            media_id,
+            offset,
+            token,
+            buffer.len(),
+            buffer.as_ptr() as VoidPtr,
+        )
+        .into_enum()
+    }
+
+    /// Queues a flush request.
+    /// # Safety
+    /// The token must not be moved or dropped until it's event is signalled.
+    pub unsafe fn flush_disk_ex(&self, token: &mut EfiIO2Token) -> EfiStatusEnum {
+        (self.flush_disk_ex)(self, token).into_enum()
+    }
+
+    /// Returns a future reading from the given byte offset into the buffer.
+    /// # Safety
+    /// The future must not be leaked (e.g.: with `mem::forget`) before it completes, as the firmware accesses the buffer until then.
+    pub unsafe fn read_disk<'a>(
+        &'a self,
+        boot_services: &'a EfiBootServices1x0,
+        media_id: u32,
+        offset: u64,
+        buffer: &'a mut [u8],
+    ) -> EfiIO2Future<'a> {
+        EfiIO2Future::new(
+            boot_services,
+            EfiIO2Operation::ReadDisk(self, media_id, offset, buffer),
+        )
+    }
+
+    /// Returns a future writing the buffer at the given byte offset.
+    /// # Safety
+    /// The future must not be leaked (e.g.: with `mem::forget`) before it completes, as the firmware accesses the buffer until then.
+    pub unsafe fn write_disk<'a>(
+        &'a self,
+        boot_services: &'a EfiBootServices1x0,
+        media_id: u32,
+        offset: u64,
+        buffer: &'a [u8],
+    ) -> EfiIO2Future<'a> {
+        EfiIO2Future::new(
+            boot_services,
+            EfiIO2Operation::WriteDisk(self, media_id, offset, buffer),
+        )
+    }
+
+    /// Returns a future flushing the device's cached writes.
+    pub fn flush_disk<'a>(&'a self, boot_services: &'a EfiBootServices1x0) -> EfiIO2Future<'a> {
+        EfiIO2Future::new(boot_services, EfiIO2Operation::FlushDisk(self))
+    }
+}
+
+impl EfiProtocol for EfiDiskIO2Protocol {
+    type Parsed = &'static Self;
+    type Error = !;
+
+    fn guid() -> EfiGuid {
+        crate::guids::EFI_DISK_IO2_PROTOCOL
+    }
+
+    unsafe fn parse(
+        ptr: NonNullVoidPtr,
+    ) -> Result<<Self as EfiProtocol>::Parsed, <Self as EfiProtocol>::Error> {
+        Ok(&*ptr.cast().as_ptr())
+    }
+}
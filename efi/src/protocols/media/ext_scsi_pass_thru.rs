@@ -0,0 +1,577 @@
+use {
+    crate::{
+        guid::EfiGuid,
+        protocols::{device_path::EfiDevicePathProtocolRaw, EfiProtocol},
+        status::{EfiStatus, EfiStatusEnum, EfiStatusError},
+        types::{EfiEvent, NonNullVoidPtr, Void, VoidMutPtr},
+    },
+    core::{convert::TryInto, iter::FusedIterator, ptr::NonNull, str},
+};
+
+/// Size of SCSI target identifiers.
+pub const EFI_SCSI_TARGET_MAX_BYTES: usize = 0x10;
+
+/// SCSI target identifier. A target filled with `0xFF` starts enumerations.
+pub type EfiScsiTarget = [u8; EFI_SCSI_TARGET_MAX_BYTES];
+
+/// Implementation of EFI's `EFI_EXT_SCSI_PASS_THRU_PROTOCOL`.
+///
+/// Data and sense buffers have to be aligned to the mode's [`io_align`](EfiExtScsiPassThruMode::io_align), e.g. by using [`EfiAlignedBuffer`](super::EfiAlignedBuffer).
+#[repr(C)]
+pub struct EfiExtScsiPassThruProtocol {
+    mode: *const EfiExtScsiPassThruMode,
+    pass_thru: extern "efiapi" fn(
+        *const Self,
+        *const u8,
+        u64,
+        *mut EfiExtScsiPassThruRequestPacket,
+        EfiEvent,
+    ) -> EfiStatus,
+    get_next_target_lun: extern "efiapi" fn(*const Self, *mut *mut u8, *mut u64) -> EfiStatus,
+    build_device_path: extern "efiapi" fn(*const Self, *const u8, u64, *mut *mut Void) -> EfiStatus,
+    get_target_lun:
+        extern "efiapi" fn(*const Self, *const Void, *mut *mut u8, *mut u64) -> EfiStatus,
+    reset_channel: extern "efiapi" fn(*const Self) -> EfiStatus,
+    reset_target_lun: extern "efiapi" fn(*const Self, *const u8, u64) -> EfiStatus,
+    get_next_target: extern "efiapi" fn(*const Self, *mut *mut u8) -> EfiStatus,
+}
+
+impl EfiExtScsiPassThruProtocol {
+    pub fn mode(&self) -> &EfiExtScsiPassThruMode {
+        unsafe { &*self.mode }
+    }
+
+    /// Sends the command to the logical unit and waits for it's completion.
+    ///
+    /// The timeout is in units of 100 ns, zero waiting indefinitely.
+    /// When the command fails, the completion describing the failure is returned alongside the error.
+    pub fn pass_thru(
+        &self,
+        target: &EfiScsiTarget,
+        lun: u64,
+        cdb: &EfiScsiCdb,
+        data: EfiScsiData<'_>,
+        sense_data: Option<&mut [u8]>,
+        timeout: u64,
+    ) -> EfiStatusEnum<EfiScsiCompletion, EfiScsiCompletion> {
+        let mut cdb: EfiScsiCdb = *cdb;
+
+        let (in_data, in_length, out_data, out_length, direction): (
+            VoidMutPtr,
+            usize,
+            VoidMutPtr,
+            usize,
+            u8,
+        ) = match data {
+            EfiScsiData::None => (0 as _, 0, 0 as _, 0, 0),
+            EfiScsiData::Read(buffer) => (buffer.as_mut_ptr() as _, buffer.len(), 0 as _, 0, 0),
+            EfiScsiData::Write(buffer) => (0 as _, 0, buffer.as_ptr() as _, buffer.len(), 1),
+            EfiScsiData::Bidirectional(read, write) => (
+                read.as_mut_ptr() as _,
+                read.len(),
+                write.as_ptr() as _,
+                write.len(),
+                2,
+            ),
+        };
+
+        let (sense_data, sense_data_length): (VoidMutPtr, usize) = match sense_data {
+            Some(buffer) => (buffer.as_mut_ptr() as _, buffer.len()),
+            None => (0 as _, 0),
+        };
+
+        let mut packet: EfiExtScsiPassThruRequestPacket = EfiExtScsiPassThruRequestPacket {
+            timeout,
+            in_data_buffer: in_data,
+            out_data_buffer: out_data,
+            sense_data,
+            cdb: cdb.bytes.as_mut_ptr() as VoidMutPtr,
+            in_transfer_length: in_length as u32,
+            out_transfer_length: out_length as u32,
+            cdb_length: cdb.length,
+            data_direction: direction,
+            host_adapter_status: 0,
+            target_status: 0,
+            /* At most 255 bytes of sense data are returned */
+            sense_data_length: sense_data_length.min(0xFF) as u8,
+        };
+
+        let status: EfiStatus = (self.pass_thru)(self, target.as_ptr(), lun, &mut packet, 0 as _);
+
+        let completion: EfiScsiCompletion = EfiScsiCompletion {
+            in_transfer_length: packet.in_transfer_length,
+            out_transfer_length: packet.out_transfer_length,
+            host_adapter_status: packet.host_adapter_status,
+            target_status: packet.target_status,
+            sense_data_length: packet.sense_data_length,
+        };
+
+        status.into_enum_data_error(|| completion, || completion)
+    }
+
+    /// Returns the target and LUN following the given ones, or [`EfiNotFound`](EfiStatusError::EfiNotFound) after the last one.
+    pub fn get_next_target_lun(
+        &self,
+        target: &EfiScsiTarget,
+        lun: u64,
+    ) -> EfiStatusEnum<(EfiScsiTarget, u64)> {
+        let mut target: EfiScsiTarget = *target;
+        let mut target_ptr: *mut u8 = target.as_mut_ptr();
+        let mut lun: u64 = lun;
+
+        (self.get_next_target_lun)(self, &mut target_ptr, &mut lun).into_enum_data(|| (target, lun))
+    }
+
+    /// Returns iterator over the logical units of every target on the channel.
+    pub fn target_luns(&self) -> EfiScsiTargetLuns<'_> {
+        EfiScsiTargetLuns {
+            protocol: self,
+            current: Some(([0xFF; EFI_SCSI_TARGET_MAX_BYTES], 0)),
+        }
+    }
+
+    /// Returns the target following the given one, or [`EfiNotFound`](EfiStatusError::EfiNotFound) after the last one.
+    pub fn get_next_target(&self, target: &EfiScsiTarget) -> EfiStatusEnum<EfiScsiTarget> {
+        let mut target: EfiScsiTarget = *target;
+        let mut target_ptr: *mut u8 = target.as_mut_ptr();
+
+        (self.get_next_target)(self, &mut target_ptr).into_enum_data(|| target)
+    }
+
+    /// Returns iterator over the targets on the channel.
+    pub fn targets(&self) -> EfiScsiTargets<'_> {
+        EfiScsiTargets {
+            protocol: self,
+            current: Some([0xFF; EFI_SCSI_TARGET_MAX_BYTES]),
+        }
+    }
+
+    /// Builds the device path node of the logical unit.
+    ///
+    /// The node is allocated from pool memory and has to be released with `free_pool`.
+    pub fn build_device_path(
+        &self,
+        target: &EfiScsiTarget,
+        lun: u64,
+    ) -> EfiStatusEnum<EfiDevicePathProtocolRaw> {
+        let mut device_path: *mut Void = 0 as _;
+
+        match (self.build_device_path)(self, target.as_ptr(), lun, &mut device_path).into_enum() {
+            EfiStatusEnum::Success(()) | EfiStatusEnum::Warning(_, ()) if device_path.is_null() => {
+                EfiStatusEnum::Error(EfiStatusError::EfiOutOfResources, ())
+            }
+            status => status.map(|()| {
+                EfiDevicePathProtocolRaw::new(unsafe { NonNull::new_unchecked(device_path) })
+            }),
+        }
+    }
+
+    /// Returns the target and LUN the device path node describes.
+    pub fn get_target_lun(
+        &self,
+        device_path: &EfiDevicePathProtocolRaw,
+    ) -> EfiStatusEnum<(EfiScsiTarget, u64)> {
+        let mut target: EfiScsiTarget = [0; EFI_SCSI_TARGET_MAX_BYTES];
+        let mut target_ptr: *mut u8 = target.as_mut_ptr();
+        let mut lun: u64 = 0;
+
+        (self.get_target_lun)(self, device_path.as_ptr(), &mut target_ptr, &mut lun)
+            .into_enum_data(|| (target, lun))
+    }
+
+    pub fn reset_channel(&self) -> EfiStatusEnum {
+        (self.reset_channel)(self).into_enum()
+    }
+
+    pub fn reset_target_lun(&self, target: &EfiScsiTarget, lun: u64) -> EfiStatusEnum {
+        (self.reset_target_lun)(self, target.as_ptr(), lun).into_enum()
+    }
+}
+
+impl EfiProtocol for EfiExtScsiPassThruProtocol {
+    type Parsed = &'static Self;
+    type Error = !;
+
+    fn guid() -> EfiGuid {
+        crate::guids::EFI_EXT_SCSI_PASS_THRU_PROTOCOL
+    }
+
+    unsafe fn parse(
+        ptr: NonNullVoidPtr,
+    ) -> Result<<Self as EfiProtocol>::Parsed, <Self as EfiProtocol>::Error> {
+        Ok(&*ptr.cast().as_ptr())
+    }
+}
+
+/// Iterator over the targets of a SCSI channel.
+pub struct EfiScsiTargets<'a> {
+    protocol: &'a EfiExtScsiPassThruProtocol,
+    current: Option<EfiScsiTarget>,
+}
+
+impl Iterator for EfiScsiTargets<'_> {
+    type Item = EfiScsiTarget;
+
+    fn next(&mut self) -> Option<<Self as Iterator>::Item> {
+        self.current = match self.protocol.get_next_target(self.current.as_ref()?) {
+            EfiStatusEnum::Success(target) | EfiStatusEnum::Warning(_, target) => Some(target),
+            EfiStatusEnum::Error(..) => None,
+        };
+
+        self.current
+    }
+}
+
+impl FusedIterator for EfiScsiTargets<'_> {}
+
+/// Iterator over the logical units of a SCSI channel, as target and LUN pairs.
+pub struct EfiScsiTargetLuns<'a> {
+    protocol: &'a EfiExtScsiPassThruProtocol,
+    current: Option<(EfiScsiTarget, u64)>,
+}
+
+impl Iterator for EfiScsiTargetLuns<'_> {
+    type Item = (EfiScsiTarget, u64);
+
+    fn next(&mut self) -> Option<<Self as Iterator>::Item> {
+        let (target, lun): &(EfiScsiTarget, u64) = self.current.as_ref()?;
+
+        self.current = match self.protocol.get_next_target_lun(target, *lun) {
+            EfiStatusEnum::Success(next) | EfiStatusEnum::Warning(_, next) => Some(next),
+            EfiStatusEnum::Error(..) => None,
+        };
+
+        self.current
+    }
+}
+
+impl FusedIterator for EfiScsiTargetLuns<'_> {}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EfiExtScsiPassThruMode {
+    adapter_id: u32,
+    attributes: u32,
+    io_align: u32,
+}
+
+impl EfiExtScsiPassThruMode {
+    /// The interface addresses physical devices on the channel.
+    pub const PHYSICAL: u32 = 0x01;
+    /// The interface addresses logical devices, e.g. of a RAID volume.
+    pub const LOGICAL: u32 = 0x02;
+    pub const NONBLOCKIO: u32 = 0x04;
+
+    /// Returns the target ID of the host adapter on the channel.
+    pub fn adapter_id(&self) -> u32 {
+        self.adapter_id
+    }
+
+    pub fn attributes(&self) -> u32 {
+        self.attributes
+    }
+
+    pub fn contains(&self, attributes: u32) -> bool {
+        self.attributes & attributes == attributes
+    }
+
+    /// Returns the required alignment of data buffers, where zero and one mean there's no requirement.
+    pub fn io_align(&self) -> u32 {
+        self.io_align
+    }
+}
+
+#[repr(C)]
+struct EfiExtScsiPassThruRequestPacket {
+    timeout: u64,
+    in_data_buffer: VoidMutPtr,
+    out_data_buffer: VoidMutPtr,
+    sense_data: VoidMutPtr,
+    cdb: VoidMutPtr,
+    in_transfer_length: u32,
+    out_transfer_length: u32,
+    cdb_length: u8,
+    data_direction: u8,
+    host_adapter_status: u8,
+    target_status: u8,
+    sense_data_length: u8,
+}
+
+/// Data transferred by a SCSI command.
+pub enum EfiScsiData<'a> {
+    None,
+    /// Data read from the device.
+    Read(&'a mut [u8]),
+    /// Data written to the device.
+    Write(&'a [u8]),
+    /// Data read from and written to the device by the same command.
+    Bidirectional(&'a mut [u8], &'a [u8]),
+}
+
+/// SCSI command descriptor block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EfiScsiCdb {
+    bytes: [u8; 16],
+    length: u8,
+}
+
+impl EfiScsiCdb {
+    pub const TEST_UNIT_READY: u8 = 0x00;
+    pub const INQUIRY: u8 = 0x12;
+    pub const READ_CAPACITY_10: u8 = 0x25;
+    pub const SERVICE_ACTION_IN_16: u8 = 0x9E;
+
+    /// Size of the standard INQUIRY data parsed by [`EfiScsiInquiryData`].
+    pub const INQUIRY_DATA_SIZE: usize = 36;
+    pub const READ_CAPACITY_10_DATA_SIZE: usize = 8;
+    pub const READ_CAPACITY_16_DATA_SIZE: usize = 32;
+
+    /// Wraps a raw CDB. Returns `None` if it's empty or longer than 16 bytes.
+    pub fn new(bytes: &[u8]) -> Option<Self> {
+        if bytes.is_empty() || bytes.len() > 16 {
+            return None;
+        }
+
+        let mut cdb: Self = Self {
+            bytes: [0; 16],
+            length: bytes.len() as u8,
+        };
+
+        cdb.bytes[..bytes.len()].copy_from_slice(bytes);
+
+        Some(cdb)
+    }
+
+    pub fn test_unit_ready() -> Self {
+        Self {
+            bytes: [
+                Self::TEST_UNIT_READY,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+            ],
+            length: 6,
+        }
+    }
+
+    /// Builds a standard INQUIRY command, returning up to `allocation_length` bytes.
+    pub fn inquiry(allocation_length: u16) -> Self {
+        let [high, low]: [u8; 2] = allocation_length.to_be_bytes();
+
+        Self {
+            bytes: [
+                Self::INQUIRY,
+                0,
+                0,
+                high,
+                low,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+            ],
+            length: 6,
+        }
+    }
+
+    /// Builds a READ CAPACITY (10) command, parsed by [`EfiScsiReadCapacity::parse_10`].
+    pub fn read_capacity_10() -> Self {
+        Self {
+            bytes: [
+                Self::READ_CAPACITY_10,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+            ],
+            length: 10,
+        }
+    }
+
+    /// Builds a READ CAPACITY (16) command, parsed by [`EfiScsiReadCapacity::parse_16`].
+    pub fn read_capacity_16(allocation_length: u32) -> Self {
+        /* Service action 0x10 of SERVICE ACTION IN (16) */
+        let mut bytes: [u8; 16] = [0; 16];
+
+        bytes[0] = Self::SERVICE_ACTION_IN_16;
+        bytes[1] = 0x10;
+        bytes[10..14].copy_from_slice(&allocation_length.to_be_bytes());
+
+        Self { bytes, length: 16 }
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.length as usize]
+    }
+}
+
+/// Outcome of a SCSI command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EfiScsiCompletion {
+    in_transfer_length: u32,
+    out_transfer_length: u32,
+    host_adapter_status: u8,
+    target_status: u8,
+    sense_data_length: u8,
+}
+
+impl EfiScsiCompletion {
+    pub const HOST_ADAPTER_STATUS_OK: u8 = 0x00;
+
+    pub const TARGET_STATUS_GOOD: u8 = 0x00;
+    pub const TARGET_STATUS_CHECK_CONDITION: u8 = 0x02;
+    pub const TARGET_STATUS_BUSY: u8 = 0x08;
+
+    /// Returns the number of bytes read from the device.
+    pub fn in_transfer_length(&self) -> u32 {
+        self.in_transfer_length
+    }
+
+    /// Returns the number of bytes written to the device.
+    pub fn out_transfer_length(&self) -> u32 {
+        self.out_transfer_length
+    }
+
+    pub fn host_adapter_status(&self) -> u8 {
+        self.host_adapter_status
+    }
+
+    pub fn target_status(&self) -> u8 {
+        self.target_status
+    }
+
+    /// Returns the number of valid bytes in the sense data buffer.
+    pub fn sense_data_length(&self) -> u8 {
+        self.sense_data_length
+    }
+}
+
+fn ascii_field(data: &[u8]) -> &str {
+    str::from_utf8(data)
+        .map(|field: &str| field.trim_end_matches([' ', '\0']))
+        .unwrap_or("")
+}
+
+/// Standard INQUIRY data.
+#[derive(Clone, Copy)]
+pub struct EfiScsiInquiryData<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> EfiScsiInquiryData<'a> {
+    /// Peripheral device type of direct access block devices, e.g. disks.
+    pub const DIRECT_ACCESS_BLOCK_DEVICE: u8 = 0x00;
+    /// Peripheral device type of CD and DVD drives.
+    pub const CD_DVD_DEVICE: u8 = 0x05;
+
+    pub fn parse(data: &'a [u8]) -> Option<Self> {
+        if data.len() < EfiScsiCdb::INQUIRY_DATA_SIZE {
+            None
+        } else {
+            Some(Self { data })
+        }
+    }
+
+    /// Returns whether a device is connected to the logical unit, zero meaning it is.
+    pub fn peripheral_qualifier(&self) -> u8 {
+        self.data[0] >> 5
+    }
+
+    pub fn peripheral_device_type(&self) -> u8 {
+        self.data[0] & 0x1F
+    }
+
+    pub fn is_removable(&self) -> bool {
+        self.data[1] & 0x80 != 0
+    }
+
+    /// Returns the SCSI standard the device claims conformance to.
+    pub fn version(&self) -> u8 {
+        self.data[2]
+    }
+
+    /// Returns the vendor identification, without padding.
+    pub fn vendor_identification(&self) -> &'a str {
+        ascii_field(&self.data[8..16])
+    }
+
+    /// Returns the product identification, without padding.
+    pub fn product_identification(&self) -> &'a str {
+        ascii_field(&self.data[16..32])
+    }
+
+    /// Returns the product revision level, without padding.
+    pub fn product_revision_level(&self) -> &'a str {
+        ascii_field(&self.data[32..36])
+    }
+}
+
+/// Capacity returned by the READ CAPACITY commands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EfiScsiReadCapacity {
+    last_lba: u64,
+    block_length: u32,
+}
+
+impl EfiScsiReadCapacity {
+    /// Parses READ CAPACITY (10) data.
+    ///
+    /// A last LBA of `0xFFFF_FFFF` means the capacity has to be read with READ CAPACITY (16).
+    pub fn parse_10(data: &[u8]) -> Option<Self> {
+        Some(Self {
+            last_lba: u64::from(u32::from_be_bytes(data.get(0..4)?.try_into().ok()?)),
+            block_length: u32::from_be_bytes(data.get(4..8)?.try_into().ok()?),
+        })
+    }
+
+    /// Parses READ CAPACITY (16) data.
+    pub fn parse_16(data: &[u8]) -> Option<Self> {
+        Some(Self {
+            last_lba: u64::from_be_bytes(data.get(0..8)?.try_into().ok()?),
+            block_length: u32::from_be_bytes(data.get(8..12)?.try_into().ok()?),
+        })
+    }
+
+    pub fn last_lba(&self) -> u64 {
+        self.last_lba
+    }
+
+    pub fn block_length(&self) -> u32 {
+        self.block_length
+    }
+
+    /// Returns the capacity in bytes.
+    pub fn capacity(&self) -> u128 {
+        (u128::from(self.last_lba) + 1) * u128::from(self.block_length)
+    }
+}
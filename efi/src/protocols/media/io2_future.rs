@@ -0,0 +1,200 @@
+use {
+    super::{EfiBlockIO2Protocol, EfiDiskIO2Protocol},
+    crate::{
+        boot_services::{
+            event::EventFuture, types::event_and_timer::EfiEventType, EfiBootServices1x0,
+        },
+        status::{EfiStatus, EfiStatusEnum},
+        types::{EfiEvent, EfiLBA},
+    },
+    core::{
+        future::Future,
+        marker::PhantomPinned,
+        pin::Pin,
+        task::{Context, Poll},
+    },
+};
+
+/// Token shared by `EFI_BLOCK_IO2_PROTOCOL` and `EFI_DISK_IO2_PROTOCOL` requests.
+#[repr(C)]
+pub struct EfiIO2Token {
+    event: EfiEvent,
+    transaction_status: EfiStatus,
+}
+
+impl EfiIO2Token {
+    /// Creates new token. When the event is null, the request is performed synchronously.
+    pub fn new(event: EfiEvent) -> Self {
+        Self {
+            event,
+            transaction_status: EfiStatus::success(),
+        }
+    }
+
+    pub fn event(&self) -> EfiEvent {
+        self.event
+    }
+
+    /// Returns the transaction's status. Only valid after the token's event is signalled.
+    pub fn transaction_status(&self) -> EfiStatusEnum {
+        self.transaction_status.into_enum()
+    }
+}
+
+pub(super) enum EfiIO2Operation<'a> {
+    ReadBlocks(&'a EfiBlockIO2Protocol, u32, EfiLBA, &'a mut [u8]),
+    WriteBlocks(&'a EfiBlockIO2Protocol, u32, EfiLBA, &'a [u8]),
+    FlushBlocks(&'a EfiBlockIO2Protocol),
+    ReadDisk(&'a EfiDiskIO2Protocol, u32, u64, &'a mut [u8]),
+    WriteDisk(&'a EfiDiskIO2Protocol, u32, u64, &'a [u8]),
+    FlushDisk(&'a EfiDiskIO2Protocol),
+}
+
+enum EfiIO2State {
+    Pending,
+    Submitted,
+    Finished,
+}
+
+/// Asynchronous Block IO 2 or Disk IO 2 request.
+///
+/// The request is submitted when the future is first polled and completes when the firmware signals the token's event, which wakes the executor's waker.
+/// Several requests can be overlapped by polling them together (e.g.: with `nautilos_async::block_on_array`).
+///
+/// Dropping an unfinished future waits for the request to finish (Disk IO 2 requests are cancelled first), as the firmware still references the buffer.
+/// As the wait relies on the token's notify function, it panics instead when the task priority level is `TPL_CALLBACK` or higher.
+pub struct EfiIO2Future<'a> {
+    boot_services: &'a EfiBootServices1x0,
+    operation: EfiIO2Operation<'a>,
+    token: EfiIO2Token,
+    completion: Option<EventFuture<'a>>,
+    state: EfiIO2State,
+    _pinned: PhantomPinned,
+}
+
+impl<'a> EfiIO2Future<'a> {
+    pub(super) fn new(
+        boot_services: &'a EfiBootServices1x0,
+        operation: EfiIO2Operation<'a>,
+    ) -> Self {
+        Self {
+            boot_services,
+            operation,
+            token: EfiIO2Token::new(0 as _),
+            completion: None,
+            state: EfiIO2State::Pending,
+            _pinned: PhantomPinned,
+        }
+    }
+
+    fn submit(&mut self) -> EfiStatusEnum {
+        let completion: EventFuture<'a> =
+            match EventFuture::new(self.boot_services, EfiEventType::NotifySignal) {
+                EfiStatusEnum::Success(completion) | EfiStatusEnum::Warning(_, completion) => {
+                    completion
+                }
+                EfiStatusEnum::Error(error, ()) => return EfiStatusEnum::Error(error, ()),
+            };
+
+        self.token.event = completion.event().raw();
+
+        let token: &mut EfiIO2Token = &mut self.token;
+
+        let result: EfiStatusEnum = unsafe {
+            match &mut self.operation {
+                EfiIO2Operation::ReadBlocks(block_io, media_id, lba, buffer) => {
+                    block_io.read_blocks_ex(*media_id, *lba, token, buffer)
+                }
+                EfiIO2Operation::WriteBlocks(block_io, media_id, lba, buffer) => {
+                    block_io.write_blocks_ex(*media_id, *lba, token, buffer)
+                }
+                EfiIO2Operation::FlushBlocks(block_io) => block_io.flush_blocks_ex(token),
+                EfiIO2Operation::ReadDisk(disk_io, media_id, offset, buffer) => {
+                    disk_io.read_disk_ex(*media_id, *offset, token, buffer)
+                }
+                EfiIO2Operation::WriteDisk(disk_io, media_id, offset, buffer) => {
+                    disk_io.write_disk_ex(*media_id, *offset, token, buffer)
+                }
+                EfiIO2Operation::FlushDisk(disk_io) => disk_io.flush_disk_ex(token),
+            }
+        };
+
+        /* Dropping the completion closes the event */
+        if !result.is_error() {
+            self.completion = Some(completion);
+        }
+
+        result
+    }
+
+    fn finish(&mut self) {
+        self.completion = None;
+        self.state = EfiIO2State::Finished;
+    }
+}
+
+impl Future for EfiIO2Future<'_> {
+    type Output = EfiStatusEnum;
+
+    fn poll(self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<<Self as Future>::Output> {
+        /* The future isn't moved out of, so the token keeps it's address */
+        let this: &mut Self = unsafe { self.get_unchecked_mut() };
+
+        match this.state {
+            EfiIO2State::Pending => {
+                let result: EfiStatusEnum = this.submit();
+
+                if result.is_error() {
+                    this.state = EfiIO2State::Finished;
+
+                    return Poll::Ready(result);
+                }
+
+                this.state = EfiIO2State::Submitted;
+            }
+            EfiIO2State::Submitted => (),
+            EfiIO2State::Finished => panic!("Future polled after completion!"),
+        }
+
+        let completion: &mut EventFuture<'_> = this
+            .completion
+            .as_mut()
+            .expect("Submitted IO 2 request without a completion event!");
+
+        match Pin::new(completion).poll(context) {
+            Poll::Ready(()) => {
+                this.finish();
+
+                Poll::Ready(this.token.transaction_status())
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl Drop for EfiIO2Future<'_> {
+    fn drop(&mut self) {
+        if let EfiIO2State::Submitted = self.state {
+            if let EfiIO2Operation::ReadDisk(disk_io, ..)
+            | EfiIO2Operation::WriteDisk(disk_io, ..)
+            | EfiIO2Operation::FlushDisk(disk_io) = self.operation
+            {
+                let _ = disk_io.cancel();
+            }
+
+            /* The notify function can't run, so waiting for it would never end */
+            if self.boot_services.current_priority_level() >= EventFuture::NOTIFY_LEVEL {
+                panic!("Unfinished IO 2 request dropped at a task priority level blocking it's completion!");
+            }
+
+            /* Stalling instead of spinning lets firmware which completes requests from it's timer make progress */
+            if let Some(completion) = &self.completion {
+                while !completion.is_signalled() {
+                    let _ = self.boot_services.stall(1);
+                }
+            }
+
+            self.finish();
+        }
+    }
+}
@@ -1,5 +1,23 @@
+mod aligned_buffer;
 mod block_io;
+mod block_io2;
 mod disk_io;
+mod disk_io2;
+mod ext_scsi_pass_thru;
+mod io2_future;
+mod nvdimm_label;
+mod nvme_pass_thru;
+mod ram_disk;
+mod simple_file_system;
 
+pub use aligned_buffer::*;
 pub use block_io::*;
+pub use block_io2::*;
 pub use disk_io::*;
+pub use disk_io2::*;
+pub use ext_scsi_pass_thru::*;
+pub use io2_future::{EfiIO2Future, EfiIO2Token};
+pub use nvdimm_label::*;
+pub use nvme_pass_thru::*;
+pub use ram_disk::*;
+pub use simple_file_system::*;
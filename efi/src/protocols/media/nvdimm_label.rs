@@ -0,0 +1,120 @@
+use {
+    crate::{
+        guid::EfiGuid,
+        protocols::EfiProtocol,
+        status::{EfiStatus, EfiStatusEnum, EfiStatusError},
+        types::NonNullVoidPtr,
+    },
+    alloc::{vec, vec::Vec},
+    core::convert::TryFrom,
+};
+
+/// Implementation of EFI's `EFI_NVDIMM_LABEL_PROTOCOL`.
+///
+/// Installed on the handle of each NVDIMM, whose device path ends with an ACPI `_ADR` node holding it's NFIT device handle.
+/// Offsets are relative to the beginning of the label storage area.
+#[repr(C)]
+pub struct EfiNvdimmLabelProtocol {
+    label_storage_information: extern "efiapi" fn(*const Self, *mut u32, *mut u32) -> EfiStatus,
+    label_storage_read: extern "efiapi" fn(*const Self, u32, u32, *mut u8) -> EfiStatus,
+    label_storage_write: extern "efiapi" fn(*const Self, u32, u32, *const u8) -> EfiStatus,
+}
+
+impl EfiNvdimmLabelProtocol {
+    pub fn label_storage_information(&self) -> EfiStatusEnum<EfiNvdimmLabelStorageInformation> {
+        let mut size_of_label_storage_area: u32 = 0;
+        let mut max_transfer_length: u32 = 0;
+
+        (self.label_storage_information)(
+            self,
+            &mut size_of_label_storage_area,
+            &mut max_transfer_length,
+        )
+        .into_enum_data(|| EfiNvdimmLabelStorageInformation {
+            size_of_label_storage_area,
+            max_transfer_length,
+        })
+    }
+
+    /// Reads the buffer's length of data, which can't be longer than the maximum transfer length.
+    pub fn label_storage_read(&self, offset: u32, buffer: &mut [u8]) -> EfiStatusEnum {
+        match u32::try_from(buffer.len()) {
+            Ok(length) => {
+                (self.label_storage_read)(self, offset, length, buffer.as_mut_ptr()).into_enum()
+            }
+            Err(_) => EfiStatusEnum::Error(EfiStatusError::EfiBadBufferSize, ()),
+        }
+    }
+
+    /// Writes the data, which can't be longer than the maximum transfer length.
+    pub fn label_storage_write(&self, offset: u32, data: &[u8]) -> EfiStatusEnum {
+        match u32::try_from(data.len()) {
+            Ok(length) => {
+                (self.label_storage_write)(self, offset, length, data.as_ptr()).into_enum()
+            }
+            Err(_) => EfiStatusEnum::Error(EfiStatusError::EfiBadBufferSize, ()),
+        }
+    }
+
+    /// Reads the whole label storage area, in transfers of at most the maximum transfer length.
+    pub fn read_label_storage_area(&self) -> EfiStatusEnum<Vec<u8>> {
+        let information: EfiNvdimmLabelStorageInformation = match self.label_storage_information() {
+            EfiStatusEnum::Success(information) | EfiStatusEnum::Warning(_, information) => {
+                information
+            }
+            EfiStatusEnum::Error(status, ()) => return EfiStatusEnum::Error(status, ()),
+        };
+
+        let mut area: Vec<u8> = vec![0; information.size_of_label_storage_area() as usize];
+
+        /* A maximum of zero means there's no limit */
+        let chunk_size: usize = match information.max_transfer_length() {
+            0 => area.len().max(1),
+            max_transfer_length => max_transfer_length as usize,
+        };
+
+        for (index, chunk) in area.chunks_mut(chunk_size).enumerate() {
+            if let EfiStatusEnum::Error(status, ()) =
+                self.label_storage_read((index * chunk_size) as u32, chunk)
+            {
+                return EfiStatusEnum::Error(status, ());
+            }
+        }
+
+        EfiStatusEnum::Success(area)
+    }
+}
+
+impl EfiProtocol for EfiNvdimmLabelProtocol {
+    type Parsed = &'static Self;
+    type Error = !;
+
+    fn guid() -> EfiGuid {
+        crate::guids::EFI_NVDIMM_LABEL_PROTOCOL
+    }
+
+    unsafe fn parse(
+        ptr: NonNullVoidPtr,
+    ) -> Result<<Self as EfiProtocol>::Parsed, <Self as EfiProtocol>::Error> {
+        Ok(&*ptr.cast().as_ptr())
+    }
+}
+
+/// Information returned by [`EfiNvdimmLabelProtocol::label_storage_information`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EfiNvdimmLabelStorageInformation {
+    size_of_label_storage_area: u32,
+    max_transfer_length: u32,
+}
+
+impl EfiNvdimmLabelStorageInformation {
+    /// Returns the size of the label storage area in bytes.
+    pub fn size_of_label_storage_area(&self) -> u32 {
+        self.size_of_label_storage_area
+    }
+
+    /// Returns the maximum number of bytes a single read or write can transfer, where zero means there's no limit.
+    pub fn max_transfer_length(&self) -> u32 {
+        self.max_transfer_length
+    }
+}
@@ -0,0 +1,573 @@
+use {
+    crate::{
+        guid::EfiGuid,
+        protocols::{device_path::EfiDevicePathProtocolRaw, EfiProtocol},
+        status::{EfiStatus, EfiStatusEnum},
+        types::{EfiEvent, NonNullVoidPtr, Void, VoidMutPtr},
+    },
+    core::{convert::TryInto, iter::FusedIterator, ptr::NonNull, str},
+};
+
+/// Implementation of EFI's `EFI_NVM_EXPRESS_PASS_THRU_PROTOCOL`.
+///
+/// Transfer buffers have to be aligned to the mode's [`io_align`](EfiNvmePassThruMode::io_align), e.g. by using [`EfiAlignedBuffer`](super::EfiAlignedBuffer).
+#[repr(C)]
+pub struct EfiNvmePassThruProtocol {
+    mode: *const EfiNvmePassThruMode,
+    pass_thru: extern "efiapi" fn(
+        *const Self,
+        u32,
+        *mut EfiNvmePassThruCommandPacket,
+        EfiEvent,
+    ) -> EfiStatus,
+    get_next_namespace: extern "efiapi" fn(*const Self, *mut u32) -> EfiStatus,
+    build_device_path: extern "efiapi" fn(*const Self, u32, *mut *mut Void) -> EfiStatus,
+    get_namespace: extern "efiapi" fn(*const Self, *const Void, *mut u32) -> EfiStatus,
+}
+
+impl EfiNvmePassThruProtocol {
+    /// Namespace ID addressing all namespaces, and starting the enumeration.
+    pub const ALL_NAMESPACES: u32 = 0xFFFF_FFFF;
+
+    pub fn mode(&self) -> &EfiNvmePassThruMode {
+        unsafe { &*self.mode }
+    }
+
+    /// Sends the command to the controller and waits for it's completion.
+    ///
+    /// Admin commands are sent to namespace zero, unless they target a namespace, like Identify Namespace.
+    /// The timeout is in units of 100 ns, zero waiting indefinitely.
+    /// When the controller reports an error, the completion is returned alongside [`EfiDeviceError`](crate::status::EfiStatusError::EfiDeviceError).
+    pub fn pass_thru(
+        &self,
+        namespace_id: u32,
+        queue_type: EfiNvmeQueueType,
+        command: &EfiNvmeCommand,
+        transfer_buffer: Option<&mut [u8]>,
+        timeout: u64,
+    ) -> EfiStatusEnum<EfiNvmeCompletion, EfiNvmeCompletion> {
+        let mut command: EfiNvmeCommand = *command;
+        let mut completion: EfiNvmeCompletion = EfiNvmeCompletion::default();
+
+        let (transfer_buffer, transfer_length): (VoidMutPtr, u32) = match transfer_buffer {
+            Some(buffer) => (buffer.as_mut_ptr() as VoidMutPtr, buffer.len() as u32),
+            None => (0 as _, 0),
+        };
+
+        let mut packet: EfiNvmePassThruCommandPacket = EfiNvmePassThruCommandPacket {
+            command_timeout: timeout,
+            transfer_buffer,
+            transfer_length,
+            metadata_buffer: 0 as _,
+            metadata_length: 0,
+            queue_type: queue_type as u8,
+            nvme_cmd: &mut command,
+            nvme_completion: &mut completion,
+        };
+
+        (self.pass_thru)(self, namespace_id, &mut packet, 0 as _)
+            .into_enum_data_error(|| completion, || completion)
+    }
+
+    /// Returns the namespace following the given one, or [`EfiNotFound`](crate::status::EfiStatusError::EfiNotFound) after the last one.
+    pub fn get_next_namespace(&self, namespace_id: u32) -> EfiStatusEnum<u32> {
+        let mut namespace_id: u32 = namespace_id;
+
+        (self.get_next_namespace)(self, &mut namespace_id).into_enum_data(|| namespace_id)
+    }
+
+    /// Returns iterator over the IDs of the controller's namespaces.
+    pub fn namespaces(&self) -> EfiNvmeNamespaces<'_> {
+        EfiNvmeNamespaces {
+            protocol: self,
+            namespace_id: Some(Self::ALL_NAMESPACES),
+        }
+    }
+
+    /// Builds the device path node of the namespace.
+    ///
+    /// The node is allocated from pool memory and has to be released with `free_pool`.
+    pub fn build_device_path(&self, namespace_id: u32) -> EfiStatusEnum<EfiDevicePathProtocolRaw> {
+        let mut device_path: *mut Void = 0 as _;
+
+        match (self.build_device_path)(self, namespace_id, &mut device_path).into_enum() {
+            EfiStatusEnum::Success(()) | EfiStatusEnum::Warning(_, ()) if device_path.is_null() => {
+                EfiStatusEnum::Error(crate::status::EfiStatusError::EfiOutOfResources, ())
+            }
+            status => status.map(|()| {
+                EfiDevicePathProtocolRaw::new(unsafe { NonNull::new_unchecked(device_path) })
+            }),
+        }
+    }
+
+    /// Returns the ID of the namespace the device path node describes.
+    pub fn get_namespace(&self, device_path: &EfiDevicePathProtocolRaw) -> EfiStatusEnum<u32> {
+        let mut namespace_id: u32 = 0;
+
+        (self.get_namespace)(self, device_path.as_ptr(), &mut namespace_id)
+            .into_enum_data(|| namespace_id)
+    }
+}
+
+impl EfiProtocol for EfiNvmePassThruProtocol {
+    type Parsed = &'static Self;
+    type Error = !;
+
+    fn guid() -> EfiGuid {
+        crate::guids::EFI_NVM_EXPRESS_PASS_THRU_PROTOCOL
+    }
+
+    unsafe fn parse(
+        ptr: NonNullVoidPtr,
+    ) -> Result<<Self as EfiProtocol>::Parsed, <Self as EfiProtocol>::Error> {
+        Ok(&*ptr.cast().as_ptr())
+    }
+}
+
+/// Iterator over the namespace IDs of a controller.
+pub struct EfiNvmeNamespaces<'a> {
+    protocol: &'a EfiNvmePassThruProtocol,
+    namespace_id: Option<u32>,
+}
+
+impl Iterator for EfiNvmeNamespaces<'_> {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<<Self as Iterator>::Item> {
+        self.namespace_id = match self.protocol.get_next_namespace(self.namespace_id?) {
+            EfiStatusEnum::Success(namespace_id) | EfiStatusEnum::Warning(_, namespace_id) => {
+                Some(namespace_id)
+            }
+            EfiStatusEnum::Error(..) => None,
+        };
+
+        self.namespace_id
+    }
+}
+
+impl FusedIterator for EfiNvmeNamespaces<'_> {}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EfiNvmePassThruMode {
+    attributes: u32,
+    io_align: u32,
+    nvme_version: u32,
+}
+
+impl EfiNvmePassThruMode {
+    /// The interface addresses the controller's namespaces directly.
+    pub const PHYSICAL: u32 = 0x01;
+    /// The interface addresses logical namespaces, e.g. of a RAID volume.
+    pub const LOGICAL: u32 = 0x02;
+    pub const NONBLOCKIO: u32 = 0x04;
+    /// The controller supports the NVM command set.
+    pub const CMD_SET_NVM: u32 = 0x08;
+
+    pub fn attributes(&self) -> u32 {
+        self.attributes
+    }
+
+    pub fn contains(&self, attributes: u32) -> bool {
+        self.attributes & attributes == attributes
+    }
+
+    /// Returns the required alignment of transfer buffers, where zero and one mean there's no requirement.
+    pub fn io_align(&self) -> u32 {
+        self.io_align
+    }
+
+    /// Returns the NVMe version the controller implements, as reported in it's `VS` register.
+    pub fn nvme_version(&self) -> u32 {
+        self.nvme_version
+    }
+}
+
+#[repr(C)]
+struct EfiNvmePassThruCommandPacket {
+    command_timeout: u64,
+    transfer_buffer: VoidMutPtr,
+    transfer_length: u32,
+    metadata_buffer: VoidMutPtr,
+    metadata_length: u32,
+    queue_type: u8,
+    nvme_cmd: *mut EfiNvmeCommand,
+    nvme_completion: *mut EfiNvmeCompletion,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EfiNvmeQueueType {
+    Admin = 0,
+    IO = 1,
+}
+
+/// NVMe submission queue entry, as passed through the protocol.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EfiNvmeCommand {
+    cdw0: u32,
+    flags: u8,
+    nsid: u32,
+    cdw2: u32,
+    cdw3: u32,
+    cdw10: u32,
+    cdw11: u32,
+    cdw12: u32,
+    cdw13: u32,
+    cdw14: u32,
+    cdw15: u32,
+}
+
+impl EfiNvmeCommand {
+    /* Admin command opcodes */
+    pub const GET_LOG_PAGE: u8 = 0x02;
+    pub const IDENTIFY: u8 = 0x06;
+
+    /* Identify's controller or namespace structures */
+    pub const CNS_NAMESPACE: u8 = 0x00;
+    pub const CNS_CONTROLLER: u8 = 0x01;
+    pub const CNS_ACTIVE_NAMESPACES: u8 = 0x02;
+
+    /* Log page identifiers */
+    pub const LOG_ERROR_INFORMATION: u8 = 0x01;
+    pub const LOG_SMART_HEALTH_INFORMATION: u8 = 0x02;
+    pub const LOG_FIRMWARE_SLOT_INFORMATION: u8 = 0x03;
+
+    /// Size of the Identify data structures.
+    pub const IDENTIFY_SIZE: usize = 4096;
+    /// Size of the SMART / Health Information log page.
+    pub const SMART_HEALTH_INFORMATION_SIZE: usize = 512;
+
+    pub const fn new(opcode: u8) -> Self {
+        Self {
+            cdw0: opcode as u32,
+            flags: 0,
+            nsid: 0,
+            cdw2: 0,
+            cdw3: 0,
+            cdw10: 0,
+            cdw11: 0,
+            cdw12: 0,
+            cdw13: 0,
+            cdw14: 0,
+            cdw15: 0,
+        }
+    }
+
+    /// Builds an Identify command returning a 4 KiB structure, selected by the CNS value.
+    pub const fn identify(cns: u8, namespace_id: u32) -> Self {
+        Self::new(Self::IDENTIFY)
+            .namespace_id(namespace_id)
+            .cdw(10, cns as u32)
+    }
+
+    /// Builds an Identify Controller command, parsed by [`EfiNvmeIdentifyController`].
+    pub const fn identify_controller() -> Self {
+        Self::identify(Self::CNS_CONTROLLER, 0)
+    }
+
+    /// Builds an Identify Namespace command, parsed by [`EfiNvmeIdentifyNamespace`].
+    pub const fn identify_namespace(namespace_id: u32) -> Self {
+        Self::identify(Self::CNS_NAMESPACE, namespace_id)
+    }
+
+    /// Builds a command listing up to 1024 active namespace IDs greater than the given one, as little-endian `u32`s.
+    pub const fn active_namespace_list(namespace_id: u32) -> Self {
+        Self::identify(Self::CNS_ACTIVE_NAMESPACES, namespace_id)
+    }
+
+    /// Builds a Get Log Page command reading `length` bytes, which has to be a non-zero multiple of 4.
+    ///
+    /// Controller-wide logs, like SMART, use [`ALL_NAMESPACES`](EfiNvmePassThruProtocol::ALL_NAMESPACES).
+    pub const fn get_log_page(log_id: u8, namespace_id: u32, length: usize) -> Self {
+        /* Zero-based number of dwords */
+        let dwords: u32 = (length / 4).saturating_sub(1) as u32;
+
+        Self::new(Self::GET_LOG_PAGE)
+            .namespace_id(namespace_id)
+            .cdw(10, log_id as u32 | (dwords & 0xFFFF) << 16)
+            .cdw(11, dwords >> 16)
+    }
+
+    pub const fn opcode(&self) -> u8 {
+        self.cdw0 as u8
+    }
+
+    pub const fn namespace_id(mut self, namespace_id: u32) -> Self {
+        self.nsid = namespace_id;
+
+        self
+    }
+
+    /// Sets one of the command dwords 2, 3 and 10 through 15, marking it as valid; Other indices are ignored.
+    pub const fn cdw(mut self, index: u8, value: u32) -> Self {
+        match index {
+            2 => self.cdw2 = value,
+            3 => self.cdw3 = value,
+            10 => self.cdw10 = value,
+            11 => self.cdw11 = value,
+            12 => self.cdw12 = value,
+            13 => self.cdw13 = value,
+            14 => self.cdw14 = value,
+            15 => self.cdw15 = value,
+            _ => return self,
+        }
+
+        self.flags |= match index {
+            2 => 0x01,
+            3 => 0x02,
+            index => 0x04 << (index - 10),
+        };
+
+        self
+    }
+}
+
+/// NVMe completion queue entry.
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EfiNvmeCompletion {
+    dw0: u32,
+    dw1: u32,
+    dw2: u32,
+    dw3: u32,
+}
+
+impl EfiNvmeCompletion {
+    /// Returns the command specific result.
+    pub fn dw0(&self) -> u32 {
+        self.dw0
+    }
+
+    pub fn dw1(&self) -> u32 {
+        self.dw1
+    }
+
+    pub fn dw2(&self) -> u32 {
+        self.dw2
+    }
+
+    pub fn dw3(&self) -> u32 {
+        self.dw3
+    }
+
+    /// Returns the status code type, zero being the generic command status.
+    pub fn status_code_type(&self) -> u8 {
+        ((self.dw3 >> 25) & 0x7) as u8
+    }
+
+    /// Returns the status code, zero being successful completion.
+    pub fn status_code(&self) -> u8 {
+        (self.dw3 >> 17) as u8
+    }
+
+    pub fn is_success(&self) -> bool {
+        self.status_code_type() == 0 && self.status_code() == 0
+    }
+}
+
+fn ascii_field(data: &[u8]) -> &str {
+    str::from_utf8(data)
+        .map(|field: &str| field.trim_end_matches([' ', '\0']))
+        .unwrap_or("")
+}
+
+fn read_u16(data: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes(data[offset..offset + 2].try_into().unwrap_or([0; 2]))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap_or([0; 4]))
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap_or([0; 8]))
+}
+
+fn read_u128(data: &[u8], offset: usize) -> u128 {
+    u128::from_le_bytes(data[offset..offset + 16].try_into().unwrap_or([0; 16]))
+}
+
+/// Identify Controller data structure.
+#[derive(Clone, Copy)]
+pub struct EfiNvmeIdentifyController<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> EfiNvmeIdentifyController<'a> {
+    pub fn parse(data: &'a [u8]) -> Option<Self> {
+        if data.len() < EfiNvmeCommand::IDENTIFY_SIZE {
+            None
+        } else {
+            Some(Self { data })
+        }
+    }
+
+    /// Returns the PCI vendor ID.
+    pub fn vendor_id(&self) -> u16 {
+        read_u16(self.data, 0)
+    }
+
+    /// Returns the PCI subsystem vendor ID.
+    pub fn subsystem_vendor_id(&self) -> u16 {
+        read_u16(self.data, 2)
+    }
+
+    /// Returns the serial number, without padding.
+    pub fn serial_number(&self) -> &'a str {
+        ascii_field(&self.data[4..24])
+    }
+
+    /// Returns the model number, without padding.
+    pub fn model_number(&self) -> &'a str {
+        ascii_field(&self.data[24..64])
+    }
+
+    /// Returns the firmware revision, without padding.
+    pub fn firmware_revision(&self) -> &'a str {
+        ascii_field(&self.data[64..72])
+    }
+
+    /// Returns the maximum namespace ID the controller supports.
+    pub fn number_of_namespaces(&self) -> u32 {
+        read_u32(self.data, 516)
+    }
+}
+
+/// Identify Namespace data structure.
+#[derive(Clone, Copy)]
+pub struct EfiNvmeIdentifyNamespace<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> EfiNvmeIdentifyNamespace<'a> {
+    pub fn parse(data: &'a [u8]) -> Option<Self> {
+        if data.len() < EfiNvmeCommand::IDENTIFY_SIZE {
+            None
+        } else {
+            Some(Self { data })
+        }
+    }
+
+    /// Returns the size of the namespace in logical blocks.
+    pub fn size(&self) -> u64 {
+        read_u64(self.data, 0)
+    }
+
+    /// Returns the number of logical blocks which may be allocated.
+    pub fn capacity(&self) -> u64 {
+        read_u64(self.data, 8)
+    }
+
+    /// Returns the number of logical blocks currently allocated.
+    pub fn utilization(&self) -> u64 {
+        read_u64(self.data, 16)
+    }
+
+    /// Returns the number of supported LBA formats.
+    pub fn lba_formats_count(&self) -> u8 {
+        /* Stored zero-based */
+        self.data[25] + 1
+    }
+
+    /// Returns the index of the LBA format the namespace is formatted with.
+    pub fn formatted_lba_format(&self) -> u8 {
+        let flbas: u8 = self.data[26];
+
+        /* The upper bits only exist for more than 16 formats */
+        (flbas & 0x0F) | ((flbas >> 5) & 0x3) << 4
+    }
+
+    /// Returns the metadata size and the logical block size of the LBA format, in bytes.
+    pub fn lba_format(&self, index: u8) -> Option<(u16, u32)> {
+        if index >= self.lba_formats_count() || index >= 64 {
+            return None;
+        }
+
+        let offset: usize = 128 + 4 * index as usize;
+        let lbads: u8 = self.data[offset + 2];
+
+        if !(9..32).contains(&lbads) {
+            None
+        } else {
+            Some((read_u16(self.data, offset), 1 << lbads))
+        }
+    }
+
+    /// Returns the logical block size the namespace is formatted with.
+    pub fn block_size(&self) -> Option<u32> {
+        self.lba_format(self.formatted_lba_format())
+            .map(|(_, block_size): (u16, u32)| block_size)
+    }
+}
+
+/// SMART / Health Information log page.
+#[derive(Clone, Copy)]
+pub struct EfiNvmeSmartLog<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> EfiNvmeSmartLog<'a> {
+    pub const CRITICAL_WARNING_SPARE: u8 = 0x01;
+    pub const CRITICAL_WARNING_TEMPERATURE: u8 = 0x02;
+    pub const CRITICAL_WARNING_RELIABILITY: u8 = 0x04;
+    pub const CRITICAL_WARNING_READ_ONLY: u8 = 0x08;
+    pub const CRITICAL_WARNING_VOLATILE_BACKUP: u8 = 0x10;
+
+    pub fn parse(data: &'a [u8]) -> Option<Self> {
+        if data.len() < EfiNvmeCommand::SMART_HEALTH_INFORMATION_SIZE {
+            None
+        } else {
+            Some(Self { data })
+        }
+    }
+
+    pub fn critical_warning(&self) -> u8 {
+        self.data[0]
+    }
+
+    /// Returns the composite temperature in Kelvin.
+    pub fn composite_temperature(&self) -> u16 {
+        read_u16(self.data, 1)
+    }
+
+    /// Returns the remaining spare capacity in percent.
+    pub fn available_spare(&self) -> u8 {
+        self.data[3]
+    }
+
+    pub fn available_spare_threshold(&self) -> u8 {
+        self.data[4]
+    }
+
+    /// Returns the estimate of the endurance used in percent, which may exceed 100.
+    pub fn percentage_used(&self) -> u8 {
+        self.data[5]
+    }
+
+    /// Returns the number of 512-byte units read, in thousands.
+    pub fn data_units_read(&self) -> u128 {
+        read_u128(self.data, 32)
+    }
+
+    /// Returns the number of 512-byte units written, in thousands.
+    pub fn data_units_written(&self) -> u128 {
+        read_u128(self.data, 48)
+    }
+
+    pub fn power_cycles(&self) -> u128 {
+        read_u128(self.data, 112)
+    }
+
+    pub fn power_on_hours(&self) -> u128 {
+        read_u128(self.data, 128)
+    }
+
+    pub fn unsafe_shutdowns(&self) -> u128 {
+        read_u128(self.data, 144)
+    }
+
+    pub fn media_errors(&self) -> u128 {
+        read_u128(self.data, 160)
+    }
+}
@@ -0,0 +1,135 @@
+use {
+    crate::{
+        guid::EfiGuid,
+        protocols::{
+            device_path::{EfiDevicePathNode, EfiDevicePathProtocolRaw},
+            EfiProtocol,
+        },
+        status::{EfiStatus, EfiStatusEnum},
+        types::{EfiFirmwareFault, NonNullVoidPtr, VoidPtr},
+    },
+    core::convert::TryInto,
+};
+
+/// Implementation of EFI's `EFI_RAM_DISK_PROTOCOL`.
+///
+/// Registered RAM disks are exposed as block devices, whose device path ends with a RAM disk node.
+/// The disk's type is one of the RAM disk type GUIDs, e.g. [`EFI_VIRTUAL_DISK_GUID`](crate::guids::EFI_VIRTUAL_DISK_GUID)
+/// for raw disk images and [`EFI_VIRTUAL_CD_GUID`](crate::guids::EFI_VIRTUAL_CD_GUID) for ISO images.
+#[repr(C)]
+pub struct EfiRamDiskProtocol {
+    register: extern "efiapi" fn(u64, u64, *const EfiGuid, VoidPtr, *mut VoidPtr) -> EfiStatus,
+    unregister: extern "efiapi" fn(VoidPtr) -> EfiStatus,
+}
+
+impl EfiRamDiskProtocol {
+    /// Registers the memory range as a RAM disk and returns it's device path.
+    ///
+    /// When `parent_device_path` is given, the RAM disk node is appended to it.
+    /// The device path is allocated from pool, and the caller frees it once the RAM disk is unregistered.
+    /// A successful registration which returns no device path is a firmware fault, as the RAM disk can't be unregistered without it.
+    /// # Safety
+    /// The memory range must hold the disk's image and remain allocated until the RAM disk is unregistered.
+    pub unsafe fn register(
+        &self,
+        base: u64,
+        size: u64,
+        disk_type: &EfiGuid,
+        parent_device_path: Option<&EfiDevicePathProtocolRaw>,
+    ) -> Result<EfiStatusEnum<EfiDevicePathProtocolRaw>, EfiFirmwareFault> {
+        let mut device_path: VoidPtr = 0 as _;
+
+        let result: EfiStatus = (self.register)(
+            base,
+            size,
+            disk_type,
+            parent_device_path.map_or(0 as _, |parent: &EfiDevicePathProtocolRaw| {
+                parent.as_ptr() as VoidPtr
+            }),
+            &mut device_path,
+        );
+
+        if let Some(error) = result.get_error() {
+            return Ok(EfiStatusEnum::Error(error, ()));
+        }
+
+        NonNullVoidPtr::new(device_path as _)
+            .ok_or(EfiFirmwareFault)
+            .map(|device_path: NonNullVoidPtr| {
+                result.into_enum_data(|| EfiDevicePathProtocolRaw::new(device_path))
+            })
+    }
+
+    /// Unregisters the RAM disk with the device path returned when it was registered.
+    pub fn unregister(&self, device_path: &EfiDevicePathProtocolRaw) -> EfiStatusEnum {
+        (self.unregister)(device_path.as_ptr() as VoidPtr).into_enum()
+    }
+}
+
+impl EfiProtocol for EfiRamDiskProtocol {
+    type Parsed = &'static Self;
+    type Error = !;
+
+    fn guid() -> EfiGuid {
+        crate::guids::EFI_RAM_DISK_PROTOCOL
+    }
+
+    unsafe fn parse(
+        ptr: NonNullVoidPtr,
+    ) -> Result<<Self as EfiProtocol>::Parsed, <Self as EfiProtocol>::Error> {
+        Ok(&*ptr.cast().as_ptr())
+    }
+}
+
+/// RAM disk media device path node, describing where the disk is held.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EfiRamDiskDevicePathNode {
+    starting_address: u64,
+    ending_address: u64,
+    disk_type: EfiGuid,
+    instance: u16,
+}
+
+impl EfiRamDiskDevicePathNode {
+    const NODE_TYPE: u8 = 0x04;
+    const SUB_TYPE: u8 = 0x09;
+    const DATA_SIZE: usize = 8 + 8 + 16 + 2;
+
+    /// Parses the node, returning `None` if it isn't a RAM disk node.
+    pub fn parse(node: &EfiDevicePathNode<'_>) -> Option<Self> {
+        let data: &[u8] = node.data();
+
+        if node.node_type() != Self::NODE_TYPE
+            || node.sub_type() != Self::SUB_TYPE
+            || data.len() < Self::DATA_SIZE
+        {
+            return None;
+        }
+
+        Some(Self {
+            starting_address: u64::from_le_bytes(data[..8].try_into().ok()?),
+            ending_address: u64::from_le_bytes(data[8..16].try_into().ok()?),
+            disk_type: EfiGuid::from_slice(&data[16..32])?,
+            instance: u16::from_le_bytes([data[32], data[33]]),
+        })
+    }
+
+    /// Returns the address of the disk's first byte.
+    pub fn starting_address(&self) -> u64 {
+        self.starting_address
+    }
+
+    /// Returns the address of the disk's last byte.
+    pub fn ending_address(&self) -> u64 {
+        self.ending_address
+    }
+
+    pub fn disk_type(&self) -> EfiGuid {
+        self.disk_type
+    }
+
+    /// Returns the instance number, distinguishing RAM disks of the same type.
+    pub fn instance(&self) -> u16 {
+        self.instance
+    }
+}
@@ -0,0 +1,251 @@
+use {
+    crate::{
+        guid::EfiGuid,
+        protocols::EfiProtocol,
+        status::{EfiStatus, EfiStatusEnum, EfiStatusError},
+        types::{NonNullVoidPtr, VoidMutPtr, VoidPtr},
+    },
+    core::ptr::NonNull,
+};
+
+/// Implementation of EFI's `EFI_SIMPLE_FILE_SYSTEM_PROTOCOL`.
+#[repr(C)]
+pub struct EfiSimpleFileSystemProtocol {
+    revision: u64,
+    open_volume: extern "efiapi" fn(*const Self, *mut *mut EfiFileProtocol) -> EfiStatus,
+}
+
+impl EfiSimpleFileSystemProtocol {
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Opens the root directory of the volume.
+    pub fn open_volume(&self) -> EfiStatusEnum<EfiFile> {
+        let mut root: *mut EfiFileProtocol = 0 as _;
+
+        match (self.open_volume)(self, &mut root).into_enum() {
+            EfiStatusEnum::Success(()) | EfiStatusEnum::Warning(_, ())
+                if NonNull::new(root).is_none() =>
+            {
+                EfiStatusEnum::Error(EfiStatusError::EfiDeviceError, ())
+            }
+            status => status.map(|()| EfiFile {
+                protocol: unsafe { NonNull::new_unchecked(root) },
+            }),
+        }
+    }
+}
+
+impl EfiProtocol for EfiSimpleFileSystemProtocol {
+    type Parsed = &'static Self;
+    type Error = !;
+
+    fn guid() -> EfiGuid {
+        crate::guids::EFI_SIMPLE_FILE_SYSTEM_PROTOCOL
+    }
+
+    unsafe fn parse(
+        ptr: NonNullVoidPtr,
+    ) -> Result<<Self as EfiProtocol>::Parsed, <Self as EfiProtocol>::Error> {
+        Ok(&*ptr.cast().as_ptr())
+    }
+}
+
+/// Implementation of EFI's `EFI_FILE_PROTOCOL`.
+///
+/// Handles are owned through [`EfiFile`], which closes them when dropped.
+#[repr(C)]
+pub struct EfiFileProtocol {
+    revision: u64,
+    open: extern "efiapi" fn(*mut Self, *mut *mut Self, *const u16, u64, u64) -> EfiStatus,
+    close: extern "efiapi" fn(*mut Self) -> EfiStatus,
+    delete: extern "efiapi" fn(*mut Self) -> EfiStatus,
+    read: extern "efiapi" fn(*mut Self, *mut usize, VoidMutPtr) -> EfiStatus,
+    write: extern "efiapi" fn(*mut Self, *mut usize, VoidPtr) -> EfiStatus,
+    get_position: extern "efiapi" fn(*mut Self, *mut u64) -> EfiStatus,
+    set_position: extern "efiapi" fn(*mut Self, u64) -> EfiStatus,
+    get_info: extern "efiapi" fn(*mut Self, *const EfiGuid, *mut usize, VoidMutPtr) -> EfiStatus,
+    set_info: extern "efiapi" fn(*mut Self, *const EfiGuid, usize, VoidPtr) -> EfiStatus,
+    flush: extern "efiapi" fn(*mut Self) -> EfiStatus,
+}
+
+/// Owned file or directory handle, which is closed when dropped.
+pub struct EfiFile {
+    protocol: NonNull<EfiFileProtocol>,
+}
+
+impl EfiFile {
+    fn as_ptr(&self) -> *mut EfiFileProtocol {
+        self.protocol.as_ptr()
+    }
+
+    fn protocol(&self) -> &EfiFileProtocol {
+        unsafe { self.protocol.as_ref() }
+    }
+
+    pub fn revision(&self) -> u64 {
+        self.protocol().revision
+    }
+
+    /// Opens a file or directory relative to this one.
+    ///
+    /// `file_name` has to be null-terminated and uses `\` as the path separator; Otherwise [`EfiInvalidParameter`](EfiStatusError::EfiInvalidParameter) is returned.
+    /// `attributes` are only used when the file is created.
+    pub fn open(
+        &self,
+        file_name: &[u16],
+        open_mode: EfiFileOpenMode,
+        attributes: EfiFileAttributes,
+    ) -> EfiStatusEnum<Self> {
+        if file_name.last() != Some(&0) {
+            return EfiStatusEnum::Error(EfiStatusError::EfiInvalidParameter, ());
+        }
+
+        let mut file: *mut EfiFileProtocol = 0 as _;
+
+        match (self.protocol().open)(
+            self.as_ptr(),
+            &mut file,
+            file_name.as_ptr(),
+            open_mode.bits(),
+            attributes.bits(),
+        )
+        .into_enum()
+        {
+            EfiStatusEnum::Success(()) | EfiStatusEnum::Warning(_, ())
+                if NonNull::new(file).is_none() =>
+            {
+                EfiStatusEnum::Error(EfiStatusError::EfiDeviceError, ())
+            }
+            status => status.map(|()| Self {
+                protocol: unsafe { NonNull::new_unchecked(file) },
+            }),
+        }
+    }
+
+    /// Reads from the current position and returns the number of bytes read.
+    ///
+    /// For directories, a single `EFI_FILE_INFO` entry is read; When the buffer is too small, the error data contains the required size.
+    pub fn read(&self, buffer: &mut [u8]) -> EfiStatusEnum<usize, usize> {
+        let mut size: usize = buffer.len();
+
+        (self.protocol().read)(self.as_ptr(), &mut size, buffer.as_mut_ptr() as VoidMutPtr)
+            .into_enum_data_error(|| size, || size)
+    }
+
+    /// Writes at the current position and returns the number of bytes written.
+    pub fn write(&self, buffer: &[u8]) -> EfiStatusEnum<usize, usize> {
+        let mut size: usize = buffer.len();
+
+        (self.protocol().write)(self.as_ptr(), &mut size, buffer.as_ptr() as VoidPtr)
+            .into_enum_data_error(|| size, || size)
+    }
+
+    /// Writes the whole buffer, continuing after partial writes.
+    pub fn write_all(&self, mut buffer: &[u8]) -> EfiStatusEnum {
+        while !buffer.is_empty() {
+            match self.write(buffer) {
+                EfiStatusEnum::Success(0) | EfiStatusEnum::Warning(_, 0) => {
+                    return EfiStatusEnum::Error(EfiStatusError::EfiVolumeFull, ())
+                }
+                EfiStatusEnum::Success(written) | EfiStatusEnum::Warning(_, written) => {
+                    buffer = &buffer[written.min(buffer.len())..]
+                }
+                EfiStatusEnum::Error(error, _) => return EfiStatusEnum::Error(error, ()),
+            }
+        }
+
+        EfiStatusEnum::Success(())
+    }
+
+    pub fn get_position(&self) -> EfiStatusEnum<u64> {
+        let mut position: u64 = 0;
+
+        (self.protocol().get_position)(self.as_ptr(), &mut position).into_enum_data(|| position)
+    }
+
+    /// Sets the position; `u64::MAX` moves it to the end of the file.
+    pub fn set_position(&self, position: u64) -> EfiStatusEnum {
+        (self.protocol().set_position)(self.as_ptr(), position).into_enum()
+    }
+
+    pub fn flush(&self) -> EfiStatusEnum {
+        (self.protocol().flush)(self.as_ptr()).into_enum()
+    }
+
+    /// Deletes the file, which closes it's handle even when deleting fails.
+    pub fn delete(self) -> EfiStatusEnum {
+        let status: EfiStatus = (self.protocol().delete)(self.as_ptr());
+
+        core::mem::forget(self);
+
+        status.into_enum()
+    }
+
+    /// Closes the handle, reporting the status `drop` discards.
+    pub fn close(self) -> EfiStatusEnum {
+        let status: EfiStatus = (self.protocol().close)(self.as_ptr());
+
+        core::mem::forget(self);
+
+        status.into_enum()
+    }
+}
+
+impl Drop for EfiFile {
+    fn drop(&mut self) {
+        let _ = (self.protocol().close)(self.as_ptr());
+    }
+}
+
+/// Mode files are opened with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EfiFileOpenMode {
+    mode: u64,
+}
+
+impl EfiFileOpenMode {
+    pub const READ: u64 = 0x0000_0000_0000_0001;
+    pub const WRITE: u64 = 0x0000_0000_0000_0002;
+    pub const CREATE: u64 = 0x8000_0000_0000_0000;
+
+    pub const fn new(mode: u64) -> Self {
+        Self { mode }
+    }
+
+    pub const fn bits(&self) -> u64 {
+        self.mode
+    }
+
+    pub const fn contains(&self, mode: u64) -> bool {
+        self.mode & mode == mode
+    }
+}
+
+/// Attributes of files and directories.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EfiFileAttributes {
+    attributes: u64,
+}
+
+impl EfiFileAttributes {
+    pub const READ_ONLY: u64 = 0x0001;
+    pub const HIDDEN: u64 = 0x0002;
+    pub const SYSTEM: u64 = 0x0004;
+    pub const RESERVED: u64 = 0x0008;
+    pub const DIRECTORY: u64 = 0x0010;
+    pub const ARCHIVE: u64 = 0x0020;
+
+    pub const fn new(attributes: u64) -> Self {
+        Self { attributes }
+    }
+
+    pub const fn bits(&self) -> u64 {
+        self.attributes
+    }
+
+    pub const fn contains(&self, attributes: u64) -> bool {
+        self.attributes & attributes == attributes
+    }
+}
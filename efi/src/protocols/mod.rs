@@ -5,8 +5,15 @@ use {
 
 pub mod console;
 pub mod device_path;
+pub mod driver;
+pub mod firmware_management;
 pub mod media;
 pub mod network;
+pub mod pci;
+pub mod rng;
+pub mod service_binding;
+pub mod timestamp;
+pub mod usb;
 
 pub trait EfiProtocol {
     type Parsed;
@@ -1,11 +1,29 @@
-use core::ops::{Deref, DerefMut};
+use {
+    crate::types::VoidMutPtr,
+    core::{
+        fmt::{Display, Formatter, Result as FmtResult},
+        marker::PhantomData,
+        ops::{Deref, DerefMut},
+        slice::from_raw_parts,
+        str::FromStr,
+    },
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct EfiIPAddressParseError;
 
 #[repr(transparent)]
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub struct EfiIPv4AddressRaw {
     address: [u8; 4],
 }
 
+impl EfiIPv4AddressRaw {
+    pub const fn new(address: [u8; 4]) -> Self {
+        Self { address }
+    }
+}
+
 impl Deref for EfiIPv4AddressRaw {
     type Target = [u8; 4];
 
@@ -20,12 +38,79 @@ impl DerefMut for EfiIPv4AddressRaw {
     }
 }
 
+impl Display for EfiIPv4AddressRaw {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "{}.{}.{}.{}",
+            self.address[0], self.address[1], self.address[2], self.address[3],
+        )
+    }
+}
+
+impl FromStr for EfiIPv4AddressRaw {
+    type Err = EfiIPAddressParseError;
+
+    fn from_str(string: &str) -> Result<Self, <Self as FromStr>::Err> {
+        let mut address: [u8; 4] = [0; 4];
+
+        let mut octets = string.split('.');
+
+        for octet in address.iter_mut() {
+            let part: &str = octets.next().ok_or(EfiIPAddressParseError)?;
+
+            /* Leading zeroes are rejected as they are ambiguous (octal notation) */
+            if part.is_empty()
+                || part.len() > 3
+                || (part.len() > 1 && part.starts_with('0'))
+                || !part.bytes().all(|byte: u8| byte.is_ascii_digit())
+            {
+                return Err(EfiIPAddressParseError);
+            }
+
+            *octet = part.parse().map_err(|_| EfiIPAddressParseError)?;
+        }
+
+        if octets.next().is_some() {
+            Err(EfiIPAddressParseError)
+        } else {
+            Ok(Self { address })
+        }
+    }
+}
+
 #[repr(transparent)]
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub struct EfiIPv6AddressRaw {
     address: [u8; 16],
 }
 
+impl EfiIPv6AddressRaw {
+    pub const fn new(address: [u8; 16]) -> Self {
+        Self { address }
+    }
+
+    pub fn segments(&self) -> [u16; 8] {
+        let mut segments: [u16; 8] = [0; 8];
+
+        for (index, segment) in segments.iter_mut().enumerate() {
+            *segment = u16::from_be_bytes([self.address[index * 2], self.address[index * 2 + 1]]);
+        }
+
+        segments
+    }
+
+    pub fn from_segments(segments: [u16; 8]) -> Self {
+        let mut address: [u8; 16] = [0; 16];
+
+        for (index, segment) in segments.iter().enumerate() {
+            address[(index * 2)..(index * 2 + 2)].copy_from_slice(&segment.to_be_bytes());
+        }
+
+        Self { address }
+    }
+}
+
 impl Deref for EfiIPv6AddressRaw {
     type Target = [u8; 16];
 
@@ -40,16 +125,276 @@ impl DerefMut for EfiIPv6AddressRaw {
     }
 }
 
+/* Formats the address in it's canonical (RFC 5952) form */
+impl Display for EfiIPv6AddressRaw {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let segments: [u16; 8] = self.segments();
+
+        /* Find the longest run of (at least two) zero segments */
+        let (mut compressed_start, mut compressed_length): (usize, usize) = (0, 0);
+
+        {
+            let mut index: usize = 0;
+
+            while index < segments.len() {
+                let length: usize = segments[index..]
+                    .iter()
+                    .take_while(|&&segment: &&u16| segment == 0)
+                    .count();
+
+                if length > compressed_length {
+                    compressed_start = index;
+                    compressed_length = length;
+                }
+
+                index += length.max(1);
+            }
+        }
+
+        if compressed_length < 2 {
+            compressed_length = 0;
+        }
+
+        let mut index: usize = 0;
+
+        while index < segments.len() {
+            if compressed_length != 0 && index == compressed_start {
+                write!(f, "::")?;
+
+                index += compressed_length;
+
+                continue;
+            }
+
+            if index != 0
+                && !(compressed_length != 0 && index == compressed_start + compressed_length)
+            {
+                write!(f, ":")?;
+            }
+
+            write!(f, "{:x}", segments[index])?;
+
+            index += 1;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for EfiIPv6AddressRaw {
+    type Err = EfiIPAddressParseError;
+
+    fn from_str(string: &str) -> Result<Self, <Self as FromStr>::Err> {
+        fn parse_groups(
+            string: &str,
+            groups: &mut [u16; 8],
+            allow_ip_v4: bool,
+        ) -> Result<usize, EfiIPAddressParseError> {
+            if string.is_empty() {
+                return Ok(0);
+            }
+
+            let mut count: usize = 0;
+
+            let mut parts = string.split(':').peekable();
+
+            while let Some(part) = parts.next() {
+                /* Embedded IPv4 address is only allowed as the last part */
+                if allow_ip_v4 && parts.peek().is_none() && part.contains('.') {
+                    if count + 2 > groups.len() {
+                        return Err(EfiIPAddressParseError);
+                    }
+
+                    let address: EfiIPv4AddressRaw = part.parse()?;
+
+                    groups[count] = u16::from_be_bytes([address[0], address[1]]);
+                    groups[count + 1] = u16::from_be_bytes([address[2], address[3]]);
+
+                    count += 2;
+
+                    break;
+                }
+
+                if count == groups.len()
+                    || part.is_empty()
+                    || part.len() > 4
+                    || !part.bytes().all(|byte: u8| byte.is_ascii_hexdigit())
+                {
+                    return Err(EfiIPAddressParseError);
+                }
+
+                groups[count] =
+                    u16::from_str_radix(part, 16).map_err(|_| EfiIPAddressParseError)?;
+
+                count += 1;
+            }
+
+            Ok(count)
+        }
+
+        let mut segments: [u16; 8] = [0; 8];
+
+        if let Some(index) = string.find("::") {
+            let (head, tail): (&str, &str) = (&string[..index], &string[(index + 2)..]);
+
+            if tail.contains("::") {
+                return Err(EfiIPAddressParseError);
+            }
+
+            let (mut head_groups, mut tail_groups): ([u16; 8], [u16; 8]) = ([0; 8], [0; 8]);
+
+            let head_count: usize = parse_groups(head, &mut head_groups, false)?;
+            let tail_count: usize = parse_groups(tail, &mut tail_groups, true)?;
+
+            /* "::" has to replace at least one segment */
+            if head_count + tail_count >= segments.len() {
+                return Err(EfiIPAddressParseError);
+            }
+
+            segments[..head_count].copy_from_slice(&head_groups[..head_count]);
+            segments[(head_groups.len() - tail_count)..]
+                .copy_from_slice(&tail_groups[..tail_count]);
+        } else if parse_groups(string, &mut segments, true)? != segments.len() {
+            return Err(EfiIPAddressParseError);
+        }
+
+        Ok(Self::from_segments(segments))
+    }
+}
+
+/// Implementation of EFI's `EFI_IP_ADDRESS`.
 #[repr(C)]
 #[derive(Clone, Copy)]
 pub(crate) union EfiIPAddressRaw {
+    addr: [u32; 4],
     ip_v4: EfiIPv4AddressRaw,
     ip_v6: EfiIPv6AddressRaw,
 }
 
+impl EfiIPAddressRaw {
+    pub(crate) const fn zeroed() -> Self {
+        Self { addr: [0; 4] }
+    }
+
+    pub(crate) fn ip_v4(&self) -> EfiIPv4AddressRaw {
+        unsafe { self.ip_v4 }
+    }
+
+    pub(crate) fn ip_v6(&self) -> EfiIPv6AddressRaw {
+        unsafe { self.ip_v6 }
+    }
+}
+
+impl From<EfiIPAddress> for EfiIPAddressRaw {
+    fn from(address: EfiIPAddress) -> Self {
+        let mut raw: Self = Self::zeroed();
+
+        match address {
+            EfiIPAddress::IPv4(address) => raw.ip_v4 = address,
+            EfiIPAddress::IPv6(address) => raw.ip_v6 = address,
+        }
+
+        raw
+    }
+}
+
 #[repr(C)]
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub enum EfiIPAddress {
     IPv4(EfiIPv4AddressRaw),
     IPv6(EfiIPv6AddressRaw),
 }
+
+impl Display for EfiIPAddress {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::IPv4(address) => Display::fmt(address, f),
+            Self::IPv6(address) => Display::fmt(address, f),
+        }
+    }
+}
+
+impl FromStr for EfiIPAddress {
+    type Err = EfiIPAddressParseError;
+
+    fn from_str(string: &str) -> Result<Self, <Self as FromStr>::Err> {
+        if string.contains(':') {
+            string.parse().map(Self::IPv6)
+        } else {
+            string.parse().map(Self::IPv4)
+        }
+    }
+}
+
+/// Implementation of EFI's `EFI_MAC_ADDRESS`.
+#[repr(transparent)]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct EfiMacAddress {
+    address: [u8; 32],
+}
+
+impl EfiMacAddress {
+    pub const fn new(address: [u8; 32]) -> Self {
+        Self { address }
+    }
+}
+
+impl Deref for EfiMacAddress {
+    type Target = [u8; 32];
+
+    fn deref(&self) -> &<Self as Deref>::Target {
+        &self.address
+    }
+}
+
+impl DerefMut for EfiMacAddress {
+    fn deref_mut(&mut self) -> &mut <Self as Deref>::Target {
+        &mut self.address
+    }
+}
+
+/// Describes a single fragment of a packet's data.
+///
+/// Shared by the TCP and UDP protocols' transmit and receive data structures.
+#[repr(C)]
+pub struct EfiFragmentData<'a> {
+    length: u32,
+    buffer: VoidMutPtr,
+    _lifetime: PhantomData<&'a mut [u8]>,
+}
+
+impl<'a> EfiFragmentData<'a> {
+    /// Creates a fragment that data can be received into.
+    pub fn new(buffer: &'a mut [u8]) -> Self {
+        Self {
+            length: buffer.len() as u32,
+            buffer: buffer.as_mut_ptr() as VoidMutPtr,
+            _lifetime: PhantomData,
+        }
+    }
+
+    /// Creates a fragment that's data will be transmitted.
+    pub fn new_transmit(buffer: &'a [u8]) -> Self {
+        Self {
+            length: buffer.len() as u32,
+            buffer: buffer.as_ptr() as VoidMutPtr,
+            _lifetime: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.length as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    pub fn data(&self) -> &[u8] {
+        if self.buffer.is_null() {
+            &[]
+        } else {
+            unsafe { from_raw_parts(self.buffer as *const u8, self.length as usize) }
+        }
+    }
+}
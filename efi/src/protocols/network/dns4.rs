@@ -0,0 +1,371 @@
+use {
+    super::common::structs::EfiIPv4AddressRaw,
+    crate::{
+        guid::EfiGuid,
+        protocols::{service_binding::EfiServiceBinding, EfiProtocol},
+        status::{EfiStatus, EfiStatusEnum},
+        types::{EfiEvent, NonNullVoidPtr, VoidMutPtr},
+        utilities::{string_from_raw, validate_string, RawUtf16StringError},
+    },
+    core::{marker::PhantomData, ptr::null_mut, slice::from_raw_parts},
+};
+
+/// Implementation of EFI's `EFI_DNS4_PROTOCOL`.
+///
+/// Instances are created through [`EfiServiceBindingProtocol<EfiDns4Protocol>`](crate::protocols::service_binding::EfiServiceBindingProtocol).
+#[repr(C)]
+pub struct EfiDns4Protocol {
+    get_mode_data: extern "efiapi" fn(*const Self, *mut EfiDns4ModeData) -> EfiStatus,
+    configure: extern "efiapi" fn(*const Self, *const EfiDns4ConfigData) -> EfiStatus,
+    host_name_to_ip:
+        extern "efiapi" fn(*const Self, *const u16, *mut EfiDns4CompletionToken) -> EfiStatus,
+    ip_to_host_name: extern "efiapi" fn(
+        *const Self,
+        EfiIPv4AddressRaw,
+        *mut EfiDns4CompletionToken,
+    ) -> EfiStatus,
+    general_look_up: extern "efiapi" fn(
+        *const Self,
+        *const u8,
+        u16,
+        u16,
+        *mut EfiDns4CompletionToken,
+    ) -> EfiStatus,
+    update_dns_cache: extern "efiapi" fn(*const Self, bool, bool, EfiDns4CacheEntry) -> EfiStatus,
+    poll: extern "efiapi" fn(*const Self) -> EfiStatus,
+    cancel: extern "efiapi" fn(*const Self, *mut EfiDns4CompletionToken) -> EfiStatus,
+}
+
+impl EfiDns4Protocol {
+    /// Returns the instance's current mode data.
+    ///
+    /// The server and cache lists are allocated from pool memory and have to be released with [`free_pool`](crate::boot_services::EfiBootServices1x0::free_pool).
+    pub fn get_mode_data(&self) -> EfiStatusEnum<EfiDns4ModeData> {
+        let mut mode_data: EfiDns4ModeData = EfiDns4ModeData {
+            config_data: EfiDns4ConfigData::new_default(&[]),
+            dns_server_count: 0,
+            dns_server_list: 0 as _,
+            dns_cache_count: 0,
+            dns_cache_list: 0 as _,
+        };
+
+        (self.get_mode_data)(self, &mut mode_data).into_enum_data(|| mode_data)
+    }
+
+    /// Configures the instance. Passing `None` resets the instance.
+    pub fn configure(&self, config_data: Option<&EfiDns4ConfigData>) -> EfiStatusEnum {
+        (self.configure)(
+            self,
+            config_data.map_or(0 as _, |config_data: &EfiDns4ConfigData| config_data),
+        )
+        .into_enum()
+    }
+
+    /// Queues a host name resolution. The host name has to be a null-terminated UTF-16 string.
+    ///
+    /// The resolved addresses are available through [`EfiDns4CompletionToken::host_to_address_data`].
+    /// # Safety
+    /// The token must not be moved or dropped until it's event is signalled or the operation is cancelled.
+    pub unsafe fn host_name_to_ip(
+        &self,
+        host_name: &[u16],
+        token: &mut EfiDns4CompletionToken,
+    ) -> Result<EfiStatusEnum, RawUtf16StringError> {
+        validate_string(host_name)
+            .map(|()| (self.host_name_to_ip)(self, host_name.as_ptr(), token).into_enum())
+    }
+
+    /// Queues a reverse resolution.
+    ///
+    /// The resolved name is available through [`EfiDns4CompletionToken::address_to_host_data`].
+    /// # Safety
+    /// The token must not be moved or dropped until it's event is signalled or the operation is cancelled.
+    pub unsafe fn ip_to_host_name(
+        &self,
+        ip_address: EfiIPv4AddressRaw,
+        token: &mut EfiDns4CompletionToken,
+    ) -> EfiStatusEnum {
+        (self.ip_to_host_name)(self, ip_address, token).into_enum()
+    }
+
+    pub fn poll(&self) -> EfiStatusEnum {
+        (self.poll)(self).into_enum()
+    }
+
+    /// Cancels a queued token. When `None` is passed, all queued tokens are cancelled.
+    pub fn cancel(&self, token: Option<&mut EfiDns4CompletionToken>) -> EfiStatusEnum {
+        (self.cancel)(
+            self,
+            token.map_or(null_mut(), |token: &mut EfiDns4CompletionToken| token),
+        )
+        .into_enum()
+    }
+}
+
+impl EfiProtocol for EfiDns4Protocol {
+    type Parsed = &'static Self;
+    type Error = !;
+
+    fn guid() -> EfiGuid {
+        crate::guids::EFI_DNS4_PROTOCOL
+    }
+
+    unsafe fn parse(
+        ptr: NonNullVoidPtr,
+    ) -> Result<<Self as EfiProtocol>::Parsed, <Self as EfiProtocol>::Error> {
+        Ok(&*ptr.cast().as_ptr())
+    }
+}
+
+impl EfiServiceBinding for EfiDns4Protocol {
+    fn service_binding_guid() -> EfiGuid {
+        crate::guids::EFI_DNS4_SERVICE_BINDING_PROTOCOL
+    }
+}
+
+#[repr(C)]
+pub struct EfiDns4ConfigData<'a> {
+    dns_server_list_count: usize,
+    dns_server_list: *const EfiIPv4AddressRaw,
+    use_default_setting: bool,
+    enable_dns_cache: bool,
+    protocol: u8,
+    station_ip: EfiIPv4AddressRaw,
+    subnet_mask: EfiIPv4AddressRaw,
+    local_port: u16,
+    retry_count: u32,
+    retry_interval: u32,
+    _lifetime: PhantomData<&'a [EfiIPv4AddressRaw]>,
+}
+
+impl<'a> EfiDns4ConfigData<'a> {
+    /* UDP's IANA protocol number */
+    const PROTOCOL_UDP: u8 = 17;
+
+    /// Creates configuration that uses the address configured through `EFI_IP4_CONFIG2_PROTOCOL`.
+    ///
+    /// When `dns_server_list` is empty, the servers provided by DHCP are used.
+    pub fn new_default(dns_server_list: &'a [EfiIPv4AddressRaw]) -> Self {
+        Self {
+            dns_server_list_count: dns_server_list.len(),
+            dns_server_list: if dns_server_list.is_empty() {
+                0 as _
+            } else {
+                dns_server_list.as_ptr()
+            },
+            use_default_setting: true,
+            enable_dns_cache: true,
+            protocol: Self::PROTOCOL_UDP,
+            station_ip: EfiIPv4AddressRaw::new([0; 4]),
+            subnet_mask: EfiIPv4AddressRaw::new([0; 4]),
+            local_port: 0,
+            retry_count: 0,
+            retry_interval: 0,
+            _lifetime: PhantomData,
+        }
+    }
+
+    pub fn new(
+        dns_server_list: &'a [EfiIPv4AddressRaw],
+        station_ip: EfiIPv4AddressRaw,
+        subnet_mask: EfiIPv4AddressRaw,
+    ) -> Self {
+        Self {
+            use_default_setting: false,
+            station_ip,
+            subnet_mask,
+            ..Self::new_default(dns_server_list)
+        }
+    }
+
+    pub fn dns_server_list(&self) -> &[EfiIPv4AddressRaw] {
+        if self.dns_server_list.is_null() {
+            &[]
+        } else {
+            unsafe { from_raw_parts(self.dns_server_list, self.dns_server_list_count) }
+        }
+    }
+
+    pub fn use_default_setting(&self) -> bool {
+        self.use_default_setting
+    }
+
+    pub fn enable_dns_cache(&self) -> bool {
+        self.enable_dns_cache
+    }
+
+    pub fn set_enable_dns_cache(&mut self, enable_dns_cache: bool) {
+        self.enable_dns_cache = enable_dns_cache;
+    }
+
+    pub fn station_ip(&self) -> EfiIPv4AddressRaw {
+        self.station_ip
+    }
+
+    pub fn subnet_mask(&self) -> EfiIPv4AddressRaw {
+        self.subnet_mask
+    }
+
+    pub fn local_port(&self) -> u16 {
+        self.local_port
+    }
+
+    pub fn set_local_port(&mut self, local_port: u16) {
+        self.local_port = local_port;
+    }
+
+    pub fn retry_count(&self) -> u32 {
+        self.retry_count
+    }
+
+    pub fn set_retry_count(&mut self, retry_count: u32) {
+        self.retry_count = retry_count;
+    }
+
+    /// Retry interval in seconds.
+    pub fn retry_interval(&self) -> u32 {
+        self.retry_interval
+    }
+
+    pub fn set_retry_interval(&mut self, retry_interval: u32) {
+        self.retry_interval = retry_interval;
+    }
+}
+
+#[repr(C)]
+pub struct EfiDns4ModeData {
+    config_data: EfiDns4ConfigData<'static>,
+    dns_server_count: u32,
+    dns_server_list: *const EfiIPv4AddressRaw,
+    dns_cache_count: u32,
+    dns_cache_list: *const EfiDns4CacheEntry,
+}
+
+impl EfiDns4ModeData {
+    pub fn config_data(&self) -> &EfiDns4ConfigData<'static> {
+        &self.config_data
+    }
+
+    pub fn dns_server_list(&self) -> &'static [EfiIPv4AddressRaw] {
+        if self.dns_server_list.is_null() {
+            &[]
+        } else {
+            unsafe { from_raw_parts(self.dns_server_list, self.dns_server_count as usize) }
+        }
+    }
+
+    pub fn dns_cache_list(&self) -> &'static [EfiDns4CacheEntry] {
+        if self.dns_cache_list.is_null() {
+            &[]
+        } else {
+            unsafe { from_raw_parts(self.dns_cache_list, self.dns_cache_count as usize) }
+        }
+    }
+}
+
+#[repr(C)]
+pub struct EfiDns4CacheEntry {
+    host_name: *const u16,
+    ip_address: *const EfiIPv4AddressRaw,
+    timeout: u32,
+}
+
+impl EfiDns4CacheEntry {
+    pub fn host_name(&self) -> Result<&[u16], RawUtf16StringError> {
+        unsafe { string_from_raw(self.host_name) }
+    }
+
+    pub fn ip_address(&self) -> Option<EfiIPv4AddressRaw> {
+        if self.ip_address.is_null() {
+            None
+        } else {
+            Some(unsafe { *self.ip_address })
+        }
+    }
+
+    /// Time in seconds until the entry expires.
+    pub fn timeout(&self) -> u32 {
+        self.timeout
+    }
+}
+
+/// Completion token used by [`EfiDns4Protocol`]'s asynchronous operations.
+#[repr(C)]
+pub struct EfiDns4CompletionToken {
+    event: EfiEvent,
+    status: EfiStatus,
+    retry_count: u32,
+    retry_interval: u32,
+    response_data: VoidMutPtr,
+}
+
+impl EfiDns4CompletionToken {
+    /// Creates a token. Zero `retry_count` and `retry_interval` use the configured values.
+    pub fn new(event: EfiEvent, retry_count: u32, retry_interval: u32) -> Self {
+        Self {
+            event,
+            status: EfiStatus::success(),
+            retry_count,
+            retry_interval,
+            response_data: null_mut(),
+        }
+    }
+
+    pub fn event(&self) -> EfiEvent {
+        self.event
+    }
+
+    /// Returns the completion status. Only valid after the token's event is signalled.
+    pub fn status(&self) -> EfiStatusEnum {
+        self.status.into_enum()
+    }
+
+    /// Returns the result of [`EfiDns4Protocol::host_name_to_ip`].
+    ///
+    /// The data is allocated from pool memory and has to be released with [`free_pool`](crate::boot_services::EfiBootServices1x0::free_pool).
+    pub fn host_to_address_data(&self) -> Option<&'static EfiDns4HostToAddressData> {
+        if self.response_data.is_null() {
+            None
+        } else {
+            Some(unsafe { &*(self.response_data as *const EfiDns4HostToAddressData) })
+        }
+    }
+
+    /// Returns the result of [`EfiDns4Protocol::ip_to_host_name`].
+    ///
+    /// The data is allocated from pool memory and has to be released with [`free_pool`](crate::boot_services::EfiBootServices1x0::free_pool).
+    pub fn address_to_host_data(&self) -> Option<&'static EfiDns4AddressToHostData> {
+        if self.response_data.is_null() {
+            None
+        } else {
+            Some(unsafe { &*(self.response_data as *const EfiDns4AddressToHostData) })
+        }
+    }
+}
+
+#[repr(C)]
+pub struct EfiDns4HostToAddressData {
+    ip_count: u32,
+    ip_list: *const EfiIPv4AddressRaw,
+}
+
+impl EfiDns4HostToAddressData {
+    pub fn ip_list(&self) -> &[EfiIPv4AddressRaw] {
+        if self.ip_list.is_null() {
+            &[]
+        } else {
+            unsafe { from_raw_parts(self.ip_list, self.ip_count as usize) }
+        }
+    }
+}
+
+#[repr(C)]
+pub struct EfiDns4AddressToHostData {
+    host_name: *const u16,
+}
+
+impl EfiDns4AddressToHostData {
+    pub fn host_name(&self) -> Result<&[u16], RawUtf16StringError> {
+        unsafe { string_from_raw(self.host_name) }
+    }
+}
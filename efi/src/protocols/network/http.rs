@@ -0,0 +1,483 @@
+use {
+    super::common::structs::{EfiIPv4AddressRaw, EfiIPv6AddressRaw},
+    crate::{
+        guid::EfiGuid,
+        protocols::{service_binding::EfiServiceBinding, EfiProtocol},
+        status::{EfiStatus, EfiStatusEnum, EfiStatusError},
+        types::{EfiEvent, NonNullVoidPtr, VoidMutPtr, VoidPtr},
+        utilities::{validate_string, RawUtf16StringError},
+    },
+    core::{marker::PhantomData, ptr::null_mut, slice::from_raw_parts},
+};
+
+/// Implementation of EFI's `EFI_HTTP_PROTOCOL`.
+///
+/// Instances are created through [`EfiServiceBindingProtocol<EfiHttpProtocol>`](crate::protocols::service_binding::EfiServiceBindingProtocol).
+#[repr(C)]
+pub struct EfiHttpProtocol {
+    get_mode_data: extern "efiapi" fn(*const Self, *mut EfiHttpConfigData) -> EfiStatus,
+    configure: extern "efiapi" fn(*const Self, *const EfiHttpConfigData) -> EfiStatus,
+    request: extern "efiapi" fn(*const Self, *mut EfiHttpToken) -> EfiStatus,
+    cancel: extern "efiapi" fn(*const Self, *mut EfiHttpToken) -> EfiStatus,
+    response: extern "efiapi" fn(*const Self, *mut EfiHttpToken) -> EfiStatus,
+    poll: extern "efiapi" fn(*const Self) -> EfiStatus,
+}
+
+impl EfiHttpProtocol {
+    /// Retrieves the current configuration.
+    ///
+    /// The access point is written to `access_point`, which has to match the configured address family.
+    /// Values which aren't valid HTTP versions are reported as `EfiDeviceError`.
+    pub fn get_mode_data<'a>(
+        &self,
+        access_point: EfiHttpAccessPoint<'a>,
+    ) -> EfiStatusEnum<EfiHttpConfigData<'a>> {
+        let mut config_data: EfiHttpConfigData =
+            EfiHttpConfigData::new(EfiHttpVersion::Unsupported, 0, access_point);
+
+        match (self.get_mode_data)(self, &mut config_data).into_enum() {
+            EfiStatusEnum::Success(()) if config_data.is_valid() => {
+                EfiStatusEnum::Success(config_data)
+            }
+            EfiStatusEnum::Warning(status, ()) if config_data.is_valid() => {
+                EfiStatusEnum::Warning(status, config_data)
+            }
+            EfiStatusEnum::Success(()) | EfiStatusEnum::Warning(..) => {
+                EfiStatusEnum::Error(EfiStatusError::EfiDeviceError, ())
+            }
+            EfiStatusEnum::Error(status, ()) => EfiStatusEnum::Error(status, ()),
+        }
+    }
+
+    /// Configures the instance. Passing `None` resets the instance to it's unconfigured state.
+    pub fn configure(&self, config_data: Option<&EfiHttpConfigData>) -> EfiStatusEnum {
+        (self.configure)(
+            self,
+            config_data.map_or(0 as _, |config_data: &EfiHttpConfigData| config_data),
+        )
+        .into_enum()
+    }
+
+    /// Queues a request. The token's event is signalled when the request is sent.
+    /// # Safety
+    /// The token and all buffers it references must not be moved or dropped until the token's event is signalled or the request is cancelled.
+    pub unsafe fn request(&self, token: &mut EfiHttpToken) -> EfiStatusEnum {
+        (self.request)(self, token).into_enum()
+    }
+
+    /// Queues a response. The token's event is signalled when the response is received.
+    ///
+    /// The first response to a request receives the status code and the headers, while later ones only receive body data.
+    /// # Safety
+    /// The token and all buffers it references must not be moved or dropped until the token's event is signalled or the response is cancelled.
+    pub unsafe fn response(&self, token: &mut EfiHttpToken) -> EfiStatusEnum {
+        (self.response)(self, token).into_enum()
+    }
+
+    /// Cancels a queued token. When `None` is passed, all queued tokens are cancelled.
+    pub fn cancel(&self, token: Option<&mut EfiHttpToken>) -> EfiStatusEnum {
+        (self.cancel)(
+            self,
+            token.map_or(null_mut(), |token: &mut EfiHttpToken| token),
+        )
+        .into_enum()
+    }
+
+    /// Polls the underlying network drivers for incoming and outgoing data.
+    pub fn poll(&self) -> EfiStatusEnum {
+        (self.poll)(self).into_enum()
+    }
+}
+
+impl EfiProtocol for EfiHttpProtocol {
+    type Parsed = &'static Self;
+    type Error = !;
+
+    fn guid() -> EfiGuid {
+        crate::guids::EFI_HTTP_PROTOCOL
+    }
+
+    unsafe fn parse(
+        ptr: NonNullVoidPtr,
+    ) -> Result<<Self as EfiProtocol>::Parsed, <Self as EfiProtocol>::Error> {
+        Ok(&*ptr.cast().as_ptr())
+    }
+}
+
+impl EfiServiceBinding for EfiHttpProtocol {
+    fn service_binding_guid() -> EfiGuid {
+        crate::guids::EFI_HTTP_SERVICE_BINDING_PROTOCOL
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum EfiHttpVersion {
+    Http10,
+    Http11,
+    Unsupported,
+}
+
+impl EfiHttpVersion {
+    fn from_raw(http_version: u32) -> Option<Self> {
+        match http_version {
+            0 => Some(Self::Http10),
+            1 => Some(Self::Http11),
+            2 => Some(Self::Unsupported),
+            _ => None,
+        }
+    }
+}
+
+#[repr(C)]
+pub struct EfiHttpConfigData<'a> {
+    /* Written by the firmware, so it's only converted once it's validated */
+    http_version: u32,
+    timeout_milliseconds: u32,
+    local_address_is_ip_v6: bool,
+    access_point: VoidMutPtr,
+    _lifetime: PhantomData<EfiHttpAccessPoint<'a>>,
+}
+
+impl<'a> EfiHttpConfigData<'a> {
+    pub fn new(
+        http_version: EfiHttpVersion,
+        timeout_milliseconds: u32,
+        access_point: EfiHttpAccessPoint<'a>,
+    ) -> Self {
+        let (local_address_is_ip_v6, access_point): (bool, VoidMutPtr) = match access_point {
+            EfiHttpAccessPoint::IPv4(access_point) => (
+                false,
+                access_point as *mut EfiHttpV4AccessPoint as VoidMutPtr,
+            ),
+            EfiHttpAccessPoint::IPv6(access_point) => (
+                true,
+                access_point as *mut EfiHttpV6AccessPoint as VoidMutPtr,
+            ),
+        };
+
+        Self {
+            http_version: http_version as u32,
+            timeout_milliseconds,
+            local_address_is_ip_v6,
+            access_point,
+            _lifetime: PhantomData,
+        }
+    }
+
+    pub fn http_version(&self) -> EfiHttpVersion {
+        EfiHttpVersion::from_raw(self.http_version).unwrap_or(EfiHttpVersion::Unsupported)
+    }
+
+    pub fn timeout_milliseconds(&self) -> u32 {
+        self.timeout_milliseconds
+    }
+
+    pub fn local_address_is_ip_v6(&self) -> bool {
+        self.local_address_is_ip_v6
+    }
+
+    fn is_valid(&self) -> bool {
+        EfiHttpVersion::from_raw(self.http_version).is_some()
+    }
+}
+
+pub enum EfiHttpAccessPoint<'a> {
+    IPv4(&'a mut EfiHttpV4AccessPoint),
+    IPv6(&'a mut EfiHttpV6AccessPoint),
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct EfiHttpV4AccessPoint {
+    use_default_address: bool,
+    local_address: EfiIPv4AddressRaw,
+    local_subnet: EfiIPv4AddressRaw,
+    local_port: u16,
+}
+
+impl EfiHttpV4AccessPoint {
+    /// Creates an access point that uses the address configured through `EFI_IP4_CONFIG2_PROTOCOL`.
+    pub fn new_default(local_port: u16) -> Self {
+        Self {
+            use_default_address: true,
+            local_address: EfiIPv4AddressRaw::new([0; 4]),
+            local_subnet: EfiIPv4AddressRaw::new([0; 4]),
+            local_port,
+        }
+    }
+
+    pub fn new(
+        local_address: EfiIPv4AddressRaw,
+        local_subnet: EfiIPv4AddressRaw,
+        local_port: u16,
+    ) -> Self {
+        Self {
+            use_default_address: false,
+            local_address,
+            local_subnet,
+            local_port,
+        }
+    }
+
+    pub fn use_default_address(&self) -> bool {
+        self.use_default_address
+    }
+
+    pub fn local_address(&self) -> EfiIPv4AddressRaw {
+        self.local_address
+    }
+
+    pub fn local_subnet(&self) -> EfiIPv4AddressRaw {
+        self.local_subnet
+    }
+
+    pub fn local_port(&self) -> u16 {
+        self.local_port
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct EfiHttpV6AccessPoint {
+    local_address: EfiIPv6AddressRaw,
+    local_port: u16,
+}
+
+impl EfiHttpV6AccessPoint {
+    pub fn new(local_address: EfiIPv6AddressRaw, local_port: u16) -> Self {
+        Self {
+            local_address,
+            local_port,
+        }
+    }
+
+    pub fn local_address(&self) -> EfiIPv6AddressRaw {
+        self.local_address
+    }
+
+    pub fn local_port(&self) -> u16 {
+        self.local_port
+    }
+}
+
+/// Completion token used by [`EfiHttpProtocol`]'s asynchronous operations.
+#[repr(C)]
+pub struct EfiHttpToken<'a> {
+    event: EfiEvent,
+    status: EfiStatus,
+    message: *mut EfiHttpMessage<'a>,
+    _lifetime: PhantomData<&'a mut EfiHttpMessage<'a>>,
+}
+
+impl<'a> EfiHttpToken<'a> {
+    pub fn new(event: EfiEvent, message: &'a mut EfiHttpMessage<'a>) -> Self {
+        Self {
+            event,
+            status: EfiStatus::success(),
+            message,
+            _lifetime: PhantomData,
+        }
+    }
+
+    pub fn event(&self) -> EfiEvent {
+        self.event
+    }
+
+    /// Returns the completion status. Only valid after the token's event is signalled.
+    pub fn status(&self) -> EfiStatusEnum {
+        self.status.into_enum()
+    }
+
+    pub fn message(&self) -> &EfiHttpMessage<'a> {
+        unsafe { &*self.message }
+    }
+}
+
+#[repr(C)]
+pub struct EfiHttpMessage<'a> {
+    data: VoidMutPtr,
+    header_count: usize,
+    headers: *const EfiHttpHeader<'a>,
+    body_length: usize,
+    body: VoidMutPtr,
+    _lifetime: PhantomData<&'a mut [u8]>,
+}
+
+impl<'a> EfiHttpMessage<'a> {
+    pub fn new_request(
+        request: &'a EfiHttpRequestData<'a>,
+        headers: &'a [EfiHttpHeader<'a>],
+        body: Option<&'a [u8]>,
+    ) -> Self {
+        let (body, body_length): (VoidMutPtr, usize) = if let Some(body) = body {
+            (body.as_ptr() as VoidMutPtr, body.len())
+        } else {
+            (null_mut(), 0)
+        };
+
+        Self {
+            data: request as *const EfiHttpRequestData as VoidMutPtr,
+            header_count: headers.len(),
+            headers: headers.as_ptr(),
+            body_length,
+            body,
+            _lifetime: PhantomData,
+        }
+    }
+
+    /// Creates a message that receives a response.
+    ///
+    /// When `response` is `None`, only body data is received.
+    pub fn new_response(response: Option<&'a mut EfiHttpResponseData>, body: &'a mut [u8]) -> Self {
+        Self {
+            data: response.map_or(null_mut(), |response: &mut EfiHttpResponseData| {
+                response as *mut EfiHttpResponseData as VoidMutPtr
+            }),
+            header_count: 0,
+            headers: 0 as _,
+            body_length: body.len(),
+            body: body.as_mut_ptr() as VoidMutPtr,
+            _lifetime: PhantomData,
+        }
+    }
+
+    /// Returns the message's headers.
+    ///
+    /// Headers of a received response are allocated from pool memory and have to be released with [`free_pool`](crate::boot_services::EfiBootServices1x0::free_pool).
+    pub fn headers(&self) -> &[EfiHttpHeader<'a>] {
+        if self.headers.is_null() {
+            &[]
+        } else {
+            unsafe { from_raw_parts(self.headers, self.header_count) }
+        }
+    }
+
+    pub fn headers_ptr(&self) -> VoidPtr {
+        self.headers as VoidPtr
+    }
+
+    /// Returns the length of the body. After a response is received, this is the length of the received data.
+    pub fn body_length(&self) -> usize {
+        self.body_length
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum EfiHttpMethod {
+    Get,
+    Post,
+    Patch,
+    Options,
+    Connect,
+    Head,
+    Put,
+    Delete,
+    Trace,
+}
+
+#[repr(C)]
+pub struct EfiHttpRequestData<'a> {
+    method: EfiHttpMethod,
+    url: *const u16,
+    _lifetime: PhantomData<&'a [u16]>,
+}
+
+impl<'a> EfiHttpRequestData<'a> {
+    /// Creates request data. The URL has to be a null-terminated UTF-16 string.
+    pub fn new(method: EfiHttpMethod, url: &'a [u16]) -> Result<Self, RawUtf16StringError> {
+        validate_string(url).map(|()| Self {
+            method,
+            url: url.as_ptr(),
+            _lifetime: PhantomData,
+        })
+    }
+
+    pub fn method(&self) -> EfiHttpMethod {
+        self.method
+    }
+}
+
+#[repr(C)]
+pub struct EfiHttpResponseData {
+    status_code: u32,
+}
+
+impl EfiHttpResponseData {
+    pub fn new() -> Self {
+        Self { status_code: 0 }
+    }
+
+    /// Returns the numeric HTTP status code of the response.
+    ///
+    /// Returns `None` when the firmware couldn't map the status code.
+    pub fn status_code(&self) -> Option<u16> {
+        const STATUS_CODES: [u16; 42] = [
+            0, 100, 101, 200, 201, 202, 203, 204, 205, 206, 300, 301, 302, 303, 304, 305, 307, 400,
+            401, 402, 403, 404, 405, 406, 407, 408, 409, 410, 411, 412, 413, 414, 415, 416, 417,
+            500, 501, 502, 503, 504, 505, 308,
+        ];
+
+        match STATUS_CODES.get(self.status_code as usize) {
+            None | Some(0) => None,
+            Some(&status_code) => Some(status_code),
+        }
+    }
+}
+
+impl Default for EfiHttpResponseData {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[repr(C)]
+pub struct EfiHttpHeader<'a> {
+    field_name: *const u8,
+    field_value: *const u8,
+    _lifetime: PhantomData<&'a [u8]>,
+}
+
+impl<'a> EfiHttpHeader<'a> {
+    /// Creates a header. Both the name and the value have to be null-terminated ASCII strings.
+    pub fn new(field_name: &'a [u8], field_value: &'a [u8]) -> Option<Self> {
+        if field_name.last() == Some(&0) && field_value.last() == Some(&0) {
+            Some(Self {
+                field_name: field_name.as_ptr(),
+                field_value: field_value.as_ptr(),
+                _lifetime: PhantomData,
+            })
+        } else {
+            None
+        }
+    }
+
+    fn string(string: *const u8) -> &'a [u8] {
+        if string.is_null() {
+            &[]
+        } else {
+            unsafe {
+                let mut length: usize = 0;
+
+                while *string.add(length) != 0 {
+                    length += 1;
+                }
+
+                from_raw_parts(string, length)
+            }
+        }
+    }
+
+    /// Returns the field's name without the null-terminator.
+    pub fn field_name(&self) -> &'a [u8] {
+        Self::string(self.field_name)
+    }
+
+    /// Returns the field's value without the null-terminator.
+    pub fn field_value(&self) -> &'a [u8] {
+        Self::string(self.field_value)
+    }
+}
@@ -0,0 +1,352 @@
+use {
+    super::common::structs::{EfiIPv4AddressRaw, EfiMacAddress},
+    crate::{
+        guid::EfiGuid,
+        protocols::EfiProtocol,
+        status::{EfiStatus, EfiStatusEnum, EfiStatusError},
+        types::{EfiEvent, NonNullVoidPtr, VoidMutPtr, VoidPtr},
+        utilities::{string_from_raw, RawUtf16StringError},
+    },
+    core::{
+        mem::{size_of, size_of_val},
+        slice::{from_raw_parts, from_raw_parts_mut},
+    },
+};
+
+/// Implementation of EFI's `EFI_IP4_CONFIG2_PROTOCOL`.
+#[repr(C)]
+pub struct EfiIp4Config2Protocol {
+    set_data: extern "efiapi" fn(*const Self, EfiIp4Config2DataType, usize, VoidPtr) -> EfiStatus,
+    get_data:
+        extern "efiapi" fn(*const Self, EfiIp4Config2DataType, *mut usize, VoidMutPtr) -> EfiStatus,
+    register_data_notify:
+        extern "efiapi" fn(*const Self, EfiIp4Config2DataType, EfiEvent) -> EfiStatus,
+    unregister_data_notify:
+        extern "efiapi" fn(*const Self, EfiIp4Config2DataType, EfiEvent) -> EfiStatus,
+}
+
+impl EfiIp4Config2Protocol {
+    /// Sets raw configuration data of the given type.
+    pub fn set_data(&self, data_type: EfiIp4Config2DataType, data: &[u8]) -> EfiStatusEnum {
+        (self.set_data)(self, data_type, data.len(), data.as_ptr() as VoidPtr).into_enum()
+    }
+
+    /// Reads raw configuration data of the given type into `buffer`.
+    ///
+    /// Returns the size of the data on success and the required size when the buffer is too small.
+    pub fn get_data(
+        &self,
+        data_type: EfiIp4Config2DataType,
+        buffer: &mut [u8],
+    ) -> EfiStatusEnum<usize, usize> {
+        let mut size: usize = buffer.len();
+
+        (self.get_data)(
+            self,
+            data_type,
+            &mut size,
+            buffer.as_mut_ptr() as VoidMutPtr,
+        )
+        .into_enum_data_error(|| size, || size)
+    }
+
+    /// Reads the interface information.
+    ///
+    /// The route table is stored past the structure, so `buffer` has to be larger than [`EfiIp4Config2InterfaceInfo`].
+    /// Returns the required size in bytes when the buffer is too small.
+    pub fn interface_info<'a>(
+        &self,
+        buffer: &'a mut [u64],
+    ) -> EfiStatusEnum<&'a EfiIp4Config2InterfaceInfo, usize> {
+        let mut size: usize = size_of_val(buffer);
+        let info: *mut EfiIp4Config2InterfaceInfo = buffer.as_mut_ptr().cast();
+
+        if size < size_of::<EfiIp4Config2InterfaceInfo>() {
+            size = 0;
+        }
+
+        (self.get_data)(
+            self,
+            EfiIp4Config2DataType::InterfaceInfo,
+            &mut size,
+            info as VoidMutPtr,
+        )
+        .into_enum_data_error(|| unsafe { &*info }, || size)
+    }
+
+    /// Returns the policy. Values which aren't valid policies are reported as `EfiDeviceError`.
+    pub fn policy(&self) -> EfiStatusEnum<EfiIp4Config2Policy> {
+        let mut policy: u32 = 0;
+        let mut size: usize = size_of::<u32>();
+
+        match (self.get_data)(
+            self,
+            EfiIp4Config2DataType::Policy,
+            &mut size,
+            &mut policy as *mut u32 as VoidMutPtr,
+        )
+        .into_enum_data(|| EfiIp4Config2Policy::from_raw(policy))
+        {
+            EfiStatusEnum::Success(Some(policy)) => EfiStatusEnum::Success(policy),
+            EfiStatusEnum::Warning(status, Some(policy)) => EfiStatusEnum::Warning(status, policy),
+            EfiStatusEnum::Success(None) | EfiStatusEnum::Warning(_, None) => {
+                EfiStatusEnum::Error(EfiStatusError::EfiDeviceError, ())
+            }
+            EfiStatusEnum::Error(status, ()) => EfiStatusEnum::Error(status, ()),
+        }
+    }
+
+    pub fn set_policy(&self, policy: EfiIp4Config2Policy) -> EfiStatusEnum {
+        (self.set_data)(
+            self,
+            EfiIp4Config2DataType::Policy,
+            size_of::<EfiIp4Config2Policy>(),
+            &policy as *const EfiIp4Config2Policy as VoidPtr,
+        )
+        .into_enum()
+    }
+
+    pub fn manual_address(&self) -> EfiStatusEnum<EfiIp4Config2ManualAddress> {
+        let mut address: EfiIp4Config2ManualAddress = EfiIp4Config2ManualAddress::new(
+            EfiIPv4AddressRaw::new([0; 4]),
+            EfiIPv4AddressRaw::new([0; 4]),
+        );
+        let mut size: usize = size_of::<EfiIp4Config2ManualAddress>();
+
+        (self.get_data)(
+            self,
+            EfiIp4Config2DataType::ManualAddress,
+            &mut size,
+            &mut address as *mut EfiIp4Config2ManualAddress as VoidMutPtr,
+        )
+        .into_enum_data(|| address)
+    }
+
+    /// Sets the station address. The policy has to be [`EfiIp4Config2Policy::Static`].
+    ///
+    /// The firmware may return `EFI_NOT_READY` and finish the configuration asynchronously,
+    /// in which case an event registered through [`register_data_notify`](Self::register_data_notify) is signalled.
+    pub fn set_manual_address(&self, address: &EfiIp4Config2ManualAddress) -> EfiStatusEnum {
+        (self.set_data)(
+            self,
+            EfiIp4Config2DataType::ManualAddress,
+            size_of::<EfiIp4Config2ManualAddress>(),
+            address as *const EfiIp4Config2ManualAddress as VoidPtr,
+        )
+        .into_enum()
+    }
+
+    /// Reads the gateway addresses into `buffer`.
+    ///
+    /// Returns the number of addresses on success and the required number when the buffer is too small.
+    pub fn gateways(&self, buffer: &mut [EfiIPv4AddressRaw]) -> EfiStatusEnum<usize, usize> {
+        self.addresses(EfiIp4Config2DataType::Gateway, buffer)
+    }
+
+    pub fn set_gateways(&self, gateways: &[EfiIPv4AddressRaw]) -> EfiStatusEnum {
+        self.set_addresses(EfiIp4Config2DataType::Gateway, gateways)
+    }
+
+    /// Reads the DNS server addresses into `buffer`.
+    ///
+    /// Returns the number of addresses on success and the required number when the buffer is too small.
+    pub fn dns_servers(&self, buffer: &mut [EfiIPv4AddressRaw]) -> EfiStatusEnum<usize, usize> {
+        self.addresses(EfiIp4Config2DataType::DnsServer, buffer)
+    }
+
+    pub fn set_dns_servers(&self, dns_servers: &[EfiIPv4AddressRaw]) -> EfiStatusEnum {
+        self.set_addresses(EfiIp4Config2DataType::DnsServer, dns_servers)
+    }
+
+    /// Registers an event that is signalled when the configuration data of the given type changes.
+    pub fn register_data_notify(
+        &self,
+        data_type: EfiIp4Config2DataType,
+        event: EfiEvent,
+    ) -> EfiStatusEnum {
+        (self.register_data_notify)(self, data_type, event).into_enum()
+    }
+
+    pub fn unregister_data_notify(
+        &self,
+        data_type: EfiIp4Config2DataType,
+        event: EfiEvent,
+    ) -> EfiStatusEnum {
+        (self.unregister_data_notify)(self, data_type, event).into_enum()
+    }
+
+    fn addresses(
+        &self,
+        data_type: EfiIp4Config2DataType,
+        buffer: &mut [EfiIPv4AddressRaw],
+    ) -> EfiStatusEnum<usize, usize> {
+        let mut size: usize = size_of_val(buffer);
+
+        (self.get_data)(
+            self,
+            data_type,
+            &mut size,
+            buffer.as_mut_ptr() as VoidMutPtr,
+        )
+        .into_enum_data_error(
+            || size / size_of::<EfiIPv4AddressRaw>(),
+            || size / size_of::<EfiIPv4AddressRaw>(),
+        )
+    }
+
+    fn set_addresses(
+        &self,
+        data_type: EfiIp4Config2DataType,
+        addresses: &[EfiIPv4AddressRaw],
+    ) -> EfiStatusEnum {
+        (self.set_data)(
+            self,
+            data_type,
+            size_of_val(addresses),
+            addresses.as_ptr() as VoidPtr,
+        )
+        .into_enum()
+    }
+}
+
+impl EfiProtocol for EfiIp4Config2Protocol {
+    type Parsed = &'static Self;
+    type Error = !;
+
+    fn guid() -> EfiGuid {
+        crate::guids::EFI_IP4_CONFIG2_PROTOCOL
+    }
+
+    unsafe fn parse(
+        ptr: NonNullVoidPtr,
+    ) -> Result<<Self as EfiProtocol>::Parsed, <Self as EfiProtocol>::Error> {
+        Ok(&*ptr.cast().as_ptr())
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum EfiIp4Config2DataType {
+    InterfaceInfo,
+    Policy,
+    ManualAddress,
+    Gateway,
+    DnsServer,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum EfiIp4Config2Policy {
+    Static,
+    Dhcp,
+}
+
+impl EfiIp4Config2Policy {
+    fn from_raw(policy: u32) -> Option<Self> {
+        match policy {
+            0 => Some(Self::Static),
+            1 => Some(Self::Dhcp),
+            _ => None,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct EfiIp4Config2ManualAddress {
+    address: EfiIPv4AddressRaw,
+    subnet_mask: EfiIPv4AddressRaw,
+}
+
+impl EfiIp4Config2ManualAddress {
+    pub fn new(address: EfiIPv4AddressRaw, subnet_mask: EfiIPv4AddressRaw) -> Self {
+        Self {
+            address,
+            subnet_mask,
+        }
+    }
+
+    pub fn address(&self) -> EfiIPv4AddressRaw {
+        self.address
+    }
+
+    pub fn subnet_mask(&self) -> EfiIPv4AddressRaw {
+        self.subnet_mask
+    }
+}
+
+#[repr(C)]
+pub struct EfiIp4Config2InterfaceInfo {
+    name: [u16; 32],
+    if_type: u8,
+    hw_address_size: u32,
+    hw_address: EfiMacAddress,
+    station_address: EfiIPv4AddressRaw,
+    subnet_mask: EfiIPv4AddressRaw,
+    route_table_size: u32,
+    route_table: *mut EfiIp4RouteTable,
+}
+
+impl EfiIp4Config2InterfaceInfo {
+    pub fn name(&self) -> Result<&[u16], RawUtf16StringError> {
+        unsafe { string_from_raw(self.name.as_ptr()) }
+    }
+
+    /// Returns the interface's ARP hardware type.
+    pub fn if_type(&self) -> u8 {
+        self.if_type
+    }
+
+    pub fn hw_address(&self) -> &[u8] {
+        &self.hw_address[..(self.hw_address_size as usize).min(self.hw_address.len())]
+    }
+
+    pub fn station_address(&self) -> EfiIPv4AddressRaw {
+        self.station_address
+    }
+
+    pub fn subnet_mask(&self) -> EfiIPv4AddressRaw {
+        self.subnet_mask
+    }
+
+    pub fn route_table(&self) -> &[EfiIp4RouteTable] {
+        if self.route_table.is_null() {
+            &[]
+        } else {
+            unsafe { from_raw_parts(self.route_table, self.route_table_size as usize) }
+        }
+    }
+
+    pub fn route_table_mut(&mut self) -> &mut [EfiIp4RouteTable] {
+        if self.route_table.is_null() {
+            &mut []
+        } else {
+            unsafe { from_raw_parts_mut(self.route_table, self.route_table_size as usize) }
+        }
+    }
+}
+
+/// Implementation of EFI's `EFI_IP4_ROUTE_TABLE`.
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct EfiIp4RouteTable {
+    subnet_address: EfiIPv4AddressRaw,
+    subnet_mask: EfiIPv4AddressRaw,
+    gateway_address: EfiIPv4AddressRaw,
+}
+
+impl EfiIp4RouteTable {
+    pub fn subnet_address(&self) -> EfiIPv4AddressRaw {
+        self.subnet_address
+    }
+
+    pub fn subnet_mask(&self) -> EfiIPv4AddressRaw {
+        self.subnet_mask
+    }
+
+    pub fn gateway_address(&self) -> EfiIPv4AddressRaw {
+        self.gateway_address
+    }
+}
@@ -1 +1,15 @@
 pub mod common;
+
+mod dns4;
+mod http;
+mod ip4_config2;
+mod pxe_base_code;
+mod tcp4;
+mod udp4;
+
+pub use dns4::*;
+pub use http::*;
+pub use ip4_config2::*;
+pub use pxe_base_code::*;
+pub use tcp4::*;
+pub use udp4::*;
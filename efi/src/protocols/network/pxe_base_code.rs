@@ -0,0 +1,622 @@
+use {
+    super::common::structs::{EfiIPAddress, EfiIPAddressRaw, EfiIPv4AddressRaw, EfiMacAddress},
+    crate::{
+        guid::EfiGuid,
+        protocols::EfiProtocol,
+        status::{EfiStatus, EfiStatusEnum, EfiStatusError},
+        types::{NonNullVoidPtr, VoidMutPtr, VoidPtr},
+    },
+    core::ptr::{null, null_mut},
+};
+
+pub const EFI_PXE_BASE_CODE_MAX_IPCNT: usize = 8;
+pub const EFI_PXE_BASE_CODE_MAX_ARP_ENTRIES: usize = 8;
+pub const EFI_PXE_BASE_CODE_MAX_ROUTE_ENTRIES: usize = 8;
+
+/// Implementation of EFI's `EFI_PXE_BASE_CODE_PROTOCOL`.
+#[repr(C)]
+pub struct EfiPxeBaseCodeProtocol {
+    revision: u64,
+    start: extern "efiapi" fn(*const Self, bool) -> EfiStatus,
+    stop: extern "efiapi" fn(*const Self) -> EfiStatus,
+    dhcp: extern "efiapi" fn(*const Self, bool) -> EfiStatus,
+    discover: extern "efiapi" fn(*const Self, u16, *mut u16, bool, VoidPtr) -> EfiStatus,
+    mtftp: extern "efiapi" fn(
+        *const Self,
+        EfiPxeBaseCodeTftpOpcode,
+        VoidMutPtr,
+        bool,
+        *mut u64,
+        *const usize,
+        *const EfiIPAddressRaw,
+        *const u8,
+        *const EfiPxeBaseCodeMtftpInfo,
+        bool,
+    ) -> EfiStatus,
+    udp_write: VoidPtr,
+    udp_read: VoidPtr,
+    set_ip_filter: VoidPtr,
+    arp: extern "efiapi" fn(*const Self, *const EfiIPAddressRaw, *mut EfiMacAddress) -> EfiStatus,
+    set_parameters: VoidPtr,
+    set_station_ip: extern "efiapi" fn(
+        *const Self,
+        *const EfiIPAddressRaw,
+        *const EfiIPAddressRaw,
+    ) -> EfiStatus,
+    set_packets: VoidPtr,
+    mode: *const EfiPxeBaseCodeMode,
+}
+
+impl EfiPxeBaseCodeProtocol {
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    pub fn mode(&self) -> &EfiPxeBaseCodeMode {
+        unsafe { &*self.mode }
+    }
+
+    pub fn start(&self, use_ip_v6: bool) -> EfiStatusEnum {
+        (self.start)(self, use_ip_v6).into_enum()
+    }
+
+    pub fn stop(&self) -> EfiStatusEnum {
+        (self.stop)(self).into_enum()
+    }
+
+    /// Runs the DHCP (or DHCPv6) S.A.R.R. cycle.
+    /// The cached packets are accessible through [`mode`](Self::mode) afterwards.
+    pub fn dhcp(&self, sort_offers: bool) -> EfiStatusEnum {
+        (self.dhcp)(self, sort_offers).into_enum()
+    }
+
+    pub fn arp(&self, ip_address: EfiIPAddress) -> EfiStatusEnum<EfiMacAddress> {
+        let ip_address: EfiIPAddressRaw = ip_address.into();
+
+        let mut mac_address: EfiMacAddress = EfiMacAddress::new([0; 32]);
+
+        (self.arp)(self, &ip_address, &mut mac_address).into_enum_data(|| mac_address)
+    }
+
+    pub fn set_station_ip(
+        &self,
+        station_ip: Option<EfiIPAddress>,
+        subnet_mask: Option<EfiIPAddress>,
+    ) -> EfiStatusEnum {
+        let station_ip: Option<EfiIPAddressRaw> = station_ip.map(Into::into);
+        let subnet_mask: Option<EfiIPAddressRaw> = subnet_mask.map(Into::into);
+
+        (self.set_station_ip)(
+            self,
+            station_ip
+                .as_ref()
+                .map_or(null(), |address: &EfiIPAddressRaw| address),
+            subnet_mask
+                .as_ref()
+                .map_or(null(), |address: &EfiIPAddressRaw| address),
+        )
+        .into_enum()
+    }
+
+    /// Retrieves the size of a file on a TFTP server.
+    ///
+    /// When `mtftp_info` is passed, multicast TFTP is used instead.
+    ///
+    /// The file name has to be a null-terminated ASCII string.
+    pub fn get_file_size(
+        &self,
+        server_ip: EfiIPAddress,
+        file_name: &[u8],
+        mtftp_info: Option<&EfiPxeBaseCodeMtftpInfo>,
+    ) -> EfiStatusEnum<u64> {
+        self.mtftp(
+            if mtftp_info.is_some() {
+                EfiPxeBaseCodeTftpOpcode::MtftpGetFileSize
+            } else {
+                EfiPxeBaseCodeTftpOpcode::TftpGetFileSize
+            },
+            None,
+            server_ip,
+            file_name,
+            mtftp_info,
+        )
+        .map_error(|_| ())
+    }
+
+    /// Downloads a file from a TFTP server into the buffer.
+    ///
+    /// On success the number of bytes read is returned.
+    /// When [`EfiBufferTooSmall`](EfiStatusError::EfiBufferTooSmall) is returned, the error data contains the required buffer size.
+    ///
+    /// When `mtftp_info` is passed, multicast TFTP is used instead.
+    ///
+    /// The file name has to be a null-terminated ASCII string.
+    pub fn read_file(
+        &self,
+        server_ip: EfiIPAddress,
+        file_name: &[u8],
+        buffer: &mut [u8],
+        mtftp_info: Option<&EfiPxeBaseCodeMtftpInfo>,
+    ) -> EfiStatusEnum<u64, u64> {
+        self.mtftp(
+            if mtftp_info.is_some() {
+                EfiPxeBaseCodeTftpOpcode::MtftpReadFile
+            } else {
+                EfiPxeBaseCodeTftpOpcode::TftpReadFile
+            },
+            Some(buffer),
+            server_ip,
+            file_name,
+            mtftp_info,
+        )
+    }
+
+    /// Reads a directory listing from a TFTP server into the buffer.
+    ///
+    /// The contents of the buffer are formatted as described by the UEFI specification.
+    /// Error data and `mtftp_info` behave the same as with [`read_file`](Self::read_file).
+    ///
+    /// The directory name has to be a null-terminated ASCII string.
+    pub fn read_directory(
+        &self,
+        server_ip: EfiIPAddress,
+        directory_name: &[u8],
+        buffer: &mut [u8],
+        mtftp_info: Option<&EfiPxeBaseCodeMtftpInfo>,
+    ) -> EfiStatusEnum<u64, u64> {
+        self.mtftp(
+            if mtftp_info.is_some() {
+                EfiPxeBaseCodeTftpOpcode::MtftpReadDirectory
+            } else {
+                EfiPxeBaseCodeTftpOpcode::TftpReadDirectory
+            },
+            Some(buffer),
+            server_ip,
+            directory_name,
+            mtftp_info,
+        )
+    }
+
+    fn mtftp(
+        &self,
+        operation: EfiPxeBaseCodeTftpOpcode,
+        buffer: Option<&mut [u8]>,
+        server_ip: EfiIPAddress,
+        file_name: &[u8],
+        mtftp_info: Option<&EfiPxeBaseCodeMtftpInfo>,
+    ) -> EfiStatusEnum<u64, u64> {
+        if file_name.last() != Some(&0) {
+            return EfiStatusEnum::Error(EfiStatusError::EfiInvalidParameter, 0);
+        }
+
+        let server_ip: EfiIPAddressRaw = server_ip.into();
+
+        let (buffer_ptr, mut buffer_size): (VoidMutPtr, u64) = if let Some(buffer) = buffer {
+            (buffer.as_mut_ptr() as VoidMutPtr, buffer.len() as u64)
+        } else {
+            (null_mut(), 0)
+        };
+
+        (self.mtftp)(
+            self,
+            operation,
+            buffer_ptr,
+            false,
+            &mut buffer_size,
+            null(),
+            &server_ip,
+            file_name.as_ptr(),
+            mtftp_info.map_or(null(), |mtftp_info: &EfiPxeBaseCodeMtftpInfo| mtftp_info),
+            false,
+        )
+        .into_enum_data_error(|| buffer_size, || buffer_size)
+    }
+}
+
+impl EfiProtocol for EfiPxeBaseCodeProtocol {
+    type Parsed = &'static Self;
+    type Error = !;
+
+    fn guid() -> EfiGuid {
+        crate::guids::EFI_PXE_BASE_CODE_PROTOCOL
+    }
+
+    unsafe fn parse(
+        ptr: NonNullVoidPtr,
+    ) -> Result<<Self as EfiProtocol>::Parsed, <Self as EfiProtocol>::Error> {
+        Ok(&*ptr.cast().as_ptr())
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum EfiPxeBaseCodeTftpOpcode {
+    TftpFirst,
+    TftpGetFileSize,
+    TftpReadFile,
+    TftpWriteFile,
+    TftpReadDirectory,
+    MtftpGetFileSize,
+    MtftpReadFile,
+    MtftpReadDirectory,
+    MtftpLast,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct EfiPxeBaseCodeMtftpInfo {
+    multicast_ip: EfiIPAddressRaw,
+    client_port: u16,
+    server_port: u16,
+    listen_timeout: u16,
+    transmit_timeout: u16,
+}
+
+impl EfiPxeBaseCodeMtftpInfo {
+    pub fn new(
+        multicast_ip: EfiIPAddress,
+        client_port: u16,
+        server_port: u16,
+        listen_timeout: u16,
+        transmit_timeout: u16,
+    ) -> Self {
+        Self {
+            multicast_ip: multicast_ip.into(),
+            client_port,
+            server_port,
+            listen_timeout,
+            transmit_timeout,
+        }
+    }
+}
+
+#[repr(C)]
+pub struct EfiPxeBaseCodeMode {
+    started: bool,
+    ip_v6_available: bool,
+    ip_v6_supported: bool,
+    using_ip_v6: bool,
+    bis_supported: bool,
+    bis_detected: bool,
+    auto_arp: bool,
+    send_guid: bool,
+    dhcp_discover_valid: bool,
+    dhcp_ack_received: bool,
+    proxy_offer_received: bool,
+    pxe_discover_valid: bool,
+    pxe_reply_received: bool,
+    pxe_bis_reply_received: bool,
+    icmp_error_received: bool,
+    tftp_error_received: bool,
+    make_callbacks: bool,
+    ttl: u8,
+    tos: u8,
+    station_ip: EfiIPAddressRaw,
+    subnet_mask: EfiIPAddressRaw,
+    dhcp_discover: EfiPxeBaseCodePacket,
+    dhcp_ack: EfiPxeBaseCodePacket,
+    proxy_offer: EfiPxeBaseCodePacket,
+    pxe_discover: EfiPxeBaseCodePacket,
+    pxe_reply: EfiPxeBaseCodePacket,
+    pxe_bis_reply: EfiPxeBaseCodePacket,
+    ip_filter: EfiPxeBaseCodeIPFilter,
+    arp_cache_entries: u32,
+    arp_cache: [EfiPxeBaseCodeArpEntry; EFI_PXE_BASE_CODE_MAX_ARP_ENTRIES],
+    route_table_entries: u32,
+    route_table: [EfiPxeBaseCodeRouteEntry; EFI_PXE_BASE_CODE_MAX_ROUTE_ENTRIES],
+    icmp_error: EfiPxeBaseCodeIcmpError,
+    tftp_error: EfiPxeBaseCodeTftpError,
+}
+
+impl EfiPxeBaseCodeMode {
+    fn ip_address(&self, address: &EfiIPAddressRaw) -> EfiIPAddress {
+        if self.using_ip_v6 {
+            EfiIPAddress::IPv6(address.ip_v6())
+        } else {
+            EfiIPAddress::IPv4(address.ip_v4())
+        }
+    }
+
+    fn packet(valid: bool, packet: &EfiPxeBaseCodePacket) -> Option<&EfiPxeBaseCodePacket> {
+        if valid {
+            Some(packet)
+        } else {
+            None
+        }
+    }
+
+    pub fn started(&self) -> bool {
+        self.started
+    }
+
+    pub fn ip_v6_available(&self) -> bool {
+        self.ip_v6_available
+    }
+
+    pub fn ip_v6_supported(&self) -> bool {
+        self.ip_v6_supported
+    }
+
+    pub fn using_ip_v6(&self) -> bool {
+        self.using_ip_v6
+    }
+
+    pub fn auto_arp(&self) -> bool {
+        self.auto_arp
+    }
+
+    pub fn ttl(&self) -> u8 {
+        self.ttl
+    }
+
+    pub fn tos(&self) -> u8 {
+        self.tos
+    }
+
+    pub fn station_ip(&self) -> EfiIPAddress {
+        self.ip_address(&self.station_ip)
+    }
+
+    pub fn subnet_mask(&self) -> EfiIPAddress {
+        self.ip_address(&self.subnet_mask)
+    }
+
+    pub fn dhcp_discover(&self) -> Option<&EfiPxeBaseCodePacket> {
+        Self::packet(self.dhcp_discover_valid, &self.dhcp_discover)
+    }
+
+    /// Returns the cached DHCP ACK packet, if one was received.
+    pub fn dhcp_ack(&self) -> Option<&EfiPxeBaseCodePacket> {
+        Self::packet(self.dhcp_ack_received, &self.dhcp_ack)
+    }
+
+    pub fn proxy_offer(&self) -> Option<&EfiPxeBaseCodePacket> {
+        Self::packet(self.proxy_offer_received, &self.proxy_offer)
+    }
+
+    pub fn pxe_discover(&self) -> Option<&EfiPxeBaseCodePacket> {
+        Self::packet(self.pxe_discover_valid, &self.pxe_discover)
+    }
+
+    pub fn pxe_reply(&self) -> Option<&EfiPxeBaseCodePacket> {
+        Self::packet(self.pxe_reply_received, &self.pxe_reply)
+    }
+
+    pub fn pxe_bis_reply(&self) -> Option<&EfiPxeBaseCodePacket> {
+        Self::packet(self.pxe_bis_reply_received, &self.pxe_bis_reply)
+    }
+
+    /// Returns the entries of the ARP cache as pairs of IP and MAC addresses.
+    pub fn arp_cache(&self) -> impl Iterator<Item = (EfiIPAddress, EfiMacAddress)> + '_ {
+        self.arp_cache[..(self.arp_cache_entries as usize).min(EFI_PXE_BASE_CODE_MAX_ARP_ENTRIES)]
+            .iter()
+            .map(move |entry: &EfiPxeBaseCodeArpEntry| {
+                (self.ip_address(&entry.ip_address), entry.mac_address)
+            })
+    }
+
+    /// Returns the entries of the route table as triples of IP address, subnet mask and gateway address.
+    pub fn route_table(
+        &self,
+    ) -> impl Iterator<Item = (EfiIPAddress, EfiIPAddress, EfiIPAddress)> + '_ {
+        self.route_table
+            [..(self.route_table_entries as usize).min(EFI_PXE_BASE_CODE_MAX_ROUTE_ENTRIES)]
+            .iter()
+            .map(move |entry: &EfiPxeBaseCodeRouteEntry| {
+                (
+                    self.ip_address(&entry.ip_address),
+                    self.ip_address(&entry.subnet_mask),
+                    self.ip_address(&entry.gateway_address),
+                )
+            })
+    }
+
+    pub fn icmp_error(&self) -> Option<&EfiPxeBaseCodeIcmpError> {
+        if self.icmp_error_received {
+            Some(&self.icmp_error)
+        } else {
+            None
+        }
+    }
+
+    pub fn tftp_error(&self) -> Option<&EfiPxeBaseCodeTftpError> {
+        if self.tftp_error_received {
+            Some(&self.tftp_error)
+        } else {
+            None
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub union EfiPxeBaseCodePacket {
+    raw: [u8; 1472],
+    dhcp_v4: EfiPxeBaseCodeDhcpV4Packet,
+    dhcp_v6: EfiPxeBaseCodeDhcpV6Packet,
+}
+
+impl EfiPxeBaseCodePacket {
+    pub fn raw(&self) -> &[u8; 1472] {
+        unsafe { &self.raw }
+    }
+
+    pub fn dhcp_v4(&self) -> &EfiPxeBaseCodeDhcpV4Packet {
+        unsafe { &self.dhcp_v4 }
+    }
+
+    pub fn dhcp_v6(&self) -> &EfiPxeBaseCodeDhcpV6Packet {
+        unsafe { &self.dhcp_v6 }
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct EfiPxeBaseCodeDhcpV4Packet {
+    bootp_opcode: u8,
+    bootp_hw_type: u8,
+    bootp_hw_address_length: u8,
+    bootp_gate_hops: u8,
+    bootp_ident: u32,
+    bootp_seconds: u16,
+    bootp_flags: u16,
+    bootp_client_ip: EfiIPv4AddressRaw,
+    bootp_your_ip: EfiIPv4AddressRaw,
+    bootp_server_ip: EfiIPv4AddressRaw,
+    bootp_gateway_ip: EfiIPv4AddressRaw,
+    bootp_hw_address: [u8; 16],
+    bootp_server_name: [u8; 64],
+    bootp_boot_file: [u8; 128],
+    dhcp_magic: u32,
+    dhcp_options: [u8; 56],
+}
+
+impl EfiPxeBaseCodeDhcpV4Packet {
+    fn null_terminated(data: &[u8]) -> &[u8] {
+        &data[..data
+            .iter()
+            .position(|&byte: &u8| byte == 0)
+            .unwrap_or(data.len())]
+    }
+
+    pub fn opcode(&self) -> u8 {
+        self.bootp_opcode
+    }
+
+    pub fn transaction_id(&self) -> u32 {
+        u32::from_be(self.bootp_ident)
+    }
+
+    pub fn client_ip(&self) -> EfiIPv4AddressRaw {
+        self.bootp_client_ip
+    }
+
+    /// Returns the address assigned to the client (`yiaddr`).
+    pub fn your_ip(&self) -> EfiIPv4AddressRaw {
+        self.bootp_your_ip
+    }
+
+    /// Returns the address of the next server to use in bootstrap (`siaddr`).
+    pub fn next_server(&self) -> EfiIPv4AddressRaw {
+        self.bootp_server_ip
+    }
+
+    pub fn gateway_ip(&self) -> EfiIPv4AddressRaw {
+        self.bootp_gateway_ip
+    }
+
+    pub fn hw_address(&self) -> &[u8] {
+        &self.bootp_hw_address
+            [..(self.bootp_hw_address_length as usize).min(self.bootp_hw_address.len())]
+    }
+
+    /// Returns the server host name (`sname`) without the null-terminator.
+    pub fn server_name(&self) -> &[u8] {
+        Self::null_terminated(&self.bootp_server_name)
+    }
+
+    /// Returns the boot file name (`file`) without the null-terminator.
+    pub fn boot_file(&self) -> &[u8] {
+        Self::null_terminated(&self.bootp_boot_file)
+    }
+
+    pub fn magic(&self) -> u32 {
+        u32::from_be(self.dhcp_magic)
+    }
+
+    pub fn options(&self) -> &[u8] {
+        &self.dhcp_options
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct EfiPxeBaseCodeDhcpV6Packet {
+    message_type_and_transaction_id: u32,
+    dhcp_options: [u8; 1024],
+}
+
+impl EfiPxeBaseCodeDhcpV6Packet {
+    pub fn message_type(&self) -> u8 {
+        self.message_type_and_transaction_id as u8
+    }
+
+    pub fn transaction_id(&self) -> u32 {
+        self.message_type_and_transaction_id >> 8
+    }
+
+    pub fn options(&self) -> &[u8] {
+        &self.dhcp_options
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct EfiPxeBaseCodeIPFilter {
+    filters: u8,
+    ip_count: u8,
+    _reserved: u16,
+    ip_list: [EfiIPAddressRaw; EFI_PXE_BASE_CODE_MAX_IPCNT],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct EfiPxeBaseCodeArpEntry {
+    ip_address: EfiIPAddressRaw,
+    mac_address: EfiMacAddress,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct EfiPxeBaseCodeRouteEntry {
+    ip_address: EfiIPAddressRaw,
+    subnet_mask: EfiIPAddressRaw,
+    gateway_address: EfiIPAddressRaw,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct EfiPxeBaseCodeIcmpError {
+    icmp_type: u8,
+    code: u8,
+    checksum: u16,
+    data_header: u32,
+    data: [u8; 494],
+}
+
+impl EfiPxeBaseCodeIcmpError {
+    pub fn icmp_type(&self) -> u8 {
+        self.icmp_type
+    }
+
+    pub fn code(&self) -> u8 {
+        self.code
+    }
+
+    pub fn checksum(&self) -> u16 {
+        self.checksum
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct EfiPxeBaseCodeTftpError {
+    error_code: u8,
+    error_string: [u8; 127],
+}
+
+impl EfiPxeBaseCodeTftpError {
+    pub fn error_code(&self) -> u8 {
+        self.error_code
+    }
+
+    /// Returns the error message without the null-terminator.
+    pub fn error_string(&self) -> &[u8] {
+        EfiPxeBaseCodeDhcpV4Packet::null_terminated(&self.error_string)
+    }
+}
@@ -0,0 +1,510 @@
+use {
+    super::common::structs::{EfiFragmentData, EfiIPv4AddressRaw},
+    crate::{
+        guid::EfiGuid,
+        protocols::{service_binding::EfiServiceBinding, EfiProtocol},
+        status::{EfiStatus, EfiStatusEnum, EfiStatusError},
+        types::{EfiEvent, EfiHandle, NonNullVoidPtr, VoidMutPtr, VoidPtr},
+    },
+    core::{marker::PhantomData, ptr::null_mut},
+};
+
+/// Implementation of EFI's `EFI_TCP4_PROTOCOL`.
+///
+/// Instances are created through [`EfiServiceBindingProtocol<EfiTcp4Protocol>`](crate::protocols::service_binding::EfiServiceBindingProtocol).
+#[repr(C)]
+pub struct EfiTcp4Protocol {
+    get_mode_data: extern "efiapi" fn(
+        *const Self,
+        *mut u32,
+        *mut EfiTcp4ConfigData,
+        VoidMutPtr,
+        VoidMutPtr,
+        VoidMutPtr,
+    ) -> EfiStatus,
+    configure: extern "efiapi" fn(*const Self, *const EfiTcp4ConfigData) -> EfiStatus,
+    routes: extern "efiapi" fn(
+        *const Self,
+        bool,
+        *const EfiIPv4AddressRaw,
+        *const EfiIPv4AddressRaw,
+        *const EfiIPv4AddressRaw,
+    ) -> EfiStatus,
+    connect: extern "efiapi" fn(*const Self, *mut EfiTcp4ConnectionToken) -> EfiStatus,
+    accept: extern "efiapi" fn(*const Self, *mut EfiTcp4ListenToken) -> EfiStatus,
+    transmit: extern "efiapi" fn(*const Self, *mut EfiTcp4IOToken) -> EfiStatus,
+    receive: extern "efiapi" fn(*const Self, *mut EfiTcp4IOToken) -> EfiStatus,
+    close: extern "efiapi" fn(*const Self, *mut EfiTcp4CloseToken) -> EfiStatus,
+    cancel: extern "efiapi" fn(*const Self, *mut EfiTcp4CompletionToken) -> EfiStatus,
+    poll: extern "efiapi" fn(*const Self) -> EfiStatus,
+}
+
+impl EfiTcp4Protocol {
+    /// Returns the connection state and the configuration.
+    ///
+    /// Values which aren't valid connection states are reported as `EfiDeviceError`.
+    pub fn get_mode_data(&self) -> EfiStatusEnum<(EfiTcp4ConnectionState, EfiTcp4ConfigData)> {
+        let mut connection_state: u32 = 0;
+
+        let mut config_data: EfiTcp4ConfigData = EfiTcp4ConfigData::new(
+            EfiTcp4AccessPoint::new_default(0, EfiIPv4AddressRaw::new([0; 4]), 0, false),
+        );
+
+        match (self.get_mode_data)(
+            self,
+            &mut connection_state,
+            &mut config_data,
+            null_mut(),
+            null_mut(),
+            null_mut(),
+        )
+        .into_enum_data(|| EfiTcp4ConnectionState::from_raw(connection_state))
+        {
+            EfiStatusEnum::Success(Some(connection_state)) => {
+                EfiStatusEnum::Success((connection_state, config_data))
+            }
+            EfiStatusEnum::Warning(status, Some(connection_state)) => {
+                EfiStatusEnum::Warning(status, (connection_state, config_data))
+            }
+            EfiStatusEnum::Success(None) | EfiStatusEnum::Warning(_, None) => {
+                EfiStatusEnum::Error(EfiStatusError::EfiDeviceError, ())
+            }
+            EfiStatusEnum::Error(status, ()) => EfiStatusEnum::Error(status, ()),
+        }
+    }
+
+    /// Configures the instance. Passing `None` resets the instance and aborts all active connections.
+    pub fn configure(&self, config_data: Option<&EfiTcp4ConfigData>) -> EfiStatusEnum {
+        (self.configure)(
+            self,
+            config_data.map_or(0 as _, |config_data: &EfiTcp4ConfigData| config_data),
+        )
+        .into_enum()
+    }
+
+    pub fn add_route(
+        &self,
+        subnet_address: EfiIPv4AddressRaw,
+        subnet_mask: EfiIPv4AddressRaw,
+        gateway_address: EfiIPv4AddressRaw,
+    ) -> EfiStatusEnum {
+        (self.routes)(self, false, &subnet_address, &subnet_mask, &gateway_address).into_enum()
+    }
+
+    pub fn delete_route(
+        &self,
+        subnet_address: EfiIPv4AddressRaw,
+        subnet_mask: EfiIPv4AddressRaw,
+        gateway_address: EfiIPv4AddressRaw,
+    ) -> EfiStatusEnum {
+        (self.routes)(self, true, &subnet_address, &subnet_mask, &gateway_address).into_enum()
+    }
+
+    /// Initiates a connection to the configured remote address.
+    /// # Safety
+    /// The token must not be moved or dropped until it's event is signalled or the operation is cancelled.
+    pub unsafe fn connect(&self, token: &mut EfiTcp4ConnectionToken) -> EfiStatusEnum {
+        (self.connect)(self, token).into_enum()
+    }
+
+    /// Listens for an incoming connection.
+    /// # Safety
+    /// The token must not be moved or dropped until it's event is signalled or the operation is cancelled.
+    pub unsafe fn accept(&self, token: &mut EfiTcp4ListenToken) -> EfiStatusEnum {
+        (self.accept)(self, token).into_enum()
+    }
+
+    /// Queues data for transmission.
+    /// # Safety
+    /// The token and all buffers it references must not be moved or dropped until the token's event is signalled or the operation is cancelled.
+    pub unsafe fn transmit(&self, token: &mut EfiTcp4IOToken) -> EfiStatusEnum {
+        (self.transmit)(self, token).into_enum()
+    }
+
+    /// Queues buffers for receiving data.
+    /// # Safety
+    /// The token and all buffers it references must not be moved or dropped until the token's event is signalled or the operation is cancelled.
+    pub unsafe fn receive(&self, token: &mut EfiTcp4IOToken) -> EfiStatusEnum {
+        (self.receive)(self, token).into_enum()
+    }
+
+    /// Gracefully closes or aborts the connection.
+    /// # Safety
+    /// The token must not be moved or dropped until it's event is signalled.
+    pub unsafe fn close(&self, token: &mut EfiTcp4CloseToken) -> EfiStatusEnum {
+        (self.close)(self, token).into_enum()
+    }
+
+    /// Cancels a queued token. When `None` is passed, all queued tokens are cancelled.
+    pub fn cancel(&self, token: Option<&mut EfiTcp4CompletionToken>) -> EfiStatusEnum {
+        (self.cancel)(
+            self,
+            token.map_or(null_mut(), |token: &mut EfiTcp4CompletionToken| token),
+        )
+        .into_enum()
+    }
+
+    pub fn poll(&self) -> EfiStatusEnum {
+        (self.poll)(self).into_enum()
+    }
+}
+
+impl EfiProtocol for EfiTcp4Protocol {
+    type Parsed = &'static Self;
+    type Error = !;
+
+    fn guid() -> EfiGuid {
+        crate::guids::EFI_TCP4_PROTOCOL
+    }
+
+    unsafe fn parse(
+        ptr: NonNullVoidPtr,
+    ) -> Result<<Self as EfiProtocol>::Parsed, <Self as EfiProtocol>::Error> {
+        Ok(&*ptr.cast().as_ptr())
+    }
+}
+
+impl EfiServiceBinding for EfiTcp4Protocol {
+    fn service_binding_guid() -> EfiGuid {
+        crate::guids::EFI_TCP4_SERVICE_BINDING_PROTOCOL
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum EfiTcp4ConnectionState {
+    Closed,
+    Listen,
+    SynSent,
+    SynReceived,
+    Established,
+    FinWait1,
+    FinWait2,
+    Closing,
+    TimeWait,
+    CloseWait,
+    LastAck,
+}
+
+impl EfiTcp4ConnectionState {
+    fn from_raw(connection_state: u32) -> Option<Self> {
+        match connection_state {
+            0 => Some(Self::Closed),
+            1 => Some(Self::Listen),
+            2 => Some(Self::SynSent),
+            3 => Some(Self::SynReceived),
+            4 => Some(Self::Established),
+            5 => Some(Self::FinWait1),
+            6 => Some(Self::FinWait2),
+            7 => Some(Self::Closing),
+            8 => Some(Self::TimeWait),
+            9 => Some(Self::CloseWait),
+            10 => Some(Self::LastAck),
+            _ => None,
+        }
+    }
+}
+
+#[repr(C)]
+pub struct EfiTcp4ConfigData {
+    type_of_service: u8,
+    time_to_live: u8,
+    access_point: EfiTcp4AccessPoint,
+    control_option: VoidPtr,
+}
+
+impl EfiTcp4ConfigData {
+    /// Creates configuration with the default TCP options.
+    pub fn new(access_point: EfiTcp4AccessPoint) -> Self {
+        Self {
+            type_of_service: 0,
+            time_to_live: 255,
+            access_point,
+            control_option: 0 as _,
+        }
+    }
+
+    pub fn type_of_service(&self) -> u8 {
+        self.type_of_service
+    }
+
+    pub fn set_type_of_service(&mut self, type_of_service: u8) {
+        self.type_of_service = type_of_service;
+    }
+
+    pub fn time_to_live(&self) -> u8 {
+        self.time_to_live
+    }
+
+    pub fn set_time_to_live(&mut self, time_to_live: u8) {
+        self.time_to_live = time_to_live;
+    }
+
+    pub fn access_point(&self) -> &EfiTcp4AccessPoint {
+        &self.access_point
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct EfiTcp4AccessPoint {
+    use_default_address: bool,
+    station_address: EfiIPv4AddressRaw,
+    subnet_mask: EfiIPv4AddressRaw,
+    station_port: u16,
+    remote_address: EfiIPv4AddressRaw,
+    remote_port: u16,
+    active_flag: bool,
+}
+
+impl EfiTcp4AccessPoint {
+    /// Creates an access point that uses the address configured through `EFI_IP4_CONFIG2_PROTOCOL`.
+    ///
+    /// When `active` is set, the instance actively connects to the remote address; otherwise it listens for connections.
+    pub fn new_default(
+        station_port: u16,
+        remote_address: EfiIPv4AddressRaw,
+        remote_port: u16,
+        active: bool,
+    ) -> Self {
+        Self {
+            use_default_address: true,
+            station_address: EfiIPv4AddressRaw::new([0; 4]),
+            subnet_mask: EfiIPv4AddressRaw::new([0; 4]),
+            station_port,
+            remote_address,
+            remote_port,
+            active_flag: active,
+        }
+    }
+
+    pub fn new(
+        station_address: EfiIPv4AddressRaw,
+        subnet_mask: EfiIPv4AddressRaw,
+        station_port: u16,
+        remote_address: EfiIPv4AddressRaw,
+        remote_port: u16,
+        active: bool,
+    ) -> Self {
+        Self {
+            use_default_address: false,
+            station_address,
+            subnet_mask,
+            station_port,
+            remote_address,
+            remote_port,
+            active_flag: active,
+        }
+    }
+
+    pub fn use_default_address(&self) -> bool {
+        self.use_default_address
+    }
+
+    pub fn station_address(&self) -> EfiIPv4AddressRaw {
+        self.station_address
+    }
+
+    pub fn subnet_mask(&self) -> EfiIPv4AddressRaw {
+        self.subnet_mask
+    }
+
+    pub fn station_port(&self) -> u16 {
+        self.station_port
+    }
+
+    pub fn remote_address(&self) -> EfiIPv4AddressRaw {
+        self.remote_address
+    }
+
+    pub fn remote_port(&self) -> u16 {
+        self.remote_port
+    }
+
+    pub fn active(&self) -> bool {
+        self.active_flag
+    }
+}
+
+#[repr(C)]
+pub struct EfiTcp4CompletionToken {
+    event: EfiEvent,
+    status: EfiStatus,
+}
+
+impl EfiTcp4CompletionToken {
+    pub fn new(event: EfiEvent) -> Self {
+        Self {
+            event,
+            status: EfiStatus::success(),
+        }
+    }
+
+    pub fn event(&self) -> EfiEvent {
+        self.event
+    }
+
+    /// Returns the completion status. Only valid after the token's event is signalled.
+    pub fn status(&self) -> EfiStatusEnum {
+        self.status.into_enum()
+    }
+}
+
+#[repr(C)]
+pub struct EfiTcp4ConnectionToken {
+    completion_token: EfiTcp4CompletionToken,
+}
+
+impl EfiTcp4ConnectionToken {
+    pub fn new(event: EfiEvent) -> Self {
+        Self {
+            completion_token: EfiTcp4CompletionToken::new(event),
+        }
+    }
+
+    pub fn completion_token(&mut self) -> &mut EfiTcp4CompletionToken {
+        &mut self.completion_token
+    }
+}
+
+#[repr(C)]
+pub struct EfiTcp4ListenToken {
+    completion_token: EfiTcp4CompletionToken,
+    new_child_handle: EfiHandle,
+}
+
+impl EfiTcp4ListenToken {
+    pub fn new(event: EfiEvent) -> Self {
+        Self {
+            completion_token: EfiTcp4CompletionToken::new(event),
+            new_child_handle: 0 as _,
+        }
+    }
+
+    pub fn completion_token(&mut self) -> &mut EfiTcp4CompletionToken {
+        &mut self.completion_token
+    }
+
+    /// Returns the handle of the child created for the accepted connection.
+    pub fn new_child_handle(&self) -> EfiHandle {
+        self.new_child_handle
+    }
+}
+
+#[repr(C)]
+pub struct EfiTcp4CloseToken {
+    completion_token: EfiTcp4CompletionToken,
+    abort_on_close: bool,
+}
+
+impl EfiTcp4CloseToken {
+    pub fn new(event: EfiEvent, abort_on_close: bool) -> Self {
+        Self {
+            completion_token: EfiTcp4CompletionToken::new(event),
+            abort_on_close,
+        }
+    }
+
+    pub fn completion_token(&mut self) -> &mut EfiTcp4CompletionToken {
+        &mut self.completion_token
+    }
+}
+
+#[repr(C)]
+pub struct EfiTcp4IOToken<'a> {
+    completion_token: EfiTcp4CompletionToken,
+    packet: VoidMutPtr,
+    _lifetime: PhantomData<&'a mut [u8]>,
+}
+
+impl<'a> EfiTcp4IOToken<'a> {
+    pub fn new_transmit<'b, const N: usize>(
+        event: EfiEvent,
+        transmit_data: &'a mut EfiTcp4TransmitData<'b, N>,
+    ) -> Self {
+        Self {
+            completion_token: EfiTcp4CompletionToken::new(event),
+            packet: transmit_data as *mut EfiTcp4TransmitData<N> as VoidMutPtr,
+            _lifetime: PhantomData,
+        }
+    }
+
+    /// The receive data only borrows the token's lifetime, so the received data can be read once the token is dropped.
+    pub fn new_receive<'b, const N: usize>(
+        event: EfiEvent,
+        receive_data: &'a mut EfiTcp4ReceiveData<'b, N>,
+    ) -> Self {
+        Self {
+            completion_token: EfiTcp4CompletionToken::new(event),
+            packet: receive_data as *mut EfiTcp4ReceiveData<N> as VoidMutPtr,
+            _lifetime: PhantomData,
+        }
+    }
+
+    pub fn completion_token(&mut self) -> &mut EfiTcp4CompletionToken {
+        &mut self.completion_token
+    }
+}
+
+/// Describes data to be transmitted in `N` fragments.
+#[repr(C)]
+pub struct EfiTcp4TransmitData<'a, const N: usize> {
+    push: bool,
+    urgent: bool,
+    data_length: u32,
+    fragment_count: u32,
+    fragment_table: [EfiFragmentData<'a>; N],
+}
+
+impl<'a, const N: usize> EfiTcp4TransmitData<'a, N> {
+    pub fn new(push: bool, urgent: bool, fragment_table: [EfiFragmentData<'a>; N]) -> Self {
+        Self {
+            push,
+            urgent,
+            data_length: fragment_table
+                .iter()
+                .map(|fragment: &EfiFragmentData| fragment.len() as u32)
+                .sum(),
+            fragment_count: N as u32,
+            fragment_table,
+        }
+    }
+}
+
+/// Describes `N` fragments that data will be received into.
+#[repr(C)]
+pub struct EfiTcp4ReceiveData<'a, const N: usize> {
+    urgent: bool,
+    data_length: u32,
+    fragment_count: u32,
+    fragment_table: [EfiFragmentData<'a>; N],
+}
+
+impl<'a, const N: usize> EfiTcp4ReceiveData<'a, N> {
+    pub fn new(fragment_table: [EfiFragmentData<'a>; N]) -> Self {
+        Self {
+            urgent: false,
+            data_length: fragment_table
+                .iter()
+                .map(|fragment: &EfiFragmentData| fragment.len() as u32)
+                .sum(),
+            fragment_count: N as u32,
+            fragment_table,
+        }
+    }
+
+    pub fn urgent(&self) -> bool {
+        self.urgent
+    }
+
+    /// Returns the amount of received data. Only valid after the token's event is signalled.
+    pub fn data_length(&self) -> usize {
+        self.data_length as usize
+    }
+
+    /// Returns the fragments with their lengths updated to the amount of received data.
+    pub fn fragments(&self) -> &[EfiFragmentData<'a>] {
+        &self.fragment_table[..(self.fragment_count as usize).min(N)]
+    }
+}
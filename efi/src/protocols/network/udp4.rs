@@ -0,0 +1,426 @@
+use {
+    super::common::structs::{EfiFragmentData, EfiIPv4AddressRaw},
+    crate::{
+        guid::EfiGuid,
+        protocols::{service_binding::EfiServiceBinding, EfiProtocol},
+        runtime_services::time::EfiTimeRepresentation,
+        status::{EfiStatus, EfiStatusEnum},
+        types::{EfiEvent, NonNullVoidPtr, VoidMutPtr},
+    },
+    core::{marker::PhantomData, ptr::null_mut, slice::from_raw_parts},
+};
+
+/// Implementation of EFI's `EFI_UDP4_PROTOCOL`.
+///
+/// Instances are created through [`EfiServiceBindingProtocol<EfiUdp4Protocol>`](crate::protocols::service_binding::EfiServiceBindingProtocol).
+#[repr(C)]
+pub struct EfiUdp4Protocol {
+    get_mode_data: extern "efiapi" fn(
+        *const Self,
+        *mut EfiUdp4ConfigData,
+        VoidMutPtr,
+        VoidMutPtr,
+        VoidMutPtr,
+    ) -> EfiStatus,
+    configure: extern "efiapi" fn(*const Self, *const EfiUdp4ConfigData) -> EfiStatus,
+    groups: extern "efiapi" fn(*const Self, bool, *const EfiIPv4AddressRaw) -> EfiStatus,
+    routes: extern "efiapi" fn(
+        *const Self,
+        bool,
+        *const EfiIPv4AddressRaw,
+        *const EfiIPv4AddressRaw,
+        *const EfiIPv4AddressRaw,
+    ) -> EfiStatus,
+    transmit: extern "efiapi" fn(*const Self, *mut EfiUdp4CompletionToken) -> EfiStatus,
+    receive: extern "efiapi" fn(*const Self, *mut EfiUdp4CompletionToken) -> EfiStatus,
+    cancel: extern "efiapi" fn(*const Self, *mut EfiUdp4CompletionToken) -> EfiStatus,
+    poll: extern "efiapi" fn(*const Self) -> EfiStatus,
+}
+
+impl EfiUdp4Protocol {
+    pub fn get_mode_data(&self) -> EfiStatusEnum<EfiUdp4ConfigData> {
+        let mut config_data: EfiUdp4ConfigData =
+            EfiUdp4ConfigData::new_default(0, EfiIPv4AddressRaw::new([0; 4]), 0);
+
+        (self.get_mode_data)(self, &mut config_data, null_mut(), null_mut(), null_mut())
+            .into_enum_data(|| config_data)
+    }
+
+    /// Configures the instance. Passing `None` resets the instance.
+    pub fn configure(&self, config_data: Option<&EfiUdp4ConfigData>) -> EfiStatusEnum {
+        (self.configure)(
+            self,
+            config_data.map_or(0 as _, |config_data: &EfiUdp4ConfigData| config_data),
+        )
+        .into_enum()
+    }
+
+    pub fn join_group(&self, multicast_address: EfiIPv4AddressRaw) -> EfiStatusEnum {
+        (self.groups)(self, true, &multicast_address).into_enum()
+    }
+
+    /// Leaves a multicast group. When `None` is passed, all groups are left.
+    pub fn leave_group(&self, multicast_address: Option<EfiIPv4AddressRaw>) -> EfiStatusEnum {
+        (self.groups)(
+            self,
+            false,
+            multicast_address
+                .as_ref()
+                .map_or(0 as _, |address: &EfiIPv4AddressRaw| address),
+        )
+        .into_enum()
+    }
+
+    pub fn add_route(
+        &self,
+        subnet_address: EfiIPv4AddressRaw,
+        subnet_mask: EfiIPv4AddressRaw,
+        gateway_address: EfiIPv4AddressRaw,
+    ) -> EfiStatusEnum {
+        (self.routes)(self, false, &subnet_address, &subnet_mask, &gateway_address).into_enum()
+    }
+
+    pub fn delete_route(
+        &self,
+        subnet_address: EfiIPv4AddressRaw,
+        subnet_mask: EfiIPv4AddressRaw,
+        gateway_address: EfiIPv4AddressRaw,
+    ) -> EfiStatusEnum {
+        (self.routes)(self, true, &subnet_address, &subnet_mask, &gateway_address).into_enum()
+    }
+
+    /// Queues a datagram for transmission.
+    /// # Safety
+    /// The token and all buffers it references must not be moved or dropped until the token's event is signalled or the operation is cancelled.
+    pub unsafe fn transmit(&self, token: &mut EfiUdp4CompletionToken) -> EfiStatusEnum {
+        (self.transmit)(self, token).into_enum()
+    }
+
+    /// Queues a receive request. The received data is available through [`EfiUdp4CompletionToken::receive_data`].
+    /// # Safety
+    /// The token must not be moved or dropped until it's event is signalled or the operation is cancelled.
+    pub unsafe fn receive(&self, token: &mut EfiUdp4CompletionToken) -> EfiStatusEnum {
+        (self.receive)(self, token).into_enum()
+    }
+
+    /// Cancels a queued token. When `None` is passed, all queued tokens are cancelled.
+    pub fn cancel(&self, token: Option<&mut EfiUdp4CompletionToken>) -> EfiStatusEnum {
+        (self.cancel)(
+            self,
+            token.map_or(null_mut(), |token: &mut EfiUdp4CompletionToken| token),
+        )
+        .into_enum()
+    }
+
+    pub fn poll(&self) -> EfiStatusEnum {
+        (self.poll)(self).into_enum()
+    }
+}
+
+impl EfiProtocol for EfiUdp4Protocol {
+    type Parsed = &'static Self;
+    type Error = !;
+
+    fn guid() -> EfiGuid {
+        crate::guids::EFI_UDP4_PROTOCOL
+    }
+
+    unsafe fn parse(
+        ptr: NonNullVoidPtr,
+    ) -> Result<<Self as EfiProtocol>::Parsed, <Self as EfiProtocol>::Error> {
+        Ok(&*ptr.cast().as_ptr())
+    }
+}
+
+impl EfiServiceBinding for EfiUdp4Protocol {
+    fn service_binding_guid() -> EfiGuid {
+        crate::guids::EFI_UDP4_SERVICE_BINDING_PROTOCOL
+    }
+}
+
+#[repr(C)]
+pub struct EfiUdp4ConfigData {
+    accept_broadcast: bool,
+    accept_promiscuous: bool,
+    accept_any_port: bool,
+    allow_duplicate_port: bool,
+    type_of_service: u8,
+    time_to_live: u8,
+    do_not_fragment: bool,
+    receive_timeout: u32,
+    transmit_timeout: u32,
+    use_default_address: bool,
+    station_address: EfiIPv4AddressRaw,
+    subnet_mask: EfiIPv4AddressRaw,
+    station_port: u16,
+    remote_address: EfiIPv4AddressRaw,
+    remote_port: u16,
+}
+
+impl EfiUdp4ConfigData {
+    /// Creates configuration that uses the address configured through `EFI_IP4_CONFIG2_PROTOCOL`.
+    pub fn new_default(
+        station_port: u16,
+        remote_address: EfiIPv4AddressRaw,
+        remote_port: u16,
+    ) -> Self {
+        Self {
+            accept_broadcast: false,
+            accept_promiscuous: false,
+            accept_any_port: false,
+            allow_duplicate_port: false,
+            type_of_service: 0,
+            time_to_live: 255,
+            do_not_fragment: false,
+            receive_timeout: 0,
+            transmit_timeout: 0,
+            use_default_address: true,
+            station_address: EfiIPv4AddressRaw::new([0; 4]),
+            subnet_mask: EfiIPv4AddressRaw::new([0; 4]),
+            station_port,
+            remote_address,
+            remote_port,
+        }
+    }
+
+    pub fn new(
+        station_address: EfiIPv4AddressRaw,
+        subnet_mask: EfiIPv4AddressRaw,
+        station_port: u16,
+        remote_address: EfiIPv4AddressRaw,
+        remote_port: u16,
+    ) -> Self {
+        Self {
+            use_default_address: false,
+            station_address,
+            subnet_mask,
+            ..Self::new_default(station_port, remote_address, remote_port)
+        }
+    }
+
+    pub fn accept_broadcast(&self) -> bool {
+        self.accept_broadcast
+    }
+
+    pub fn set_accept_broadcast(&mut self, accept_broadcast: bool) {
+        self.accept_broadcast = accept_broadcast;
+    }
+
+    pub fn accept_any_port(&self) -> bool {
+        self.accept_any_port
+    }
+
+    pub fn set_accept_any_port(&mut self, accept_any_port: bool) {
+        self.accept_any_port = accept_any_port;
+    }
+
+    pub fn allow_duplicate_port(&self) -> bool {
+        self.allow_duplicate_port
+    }
+
+    pub fn set_allow_duplicate_port(&mut self, allow_duplicate_port: bool) {
+        self.allow_duplicate_port = allow_duplicate_port;
+    }
+
+    /// Receive timeout in microseconds. Zero means no timeout.
+    pub fn receive_timeout(&self) -> u32 {
+        self.receive_timeout
+    }
+
+    pub fn set_receive_timeout(&mut self, receive_timeout: u32) {
+        self.receive_timeout = receive_timeout;
+    }
+
+    /// Transmit timeout in microseconds. Zero means no timeout.
+    pub fn transmit_timeout(&self) -> u32 {
+        self.transmit_timeout
+    }
+
+    pub fn set_transmit_timeout(&mut self, transmit_timeout: u32) {
+        self.transmit_timeout = transmit_timeout;
+    }
+
+    pub fn use_default_address(&self) -> bool {
+        self.use_default_address
+    }
+
+    pub fn station_address(&self) -> EfiIPv4AddressRaw {
+        self.station_address
+    }
+
+    pub fn subnet_mask(&self) -> EfiIPv4AddressRaw {
+        self.subnet_mask
+    }
+
+    pub fn station_port(&self) -> u16 {
+        self.station_port
+    }
+
+    pub fn remote_address(&self) -> EfiIPv4AddressRaw {
+        self.remote_address
+    }
+
+    pub fn remote_port(&self) -> u16 {
+        self.remote_port
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct EfiUdp4SessionData {
+    source_address: EfiIPv4AddressRaw,
+    source_port: u16,
+    destination_address: EfiIPv4AddressRaw,
+    destination_port: u16,
+}
+
+impl EfiUdp4SessionData {
+    pub fn new(
+        source_address: EfiIPv4AddressRaw,
+        source_port: u16,
+        destination_address: EfiIPv4AddressRaw,
+        destination_port: u16,
+    ) -> Self {
+        Self {
+            source_address,
+            source_port,
+            destination_address,
+            destination_port,
+        }
+    }
+
+    pub fn source_address(&self) -> EfiIPv4AddressRaw {
+        self.source_address
+    }
+
+    pub fn source_port(&self) -> u16 {
+        self.source_port
+    }
+
+    pub fn destination_address(&self) -> EfiIPv4AddressRaw {
+        self.destination_address
+    }
+
+    pub fn destination_port(&self) -> u16 {
+        self.destination_port
+    }
+}
+
+/// Completion token used by [`EfiUdp4Protocol`]'s asynchronous operations.
+#[repr(C)]
+pub struct EfiUdp4CompletionToken<'a> {
+    event: EfiEvent,
+    status: EfiStatus,
+    packet: VoidMutPtr,
+    _lifetime: PhantomData<&'a [u8]>,
+}
+
+impl<'a> EfiUdp4CompletionToken<'a> {
+    pub fn new_transmit<const N: usize>(
+        event: EfiEvent,
+        transmit_data: &'a EfiUdp4TransmitData<'a, N>,
+    ) -> Self {
+        Self {
+            event,
+            status: EfiStatus::success(),
+            packet: transmit_data as *const EfiUdp4TransmitData<N> as VoidMutPtr,
+            _lifetime: PhantomData,
+        }
+    }
+
+    pub fn new_receive(event: EfiEvent) -> Self {
+        Self {
+            event,
+            status: EfiStatus::success(),
+            packet: null_mut(),
+            _lifetime: PhantomData,
+        }
+    }
+
+    pub fn event(&self) -> EfiEvent {
+        self.event
+    }
+
+    /// Returns the completion status. Only valid after the token's event is signalled.
+    pub fn status(&self) -> EfiStatusEnum {
+        self.status.into_enum()
+    }
+
+    /// Returns the received datagram.
+    ///
+    /// The data is owned by the firmware and is released by signalling [`EfiUdp4ReceiveData::recycle_signal`].
+    pub fn receive_data(&self) -> Option<&EfiUdp4ReceiveData> {
+        if self.packet.is_null() {
+            None
+        } else {
+            Some(unsafe { &*(self.packet as *const EfiUdp4ReceiveData) })
+        }
+    }
+}
+
+/// Describes a datagram to be transmitted in `N` fragments.
+#[repr(C)]
+pub struct EfiUdp4TransmitData<'a, const N: usize> {
+    session_data: *const EfiUdp4SessionData,
+    gateway_address: *const EfiIPv4AddressRaw,
+    data_length: u32,
+    fragment_count: u32,
+    fragment_table: [EfiFragmentData<'a>; N],
+}
+
+impl<'a, const N: usize> EfiUdp4TransmitData<'a, N> {
+    /// When `session_data` is `None`, the configured addresses and ports are used.
+    pub fn new(
+        session_data: Option<&'a EfiUdp4SessionData>,
+        gateway_address: Option<&'a EfiIPv4AddressRaw>,
+        fragment_table: [EfiFragmentData<'a>; N],
+    ) -> Self {
+        Self {
+            session_data: session_data
+                .map_or(0 as _, |session_data: &EfiUdp4SessionData| session_data),
+            gateway_address: gateway_address
+                .map_or(0 as _, |gateway_address: &EfiIPv4AddressRaw| {
+                    gateway_address
+                }),
+            data_length: fragment_table
+                .iter()
+                .map(|fragment: &EfiFragmentData| fragment.len() as u32)
+                .sum(),
+            fragment_count: N as u32,
+            fragment_table,
+        }
+    }
+}
+
+#[repr(C)]
+pub struct EfiUdp4ReceiveData {
+    time_stamp: EfiTimeRepresentation,
+    recycle_signal: EfiEvent,
+    session_data: EfiUdp4SessionData,
+    data_length: u32,
+    fragment_count: u32,
+    fragment_table: [EfiFragmentData<'static>; 1],
+}
+
+impl EfiUdp4ReceiveData {
+    pub fn time_stamp(&self) -> &EfiTimeRepresentation {
+        &self.time_stamp
+    }
+
+    /// Returns the event that has to be signalled once the received data is no longer used.
+    pub fn recycle_signal(&self) -> EfiEvent {
+        self.recycle_signal
+    }
+
+    pub fn session_data(&self) -> &EfiUdp4SessionData {
+        &self.session_data
+    }
+
+    pub fn data_length(&self) -> usize {
+        self.data_length as usize
+    }
+
+    pub fn fragments(&self) -> &[EfiFragmentData<'static>] {
+        unsafe { from_raw_parts(self.fragment_table.as_ptr(), self.fragment_count as usize) }
+    }
+}
@@ -0,0 +1,317 @@
+use {
+    crate::types::VoidMutPtr,
+    core::{marker::PhantomData, mem::size_of, ptr::read_unaligned},
+};
+
+/// Width of PCI accesses shared by `EFI_PCI_ROOT_BRIDGE_IO_PROTOCOL` and `EFI_PCI_IO_PROTOCOL`.
+///
+/// The `Fifo` widths access the same address repeatedly, the `Fill` ones access the same buffer element repeatedly.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum EfiPciIoWidth {
+    Uint8,
+    Uint16,
+    Uint32,
+    Uint64,
+    FifoUint8,
+    FifoUint16,
+    FifoUint32,
+    FifoUint64,
+    FillUint8,
+    FillUint16,
+    FillUint32,
+    FillUint64,
+}
+
+mod sealed {
+    pub trait Sealed {}
+
+    impl Sealed for u8 {}
+    impl Sealed for u16 {}
+    impl Sealed for u32 {}
+    impl Sealed for u64 {}
+}
+
+/// Implemented by the integer types PCI accesses can be performed with.
+pub trait EfiPciIoUnit: sealed::Sealed + Copy {
+    const WIDTH: EfiPciIoWidth;
+}
+
+impl EfiPciIoUnit for u8 {
+    const WIDTH: EfiPciIoWidth = EfiPciIoWidth::Uint8;
+}
+
+impl EfiPciIoUnit for u16 {
+    const WIDTH: EfiPciIoWidth = EfiPciIoWidth::Uint16;
+}
+
+impl EfiPciIoUnit for u32 {
+    const WIDTH: EfiPciIoWidth = EfiPciIoWidth::Uint32;
+}
+
+impl EfiPciIoUnit for u64 {
+    const WIDTH: EfiPciIoWidth = EfiPciIoWidth::Uint64;
+}
+
+/// Type of DMA mappings created through `map`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum EfiPciIoOperation {
+    /// The device reads from system memory.
+    BusMasterRead,
+    /// The device writes to system memory.
+    BusMasterWrite,
+    /// The device and processor access the memory simultaneously.
+    BusMasterCommonBuffer,
+    /// Equivalent of [`BusMasterRead`](Self::BusMasterRead) allowing addresses above 4 GiB. Root bridges only.
+    BusMasterRead64,
+    /// Equivalent of [`BusMasterWrite`](Self::BusMasterWrite) allowing addresses above 4 GiB. Root bridges only.
+    BusMasterWrite64,
+    /// Equivalent of [`BusMasterCommonBuffer`](Self::BusMasterCommonBuffer) allowing addresses above 4 GiB. Root bridges only.
+    BusMasterCommonBuffer64,
+}
+
+/// DMA mapping returned by `map`, which has to be released through `unmap`.
+#[derive(Debug)]
+pub struct EfiPciIoMapping {
+    pub(super) device_address: u64,
+    pub(super) length: usize,
+    pub(super) mapping: VoidMutPtr,
+}
+
+impl EfiPciIoMapping {
+    /// Returns the address the device has to use.
+    pub fn device_address(&self) -> u64 {
+        self.device_address
+    }
+
+    /// Returns the number of mapped bytes, which can be less than the requested ones.
+    pub fn length(&self) -> usize {
+        self.length
+    }
+}
+
+/// PCI attributes as defined by the `EFI_PCI_ATTRIBUTE_*` and `EFI_PCI_IO_ATTRIBUTE_*` constants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EfiPciAttributes {
+    attributes: u64,
+}
+
+impl EfiPciAttributes {
+    pub const ISA_MOTHERBOARD_IO: u64 = 0x0001;
+    pub const ISA_IO: u64 = 0x0002;
+    pub const VGA_PALETTE_IO: u64 = 0x0004;
+    pub const VGA_MEMORY: u64 = 0x0008;
+    pub const VGA_IO: u64 = 0x0010;
+    pub const IDE_PRIMARY_IO: u64 = 0x0020;
+    pub const IDE_SECONDARY_IO: u64 = 0x0040;
+    pub const MEMORY_WRITE_COMBINE: u64 = 0x0080;
+    pub const IO: u64 = 0x0100;
+    pub const MEMORY: u64 = 0x0200;
+    pub const BUS_MASTER: u64 = 0x0400;
+    pub const MEMORY_CACHED: u64 = 0x0800;
+    pub const MEMORY_DISABLE: u64 = 0x1000;
+    pub const EMBEDDED_DEVICE: u64 = 0x2000;
+    pub const EMBEDDED_ROM: u64 = 0x4000;
+    pub const DUAL_ADDRESS_CYCLE: u64 = 0x8000;
+    pub const ISA_IO_16: u64 = 0x0001_0000;
+    pub const VGA_PALETTE_IO_16: u64 = 0x0002_0000;
+    pub const VGA_IO_16: u64 = 0x0004_0000;
+
+    pub const fn new(attributes: u64) -> Self {
+        Self { attributes }
+    }
+
+    pub const fn bits(&self) -> u64 {
+        self.attributes
+    }
+
+    pub const fn contains(&self, attributes: u64) -> bool {
+        self.attributes & attributes == attributes
+    }
+}
+
+/// PCI address used by the root bridge's configuration space accesses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EfiPciConfigurationAddress {
+    pub bus: u8,
+    pub device: u8,
+    pub function: u8,
+    pub register: u32,
+}
+
+impl EfiPciConfigurationAddress {
+    pub const fn new(bus: u8, device: u8, function: u8, register: u32) -> Self {
+        Self {
+            bus,
+            device,
+            function,
+            register,
+        }
+    }
+
+    /// Encodes the address. Registers above `0xFF` are encoded as extended registers.
+    pub const fn encode(&self) -> u64 {
+        let address: u64 =
+            (self.bus as u64) << 24 | (self.device as u64) << 16 | (self.function as u64) << 8;
+
+        if self.register > 0xFF {
+            address | (self.register as u64) << 32
+        } else {
+            address | self.register as u64
+        }
+    }
+}
+
+/// Location of a PCI function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EfiPciLocation {
+    pub segment: usize,
+    pub bus: usize,
+    pub device: usize,
+    pub function: usize,
+}
+
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+struct EfiAcpiAddressSpaceDescriptorRaw {
+    descriptor: u8,
+    length: u16,
+    resource_type: u8,
+    general_flags: u8,
+    specific_flags: u8,
+    granularity: u64,
+    range_minimum: u64,
+    range_maximum: u64,
+    translation_offset: u64,
+    address_length: u64,
+}
+
+const ACPI_ADDRESS_SPACE_DESCRIPTOR: u8 = 0x8A;
+
+/// Type of the resource described by an [`EfiAcpiAddressSpaceDescriptor`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum EfiAcpiResourceType {
+    Memory,
+    Io,
+    BusNumber,
+    Other(u8),
+}
+
+/// ACPI QWORD address space descriptor describing a root bridge's or BAR's resources.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EfiAcpiAddressSpaceDescriptor {
+    resource_type: u8,
+    general_flags: u8,
+    specific_flags: u8,
+    granularity: u64,
+    range_minimum: u64,
+    range_maximum: u64,
+    translation_offset: u64,
+    address_length: u64,
+}
+
+impl EfiAcpiAddressSpaceDescriptor {
+    pub fn resource_type(&self) -> EfiAcpiResourceType {
+        match self.resource_type {
+            0 => EfiAcpiResourceType::Memory,
+            1 => EfiAcpiResourceType::Io,
+            2 => EfiAcpiResourceType::BusNumber,
+            resource_type => EfiAcpiResourceType::Other(resource_type),
+        }
+    }
+
+    pub fn general_flags(&self) -> u8 {
+        self.general_flags
+    }
+
+    /// Returns the type specific flags (e.g.: cacheability and prefetchability of memory).
+    pub fn specific_flags(&self) -> u8 {
+        self.specific_flags
+    }
+
+    /// Returns the address space granularity (e.g.: `32` or `64` for memory).
+    pub fn granularity(&self) -> u64 {
+        self.granularity
+    }
+
+    pub fn range_minimum(&self) -> u64 {
+        self.range_minimum
+    }
+
+    pub fn range_maximum(&self) -> u64 {
+        self.range_maximum
+    }
+
+    pub fn translation_offset(&self) -> u64 {
+        self.translation_offset
+    }
+
+    pub fn address_length(&self) -> u64 {
+        self.address_length
+    }
+}
+
+/// Iterator over a list of ACPI address space descriptors terminated by an end tag.
+pub struct EfiAcpiAddressSpaceDescriptors<'a> {
+    pointer: *const u8,
+    _lifetime: PhantomData<&'a u8>,
+}
+
+impl<'a> EfiAcpiAddressSpaceDescriptors<'a> {
+    /// # Safety
+    /// The pointer must point to a list of descriptors terminated by an end tag.
+    pub unsafe fn new(pointer: *const u8) -> Self {
+        Self {
+            pointer,
+            _lifetime: PhantomData,
+        }
+    }
+
+    /// Returns the pointer to the beginning of the remaining descriptors (e.g.: to release them).
+    pub fn as_ptr(&self) -> *const u8 {
+        self.pointer
+    }
+}
+
+impl Iterator for EfiAcpiAddressSpaceDescriptors<'_> {
+    type Item = EfiAcpiAddressSpaceDescriptor;
+
+    fn next(&mut self) -> Option<<Self as Iterator>::Item> {
+        if self.pointer.is_null() {
+            return None;
+        }
+
+        let descriptor: u8 = unsafe { *self.pointer };
+
+        /* Anything besides address space descriptors (including the end tag) ends the list */
+        if descriptor != ACPI_ADDRESS_SPACE_DESCRIPTOR {
+            return None;
+        }
+
+        let raw: EfiAcpiAddressSpaceDescriptorRaw =
+            unsafe { read_unaligned(self.pointer as *const EfiAcpiAddressSpaceDescriptorRaw) };
+
+        /* The length doesn't include the descriptor's type and length fields */
+        let length: usize = usize::from(raw.length) + 3;
+
+        if length < size_of::<EfiAcpiAddressSpaceDescriptorRaw>() {
+            return None;
+        }
+
+        self.pointer = unsafe { self.pointer.add(length) };
+
+        Some(EfiAcpiAddressSpaceDescriptor {
+            resource_type: raw.resource_type,
+            general_flags: raw.general_flags,
+            specific_flags: raw.specific_flags,
+            granularity: raw.granularity,
+            range_minimum: raw.range_minimum,
+            range_maximum: raw.range_maximum,
+            translation_offset: raw.translation_offset,
+            address_length: raw.address_length,
+        })
+    }
+}
@@ -0,0 +1,5 @@
+mod common;
+mod pci_io;
+mod root_bridge_io;
+
+pub use {common::*, pci_io::*, root_bridge_io::*};
@@ -0,0 +1,416 @@
+use {
+    super::common::{
+        EfiAcpiAddressSpaceDescriptors, EfiPciAttributes, EfiPciIoMapping, EfiPciIoOperation,
+        EfiPciIoUnit, EfiPciIoWidth, EfiPciLocation,
+    },
+    crate::{
+        boot_services::types::memory::{EfiAllocateType, EfiMemoryType},
+        guid::EfiGuid,
+        protocols::EfiProtocol,
+        status::{EfiStatus, EfiStatusEnum},
+        types::{EfiPhysicalAddress, NonNullVoidPtr, VoidMutPtr, VoidPtr},
+    },
+    core::slice::from_raw_parts,
+};
+
+#[repr(C)]
+struct EfiPciIoBarAccess {
+    read: extern "efiapi" fn(
+        *const EfiPciIoProtocol,
+        EfiPciIoWidth,
+        u8,
+        u64,
+        usize,
+        VoidMutPtr,
+    ) -> EfiStatus,
+    write: extern "efiapi" fn(
+        *const EfiPciIoProtocol,
+        EfiPciIoWidth,
+        u8,
+        u64,
+        usize,
+        VoidPtr,
+    ) -> EfiStatus,
+}
+
+impl EfiPciIoBarAccess {
+    fn read<T: EfiPciIoUnit>(
+        &self,
+        this: &EfiPciIoProtocol,
+        bar: u8,
+        offset: u64,
+        buffer: &mut [T],
+    ) -> EfiStatusEnum {
+        (self.read)(
+            this,
+            T::WIDTH,
+            bar,
+            offset,
+            buffer.len(),
+            buffer.as_mut_ptr() as VoidMutPtr,
+        )
+        .into_enum()
+    }
+
+    fn write<T: EfiPciIoUnit>(
+        &self,
+        this: &EfiPciIoProtocol,
+        bar: u8,
+        offset: u64,
+        buffer: &[T],
+    ) -> EfiStatusEnum {
+        (self.write)(
+            this,
+            T::WIDTH,
+            bar,
+            offset,
+            buffer.len(),
+            buffer.as_ptr() as VoidPtr,
+        )
+        .into_enum()
+    }
+}
+
+#[repr(C)]
+struct EfiPciIoConfigurationAccess {
+    read: extern "efiapi" fn(
+        *const EfiPciIoProtocol,
+        EfiPciIoWidth,
+        u32,
+        usize,
+        VoidMutPtr,
+    ) -> EfiStatus,
+    write: extern "efiapi" fn(
+        *const EfiPciIoProtocol,
+        EfiPciIoWidth,
+        u32,
+        usize,
+        VoidPtr,
+    ) -> EfiStatus,
+}
+
+type EfiPciIoPoll = extern "efiapi" fn(
+    *const EfiPciIoProtocol,
+    EfiPciIoWidth,
+    u8,
+    u64,
+    u64,
+    u64,
+    u64,
+    *mut u64,
+) -> EfiStatus;
+
+/// Operation performed by [`EfiPciIoProtocol::attributes`].
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum EfiPciIoAttributeOperation {
+    Get,
+    Set,
+    Enable,
+    Disable,
+    Supported,
+}
+
+/// Implementation of EFI's `EFI_PCI_IO_PROTOCOL`.
+#[repr(C)]
+pub struct EfiPciIoProtocol {
+    poll_mem: EfiPciIoPoll,
+    poll_io: EfiPciIoPoll,
+    mem: EfiPciIoBarAccess,
+    io: EfiPciIoBarAccess,
+    pci: EfiPciIoConfigurationAccess,
+    copy_mem: extern "efiapi" fn(*const Self, EfiPciIoWidth, u8, u64, u8, u64, usize) -> EfiStatus,
+    map: extern "efiapi" fn(
+        *const Self,
+        EfiPciIoOperation,
+        VoidPtr,
+        *mut usize,
+        *mut EfiPhysicalAddress,
+        *mut VoidMutPtr,
+    ) -> EfiStatus,
+    unmap: extern "efiapi" fn(*const Self, VoidMutPtr) -> EfiStatus,
+    allocate_buffer: extern "efiapi" fn(
+        *const Self,
+        EfiAllocateType,
+        EfiMemoryType,
+        usize,
+        *mut VoidMutPtr,
+        u64,
+    ) -> EfiStatus,
+    free_buffer: extern "efiapi" fn(*const Self, usize, VoidMutPtr) -> EfiStatus,
+    flush: extern "efiapi" fn(*const Self) -> EfiStatus,
+    get_location: extern "efiapi" fn(
+        *const Self,
+        *mut usize,
+        *mut usize,
+        *mut usize,
+        *mut usize,
+    ) -> EfiStatus,
+    attributes:
+        extern "efiapi" fn(*const Self, EfiPciIoAttributeOperation, u64, *mut u64) -> EfiStatus,
+    get_bar_attributes: extern "efiapi" fn(*const Self, u8, *mut u64, *mut VoidPtr) -> EfiStatus,
+    set_bar_attributes: extern "efiapi" fn(*const Self, u64, u8, *mut u64, *mut u64) -> EfiStatus,
+    rom_size: u64,
+    rom_image: VoidPtr,
+}
+
+impl EfiPciIoProtocol {
+    /// Polls the offset within the BAR until `(value & mask) == expected` or the delay (in 100ns units) expires.
+    ///
+    /// Returns the last read value.
+    pub fn poll_mem(
+        &self,
+        width: EfiPciIoWidth,
+        bar: u8,
+        offset: u64,
+        mask: u64,
+        expected: u64,
+        delay: u64,
+    ) -> EfiStatusEnum<u64> {
+        let mut result: u64 = 0;
+
+        (self.poll_mem)(self, width, bar, offset, mask, expected, delay, &mut result)
+            .into_enum_data(|| result)
+    }
+
+    /// Polls the offset within the BAR until `(value & mask) == expected` or the delay (in 100ns units) expires.
+    ///
+    /// Returns the last read value.
+    pub fn poll_io(
+        &self,
+        width: EfiPciIoWidth,
+        bar: u8,
+        offset: u64,
+        mask: u64,
+        expected: u64,
+        delay: u64,
+    ) -> EfiStatusEnum<u64> {
+        let mut result: u64 = 0;
+
+        (self.poll_io)(self, width, bar, offset, mask, expected, delay, &mut result)
+            .into_enum_data(|| result)
+    }
+
+    pub fn mem_read<T: EfiPciIoUnit>(
+        &self,
+        bar: u8,
+        offset: u64,
+        buffer: &mut [T],
+    ) -> EfiStatusEnum {
+        self.mem.read(self, bar, offset, buffer)
+    }
+
+    pub fn mem_write<T: EfiPciIoUnit>(&self, bar: u8, offset: u64, buffer: &[T]) -> EfiStatusEnum {
+        self.mem.write(self, bar, offset, buffer)
+    }
+
+    pub fn io_read<T: EfiPciIoUnit>(
+        &self,
+        bar: u8,
+        offset: u64,
+        buffer: &mut [T],
+    ) -> EfiStatusEnum {
+        self.io.read(self, bar, offset, buffer)
+    }
+
+    pub fn io_write<T: EfiPciIoUnit>(&self, bar: u8, offset: u64, buffer: &[T]) -> EfiStatusEnum {
+        self.io.write(self, bar, offset, buffer)
+    }
+
+    /// Reads from the function's configuration space.
+    pub fn pci_read<T: EfiPciIoUnit>(&self, offset: u32, buffer: &mut [T]) -> EfiStatusEnum {
+        (self.pci.read)(
+            self,
+            T::WIDTH,
+            offset,
+            buffer.len(),
+            buffer.as_mut_ptr() as VoidMutPtr,
+        )
+        .into_enum()
+    }
+
+    /// Writes to the function's configuration space.
+    pub fn pci_write<T: EfiPciIoUnit>(&self, offset: u32, buffer: &[T]) -> EfiStatusEnum {
+        (self.pci.write)(
+            self,
+            T::WIDTH,
+            offset,
+            buffer.len(),
+            buffer.as_ptr() as VoidPtr,
+        )
+        .into_enum()
+    }
+
+    /// Copies `count` elements of the given width between offsets within the BARs.
+    pub fn copy_mem(
+        &self,
+        width: EfiPciIoWidth,
+        (destination_bar, destination_offset): (u8, u64),
+        (source_bar, source_offset): (u8, u64),
+        count: usize,
+    ) -> EfiStatusEnum {
+        (self.copy_mem)(
+            self,
+            width,
+            destination_bar,
+            destination_offset,
+            source_bar,
+            source_offset,
+            count,
+        )
+        .into_enum()
+    }
+
+    /// Maps system memory for DMA.
+    /// # Safety
+    /// The memory must stay valid until the mapping is released through [`unmap`](Self::unmap).
+    pub unsafe fn map(
+        &self,
+        operation: EfiPciIoOperation,
+        host_address: VoidPtr,
+        size: usize,
+    ) -> EfiStatusEnum<EfiPciIoMapping> {
+        let mut mapping: EfiPciIoMapping = EfiPciIoMapping {
+            device_address: 0,
+            length: size,
+            mapping: 0 as _,
+        };
+
+        (self.map)(
+            self,
+            operation,
+            host_address,
+            &mut mapping.length,
+            &mut mapping.device_address,
+            &mut mapping.mapping,
+        )
+        .into_enum_data(|| mapping)
+    }
+
+    pub fn unmap(&self, mapping: EfiPciIoMapping) -> EfiStatusEnum {
+        (self.unmap)(self, mapping.mapping).into_enum()
+    }
+
+    /// Allocates pages suitable for [`EfiPciIoOperation::BusMasterCommonBuffer`] mappings.
+    ///
+    /// Only [`EfiPciAttributes::MEMORY_WRITE_COMBINE`], [`EfiPciAttributes::MEMORY_CACHED`] and [`EfiPciAttributes::DUAL_ADDRESS_CYCLE`] are accepted.
+    pub fn allocate_buffer(
+        &self,
+        memory_type: EfiMemoryType,
+        pages: usize,
+        attributes: EfiPciAttributes,
+    ) -> EfiStatusEnum<VoidMutPtr> {
+        let mut host_address: VoidMutPtr = 0 as _;
+
+        (self.allocate_buffer)(
+            self,
+            EfiAllocateType::AllocateAnyPages,
+            memory_type,
+            pages,
+            &mut host_address,
+            attributes.bits(),
+        )
+        .into_enum_data(|| host_address)
+    }
+
+    /// Frees pages allocated through [`allocate_buffer`](Self::allocate_buffer).
+    /// # Safety
+    /// The address and page count must be the ones of an allocation that is no longer used.
+    pub unsafe fn free_buffer(&self, pages: usize, host_address: VoidMutPtr) -> EfiStatusEnum {
+        (self.free_buffer)(self, pages, host_address).into_enum()
+    }
+
+    /// Flushes posted writes to system memory.
+    pub fn flush(&self) -> EfiStatusEnum {
+        (self.flush)(self).into_enum()
+    }
+
+    /// Returns the segment, bus, device and function numbers of the controller.
+    pub fn get_location(&self) -> EfiStatusEnum<EfiPciLocation> {
+        let mut location: EfiPciLocation = EfiPciLocation {
+            segment: 0,
+            bus: 0,
+            device: 0,
+            function: 0,
+        };
+
+        (self.get_location)(
+            self,
+            &mut location.segment,
+            &mut location.bus,
+            &mut location.device,
+            &mut location.function,
+        )
+        .into_enum_data(|| location)
+    }
+
+    /// Performs the attribute operation. Returns the resulting attributes for [`EfiPciIoAttributeOperation::Get`] and [`EfiPciIoAttributeOperation::Supported`].
+    pub fn attributes(
+        &self,
+        operation: EfiPciIoAttributeOperation,
+        attributes: EfiPciAttributes,
+    ) -> EfiStatusEnum<EfiPciAttributes> {
+        let mut result: u64 = 0;
+
+        let result_ptr: *mut u64 = match operation {
+            EfiPciIoAttributeOperation::Get | EfiPciIoAttributeOperation::Supported => &mut result,
+            _ => 0 as _,
+        };
+
+        (self.attributes)(self, operation, attributes.bits(), result_ptr)
+            .into_enum_data(|| EfiPciAttributes::new(result))
+    }
+
+    /// Returns the attributes supported by the BAR and it's resource descriptors.
+    ///
+    /// The descriptors' buffer is allocated by the firmware and must be released through `free_pool` using [`EfiAcpiAddressSpaceDescriptors::as_ptr`].
+    pub fn get_bar_attributes(
+        &self,
+        bar: u8,
+    ) -> EfiStatusEnum<(EfiPciAttributes, EfiAcpiAddressSpaceDescriptors<'static>)> {
+        let mut supports: u64 = 0;
+        let mut resources: VoidPtr = 0 as _;
+
+        (self.get_bar_attributes)(self, bar, &mut supports, &mut resources).into_enum_data(|| {
+            (EfiPciAttributes::new(supports), unsafe {
+                EfiAcpiAddressSpaceDescriptors::new(resources as _)
+            })
+        })
+    }
+
+    /// Sets the attributes of the range within the BAR, which may be adjusted by the firmware.
+    pub fn set_bar_attributes(
+        &self,
+        attributes: EfiPciAttributes,
+        bar: u8,
+        offset: &mut u64,
+        length: &mut u64,
+    ) -> EfiStatusEnum {
+        (self.set_bar_attributes)(self, attributes.bits(), bar, offset, length).into_enum()
+    }
+
+    /// Returns the copy of the option ROM, if the controller has one.
+    pub fn rom_image(&self) -> Option<&[u8]> {
+        if self.rom_image.is_null() || self.rom_size == 0 {
+            None
+        } else {
+            Some(unsafe { from_raw_parts(self.rom_image as *const u8, self.rom_size as usize) })
+        }
+    }
+}
+
+impl EfiProtocol for EfiPciIoProtocol {
+    type Parsed = &'static Self;
+    type Error = !;
+
+    fn guid() -> EfiGuid {
+        crate::guids::EFI_PCI_IO_PROTOCOL
+    }
+
+    unsafe fn parse(
+        ptr: NonNullVoidPtr,
+    ) -> Result<<Self as EfiProtocol>::Parsed, <Self as EfiProtocol>::Error> {
+        Ok(&*ptr.cast().as_ptr())
+    }
+}
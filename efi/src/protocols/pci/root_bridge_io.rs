@@ -0,0 +1,315 @@
+use {
+    super::common::{
+        EfiAcpiAddressSpaceDescriptors, EfiPciAttributes, EfiPciConfigurationAddress,
+        EfiPciIoMapping, EfiPciIoOperation, EfiPciIoUnit, EfiPciIoWidth,
+    },
+    crate::{
+        boot_services::types::memory::{EfiAllocateType, EfiMemoryType},
+        guid::EfiGuid,
+        protocols::EfiProtocol,
+        status::{EfiStatus, EfiStatusEnum},
+        types::{EfiHandle, EfiPhysicalAddress, NonNullVoidPtr, VoidMutPtr, VoidPtr},
+    },
+};
+
+#[repr(C)]
+struct EfiPciRootBridgeIoAccess {
+    read: extern "efiapi" fn(
+        *const EfiPciRootBridgeIoProtocol,
+        EfiPciIoWidth,
+        u64,
+        usize,
+        VoidMutPtr,
+    ) -> EfiStatus,
+    write: extern "efiapi" fn(
+        *const EfiPciRootBridgeIoProtocol,
+        EfiPciIoWidth,
+        u64,
+        usize,
+        VoidPtr,
+    ) -> EfiStatus,
+}
+
+impl EfiPciRootBridgeIoAccess {
+    fn read<T: EfiPciIoUnit>(
+        &self,
+        this: &EfiPciRootBridgeIoProtocol,
+        address: u64,
+        buffer: &mut [T],
+    ) -> EfiStatusEnum {
+        (self.read)(
+            this,
+            T::WIDTH,
+            address,
+            buffer.len(),
+            buffer.as_mut_ptr() as VoidMutPtr,
+        )
+        .into_enum()
+    }
+
+    fn write<T: EfiPciIoUnit>(
+        &self,
+        this: &EfiPciRootBridgeIoProtocol,
+        address: u64,
+        buffer: &[T],
+    ) -> EfiStatusEnum {
+        (self.write)(
+            this,
+            T::WIDTH,
+            address,
+            buffer.len(),
+            buffer.as_ptr() as VoidPtr,
+        )
+        .into_enum()
+    }
+}
+
+type EfiPciRootBridgeIoPoll = extern "efiapi" fn(
+    *const EfiPciRootBridgeIoProtocol,
+    EfiPciIoWidth,
+    u64,
+    u64,
+    u64,
+    u64,
+    *mut u64,
+) -> EfiStatus;
+
+/// Implementation of EFI's `EFI_PCI_ROOT_BRIDGE_IO_PROTOCOL`.
+#[repr(C)]
+pub struct EfiPciRootBridgeIoProtocol {
+    parent_handle: EfiHandle,
+    poll_mem: EfiPciRootBridgeIoPoll,
+    poll_io: EfiPciRootBridgeIoPoll,
+    mem: EfiPciRootBridgeIoAccess,
+    io: EfiPciRootBridgeIoAccess,
+    pci: EfiPciRootBridgeIoAccess,
+    copy_mem: extern "efiapi" fn(*const Self, EfiPciIoWidth, u64, u64, usize) -> EfiStatus,
+    map: extern "efiapi" fn(
+        *const Self,
+        EfiPciIoOperation,
+        VoidPtr,
+        *mut usize,
+        *mut EfiPhysicalAddress,
+        *mut VoidMutPtr,
+    ) -> EfiStatus,
+    unmap: extern "efiapi" fn(*const Self, VoidMutPtr) -> EfiStatus,
+    allocate_buffer: extern "efiapi" fn(
+        *const Self,
+        EfiAllocateType,
+        EfiMemoryType,
+        usize,
+        *mut VoidMutPtr,
+        u64,
+    ) -> EfiStatus,
+    free_buffer: extern "efiapi" fn(*const Self, usize, VoidMutPtr) -> EfiStatus,
+    flush: extern "efiapi" fn(*const Self) -> EfiStatus,
+    get_attributes: extern "efiapi" fn(*const Self, *mut u64, *mut u64) -> EfiStatus,
+    set_attributes: extern "efiapi" fn(*const Self, u64, *mut u64, *mut u64) -> EfiStatus,
+    configuration: extern "efiapi" fn(*const Self, *mut VoidPtr) -> EfiStatus,
+    segment_number: u32,
+}
+
+impl EfiPciRootBridgeIoProtocol {
+    /// Returns the handle of the host bridge the root bridge belongs to.
+    pub fn parent_handle(&self) -> EfiHandle {
+        self.parent_handle
+    }
+
+    pub fn segment_number(&self) -> u32 {
+        self.segment_number
+    }
+
+    /// Polls the memory address until `(value & mask) == expected` or the delay (in 100ns units) expires.
+    ///
+    /// Returns the last read value.
+    pub fn poll_mem(
+        &self,
+        width: EfiPciIoWidth,
+        address: u64,
+        mask: u64,
+        expected: u64,
+        delay: u64,
+    ) -> EfiStatusEnum<u64> {
+        let mut result: u64 = 0;
+
+        (self.poll_mem)(self, width, address, mask, expected, delay, &mut result)
+            .into_enum_data(|| result)
+    }
+
+    /// Polls the I/O address until `(value & mask) == expected` or the delay (in 100ns units) expires.
+    ///
+    /// Returns the last read value.
+    pub fn poll_io(
+        &self,
+        width: EfiPciIoWidth,
+        address: u64,
+        mask: u64,
+        expected: u64,
+        delay: u64,
+    ) -> EfiStatusEnum<u64> {
+        let mut result: u64 = 0;
+
+        (self.poll_io)(self, width, address, mask, expected, delay, &mut result)
+            .into_enum_data(|| result)
+    }
+
+    pub fn mem_read<T: EfiPciIoUnit>(&self, address: u64, buffer: &mut [T]) -> EfiStatusEnum {
+        self.mem.read(self, address, buffer)
+    }
+
+    pub fn mem_write<T: EfiPciIoUnit>(&self, address: u64, buffer: &[T]) -> EfiStatusEnum {
+        self.mem.write(self, address, buffer)
+    }
+
+    pub fn io_read<T: EfiPciIoUnit>(&self, address: u64, buffer: &mut [T]) -> EfiStatusEnum {
+        self.io.read(self, address, buffer)
+    }
+
+    pub fn io_write<T: EfiPciIoUnit>(&self, address: u64, buffer: &[T]) -> EfiStatusEnum {
+        self.io.write(self, address, buffer)
+    }
+
+    /// Reads from the configuration space of the function at the given address.
+    pub fn pci_read<T: EfiPciIoUnit>(
+        &self,
+        address: EfiPciConfigurationAddress,
+        buffer: &mut [T],
+    ) -> EfiStatusEnum {
+        self.pci.read(self, address.encode(), buffer)
+    }
+
+    /// Writes to the configuration space of the function at the given address.
+    pub fn pci_write<T: EfiPciIoUnit>(
+        &self,
+        address: EfiPciConfigurationAddress,
+        buffer: &[T],
+    ) -> EfiStatusEnum {
+        self.pci.write(self, address.encode(), buffer)
+    }
+
+    /// Copies `count` elements of the given width between memory mapped addresses.
+    pub fn copy_mem(
+        &self,
+        width: EfiPciIoWidth,
+        destination: u64,
+        source: u64,
+        count: usize,
+    ) -> EfiStatusEnum {
+        (self.copy_mem)(self, width, destination, source, count).into_enum()
+    }
+
+    /// Maps system memory for DMA.
+    /// # Safety
+    /// The memory must stay valid until the mapping is released through [`unmap`](Self::unmap).
+    pub unsafe fn map(
+        &self,
+        operation: EfiPciIoOperation,
+        host_address: VoidPtr,
+        size: usize,
+    ) -> EfiStatusEnum<EfiPciIoMapping> {
+        let mut mapping: EfiPciIoMapping = EfiPciIoMapping {
+            device_address: 0,
+            length: size,
+            mapping: 0 as _,
+        };
+
+        (self.map)(
+            self,
+            operation,
+            host_address,
+            &mut mapping.length,
+            &mut mapping.device_address,
+            &mut mapping.mapping,
+        )
+        .into_enum_data(|| mapping)
+    }
+
+    pub fn unmap(&self, mapping: EfiPciIoMapping) -> EfiStatusEnum {
+        (self.unmap)(self, mapping.mapping).into_enum()
+    }
+
+    /// Allocates pages suitable for [`EfiPciIoOperation::BusMasterCommonBuffer`] mappings.
+    ///
+    /// Only [`EfiPciAttributes::MEMORY_WRITE_COMBINE`], [`EfiPciAttributes::MEMORY_CACHED`] and [`EfiPciAttributes::DUAL_ADDRESS_CYCLE`] are accepted.
+    pub fn allocate_buffer(
+        &self,
+        memory_type: EfiMemoryType,
+        pages: usize,
+        attributes: EfiPciAttributes,
+    ) -> EfiStatusEnum<VoidMutPtr> {
+        let mut host_address: VoidMutPtr = 0 as _;
+
+        (self.allocate_buffer)(
+            self,
+            EfiAllocateType::AllocateAnyPages,
+            memory_type,
+            pages,
+            &mut host_address,
+            attributes.bits(),
+        )
+        .into_enum_data(|| host_address)
+    }
+
+    /// Frees pages allocated through [`allocate_buffer`](Self::allocate_buffer).
+    /// # Safety
+    /// The address and page count must be the ones of an allocation that is no longer used.
+    pub unsafe fn free_buffer(&self, pages: usize, host_address: VoidMutPtr) -> EfiStatusEnum {
+        (self.free_buffer)(self, pages, host_address).into_enum()
+    }
+
+    /// Flushes posted writes to system memory.
+    pub fn flush(&self) -> EfiStatusEnum {
+        (self.flush)(self).into_enum()
+    }
+
+    /// Returns the supported and the currently set attributes.
+    pub fn get_attributes(&self) -> EfiStatusEnum<(EfiPciAttributes, EfiPciAttributes)> {
+        let (mut supports, mut attributes): (u64, u64) = (0, 0);
+
+        (self.get_attributes)(self, &mut supports, &mut attributes).into_enum_data(|| {
+            (
+                EfiPciAttributes::new(supports),
+                EfiPciAttributes::new(attributes),
+            )
+        })
+    }
+
+    /// Sets the attributes. Memory attributes apply to the given resource range, which may be adjusted by the firmware.
+    pub fn set_attributes(
+        &self,
+        attributes: EfiPciAttributes,
+        resource: Option<(&mut u64, &mut u64)>,
+    ) -> EfiStatusEnum {
+        let (base, length): (*mut u64, *mut u64) = resource
+            .map_or((0 as _, 0 as _), |(base, length): (&mut u64, &mut u64)| {
+                (base, length)
+            });
+
+        (self.set_attributes)(self, attributes.bits(), base, length).into_enum()
+    }
+
+    /// Returns the resources assigned to the root bridge.
+    ///
+    /// The descriptors are owned by the firmware and must not be freed.
+    pub fn configuration(&self) -> EfiStatusEnum<EfiAcpiAddressSpaceDescriptors<'_>> {
+        let mut resources: VoidPtr = 0 as _;
+
+        (self.configuration)(self, &mut resources)
+            .into_enum_data(|| unsafe { EfiAcpiAddressSpaceDescriptors::new(resources as _) })
+    }
+}
+
+impl EfiProtocol for EfiPciRootBridgeIoProtocol {
+    type Parsed = &'static Self;
+    type Error = !;
+
+    fn guid() -> EfiGuid {
+        crate::guids::EFI_PCI_ROOT_BRIDGE_IO_PROTOCOL
+    }
+
+    unsafe fn parse(
+        ptr: NonNullVoidPtr,
+    ) -> Result<<Self as EfiProtocol>::Parsed, <Self as EfiProtocol>::Error> {
+        Ok(&*ptr.cast().as_ptr())
+    }
+}
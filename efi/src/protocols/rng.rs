@@ -0,0 +1,63 @@
+use {
+    crate::{
+        guid::EfiGuid,
+        protocols::EfiProtocol,
+        status::{EfiStatus, EfiStatusEnum},
+        types::NonNullVoidPtr,
+    },
+    core::mem::{size_of, size_of_val},
+};
+
+/// Implementation of EFI's `EFI_RNG_PROTOCOL`.
+#[repr(C)]
+pub struct EfiRngProtocol {
+    get_info: extern "efiapi" fn(*const Self, *mut usize, *mut EfiGuid) -> EfiStatus,
+    get_rng: extern "efiapi" fn(*const Self, *const EfiGuid, usize, *mut u8) -> EfiStatus,
+}
+
+impl EfiRngProtocol {
+    /// Fills `algorithms` with the GUIDs of the supported algorithms and returns the filled part.
+    ///
+    /// Returns the number of supported algorithms when the buffer is too small.
+    pub fn get_info<'a>(
+        &self,
+        algorithms: &'a mut [EfiGuid],
+    ) -> EfiStatusEnum<&'a [EfiGuid], usize> {
+        let mut size: usize = size_of_val(algorithms);
+
+        let algorithms_ptr: *mut EfiGuid = algorithms.as_mut_ptr();
+
+        (self.get_info)(self, &mut size, algorithms_ptr).into_enum_data_error(
+            move || &algorithms[..size / size_of::<EfiGuid>()],
+            || size / size_of::<EfiGuid>(),
+        )
+    }
+
+    /// Fills `buffer` with random data.
+    ///
+    /// When `algorithm` is `None`, the firmware's default algorithm is used.
+    pub fn get_rng(&self, algorithm: Option<&EfiGuid>, buffer: &mut [u8]) -> EfiStatusEnum {
+        (self.get_rng)(
+            self,
+            algorithm.map_or(0 as _, |algorithm: &EfiGuid| algorithm),
+            buffer.len(),
+            buffer.as_mut_ptr(),
+        )
+        .into_enum()
+    }
+}
+
+impl EfiProtocol for EfiRngProtocol {
+    type Parsed = &'static Self;
+    type Error = !;
+
+    fn guid() -> EfiGuid {
+        crate::guids::EFI_RNG_PROTOCOL
+    }
+
+    unsafe fn parse(
+        ptr: NonNullVoidPtr,
+    ) -> Result<<Self as EfiProtocol>::Parsed, <Self as EfiProtocol>::Error> {
+        Ok(&*ptr.cast().as_ptr())
+    }
+}
@@ -0,0 +1,153 @@
+use {
+    crate::{
+        boot_services::{types::protocol_handler::EfiOpenProtocolAttributes, EfiBootServices1x1},
+        guid::EfiGuid,
+        protocols::EfiProtocol,
+        status::{EfiStatus, EfiStatusEnum},
+        types::{EfiFirmwareFault, EfiHandle, NonNullVoidPtr},
+    },
+    core::{marker::PhantomData, ops::Deref},
+};
+
+/// Implemented by protocols which instances are created through a service binding protocol.
+pub trait EfiServiceBinding: EfiProtocol<Parsed = &'static Self, Error = !> + 'static {
+    fn service_binding_guid() -> EfiGuid;
+}
+
+/// Implementation of EFI's `EFI_SERVICE_BINDING_PROTOCOL`.
+///
+/// The protocol's GUID is determined by the protocol it creates instances of.
+#[repr(C)]
+pub struct EfiServiceBindingProtocol<T>
+where
+    T: EfiServiceBinding,
+{
+    create_child: extern "efiapi" fn(*const Self, *mut EfiHandle) -> EfiStatus,
+    destroy_child: extern "efiapi" fn(*const Self, EfiHandle) -> EfiStatus,
+    _protocol: PhantomData<*const T>,
+}
+
+impl<T> EfiServiceBindingProtocol<T>
+where
+    T: EfiServiceBinding,
+{
+    /// Creates a child handle and installs the protocol on it.
+    ///
+    /// When `handle` is passed, the protocol is installed on the existing handle instead.
+    pub fn create_child(&self, handle: Option<EfiHandle>) -> EfiStatusEnum<EfiHandle> {
+        let mut handle: EfiHandle = handle.unwrap_or(0 as _);
+
+        (self.create_child)(self, &mut handle).into_enum_data(|| handle)
+    }
+
+    pub fn destroy_child(&self, handle: EfiHandle) -> EfiStatusEnum {
+        (self.destroy_child)(self, handle).into_enum()
+    }
+
+    /// Creates a child handle and opens the protocol on it on behalf of `agent_handle`.
+    ///
+    /// The child is destroyed when the returned object is dropped.
+    pub fn open_child<'a>(
+        &'a self,
+        boot_services: &EfiBootServices1x1,
+        agent_handle: EfiHandle,
+    ) -> Result<EfiStatusEnum<EfiServiceChild<'a, T>>, EfiFirmwareFault> {
+        let (warning, handle) = match self.create_child(None).unfold() {
+            Ok(data) => data,
+            Err((status, ())) => return Ok(EfiStatusEnum::Error(status, ())),
+        };
+
+        let result = boot_services.open_protocol::<T>(
+            handle,
+            agent_handle,
+            None,
+            EfiOpenProtocolAttributes::GetProtocol,
+        );
+
+        let protocol: &'static T = match result.map(EfiStatusEnum::unfold) {
+            Ok(Ok((_, Ok(protocol)))) => protocol,
+            Ok(Ok((_, Err(error)))) => error,
+            Ok(Err((status, ()))) => {
+                let _ = self.destroy_child(handle);
+
+                return Ok(EfiStatusEnum::Error(status, ()));
+            }
+            Err(error) => {
+                let _ = self.destroy_child(handle);
+
+                return Err(error);
+            }
+        };
+
+        let child: EfiServiceChild<T> = EfiServiceChild {
+            service_binding: self,
+            handle,
+            protocol,
+        };
+
+        Ok(if let Some(warning) = warning {
+            EfiStatusEnum::Warning(warning, child)
+        } else {
+            EfiStatusEnum::Success(child)
+        })
+    }
+}
+
+impl<T> EfiProtocol for EfiServiceBindingProtocol<T>
+where
+    T: EfiServiceBinding,
+{
+    type Parsed = &'static Self;
+    type Error = !;
+
+    fn guid() -> EfiGuid {
+        T::service_binding_guid()
+    }
+
+    unsafe fn parse(
+        ptr: NonNullVoidPtr,
+    ) -> Result<<Self as EfiProtocol>::Parsed, <Self as EfiProtocol>::Error> {
+        Ok(&*ptr.cast().as_ptr())
+    }
+}
+
+/// Child created by [`EfiServiceBindingProtocol::open_child`].
+///
+/// Dereferences to the protocol instance installed on the child handle.
+pub struct EfiServiceChild<'a, T>
+where
+    T: EfiServiceBinding,
+{
+    service_binding: &'a EfiServiceBindingProtocol<T>,
+    handle: EfiHandle,
+    protocol: &'static T,
+}
+
+impl<T> EfiServiceChild<'_, T>
+where
+    T: EfiServiceBinding,
+{
+    pub fn handle(&self) -> EfiHandle {
+        self.handle
+    }
+}
+
+impl<T> Deref for EfiServiceChild<'_, T>
+where
+    T: EfiServiceBinding,
+{
+    type Target = T;
+
+    fn deref(&self) -> &<Self as Deref>::Target {
+        self.protocol
+    }
+}
+
+impl<T> Drop for EfiServiceChild<'_, T>
+where
+    T: EfiServiceBinding,
+{
+    fn drop(&mut self) {
+        let _ = self.service_binding.destroy_child(self.handle);
+    }
+}
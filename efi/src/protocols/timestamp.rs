@@ -0,0 +1,82 @@
+use crate::{
+    guid::EfiGuid,
+    protocols::EfiProtocol,
+    status::{EfiStatus, EfiStatusEnum},
+    types::NonNullVoidPtr,
+};
+
+/// Implementation of EFI's `EFI_TIMESTAMP_PROTOCOL`.
+///
+/// The counter it exposes is monotonic and keeps running through the boot, unlike the virtual time of timer events.
+#[repr(C)]
+pub struct EfiTimestampProtocol {
+    get_timestamp: extern "efiapi" fn() -> u64,
+    get_properties: extern "efiapi" fn(*mut EfiTimestampProperties) -> EfiStatus,
+}
+
+impl EfiTimestampProtocol {
+    /// Returns the current value of the counter.
+    pub fn get_timestamp(&self) -> u64 {
+        (self.get_timestamp)()
+    }
+
+    pub fn get_properties(&self) -> EfiStatusEnum<EfiTimestampProperties> {
+        let mut properties: EfiTimestampProperties = EfiTimestampProperties::default();
+
+        (self.get_properties)(&mut properties).into_enum_data(|| properties)
+    }
+}
+
+impl EfiProtocol for EfiTimestampProtocol {
+    type Parsed = &'static Self;
+    type Error = !;
+
+    fn guid() -> EfiGuid {
+        crate::guids::EFI_TIMESTAMP_PROTOCOL
+    }
+
+    unsafe fn parse(
+        ptr: NonNullVoidPtr,
+    ) -> Result<<Self as EfiProtocol>::Parsed, <Self as EfiProtocol>::Error> {
+        Ok(&*ptr.cast().as_ptr())
+    }
+}
+
+/// Properties of the counter, returned by [`EfiTimestampProtocol::get_properties`].
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EfiTimestampProperties {
+    frequency: u64,
+    end_value: u64,
+}
+
+impl EfiTimestampProperties {
+    /// Returns the frequency of the counter in Hz.
+    pub fn frequency(&self) -> u64 {
+        self.frequency
+    }
+
+    /// Returns the value the counter reaches before it wraps around to zero.
+    pub fn end_value(&self) -> u64 {
+        self.end_value
+    }
+
+    /// Returns the number of ticks between two timestamps, accounting for the counter wrapping around once.
+    pub fn elapsed(&self, start: u64, end: u64) -> u64 {
+        if end >= start {
+            end - start
+        } else {
+            self.end_value
+                .saturating_sub(start)
+                .wrapping_add(end)
+                .wrapping_add(1)
+        }
+    }
+
+    /// Converts a number of ticks into nanoseconds, or `None` if the frequency is zero.
+    pub fn to_nanoseconds(&self, ticks: u64) -> Option<u64> {
+        (u128::from(ticks) * 1_000_000_000)
+            .checked_div(u128::from(self.frequency))
+            .map(|nanoseconds: u128| nanoseconds.min(u128::from(u64::MAX)) as u64)
+    }
+}
@@ -0,0 +1,421 @@
+/// Standard USB device descriptor.
+#[repr(C, packed)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EfiUsbDeviceDescriptor {
+    length: u8,
+    descriptor_type: u8,
+    bcd_usb: u16,
+    device_class: u8,
+    device_sub_class: u8,
+    device_protocol: u8,
+    max_packet_size0: u8,
+    id_vendor: u16,
+    id_product: u16,
+    bcd_device: u16,
+    str_manufacturer: u8,
+    str_product: u8,
+    str_serial_number: u8,
+    num_configurations: u8,
+}
+
+impl EfiUsbDeviceDescriptor {
+    /// Returns the USB specification release the device complies with, as a binary-coded decimal.
+    pub fn usb_version(&self) -> u16 {
+        self.bcd_usb
+    }
+
+    /// Returns the device class, zero meaning each interface specifies it's own.
+    pub fn device_class(&self) -> u8 {
+        self.device_class
+    }
+
+    pub fn device_sub_class(&self) -> u8 {
+        self.device_sub_class
+    }
+
+    pub fn device_protocol(&self) -> u8 {
+        self.device_protocol
+    }
+
+    /// Returns the maximum packet size of the default control endpoint.
+    pub fn max_packet_size0(&self) -> u8 {
+        self.max_packet_size0
+    }
+
+    pub fn vendor_id(&self) -> u16 {
+        self.id_vendor
+    }
+
+    pub fn product_id(&self) -> u16 {
+        self.id_product
+    }
+
+    /// Returns the device release number, as a binary-coded decimal.
+    pub fn device_version(&self) -> u16 {
+        self.bcd_device
+    }
+
+    /// Returns the index of the manufacturer's string descriptor, zero meaning there's none.
+    pub fn manufacturer_index(&self) -> u8 {
+        self.str_manufacturer
+    }
+
+    /// Returns the index of the product's string descriptor, zero meaning there's none.
+    pub fn product_index(&self) -> u8 {
+        self.str_product
+    }
+
+    /// Returns the index of the serial number's string descriptor, zero meaning there's none.
+    pub fn serial_number_index(&self) -> u8 {
+        self.str_serial_number
+    }
+
+    pub fn num_configurations(&self) -> u8 {
+        self.num_configurations
+    }
+}
+
+/// Standard USB configuration descriptor, without the interface and endpoint descriptors following it.
+#[repr(C, packed)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EfiUsbConfigDescriptor {
+    length: u8,
+    descriptor_type: u8,
+    total_length: u16,
+    num_interfaces: u8,
+    configuration_value: u8,
+    configuration: u8,
+    attributes: u8,
+    max_power: u8,
+}
+
+impl EfiUsbConfigDescriptor {
+    pub const SELF_POWERED: u8 = 0x40;
+    pub const REMOTE_WAKEUP: u8 = 0x20;
+
+    /// Returns the length of the configuration, including the interface and endpoint descriptors.
+    pub fn total_length(&self) -> u16 {
+        self.total_length
+    }
+
+    pub fn num_interfaces(&self) -> u8 {
+        self.num_interfaces
+    }
+
+    /// Returns the value selecting the configuration through `SET_CONFIGURATION`.
+    pub fn configuration_value(&self) -> u8 {
+        self.configuration_value
+    }
+
+    /// Returns the index of the configuration's string descriptor, zero meaning there's none.
+    pub fn configuration_index(&self) -> u8 {
+        self.configuration
+    }
+
+    pub fn attributes(&self) -> u8 {
+        self.attributes
+    }
+
+    /// Returns the maximum power consumption, in mA.
+    pub fn max_power(&self) -> u16 {
+        /* Reported in units of 2 mA */
+        u16::from(self.max_power) * 2
+    }
+}
+
+/// Standard USB interface descriptor.
+#[repr(C, packed)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EfiUsbInterfaceDescriptor {
+    length: u8,
+    descriptor_type: u8,
+    interface_number: u8,
+    alternate_setting: u8,
+    num_endpoints: u8,
+    interface_class: u8,
+    interface_sub_class: u8,
+    interface_protocol: u8,
+    interface: u8,
+}
+
+impl EfiUsbInterfaceDescriptor {
+    /* Interface classes */
+    pub const CLASS_AUDIO: u8 = 0x01;
+    pub const CLASS_COMMUNICATIONS: u8 = 0x02;
+    pub const CLASS_HID: u8 = 0x03;
+    pub const CLASS_MASS_STORAGE: u8 = 0x08;
+    pub const CLASS_HUB: u8 = 0x09;
+    pub const CLASS_SMART_CARD: u8 = 0x0B;
+    pub const CLASS_VENDOR_SPECIFIC: u8 = 0xFF;
+
+    pub fn interface_number(&self) -> u8 {
+        self.interface_number
+    }
+
+    pub fn alternate_setting(&self) -> u8 {
+        self.alternate_setting
+    }
+
+    /// Returns the number of endpoints, excluding the default control endpoint.
+    pub fn num_endpoints(&self) -> u8 {
+        self.num_endpoints
+    }
+
+    pub fn interface_class(&self) -> u8 {
+        self.interface_class
+    }
+
+    pub fn interface_sub_class(&self) -> u8 {
+        self.interface_sub_class
+    }
+
+    pub fn interface_protocol(&self) -> u8 {
+        self.interface_protocol
+    }
+
+    /// Returns the index of the interface's string descriptor, zero meaning there's none.
+    pub fn interface_index(&self) -> u8 {
+        self.interface
+    }
+}
+
+/// Type of transfers an endpoint performs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EfiUsbTransferType {
+    Control,
+    Isochronous,
+    Bulk,
+    Interrupt,
+}
+
+/// Standard USB endpoint descriptor.
+#[repr(C, packed)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EfiUsbEndpointDescriptor {
+    length: u8,
+    descriptor_type: u8,
+    endpoint_address: u8,
+    attributes: u8,
+    max_packet_size: u16,
+    interval: u8,
+}
+
+impl EfiUsbEndpointDescriptor {
+    /// Direction bit of endpoint addresses, set for IN endpoints.
+    pub const DIRECTION_IN: u8 = 0x80;
+
+    /// Returns the endpoint's address, including the direction bit, as passed to the transfer functions.
+    pub fn endpoint_address(&self) -> u8 {
+        self.endpoint_address
+    }
+
+    /// Returns whether the endpoint transfers data to the host.
+    pub fn is_in(&self) -> bool {
+        self.endpoint_address & Self::DIRECTION_IN != 0
+    }
+
+    pub fn attributes(&self) -> u8 {
+        self.attributes
+    }
+
+    pub fn transfer_type(&self) -> EfiUsbTransferType {
+        match self.attributes & 0x3 {
+            0 => EfiUsbTransferType::Control,
+            1 => EfiUsbTransferType::Isochronous,
+            2 => EfiUsbTransferType::Bulk,
+            _ => EfiUsbTransferType::Interrupt,
+        }
+    }
+
+    pub fn max_packet_size(&self) -> u16 {
+        /* High speed endpoints store additional transactions per microframe in the upper bits */
+        self.max_packet_size & 0x07FF
+    }
+
+    /// Returns the polling interval, in frames or microframes depending on the device's speed.
+    pub fn interval(&self) -> u8 {
+        self.interval
+    }
+}
+
+/// Setup packet of a control transfer.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EfiUsbDeviceRequest {
+    request_type: u8,
+    request: u8,
+    value: u16,
+    index: u16,
+    length: u16,
+}
+
+impl EfiUsbDeviceRequest {
+    /* Request type bits */
+    pub const DEVICE_TO_HOST: u8 = 0x80;
+    pub const TYPE_STANDARD: u8 = 0x00;
+    pub const TYPE_CLASS: u8 = 0x20;
+    pub const TYPE_VENDOR: u8 = 0x40;
+    pub const RECIPIENT_DEVICE: u8 = 0x00;
+    pub const RECIPIENT_INTERFACE: u8 = 0x01;
+    pub const RECIPIENT_ENDPOINT: u8 = 0x02;
+
+    /* Standard requests */
+    pub const GET_STATUS: u8 = 0x00;
+    pub const CLEAR_FEATURE: u8 = 0x01;
+    pub const SET_FEATURE: u8 = 0x03;
+    pub const GET_DESCRIPTOR: u8 = 0x06;
+    pub const GET_CONFIGURATION: u8 = 0x08;
+    pub const SET_CONFIGURATION: u8 = 0x09;
+
+    /* Descriptor types */
+    pub const DESCRIPTOR_DEVICE: u8 = 0x01;
+    pub const DESCRIPTOR_CONFIGURATION: u8 = 0x02;
+    pub const DESCRIPTOR_STRING: u8 = 0x03;
+    pub const DESCRIPTOR_INTERFACE: u8 = 0x04;
+    pub const DESCRIPTOR_ENDPOINT: u8 = 0x05;
+
+    pub const fn new(request_type: u8, request: u8, value: u16, index: u16, length: u16) -> Self {
+        Self {
+            request_type,
+            request,
+            value,
+            index,
+            length,
+        }
+    }
+
+    /// Builds a `GET_DESCRIPTOR` request. The language ID is only used by string descriptors.
+    pub const fn get_descriptor(
+        descriptor_type: u8,
+        descriptor_index: u8,
+        language_id: u16,
+        length: u16,
+    ) -> Self {
+        Self::new(
+            Self::DEVICE_TO_HOST | Self::TYPE_STANDARD | Self::RECIPIENT_DEVICE,
+            Self::GET_DESCRIPTOR,
+            (descriptor_type as u16) << 8 | descriptor_index as u16,
+            language_id,
+            length,
+        )
+    }
+
+    /// Builds a `GET_CONFIGURATION` request, returning the current configuration value in a single byte.
+    pub const fn get_configuration() -> Self {
+        Self::new(
+            Self::DEVICE_TO_HOST | Self::TYPE_STANDARD | Self::RECIPIENT_DEVICE,
+            Self::GET_CONFIGURATION,
+            0,
+            0,
+            1,
+        )
+    }
+
+    pub const fn set_configuration(configuration_value: u8) -> Self {
+        Self::new(
+            Self::TYPE_STANDARD | Self::RECIPIENT_DEVICE,
+            Self::SET_CONFIGURATION,
+            configuration_value as u16,
+            0,
+            0,
+        )
+    }
+
+    pub const fn request_type(&self) -> u8 {
+        self.request_type
+    }
+
+    pub const fn request(&self) -> u8 {
+        self.request
+    }
+
+    pub const fn value(&self) -> u16 {
+        self.value
+    }
+
+    pub const fn index(&self) -> u16 {
+        self.index
+    }
+
+    pub const fn length(&self) -> u16 {
+        self.length
+    }
+}
+
+/// Direction of a control transfer's data stage.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(super) enum EfiUsbDataDirection {
+    DataIn,
+    DataOut,
+    NoData,
+}
+
+/// Data stage of a control transfer.
+pub enum EfiUsbData<'a> {
+    None,
+    /// Data read from the device.
+    In(&'a mut [u8]),
+    /// Data written to the device.
+    Out(&'a [u8]),
+}
+
+/// Errors detected by the host controller during a transfer.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EfiUsbTransferStatus {
+    status: u32,
+}
+
+impl EfiUsbTransferStatus {
+    pub const NOT_EXECUTE: u32 = 0x0001;
+    pub const STALL: u32 = 0x0002;
+    pub const BUFFER: u32 = 0x0004;
+    pub const BABBLE: u32 = 0x0008;
+    pub const NAK: u32 = 0x0010;
+    pub const CRC: u32 = 0x0020;
+    pub const TIMEOUT: u32 = 0x0040;
+    pub const BIT_STUFF: u32 = 0x0080;
+    pub const SYSTEM: u32 = 0x0100;
+
+    pub const fn new(status: u32) -> Self {
+        Self { status }
+    }
+
+    pub const fn bits(&self) -> u32 {
+        self.status
+    }
+
+    pub const fn contains(&self, status: u32) -> bool {
+        self.status & status == status
+    }
+
+    /// Returns whether the transfer completed without errors.
+    pub const fn is_success(&self) -> bool {
+        self.status == 0
+    }
+}
+
+/// Speed of USB devices and host controllers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum EfiUsbSpeed {
+    /// 1.5 Mb/s.
+    Low,
+    /// 12 Mb/s.
+    Full,
+    /// 480 Mb/s.
+    High,
+    /// 5 Gb/s.
+    Super,
+}
+
+impl EfiUsbSpeed {
+    pub(super) fn from_raw(speed: u8) -> Option<Self> {
+        match speed {
+            0 => Some(Self::Full),
+            1 => Some(Self::Low),
+            2 => Some(Self::High),
+            3 => Some(Self::Super),
+            _ => None,
+        }
+    }
+}
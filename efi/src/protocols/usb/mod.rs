@@ -0,0 +1,5 @@
+mod common;
+mod usb2_hc;
+mod usb_io;
+
+pub use {common::*, usb2_hc::*, usb_io::*};
@@ -0,0 +1,365 @@
+use {
+    super::{
+        common::{EfiUsbDataDirection, EfiUsbDeviceRequest, EfiUsbSpeed},
+        usb_io::EfiAsyncUsbTransferCallback,
+    },
+    crate::{
+        guid::EfiGuid,
+        protocols::EfiProtocol,
+        status::{EfiStatus, EfiStatusEnum},
+        types::{NonNullVoidPtr, VoidMutPtr},
+    },
+    core::iter::FusedIterator,
+};
+
+/// Transaction translator of the high speed hub a low or full speed device is attached to.
+#[repr(C)]
+struct EfiUsb2HcTransactionTranslator {
+    translator_hub_address: u8,
+    translator_port_number: u8,
+}
+
+/// Implementation of EFI's `EFI_USB2_HC_PROTOCOL`.
+///
+/// Transfers are performed by the USB bus driver on the devices' behalf; Use [`EfiUsbIoProtocol`](super::EfiUsbIoProtocol) for them instead.
+/// Root hub ports are numbered starting from zero.
+#[repr(C)]
+pub struct EfiUsb2HcProtocol {
+    get_capability: extern "efiapi" fn(*const Self, *mut u8, *mut u8, *mut u8) -> EfiStatus,
+    reset: extern "efiapi" fn(*const Self, u16) -> EfiStatus,
+    get_state: extern "efiapi" fn(*const Self, *mut EfiUsbHcState) -> EfiStatus,
+    set_state: extern "efiapi" fn(*const Self, EfiUsbHcState) -> EfiStatus,
+    control_transfer: extern "efiapi" fn(
+        *const Self,
+        u8,
+        u8,
+        usize,
+        *const EfiUsbDeviceRequest,
+        EfiUsbDataDirection,
+        VoidMutPtr,
+        *mut usize,
+        usize,
+        *const EfiUsb2HcTransactionTranslator,
+        *mut u32,
+    ) -> EfiStatus,
+    bulk_transfer: extern "efiapi" fn(
+        *const Self,
+        u8,
+        u8,
+        u8,
+        usize,
+        u8,
+        *mut VoidMutPtr,
+        *mut usize,
+        *mut u8,
+        usize,
+        *const EfiUsb2HcTransactionTranslator,
+        *mut u32,
+    ) -> EfiStatus,
+    async_interrupt_transfer: extern "efiapi" fn(
+        *const Self,
+        u8,
+        u8,
+        u8,
+        usize,
+        bool,
+        *mut u8,
+        usize,
+        usize,
+        *const EfiUsb2HcTransactionTranslator,
+        Option<EfiAsyncUsbTransferCallback>,
+        VoidMutPtr,
+    ) -> EfiStatus,
+    sync_interrupt_transfer: extern "efiapi" fn(
+        *const Self,
+        u8,
+        u8,
+        u8,
+        usize,
+        VoidMutPtr,
+        *mut usize,
+        *mut u8,
+        usize,
+        *const EfiUsb2HcTransactionTranslator,
+        *mut u32,
+    ) -> EfiStatus,
+    isochronous_transfer: extern "efiapi" fn(
+        *const Self,
+        u8,
+        u8,
+        u8,
+        usize,
+        u8,
+        *mut VoidMutPtr,
+        usize,
+        *const EfiUsb2HcTransactionTranslator,
+        *mut u32,
+    ) -> EfiStatus,
+    async_isochronous_transfer: extern "efiapi" fn(
+        *const Self,
+        u8,
+        u8,
+        u8,
+        usize,
+        u8,
+        *mut VoidMutPtr,
+        usize,
+        *const EfiUsb2HcTransactionTranslator,
+        Option<EfiAsyncUsbTransferCallback>,
+        VoidMutPtr,
+    ) -> EfiStatus,
+    get_root_hub_port_status:
+        extern "efiapi" fn(*const Self, u8, *mut EfiUsbPortStatus) -> EfiStatus,
+    set_root_hub_port_feature: extern "efiapi" fn(*const Self, u8, EfiUsbPortFeature) -> EfiStatus,
+    clear_root_hub_port_feature:
+        extern "efiapi" fn(*const Self, u8, EfiUsbPortFeature) -> EfiStatus,
+    major_revision: u16,
+    minor_revision: u16,
+}
+
+impl EfiUsb2HcProtocol {
+    /* Reset attributes */
+    /// Resets every host controller and the devices attached to them.
+    pub const RESET_GLOBAL: u16 = 0x0001;
+    /// Resets the host controller, without the devices attached to it.
+    pub const RESET_HOST_CONTROLLER: u16 = 0x0002;
+    pub const RESET_GLOBAL_WITH_DEBUG: u16 = 0x0004;
+    pub const RESET_HOST_WITH_DEBUG: u16 = 0x0008;
+
+    pub fn get_capability(&self) -> EfiStatusEnum<EfiUsb2HcCapability> {
+        let mut max_speed: u8 = 0;
+        let mut port_count: u8 = 0;
+        let mut is_64_bit_capable: u8 = 0;
+
+        (self.get_capability)(
+            self,
+            &mut max_speed,
+            &mut port_count,
+            &mut is_64_bit_capable,
+        )
+        .into_enum_data(|| EfiUsb2HcCapability {
+            max_speed,
+            port_count,
+            is_64_bit_capable: is_64_bit_capable != 0,
+        })
+    }
+
+    pub fn reset(&self, attributes: u16) -> EfiStatusEnum {
+        (self.reset)(self, attributes).into_enum()
+    }
+
+    pub fn get_state(&self) -> EfiStatusEnum<EfiUsbHcState> {
+        let mut state: EfiUsbHcState = EfiUsbHcState::Halt;
+
+        (self.get_state)(self, &mut state).into_enum_data(|| state)
+    }
+
+    pub fn set_state(&self, state: EfiUsbHcState) -> EfiStatusEnum {
+        (self.set_state)(self, state).into_enum()
+    }
+
+    pub fn get_root_hub_port_status(&self, port_number: u8) -> EfiStatusEnum<EfiUsbPortStatus> {
+        let mut status: EfiUsbPortStatus = EfiUsbPortStatus::default();
+
+        (self.get_root_hub_port_status)(self, port_number, &mut status).into_enum_data(|| status)
+    }
+
+    /// Returns iterator over the root hub's port numbers and statuses.
+    ///
+    /// Iteration stops early at the first port whose status can't be read.
+    pub fn root_hub_ports(&self) -> EfiUsbRootHubPorts<'_> {
+        let port_count: u8 = match self.get_capability() {
+            EfiStatusEnum::Success(capability) | EfiStatusEnum::Warning(_, capability) => {
+                capability.port_count()
+            }
+            EfiStatusEnum::Error(..) => 0,
+        };
+
+        EfiUsbRootHubPorts {
+            protocol: self,
+            port_number: 0,
+            port_count,
+        }
+    }
+
+    pub fn set_root_hub_port_feature(
+        &self,
+        port_number: u8,
+        feature: EfiUsbPortFeature,
+    ) -> EfiStatusEnum {
+        (self.set_root_hub_port_feature)(self, port_number, feature).into_enum()
+    }
+
+    /// Clears the feature, which for the change features acknowledges the change.
+    pub fn clear_root_hub_port_feature(
+        &self,
+        port_number: u8,
+        feature: EfiUsbPortFeature,
+    ) -> EfiStatusEnum {
+        (self.clear_root_hub_port_feature)(self, port_number, feature).into_enum()
+    }
+
+    /// Returns the major and minor revision of the USB specification the host controller implements.
+    pub fn revision(&self) -> (u16, u16) {
+        (self.major_revision, self.minor_revision)
+    }
+}
+
+impl EfiProtocol for EfiUsb2HcProtocol {
+    type Parsed = &'static Self;
+    type Error = !;
+
+    fn guid() -> EfiGuid {
+        crate::guids::EFI_USB2_HC_PROTOCOL
+    }
+
+    unsafe fn parse(
+        ptr: NonNullVoidPtr,
+    ) -> Result<<Self as EfiProtocol>::Parsed, <Self as EfiProtocol>::Error> {
+        Ok(&*ptr.cast().as_ptr())
+    }
+}
+
+/// Iterator over the ports of a host controller's root hub.
+pub struct EfiUsbRootHubPorts<'a> {
+    protocol: &'a EfiUsb2HcProtocol,
+    port_number: u8,
+    port_count: u8,
+}
+
+impl Iterator for EfiUsbRootHubPorts<'_> {
+    type Item = (u8, EfiUsbPortStatus);
+
+    fn next(&mut self) -> Option<<Self as Iterator>::Item> {
+        if self.port_number >= self.port_count {
+            return None;
+        }
+
+        let port_number: u8 = self.port_number;
+
+        match self.protocol.get_root_hub_port_status(port_number) {
+            EfiStatusEnum::Success(status) | EfiStatusEnum::Warning(_, status) => {
+                self.port_number += 1;
+
+                Some((port_number, status))
+            }
+            EfiStatusEnum::Error(..) => {
+                self.port_number = self.port_count;
+
+                None
+            }
+        }
+    }
+}
+
+impl FusedIterator for EfiUsbRootHubPorts<'_> {}
+
+/// Capabilities returned by [`EfiUsb2HcProtocol::get_capability`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EfiUsb2HcCapability {
+    max_speed: u8,
+    port_count: u8,
+    is_64_bit_capable: bool,
+}
+
+impl EfiUsb2HcCapability {
+    /// Returns the highest speed the host controller supports, or `None` for speeds unknown to the specification.
+    pub fn max_speed(&self) -> Option<EfiUsbSpeed> {
+        EfiUsbSpeed::from_raw(self.max_speed)
+    }
+
+    /// Returns the number of root hub ports.
+    pub fn port_count(&self) -> u8 {
+        self.port_count
+    }
+
+    /// Returns whether the host controller can access memory above 4 GiB.
+    pub fn is_64_bit_capable(&self) -> bool {
+        self.is_64_bit_capable
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EfiUsbHcState {
+    Halt,
+    Operational,
+    Suspend,
+}
+
+/// Status of a root hub port and it's pending changes.
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EfiUsbPortStatus {
+    port_status: u16,
+    port_change_status: u16,
+}
+
+impl EfiUsbPortStatus {
+    /* Port status bits */
+    pub const CONNECTION: u16 = 0x0001;
+    pub const ENABLE: u16 = 0x0002;
+    pub const SUSPEND: u16 = 0x0004;
+    pub const OVER_CURRENT: u16 = 0x0008;
+    pub const RESET: u16 = 0x0010;
+    pub const POWER: u16 = 0x0100;
+    pub const LOW_SPEED: u16 = 0x0200;
+    pub const HIGH_SPEED: u16 = 0x0400;
+    pub const SUPER_SPEED: u16 = 0x0800;
+    /// The port is owned by a companion host controller.
+    pub const OWNER: u16 = 0x2000;
+
+    /* Port change status bits */
+    pub const CONNECTION_CHANGE: u16 = 0x0001;
+    pub const ENABLE_CHANGE: u16 = 0x0002;
+    pub const SUSPEND_CHANGE: u16 = 0x0004;
+    pub const OVER_CURRENT_CHANGE: u16 = 0x0008;
+    pub const RESET_CHANGE: u16 = 0x0010;
+
+    pub fn port_status(&self) -> u16 {
+        self.port_status
+    }
+
+    pub fn port_change_status(&self) -> u16 {
+        self.port_change_status
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.port_status & Self::CONNECTION != 0
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.port_status & Self::ENABLE != 0
+    }
+
+    /// Returns the speed of the attached device, or `None` if there's none.
+    pub fn speed(&self) -> Option<EfiUsbSpeed> {
+        if !self.is_connected() {
+            None
+        } else if self.port_status & Self::SUPER_SPEED != 0 {
+            Some(EfiUsbSpeed::Super)
+        } else if self.port_status & Self::HIGH_SPEED != 0 {
+            Some(EfiUsbSpeed::High)
+        } else if self.port_status & Self::LOW_SPEED != 0 {
+            Some(EfiUsbSpeed::Low)
+        } else {
+            Some(EfiUsbSpeed::Full)
+        }
+    }
+}
+
+/// Features of root hub ports, set and cleared through [`EfiUsb2HcProtocol`].
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EfiUsbPortFeature {
+    Enable = 1,
+    Suspend = 2,
+    Reset = 4,
+    Power = 8,
+    Owner = 13,
+    ConnectChange = 16,
+    EnableChange = 17,
+    SuspendChange = 18,
+    OverCurrentChange = 19,
+    ResetChange = 20,
+}
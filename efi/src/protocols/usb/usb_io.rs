@@ -0,0 +1,379 @@
+use {
+    super::common::{
+        EfiUsbConfigDescriptor, EfiUsbData, EfiUsbDataDirection, EfiUsbDeviceDescriptor,
+        EfiUsbDeviceRequest, EfiUsbEndpointDescriptor, EfiUsbInterfaceDescriptor,
+        EfiUsbTransferStatus,
+    },
+    crate::{
+        boot_services::EfiBootServices1x0,
+        guid::EfiGuid,
+        protocols::EfiProtocol,
+        status::{EfiStatus, EfiStatusEnum},
+        types::{NonNullVoidPtr, VoidMutPtr, VoidPtr},
+        utilities::string_from_raw,
+    },
+    alloc::vec::Vec,
+    core::{iter::FusedIterator, ptr::NonNull, slice::from_raw_parts},
+};
+
+/// Called by the USB bus driver when an asynchronous transfer completes, with the data, it's length, the context and the transfer status.
+pub type EfiAsyncUsbTransferCallback =
+    extern "efiapi" fn(VoidMutPtr, usize, VoidMutPtr, u32) -> EfiStatus;
+
+/// Language ID of US English, which most devices provide their strings in.
+pub const EFI_USB_LANGUAGE_ENGLISH_US: u16 = 0x0409;
+
+/// Implementation of EFI's `EFI_USB_IO_PROTOCOL`.
+///
+/// Installed by the USB bus driver on the handle of each device's interface.
+/// Timeouts are in milliseconds, where zero waits indefinitely.
+#[repr(C)]
+pub struct EfiUsbIoProtocol {
+    control_transfer: extern "efiapi" fn(
+        *const Self,
+        *const EfiUsbDeviceRequest,
+        EfiUsbDataDirection,
+        u32,
+        VoidMutPtr,
+        usize,
+        *mut u32,
+    ) -> EfiStatus,
+    bulk_transfer:
+        extern "efiapi" fn(*const Self, u8, VoidMutPtr, *mut usize, usize, *mut u32) -> EfiStatus,
+    async_interrupt_transfer: extern "efiapi" fn(
+        *const Self,
+        u8,
+        bool,
+        usize,
+        usize,
+        Option<EfiAsyncUsbTransferCallback>,
+        VoidMutPtr,
+    ) -> EfiStatus,
+    sync_interrupt_transfer:
+        extern "efiapi" fn(*const Self, u8, VoidMutPtr, *mut usize, usize, *mut u32) -> EfiStatus,
+    isochronous_transfer:
+        extern "efiapi" fn(*const Self, u8, VoidMutPtr, usize, *mut u32) -> EfiStatus,
+    async_isochronous_transfer: extern "efiapi" fn(
+        *const Self,
+        u8,
+        VoidMutPtr,
+        usize,
+        Option<EfiAsyncUsbTransferCallback>,
+        VoidMutPtr,
+    ) -> EfiStatus,
+    get_device_descriptor:
+        extern "efiapi" fn(*const Self, *mut EfiUsbDeviceDescriptor) -> EfiStatus,
+    get_config_descriptor:
+        extern "efiapi" fn(*const Self, *mut EfiUsbConfigDescriptor) -> EfiStatus,
+    get_interface_descriptor:
+        extern "efiapi" fn(*const Self, *mut EfiUsbInterfaceDescriptor) -> EfiStatus,
+    get_endpoint_descriptor:
+        extern "efiapi" fn(*const Self, u8, *mut EfiUsbEndpointDescriptor) -> EfiStatus,
+    get_string_descriptor: extern "efiapi" fn(*const Self, u16, u8, *mut *mut u16) -> EfiStatus,
+    get_supported_languages: extern "efiapi" fn(*const Self, *mut *mut u16, *mut u16) -> EfiStatus,
+    port_reset: extern "efiapi" fn(*const Self) -> EfiStatus,
+}
+
+impl EfiUsbIoProtocol {
+    /// Performs a transfer on the default control endpoint.
+    ///
+    /// The transfer status is returned alongside errors as well, describing the failure.
+    pub fn control_transfer(
+        &self,
+        request: &EfiUsbDeviceRequest,
+        data: EfiUsbData<'_>,
+        timeout: u32,
+    ) -> EfiStatusEnum<EfiUsbTransferStatus, EfiUsbTransferStatus> {
+        let (direction, buffer, length): (EfiUsbDataDirection, VoidMutPtr, usize) = match data {
+            EfiUsbData::None => (EfiUsbDataDirection::NoData, 0 as _, 0),
+            EfiUsbData::In(buffer) => (
+                EfiUsbDataDirection::DataIn,
+                buffer.as_mut_ptr() as _,
+                buffer.len(),
+            ),
+            EfiUsbData::Out(buffer) => (
+                EfiUsbDataDirection::DataOut,
+                buffer.as_ptr() as _,
+                buffer.len(),
+            ),
+        };
+        let mut status: u32 = 0;
+
+        (self.control_transfer)(
+            self,
+            request,
+            direction,
+            timeout,
+            buffer,
+            length,
+            &mut status,
+        )
+        .into_enum_data_error(
+            || EfiUsbTransferStatus::new(status),
+            || EfiUsbTransferStatus::new(status),
+        )
+    }
+
+    /// Reads a descriptor through a `GET_DESCRIPTOR` request, filling as much of the buffer as the descriptor's length allows.
+    ///
+    /// Unlike [`get_config_descriptor`](Self::get_config_descriptor), this returns the interface and endpoint descriptors following the configuration descriptor.
+    pub fn get_descriptor(
+        &self,
+        descriptor_type: u8,
+        descriptor_index: u8,
+        language_id: u16,
+        buffer: &mut [u8],
+        timeout: u32,
+    ) -> EfiStatusEnum<EfiUsbTransferStatus, EfiUsbTransferStatus> {
+        let length: usize = buffer.len().min(usize::from(u16::MAX));
+        let request: EfiUsbDeviceRequest = EfiUsbDeviceRequest::get_descriptor(
+            descriptor_type,
+            descriptor_index,
+            language_id,
+            length as u16,
+        );
+
+        self.control_transfer(&request, EfiUsbData::In(&mut buffer[..length]), timeout)
+    }
+
+    /// Performs a bulk transfer on the endpoint and returns the number of bytes transferred.
+    ///
+    /// The buffer is only read for OUT endpoints.
+    pub fn bulk_transfer(
+        &self,
+        endpoint_address: u8,
+        data: &mut [u8],
+        timeout: usize,
+    ) -> EfiStatusEnum<(usize, EfiUsbTransferStatus), (usize, EfiUsbTransferStatus)> {
+        let mut length: usize = data.len();
+        let mut status: u32 = 0;
+
+        (self.bulk_transfer)(
+            self,
+            endpoint_address,
+            data.as_mut_ptr() as VoidMutPtr,
+            &mut length,
+            timeout,
+            &mut status,
+        )
+        .into_enum_data_error(
+            || (length, EfiUsbTransferStatus::new(status)),
+            || (length, EfiUsbTransferStatus::new(status)),
+        )
+    }
+
+    /// Performs a single interrupt transfer on the endpoint and returns the number of bytes transferred.
+    ///
+    /// The buffer is only read for OUT endpoints.
+    pub fn sync_interrupt_transfer(
+        &self,
+        endpoint_address: u8,
+        data: &mut [u8],
+        timeout: usize,
+    ) -> EfiStatusEnum<(usize, EfiUsbTransferStatus), (usize, EfiUsbTransferStatus)> {
+        let mut length: usize = data.len();
+        let mut status: u32 = 0;
+
+        (self.sync_interrupt_transfer)(
+            self,
+            endpoint_address,
+            data.as_mut_ptr() as VoidMutPtr,
+            &mut length,
+            timeout,
+            &mut status,
+        )
+        .into_enum_data_error(
+            || (length, EfiUsbTransferStatus::new(status)),
+            || (length, EfiUsbTransferStatus::new(status)),
+        )
+    }
+
+    /// Starts polling the interrupt IN endpoint every `polling_interval` milliseconds, calling the callback with each transfer's data.
+    /// # Safety
+    /// The context must stay valid until the transfer is stopped through [`stop_async_interrupt_transfer`](Self::stop_async_interrupt_transfer).
+    pub unsafe fn start_async_interrupt_transfer(
+        &self,
+        endpoint_address: u8,
+        polling_interval: usize,
+        data_length: usize,
+        callback: EfiAsyncUsbTransferCallback,
+        context: VoidMutPtr,
+    ) -> EfiStatusEnum {
+        (self.async_interrupt_transfer)(
+            self,
+            endpoint_address,
+            true,
+            polling_interval,
+            data_length,
+            Some(callback),
+            context,
+        )
+        .into_enum()
+    }
+
+    /// Stops polling the endpoint started through [`start_async_interrupt_transfer`](Self::start_async_interrupt_transfer).
+    pub fn stop_async_interrupt_transfer(&self, endpoint_address: u8) -> EfiStatusEnum {
+        (self.async_interrupt_transfer)(self, endpoint_address, false, 0, 0, None, 0 as _)
+            .into_enum()
+    }
+
+    /// Performs an isochronous transfer on the endpoint.
+    ///
+    /// The buffer is only read for OUT endpoints.
+    pub fn isochronous_transfer(
+        &self,
+        endpoint_address: u8,
+        data: &mut [u8],
+    ) -> EfiStatusEnum<EfiUsbTransferStatus, EfiUsbTransferStatus> {
+        let mut status: u32 = 0;
+
+        (self.isochronous_transfer)(
+            self,
+            endpoint_address,
+            data.as_mut_ptr() as VoidMutPtr,
+            data.len(),
+            &mut status,
+        )
+        .into_enum_data_error(
+            || EfiUsbTransferStatus::new(status),
+            || EfiUsbTransferStatus::new(status),
+        )
+    }
+
+    pub fn get_device_descriptor(&self) -> EfiStatusEnum<EfiUsbDeviceDescriptor> {
+        let mut descriptor: EfiUsbDeviceDescriptor = EfiUsbDeviceDescriptor::default();
+
+        (self.get_device_descriptor)(self, &mut descriptor).into_enum_data(|| descriptor)
+    }
+
+    /// Returns the descriptor of the device's active configuration.
+    pub fn get_config_descriptor(&self) -> EfiStatusEnum<EfiUsbConfigDescriptor> {
+        let mut descriptor: EfiUsbConfigDescriptor = EfiUsbConfigDescriptor::default();
+
+        (self.get_config_descriptor)(self, &mut descriptor).into_enum_data(|| descriptor)
+    }
+
+    /// Returns the descriptor of the interface the protocol is installed for.
+    pub fn get_interface_descriptor(&self) -> EfiStatusEnum<EfiUsbInterfaceDescriptor> {
+        let mut descriptor: EfiUsbInterfaceDescriptor = EfiUsbInterfaceDescriptor::default();
+
+        (self.get_interface_descriptor)(self, &mut descriptor).into_enum_data(|| descriptor)
+    }
+
+    /// Returns the descriptor of the interface's endpoint with the given index, starting from zero.
+    pub fn get_endpoint_descriptor(
+        &self,
+        endpoint_index: u8,
+    ) -> EfiStatusEnum<EfiUsbEndpointDescriptor> {
+        let mut descriptor: EfiUsbEndpointDescriptor = EfiUsbEndpointDescriptor::default();
+
+        (self.get_endpoint_descriptor)(self, endpoint_index, &mut descriptor)
+            .into_enum_data(|| descriptor)
+    }
+
+    /// Returns iterator over the interface's endpoint descriptors.
+    pub fn endpoint_descriptors(&self) -> EfiUsbEndpointDescriptors<'_> {
+        EfiUsbEndpointDescriptors {
+            protocol: self,
+            index: 0,
+        }
+    }
+
+    /// Returns a copy of the string descriptor in the given language, without the null terminator.
+    ///
+    /// [`EfiNotFound`](crate::status::EfiStatusError::EfiNotFound) is returned if the device has no such string.
+    pub fn get_string_descriptor(
+        &self,
+        boot_services: &EfiBootServices1x0,
+        language_id: u16,
+        string_index: u8,
+    ) -> EfiStatusEnum<Vec<u16>> {
+        let mut string: *mut u16 = 0 as _;
+
+        (self.get_string_descriptor)(self, language_id, string_index, &mut string)
+            .into_enum_data(|| unsafe { take_pool_string(boot_services, string) })
+    }
+
+    /// Returns the IDs of the languages the device provides it's strings in.
+    pub fn get_supported_languages(&self) -> EfiStatusEnum<&[u16]> {
+        let mut table: *mut u16 = 0 as _;
+        let mut table_size: u16 = 0;
+
+        (self.get_supported_languages)(self, &mut table, &mut table_size).into_enum_data(|| {
+            if table.is_null() {
+                &[][..]
+            } else {
+                /* The size is in bytes */
+                unsafe { from_raw_parts(table, usize::from(table_size) / 2) }
+            }
+        })
+    }
+
+    /// Resets the device's port and restores it's configuration.
+    pub fn port_reset(&self) -> EfiStatusEnum {
+        (self.port_reset)(self).into_enum()
+    }
+}
+
+impl EfiProtocol for EfiUsbIoProtocol {
+    type Parsed = &'static Self;
+    type Error = !;
+
+    fn guid() -> EfiGuid {
+        crate::guids::EFI_USB_IO_PROTOCOL
+    }
+
+    unsafe fn parse(
+        ptr: NonNullVoidPtr,
+    ) -> Result<<Self as EfiProtocol>::Parsed, <Self as EfiProtocol>::Error> {
+        Ok(&*ptr.cast().as_ptr())
+    }
+}
+
+/// Iterator over the endpoint descriptors of an interface.
+pub struct EfiUsbEndpointDescriptors<'a> {
+    protocol: &'a EfiUsbIoProtocol,
+    index: u8,
+}
+
+impl Iterator for EfiUsbEndpointDescriptors<'_> {
+    type Item = EfiUsbEndpointDescriptor;
+
+    fn next(&mut self) -> Option<<Self as Iterator>::Item> {
+        /* Endpoints are numbered from 0 to 15 */
+        if self.index >= 16 {
+            return None;
+        }
+
+        match self.protocol.get_endpoint_descriptor(self.index) {
+            EfiStatusEnum::Success(descriptor) | EfiStatusEnum::Warning(_, descriptor) => {
+                self.index += 1;
+
+                Some(descriptor)
+            }
+            EfiStatusEnum::Error(..) => {
+                self.index = 16;
+
+                None
+            }
+        }
+    }
+}
+
+impl FusedIterator for EfiUsbEndpointDescriptors<'_> {}
+
+/// Copies a string allocated by the firmware and releases it.
+unsafe fn take_pool_string(boot_services: &EfiBootServices1x0, string: *mut u16) -> Vec<u16> {
+    let string: NonNull<u16> = match NonNull::new(string) {
+        Some(string) => string,
+        None => return Vec::new(),
+    };
+
+    let copy: Vec<u16> = string_from_raw(string.as_ptr())
+        .map(<[u16]>::to_vec)
+        .unwrap_or_default();
+
+    let _ = boot_services.free_pool(string.as_ptr() as VoidPtr);
+
+    copy
+}
@@ -0,0 +1,253 @@
+use {
+    crate::{
+        guids,
+        protocols::media::{EfiFile, EfiFileAttributes, EfiFileOpenMode},
+        runtime_services::{
+            capsule::{
+                EfiCapsuleBlockDescriptors, EfiCapsuleBuffer, EfiCapsuleFlags, EfiCapsuleHeader,
+            },
+            miscellaneous::EfiResetType,
+            variable::EfiVariableAttributes,
+            EfiRuntimeServices, EfiRuntimeServicesRevision2x0,
+        },
+        status::{EfiStatus, EfiStatusEnum, EfiStatusError},
+        variables::{
+            os_indications, EFI_OS_INDICATIONS_SUPPORTED_VARIABLE_NAME,
+            EFI_OS_INDICATIONS_VARIABLE_NAME,
+        },
+    },
+    alloc::{format, vec::Vec},
+};
+
+/// Directory on the EFI system partition the firmware looks for capsules in.
+const CAPSULE_DIRECTORY: [&str; 2] = ["EFI", "UpdateCapsule"];
+
+/* Non-volatile, boot service and runtime access */
+const OS_INDICATIONS_ATTRIBUTES: u32 = 0x07;
+
+/// How [`deliver`] handed the capsules to the firmware.
+pub enum EfiCapsuleDelivery<'a> {
+    /// Passed through `UpdateCapsule`. Capsules persisting across reset are processed after a reset of the given type.
+    ///
+    /// The scatter-gather list describing them has to stay alive until then, which [`reset`](Self::reset) takes care of.
+    UpdateCapsule {
+        reset_type: EfiResetType,
+        scatter_gather_list: Option<EfiCapsuleBlockDescriptors<'a>>,
+    },
+    /// Written to `\EFI\UpdateCapsule` and announced through `OsIndications`. The capsules are processed on the next boot.
+    OnDisk,
+}
+
+impl<'a> EfiCapsuleDelivery<'a> {
+    pub fn reset_type(&self) -> EfiResetType {
+        match self {
+            Self::UpdateCapsule { reset_type, .. } => *reset_type,
+            Self::OnDisk => EfiResetType::Cold,
+        }
+    }
+
+    /// Resets the system, so the firmware processes the delivered capsules.
+    pub fn reset(self, runtime_services: &EfiRuntimeServices) -> ! {
+        runtime_services
+            .revision_1_0()
+            .reset(self.reset_type(), EfiStatus::success(), &[])
+    }
+}
+
+/// Delivers the capsules through `UpdateCapsule`, falling back to the on-disk mechanism when the firmware can't take them in memory.
+///
+/// Capsules with the [`PERSIST_ACROSS_RESET`](EfiCapsuleFlags::PERSIST_ACROSS_RESET) flag are described by a scatter-gather list over their buffers, which have to stay valid until the reset.
+/// The fallback writes the capsules under `esp_root`, the root directory of the EFI system partition; Without it, the error reported by the firmware is returned.
+pub fn deliver<'a>(
+    runtime_services: &EfiRuntimeServices,
+    capsules: &[&'a EfiCapsuleBuffer],
+    esp_root: Option<&EfiFile>,
+) -> EfiStatusEnum<EfiCapsuleDelivery<'a>> {
+    let error: EfiStatusError = match update_capsule(runtime_services, capsules) {
+        EfiStatusEnum::Error(
+            error @ (EfiStatusError::EfiUnsupported | EfiStatusError::EfiOutOfResources),
+            (),
+        ) => error,
+        status => return status,
+    };
+
+    let esp_root: &EfiFile = match esp_root {
+        Some(esp_root) => esp_root,
+        None => return EfiStatusEnum::Error(error, ()),
+    };
+
+    if !file_capsule_delivery_supported(runtime_services) {
+        return EfiStatusEnum::Error(error, ());
+    }
+
+    /* The files are written first, so the firmware never looks for missing ones */
+    if let EfiStatusEnum::Error(error, ()) = write_capsule_files(esp_root, capsules) {
+        return EfiStatusEnum::Error(error, ());
+    }
+
+    request_file_capsule_delivery(runtime_services).map(|()| EfiCapsuleDelivery::OnDisk)
+}
+
+/// Passes the capsules to `UpdateCapsule`, after checking they fit the firmware's capabilities.
+pub fn update_capsule<'a>(
+    runtime_services: &EfiRuntimeServices,
+    capsules: &[&'a EfiCapsuleBuffer],
+) -> EfiStatusEnum<EfiCapsuleDelivery<'a>> {
+    let services: &dyn EfiRuntimeServicesRevision2x0 = match runtime_services.revision_2_0() {
+        Some(services) => services,
+        None => return EfiStatusEnum::Error(EfiStatusError::EfiUnsupported, ()),
+    };
+
+    let headers: Vec<&EfiCapsuleHeader> = capsules
+        .iter()
+        .map(|capsule: &&EfiCapsuleBuffer| capsule.header())
+        .collect();
+
+    let (maximum_capsule_size, reset_type): (u64, EfiResetType) =
+        match services.query_capsule_capabilities(&headers).unfold() {
+            Ok((_, capabilities)) => capabilities,
+            Err((error, ())) => return EfiStatusEnum::Error(error, ()),
+        };
+
+    let size: u64 = headers
+        .iter()
+        .map(|header: &&EfiCapsuleHeader| u64::from(header.capsule_image_size()))
+        .sum();
+
+    if size > maximum_capsule_size {
+        return EfiStatusEnum::Error(EfiStatusError::EfiOutOfResources, ());
+    }
+
+    let scatter_gather_list: Option<EfiCapsuleBlockDescriptors<'a>> =
+        if headers.iter().any(|header: &&EfiCapsuleHeader| {
+            header
+                .flags()
+                .contains(EfiCapsuleFlags::PERSIST_ACROSS_RESET)
+        }) {
+            let mut scatter_gather_list: EfiCapsuleBlockDescriptors<'a> =
+                EfiCapsuleBlockDescriptors::new();
+
+            for capsule in capsules {
+                scatter_gather_list.push_capsule(capsule);
+            }
+
+            Some(scatter_gather_list)
+        } else {
+            None
+        };
+
+    services
+        .update_capsule(&headers, scatter_gather_list.as_ref())
+        .map(|()| EfiCapsuleDelivery::UpdateCapsule {
+            reset_type,
+            scatter_gather_list,
+        })
+}
+
+/// Returns whether `OsIndicationsSupported` advertises capsule delivery through files.
+pub fn file_capsule_delivery_supported(runtime_services: &EfiRuntimeServices) -> bool {
+    match read_os_indications(runtime_services, EFI_OS_INDICATIONS_SUPPORTED_VARIABLE_NAME) {
+        EfiStatusEnum::Success(supported) | EfiStatusEnum::Warning(_, supported) => {
+            supported & os_indications::FILE_CAPSULE_DELIVERY_SUPPORTED != 0
+        }
+        EfiStatusEnum::Error(_, ()) => false,
+    }
+}
+
+/// Sets the bit in `OsIndications` which makes the firmware process the capsules in `\EFI\UpdateCapsule` on the next boot.
+pub fn request_file_capsule_delivery(runtime_services: &EfiRuntimeServices) -> EfiStatusEnum {
+    /* The variable doesn't exist until a bit is requested */
+    let indications: u64 =
+        match read_os_indications(runtime_services, EFI_OS_INDICATIONS_VARIABLE_NAME) {
+            EfiStatusEnum::Success(indications) | EfiStatusEnum::Warning(_, indications) => {
+                indications
+            }
+            EfiStatusEnum::Error(EfiStatusError::EfiNotFound, ()) => 0,
+            EfiStatusEnum::Error(error, ()) => return EfiStatusEnum::Error(error, ()),
+        };
+
+    runtime_services.revision_1_0().set_variable(
+        EFI_OS_INDICATIONS_VARIABLE_NAME,
+        &guids::EFI_GLOBAL_VARIABLE,
+        &EfiVariableAttributes::new(OS_INDICATIONS_ATTRIBUTES),
+        &(indications | os_indications::FILE_CAPSULE_DELIVERY_SUPPORTED).to_le_bytes(),
+    )
+}
+
+fn read_os_indications(runtime_services: &EfiRuntimeServices, name: &[u16]) -> EfiStatusEnum<u64> {
+    let mut data: [u8; 8] = [0; 8];
+
+    runtime_services
+        .revision_1_0()
+        .get_variable(name, &guids::EFI_GLOBAL_VARIABLE, Some(&mut data))
+        .map(|_| u64::from_le_bytes(data))
+        .map_error(|_| ())
+}
+
+/// Writes the capsules to `\EFI\UpdateCapsule`, creating the directory if needed.
+///
+/// The firmware processes the files in alphabetical order, so they're named after the capsules' indices.
+pub fn write_capsule_files(esp_root: &EfiFile, capsules: &[&EfiCapsuleBuffer]) -> EfiStatusEnum {
+    let mut directory: Option<EfiFile> = None;
+
+    for name in CAPSULE_DIRECTORY.iter() {
+        let parent: &EfiFile = directory.as_ref().unwrap_or(esp_root);
+
+        directory = match parent
+            .open(
+                &file_name(name),
+                EfiFileOpenMode::new(
+                    EfiFileOpenMode::READ | EfiFileOpenMode::WRITE | EfiFileOpenMode::CREATE,
+                ),
+                EfiFileAttributes::new(EfiFileAttributes::DIRECTORY),
+            )
+            .unfold()
+        {
+            Ok((_, directory)) => Some(directory),
+            Err((error, ())) => return EfiStatusEnum::Error(error, ()),
+        };
+    }
+
+    let directory: &EfiFile = directory.as_ref().unwrap_or(esp_root);
+
+    for (index, capsule) in capsules.iter().enumerate() {
+        let name: Vec<u16> = file_name(&format!("Capsule{:04}.bin", index));
+
+        /* Left-over files are replaced instead of partially overwritten */
+        if let EfiStatusEnum::Success(file) | EfiStatusEnum::Warning(_, file) = directory.open(
+            &name,
+            EfiFileOpenMode::new(EfiFileOpenMode::READ | EfiFileOpenMode::WRITE),
+            EfiFileAttributes::new(0),
+        ) {
+            let _ = file.delete();
+        }
+
+        let file: EfiFile = match directory
+            .open(
+                &name,
+                EfiFileOpenMode::new(
+                    EfiFileOpenMode::READ | EfiFileOpenMode::WRITE | EfiFileOpenMode::CREATE,
+                ),
+                EfiFileAttributes::new(0),
+            )
+            .unfold()
+        {
+            Ok((_, file)) => file,
+            Err((error, ())) => return EfiStatusEnum::Error(error, ()),
+        };
+
+        if let EfiStatusEnum::Error(error, ()) = file.write_all(capsule.as_bytes()) {
+            return EfiStatusEnum::Error(error, ());
+        }
+
+        if let EfiStatusEnum::Error(error, ()) = file.close() {
+            return EfiStatusEnum::Error(error, ());
+        }
+    }
+
+    EfiStatusEnum::Success(())
+}
+
+fn file_name(name: &str) -> Vec<u16> {
+    name.encode_utf16().chain(Some(0)).collect()
+}
@@ -1,6 +1,8 @@
+pub mod capsule_delivery;
 mod v1_0;
+mod v2_0;
 
-pub use v1_0::*;
+pub use {v1_0::*, v2_0::*};
 
 use crate::{runtime_services::EfiRuntimeServicesRevision1x0Raw, *};
 
@@ -8,6 +10,7 @@ use crate::{runtime_services::EfiRuntimeServicesRevision1x0Raw, *};
 pub struct EfiRuntimeServices {
     table_header: EfiTableHeader,
     v1_0: EfiRuntimeServicesRevision1x0Raw,
+    v2_0: EfiRuntimeServicesRevision2x0Raw,
 }
 
 impl EfiRuntimeServices {
@@ -19,11 +22,20 @@ impl EfiRuntimeServices {
         &self.table_header
     }
 
-    pub fn revision(&self) -> u32 {
+    pub fn revision(&self) -> EfiRevision {
         self.table_header.revision()
     }
 
     pub fn revision_1_0(&self) -> &dyn EfiRuntimeServicesRevision1x0 {
         &self.v1_0
     }
+
+    /// Returns the services introduced with UEFI 2.0, if the table's revision provides them.
+    pub fn revision_2_0(&self) -> Option<&dyn EfiRuntimeServicesRevision2x0> {
+        if self.table_header.revision() >= EfiRevision::EFI_2_00 {
+            Some(&self.v2_0)
+        } else {
+            None
+        }
+    }
 }
@@ -1,7 +1,7 @@
 use crate::*;
 
 #[repr(C)]
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[non_exhaustive]
 pub enum EfiResetType {
     Cold,
@@ -16,8 +16,7 @@ pub(super) struct EfiVariableRaw {
         VoidMutPtr,
     ) -> EfiStatus,
     get_next_variable_name: extern "efiapi" fn(*mut usize, *mut u16, *mut EfiGuid) -> EfiStatus,
-    set_variable:
-        extern "efiapi" fn(*const u16, *const EfiGuid, *const u32, usize, VoidPtr) -> EfiStatus,
+    set_variable: extern "efiapi" fn(*const u16, *const EfiGuid, u32, usize, VoidPtr) -> EfiStatus,
 }
 
 impl EfiVariableRaw {
@@ -94,7 +93,7 @@ impl EfiVariableRaw {
         (self.set_variable)(
             variable_name_ptr,
             vendor_guid,
-            attributes.borrow(),
+            attributes.attributes,
             data.len(),
             data.as_ptr() as VoidPtr,
         )
@@ -132,6 +131,10 @@ pub struct EfiVariableAttributes {
 }
 
 impl EfiVariableAttributes {
+    pub const fn new(attributes: u32) -> Self {
+        Self { attributes }
+    }
+
     pub fn non_volatile(&self) -> bool {
         self.attributes & 1 == 1
     }
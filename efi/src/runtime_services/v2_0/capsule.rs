@@ -0,0 +1,427 @@
+use {
+    crate::{runtime_services::miscellaneous::EfiResetType, *},
+    alloc::{vec, vec::Vec},
+    core::{
+        convert::TryFrom,
+        marker::PhantomData,
+        mem::size_of,
+        slice::{from_raw_parts, from_raw_parts_mut},
+    },
+};
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub(super) struct EfiCapsuleRaw {
+    update_capsule:
+        extern "efiapi" fn(*const *const EfiCapsuleHeader, usize, EfiPhysicalAddress) -> EfiStatus,
+    query_capsule_capabilities:
+        extern "efiapi" fn(*const *const EfiCapsuleHeader, usize, *mut u64, *mut u32) -> EfiStatus,
+}
+
+impl EfiCapsuleRaw {
+    pub(super) fn update_capsule(
+        &self,
+        capsules: &[&EfiCapsuleHeader],
+        scatter_gather_list: Option<&EfiCapsuleBlockDescriptors>,
+    ) -> EfiStatusEnum {
+        (self.update_capsule)(
+            capsules.as_ptr() as *const *const EfiCapsuleHeader,
+            capsules.len(),
+            scatter_gather_list.map_or(0, EfiCapsuleBlockDescriptors::address),
+        )
+        .into_enum()
+    }
+
+    pub(super) fn query_capsule_capabilities(
+        &self,
+        capsules: &[&EfiCapsuleHeader],
+    ) -> EfiStatusEnum<(u64, EfiResetType)> {
+        let mut maximum_capsule_size: u64 = 0;
+        let mut reset_type: u32 = 0;
+
+        (self.query_capsule_capabilities)(
+            capsules.as_ptr() as *const *const EfiCapsuleHeader,
+            capsules.len(),
+            &mut maximum_capsule_size,
+            &mut reset_type,
+        )
+        .into_enum_data(|| {
+            (
+                maximum_capsule_size,
+                match reset_type {
+                    1 => EfiResetType::Warm,
+                    2 => EfiResetType::Shutdown,
+                    3 => EfiResetType::PlatformSpecific,
+                    /* Unknown types fall back to the most thorough reset */
+                    _ => EfiResetType::Cold,
+                },
+            )
+        })
+    }
+}
+
+pub trait EfiCapsule {
+    /// Passes the capsules to the firmware.
+    ///
+    /// Capsules with the [`PERSIST_ACROSS_RESET`](EfiCapsuleFlags::PERSIST_ACROSS_RESET) flag also have to be described by the scatter-gather list.
+    /// When the [`INITIATE_RESET`](EfiCapsuleFlags::INITIATE_RESET) flag is set, the firmware resets the system instead of returning.
+    fn update_capsule(
+        &self,
+        capsules: &[&EfiCapsuleHeader],
+        scatter_gather_list: Option<&EfiCapsuleBlockDescriptors>,
+    ) -> EfiStatusEnum;
+
+    /// Returns the maximum size of the capsules and the reset they require to be processed.
+    fn query_capsule_capabilities(
+        &self,
+        capsules: &[&EfiCapsuleHeader],
+    ) -> EfiStatusEnum<(u64, EfiResetType)>;
+}
+
+/// Flags of capsules, as found in [`EfiCapsuleHeader`]s.
+///
+/// The lower 16 bits are defined by the capsule's GUID.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EfiCapsuleFlags {
+    flags: u32,
+}
+
+impl EfiCapsuleFlags {
+    pub const PERSIST_ACROSS_RESET: u32 = 0x0001_0000;
+    /// Requires [`PERSIST_ACROSS_RESET`](Self::PERSIST_ACROSS_RESET).
+    pub const POPULATE_SYSTEM_TABLE: u32 = 0x0002_0000;
+    /// Requires [`PERSIST_ACROSS_RESET`](Self::PERSIST_ACROSS_RESET).
+    pub const INITIATE_RESET: u32 = 0x0004_0000;
+
+    pub const fn new(flags: u32) -> Self {
+        Self { flags }
+    }
+
+    pub const fn bits(&self) -> u32 {
+        self.flags
+    }
+
+    pub const fn contains(&self, flags: u32) -> bool {
+        self.flags & flags == flags
+    }
+}
+
+/// Implementation of EFI's `EFI_CAPSULE_HEADER`, which is followed by the capsule's payload.
+#[repr(C)]
+#[derive(Debug)]
+pub struct EfiCapsuleHeader {
+    capsule_guid: EfiGuid,
+    header_size: u32,
+    flags: u32,
+    capsule_image_size: u32,
+}
+
+impl EfiCapsuleHeader {
+    pub fn capsule_guid(&self) -> EfiGuid {
+        self.capsule_guid
+    }
+
+    pub fn header_size(&self) -> u32 {
+        self.header_size
+    }
+
+    pub fn flags(&self) -> EfiCapsuleFlags {
+        EfiCapsuleFlags::new(self.flags)
+    }
+
+    /// Size of the whole capsule, including the header.
+    pub fn capsule_image_size(&self) -> u32 {
+        self.capsule_image_size
+    }
+}
+
+/// Capsule built by [`EfiCapsuleBuilder`], stored in a single contiguous buffer.
+pub struct EfiCapsuleBuffer {
+    /* Kept as "u64"s, as capsules are 8-byte aligned */
+    buffer: Vec<u64>,
+}
+
+impl EfiCapsuleBuffer {
+    pub fn header(&self) -> &EfiCapsuleHeader {
+        unsafe { &*(self.buffer.as_ptr() as *const EfiCapsuleHeader) }
+    }
+
+    /// Returns the whole capsule, including the header.
+    pub fn as_bytes(&self) -> &[u8] {
+        unsafe {
+            from_raw_parts(
+                self.buffer.as_ptr() as *const u8,
+                self.header().capsule_image_size as usize,
+            )
+        }
+    }
+
+    pub fn payload(&self) -> &[u8] {
+        &self.as_bytes()[self.header().header_size as usize..]
+    }
+
+    /// Returns the capsule's address, which is it's physical address while boot services' identity mapping is in place.
+    pub fn address(&self) -> EfiPhysicalAddress {
+        self.buffer.as_ptr() as EfiPhysicalAddress
+    }
+}
+
+/// Builds capsules passed to [`update_capsule`](EfiCapsule::update_capsule) or delivered on disk.
+pub struct EfiCapsuleBuilder {
+    capsule_guid: EfiGuid,
+    flags: EfiCapsuleFlags,
+    payload: Vec<u8>,
+}
+
+impl EfiCapsuleBuilder {
+    /// Creates a capsule carrying the payload, which is interpreted according to the capsule's GUID.
+    pub fn new(capsule_guid: EfiGuid, payload: Vec<u8>) -> Self {
+        Self {
+            capsule_guid,
+            flags: EfiCapsuleFlags::new(0),
+            payload,
+        }
+    }
+
+    /// Creates a capsule updating images through `EFI_FIRMWARE_MANAGEMENT_PROTOCOL`.
+    pub fn firmware_management(payload: &EfiFmpCapsulePayload) -> Self {
+        Self::new(
+            crate::guids::EFI_FIRMWARE_MANAGEMENT_CAPSULE_ID,
+            payload.to_bytes(),
+        )
+    }
+
+    pub fn flags(mut self, flags: EfiCapsuleFlags) -> Self {
+        self.flags = flags;
+
+        self
+    }
+
+    /// Returns `None` when the capsule exceeds the 4 GiB the header can describe.
+    pub fn build(self) -> Option<EfiCapsuleBuffer> {
+        let header_size: usize = size_of::<EfiCapsuleHeader>();
+        let capsule_image_size: u32 = u32::try_from(header_size + self.payload.len()).ok()?;
+
+        let mut buffer: Vec<u64> =
+            vec![0; (capsule_image_size as usize + size_of::<u64>() - 1) / size_of::<u64>()];
+
+        unsafe {
+            (buffer.as_mut_ptr() as *mut EfiCapsuleHeader).write(EfiCapsuleHeader {
+                capsule_guid: self.capsule_guid,
+                header_size: header_size as u32,
+                flags: self.flags.bits(),
+                capsule_image_size,
+            });
+
+            from_raw_parts_mut(
+                (buffer.as_mut_ptr() as *mut u8).add(header_size),
+                self.payload.len(),
+            )
+            .copy_from_slice(&self.payload);
+        }
+
+        Some(EfiCapsuleBuffer { buffer })
+    }
+}
+
+/// Payload of capsules updating images through `EFI_FIRMWARE_MANAGEMENT_PROTOCOL`.
+///
+/// Serialized as `EFI_FIRMWARE_MANAGEMENT_CAPSULE_HEADER`, followed by the embedded drivers and the image updates.
+#[derive(Default)]
+pub struct EfiFmpCapsulePayload {
+    drivers: Vec<Vec<u8>>,
+    images: Vec<EfiFmpCapsuleImage>,
+}
+
+impl EfiFmpCapsulePayload {
+    const VERSION: u32 = 1;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Embeds a driver, which the firmware loads before applying the images.
+    pub fn driver(mut self, driver: &[u8]) -> Self {
+        self.drivers.push(driver.to_vec());
+
+        self
+    }
+
+    pub fn image(mut self, image: EfiFmpCapsuleImage) -> Self {
+        self.images.push(image);
+
+        self
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let items: usize = self.drivers.len() + self.images.len();
+
+        let mut bytes: Vec<u8> = Vec::new();
+
+        bytes.extend_from_slice(&Self::VERSION.to_le_bytes());
+        bytes.extend_from_slice(&(self.drivers.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(&(self.images.len() as u16).to_le_bytes());
+
+        /* Offsets of the items, relative to the header, are filled once they're placed */
+        let offsets: usize = bytes.len();
+
+        bytes.resize(offsets + items * size_of::<u64>(), 0);
+
+        let place = |bytes: &mut Vec<u8>, index: usize, item: &[u8]| {
+            let offset: u64 = bytes.len() as u64;
+
+            bytes[offsets + index * size_of::<u64>()..][..size_of::<u64>()]
+                .copy_from_slice(&offset.to_le_bytes());
+            bytes.extend_from_slice(item);
+        };
+
+        for (index, driver) in self.drivers.iter().enumerate() {
+            place(&mut bytes, index, driver);
+        }
+
+        for (index, image) in self.images.iter().enumerate() {
+            place(&mut bytes, self.drivers.len() + index, &image.to_bytes());
+        }
+
+        bytes
+    }
+}
+
+/// Image update carried by an [`EfiFmpCapsulePayload`].
+///
+/// Serialized as `EFI_FIRMWARE_MANAGEMENT_CAPSULE_IMAGE_HEADER`, followed by the image and the vendor code.
+pub struct EfiFmpCapsuleImage {
+    update_image_type_id: EfiGuid,
+    update_image_index: u8,
+    image: Vec<u8>,
+    vendor_code: Vec<u8>,
+    update_hardware_instance: u64,
+    image_capsule_support: u64,
+}
+
+impl EfiFmpCapsuleImage {
+    const VERSION: u32 = 3;
+
+    /// Updates the image with the given type and index, as found in the image's descriptor.
+    pub fn new(update_image_type_id: EfiGuid, update_image_index: u8, image: &[u8]) -> Self {
+        Self {
+            update_image_type_id,
+            update_image_index,
+            image: image.to_vec(),
+            vendor_code: Vec::new(),
+            update_hardware_instance: 0,
+            image_capsule_support: 0,
+        }
+    }
+
+    pub fn vendor_code(mut self, vendor_code: &[u8]) -> Self {
+        self.vendor_code = vendor_code.to_vec();
+
+        self
+    }
+
+    /// Restricts the update to a single device instance; `0` updates all instances.
+    pub fn update_hardware_instance(mut self, update_hardware_instance: u64) -> Self {
+        self.update_hardware_instance = update_hardware_instance;
+
+        self
+    }
+
+    pub fn image_capsule_support(mut self, image_capsule_support: u64) -> Self {
+        self.image_capsule_support = image_capsule_support;
+
+        self
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes: Vec<u8> = Vec::new();
+
+        bytes.extend_from_slice(&Self::VERSION.to_le_bytes());
+        bytes.extend_from_slice(&self.update_image_type_id.to_array());
+        bytes.extend_from_slice(&[self.update_image_index, 0, 0, 0]);
+        bytes.extend_from_slice(&(self.image.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&(self.vendor_code.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&self.update_hardware_instance.to_le_bytes());
+        bytes.extend_from_slice(&self.image_capsule_support.to_le_bytes());
+        bytes.extend_from_slice(&self.image);
+        bytes.extend_from_slice(&self.vendor_code);
+
+        bytes
+    }
+}
+
+/// Implementation of EFI's `EFI_CAPSULE_BLOCK_DESCRIPTOR`.
+///
+/// A zero length marks a continuation pointer, or the list's end when the address is zero as well.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EfiCapsuleBlockDescriptor {
+    length: u64,
+    address: EfiPhysicalAddress,
+}
+
+impl EfiCapsuleBlockDescriptor {
+    pub const fn length(&self) -> u64 {
+        self.length
+    }
+
+    pub const fn address(&self) -> EfiPhysicalAddress {
+        self.address
+    }
+}
+
+/// Scatter-gather list describing where capsules persisting across reset are placed in memory.
+///
+/// The list is kept terminated and in a single buffer, so no continuation pointers are used.
+pub struct EfiCapsuleBlockDescriptors<'a> {
+    descriptors: Vec<EfiCapsuleBlockDescriptor>,
+    _capsules: PhantomData<&'a EfiCapsuleBuffer>,
+}
+
+impl<'a> EfiCapsuleBlockDescriptors<'a> {
+    pub fn new() -> Self {
+        Self {
+            descriptors: vec![EfiCapsuleBlockDescriptor {
+                length: 0,
+                address: 0,
+            }],
+            _capsules: PhantomData,
+        }
+    }
+
+    /// Describes the capsule's contiguous buffer with a single block.
+    pub fn push_capsule(&mut self, capsule: &'a EfiCapsuleBuffer) {
+        unsafe {
+            self.push_block(
+                capsule.address(),
+                u64::from(capsule.header().capsule_image_size),
+            )
+        }
+    }
+
+    /// Appends a block of physically contiguous memory.
+    /// # Safety
+    /// The block has to stay valid until the capsules are processed, which may be after reset.
+    pub unsafe fn push_block(&mut self, address: EfiPhysicalAddress, length: u64) {
+        let end: usize = self.descriptors.len() - 1;
+
+        self.descriptors
+            .insert(end, EfiCapsuleBlockDescriptor { length, address });
+    }
+
+    /// Returns the descriptors, including the terminating one.
+    pub fn descriptors(&self) -> &[EfiCapsuleBlockDescriptor] {
+        &self.descriptors
+    }
+
+    /// Returns the list's address, which is it's physical address while boot services' identity mapping is in place.
+    pub fn address(&self) -> EfiPhysicalAddress {
+        self.descriptors.as_ptr() as EfiPhysicalAddress
+    }
+}
+
+impl<'a> Default for EfiCapsuleBlockDescriptors<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
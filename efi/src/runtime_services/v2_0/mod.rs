@@ -0,0 +1,44 @@
+pub mod capsule;
+pub mod variable_info;
+
+use crate::EfiStatusEnum;
+
+use super::{miscellaneous::EfiResetType, variable::EfiVariableAttributes};
+use capsule::{EfiCapsule, EfiCapsuleBlockDescriptors, EfiCapsuleHeader, EfiCapsuleRaw};
+use variable_info::{EfiVariableInfo, EfiVariableInfoRaw, EfiVariableStorageInfo};
+
+#[repr(C)]
+pub struct EfiRuntimeServicesRevision2x0Raw {
+    capsule: EfiCapsuleRaw,
+    variable_info: EfiVariableInfoRaw,
+}
+
+pub trait EfiRuntimeServicesRevision2x0: EfiCapsule + EfiVariableInfo {}
+
+impl EfiRuntimeServicesRevision2x0 for EfiRuntimeServicesRevision2x0Raw {}
+
+impl EfiCapsule for EfiRuntimeServicesRevision2x0Raw {
+    fn update_capsule(
+        &self,
+        capsules: &[&EfiCapsuleHeader],
+        scatter_gather_list: Option<&EfiCapsuleBlockDescriptors>,
+    ) -> EfiStatusEnum {
+        self.capsule.update_capsule(capsules, scatter_gather_list)
+    }
+
+    fn query_capsule_capabilities(
+        &self,
+        capsules: &[&EfiCapsuleHeader],
+    ) -> EfiStatusEnum<(u64, EfiResetType)> {
+        self.capsule.query_capsule_capabilities(capsules)
+    }
+}
+
+impl EfiVariableInfo for EfiRuntimeServicesRevision2x0Raw {
+    fn query_variable_info(
+        &self,
+        attributes: &EfiVariableAttributes,
+    ) -> EfiStatusEnum<EfiVariableStorageInfo> {
+        self.variable_info.query_variable_info(attributes)
+    }
+}
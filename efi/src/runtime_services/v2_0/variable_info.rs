@@ -0,0 +1,58 @@
+use crate::{runtime_services::variable::EfiVariableAttributes, *};
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub(super) struct EfiVariableInfoRaw {
+    query_variable_info: extern "efiapi" fn(u32, *mut u64, *mut u64, *mut u64) -> EfiStatus,
+}
+
+impl EfiVariableInfoRaw {
+    pub(super) fn query_variable_info(
+        &self,
+        attributes: &EfiVariableAttributes,
+    ) -> EfiStatusEnum<EfiVariableStorageInfo> {
+        let mut info: EfiVariableStorageInfo = EfiVariableStorageInfo {
+            maximum_storage_size: 0,
+            remaining_storage_size: 0,
+            maximum_variable_size: 0,
+        };
+
+        (self.query_variable_info)(
+            **attributes,
+            &mut info.maximum_storage_size,
+            &mut info.remaining_storage_size,
+            &mut info.maximum_variable_size,
+        )
+        .into_enum_data(|| info)
+    }
+}
+
+pub trait EfiVariableInfo {
+    /// Returns the storage available to variables with the given attributes.
+    fn query_variable_info(
+        &self,
+        attributes: &EfiVariableAttributes,
+    ) -> EfiStatusEnum<EfiVariableStorageInfo>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EfiVariableStorageInfo {
+    maximum_storage_size: u64,
+    remaining_storage_size: u64,
+    maximum_variable_size: u64,
+}
+
+impl EfiVariableStorageInfo {
+    pub const fn maximum_storage_size(&self) -> u64 {
+        self.maximum_storage_size
+    }
+
+    pub const fn remaining_storage_size(&self) -> u64 {
+        self.remaining_storage_size
+    }
+
+    /// Maximum size of a single variable, including it's name.
+    pub const fn maximum_variable_size(&self) -> u64 {
+        self.maximum_variable_size
+    }
+}
@@ -188,6 +188,16 @@ impl<T, E> EfiStatusEnum<T, E> {
     }
 }
 
+impl<T, E> From<&EfiStatusEnum<T, E>> for EfiStatus {
+    fn from(status: &EfiStatusEnum<T, E>) -> Self {
+        match status {
+            EfiStatusEnum::Success(_) => Self::success(),
+            EfiStatusEnum::Warning(warning, _) => (*warning).into(),
+            EfiStatusEnum::Error(error, _) => (*error).into(),
+        }
+    }
+}
+
 impl<T, E> Display for EfiStatusEnum<T, E> {
     default fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
         match self {
@@ -304,3 +314,65 @@ pub enum EfiStatusError {
     EfiIpAddressConflict,
     EfiHttpError,
 }
+
+impl From<EfiStatusWarning> for EfiStatus {
+    fn from(warning: EfiStatusWarning) -> Self {
+        use EfiStatusWarning::*;
+
+        Self::warning(match warning {
+            UnknownWarning(status) => status,
+
+            EfiWarnUnknownGlyph => 1,
+            EfiWarnDeleteFailure => 2,
+            EfiWarnWriteFailure => 3,
+            EfiWarnBufferTooSmall => 4,
+            EfiWarnStaleData => 5,
+            EfiWarnFileSystem => 6,
+            EfiWarnResetRequired => 7,
+        })
+    }
+}
+
+impl From<EfiStatusError> for EfiStatus {
+    fn from(error: EfiStatusError) -> Self {
+        use EfiStatusError::*;
+
+        Self::error(match error {
+            UnknownError(status) => status,
+
+            EfiLoadError => 1,
+            EfiInvalidParameter => 2,
+            EfiUnsupported => 3,
+            EfiBadBufferSize => 4,
+            EfiBufferTooSmall => 5,
+            EfiNotReady => 6,
+            EfiDeviceError => 7,
+            EfiWriteProtected => 8,
+            EfiOutOfResources => 9,
+            EfiVolumeCorrupted => 10,
+            EfiVolumeFull => 11,
+            EfiNoMedia => 12,
+            EfiMediaChanged => 13,
+            EfiNotFound => 14,
+            EfiAccessDenied => 15,
+            EfiNoResponse => 16,
+            EfiNoMapping => 17,
+            EfiTimeout => 18,
+            EfiNotStarted => 19,
+            EfiAlreadyStarted => 20,
+            EfiAborted => 21,
+            EfiIcmpError => 22,
+            EfiTftpError => 23,
+            EfiProtocolError => 24,
+            EfiIncompatibleVersion => 25,
+            EfiSecurityViolation => 26,
+            EfiCrcError => 27,
+            EfiEndOfMedia => 28,
+            EfiEndOfFile => 31,
+            EfiInvalidLanguage => 32,
+            EfiCompromisedData => 33,
+            EfiIpAddressConflict => 34,
+            EfiHttpError => 35,
+        })
+    }
+}
@@ -0,0 +1,347 @@
+use core::{convert::TryInto, iter::FusedIterator, str};
+
+/// Size of the base EDID block, as well as each extension block.
+pub const EDID_BLOCK_SIZE: usize = 128;
+
+const HEADER: [u8; 8] = [0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x00];
+
+const ESTABLISHED_TIMINGS_OFFSET: usize = 0x23;
+const STANDARD_TIMINGS_OFFSET: usize = 0x26;
+const DESCRIPTORS_OFFSET: usize = 0x36;
+const DESCRIPTOR_SIZE: usize = 18;
+const DESCRIPTORS_COUNT: usize = 4;
+
+/* Display descriptor tags */
+const MONITOR_NAME_TAG: u8 = 0xFC;
+const STANDARD_TIMINGS_TAG: u8 = 0xFA;
+
+/// Timings advertised through the established timings bitmap, most significant bit first.
+#[rustfmt::skip]
+const ESTABLISHED_TIMINGS: [EdidTiming; 17] = [
+    EdidTiming::new(720, 400, 70),
+    EdidTiming::new(720, 400, 88),
+    EdidTiming::new(640, 480, 60),
+    EdidTiming::new(640, 480, 67),
+    EdidTiming::new(640, 480, 72),
+    EdidTiming::new(640, 480, 75),
+    EdidTiming::new(800, 600, 56),
+    EdidTiming::new(800, 600, 60),
+    EdidTiming::new(800, 600, 72),
+    EdidTiming::new(800, 600, 75),
+    EdidTiming::new(832, 624, 75),
+    /* Interlaced */
+    EdidTiming::new(1024, 768, 87),
+    EdidTiming::new(1024, 768, 60),
+    EdidTiming::new(1024, 768, 70),
+    EdidTiming::new(1024, 768, 75),
+    EdidTiming::new(1280, 1024, 75),
+    EdidTiming::new(1152, 870, 75),
+];
+
+/// Base block of VESA's Extended Display Identification Data, version 1.4.
+///
+/// Earlier 1.x versions share the layout and are parsed the same way. Extension blocks aren't interpreted.
+#[derive(Clone, Copy)]
+pub struct Edid<'a> {
+    block: &'a [u8; EDID_BLOCK_SIZE],
+}
+
+impl<'a> Edid<'a> {
+    /// Parses the base block and verifies it's header and checksum.
+    pub fn parse(data: &'a [u8]) -> Option<Self> {
+        let block: &'a [u8; EDID_BLOCK_SIZE] = data.get(..EDID_BLOCK_SIZE)?.try_into().ok()?;
+
+        if block[..HEADER.len()] != HEADER
+            || block
+                .iter()
+                .fold(0, |sum: u8, &byte: &u8| sum.wrapping_add(byte))
+                != 0
+        {
+            return None;
+        }
+
+        Some(Self { block })
+    }
+
+    /// Returns the three letter PNP ID of the manufacturer.
+    pub fn manufacturer_id(&self) -> [u8; 3] {
+        let id: u16 = u16::from_be_bytes([self.block[0x08], self.block[0x09]]);
+
+        /* Five bits per letter, 1 being 'A' */
+        [10, 5, 0].map(|shift: u16| b'A' - 1 + ((id >> shift) & 0x1F) as u8)
+    }
+
+    pub fn product_code(&self) -> u16 {
+        u16::from_le_bytes([self.block[0x0A], self.block[0x0B]])
+    }
+
+    /// Returns the serial number, or zero if it's not given.
+    pub fn serial_number(&self) -> u32 {
+        u32::from_le_bytes([
+            self.block[0x0C],
+            self.block[0x0D],
+            self.block[0x0E],
+            self.block[0x0F],
+        ])
+    }
+
+    /// Returns the week of manufacture, which may be zero, or `0xFF` when the year is the model year.
+    pub fn manufacture_week(&self) -> u8 {
+        self.block[0x10]
+    }
+
+    pub fn manufacture_year(&self) -> u16 {
+        1990 + u16::from(self.block[0x11])
+    }
+
+    /// Returns the EDID version and revision.
+    pub fn version(&self) -> (u8, u8) {
+        (self.block[0x12], self.block[0x13])
+    }
+
+    pub fn is_digital(&self) -> bool {
+        self.block[0x14] & 0x80 != 0
+    }
+
+    /// Returns the horizontal and vertical size of the screen in centimeters.
+    ///
+    /// Projectors and displays only advertising their aspect ratio return `None`.
+    pub fn physical_size(&self) -> Option<(u8, u8)> {
+        match (self.block[0x15], self.block[0x16]) {
+            (0, _) | (_, 0) => None,
+            size => Some(size),
+        }
+    }
+
+    /// Returns the number of extension blocks following the base one.
+    pub fn extension_count(&self) -> u8 {
+        self.block[0x7E]
+    }
+
+    pub fn established_timings(&self) -> impl Iterator<Item = EdidTiming> + 'a {
+        let bitmap: u32 = u32::from_be_bytes([
+            self.block[ESTABLISHED_TIMINGS_OFFSET],
+            self.block[ESTABLISHED_TIMINGS_OFFSET + 1],
+            self.block[ESTABLISHED_TIMINGS_OFFSET + 2],
+            0,
+        ]);
+
+        ESTABLISHED_TIMINGS
+            .iter()
+            .enumerate()
+            .filter(move |&(index, _): &(usize, &EdidTiming)| bitmap & (1 << (31 - index)) != 0)
+            .map(|(_, &timing): (usize, &EdidTiming)| timing)
+    }
+
+    /// Returns the standard timings, including those listed in standard timing descriptors.
+    pub fn standard_timings(&self) -> impl Iterator<Item = EdidTiming> + 'a {
+        let version: (u8, u8) = self.version();
+
+        self.block[STANDARD_TIMINGS_OFFSET..DESCRIPTORS_OFFSET]
+            .chunks(2)
+            .chain(
+                self.display_descriptors(STANDARD_TIMINGS_TAG)
+                    .flat_map(|data: &'a [u8]| data[..12].chunks(2)),
+            )
+            .filter_map(move |timing: &[u8]| EdidTiming::from_standard(timing, version))
+    }
+
+    /// Returns the detailed timings, the first one being the preferred timing.
+    pub fn detailed_timings(&self) -> EdidDetailedTimings<'a> {
+        EdidDetailedTimings {
+            descriptors: self.descriptors(),
+        }
+    }
+
+    /// Returns the display's preferred timing, which is it's native mode for flat panels.
+    pub fn preferred_timing(&self) -> Option<EdidDetailedTiming> {
+        self.detailed_timings().next()
+    }
+
+    /// Returns the horizontal and vertical resolution of the preferred timing.
+    pub fn native_resolution(&self) -> Option<(u16, u16)> {
+        self.preferred_timing().map(|timing: EdidDetailedTiming| {
+            (timing.horizontal_active(), timing.vertical_active())
+        })
+    }
+
+    /// Returns the monitor name, if any descriptor contains it.
+    pub fn monitor_name(&self) -> Option<&'a str> {
+        self.display_descriptors(MONITOR_NAME_TAG)
+            .next()
+            .and_then(|data: &'a [u8]| {
+                /* Terminated by a line feed and padded with spaces */
+                let length: usize = data
+                    .iter()
+                    .position(|&byte: &u8| byte == b'\n')
+                    .unwrap_or(data.len());
+
+                str::from_utf8(&data[..length]).ok()
+            })
+    }
+
+    fn descriptors(&self) -> core::slice::Chunks<'a, u8> {
+        self.block[DESCRIPTORS_OFFSET..DESCRIPTORS_OFFSET + DESCRIPTOR_SIZE * DESCRIPTORS_COUNT]
+            .chunks(DESCRIPTOR_SIZE)
+    }
+
+    /// Returns the data of the display descriptors with the given tag.
+    fn display_descriptors(&self, tag: u8) -> impl Iterator<Item = &'a [u8]> + 'a {
+        self.descriptors()
+            .filter(move |descriptor: &&'a [u8]| descriptor[..2] == [0, 0] && descriptor[3] == tag)
+            .map(|descriptor: &'a [u8]| &descriptor[5..])
+    }
+}
+
+/// Resolution and refresh rate, as listed by established and standard timings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EdidTiming {
+    width: u16,
+    height: u16,
+    refresh_rate: u8,
+}
+
+impl EdidTiming {
+    const fn new(width: u16, height: u16, refresh_rate: u8) -> Self {
+        Self {
+            width,
+            height,
+            refresh_rate,
+        }
+    }
+
+    fn from_standard(timing: &[u8], version: (u8, u8)) -> Option<Self> {
+        /* Unused entries are filled with 0x01 */
+        if timing == [0x01, 0x01] || timing[0] == 0 {
+            return None;
+        }
+
+        let width: u16 = (u16::from(timing[0]) + 31) * 8;
+
+        let height: u16 = match timing[1] >> 6 {
+            /* EDID 1.3 replaced 1:1 with 16:10 */
+            0 if version < (1, 3) => width,
+            0 => width * 10 / 16,
+            1 => width * 3 / 4,
+            2 => width * 4 / 5,
+            _ => width * 9 / 16,
+        };
+
+        Some(Self::new(width, height, (timing[1] & 0x3F) + 60))
+    }
+
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    /// Returns the refresh rate in Hz.
+    pub fn refresh_rate(&self) -> u8 {
+        self.refresh_rate
+    }
+}
+
+/// Detailed timing descriptor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EdidDetailedTiming {
+    raw: [u8; DESCRIPTOR_SIZE],
+}
+
+impl EdidDetailedTiming {
+    fn low_high(&self, low: usize, high: usize, shift: u32) -> u16 {
+        u16::from(self.raw[low]) | u16::from((self.raw[high] >> shift) & 0xF) << 8
+    }
+
+    /// Returns the pixel clock in units of 10 kHz.
+    pub fn pixel_clock(&self) -> u16 {
+        u16::from_le_bytes([self.raw[0], self.raw[1]])
+    }
+
+    pub fn horizontal_active(&self) -> u16 {
+        self.low_high(2, 4, 4)
+    }
+
+    pub fn horizontal_blanking(&self) -> u16 {
+        self.low_high(3, 4, 0)
+    }
+
+    pub fn vertical_active(&self) -> u16 {
+        self.low_high(5, 7, 4)
+    }
+
+    pub fn vertical_blanking(&self) -> u16 {
+        self.low_high(6, 7, 0)
+    }
+
+    pub fn horizontal_sync_offset(&self) -> u16 {
+        u16::from(self.raw[8]) | u16::from(self.raw[11] >> 6) << 8
+    }
+
+    pub fn horizontal_sync_pulse_width(&self) -> u16 {
+        u16::from(self.raw[9]) | u16::from((self.raw[11] >> 4) & 0x3) << 8
+    }
+
+    pub fn vertical_sync_offset(&self) -> u8 {
+        self.raw[10] >> 4 | ((self.raw[11] >> 2) & 0x3) << 4
+    }
+
+    pub fn vertical_sync_pulse_width(&self) -> u8 {
+        self.raw[10] & 0xF | (self.raw[11] & 0x3) << 4
+    }
+
+    /// Returns the horizontal size of the image in millimeters.
+    pub fn horizontal_image_size(&self) -> u16 {
+        self.low_high(12, 14, 4)
+    }
+
+    /// Returns the vertical size of the image in millimeters.
+    pub fn vertical_image_size(&self) -> u16 {
+        self.low_high(13, 14, 0)
+    }
+
+    pub fn horizontal_border(&self) -> u8 {
+        self.raw[15]
+    }
+
+    pub fn vertical_border(&self) -> u8 {
+        self.raw[16]
+    }
+
+    pub fn is_interlaced(&self) -> bool {
+        self.raw[17] & 0x80 != 0
+    }
+
+    /// Returns the refresh rate in Hz, rounded to the nearest integer.
+    pub fn refresh_rate(&self) -> u32 {
+        let total: u32 = (u32::from(self.horizontal_active())
+            + u32::from(self.horizontal_blanking()))
+            * (u32::from(self.vertical_active()) + u32::from(self.vertical_blanking()));
+
+        (u32::from(self.pixel_clock()) * 10_000 + total / 2)
+            .checked_div(total)
+            .unwrap_or(0)
+    }
+}
+
+/// Iterator over the detailed timing descriptors of the base block.
+pub struct EdidDetailedTimings<'a> {
+    descriptors: core::slice::Chunks<'a, u8>,
+}
+
+impl Iterator for EdidDetailedTimings<'_> {
+    type Item = EdidDetailedTiming;
+
+    fn next(&mut self) -> Option<<Self as Iterator>::Item> {
+        /* Display descriptors start with a zero pixel clock */
+        self.descriptors
+            .find(|descriptor: &&[u8]| descriptor[..2] != [0, 0])
+            .map(|descriptor: &[u8]| EdidDetailedTiming {
+                raw: descriptor.try_into().unwrap_or([0; DESCRIPTOR_SIZE]),
+            })
+    }
+}
+
+impl FusedIterator for EdidDetailedTimings<'_> {}
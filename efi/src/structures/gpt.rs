@@ -0,0 +1,216 @@
+use {
+    crate::{crc32::Crc32, guid::EfiGuid, types::EfiLBA},
+    core::mem::size_of,
+};
+
+/// "EFI PART"
+pub const EFI_PTAB_HEADER_ID: u64 = 0x5452_4150_2049_4645;
+
+const HEADER_CRC32_OFFSET: usize = 16;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct EfiPartitionTableHeaderRaw {
+    signature: u64,
+    revision: u32,
+    header_size: u32,
+    header_crc32: u32,
+    reserved: u32,
+    my_lba: EfiLBA,
+    alternate_lba: EfiLBA,
+    first_usable_lba: EfiLBA,
+    last_usable_lba: EfiLBA,
+    disk_guid: EfiGuid,
+    partition_entry_lba: EfiLBA,
+    number_of_partition_entries: u32,
+    size_of_partition_entry: u32,
+    partition_entry_array_crc32: u32,
+}
+
+/// GUID Partition Table header.
+#[derive(Clone, Copy)]
+pub struct EfiPartitionTableHeader {
+    raw: EfiPartitionTableHeaderRaw,
+}
+
+impl EfiPartitionTableHeader {
+    /// Parses the header from the beginning of the block and verifies it's signature and checksum.
+    pub fn parse(block: &[u8]) -> Option<Self> {
+        /* Only the fields defined by the specification are included in the structure */
+        const DEFINED_SIZE: usize = 92;
+
+        if block.len() < size_of::<EfiPartitionTableHeaderRaw>() {
+            return None;
+        }
+
+        let raw: EfiPartitionTableHeaderRaw =
+            unsafe { (block.as_ptr() as *const EfiPartitionTableHeaderRaw).read_unaligned() };
+
+        let header_size: usize = raw.header_size as usize;
+
+        if raw.signature != EFI_PTAB_HEADER_ID
+            || header_size < DEFINED_SIZE
+            || block.len() < header_size
+        {
+            return None;
+        }
+
+        let mut crc: Crc32 = Crc32::new();
+
+        /* Calculate with "header_crc32" being zeroed out */
+        crc.update(&block[..HEADER_CRC32_OFFSET]);
+        crc.update_zeroed(size_of::<u32>());
+        crc.update(&block[HEADER_CRC32_OFFSET + size_of::<u32>()..header_size]);
+
+        if crc.finish() == raw.header_crc32 {
+            Some(Self { raw })
+        } else {
+            None
+        }
+    }
+
+    pub fn revision(&self) -> u32 {
+        self.raw.revision
+    }
+
+    pub fn my_lba(&self) -> EfiLBA {
+        self.raw.my_lba
+    }
+
+    /// Returns the LBA of the other copy of the header.
+    pub fn alternate_lba(&self) -> EfiLBA {
+        self.raw.alternate_lba
+    }
+
+    pub fn first_usable_lba(&self) -> EfiLBA {
+        self.raw.first_usable_lba
+    }
+
+    pub fn last_usable_lba(&self) -> EfiLBA {
+        self.raw.last_usable_lba
+    }
+
+    pub fn disk_guid(&self) -> EfiGuid {
+        self.raw.disk_guid
+    }
+
+    pub fn partition_entry_lba(&self) -> EfiLBA {
+        self.raw.partition_entry_lba
+    }
+
+    pub fn number_of_partition_entries(&self) -> u32 {
+        self.raw.number_of_partition_entries
+    }
+
+    pub fn size_of_partition_entry(&self) -> u32 {
+        self.raw.size_of_partition_entry
+    }
+
+    /// Returns the size of the partition entry array in bytes.
+    pub fn partition_entry_array_size(&self) -> usize {
+        self.raw.number_of_partition_entries as usize * self.raw.size_of_partition_entry as usize
+    }
+
+    /// Verifies the checksum of the partition entry array and returns an iterator over it's entries.
+    pub fn parse_entries<'a>(&self, data: &'a [u8]) -> Option<EfiPartitionEntries<'a>> {
+        let size: usize = self.partition_entry_array_size();
+        let entry_size: usize = self.raw.size_of_partition_entry as usize;
+
+        if entry_size < size_of::<EfiPartitionEntryRaw>() || data.len() < size {
+            return None;
+        }
+
+        let mut crc: Crc32 = Crc32::new();
+
+        crc.update(&data[..size]);
+
+        if crc.finish() == self.raw.partition_entry_array_crc32 {
+            Some(EfiPartitionEntries {
+                data: &data[..size],
+                entry_size,
+            })
+        } else {
+            None
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct EfiPartitionEntryRaw {
+    partition_type_guid: EfiGuid,
+    unique_partition_guid: EfiGuid,
+    starting_lba: EfiLBA,
+    ending_lba: EfiLBA,
+    attributes: u64,
+    partition_name: [u16; 36],
+}
+
+/// GUID Partition Table entry.
+#[derive(Clone, Copy)]
+pub struct EfiPartitionEntry {
+    raw: EfiPartitionEntryRaw,
+}
+
+impl EfiPartitionEntry {
+    pub fn partition_type_guid(&self) -> EfiGuid {
+        self.raw.partition_type_guid
+    }
+
+    pub fn unique_partition_guid(&self) -> EfiGuid {
+        self.raw.unique_partition_guid
+    }
+
+    pub fn starting_lba(&self) -> EfiLBA {
+        self.raw.starting_lba
+    }
+
+    /// Returns the last LBA of the partition (inclusive).
+    pub fn ending_lba(&self) -> EfiLBA {
+        self.raw.ending_lba
+    }
+
+    pub fn attributes(&self) -> u64 {
+        self.raw.attributes
+    }
+
+    /// Returns the partition's name without the null terminator.
+    pub fn partition_name(&self) -> &[u16] {
+        let name: &[u16] = &self.raw.partition_name;
+
+        &name[..name
+            .iter()
+            .position(|&unit: &u16| unit == 0)
+            .unwrap_or(name.len())]
+    }
+}
+
+/// Iterator over the used entries of a partition entry array.
+pub struct EfiPartitionEntries<'a> {
+    data: &'a [u8],
+    entry_size: usize,
+}
+
+impl Iterator for EfiPartitionEntries<'_> {
+    type Item = EfiPartitionEntry;
+
+    fn next(&mut self) -> Option<<Self as Iterator>::Item> {
+        while self.data.len() >= self.entry_size {
+            let (entry, rest): (&[u8], &[u8]) = self.data.split_at(self.entry_size);
+
+            self.data = rest;
+
+            /* Unused entries have a zeroed type */
+            if entry[..size_of::<EfiGuid>()] != [0; 16] {
+                /* The entry size was checked against the structure's one by "parse_entries" */
+                return Some(EfiPartitionEntry {
+                    raw: unsafe {
+                        (entry.as_ptr() as *const EfiPartitionEntryRaw).read_unaligned()
+                    },
+                });
+            }
+        }
+
+        None
+    }
+}
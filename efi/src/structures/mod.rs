@@ -1 +1,4 @@
+pub mod edid;
+pub mod gpt;
 pub mod load_option;
+pub mod nvdimm_label;
@@ -0,0 +1,393 @@
+use {
+    crate::guid::EfiGuid,
+    core::{convert::TryFrom, iter::FusedIterator, mem::size_of},
+};
+
+/// "NAMESPACE_INDEX\0"
+pub const EFI_NVDIMM_LABEL_INDEX_SIG: [u8; 16] = *b"NAMESPACE_INDEX\0";
+
+/// Index blocks are aligned to, and at least as big as, this size.
+pub const EFI_NVDIMM_LABEL_INDEX_ALIGN: usize = 256;
+
+const INDEX_CHECKSUM_OFFSET: usize = 64;
+const LABEL_CHECKSUM_OFFSET: usize = 248;
+
+/// Size of labels before version 1.2, which have no type GUID or checksum.
+const LABEL_SIZE_1_1: usize = 128;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct EfiNvdimmLabelIndexBlockRaw {
+    sig: [u8; 16],
+    flags: [u8; 3],
+    label_size: u8,
+    seq: u32,
+    my_off: u64,
+    my_size: u64,
+    other_off: u64,
+    label_off: u64,
+    n_slot: u32,
+    major: u16,
+    minor: u16,
+    checksum: u64,
+}
+
+/// Index block of an NVDIMM's label storage area.
+///
+/// The area starts with two index blocks, of which the one with the newer sequence number is current.
+#[derive(Clone, Copy)]
+pub struct EfiNvdimmLabelIndexBlock<'a> {
+    raw: EfiNvdimmLabelIndexBlockRaw,
+    free: &'a [u8],
+}
+
+impl<'a> EfiNvdimmLabelIndexBlock<'a> {
+    /// Parses the index block at the offset and verifies it's signature, location and checksum.
+    pub fn parse(area: &'a [u8], offset: usize) -> Option<Self> {
+        let block: &[u8] = area.get(offset..)?;
+
+        if block.len() < size_of::<EfiNvdimmLabelIndexBlockRaw>() {
+            return None;
+        }
+
+        let raw: EfiNvdimmLabelIndexBlockRaw =
+            unsafe { (block.as_ptr() as *const EfiNvdimmLabelIndexBlockRaw).read_unaligned() };
+
+        let size: usize = raw.my_size as usize;
+        let free_size: usize = (raw.n_slot as usize).div_ceil(8);
+
+        if raw.sig != EFI_NVDIMM_LABEL_INDEX_SIG
+            || raw.my_off != offset as u64
+            || size < size_of::<EfiNvdimmLabelIndexBlockRaw>() + free_size
+            || block.len() < size
+            /* Sequence numbers cycle through 1, 2 and 3 */
+            || !(1..=3).contains(&raw.seq)
+            /* Labels are either 128 or 256 bytes big */
+            || raw.label_size > 1
+        {
+            return None;
+        }
+
+        /* Calculate with "checksum" being zeroed out */
+        let mut fletcher: Fletcher64 = Fletcher64::new();
+
+        fletcher.update(&block[..INDEX_CHECKSUM_OFFSET]);
+        fletcher.update(&[0; size_of::<u64>()]);
+        fletcher.update(&block[INDEX_CHECKSUM_OFFSET + size_of::<u64>()..size]);
+
+        if fletcher.finish() == raw.checksum {
+            Some(Self {
+                raw,
+                free: &block[size_of::<EfiNvdimmLabelIndexBlockRaw>()..][..free_size],
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Returns the sequence number, which is between 1 and 3.
+    pub fn seq(&self) -> u32 {
+        self.raw.seq
+    }
+
+    /// Returns whether this block is newer than the other one, based on their sequence numbers.
+    pub fn is_newer_than(&self, other: &Self) -> bool {
+        self.raw.seq == other.raw.seq % 3 + 1
+    }
+
+    /// Returns the size of each label in bytes.
+    pub fn label_size(&self) -> usize {
+        LABEL_SIZE_1_1 << self.raw.label_size
+    }
+
+    /// Returns the offset of this index block within the label storage area.
+    pub fn my_off(&self) -> u64 {
+        self.raw.my_off
+    }
+
+    /// Returns the size of this index block, including the free bitmap and padding.
+    pub fn my_size(&self) -> u64 {
+        self.raw.my_size
+    }
+
+    /// Returns the offset of the other index block within the label storage area.
+    pub fn other_off(&self) -> u64 {
+        self.raw.other_off
+    }
+
+    /// Returns the offset of the first label slot within the label storage area.
+    pub fn label_off(&self) -> u64 {
+        self.raw.label_off
+    }
+
+    /// Returns the number of label slots.
+    pub fn n_slot(&self) -> u32 {
+        self.raw.n_slot
+    }
+
+    /// Returns the major and minor version of the label format.
+    pub fn version(&self) -> (u16, u16) {
+        (self.raw.major, self.raw.minor)
+    }
+
+    /// Returns whether the slot is free, which slots past the last one are considered to be.
+    pub fn is_free(&self, slot: u32) -> bool {
+        slot >= self.raw.n_slot || self.free[slot as usize / 8] & (1 << (slot % 8)) != 0
+    }
+}
+
+/// Label storage area of an NVDIMM, read through [`EfiNvdimmLabelProtocol`](crate::protocols::media::EfiNvdimmLabelProtocol).
+#[derive(Clone, Copy)]
+pub struct EfiNvdimmLabelArea<'a> {
+    area: &'a [u8],
+    index: EfiNvdimmLabelIndexBlock<'a>,
+}
+
+impl<'a> EfiNvdimmLabelArea<'a> {
+    /// Locates the current index block.
+    ///
+    /// Returns `None` when neither index block is valid, as is the case for NVDIMMs without labels.
+    pub fn parse(area: &'a [u8]) -> Option<Self> {
+        let first: Option<EfiNvdimmLabelIndexBlock<'a>> = EfiNvdimmLabelIndexBlock::parse(area, 0);
+
+        /* The second block follows the first, which may be the corrupted one */
+        let second: Option<EfiNvdimmLabelIndexBlock<'a>> = match first {
+            Some(first) => EfiNvdimmLabelIndexBlock::parse(area, first.other_off() as usize),
+            None => (1..area.len() / EFI_NVDIMM_LABEL_INDEX_ALIGN).find_map(|blocks: usize| {
+                EfiNvdimmLabelIndexBlock::parse(area, blocks * EFI_NVDIMM_LABEL_INDEX_ALIGN)
+            }),
+        };
+
+        let index: EfiNvdimmLabelIndexBlock<'a> = match (first, second) {
+            (Some(first), Some(second)) if second.is_newer_than(&first) => second,
+            (Some(first), _) => first,
+            (None, second) => second?,
+        };
+
+        Some(Self { area, index })
+    }
+
+    /// Returns the current index block.
+    pub fn index(&self) -> EfiNvdimmLabelIndexBlock<'a> {
+        self.index
+    }
+
+    /// Returns iterator over the labels in the used slots, skipping the ones which fail verification.
+    pub fn labels(&self) -> EfiNvdimmLabels<'a> {
+        EfiNvdimmLabels {
+            area: self.area,
+            index: self.index,
+            slot: 0,
+        }
+    }
+}
+
+/// Iterator over the labels of a label storage area.
+pub struct EfiNvdimmLabels<'a> {
+    area: &'a [u8],
+    index: EfiNvdimmLabelIndexBlock<'a>,
+    slot: u32,
+}
+
+impl<'a> Iterator for EfiNvdimmLabels<'a> {
+    type Item = EfiNvdimmNamespaceLabel<'a>;
+
+    fn next(&mut self) -> Option<<Self as Iterator>::Item> {
+        while self.slot < self.index.n_slot() {
+            let slot: u32 = self.slot;
+
+            self.slot += 1;
+
+            if self.index.is_free(slot) {
+                continue;
+            }
+
+            let label_size: usize = self.index.label_size();
+
+            /* The following slots are out of the area's bounds as well */
+            let offset: usize = (slot as usize)
+                .checked_mul(label_size)
+                .zip(usize::try_from(self.index.label_off()).ok())
+                .and_then(|(offset, label_off): (usize, usize)| offset.checked_add(label_off))?;
+
+            if let Some(label) = offset
+                .checked_add(label_size)
+                .and_then(|end: usize| self.area.get(offset..end))
+                .and_then(EfiNvdimmNamespaceLabel::parse)
+            {
+                /* Labels record the slot they're meant to be in */
+                if label.slot() == slot {
+                    return Some(label);
+                }
+            }
+        }
+
+        None
+    }
+}
+
+impl FusedIterator for EfiNvdimmLabels<'_> {}
+
+/// Namespace label of an NVDIMM, describing the part of a namespace the NVDIMM holds.
+///
+/// The labels of a namespace spread across several NVDIMMs share it's UUID and form a label set.
+#[derive(Clone, Copy)]
+pub struct EfiNvdimmNamespaceLabel<'a> {
+    label: &'a [u8],
+}
+
+impl<'a> EfiNvdimmNamespaceLabel<'a> {
+    /* Flags */
+    /// The namespace is read-only.
+    pub const FLAGS_ROLABEL: u32 = 0x0000_0001;
+    /// The label describes a namespace local to this NVDIMM, rather than an interleave set.
+    pub const FLAGS_LOCAL: u32 = 0x0000_0002;
+    /// The label set is being updated and may be incomplete.
+    pub const FLAGS_UPDATING: u32 = 0x0000_0008;
+
+    /// Parses the label, verifying it's checksum if it's format has one.
+    pub fn parse(label: &'a [u8]) -> Option<Self> {
+        if label.len() < LABEL_SIZE_1_1 {
+            return None;
+        }
+
+        if label.len() >= LABEL_CHECKSUM_OFFSET + size_of::<u64>() {
+            let mut fletcher: Fletcher64 = Fletcher64::new();
+
+            fletcher.update(&label[..LABEL_CHECKSUM_OFFSET]);
+            fletcher.update(&[0; size_of::<u64>()]);
+            fletcher.update(&label[LABEL_CHECKSUM_OFFSET + size_of::<u64>()..]);
+
+            if fletcher.finish() != read_u64(label, LABEL_CHECKSUM_OFFSET) {
+                return None;
+            }
+        }
+
+        Some(Self { label })
+    }
+
+    /// Returns the UUID of the namespace.
+    pub fn uuid(&self) -> EfiGuid {
+        read_guid(self.label, 0)
+    }
+
+    /// Returns the namespace's name, without the null terminator.
+    pub fn name(&self) -> &'a [u8] {
+        let name: &[u8] = &self.label[16..80];
+
+        &name[..name
+            .iter()
+            .position(|&byte: &u8| byte == 0)
+            .unwrap_or(name.len())]
+    }
+
+    pub fn flags(&self) -> u32 {
+        read_u32(self.label, 80)
+    }
+
+    pub fn contains(&self, flags: u32) -> bool {
+        self.flags() & flags == flags
+    }
+
+    /// Returns the number of labels in the label set.
+    pub fn n_label(&self) -> u16 {
+        read_u16(self.label, 84)
+    }
+
+    /// Returns the position of the label within the label set.
+    pub fn position(&self) -> u16 {
+        read_u16(self.label, 86)
+    }
+
+    /// Returns the cookie identifying the interleave set the namespace belongs to.
+    pub fn set_cookie(&self) -> u64 {
+        read_u64(self.label, 88)
+    }
+
+    /// Returns the namespace's logical block size, where zero means the namespace is byte addressable.
+    pub fn lba_size(&self) -> u64 {
+        read_u64(self.label, 96)
+    }
+
+    /// Returns the NVDIMM physical address of the namespace's part on this NVDIMM.
+    pub fn dpa(&self) -> u64 {
+        read_u64(self.label, 104)
+    }
+
+    /// Returns the size of the namespace's part on this NVDIMM.
+    pub fn raw_size(&self) -> u64 {
+        read_u64(self.label, 112)
+    }
+
+    /// Returns the slot the label occupies.
+    pub fn slot(&self) -> u32 {
+        read_u32(self.label, 120)
+    }
+
+    /// Returns the alignment hint, as a power of two in 4 KiB units.
+    pub fn alignment(&self) -> u8 {
+        self.label[124]
+    }
+
+    /// Returns the address range type GUID of the region, or `None` for labels before version 1.2.
+    pub fn type_guid(&self) -> Option<EfiGuid> {
+        (self.label.len() > LABEL_SIZE_1_1).then(|| read_guid(self.label, 128))
+    }
+
+    /// Returns the GUID of the address abstraction the namespace is accessed through, or `None` for labels before version 1.2.
+    pub fn address_abstraction_guid(&self) -> Option<EfiGuid> {
+        (self.label.len() > LABEL_SIZE_1_1).then(|| read_guid(self.label, 144))
+    }
+}
+
+/// Fletcher's 64-bit checksum over little endian 32-bit words, as used by the labels.
+struct Fletcher64 {
+    low: u32,
+    high: u32,
+}
+
+impl Fletcher64 {
+    const fn new() -> Self {
+        Self { low: 0, high: 0 }
+    }
+
+    /// Updates the checksum; Trailing bytes which don't form a whole word are ignored.
+    fn update(&mut self, data: &[u8]) {
+        for word in data.chunks(size_of::<u32>()) {
+            if let [a, b, c, d] = *word {
+                self.low = self.low.wrapping_add(u32::from_le_bytes([a, b, c, d]));
+                self.high = self.high.wrapping_add(self.low);
+            }
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        u64::from(self.high) << 32 | u64::from(self.low)
+    }
+}
+
+fn read_u16(data: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([data[offset], data[offset + 1]])
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    let mut bytes: [u8; 4] = [0; 4];
+
+    bytes.copy_from_slice(&data[offset..offset + 4]);
+
+    u32::from_le_bytes(bytes)
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    let mut bytes: [u8; 8] = [0; 8];
+
+    bytes.copy_from_slice(&data[offset..offset + 8]);
+
+    u64::from_le_bytes(bytes)
+}
+
+fn read_guid(data: &[u8], offset: usize) -> EfiGuid {
+    unsafe {
+        (data[offset..offset + size_of::<EfiGuid>()].as_ptr() as *const EfiGuid).read_unaligned()
+    }
+}
@@ -3,7 +3,8 @@ use crate::{
     protocols::console::{EfiSimpleTextInputProtocol, EfiSimpleTextOutputProtocol},
     runtime_services::EfiRuntimeServices,
     utilities::{string_from_raw, RawUtf16StringError},
-    EfiConfigurationTable, EfiConfigurationTableEntry, EfiHandle, EfiTableHeader, Void,
+    EfiConfigurationTable, EfiConfigurationTableEntry, EfiHandle, EfiRevision, EfiTableHeader,
+    Void,
 };
 
 #[repr(C)]
@@ -34,7 +35,7 @@ impl EfiSystemTable {
         &self.table_header
     }
 
-    pub fn revision(&self) -> u32 {
+    pub fn revision(&self) -> EfiRevision {
         self.table_header.revision()
     }
 
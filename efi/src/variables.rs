@@ -4,3 +4,33 @@ pub const EFI_BOOT_CURRENT_VARIABLE_NAME: &[u16] = &[
     'C' as u16, 'u' as u16, 'r' as u16, 'r' as u16,
     'e' as u16, 'n' as u16, 't' as u16, 0,
 ];
+
+#[rustfmt::skip]
+pub const EFI_OS_INDICATIONS_VARIABLE_NAME: &[u16] = &[
+    'O' as u16, 's' as u16, 'I' as u16, 'n' as u16,
+    'd' as u16, 'i' as u16, 'c' as u16, 'a' as u16,
+    't' as u16, 'i' as u16, 'o' as u16, 'n' as u16,
+    's' as u16, 0,
+];
+
+#[rustfmt::skip]
+pub const EFI_OS_INDICATIONS_SUPPORTED_VARIABLE_NAME: &[u16] = &[
+    'O' as u16, 's' as u16, 'I' as u16, 'n' as u16,
+    'd' as u16, 'i' as u16, 'c' as u16, 'a' as u16,
+    't' as u16, 'i' as u16, 'o' as u16, 'n' as u16,
+    's' as u16, 'S' as u16, 'u' as u16, 'p' as u16,
+    'p' as u16, 'o' as u16, 'r' as u16, 't' as u16,
+    'e' as u16, 'd' as u16, 0,
+];
+
+/// Bits of the `OsIndications` and `OsIndicationsSupported` variables.
+pub mod os_indications {
+    pub const BOOT_TO_FW_UI: u64 = 0x0001;
+    pub const TIMESTAMP_REVOCATION: u64 = 0x0002;
+    pub const FILE_CAPSULE_DELIVERY_SUPPORTED: u64 = 0x0004;
+    pub const FMP_CAPSULE_SUPPORTED: u64 = 0x0008;
+    pub const CAPSULE_RESULT_VAR_SUPPORTED: u64 = 0x0010;
+    pub const START_OS_RECOVERY: u64 = 0x0020;
+    pub const START_PLATFORM_RECOVERY: u64 = 0x0040;
+    pub const JSON_CONFIG_DATA_REFRESH: u64 = 0x0080;
+}
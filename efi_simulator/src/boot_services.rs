@@ -0,0 +1,1044 @@
+use {
+    crate::{
+        events::{
+            EventCheck, Notification, EFI_EVENT_GROUP_EXIT_BOOT_SERVICES,
+            EVT_SIGNAL_EXIT_BOOT_SERVICES, TPL_APPLICATION,
+        },
+        firmware::{dispatch_notifications, with_firmware, Firmware},
+        handles::{OpenProtocolInformationEntry, BY_REGISTER_NOTIFY},
+        memory::{MemoryDescriptor, MEMORY_DESCRIPTOR_SIZE, MEMORY_DESCRIPTOR_VERSION},
+        status::{
+            into_status, EFI_BUFFER_TOO_SMALL, EFI_INVALID_PARAMETER, EFI_NOT_FOUND, EFI_NOT_READY,
+            EFI_SUCCESS, EFI_UNSUPPORTED,
+        },
+        tables::{
+            ConfigurationTableEntry, SystemTable, TableHeader, EFI_BOOT_SERVICES_SIGNATURE,
+            EFI_SIMULATED_REVISION,
+        },
+    },
+    efi::{
+        boot_services::types::event_and_timer::EfiEventNotifyCallback, guids, EfiEvent, EfiGuid,
+        EfiHandle, EfiStatus, VoidMutPtr, VoidPtr,
+    },
+    std::{mem::size_of, slice::from_raw_parts},
+};
+
+/* Boot services handing out pool buffers allocate them as boot services data */
+pub(crate) const EFI_BOOT_SERVICES_DATA: u32 = 4;
+
+const EFI_NATIVE_INTERFACE: u32 = 0;
+
+const TEST_PROTOCOL: u32 = 0x04;
+
+/// Simulated `EFI_BOOT_SERVICES`, including the services up to UEFI 2.0.
+#[repr(C)]
+pub(crate) struct BootServicesTable {
+    header: TableHeader,
+
+    /*~~~~~~~~~~*/
+    raise_tpl: extern "efiapi" fn(usize) -> usize,
+    restore_tpl: extern "efiapi" fn(usize),
+
+    /*~~~~~~~~~~*/
+    allocate_pages: extern "efiapi" fn(u32, u32, usize, *mut u64) -> EfiStatus,
+    free_pages: extern "efiapi" fn(u64, usize) -> EfiStatus,
+    get_memory_map:
+        extern "efiapi" fn(*mut usize, *mut u8, *mut usize, *mut usize, *mut u32) -> EfiStatus,
+    allocate_pool: extern "efiapi" fn(u32, usize, *mut VoidPtr) -> EfiStatus,
+    free_pool: extern "efiapi" fn(VoidPtr) -> EfiStatus,
+
+    /*~~~~~~~~~~*/
+    create_event: extern "efiapi" fn(
+        u32,
+        usize,
+        Option<EfiEventNotifyCallback>,
+        VoidPtr,
+        *mut EfiEvent,
+    ) -> EfiStatus,
+    set_timer: extern "efiapi" fn(EfiEvent, u32, u64) -> EfiStatus,
+    wait_for_event: extern "efiapi" fn(usize, *const EfiEvent, *mut usize) -> EfiStatus,
+    signal_event: extern "efiapi" fn(EfiEvent) -> EfiStatus,
+    close_event: extern "efiapi" fn(EfiEvent) -> EfiStatus,
+    check_event: extern "efiapi" fn(EfiEvent) -> EfiStatus,
+
+    /*~~~~~~~~~~*/
+    install_protocol_interface:
+        extern "efiapi" fn(*mut EfiHandle, *const EfiGuid, u32, VoidPtr) -> EfiStatus,
+    reinstall_protocol_interface:
+        extern "efiapi" fn(EfiHandle, *const EfiGuid, VoidPtr, VoidPtr) -> EfiStatus,
+    uninstall_protocol_interface:
+        extern "efiapi" fn(EfiHandle, *const EfiGuid, VoidPtr) -> EfiStatus,
+    handle_protocol: extern "efiapi" fn(EfiHandle, *const EfiGuid, *mut VoidPtr) -> EfiStatus,
+    _reserved: VoidPtr,
+    register_protocol_notify:
+        extern "efiapi" fn(*const EfiGuid, EfiEvent, *mut VoidPtr) -> EfiStatus,
+    locate_handle:
+        extern "efiapi" fn(u32, *const EfiGuid, VoidPtr, *mut usize, *mut EfiHandle) -> EfiStatus,
+    locate_device_path:
+        extern "efiapi" fn(*const EfiGuid, *mut *const u8, *mut EfiHandle) -> EfiStatus,
+    install_configuration_table: extern "efiapi" fn(*const EfiGuid, VoidPtr) -> EfiStatus,
+
+    /*~~~~~~~~~~*/
+    load_image:
+        extern "efiapi" fn(bool, EfiHandle, VoidPtr, VoidPtr, usize, *mut EfiHandle) -> EfiStatus,
+    start_image: extern "efiapi" fn(EfiHandle, *mut usize, *mut *const u16) -> EfiStatus,
+    exit: extern "efiapi" fn(EfiHandle, EfiStatus, usize, *const u16) -> EfiStatus,
+    unload_image: extern "efiapi" fn(EfiHandle) -> EfiStatus,
+    exit_boot_services: extern "efiapi" fn(EfiHandle, usize) -> EfiStatus,
+
+    /*~~~~~~~~~~*/
+    get_next_monotonic_count: extern "efiapi" fn(*mut u64) -> EfiStatus,
+    stall: extern "efiapi" fn(usize) -> EfiStatus,
+    set_watchdog_timer: extern "efiapi" fn(usize, u64, usize, *const u16) -> EfiStatus,
+
+    /*~~~~~~~~~~*/
+    connect_controller: extern "efiapi" fn(EfiHandle, *const EfiHandle, VoidPtr, bool) -> EfiStatus,
+    disconnect_controller: extern "efiapi" fn(EfiHandle, EfiHandle, EfiHandle) -> EfiStatus,
+
+    /*~~~~~~~~~~*/
+    open_protocol: extern "efiapi" fn(
+        EfiHandle,
+        *const EfiGuid,
+        *mut VoidPtr,
+        EfiHandle,
+        EfiHandle,
+        u32,
+    ) -> EfiStatus,
+    close_protocol:
+        extern "efiapi" fn(EfiHandle, *const EfiGuid, EfiHandle, EfiHandle) -> EfiStatus,
+    open_protocol_information: extern "efiapi" fn(
+        EfiHandle,
+        *const EfiGuid,
+        *mut *const OpenProtocolInformationEntry,
+        *mut usize,
+    ) -> EfiStatus,
+
+    /*~~~~~~~~~~*/
+    protocols_per_handle:
+        extern "efiapi" fn(EfiHandle, *mut *const *const EfiGuid, *mut usize) -> EfiStatus,
+    locate_handle_buffer: extern "efiapi" fn(
+        u32,
+        *const EfiGuid,
+        VoidPtr,
+        *mut usize,
+        *mut *const EfiHandle,
+    ) -> EfiStatus,
+    locate_protocol: extern "efiapi" fn(*const EfiGuid, VoidPtr, *mut VoidPtr) -> EfiStatus,
+    /* Variadic functions can't be defined on stable ABIs, so they're left out */
+    install_multiple_protocol_interfaces: VoidPtr,
+    uninstall_multiple_protocol_interfaces: VoidPtr,
+
+    /*~~~~~~~~~~*/
+    calculate_crc32: extern "efiapi" fn(VoidPtr, usize, *mut u32) -> EfiStatus,
+
+    /*~~~~~~~~~~*/
+    copy_mem: extern "efiapi" fn(VoidMutPtr, VoidPtr, usize),
+    set_mem: extern "efiapi" fn(VoidMutPtr, usize, u8),
+
+    /*~~~~~~~~~~*/
+    create_event_ex: extern "efiapi" fn(
+        u32,
+        usize,
+        Option<EfiEventNotifyCallback>,
+        VoidPtr,
+        *const EfiGuid,
+        *mut EfiEvent,
+    ) -> EfiStatus,
+}
+
+impl BootServicesTable {
+    pub(crate) fn new() -> Box<Self> {
+        let mut table: Box<Self> = Box::new(Self {
+            header: TableHeader::new::<Self>(EFI_BOOT_SERVICES_SIGNATURE, EFI_SIMULATED_REVISION),
+            raise_tpl,
+            restore_tpl,
+            allocate_pages,
+            free_pages,
+            get_memory_map,
+            allocate_pool,
+            free_pool,
+            create_event,
+            set_timer,
+            wait_for_event,
+            signal_event,
+            close_event,
+            check_event,
+            install_protocol_interface,
+            reinstall_protocol_interface,
+            uninstall_protocol_interface,
+            handle_protocol,
+            _reserved: 0 as _,
+            register_protocol_notify,
+            locate_handle,
+            locate_device_path,
+            install_configuration_table,
+            load_image,
+            start_image,
+            exit,
+            unload_image,
+            exit_boot_services,
+            get_next_monotonic_count,
+            stall,
+            set_watchdog_timer,
+            connect_controller,
+            disconnect_controller,
+            open_protocol,
+            close_protocol,
+            open_protocol_information,
+            protocols_per_handle,
+            locate_handle_buffer,
+            locate_protocol,
+            install_multiple_protocol_interfaces: 0 as _,
+            uninstall_multiple_protocol_interfaces: 0 as _,
+            calculate_crc32,
+            copy_mem,
+            set_mem,
+            create_event_ex,
+        });
+
+        table.header.update_crc32();
+
+        table
+    }
+}
+
+/// Runs a service against the firmware and then any notification function it made ready.
+pub(crate) fn service<F: FnOnce(&mut Firmware) -> Result<(), EfiStatus>>(f: F) -> EfiStatus {
+    let status: EfiStatus = into_status(with_firmware(f));
+
+    dispatch_notifications();
+
+    status
+}
+
+pub(crate) fn signal_all(firmware: &mut Firmware, events: Vec<EfiEvent>) {
+    for event in events {
+        let _ = firmware.events.signal(event);
+    }
+}
+
+/* TASK PRIORITY */
+
+extern "efiapi" fn raise_tpl(new_tpl: usize) -> usize {
+    with_firmware(|firmware: &mut Firmware| firmware.events.set_tpl(new_tpl))
+}
+
+extern "efiapi" fn restore_tpl(old_tpl: usize) {
+    with_firmware(|firmware: &mut Firmware| firmware.events.set_tpl(old_tpl));
+
+    dispatch_notifications();
+}
+
+/* MEMORY */
+
+extern "efiapi" fn allocate_pages(
+    allocate_type: u32,
+    memory_type: u32,
+    pages: usize,
+    memory: *mut u64,
+) -> EfiStatus {
+    if memory.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    service(|firmware: &mut Firmware| {
+        let address: u64 =
+            firmware
+                .memory
+                .allocate_pages(allocate_type, memory_type, pages, unsafe { *memory })?;
+
+        unsafe { *memory = address };
+
+        Ok(())
+    })
+}
+
+extern "efiapi" fn free_pages(memory: u64, pages: usize) -> EfiStatus {
+    service(|firmware: &mut Firmware| firmware.memory.free_pages(memory, pages))
+}
+
+extern "efiapi" fn get_memory_map(
+    memory_map_size: *mut usize,
+    memory_map: *mut u8,
+    map_key: *mut usize,
+    descriptor_size: *mut usize,
+    descriptor_version: *mut u32,
+) -> EfiStatus {
+    if memory_map_size.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    service(|firmware: &mut Firmware| {
+        let descriptors: Vec<MemoryDescriptor> = firmware.memory.descriptors();
+        let required: usize = descriptors.len() * MEMORY_DESCRIPTOR_SIZE;
+
+        unsafe {
+            if !descriptor_size.is_null() {
+                *descriptor_size = MEMORY_DESCRIPTOR_SIZE;
+            }
+
+            if !descriptor_version.is_null() {
+                *descriptor_version = MEMORY_DESCRIPTOR_VERSION;
+            }
+
+            if *memory_map_size < required {
+                *memory_map_size = required;
+
+                return Err(EFI_BUFFER_TOO_SMALL);
+            }
+
+            if memory_map.is_null() || map_key.is_null() {
+                return Err(EFI_INVALID_PARAMETER);
+            }
+
+            memory_map.write_bytes(0, required);
+
+            for (index, descriptor) in descriptors.iter().enumerate() {
+                (memory_map.add(index * MEMORY_DESCRIPTOR_SIZE) as *mut MemoryDescriptor)
+                    .write_unaligned(*descriptor);
+            }
+
+            *memory_map_size = required;
+            *map_key = firmware.memory.map_key();
+        }
+
+        Ok(())
+    })
+}
+
+extern "efiapi" fn allocate_pool(memory_type: u32, size: usize, buffer: *mut VoidPtr) -> EfiStatus {
+    if buffer.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    service(|firmware: &mut Firmware| {
+        let allocation: *mut u8 = firmware.memory.allocate_pool(memory_type, size)?;
+
+        unsafe { *buffer = allocation as VoidPtr };
+
+        Ok(())
+    })
+}
+
+extern "efiapi" fn free_pool(buffer: VoidPtr) -> EfiStatus {
+    service(|firmware: &mut Firmware| firmware.memory.free_pool(buffer as *mut u8))
+}
+
+/* EVENT & TIMER */
+
+fn create(
+    event_type: u32,
+    tpl: usize,
+    notify: Option<EfiEventNotifyCallback>,
+    context: VoidPtr,
+    group: *const EfiGuid,
+    event: *mut EfiEvent,
+) -> EfiStatus {
+    if event.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    service(|firmware: &mut Firmware| {
+        let group: Option<EfiGuid> = if group.is_null() {
+            None
+        } else {
+            Some(unsafe { *group })
+        };
+
+        let created: EfiEvent = firmware
+            .events
+            .create(event_type, tpl, notify, context, group)?;
+
+        unsafe { *event = created };
+
+        Ok(())
+    })
+}
+
+extern "efiapi" fn create_event(
+    event_type: u32,
+    tpl: usize,
+    notify: Option<EfiEventNotifyCallback>,
+    context: VoidPtr,
+    event: *mut EfiEvent,
+) -> EfiStatus {
+    create(event_type, tpl, notify, context, 0 as _, event)
+}
+
+extern "efiapi" fn create_event_ex(
+    event_type: u32,
+    tpl: usize,
+    notify: Option<EfiEventNotifyCallback>,
+    context: VoidPtr,
+    group: *const EfiGuid,
+    event: *mut EfiEvent,
+) -> EfiStatus {
+    create(event_type, tpl, notify, context, group, event)
+}
+
+extern "efiapi" fn set_timer(event: EfiEvent, timer_type: u32, trigger_time: u64) -> EfiStatus {
+    service(|firmware: &mut Firmware| firmware.events.set_timer(event, timer_type, trigger_time))
+}
+
+/// Checks an event, running it's wait notification function outside the firmware if needed.
+fn check(event: EfiEvent) -> Result<(), EfiStatus> {
+    let notification: Notification =
+        match with_firmware(|firmware: &mut Firmware| firmware.events.check(event))? {
+            EventCheck::Signaled => return Ok(()),
+            EventCheck::NotReady => return Err(EFI_NOT_READY),
+            EventCheck::Notify(notification) => notification,
+        };
+
+    let (notify, event, context): Notification = notification;
+
+    notify(event, context);
+
+    with_firmware(|firmware: &mut Firmware| firmware.events.check_notified(event))
+}
+
+/// Blocks by advancing the virtual clock to the next timer.
+///
+/// Returns `EFI_NOT_READY` instead of hanging when no armed timer could ever signal the events.
+extern "efiapi" fn wait_for_event(
+    number_of_events: usize,
+    events: *const EfiEvent,
+    index: *mut usize,
+) -> EfiStatus {
+    if number_of_events == 0 || events.is_null() || index.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    if with_firmware(|firmware: &mut Firmware| firmware.events.tpl()) != TPL_APPLICATION {
+        return EFI_UNSUPPORTED;
+    }
+
+    let events: &[EfiEvent] = unsafe { from_raw_parts(events, number_of_events) };
+
+    loop {
+        for (event_index, &event) in events.iter().enumerate() {
+            match check(event) {
+                Err(EFI_NOT_READY) => {}
+                result => {
+                    unsafe { *index = event_index };
+
+                    return into_status(result);
+                }
+            }
+        }
+
+        match with_firmware(|firmware: &mut Firmware| firmware.events.next_deadline()) {
+            Some(ticks) => {
+                with_firmware(|firmware: &mut Firmware| firmware.events.advance(ticks.max(1)));
+
+                dispatch_notifications();
+            }
+            None => return EFI_NOT_READY,
+        }
+    }
+}
+
+extern "efiapi" fn signal_event(event: EfiEvent) -> EfiStatus {
+    service(|firmware: &mut Firmware| firmware.events.signal(event))
+}
+
+extern "efiapi" fn close_event(event: EfiEvent) -> EfiStatus {
+    service(|firmware: &mut Firmware| firmware.events.close(event))
+}
+
+extern "efiapi" fn check_event(event: EfiEvent) -> EfiStatus {
+    let status: EfiStatus = into_status(check(event));
+
+    dispatch_notifications();
+
+    status
+}
+
+/* PROTOCOL HANDLER */
+
+extern "efiapi" fn install_protocol_interface(
+    handle: *mut EfiHandle,
+    protocol: *const EfiGuid,
+    interface_type: u32,
+    interface: VoidPtr,
+) -> EfiStatus {
+    if handle.is_null() || protocol.is_null() || interface_type != EFI_NATIVE_INTERFACE {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    service(|firmware: &mut Firmware| {
+        let (installed, events): (EfiHandle, Vec<EfiEvent>) =
+            firmware
+                .handles
+                .install(unsafe { *handle }, unsafe { &*protocol }, interface)?;
+
+        unsafe { *handle = installed };
+
+        signal_all(firmware, events);
+
+        Ok(())
+    })
+}
+
+extern "efiapi" fn reinstall_protocol_interface(
+    handle: EfiHandle,
+    protocol: *const EfiGuid,
+    old_interface: VoidPtr,
+    new_interface: VoidPtr,
+) -> EfiStatus {
+    if protocol.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    service(|firmware: &mut Firmware| {
+        let events: Vec<EfiEvent> = firmware.handles.reinstall(
+            handle,
+            unsafe { &*protocol },
+            old_interface,
+            new_interface,
+        )?;
+
+        signal_all(firmware, events);
+
+        Ok(())
+    })
+}
+
+extern "efiapi" fn uninstall_protocol_interface(
+    handle: EfiHandle,
+    protocol: *const EfiGuid,
+    interface: VoidPtr,
+) -> EfiStatus {
+    if protocol.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    service(|firmware: &mut Firmware| {
+        firmware
+            .handles
+            .uninstall(handle, unsafe { &*protocol }, interface)
+    })
+}
+
+extern "efiapi" fn handle_protocol(
+    handle: EfiHandle,
+    protocol: *const EfiGuid,
+    interface: *mut VoidPtr,
+) -> EfiStatus {
+    if protocol.is_null() || interface.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    service(|firmware: &mut Firmware| {
+        let found: VoidPtr = firmware.handles.interface(handle, unsafe { &*protocol })?;
+
+        unsafe { *interface = found };
+
+        Ok(())
+    })
+}
+
+extern "efiapi" fn register_protocol_notify(
+    protocol: *const EfiGuid,
+    event: EfiEvent,
+    registration: *mut VoidPtr,
+) -> EfiStatus {
+    if protocol.is_null() || registration.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    service(|firmware: &mut Firmware| {
+        let key: VoidPtr = firmware
+            .handles
+            .register_notify(unsafe { &*protocol }, event);
+
+        unsafe { *registration = key };
+
+        Ok(())
+    })
+}
+
+/// Runs a handle search, consuming the registration's queued handle when searching by it.
+fn search(
+    firmware: &mut Firmware,
+    search_type: u32,
+    protocol: *const EfiGuid,
+    search_key: VoidPtr,
+) -> Result<Vec<EfiHandle>, EfiStatus> {
+    let protocol: Option<&EfiGuid> = unsafe { protocol.as_ref() };
+    let handles: Vec<EfiHandle> = firmware.handles.locate(search_type, protocol, search_key)?;
+
+    if search_type == BY_REGISTER_NOTIFY {
+        firmware.handles.consume_registration(search_key);
+    }
+
+    Ok(handles)
+}
+
+extern "efiapi" fn locate_handle(
+    search_type: u32,
+    protocol: *const EfiGuid,
+    search_key: VoidPtr,
+    buffer_size: *mut usize,
+    buffer: *mut EfiHandle,
+) -> EfiStatus {
+    if buffer_size.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    service(|firmware: &mut Firmware| {
+        let handles: Vec<EfiHandle> =
+            firmware
+                .handles
+                .locate(search_type, unsafe { protocol.as_ref() }, search_key)?;
+        let required: usize = handles.len() * size_of::<EfiHandle>();
+
+        unsafe {
+            if *buffer_size < required {
+                *buffer_size = required;
+
+                return Err(EFI_BUFFER_TOO_SMALL);
+            }
+
+            if buffer.is_null() {
+                return Err(EFI_INVALID_PARAMETER);
+            }
+
+            /* The registration only advances once the caller actually received the handle */
+            if search_type == BY_REGISTER_NOTIFY {
+                firmware.handles.consume_registration(search_key);
+            }
+
+            buffer.copy_from_nonoverlapping(handles.as_ptr(), handles.len());
+            *buffer_size = required;
+        }
+
+        Ok(())
+    })
+}
+
+/// Returns the size of a device path in bytes, without it's end node.
+pub(crate) unsafe fn device_path_size(device_path: *const u8) -> usize {
+    let mut size: usize = 0;
+
+    loop {
+        let node: *const u8 = device_path.add(size);
+
+        if *node == 0x7F && *node.add(1) == 0xFF {
+            break size;
+        }
+
+        size += usize::from(u16::from_le_bytes([*node.add(2), *node.add(3)])).max(4);
+    }
+}
+
+extern "efiapi" fn locate_device_path(
+    protocol: *const EfiGuid,
+    device_path: *mut *const u8,
+    device: *mut EfiHandle,
+) -> EfiStatus {
+    if protocol.is_null() || device_path.is_null() || unsafe { *device_path }.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    service(|firmware: &mut Firmware| {
+        let path: *const u8 = unsafe { *device_path };
+        let path_size: usize = unsafe { device_path_size(path) };
+        let path: &[u8] = unsafe { from_raw_parts(path, path_size) };
+
+        /* The handle which device path is the longest prefix of the given one wins */
+        let (handle, matched): (EfiHandle, usize) = firmware
+            .handles
+            .interfaces(&guids::EFI_DEVICE_PATH_PROTOCOL)
+            .into_iter()
+            .filter(|&(handle, _): &(EfiHandle, VoidPtr)| {
+                firmware
+                    .handles
+                    .interface(handle, unsafe { &*protocol })
+                    .is_ok()
+            })
+            .filter_map(|(handle, candidate): (EfiHandle, VoidPtr)| {
+                let candidate: *const u8 = candidate as *const u8;
+                let size: usize = unsafe { device_path_size(candidate) };
+
+                if size <= path_size && path[..size] == *unsafe { from_raw_parts(candidate, size) }
+                {
+                    Some((handle, size))
+                } else {
+                    None
+                }
+            })
+            .max_by_key(|&(_, size): &(EfiHandle, usize)| size)
+            .ok_or(EFI_NOT_FOUND)?;
+
+        if device.is_null() {
+            return Err(EFI_INVALID_PARAMETER);
+        }
+
+        unsafe {
+            *device = handle;
+            *device_path = (*device_path).add(matched);
+        }
+
+        Ok(())
+    })
+}
+
+extern "efiapi" fn install_configuration_table(guid: *const EfiGuid, table: VoidPtr) -> EfiStatus {
+    if guid.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    let guid: EfiGuid = unsafe { *guid };
+
+    service(|firmware: &mut Firmware| {
+        let existing: Option<usize> = firmware
+            .configuration_tables
+            .iter()
+            .position(|entry: &ConfigurationTableEntry| entry.guid == guid);
+
+        match (existing, table.is_null()) {
+            (Some(index), true) => {
+                firmware.configuration_tables.remove(index);
+            }
+            (None, true) => return Err(EFI_NOT_FOUND),
+            (Some(index), false) => firmware.configuration_tables[index].table = table,
+            (None, false) => firmware
+                .configuration_tables
+                .push(ConfigurationTableEntry { guid, table }),
+        }
+
+        firmware.update_configuration_tables();
+        firmware.events.signal_group(&guid);
+
+        Ok(())
+    })
+}
+
+/* IMAGE */
+
+/* Images can't be loaded on the host */
+
+extern "efiapi" fn load_image(
+    _: bool,
+    _: EfiHandle,
+    _: VoidPtr,
+    _: VoidPtr,
+    _: usize,
+    _: *mut EfiHandle,
+) -> EfiStatus {
+    EFI_UNSUPPORTED
+}
+
+extern "efiapi" fn start_image(_: EfiHandle, _: *mut usize, _: *mut *const u16) -> EfiStatus {
+    EFI_UNSUPPORTED
+}
+
+extern "efiapi" fn exit(_: EfiHandle, _: EfiStatus, _: usize, _: *const u16) -> EfiStatus {
+    EFI_UNSUPPORTED
+}
+
+extern "efiapi" fn unload_image(_: EfiHandle) -> EfiStatus {
+    EFI_UNSUPPORTED
+}
+
+/// Terminates boot services when the map key is current.
+///
+/// As on real firmware, the console and boot services pointers of the system table are cleared.
+extern "efiapi" fn exit_boot_services(_: EfiHandle, map_key: usize) -> EfiStatus {
+    let status: EfiStatus = with_firmware(|firmware: &mut Firmware| {
+        if firmware.boot_services_exited || map_key != firmware.memory.map_key() {
+            return EFI_INVALID_PARAMETER;
+        }
+
+        firmware.events.signal_type(EVT_SIGNAL_EXIT_BOOT_SERVICES);
+        firmware
+            .events
+            .signal_group(&EFI_EVENT_GROUP_EXIT_BOOT_SERVICES);
+
+        EFI_SUCCESS
+    });
+
+    if status != EFI_SUCCESS {
+        return status;
+    }
+
+    dispatch_notifications();
+
+    with_firmware(|firmware: &mut Firmware| {
+        let system_table: &mut SystemTable = unsafe { &mut *firmware.system_table };
+
+        firmware.boot_services_exited = true;
+        firmware.watchdog = None;
+
+        system_table.console_in_handle = 0 as _;
+        system_table.con_in = 0 as _;
+        system_table.console_out_handle = 0 as _;
+        system_table.con_out = 0 as _;
+        system_table.standard_error_handle = 0 as _;
+        system_table.std_err = 0 as _;
+        system_table.boot_services = 0 as _;
+        system_table.header.update_crc32();
+    });
+
+    EFI_SUCCESS
+}
+
+/* MISCELLANEOUS */
+
+extern "efiapi" fn get_next_monotonic_count(count: *mut u64) -> EfiStatus {
+    if count.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    with_firmware(|firmware: &mut Firmware| {
+        /* Only the low 32 bits count during boot, the high ones change on every boot */
+        let low: u64 = (firmware.monotonic_count as u32).wrapping_add(1).into();
+
+        firmware.monotonic_count = (firmware.monotonic_count & !0xFFFF_FFFF) | low;
+
+        unsafe { *count = firmware.monotonic_count };
+    });
+
+    EFI_SUCCESS
+}
+
+extern "efiapi" fn stall(microseconds: usize) -> EfiStatus {
+    with_firmware(|firmware: &mut Firmware| {
+        firmware
+            .events
+            .advance((microseconds as u64).saturating_mul(10))
+    });
+
+    dispatch_notifications();
+
+    EFI_SUCCESS
+}
+
+extern "efiapi" fn set_watchdog_timer(
+    timeout: usize,
+    _: u64,
+    _: usize,
+    _: *const u16,
+) -> EfiStatus {
+    with_firmware(|firmware: &mut Firmware| {
+        firmware.watchdog = if timeout == 0 { None } else { Some(timeout) };
+    });
+
+    EFI_SUCCESS
+}
+
+/* DRIVER SUPPORT */
+
+/* No drivers are simulated */
+
+extern "efiapi" fn connect_controller(
+    controller: EfiHandle,
+    _: *const EfiHandle,
+    _: VoidPtr,
+    _: bool,
+) -> EfiStatus {
+    if with_firmware(|firmware: &mut Firmware| firmware.handles.contains(controller)) {
+        EFI_NOT_FOUND
+    } else {
+        EFI_INVALID_PARAMETER
+    }
+}
+
+extern "efiapi" fn disconnect_controller(
+    controller: EfiHandle,
+    _: EfiHandle,
+    _: EfiHandle,
+) -> EfiStatus {
+    if with_firmware(|firmware: &mut Firmware| firmware.handles.contains(controller)) {
+        EFI_SUCCESS
+    } else {
+        EFI_INVALID_PARAMETER
+    }
+}
+
+/* OPEN & CLOSE PROTOCOL */
+
+extern "efiapi" fn open_protocol(
+    handle: EfiHandle,
+    protocol: *const EfiGuid,
+    interface: *mut VoidPtr,
+    agent_handle: EfiHandle,
+    controller_handle: EfiHandle,
+    attributes: u32,
+) -> EfiStatus {
+    if protocol.is_null() || (interface.is_null() && attributes != TEST_PROTOCOL) {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    service(|firmware: &mut Firmware| {
+        let opened: VoidPtr = firmware.handles.open(
+            handle,
+            unsafe { &*protocol },
+            agent_handle,
+            controller_handle,
+            attributes,
+        )?;
+
+        if attributes != TEST_PROTOCOL {
+            unsafe { *interface = opened };
+        }
+
+        Ok(())
+    })
+}
+
+extern "efiapi" fn close_protocol(
+    handle: EfiHandle,
+    protocol: *const EfiGuid,
+    agent_handle: EfiHandle,
+    controller_handle: EfiHandle,
+) -> EfiStatus {
+    if protocol.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    service(|firmware: &mut Firmware| {
+        firmware.handles.close(
+            handle,
+            unsafe { &*protocol },
+            agent_handle,
+            controller_handle,
+        )
+    })
+}
+
+extern "efiapi" fn open_protocol_information(
+    handle: EfiHandle,
+    protocol: *const EfiGuid,
+    entry_buffer: *mut *const OpenProtocolInformationEntry,
+    entry_count: *mut usize,
+) -> EfiStatus {
+    if protocol.is_null() || entry_buffer.is_null() || entry_count.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    service(|firmware: &mut Firmware| {
+        let entries: Vec<OpenProtocolInformationEntry> = firmware
+            .handles
+            .open_information(handle, unsafe { &*protocol })?;
+
+        let buffer: *mut OpenProtocolInformationEntry = firmware
+            .memory
+            .allocate_pool_from(EFI_BOOT_SERVICES_DATA, &entries)?;
+
+        unsafe {
+            *entry_buffer = buffer;
+            *entry_count = entries.len();
+        }
+
+        Ok(())
+    })
+}
+
+/* LIBRARY */
+
+extern "efiapi" fn protocols_per_handle(
+    handle: EfiHandle,
+    protocol_buffer: *mut *const *const EfiGuid,
+    protocol_buffer_count: *mut usize,
+) -> EfiStatus {
+    if protocol_buffer.is_null() || protocol_buffer_count.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    service(|firmware: &mut Firmware| {
+        let protocols: Vec<*const EfiGuid> = firmware.handles.protocols(handle)?;
+
+        let buffer: *mut *const EfiGuid = firmware
+            .memory
+            .allocate_pool_from(EFI_BOOT_SERVICES_DATA, &protocols)?;
+
+        unsafe {
+            *protocol_buffer = buffer;
+            *protocol_buffer_count = protocols.len();
+        }
+
+        Ok(())
+    })
+}
+
+extern "efiapi" fn locate_handle_buffer(
+    search_type: u32,
+    protocol: *const EfiGuid,
+    search_key: VoidPtr,
+    number_of_handles: *mut usize,
+    buffer: *mut *const EfiHandle,
+) -> EfiStatus {
+    if number_of_handles.is_null() || buffer.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    service(|firmware: &mut Firmware| {
+        let handles: Vec<EfiHandle> = search(firmware, search_type, protocol, search_key)?;
+
+        let allocation: *mut EfiHandle = firmware
+            .memory
+            .allocate_pool_from(EFI_BOOT_SERVICES_DATA, &handles)?;
+
+        unsafe {
+            *buffer = allocation;
+            *number_of_handles = handles.len();
+        }
+
+        Ok(())
+    })
+}
+
+extern "efiapi" fn locate_protocol(
+    protocol: *const EfiGuid,
+    registration: VoidPtr,
+    interface: *mut VoidPtr,
+) -> EfiStatus {
+    if protocol.is_null() || interface.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    service(|firmware: &mut Firmware| {
+        let found: Result<VoidPtr, EfiStatus> = firmware
+            .handles
+            .locate_protocol(unsafe { &*protocol }, registration);
+
+        unsafe { *interface = *found.as_ref().unwrap_or(&(0 as _)) };
+
+        found.map(|_| ())
+    })
+}
+
+/* 32-BIT CRC */
+
+extern "efiapi" fn calculate_crc32(data: VoidPtr, data_size: usize, crc32: *mut u32) -> EfiStatus {
+    if data.is_null() || data_size == 0 || crc32.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    let data: &[u8] = unsafe { from_raw_parts(data as *const u8, data_size) };
+
+    let crc: u32 = data.iter().fold(!0u32, |crc: u32, &byte: &u8| {
+        (0..8).fold(crc ^ u32::from(byte), |crc: u32, _| {
+            if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            }
+        })
+    });
+
+    unsafe { *crc32 = !crc };
+
+    EFI_SUCCESS
+}
+
+/* MISCELLANEOUS */
+
+extern "efiapi" fn copy_mem(destination: VoidMutPtr, source: VoidPtr, length: usize) {
+    unsafe { (destination as *mut u8).copy_from(source as *const u8, length) };
+}
+
+extern "efiapi" fn set_mem(buffer: VoidMutPtr, size: usize, value: u8) {
+    unsafe { (buffer as *mut u8).write_bytes(value, size) };
+}
@@ -0,0 +1,357 @@
+use {
+    crate::{
+        firmware::with_firmware,
+        status::{into_status, EFI_INVALID_PARAMETER, EFI_NOT_READY, EFI_SUCCESS, EFI_UNSUPPORTED},
+    },
+    efi::{utilities::string_length, EfiEvent, EfiStatus, VoidPtr},
+    std::{cell::UnsafeCell, char::decode_utf16, collections::VecDeque, slice::from_raw_parts},
+};
+
+/* Text modes as (columns, rows) */
+const MODES: [(usize, usize); 2] = [(80, 25), (80, 50)];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ConsoleStream {
+    Output,
+    Error,
+}
+
+/// Captured console output and the queue of keys waiting to be read.
+#[derive(Default)]
+pub(crate) struct Console {
+    pub(crate) output: String,
+    pub(crate) error: String,
+    pub(crate) keys: VecDeque<(u16, u16)>,
+}
+
+impl Console {
+    fn stream_mut(&mut self, stream: ConsoleStream) -> &mut String {
+        match stream {
+            ConsoleStream::Output => &mut self.output,
+            ConsoleStream::Error => &mut self.error,
+        }
+    }
+}
+
+#[repr(C)]
+struct TextOutputMode {
+    max_mode: i32,
+    mode: i32,
+    attribute: i32,
+    cursor_column: i32,
+    cursor_row: i32,
+    cursor_visible: bool,
+}
+
+/// Simulated `EFI_SIMPLE_TEXT_OUTPUT_PROTOCOL` capturing everything written to it.
+///
+/// Besides the written text, the protocol keeps the displayed characters with their attributes,
+/// which scroll up when the cursor moves past the last row.
+#[repr(C)]
+pub(crate) struct TextOutput {
+    reset: extern "efiapi" fn(*const Self, bool) -> EfiStatus,
+    output_string: extern "efiapi" fn(*const Self, *const u16) -> EfiStatus,
+    test_string: extern "efiapi" fn(*const Self, *const u16) -> EfiStatus,
+    query_mode: extern "efiapi" fn(*const Self, usize, *mut usize, *mut usize) -> EfiStatus,
+    set_mode: extern "efiapi" fn(*const Self, usize) -> EfiStatus,
+    set_attribute: extern "efiapi" fn(*const Self, usize) -> EfiStatus,
+    clear_screen: extern "efiapi" fn(*const Self) -> EfiStatus,
+    set_cursor_position: extern "efiapi" fn(*const Self, usize, usize) -> EfiStatus,
+    enable_cursor: extern "efiapi" fn(*const Self, bool) -> EfiStatus,
+    mode: *const TextOutputMode,
+    stream: ConsoleStream,
+    mode_data: UnsafeCell<TextOutputMode>,
+    cells: UnsafeCell<Vec<(char, u8)>>,
+}
+
+impl TextOutput {
+    pub(crate) fn new(stream: ConsoleStream) -> Box<Self> {
+        let mut output: Box<Self> = Box::new(Self {
+            reset: text_output_reset,
+            output_string: text_output_output_string,
+            test_string: text_output_test_string,
+            query_mode: text_output_query_mode,
+            set_mode: text_output_set_mode,
+            set_attribute: text_output_set_attribute,
+            clear_screen: text_output_clear_screen,
+            set_cursor_position: text_output_set_cursor_position,
+            enable_cursor: text_output_enable_cursor,
+            mode: 0 as _,
+            stream,
+            mode_data: UnsafeCell::new(TextOutputMode {
+                max_mode: MODES.len() as i32,
+                mode: 0,
+                attribute: 0x07,
+                cursor_column: 0,
+                cursor_row: 0,
+                cursor_visible: true,
+            }),
+            cells: UnsafeCell::new(vec![(' ', 0x07); MODES[0].0 * MODES[0].1]),
+        });
+
+        output.mode = output.mode_data.get();
+
+        output
+    }
+
+    fn mode_data(&self) -> *mut TextOutputMode {
+        self.mode_data.get()
+    }
+
+    fn dimensions(&self) -> (usize, usize) {
+        MODES[unsafe { &*self.mode_data() }.mode as usize]
+    }
+
+    fn cells(&self) -> *mut Vec<(char, u8)> {
+        self.cells.get()
+    }
+
+    /// Returns the displayed rows, without trailing spaces.
+    pub(crate) fn screen(&self) -> Vec<String> {
+        let (columns, _): (usize, usize) = self.dimensions();
+
+        unsafe { &*self.cells() }
+            .chunks(columns)
+            .map(|row: &[(char, u8)]| {
+                row.iter()
+                    .map(|&(character, _): &(char, u8)| character)
+                    .collect::<String>()
+                    .trim_end()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    /// Returns the attribute of the displayed cell, if it's within the screen.
+    pub(crate) fn attribute(&self, column: usize, row: usize) -> Option<u8> {
+        let (columns, rows): (usize, usize) = self.dimensions();
+
+        if column < columns && row < rows {
+            Some(unsafe { &*self.cells() }[row * columns + column].1)
+        } else {
+            None
+        }
+    }
+
+    /// Fills the screen with spaces of the current attribute.
+    fn clear(&self) {
+        let (columns, rows): (usize, usize) = self.dimensions();
+        let attribute: u8 = unsafe { &*self.mode_data() }.attribute as u8;
+
+        unsafe { *self.cells() = vec![(' ', attribute); columns * rows] };
+    }
+
+    /// Moves the cursor to the next row, scrolling the screen up when it's on the last one.
+    fn next_row(&self) {
+        let (columns, rows): (usize, usize) = self.dimensions();
+        let mode: &mut TextOutputMode = unsafe { &mut *self.mode_data() };
+
+        if mode.cursor_row as usize + 1 < rows {
+            mode.cursor_row += 1;
+        } else {
+            let cells: &mut Vec<(char, u8)> = unsafe { &mut *self.cells() };
+
+            cells.drain(..columns);
+            cells.resize(columns * rows, (' ', mode.attribute as u8));
+        }
+    }
+
+    fn write_character(&self, character: char) {
+        let (columns, _): (usize, usize) = self.dimensions();
+        let mode: &mut TextOutputMode = unsafe { &mut *self.mode_data() };
+
+        match character {
+            '\r' => mode.cursor_column = 0,
+            '\n' => self.next_row(),
+            '\u{8}' => mode.cursor_column = (mode.cursor_column - 1).max(0),
+            _ => {
+                let cells: &mut Vec<(char, u8)> = unsafe { &mut *self.cells() };
+
+                cells[mode.cursor_row as usize * columns + mode.cursor_column as usize] =
+                    (character, mode.attribute as u8);
+
+                mode.cursor_column += 1;
+
+                if mode.cursor_column as usize >= columns {
+                    mode.cursor_column = 0;
+
+                    self.next_row();
+                }
+            }
+        }
+    }
+}
+
+extern "efiapi" fn text_output_reset(this: *const TextOutput, _: bool) -> EfiStatus {
+    text_output_clear_screen(this)
+}
+
+extern "efiapi" fn text_output_output_string(
+    this: *const TextOutput,
+    string: *const u16,
+) -> EfiStatus {
+    let this: &TextOutput = unsafe { &*this };
+
+    let string: &[u16] = match unsafe { string_length(string) } {
+        Ok(length) => unsafe { from_raw_parts(string, length) },
+        Err(_) => return EFI_INVALID_PARAMETER,
+    };
+
+    let text: String = decode_utf16(string.iter().cloned())
+        .map(|character| character.unwrap_or(char::REPLACEMENT_CHARACTER))
+        .inspect(|&character: &char| this.write_character(character))
+        .collect();
+
+    with_firmware(|firmware| firmware.console.stream_mut(this.stream).push_str(&text));
+
+    EFI_SUCCESS
+}
+
+extern "efiapi" fn text_output_test_string(_: *const TextOutput, string: *const u16) -> EfiStatus {
+    match unsafe { string_length(string) } {
+        Ok(_) => EFI_SUCCESS,
+        Err(_) => EFI_INVALID_PARAMETER,
+    }
+}
+
+extern "efiapi" fn text_output_query_mode(
+    _: *const TextOutput,
+    mode_number: usize,
+    columns: *mut usize,
+    rows: *mut usize,
+) -> EfiStatus {
+    match MODES.get(mode_number) {
+        Some(&(mode_columns, mode_rows)) if !columns.is_null() && !rows.is_null() => {
+            unsafe {
+                *columns = mode_columns;
+                *rows = mode_rows;
+            }
+
+            EFI_SUCCESS
+        }
+        Some(_) => EFI_INVALID_PARAMETER,
+        None => EFI_UNSUPPORTED,
+    }
+}
+
+extern "efiapi" fn text_output_set_mode(this: *const TextOutput, mode_number: usize) -> EfiStatus {
+    if mode_number >= MODES.len() {
+        return EFI_UNSUPPORTED;
+    }
+
+    unsafe { (*(*this).mode_data()).mode = mode_number as i32 };
+
+    text_output_clear_screen(this)
+}
+
+extern "efiapi" fn text_output_set_attribute(
+    this: *const TextOutput,
+    attribute: usize,
+) -> EfiStatus {
+    if attribute > 0x7F {
+        return EFI_UNSUPPORTED;
+    }
+
+    unsafe { (*(*this).mode_data()).attribute = attribute as i32 };
+
+    EFI_SUCCESS
+}
+
+extern "efiapi" fn text_output_clear_screen(this: *const TextOutput) -> EfiStatus {
+    unsafe { (*this).clear() };
+
+    let mode: &mut TextOutputMode = unsafe { &mut *(*this).mode_data() };
+
+    mode.cursor_column = 0;
+    mode.cursor_row = 0;
+
+    EFI_SUCCESS
+}
+
+extern "efiapi" fn text_output_set_cursor_position(
+    this: *const TextOutput,
+    column: usize,
+    row: usize,
+) -> EfiStatus {
+    let this: &TextOutput = unsafe { &*this };
+    let (columns, rows): (usize, usize) = this.dimensions();
+
+    if column >= columns || row >= rows {
+        return EFI_UNSUPPORTED;
+    }
+
+    let mode: &mut TextOutputMode = unsafe { &mut *this.mode_data() };
+
+    mode.cursor_column = column as i32;
+    mode.cursor_row = row as i32;
+
+    EFI_SUCCESS
+}
+
+extern "efiapi" fn text_output_enable_cursor(this: *const TextOutput, visible: bool) -> EfiStatus {
+    unsafe { (*(*this).mode_data()).cursor_visible = visible };
+
+    EFI_SUCCESS
+}
+
+#[repr(C)]
+struct InputKey {
+    scan_code: u16,
+    unicode_char: u16,
+}
+
+/// Simulated `EFI_SIMPLE_TEXT_INPUT_PROTOCOL` reading keys queued by the test.
+#[repr(C)]
+pub(crate) struct TextInput {
+    reset: extern "efiapi" fn(*const Self, bool) -> EfiStatus,
+    read_key_stroke: extern "efiapi" fn(*const Self, *mut InputKey) -> EfiStatus,
+    wait_for_key: EfiEvent,
+}
+
+impl TextInput {
+    pub(crate) fn new(wait_for_key: EfiEvent) -> Box<Self> {
+        Box::new(Self {
+            reset: text_input_reset,
+            read_key_stroke: text_input_read_key_stroke,
+            wait_for_key,
+        })
+    }
+}
+
+/// Notification function of the `WaitForKey` event.
+pub(crate) extern "efiapi" fn text_input_wait_for_key(event: EfiEvent, _: VoidPtr) {
+    with_firmware(|firmware| {
+        if !firmware.console.keys.is_empty() {
+            let _ = firmware.events.signal(event);
+        }
+    });
+}
+
+extern "efiapi" fn text_input_reset(_: *const TextInput, _: bool) -> EfiStatus {
+    with_firmware(|firmware| firmware.console.keys.clear());
+
+    EFI_SUCCESS
+}
+
+extern "efiapi" fn text_input_read_key_stroke(
+    _: *const TextInput,
+    key: *mut InputKey,
+) -> EfiStatus {
+    if key.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    into_status(with_firmware(|firmware| {
+        let (scan_code, unicode_char): (u16, u16) =
+            firmware.console.keys.pop_front().ok_or(EFI_NOT_READY)?;
+
+        unsafe {
+            *key = InputKey {
+                scan_code,
+                unicode_char,
+            }
+        };
+
+        Ok(())
+    }))
+}
@@ -0,0 +1,770 @@
+use {
+    crate::{
+        boot_services::service,
+        events::{EventTable, EVT_NOTIFY_SIGNAL, EVT_TIMER, TIMER_RELATIVE, TPL_NOTIFY},
+        firmware::{with_firmware, Firmware},
+        status::{
+            EFI_ABORTED, EFI_BAD_BUFFER_SIZE, EFI_DEVICE_ERROR, EFI_INVALID_PARAMETER,
+            EFI_MEDIA_CHANGED, EFI_SUCCESS, EFI_WRITE_PROTECTED,
+        },
+    },
+    efi::{EfiEvent, EfiGuid, EfiLBA, EfiStatus, VoidMutPtr, VoidPtr},
+    std::{
+        fs::{File, OpenOptions},
+        io,
+        os::unix::fs::FileExt,
+        path::PathBuf,
+        ptr::copy_nonoverlapping,
+        slice::{from_raw_parts, from_raw_parts_mut},
+    },
+};
+
+const BLOCK_IO_REVISION: u64 = 0x2001F;
+const DISK_IO_REVISION: u64 = 0x10000;
+const DISK_IO2_REVISION: u64 = 0x20000;
+
+/// Time in 100ns units it takes an asynchronous IO 2 request to complete.
+const IO2_LATENCY: u64 = 1000;
+
+/* Vendor GUID of the simulator's device path nodes */
+const SIMULATED_DISK_VENDOR: EfiGuid = EfiGuid::from_tuple((
+    0x2A5B_7C61,
+    0x8E0F,
+    0x4D3C,
+    [0x9B, 0x47, 0x1E, 0x5D, 0x60, 0xC2, 0xA8, 0x13],
+));
+
+/// A disk image on the host exposed through Block IO, Disk IO and their IO 2 counterparts.
+#[derive(Clone)]
+pub struct SimulatedDisk {
+    path: PathBuf,
+    block_size: u32,
+    read_only: bool,
+    removable: bool,
+}
+
+impl SimulatedDisk {
+    /// Creates a writable, non-removable disk with 512 byte blocks.
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Self {
+            path: path.into(),
+            block_size: 512,
+            read_only: false,
+            removable: false,
+        }
+    }
+
+    pub fn block_size(mut self, block_size: u32) -> Self {
+        self.block_size = block_size;
+
+        self
+    }
+
+    pub fn read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+
+        self
+    }
+
+    pub fn removable(mut self, removable: bool) -> Self {
+        self.removable = removable;
+
+        self
+    }
+}
+
+#[repr(C)]
+struct BlockIoMedia {
+    media_id: u32,
+    removable_media: bool,
+    media_present: bool,
+    logical_partition: bool,
+    read_only: bool,
+    write_caching: bool,
+    block_size: u32,
+    io_alignment: u32,
+    last_block: EfiLBA,
+    lowest_aligned_lba: EfiLBA,
+    logical_blocks_per_physical_block: u32,
+    optimal_transfer_length_granularity: u32,
+}
+
+/// Backing store of a simulated disk.
+enum Storage {
+    /// Disk image on the host.
+    File(File),
+    /// RAM disk, holding the image in the simulated program's memory.
+    Memory(*mut u8),
+}
+
+impl Storage {
+    fn read(&self, offset: u64, buffer: &mut [u8]) -> io::Result<()> {
+        match self {
+            Self::File(file) => file.read_exact_at(buffer, offset),
+            Self::Memory(base) => {
+                unsafe {
+                    copy_nonoverlapping(
+                        base.add(offset as usize),
+                        buffer.as_mut_ptr(),
+                        buffer.len(),
+                    )
+                };
+
+                Ok(())
+            }
+        }
+    }
+
+    fn write(&self, offset: u64, buffer: &[u8]) -> io::Result<()> {
+        match self {
+            Self::File(file) => file.write_all_at(buffer, offset),
+            Self::Memory(base) => {
+                unsafe {
+                    copy_nonoverlapping(buffer.as_ptr(), base.add(offset as usize), buffer.len())
+                };
+
+                Ok(())
+            }
+        }
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        match self {
+            Self::File(file) => file.sync_data(),
+            Self::Memory(_) => Ok(()),
+        }
+    }
+}
+
+/// Simulated `EFI_BLOCK_IO_PROTOCOL` followed by the state backing it.
+#[repr(C)]
+pub(crate) struct BlockIo {
+    revision: u64,
+    media: *const BlockIoMedia,
+    reset: extern "efiapi" fn(*const Self, bool) -> EfiStatus,
+    read_blocks: extern "efiapi" fn(*const Self, u32, EfiLBA, usize, VoidMutPtr) -> EfiStatus,
+    write_blocks: extern "efiapi" fn(*const Self, u32, EfiLBA, usize, VoidPtr) -> EfiStatus,
+    flush_blocks: extern "efiapi" fn(*const Self) -> EfiStatus,
+    media_data: BlockIoMedia,
+    storage: Storage,
+}
+
+impl BlockIo {
+    fn media(&self) -> &BlockIoMedia {
+        &self.media_data
+    }
+
+    fn size(&self) -> u64 {
+        (self.media_data.last_block + 1) * u64::from(self.media_data.block_size)
+    }
+
+    /// Validates a block transfer and returns it's byte offset.
+    fn block_offset(&self, media_id: u32, lba: EfiLBA, size: usize) -> Result<u64, EfiStatus> {
+        let media: &BlockIoMedia = self.media();
+
+        if media_id != media.media_id {
+            return Err(EFI_MEDIA_CHANGED);
+        }
+
+        /* Block sizes are powers of two */
+        if size & (media.block_size as usize - 1) != 0 {
+            return Err(EFI_BAD_BUFFER_SIZE);
+        }
+
+        let blocks: u64 = (size / media.block_size as usize) as u64;
+
+        if lba > media.last_block || lba + blocks > media.last_block + 1 {
+            return Err(EFI_INVALID_PARAMETER);
+        }
+
+        Ok(lba * u64::from(media.block_size))
+    }
+}
+
+extern "efiapi" fn block_io_reset(_: *const BlockIo, _: bool) -> EfiStatus {
+    EFI_SUCCESS
+}
+
+extern "efiapi" fn block_io_read_blocks(
+    this: *const BlockIo,
+    media_id: u32,
+    lba: EfiLBA,
+    size: usize,
+    buffer: VoidMutPtr,
+) -> EfiStatus {
+    let this: &BlockIo = unsafe { &*this };
+
+    if buffer.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    match this.block_offset(media_id, lba, size) {
+        Ok(offset) => read(&this.storage, offset, buffer, size),
+        Err(status) => status,
+    }
+}
+
+extern "efiapi" fn block_io_write_blocks(
+    this: *const BlockIo,
+    media_id: u32,
+    lba: EfiLBA,
+    size: usize,
+    buffer: VoidPtr,
+) -> EfiStatus {
+    let this: &BlockIo = unsafe { &*this };
+
+    if this.media().read_only {
+        return EFI_WRITE_PROTECTED;
+    }
+
+    if buffer.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    match this.block_offset(media_id, lba, size) {
+        Ok(offset) => write(&this.storage, offset, buffer, size),
+        Err(status) => status,
+    }
+}
+
+extern "efiapi" fn block_io_flush_blocks(this: *const BlockIo) -> EfiStatus {
+    flush(unsafe { &*this })
+}
+
+/// Simulated `EFI_DISK_IO_PROTOCOL` forwarding to the disk's Block IO state.
+#[repr(C)]
+pub(crate) struct DiskIo {
+    revision: u64,
+    read_disk: extern "efiapi" fn(*const Self, u32, u64, usize, VoidMutPtr) -> EfiStatus,
+    write_disk: extern "efiapi" fn(*const Self, u32, u64, usize, VoidPtr) -> EfiStatus,
+    block_io: *const BlockIo,
+}
+
+impl DiskIo {
+    fn block_io(&self) -> &BlockIo {
+        unsafe { &*self.block_io }
+    }
+
+    fn validate(&self, media_id: u32, offset: u64, size: usize) -> Result<(), EfiStatus> {
+        let block_io: &BlockIo = self.block_io();
+
+        if media_id != block_io.media().media_id {
+            Err(EFI_MEDIA_CHANGED)
+        } else if offset
+            .checked_add(size as u64)
+            .filter(|&end: &u64| end <= block_io.size())
+            .is_none()
+        {
+            Err(EFI_INVALID_PARAMETER)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+extern "efiapi" fn disk_io_read_disk(
+    this: *const DiskIo,
+    media_id: u32,
+    offset: u64,
+    size: usize,
+    buffer: VoidMutPtr,
+) -> EfiStatus {
+    let this: &DiskIo = unsafe { &*this };
+
+    if size != 0 && buffer.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    match this.validate(media_id, offset, size) {
+        Ok(()) => read(&this.block_io().storage, offset, buffer, size),
+        Err(status) => status,
+    }
+}
+
+extern "efiapi" fn disk_io_write_disk(
+    this: *const DiskIo,
+    media_id: u32,
+    offset: u64,
+    size: usize,
+    buffer: VoidPtr,
+) -> EfiStatus {
+    let this: &DiskIo = unsafe { &*this };
+
+    if this.block_io().media().read_only {
+        return EFI_WRITE_PROTECTED;
+    }
+
+    if size != 0 && buffer.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    match this.validate(media_id, offset, size) {
+        Ok(()) => write(&this.block_io().storage, offset, buffer, size),
+        Err(status) => status,
+    }
+}
+
+/// Token passed to IO 2 requests.
+#[repr(C)]
+pub(crate) struct Io2Token {
+    event: EfiEvent,
+    transaction_status: EfiStatus,
+}
+
+enum Transfer {
+    Read(u64, VoidMutPtr, usize),
+    Write(u64, VoidPtr, usize),
+    Flush,
+}
+
+impl Transfer {
+    fn perform(&self, block_io: &BlockIo) -> EfiStatus {
+        match *self {
+            Self::Read(offset, buffer, size) => read(&block_io.storage, offset, buffer, size),
+            Self::Write(offset, buffer, size) => write(&block_io.storage, offset, buffer, size),
+            Self::Flush => flush(block_io),
+        }
+    }
+}
+
+/// An asynchronous IO 2 request, performed once it's timer expires.
+pub(crate) struct Io2Request {
+    timer: EfiEvent,
+    block_io: *const BlockIo,
+    token: *mut Io2Token,
+    transfer: Transfer,
+}
+
+impl Io2Request {
+    fn complete(&self, events: &mut EventTable, status: EfiStatus) {
+        let token: &mut Io2Token = unsafe { &mut *self.token };
+
+        token.transaction_status = status;
+
+        let _ = events.signal(token.event);
+    }
+}
+
+/// Queues the transfer or, when the token has no event, performs it synchronously.
+fn submit(block_io: *const BlockIo, token: *mut Io2Token, transfer: Transfer) -> EfiStatus {
+    if token.is_null() || unsafe { (*token).event.is_null() } {
+        let status: EfiStatus = transfer.perform(unsafe { &*block_io });
+
+        if !token.is_null() {
+            unsafe { (*token).transaction_status = status };
+        }
+
+        return status;
+    }
+
+    service(|firmware: &mut Firmware| {
+        let timer: EfiEvent = firmware.events.create(
+            EVT_TIMER | EVT_NOTIFY_SIGNAL,
+            TPL_NOTIFY,
+            Some(io2_complete),
+            0 as _,
+            None,
+        )?;
+
+        firmware
+            .events
+            .set_timer(timer, TIMER_RELATIVE, IO2_LATENCY)?;
+
+        firmware.io2_requests.push(Io2Request {
+            timer,
+            block_io,
+            token,
+            transfer,
+        });
+
+        Ok(())
+    })
+}
+
+extern "efiapi" fn io2_complete(timer: EfiEvent, _: VoidPtr) {
+    let request: Option<Io2Request> = with_firmware(|firmware: &mut Firmware| {
+        let _ = firmware.events.close(timer);
+
+        firmware
+            .io2_requests
+            .iter()
+            .position(|request: &Io2Request| request.timer == timer)
+            .map(|index: usize| firmware.io2_requests.remove(index))
+    });
+
+    /* The transfer doesn't need the firmware, which could be a RAM disk in the simulated program's memory */
+    if let Some(request) = request {
+        let status: EfiStatus = request.transfer.perform(unsafe { &*request.block_io });
+
+        with_firmware(|firmware: &mut Firmware| request.complete(&mut firmware.events, status));
+    }
+}
+
+/// Completes the disk's outstanding IO 2 requests with `EFI_ABORTED`.
+pub(crate) fn abort_requests(firmware: &mut Firmware, block_io: *const BlockIo) {
+    let (aborted, outstanding): (Vec<Io2Request>, Vec<Io2Request>) = firmware
+        .io2_requests
+        .drain(..)
+        .partition(|request: &Io2Request| request.block_io == block_io);
+
+    firmware.io2_requests = outstanding;
+
+    for request in aborted {
+        let _ = firmware.events.close(request.timer);
+
+        request.complete(&mut firmware.events, EFI_ABORTED);
+    }
+}
+
+/// Simulated `EFI_BLOCK_IO2_PROTOCOL` queueing requests on the disk's Block IO state.
+#[repr(C)]
+pub(crate) struct BlockIo2 {
+    media: *const BlockIoMedia,
+    reset: extern "efiapi" fn(*const Self, bool) -> EfiStatus,
+    read_blocks_ex:
+        extern "efiapi" fn(*const Self, u32, EfiLBA, *mut Io2Token, usize, VoidMutPtr) -> EfiStatus,
+    write_blocks_ex:
+        extern "efiapi" fn(*const Self, u32, EfiLBA, *mut Io2Token, usize, VoidPtr) -> EfiStatus,
+    flush_blocks_ex: extern "efiapi" fn(*const Self, *mut Io2Token) -> EfiStatus,
+    block_io: *const BlockIo,
+}
+
+impl BlockIo2 {
+    fn block_io(&self) -> &BlockIo {
+        unsafe { &*self.block_io }
+    }
+}
+
+extern "efiapi" fn block_io2_reset(this: *const BlockIo2, _: bool) -> EfiStatus {
+    let block_io: *const BlockIo = unsafe { (*this).block_io };
+
+    service(|firmware: &mut Firmware| {
+        abort_requests(firmware, block_io);
+
+        Ok(())
+    })
+}
+
+extern "efiapi" fn block_io2_read_blocks_ex(
+    this: *const BlockIo2,
+    media_id: u32,
+    lba: EfiLBA,
+    token: *mut Io2Token,
+    size: usize,
+    buffer: VoidMutPtr,
+) -> EfiStatus {
+    let this: &BlockIo2 = unsafe { &*this };
+
+    if buffer.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    match this.block_io().block_offset(media_id, lba, size) {
+        Ok(offset) => submit(this.block_io, token, Transfer::Read(offset, buffer, size)),
+        Err(status) => status,
+    }
+}
+
+extern "efiapi" fn block_io2_write_blocks_ex(
+    this: *const BlockIo2,
+    media_id: u32,
+    lba: EfiLBA,
+    token: *mut Io2Token,
+    size: usize,
+    buffer: VoidPtr,
+) -> EfiStatus {
+    let this: &BlockIo2 = unsafe { &*this };
+
+    if this.block_io().media().read_only {
+        return EFI_WRITE_PROTECTED;
+    }
+
+    if buffer.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    match this.block_io().block_offset(media_id, lba, size) {
+        Ok(offset) => submit(this.block_io, token, Transfer::Write(offset, buffer, size)),
+        Err(status) => status,
+    }
+}
+
+extern "efiapi" fn block_io2_flush_blocks_ex(
+    this: *const BlockIo2,
+    token: *mut Io2Token,
+) -> EfiStatus {
+    submit(unsafe { (*this).block_io }, token, Transfer::Flush)
+}
+
+/// Simulated `EFI_DISK_IO2_PROTOCOL` queueing requests on the disk's Block IO state.
+#[repr(C)]
+pub(crate) struct DiskIo2 {
+    revision: u64,
+    cancel: extern "efiapi" fn(*const Self) -> EfiStatus,
+    read_disk_ex:
+        extern "efiapi" fn(*const Self, u32, u64, *mut Io2Token, usize, VoidMutPtr) -> EfiStatus,
+    write_disk_ex:
+        extern "efiapi" fn(*const Self, u32, u64, *mut Io2Token, usize, VoidPtr) -> EfiStatus,
+    flush_disk_ex: extern "efiapi" fn(*const Self, *mut Io2Token) -> EfiStatus,
+    disk_io: *const DiskIo,
+}
+
+impl DiskIo2 {
+    fn disk_io(&self) -> &DiskIo {
+        unsafe { &*self.disk_io }
+    }
+}
+
+extern "efiapi" fn disk_io2_cancel(this: *const DiskIo2) -> EfiStatus {
+    let block_io: *const BlockIo = unsafe { (*this).disk_io().block_io };
+
+    service(|firmware: &mut Firmware| {
+        abort_requests(firmware, block_io);
+
+        Ok(())
+    })
+}
+
+extern "efiapi" fn disk_io2_read_disk_ex(
+    this: *const DiskIo2,
+    media_id: u32,
+    offset: u64,
+    token: *mut Io2Token,
+    size: usize,
+    buffer: VoidMutPtr,
+) -> EfiStatus {
+    let this: &DiskIo2 = unsafe { &*this };
+
+    if size != 0 && buffer.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    match this.disk_io().validate(media_id, offset, size) {
+        Ok(()) => submit(
+            this.disk_io().block_io,
+            token,
+            Transfer::Read(offset, buffer, size),
+        ),
+        Err(status) => status,
+    }
+}
+
+extern "efiapi" fn disk_io2_write_disk_ex(
+    this: *const DiskIo2,
+    media_id: u32,
+    offset: u64,
+    token: *mut Io2Token,
+    size: usize,
+    buffer: VoidPtr,
+) -> EfiStatus {
+    let this: &DiskIo2 = unsafe { &*this };
+
+    if this.disk_io().block_io().media().read_only {
+        return EFI_WRITE_PROTECTED;
+    }
+
+    if size != 0 && buffer.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    match this.disk_io().validate(media_id, offset, size) {
+        Ok(()) => submit(
+            this.disk_io().block_io,
+            token,
+            Transfer::Write(offset, buffer, size),
+        ),
+        Err(status) => status,
+    }
+}
+
+extern "efiapi" fn disk_io2_flush_disk_ex(this: *const DiskIo2, token: *mut Io2Token) -> EfiStatus {
+    submit(
+        unsafe { (*this).disk_io().block_io },
+        token,
+        Transfer::Flush,
+    )
+}
+
+fn read(storage: &Storage, offset: u64, buffer: VoidMutPtr, size: usize) -> EfiStatus {
+    if size == 0 {
+        return EFI_SUCCESS;
+    }
+
+    let buffer: &mut [u8] = unsafe { from_raw_parts_mut(buffer as *mut u8, size) };
+
+    match storage.read(offset, buffer) {
+        Ok(()) => EFI_SUCCESS,
+        Err(_) => EFI_DEVICE_ERROR,
+    }
+}
+
+fn flush(block_io: &BlockIo) -> EfiStatus {
+    if block_io.media().read_only || block_io.storage.flush().is_ok() {
+        EFI_SUCCESS
+    } else {
+        EFI_DEVICE_ERROR
+    }
+}
+
+fn write(storage: &Storage, offset: u64, buffer: VoidPtr, size: usize) -> EfiStatus {
+    if size == 0 {
+        return EFI_SUCCESS;
+    }
+
+    let buffer: &[u8] = unsafe { from_raw_parts(buffer as *const u8, size) };
+
+    match storage.write(offset, buffer) {
+        Ok(()) => EFI_SUCCESS,
+        Err(_) => EFI_DEVICE_ERROR,
+    }
+}
+
+/// The protocols installed on a simulated disk's handle.
+pub(crate) struct DiskProtocols {
+    pub(crate) block_io: Box<BlockIo>,
+    pub(crate) disk_io: Box<DiskIo>,
+    pub(crate) block_io2: Box<BlockIo2>,
+    pub(crate) disk_io2: Box<DiskIo2>,
+    pub(crate) device_path: Box<[u8]>,
+}
+
+impl DiskProtocols {
+    pub(crate) fn open(disk: &SimulatedDisk, index: u32) -> io::Result<Self> {
+        if disk.block_size == 0 || !disk.block_size.is_power_of_two() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Block size has to be a power of two!",
+            ));
+        }
+
+        let file: File = OpenOptions::new()
+            .read(true)
+            .write(!disk.read_only)
+            .open(&disk.path)?;
+
+        let blocks: u64 = file.metadata()?.len() / u64::from(disk.block_size);
+
+        if blocks == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Disk image is smaller than a single block!",
+            ));
+        }
+
+        Ok(Self::new(
+            Storage::File(file),
+            BlockIoMedia {
+                media_id: index,
+                removable_media: disk.removable,
+                media_present: true,
+                logical_partition: false,
+                read_only: disk.read_only,
+                write_caching: false,
+                block_size: disk.block_size,
+                io_alignment: 0,
+                last_block: blocks - 1,
+                lowest_aligned_lba: 0,
+                logical_blocks_per_physical_block: 1,
+                optimal_transfer_length_granularity: 0,
+            },
+            device_path(index),
+        ))
+    }
+
+    /// Creates the protocols of a RAM disk holding whole blocks at `base`.
+    pub(crate) fn ram_disk(
+        base: *mut u8,
+        blocks: u64,
+        block_size: u32,
+        read_only: bool,
+        device_path: Box<[u8]>,
+    ) -> Self {
+        Self::new(
+            Storage::Memory(base),
+            BlockIoMedia {
+                media_id: 0,
+                removable_media: false,
+                media_present: true,
+                logical_partition: false,
+                read_only,
+                write_caching: false,
+                block_size,
+                io_alignment: 0,
+                last_block: blocks - 1,
+                lowest_aligned_lba: 0,
+                logical_blocks_per_physical_block: 1,
+                optimal_transfer_length_granularity: 0,
+            },
+            device_path,
+        )
+    }
+
+    fn new(storage: Storage, media: BlockIoMedia, device_path: Box<[u8]>) -> Self {
+        let mut block_io: Box<BlockIo> = Box::new(BlockIo {
+            revision: BLOCK_IO_REVISION,
+            media: 0 as _,
+            reset: block_io_reset,
+            read_blocks: block_io_read_blocks,
+            write_blocks: block_io_write_blocks,
+            flush_blocks: block_io_flush_blocks,
+            media_data: media,
+            storage,
+        });
+
+        block_io.media = &block_io.media_data;
+
+        let disk_io: Box<DiskIo> = Box::new(DiskIo {
+            revision: DISK_IO_REVISION,
+            read_disk: disk_io_read_disk,
+            write_disk: disk_io_write_disk,
+            block_io: &*block_io,
+        });
+
+        let block_io2: Box<BlockIo2> = Box::new(BlockIo2 {
+            media: &block_io.media_data,
+            reset: block_io2_reset,
+            read_blocks_ex: block_io2_read_blocks_ex,
+            write_blocks_ex: block_io2_write_blocks_ex,
+            flush_blocks_ex: block_io2_flush_blocks_ex,
+            block_io: &*block_io,
+        });
+
+        let disk_io2: Box<DiskIo2> = Box::new(DiskIo2 {
+            revision: DISK_IO2_REVISION,
+            cancel: disk_io2_cancel,
+            read_disk_ex: disk_io2_read_disk_ex,
+            write_disk_ex: disk_io2_write_disk_ex,
+            flush_disk_ex: disk_io2_flush_disk_ex,
+            disk_io: &*disk_io,
+        });
+
+        Self {
+            block_io,
+            disk_io,
+            block_io2,
+            disk_io2,
+            device_path,
+        }
+    }
+}
+
+/// Builds a vendor-defined hardware node identifying the disk, followed by an end node.
+fn device_path(index: u32) -> Box<[u8]> {
+    const HARDWARE_DEVICE_PATH: u8 = 0x01;
+    const HW_VENDOR_DP: u8 = 0x04;
+    const VENDOR_NODE_LENGTH: u16 = 4 + 16 + 4;
+
+    let mut device_path: Vec<u8> = vec![HARDWARE_DEVICE_PATH, HW_VENDOR_DP];
+
+    device_path.extend_from_slice(&VENDOR_NODE_LENGTH.to_le_bytes());
+    device_path.extend_from_slice(unsafe {
+        from_raw_parts(
+            &SIMULATED_DISK_VENDOR as *const EfiGuid as *const u8,
+            std::mem::size_of::<EfiGuid>(),
+        )
+    });
+    device_path.extend_from_slice(&index.to_le_bytes());
+    device_path.extend_from_slice(&[0x7F, 0xFF, 0x04, 0x00]);
+
+    device_path.into_boxed_slice()
+}
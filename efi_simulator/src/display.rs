@@ -0,0 +1,41 @@
+/// Simulated `EFI_EDID_DISCOVERED_PROTOCOL` and `EFI_EDID_ACTIVE_PROTOCOL`, which share their layout.
+#[repr(C)]
+pub(crate) struct EdidProtocol {
+    size_of_edid: u32,
+    edid: *const u8,
+}
+
+/// Protocols installed on a display's handle. The active EDID is the discovered one, as nothing overrides it.
+pub(crate) struct DisplayProtocols {
+    /* Only reached through the protocols, which point into it */
+    #[allow(dead_code)]
+    edid: Box<[u8]>,
+    pub(crate) discovered: Box<EdidProtocol>,
+    pub(crate) active: Box<EdidProtocol>,
+}
+
+impl DisplayProtocols {
+    pub(crate) fn new(edid: &[u8]) -> Self {
+        let edid: Box<[u8]> = edid.into();
+
+        let protocol = || {
+            Box::new(EdidProtocol {
+                size_of_edid: edid.len() as u32,
+                edid: if edid.is_empty() {
+                    0 as _
+                } else {
+                    edid.as_ptr()
+                },
+            })
+        };
+
+        let discovered: Box<EdidProtocol> = protocol();
+        let active: Box<EdidProtocol> = protocol();
+
+        Self {
+            edid,
+            discovered,
+            active,
+        }
+    }
+}
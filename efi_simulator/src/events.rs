@@ -0,0 +1,319 @@
+use {
+    crate::status::{EFI_INVALID_PARAMETER, EFI_NOT_READY},
+    efi::{
+        boot_services::types::event_and_timer::EfiEventNotifyCallback, EfiEvent, EfiGuid,
+        EfiStatus, VoidPtr,
+    },
+};
+
+pub(crate) const EVT_TIMER: u32 = 0x8000_0000;
+pub(crate) const EVT_RUNTIME: u32 = 0x4000_0000;
+pub(crate) const EVT_NOTIFY_WAIT: u32 = 0x0000_0100;
+pub(crate) const EVT_NOTIFY_SIGNAL: u32 = 0x0000_0200;
+pub(crate) const EVT_SIGNAL_EXIT_BOOT_SERVICES: u32 = 0x0000_0201;
+pub(crate) const EVT_SIGNAL_VIRTUAL_ADDRESS_CHANGE: u32 = 0x6000_0202;
+
+pub(crate) const TPL_APPLICATION: usize = 4;
+pub(crate) const TPL_CALLBACK: usize = 8;
+pub(crate) const TPL_NOTIFY: usize = 16;
+
+pub(crate) const EFI_EVENT_GROUP_EXIT_BOOT_SERVICES: EfiGuid = EfiGuid::from_tuple((
+    0x27AB_F055,
+    0xB1B8,
+    0x4C26,
+    [0x80, 0x48, 0x74, 0x8F, 0x37, 0xBA, 0xA2, 0xDF],
+));
+pub(crate) const EFI_EVENT_GROUP_VIRTUAL_ADDRESS_CHANGE: EfiGuid = EfiGuid::from_tuple((
+    0x13FA_7698,
+    0xC831,
+    0x49C7,
+    [0x87, 0xEA, 0x8F, 0x43, 0xFC, 0xC2, 0x51, 0x96],
+));
+
+const TIMER_CANCEL: u32 = 0;
+const TIMER_PERIODIC: u32 = 1;
+pub(crate) const TIMER_RELATIVE: u32 = 2;
+
+/// A notification function queued for invocation together with it's event and context.
+pub(crate) type Notification = (EfiEventNotifyCallback, EfiEvent, VoidPtr);
+
+struct Timer {
+    deadline: u64,
+    period: Option<u64>,
+}
+
+struct Event {
+    event_type: u32,
+    tpl: usize,
+    notify: Option<(EfiEventNotifyCallback, VoidPtr)>,
+    group: Option<EfiGuid>,
+    signaled: bool,
+    timer: Option<Timer>,
+}
+
+pub(crate) enum EventCheck {
+    Signaled,
+    NotReady,
+    /// The event is a wait event which notification function has to run before checking again.
+    Notify(Notification),
+}
+
+/// Events and timers driven by a virtual clock.
+///
+/// The clock is counted in 100ns units and only advances through `Stall` and `WaitForEvent`.
+pub(crate) struct EventTable {
+    events: Vec<Box<Event>>,
+    pending: Vec<Notification>,
+    time: u64,
+    tpl: usize,
+}
+
+impl EventTable {
+    pub(crate) fn new() -> Self {
+        Self {
+            events: Vec::new(),
+            pending: Vec::new(),
+            time: 0,
+            tpl: TPL_APPLICATION,
+        }
+    }
+
+    fn position(&self, event: EfiEvent) -> Option<usize> {
+        self.events
+            .iter()
+            .position(|entry: &Box<Event>| &**entry as *const Event as EfiEvent == event)
+    }
+
+    fn handle(&self, index: usize) -> EfiEvent {
+        &*self.events[index] as *const Event as EfiEvent
+    }
+
+    pub(crate) fn create(
+        &mut self,
+        event_type: u32,
+        tpl: usize,
+        notify: Option<EfiEventNotifyCallback>,
+        context: VoidPtr,
+        group: Option<EfiGuid>,
+    ) -> Result<EfiEvent, EfiStatus> {
+        const KNOWN: u32 = EVT_TIMER
+            | EVT_RUNTIME
+            | EVT_NOTIFY_WAIT
+            | EVT_NOTIFY_SIGNAL
+            | EVT_SIGNAL_EXIT_BOOT_SERVICES
+            | EVT_SIGNAL_VIRTUAL_ADDRESS_CHANGE;
+
+        let notifies: bool = event_type & (EVT_NOTIFY_WAIT | EVT_NOTIFY_SIGNAL) != 0;
+
+        if event_type & !KNOWN != 0
+            || event_type & (EVT_NOTIFY_WAIT | EVT_NOTIFY_SIGNAL)
+                == EVT_NOTIFY_WAIT | EVT_NOTIFY_SIGNAL
+            || (notifies
+                && (notify.is_none()
+                    || ![TPL_APPLICATION, TPL_CALLBACK, TPL_NOTIFY].contains(&tpl)))
+        {
+            return Err(EFI_INVALID_PARAMETER);
+        }
+
+        self.events.push(Box::new(Event {
+            event_type,
+            tpl,
+            notify: if notifies {
+                notify.map(|notify: EfiEventNotifyCallback| (notify, context))
+            } else {
+                None
+            },
+            group,
+            signaled: false,
+            timer: None,
+        }));
+
+        Ok(self.handle(self.events.len() - 1))
+    }
+
+    pub(crate) fn close(&mut self, event: EfiEvent) -> Result<(), EfiStatus> {
+        let index: usize = self.position(event).ok_or(EFI_INVALID_PARAMETER)?;
+
+        self.events.remove(index);
+        self.pending
+            .retain(|&(_, pending, _): &Notification| pending != event);
+
+        Ok(())
+    }
+
+    fn signal_index(&mut self, index: usize) {
+        let handle: EfiEvent = self.handle(index);
+        let event: &mut Event = &mut self.events[index];
+
+        if event.event_type & EVT_NOTIFY_SIGNAL != 0 {
+            if let Some((notify, context)) = event.notify {
+                if !self
+                    .pending
+                    .iter()
+                    .any(|&(_, pending, _): &Notification| pending == handle)
+                {
+                    self.pending.push((notify, handle, context));
+                }
+            }
+        } else {
+            event.signaled = true;
+        }
+    }
+
+    /// Signals the event or, when it belongs to a group, every event in the group.
+    pub(crate) fn signal(&mut self, event: EfiEvent) -> Result<(), EfiStatus> {
+        let index: usize = self.position(event).ok_or(EFI_INVALID_PARAMETER)?;
+
+        match self.events[index].group {
+            Some(group) => self.signal_group(&group),
+            None => self.signal_index(index),
+        }
+
+        Ok(())
+    }
+
+    /// Signals every event created in the group.
+    pub(crate) fn signal_group(&mut self, group: &EfiGuid) {
+        for index in 0..self.events.len() {
+            if self.events[index].group.as_ref() == Some(group) {
+                self.signal_index(index);
+            }
+        }
+    }
+
+    /// Signals every event created with exactly the given type.
+    pub(crate) fn signal_type(&mut self, event_type: u32) {
+        for index in 0..self.events.len() {
+            if self.events[index].event_type == event_type {
+                self.signal_index(index);
+            }
+        }
+    }
+
+    pub(crate) fn check(&mut self, event: EfiEvent) -> Result<EventCheck, EfiStatus> {
+        let index: usize = self.position(event).ok_or(EFI_INVALID_PARAMETER)?;
+        let event: &mut Event = &mut self.events[index];
+
+        if event.event_type & EVT_NOTIFY_SIGNAL != 0 {
+            Err(EFI_INVALID_PARAMETER)
+        } else if event.signaled {
+            event.signaled = false;
+
+            Ok(EventCheck::Signaled)
+        } else if let Some((notify, context)) = event.notify {
+            Ok(EventCheck::Notify((notify, self.handle(index), context)))
+        } else {
+            Ok(EventCheck::NotReady)
+        }
+    }
+
+    /// Checks the event again after it's wait notification function ran.
+    pub(crate) fn check_notified(&mut self, event: EfiEvent) -> Result<(), EfiStatus> {
+        let index: usize = self.position(event).ok_or(EFI_INVALID_PARAMETER)?;
+        let event: &mut Event = &mut self.events[index];
+
+        if event.signaled {
+            event.signaled = false;
+
+            Ok(())
+        } else {
+            Err(EFI_NOT_READY)
+        }
+    }
+
+    /// Arms or cancels the event's timer. The trigger time is in 100ns units.
+    pub(crate) fn set_timer(
+        &mut self,
+        event: EfiEvent,
+        timer_type: u32,
+        trigger_time: u64,
+    ) -> Result<(), EfiStatus> {
+        let index: usize = self.position(event).ok_or(EFI_INVALID_PARAMETER)?;
+        let now: u64 = self.time;
+        let event: &mut Event = &mut self.events[index];
+
+        if event.event_type & EVT_TIMER == 0 {
+            return Err(EFI_INVALID_PARAMETER);
+        }
+
+        event.timer = match timer_type {
+            TIMER_CANCEL => None,
+            TIMER_PERIODIC => Some(Timer {
+                deadline: now + trigger_time.max(1),
+                period: Some(trigger_time.max(1)),
+            }),
+            TIMER_RELATIVE => Some(Timer {
+                deadline: now + trigger_time,
+                period: None,
+            }),
+            _ => return Err(EFI_INVALID_PARAMETER),
+        };
+
+        Ok(())
+    }
+
+    /// Advances the virtual clock and signals every timer which deadline passed.
+    pub(crate) fn advance(&mut self, ticks: u64) {
+        self.time = self.time.saturating_add(ticks);
+
+        let now: u64 = self.time;
+
+        for index in 0..self.events.len() {
+            let event: &mut Event = &mut self.events[index];
+
+            match &mut event.timer {
+                Some(timer) if timer.deadline <= now => match timer.period {
+                    Some(period) => {
+                        while timer.deadline <= now {
+                            timer.deadline += period;
+                        }
+                    }
+                    None => event.timer = None,
+                },
+                _ => continue,
+            }
+
+            self.signal_index(index);
+        }
+    }
+
+    /// Returns the number of ticks until the nearest timer expires.
+    pub(crate) fn next_deadline(&self) -> Option<u64> {
+        self.events
+            .iter()
+            .filter_map(|event: &Box<Event>| event.timer.as_ref())
+            .map(|timer: &Timer| timer.deadline.saturating_sub(self.time))
+            .min()
+    }
+
+    pub(crate) fn time(&self) -> u64 {
+        self.time
+    }
+
+    pub(crate) fn take_notifications(&mut self) -> Vec<Notification> {
+        let tpl: usize = self.tpl;
+        let events: &Vec<Box<Event>> = &self.events;
+
+        /* Notifications run only once the TPL drops below the event's TPL */
+        let (ready, deferred): (Vec<Notification>, Vec<Notification>) = self
+            .pending
+            .drain(..)
+            .partition(|&(_, event, _): &Notification| {
+                events.iter().any(|entry: &Box<Event>| {
+                    &**entry as *const Event as EfiEvent == event && entry.tpl > tpl
+                })
+            });
+
+        self.pending = deferred;
+
+        ready
+    }
+
+    pub(crate) fn tpl(&self) -> usize {
+        self.tpl
+    }
+
+    pub(crate) fn set_tpl(&mut self, tpl: usize) -> usize {
+        std::mem::replace(&mut self.tpl, tpl)
+    }
+}
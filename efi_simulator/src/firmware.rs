@@ -0,0 +1,106 @@
+use {
+    crate::{
+        console::Console,
+        disk::{DiskProtocols, Io2Request},
+        events::{EventTable, Notification},
+        handles::HandleDatabase,
+        memory::Memory,
+        pointer::PointerInput,
+        tables::{ConfigurationTableEntry, SystemTable},
+        variables::VariableStore,
+    },
+    efi::{
+        runtime_services::{miscellaneous::EfiResetType, time::EfiTimeRepresentation},
+        EfiHandle, EfiStatus,
+    },
+    std::{cell::RefCell, rc::Rc},
+};
+
+/// Called by `ResetSystem` with the reset type, status and reset data.
+pub(crate) type ResetHandler = Rc<dyn Fn(EfiResetType, EfiStatus, &[u8]) -> !>;
+
+/// State shared by every simulated service.
+pub(crate) struct Firmware {
+    pub(crate) variables: VariableStore,
+    pub(crate) handles: HandleDatabase,
+    pub(crate) memory: Memory,
+    pub(crate) events: EventTable,
+    pub(crate) console: Console,
+    pub(crate) pointer: PointerInput,
+    pub(crate) configuration_tables: Vec<ConfigurationTableEntry>,
+    pub(crate) system_table: *mut SystemTable,
+    pub(crate) time: EfiTimeRepresentation,
+    pub(crate) wakeup: (bool, EfiTimeRepresentation),
+    pub(crate) monotonic_count: u64,
+    pub(crate) watchdog: Option<usize>,
+    pub(crate) boot_services_exited: bool,
+    pub(crate) virtual_address_map_set: bool,
+    pub(crate) capsule_updates: bool,
+    pub(crate) capsules: Vec<Vec<u8>>,
+    /// RAM disks registered through `EFI_RAM_DISK_PROTOCOL`.
+    pub(crate) ram_disks: Vec<(EfiHandle, DiskProtocols)>,
+    /// Asynchronous Block IO 2 and Disk IO 2 requests which didn't complete yet.
+    pub(crate) io2_requests: Vec<Io2Request>,
+    pub(crate) reset_handler: ResetHandler,
+}
+
+impl Firmware {
+    /// Points the system table at the current configuration tables and reseals it's checksum.
+    pub(crate) fn update_configuration_tables(&mut self) {
+        let system_table: &mut SystemTable = unsafe { &mut *self.system_table };
+
+        system_table.configuration_tables_count = self.configuration_tables.len();
+        system_table.configuration_tables = self.configuration_tables.as_ptr();
+        system_table.header.update_crc32();
+    }
+}
+
+thread_local! {
+    /* The services are plain function pointers, so they reach the simulator through this */
+    static FIRMWARE: RefCell<Option<Rc<RefCell<Firmware>>>> = const { RefCell::new(None) };
+}
+
+pub(crate) fn install(firmware: Rc<RefCell<Firmware>>) {
+    FIRMWARE.with(|current: &RefCell<Option<Rc<RefCell<Firmware>>>>| {
+        if current.replace(Some(firmware)).is_some() {
+            panic!("Only one simulator can be active per thread!");
+        }
+    });
+}
+
+pub(crate) fn uninstall() {
+    FIRMWARE.with(|current: &RefCell<Option<Rc<RefCell<Firmware>>>>| current.replace(None));
+}
+
+/// Runs `f` with the firmware of the simulator active on this thread.
+pub(crate) fn with_firmware<F: FnOnce(&mut Firmware) -> R, R>(f: F) -> R {
+    let firmware: Rc<RefCell<Firmware>> =
+        FIRMWARE.with(|current: &RefCell<Option<Rc<RefCell<Firmware>>>>| {
+            current
+                .borrow()
+                .clone()
+                .expect("Firmware service called without an active simulator!")
+        });
+
+    let mut firmware = firmware.borrow_mut();
+
+    f(&mut firmware)
+}
+
+/// Runs notification functions which became ready.
+///
+/// They are called without holding the firmware, as they're free to call services themselves.
+pub(crate) fn dispatch_notifications() {
+    loop {
+        let notifications: Vec<Notification> =
+            with_firmware(|firmware: &mut Firmware| firmware.events.take_notifications());
+
+        if notifications.is_empty() {
+            break;
+        }
+
+        for (notify, event, context) in notifications {
+            notify(event, context);
+        }
+    }
+}
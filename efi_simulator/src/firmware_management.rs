@@ -0,0 +1,555 @@
+use {
+    crate::{
+        boot_services::EFI_BOOT_SERVICES_DATA,
+        firmware::{with_firmware, Firmware},
+        status::{
+            into_status, EFI_ABORTED, EFI_BUFFER_TOO_SMALL, EFI_INVALID_PARAMETER, EFI_SUCCESS,
+        },
+    },
+    efi::{EfiGuid, EfiStatus, VoidMutPtr, VoidPtr},
+    std::{cell::RefCell, mem::size_of, slice::from_raw_parts},
+};
+
+const DESCRIPTOR_VERSION: u32 = 4;
+const ESRT_RESOURCE_VERSION: u64 = 1;
+
+/* Versions are tracked per image */
+const PACKAGE_VERSION_NOT_SUPPORTED: u32 = 0xFFFF_FFFE;
+
+const IMAGE_ATTRIBUTE_IMAGE_UPDATABLE: u64 = 0x0001;
+const PACKAGE_ATTRIBUTE_VERSION_UPDATABLE: u64 = 0x0001;
+
+const IMAGE_UPDATABLE_VALID: u32 = 0x0001;
+const IMAGE_UPDATABLE_INVALID: u32 = 0x0002;
+const IMAGE_UPDATABLE_INVALID_OLD: u32 = 0x0008;
+
+const LAST_ATTEMPT_STATUS_SUCCESS: u32 = 0;
+const LAST_ATTEMPT_STATUS_INCORRECT_VERSION: u32 = 3;
+const LAST_ATTEMPT_STATUS_INVALID_FORMAT: u32 = 4;
+
+const FW_TYPE_SYSTEM_FIRMWARE: u32 = 1;
+
+/// Firmware image managed through the simulated `EFI_FIRMWARE_MANAGEMENT_PROTOCOL` and listed in the ESRT.
+///
+/// Images start with their version, as a little-endian `u32`, followed by arbitrary data.
+#[derive(Clone)]
+pub struct SimulatedFirmwareImage {
+    image_type_id: EfiGuid,
+    name: String,
+    lowest_supported_version: u32,
+    data: Vec<u8>,
+}
+
+impl SimulatedFirmwareImage {
+    /// Creates an updatable image accepting any version.
+    pub fn new(image_type_id: EfiGuid, data: &[u8]) -> Self {
+        Self {
+            image_type_id,
+            name: String::from("Simulated Firmware"),
+            lowest_supported_version: 0,
+            data: data.to_vec(),
+        }
+    }
+
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = String::from(name);
+
+        self
+    }
+
+    /// Images with an older version are rejected by `CheckImage` and `SetImage`.
+    pub fn lowest_supported_version(mut self, version: u32) -> Self {
+        self.lowest_supported_version = version;
+
+        self
+    }
+}
+
+/// Reads the version in the image's header.
+fn image_version(data: &[u8]) -> Option<u32> {
+    data.get(..size_of::<u32>())
+        .map(|version: &[u8]| u32::from_le_bytes([version[0], version[1], version[2], version[3]]))
+}
+
+#[repr(C)]
+struct ImageDescriptor {
+    image_index: u8,
+    image_type_id: EfiGuid,
+    image_id: u64,
+    image_id_name: *const u16,
+    version: u32,
+    version_name: *const u16,
+    size: usize,
+    attributes_supported: u64,
+    attributes_setting: u64,
+    compatibilities: u64,
+    lowest_supported_image_version: u32,
+    last_attempt_version: u32,
+    last_attempt_status: u32,
+    hardware_instance: u64,
+    dependencies: *const u8,
+}
+
+#[repr(C)]
+struct EsrtHeader {
+    fw_resource_count: u32,
+    fw_resource_count_max: u32,
+    fw_resource_version: u64,
+}
+
+#[repr(C)]
+struct EsrtEntry {
+    fw_class: EfiGuid,
+    fw_type: u32,
+    fw_version: u32,
+    lowest_supported_fw_version: u32,
+    capsule_flags: u32,
+    last_attempt_version: u32,
+    last_attempt_status: u32,
+}
+
+struct Image {
+    image: SimulatedFirmwareImage,
+    name: Vec<u16>,
+    version: u32,
+    last_attempt_version: u32,
+    last_attempt_status: u32,
+}
+
+struct FirmwareManagementState {
+    images: Vec<Image>,
+    package_version: u32,
+    package_version_name: Vec<u16>,
+    /* Kept as "u64"s for the header's alignment */
+    esrt: Box<[u64]>,
+}
+
+impl FirmwareManagementState {
+    /// Mirrors the images' state into the ESRT.
+    fn update_esrt(&mut self) {
+        let header: *mut EsrtHeader = self.esrt.as_mut_ptr().cast();
+
+        unsafe {
+            header.write(EsrtHeader {
+                fw_resource_count: self.images.len() as u32,
+                fw_resource_count_max: self.images.len() as u32,
+                fw_resource_version: ESRT_RESOURCE_VERSION,
+            });
+
+            let entries: *mut EsrtEntry = header.add(1).cast();
+
+            for (index, image) in self.images.iter().enumerate() {
+                entries.add(index).write(EsrtEntry {
+                    fw_class: image.image.image_type_id,
+                    fw_type: FW_TYPE_SYSTEM_FIRMWARE,
+                    fw_version: image.version,
+                    lowest_supported_fw_version: image.image.lowest_supported_version,
+                    capsule_flags: 0,
+                    last_attempt_version: image.last_attempt_version,
+                    last_attempt_status: image.last_attempt_status,
+                });
+            }
+        }
+    }
+
+    /// Returns the index of the image the protocol's 1-based index refers to.
+    fn image_index(&self, image_index: u8) -> Result<usize, EfiStatus> {
+        match usize::from(image_index).checked_sub(1) {
+            Some(index) if index < self.images.len() => Ok(index),
+            _ => Err(EFI_INVALID_PARAMETER),
+        }
+    }
+
+    fn check(&self, index: usize, data: &[u8]) -> u32 {
+        match image_version(data) {
+            None => IMAGE_UPDATABLE_INVALID,
+            Some(version) if version < self.images[index].image.lowest_supported_version => {
+                IMAGE_UPDATABLE_INVALID_OLD
+            }
+            Some(_) => IMAGE_UPDATABLE_VALID,
+        }
+    }
+}
+
+/// Simulated `EFI_FIRMWARE_MANAGEMENT_PROTOCOL` followed by the state backing it.
+#[repr(C)]
+pub(crate) struct FirmwareManagement {
+    get_image_info: extern "efiapi" fn(
+        *const Self,
+        *mut usize,
+        VoidMutPtr,
+        *mut u32,
+        *mut u8,
+        *mut usize,
+        *mut u32,
+        *mut *mut u16,
+    ) -> EfiStatus,
+    get_image: extern "efiapi" fn(*const Self, u8, VoidMutPtr, *mut usize) -> EfiStatus,
+    set_image: extern "efiapi" fn(
+        *const Self,
+        u8,
+        VoidPtr,
+        usize,
+        VoidPtr,
+        Option<extern "efiapi" fn(usize) -> EfiStatus>,
+        *mut *mut u16,
+    ) -> EfiStatus,
+    check_image: extern "efiapi" fn(*const Self, u8, VoidPtr, usize, *mut u32) -> EfiStatus,
+    get_package_info: extern "efiapi" fn(
+        *const Self,
+        *mut u32,
+        *mut *mut u16,
+        *mut u32,
+        *mut u64,
+        *mut u64,
+    ) -> EfiStatus,
+    set_package_info:
+        extern "efiapi" fn(*const Self, VoidPtr, usize, VoidPtr, u32, *const u16) -> EfiStatus,
+    state: RefCell<FirmwareManagementState>,
+}
+
+impl FirmwareManagement {
+    pub(crate) fn new(images: &[SimulatedFirmwareImage]) -> Box<Self> {
+        let images: Vec<Image> = images
+            .iter()
+            .map(|image: &SimulatedFirmwareImage| {
+                let version: u32 = image_version(&image.data).unwrap_or(0);
+
+                Image {
+                    image: image.clone(),
+                    name: image.name.encode_utf16().chain(Some(0)).collect(),
+                    version,
+                    last_attempt_version: version,
+                    last_attempt_status: LAST_ATTEMPT_STATUS_SUCCESS,
+                }
+            })
+            .collect();
+
+        let esrt_size: usize = size_of::<EsrtHeader>() + images.len() * size_of::<EsrtEntry>();
+
+        let mut state: FirmwareManagementState = FirmwareManagementState {
+            images,
+            package_version: PACKAGE_VERSION_NOT_SUPPORTED,
+            package_version_name: "Simulated Package".encode_utf16().chain(Some(0)).collect(),
+            esrt: vec![0; (esrt_size + size_of::<u64>() - 1) / size_of::<u64>()].into_boxed_slice(),
+        };
+
+        state.update_esrt();
+
+        Box::new(Self {
+            get_image_info: fmp_get_image_info,
+            get_image: fmp_get_image,
+            set_image: fmp_set_image,
+            check_image: fmp_check_image,
+            get_package_info: fmp_get_package_info,
+            set_package_info: fmp_set_package_info,
+            state: RefCell::new(state),
+        })
+    }
+
+    /// Returns the ESRT, which stays at the same address while the images get updated.
+    pub(crate) fn esrt(&self) -> VoidPtr {
+        self.state.borrow().esrt.as_ptr() as VoidPtr
+    }
+
+    /// Returns the version and data of the image with the given index, starting from 0.
+    pub(crate) fn image(&self, index: usize) -> Option<(u32, Vec<u8>)> {
+        self.state
+            .borrow()
+            .images
+            .get(index)
+            .map(|image: &Image| (image.version, image.image.data.clone()))
+    }
+}
+
+/// Copies the string into pool memory, as the caller is responsible for releasing it.
+fn pool_string(string: &[u16]) -> Result<*mut u16, EfiStatus> {
+    with_firmware(|firmware: &mut Firmware| {
+        firmware
+            .memory
+            .allocate_pool_from(EFI_BOOT_SERVICES_DATA, string)
+    })
+}
+
+extern "efiapi" fn fmp_get_image_info(
+    this: *const FirmwareManagement,
+    size: *mut usize,
+    descriptors: VoidMutPtr,
+    descriptor_version: *mut u32,
+    descriptor_count: *mut u8,
+    descriptor_size: *mut usize,
+    package_version: *mut u32,
+    package_version_name: *mut *mut u16,
+) -> EfiStatus {
+    let state = unsafe { &*this }.state.borrow();
+
+    if size.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    let required: usize = state.images.len() * size_of::<ImageDescriptor>();
+
+    unsafe {
+        if *size < required {
+            *size = required;
+
+            return EFI_BUFFER_TOO_SMALL;
+        }
+
+        if descriptors.is_null()
+            || descriptor_version.is_null()
+            || descriptor_count.is_null()
+            || descriptor_size.is_null()
+            || package_version.is_null()
+        {
+            return EFI_INVALID_PARAMETER;
+        }
+
+        for (index, image) in state.images.iter().enumerate() {
+            (descriptors as *mut ImageDescriptor)
+                .add(index)
+                .write_unaligned(ImageDescriptor {
+                    image_index: index as u8 + 1,
+                    image_type_id: image.image.image_type_id,
+                    image_id: index as u64 + 1,
+                    image_id_name: image.name.as_ptr(),
+                    version: image.version,
+                    version_name: 0 as _,
+                    size: image.image.data.len(),
+                    attributes_supported: IMAGE_ATTRIBUTE_IMAGE_UPDATABLE,
+                    attributes_setting: IMAGE_ATTRIBUTE_IMAGE_UPDATABLE,
+                    compatibilities: 0,
+                    lowest_supported_image_version: image.image.lowest_supported_version,
+                    last_attempt_version: image.last_attempt_version,
+                    last_attempt_status: image.last_attempt_status,
+                    hardware_instance: 0,
+                    dependencies: 0 as _,
+                });
+        }
+
+        *size = required;
+        *descriptor_version = DESCRIPTOR_VERSION;
+        *descriptor_count = state.images.len() as u8;
+        *descriptor_size = size_of::<ImageDescriptor>();
+        *package_version = state.package_version;
+
+        if !package_version_name.is_null() {
+            match pool_string(&state.package_version_name) {
+                Ok(name) => *package_version_name = name,
+                Err(status) => return status,
+            }
+        }
+    }
+
+    EFI_SUCCESS
+}
+
+extern "efiapi" fn fmp_get_image(
+    this: *const FirmwareManagement,
+    image_index: u8,
+    image: VoidMutPtr,
+    size: *mut usize,
+) -> EfiStatus {
+    let state = unsafe { &*this }.state.borrow();
+
+    if size.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    let index: usize = match state.image_index(image_index) {
+        Ok(index) => index,
+        Err(status) => return status,
+    };
+
+    let data: &[u8] = &state.images[index].image.data;
+
+    unsafe {
+        if *size < data.len() {
+            *size = data.len();
+
+            return EFI_BUFFER_TOO_SMALL;
+        }
+
+        if image.is_null() {
+            return EFI_INVALID_PARAMETER;
+        }
+
+        (image as *mut u8).copy_from_nonoverlapping(data.as_ptr(), data.len());
+
+        *size = data.len();
+    }
+
+    EFI_SUCCESS
+}
+
+extern "efiapi" fn fmp_set_image(
+    this: *const FirmwareManagement,
+    image_index: u8,
+    image: VoidPtr,
+    size: usize,
+    _: VoidPtr,
+    progress: Option<extern "efiapi" fn(usize) -> EfiStatus>,
+    abort_reason: *mut *mut u16,
+) -> EfiStatus {
+    let this: &FirmwareManagement = unsafe { &*this };
+
+    if image.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    let data: &[u8] = unsafe { from_raw_parts(image as *const u8, size) };
+
+    let index: usize = {
+        let mut state = this.state.borrow_mut();
+
+        let index: usize = match state.image_index(image_index) {
+            Ok(index) => index,
+            Err(status) => return status,
+        };
+
+        let check: u32 = state.check(index, data);
+
+        if check != IMAGE_UPDATABLE_VALID {
+            let (status, reason): (u32, &str) = if check == IMAGE_UPDATABLE_INVALID_OLD {
+                (LAST_ATTEMPT_STATUS_INCORRECT_VERSION, "Version too old")
+            } else {
+                (LAST_ATTEMPT_STATUS_INVALID_FORMAT, "Invalid image")
+            };
+
+            state.images[index].last_attempt_version = image_version(data).unwrap_or(0);
+            state.images[index].last_attempt_status = status;
+            state.update_esrt();
+
+            if !abort_reason.is_null() {
+                let reason: Vec<u16> = reason.encode_utf16().chain(Some(0)).collect();
+
+                if let Ok(reason) = pool_string(&reason) {
+                    unsafe { *abort_reason = reason };
+                }
+            }
+
+            return EFI_ABORTED;
+        }
+
+        index
+    };
+
+    /* Reported without holding the state, as the caller is free to call the protocol */
+    if let Some(progress) = progress {
+        progress(50);
+    }
+
+    {
+        let mut state = this.state.borrow_mut();
+
+        let version: u32 = image_version(data).unwrap_or(0);
+
+        let image: &mut Image = &mut state.images[index];
+
+        image.image.data = data.to_vec();
+        image.version = version;
+        image.last_attempt_version = version;
+        image.last_attempt_status = LAST_ATTEMPT_STATUS_SUCCESS;
+
+        state.update_esrt();
+    }
+
+    if let Some(progress) = progress {
+        progress(100);
+    }
+
+    EFI_SUCCESS
+}
+
+extern "efiapi" fn fmp_check_image(
+    this: *const FirmwareManagement,
+    image_index: u8,
+    image: VoidPtr,
+    size: usize,
+    updatable: *mut u32,
+) -> EfiStatus {
+    let state = unsafe { &*this }.state.borrow();
+
+    if image.is_null() || updatable.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    into_status(state.image_index(image_index).map(|index: usize| unsafe {
+        *updatable = state.check(index, from_raw_parts(image as *const u8, size));
+    }))
+}
+
+extern "efiapi" fn fmp_get_package_info(
+    this: *const FirmwareManagement,
+    package_version: *mut u32,
+    package_version_name: *mut *mut u16,
+    package_version_name_max_length: *mut u32,
+    attributes_supported: *mut u64,
+    attributes_setting: *mut u64,
+) -> EfiStatus {
+    let state = unsafe { &*this }.state.borrow();
+
+    unsafe {
+        if !package_version.is_null() {
+            *package_version = state.package_version;
+        }
+
+        if !package_version_name.is_null() {
+            match pool_string(&state.package_version_name) {
+                Ok(name) => *package_version_name = name,
+                Err(status) => return status,
+            }
+        }
+
+        if !package_version_name_max_length.is_null() {
+            *package_version_name_max_length = 64;
+        }
+
+        if !attributes_supported.is_null() {
+            *attributes_supported = PACKAGE_ATTRIBUTE_VERSION_UPDATABLE;
+        }
+
+        if !attributes_setting.is_null() {
+            *attributes_setting = PACKAGE_ATTRIBUTE_VERSION_UPDATABLE;
+        }
+    }
+
+    EFI_SUCCESS
+}
+
+extern "efiapi" fn fmp_set_package_info(
+    this: *const FirmwareManagement,
+    _: VoidPtr,
+    _: usize,
+    _: VoidPtr,
+    package_version: u32,
+    package_version_name: *const u16,
+) -> EfiStatus {
+    let mut state = unsafe { &*this }.state.borrow_mut();
+
+    if package_version_name.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    let mut name: Vec<u16> = Vec::new();
+
+    for offset in 0..64 {
+        let character: u16 = unsafe { *package_version_name.add(offset) };
+
+        name.push(character);
+
+        if character == 0 {
+            break;
+        }
+    }
+
+    /* Longer than the maximum length reported by GetPackageInfo */
+    if name.last() != Some(&0) {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    state.package_version = package_version;
+    state.package_version_name = name;
+
+    EFI_SUCCESS
+}
@@ -0,0 +1,451 @@
+use {
+    crate::status::{
+        EFI_ACCESS_DENIED, EFI_ALREADY_STARTED, EFI_INVALID_PARAMETER, EFI_NOT_FOUND,
+        EFI_UNSUPPORTED,
+    },
+    efi::{EfiEvent, EfiGuid, EfiHandle, EfiStatus, VoidPtr},
+    std::collections::VecDeque,
+};
+
+pub(crate) const ALL_HANDLES: u32 = 0;
+pub(crate) const BY_REGISTER_NOTIFY: u32 = 1;
+pub(crate) const BY_PROTOCOL: u32 = 2;
+
+const BY_HANDLE_PROTOCOL: u32 = 0x01;
+const GET_PROTOCOL: u32 = 0x02;
+const TEST_PROTOCOL: u32 = 0x04;
+const BY_CHILD_CONTROLLER: u32 = 0x08;
+const BY_DRIVER: u32 = 0x10;
+const EXCLUSIVE: u32 = 0x20;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub(crate) struct OpenProtocolInformationEntry {
+    agent_handle: EfiHandle,
+    controller_handle: EfiHandle,
+    attributes: u32,
+    open_count: u32,
+}
+
+struct ProtocolInterface {
+    guid: EfiGuid,
+    interface: VoidPtr,
+    open_list: Vec<OpenProtocolInformationEntry>,
+}
+
+struct Handle {
+    protocols: Vec<Box<ProtocolInterface>>,
+}
+
+struct Registration {
+    guid: EfiGuid,
+    event: EfiEvent,
+    pending: VecDeque<EfiHandle>,
+}
+
+/// Handles and the protocol interfaces installed on them.
+///
+/// Handles and registration keys are the addresses of their boxed entries.
+#[derive(Default)]
+pub(crate) struct HandleDatabase {
+    handles: Vec<Box<Handle>>,
+    registrations: Vec<Box<Registration>>,
+}
+
+impl HandleDatabase {
+    fn handle_of(entry: &Handle) -> EfiHandle {
+        entry as *const Handle as EfiHandle
+    }
+
+    fn position(&self, handle: EfiHandle) -> Option<usize> {
+        self.handles
+            .iter()
+            .position(|entry: &Box<Handle>| Self::handle_of(entry) == handle)
+    }
+
+    fn protocol(&self, handle: EfiHandle, guid: &EfiGuid) -> Result<&ProtocolInterface, EfiStatus> {
+        let index: usize = self.position(handle).ok_or(EFI_INVALID_PARAMETER)?;
+
+        self.handles[index]
+            .protocols
+            .iter()
+            .find(|protocol: &&Box<ProtocolInterface>| protocol.guid == *guid)
+            .map(|protocol: &Box<ProtocolInterface>| &**protocol)
+            .ok_or(EFI_UNSUPPORTED)
+    }
+
+    fn protocol_mut(
+        &mut self,
+        handle: EfiHandle,
+        guid: &EfiGuid,
+    ) -> Result<&mut ProtocolInterface, EfiStatus> {
+        let index: usize = self.position(handle).ok_or(EFI_INVALID_PARAMETER)?;
+
+        self.handles[index]
+            .protocols
+            .iter_mut()
+            .find(|protocol: &&mut Box<ProtocolInterface>| protocol.guid == *guid)
+            .map(|protocol: &mut Box<ProtocolInterface>| &mut **protocol)
+            .ok_or(EFI_UNSUPPORTED)
+    }
+
+    /// Queues the handle for every registration of the protocol and returns the events to signal.
+    fn notify(&mut self, handle: EfiHandle, guid: &EfiGuid) -> Vec<EfiEvent> {
+        self.registrations
+            .iter_mut()
+            .filter(|registration: &&mut Box<Registration>| registration.guid == *guid)
+            .map(|registration: &mut Box<Registration>| {
+                registration.pending.push_back(handle);
+
+                registration.event
+            })
+            .collect()
+    }
+
+    pub(crate) fn contains(&self, handle: EfiHandle) -> bool {
+        self.position(handle).is_some()
+    }
+
+    /// Installs the interface, creating a new handle when `handle` is null.
+    ///
+    /// Returns the handle and the protocol notification events that have to be signalled.
+    pub(crate) fn install(
+        &mut self,
+        handle: EfiHandle,
+        guid: &EfiGuid,
+        interface: VoidPtr,
+    ) -> Result<(EfiHandle, Vec<EfiEvent>), EfiStatus> {
+        let protocol: Box<ProtocolInterface> = Box::new(ProtocolInterface {
+            guid: *guid,
+            interface,
+            open_list: Vec::new(),
+        });
+
+        let handle: EfiHandle = if handle.is_null() {
+            self.handles.push(Box::new(Handle {
+                protocols: vec![protocol],
+            }));
+
+            Self::handle_of(self.handles.last().unwrap())
+        } else {
+            let index: usize = self.position(handle).ok_or(EFI_INVALID_PARAMETER)?;
+
+            if self.protocol(handle, guid).is_ok() {
+                return Err(EFI_INVALID_PARAMETER);
+            }
+
+            self.handles[index].protocols.push(protocol);
+
+            handle
+        };
+
+        Ok((handle, self.notify(handle, guid)))
+    }
+
+    pub(crate) fn reinstall(
+        &mut self,
+        handle: EfiHandle,
+        guid: &EfiGuid,
+        old_interface: VoidPtr,
+        new_interface: VoidPtr,
+    ) -> Result<Vec<EfiEvent>, EfiStatus> {
+        let protocol: &mut ProtocolInterface =
+            self.protocol_mut(handle, guid).map_err(|_| EFI_NOT_FOUND)?;
+
+        if protocol.interface != old_interface {
+            return Err(EFI_NOT_FOUND);
+        }
+
+        if protocol
+            .open_list
+            .iter()
+            .any(|entry: &OpenProtocolInformationEntry| {
+                entry.attributes & (BY_DRIVER | EXCLUSIVE) != 0
+            })
+        {
+            return Err(EFI_ACCESS_DENIED);
+        }
+
+        protocol.interface = new_interface;
+
+        Ok(self.notify(handle, guid))
+    }
+
+    pub(crate) fn uninstall(
+        &mut self,
+        handle: EfiHandle,
+        guid: &EfiGuid,
+        interface: VoidPtr,
+    ) -> Result<(), EfiStatus> {
+        let index: usize = self.position(handle).ok_or(EFI_INVALID_PARAMETER)?;
+        let protocols: &mut Vec<Box<ProtocolInterface>> = &mut self.handles[index].protocols;
+
+        let protocol: usize = protocols
+            .iter()
+            .position(|protocol: &Box<ProtocolInterface>| {
+                protocol.guid == *guid && protocol.interface == interface
+            })
+            .ok_or(EFI_NOT_FOUND)?;
+
+        if protocols[protocol]
+            .open_list
+            .iter()
+            .any(|entry: &OpenProtocolInformationEntry| {
+                entry.attributes & (BY_DRIVER | EXCLUSIVE) != 0
+            })
+        {
+            return Err(EFI_ACCESS_DENIED);
+        }
+
+        protocols.remove(protocol);
+
+        if protocols.is_empty() {
+            self.handles.remove(index);
+
+            for registration in self.registrations.iter_mut() {
+                registration
+                    .pending
+                    .retain(|&pending: &EfiHandle| pending != handle);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn interface(
+        &self,
+        handle: EfiHandle,
+        guid: &EfiGuid,
+    ) -> Result<VoidPtr, EfiStatus> {
+        self.protocol(handle, guid)
+            .map(|protocol: &ProtocolInterface| protocol.interface)
+    }
+
+    pub(crate) fn register_notify(&mut self, guid: &EfiGuid, event: EfiEvent) -> VoidPtr {
+        self.registrations.push(Box::new(Registration {
+            guid: *guid,
+            event,
+            pending: VecDeque::new(),
+        }));
+
+        &**self.registrations.last().unwrap() as *const Registration as VoidPtr
+    }
+
+    fn registration_mut(&mut self, registration: VoidPtr) -> Option<&mut Registration> {
+        self.registrations
+            .iter_mut()
+            .find(|entry: &&mut Box<Registration>| {
+                &***entry as *const Registration as VoidPtr == registration
+            })
+            .map(|entry: &mut Box<Registration>| &mut **entry)
+    }
+
+    /// Returns the handles matching the search. Registration queues are left untouched.
+    pub(crate) fn locate(
+        &mut self,
+        search_type: u32,
+        guid: Option<&EfiGuid>,
+        search_key: VoidPtr,
+    ) -> Result<Vec<EfiHandle>, EfiStatus> {
+        let handles: Vec<EfiHandle> = match (search_type, guid) {
+            (ALL_HANDLES, _) => self
+                .handles
+                .iter()
+                .map(|entry: &Box<Handle>| Self::handle_of(entry))
+                .collect(),
+            (BY_PROTOCOL, Some(guid)) => self
+                .handles
+                .iter()
+                .filter(|entry: &&Box<Handle>| {
+                    entry
+                        .protocols
+                        .iter()
+                        .any(|protocol: &Box<ProtocolInterface>| protocol.guid == *guid)
+                })
+                .map(|entry: &Box<Handle>| Self::handle_of(entry))
+                .collect(),
+            (BY_REGISTER_NOTIFY, _) => self
+                .registration_mut(search_key)
+                .ok_or(EFI_INVALID_PARAMETER)?
+                .pending
+                .front()
+                .cloned()
+                .into_iter()
+                .collect(),
+            _ => return Err(EFI_INVALID_PARAMETER),
+        };
+
+        if handles.is_empty() {
+            Err(EFI_NOT_FOUND)
+        } else {
+            Ok(handles)
+        }
+    }
+
+    /// Removes the handle returned by the last successful `ByRegisterNotify` search.
+    pub(crate) fn consume_registration(&mut self, registration: VoidPtr) -> Option<EfiHandle> {
+        self.registration_mut(registration)
+            .and_then(|registration: &mut Registration| registration.pending.pop_front())
+    }
+
+    /// Returns the first interface of the protocol or the next one announced to the registration.
+    pub(crate) fn locate_protocol(
+        &mut self,
+        guid: &EfiGuid,
+        registration: VoidPtr,
+    ) -> Result<VoidPtr, EfiStatus> {
+        if registration.is_null() {
+            let handles: Vec<EfiHandle> = self.locate(BY_PROTOCOL, Some(guid), registration)?;
+
+            self.interface(handles[0], guid)
+        } else {
+            let handle: EfiHandle = self
+                .consume_registration(registration)
+                .ok_or(EFI_NOT_FOUND)?;
+
+            self.interface(handle, guid).map_err(|_| EFI_NOT_FOUND)
+        }
+    }
+
+    /// Returns every handle carrying the protocol together with it's interface.
+    pub(crate) fn interfaces(&self, guid: &EfiGuid) -> Vec<(EfiHandle, VoidPtr)> {
+        self.handles
+            .iter()
+            .filter_map(|entry: &Box<Handle>| {
+                entry
+                    .protocols
+                    .iter()
+                    .find(|protocol: &&Box<ProtocolInterface>| protocol.guid == *guid)
+                    .map(|protocol: &Box<ProtocolInterface>| {
+                        (Self::handle_of(entry), protocol.interface)
+                    })
+            })
+            .collect()
+    }
+
+    pub(crate) fn protocols(&self, handle: EfiHandle) -> Result<Vec<*const EfiGuid>, EfiStatus> {
+        let index: usize = self.position(handle).ok_or(EFI_INVALID_PARAMETER)?;
+
+        Ok(self.handles[index]
+            .protocols
+            .iter()
+            .map(|protocol: &Box<ProtocolInterface>| &protocol.guid as *const EfiGuid)
+            .collect())
+    }
+
+    pub(crate) fn open(
+        &mut self,
+        handle: EfiHandle,
+        guid: &EfiGuid,
+        agent_handle: EfiHandle,
+        controller_handle: EfiHandle,
+        attributes: u32,
+    ) -> Result<VoidPtr, EfiStatus> {
+        let valid_agent: bool = self.contains(agent_handle);
+        let valid_controller: bool = self.contains(controller_handle);
+
+        let valid: bool = match attributes {
+            BY_HANDLE_PROTOCOL | GET_PROTOCOL | TEST_PROTOCOL => true,
+            BY_CHILD_CONTROLLER => valid_agent && valid_controller && controller_handle != handle,
+            BY_DRIVER | 0x30 => valid_agent && valid_controller,
+            EXCLUSIVE => valid_agent,
+            _ => false,
+        };
+
+        if !valid {
+            return Err(EFI_INVALID_PARAMETER);
+        }
+
+        let protocol: &mut ProtocolInterface = self.protocol_mut(handle, guid)?;
+
+        if attributes == TEST_PROTOCOL {
+            return Ok(protocol.interface);
+        }
+
+        if attributes & (BY_DRIVER | EXCLUSIVE) != 0 {
+            if let Some(entry) =
+                protocol
+                    .open_list
+                    .iter()
+                    .find(|entry: &&OpenProtocolInformationEntry| {
+                        entry.attributes & (BY_DRIVER | EXCLUSIVE) != 0
+                    })
+            {
+                return Err(
+                    if entry.agent_handle == agent_handle && entry.attributes == attributes {
+                        EFI_ALREADY_STARTED
+                    } else {
+                        EFI_ACCESS_DENIED
+                    },
+                );
+            }
+        }
+
+        match protocol
+            .open_list
+            .iter_mut()
+            .find(|entry: &&mut OpenProtocolInformationEntry| {
+                entry.agent_handle == agent_handle
+                    && entry.controller_handle == controller_handle
+                    && entry.attributes == attributes
+            }) {
+            Some(entry) => entry.open_count += 1,
+            None => protocol.open_list.push(OpenProtocolInformationEntry {
+                agent_handle,
+                controller_handle,
+                attributes,
+                open_count: 1,
+            }),
+        }
+
+        Ok(protocol.interface)
+    }
+
+    pub(crate) fn close(
+        &mut self,
+        handle: EfiHandle,
+        guid: &EfiGuid,
+        agent_handle: EfiHandle,
+        controller_handle: EfiHandle,
+    ) -> Result<(), EfiStatus> {
+        if !self.contains(agent_handle)
+            || !(controller_handle.is_null() || self.contains(controller_handle))
+        {
+            return Err(EFI_INVALID_PARAMETER);
+        }
+
+        let protocol: &mut ProtocolInterface =
+            self.protocol_mut(handle, guid)
+                .map_err(|status: EfiStatus| {
+                    if status == EFI_UNSUPPORTED {
+                        EFI_NOT_FOUND
+                    } else {
+                        status
+                    }
+                })?;
+
+        let count: usize = protocol.open_list.len();
+
+        protocol
+            .open_list
+            .retain(|entry: &OpenProtocolInformationEntry| {
+                entry.agent_handle != agent_handle || entry.controller_handle != controller_handle
+            });
+
+        if protocol.open_list.len() == count {
+            Err(EFI_NOT_FOUND)
+        } else {
+            Ok(())
+        }
+    }
+
+    pub(crate) fn open_information(
+        &self,
+        handle: EfiHandle,
+        guid: &EfiGuid,
+    ) -> Result<Vec<OpenProtocolInformationEntry>, EfiStatus> {
+        self.protocol(handle, guid)
+            .map(|protocol: &ProtocolInterface| protocol.open_list.clone())
+            .map_err(|_| EFI_NOT_FOUND)
+    }
+}
@@ -0,0 +1,375 @@
+use {
+    crate::{
+        boot_services::{service, EFI_BOOT_SERVICES_DATA},
+        firmware::Firmware,
+        network::{complete, SimulatedNetwork},
+        status::{
+            EFI_ACCESS_DENIED, EFI_ALREADY_STARTED, EFI_INVALID_PARAMETER, EFI_NOT_FOUND,
+            EFI_NOT_STARTED, EFI_SUCCESS, EFI_UNSUPPORTED,
+        },
+    },
+    efi::{EfiEvent, EfiStatus, VoidMutPtr},
+    std::{cell::RefCell, mem::size_of, ptr::copy_nonoverlapping, rc::Rc, slice::from_raw_parts},
+};
+
+/* EFI_HTTP_VERSION */
+const HTTP_VERSION_11: u32 = 1;
+
+/* EFI_HTTP_METHOD */
+const HTTP_METHOD_GET: u32 = 0;
+const HTTP_METHOD_HEAD: u32 = 5;
+
+/* EFI_HTTP_STATUS_CODE */
+const HTTP_STATUS_200_OK: u32 = 3;
+const HTTP_STATUS_404_NOT_FOUND: u32 = 21;
+
+#[repr(C)]
+struct HttpConfigData {
+    http_version: u32,
+    timeout_milliseconds: u32,
+    local_address_is_ip_v6: bool,
+    access_point: VoidMutPtr,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct HttpV4AccessPoint {
+    use_default_address: bool,
+    local_address: [u8; 4],
+    local_subnet: [u8; 4],
+    local_port: u16,
+}
+
+#[repr(C)]
+struct HttpToken {
+    event: EfiEvent,
+    status: EfiStatus,
+    message: *mut HttpMessage,
+}
+
+#[repr(C)]
+struct HttpMessage {
+    data: VoidMutPtr,
+    header_count: usize,
+    headers: *mut HttpHeader,
+    body_length: usize,
+    body: VoidMutPtr,
+}
+
+#[repr(C)]
+struct HttpRequestData {
+    method: u32,
+    url: *const u16,
+}
+
+#[repr(C)]
+struct HttpResponseData {
+    status_code: u32,
+}
+
+#[repr(C)]
+struct HttpHeader {
+    field_name: *const u8,
+    field_value: *const u8,
+}
+
+/// Response to the last request, handed out over one or more response tokens.
+struct PendingResponse {
+    status_code: u32,
+    content_length: usize,
+    body: Vec<u8>,
+    headers_received: bool,
+}
+
+#[derive(Default)]
+struct HttpState {
+    config: Option<(u32, u32, HttpV4AccessPoint)>,
+    response: Option<PendingResponse>,
+}
+
+/// Simulated IPv4-only `EFI_HTTP_PROTOCOL` instance, answering `GET` and `HEAD` requests from the network's resources.
+///
+/// Requests and responses complete immediately, so there's never a token to cancel.
+#[repr(C)]
+pub(crate) struct Http {
+    get_mode_data: extern "efiapi" fn(*const Self, *mut HttpConfigData) -> EfiStatus,
+    configure: extern "efiapi" fn(*const Self, *const HttpConfigData) -> EfiStatus,
+    request: extern "efiapi" fn(*const Self, *mut HttpToken) -> EfiStatus,
+    cancel: extern "efiapi" fn(*const Self, *mut HttpToken) -> EfiStatus,
+    response: extern "efiapi" fn(*const Self, *mut HttpToken) -> EfiStatus,
+    poll: extern "efiapi" fn(*const Self) -> EfiStatus,
+    network: Rc<SimulatedNetwork>,
+    state: RefCell<HttpState>,
+}
+
+impl Http {
+    pub(crate) fn new(network: Rc<SimulatedNetwork>) -> Box<Self> {
+        Box::new(Self {
+            get_mode_data: http_get_mode_data,
+            configure: http_configure,
+            request: http_request,
+            cancel: http_cancel,
+            response: http_response,
+            poll: http_poll,
+            network,
+            state: RefCell::new(HttpState::default()),
+        })
+    }
+
+    /// Forgets the configuration and the pending response.
+    pub(crate) fn reset(&self) {
+        *self.state.borrow_mut() = HttpState::default();
+    }
+}
+
+/// Returns the message of a token which can be queued, which needs an event.
+fn validate_token(
+    token: *mut HttpToken,
+) -> Result<(&'static mut HttpToken, &'static mut HttpMessage), EfiStatus> {
+    let token: &mut HttpToken = match unsafe { token.as_mut() } {
+        Some(token) if !token.event.is_null() => token,
+        _ => return Err(EFI_INVALID_PARAMETER),
+    };
+
+    let message: &mut HttpMessage =
+        unsafe { token.message.as_mut() }.ok_or(EFI_INVALID_PARAMETER)?;
+
+    Ok((token, message))
+}
+
+/// Copies the headers into a single pool allocation, the header array followed by the null-terminated strings, so it's released by freeing the array.
+fn allocate_headers(
+    firmware: &mut Firmware,
+    headers: &[(&str, String)],
+) -> Result<*mut HttpHeader, EfiStatus> {
+    let array_size: usize = headers.len() * size_of::<HttpHeader>();
+
+    let mut block: Vec<u8> = vec![0; array_size];
+    let mut offsets: Vec<(usize, usize)> = Vec::with_capacity(headers.len());
+
+    for (name, value) in headers {
+        let name_offset: usize = block.len();
+
+        block.extend_from_slice(name.as_bytes());
+        block.push(0);
+
+        let value_offset: usize = block.len();
+
+        block.extend_from_slice(value.as_bytes());
+        block.push(0);
+
+        offsets.push((name_offset, value_offset));
+    }
+
+    let base: *mut u8 = firmware
+        .memory
+        .allocate_pool_from(EFI_BOOT_SERVICES_DATA, &block)?;
+
+    for (index, &(name_offset, value_offset)) in offsets.iter().enumerate() {
+        unsafe {
+            (base as *mut HttpHeader).add(index).write(HttpHeader {
+                field_name: base.add(name_offset),
+                field_value: base.add(value_offset),
+            })
+        };
+    }
+
+    Ok(base as *mut HttpHeader)
+}
+
+extern "efiapi" fn http_get_mode_data(
+    this: *const Http,
+    config_data: *mut HttpConfigData,
+) -> EfiStatus {
+    let state = unsafe { &*this }.state.borrow();
+
+    let (http_version, timeout_milliseconds, access_point): (u32, u32, HttpV4AccessPoint) =
+        match state.config {
+            Some(config) => config,
+            None => return EFI_NOT_STARTED,
+        };
+
+    let config_data: &mut HttpConfigData = match unsafe { config_data.as_mut() } {
+        Some(config_data) if !config_data.access_point.is_null() => config_data,
+        _ => return EFI_INVALID_PARAMETER,
+    };
+
+    config_data.http_version = http_version;
+    config_data.timeout_milliseconds = timeout_milliseconds;
+    config_data.local_address_is_ip_v6 = false;
+
+    unsafe { *(config_data.access_point as *mut HttpV4AccessPoint) = access_point };
+
+    EFI_SUCCESS
+}
+
+extern "efiapi" fn http_configure(
+    this: *const Http,
+    config_data: *const HttpConfigData,
+) -> EfiStatus {
+    let this: &Http = unsafe { &*this };
+
+    let config_data: &HttpConfigData = match unsafe { config_data.as_ref() } {
+        Some(config_data) => config_data,
+        None => {
+            this.reset();
+
+            return EFI_SUCCESS;
+        }
+    };
+
+    if config_data.access_point.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    if config_data.local_address_is_ip_v6 || config_data.http_version > HTTP_VERSION_11 {
+        return EFI_UNSUPPORTED;
+    }
+
+    let mut state = this.state.borrow_mut();
+
+    if state.config.is_some() {
+        return EFI_ALREADY_STARTED;
+    }
+
+    let mut access_point: HttpV4AccessPoint =
+        unsafe { *(config_data.access_point as *const HttpV4AccessPoint) };
+
+    if access_point.use_default_address {
+        access_point.local_address = this.network.station_ip();
+        access_point.local_subnet = this.network.subnet_mask();
+    }
+
+    state.config = Some((
+        config_data.http_version,
+        config_data.timeout_milliseconds,
+        access_point,
+    ));
+
+    EFI_SUCCESS
+}
+
+extern "efiapi" fn http_request(this: *const Http, token: *mut HttpToken) -> EfiStatus {
+    let this: &Http = unsafe { &*this };
+
+    service(|firmware: &mut Firmware| {
+        let mut state = this.state.borrow_mut();
+
+        if state.config.is_none() {
+            return Err(EFI_NOT_STARTED);
+        }
+
+        let (token, message): (&mut HttpToken, &mut HttpMessage) = validate_token(token)?;
+
+        let request: &HttpRequestData =
+            unsafe { (message.data as *const HttpRequestData).as_ref() }
+                .filter(|request: &&HttpRequestData| !request.url.is_null())
+                .ok_or(EFI_INVALID_PARAMETER)?;
+
+        let url: String = {
+            let mut length: usize = 0;
+
+            while unsafe { *request.url.add(length) } != 0 {
+                length += 1;
+            }
+
+            String::from_utf16(unsafe { from_raw_parts(request.url, length) })
+                .map_err(|_| EFI_INVALID_PARAMETER)?
+        };
+
+        if request.method != HTTP_METHOD_GET && request.method != HTTP_METHOD_HEAD {
+            return Err(EFI_UNSUPPORTED);
+        }
+
+        let (status_code, body): (u32, &[u8]) = match this.network.resource(&url) {
+            Some(body) => (HTTP_STATUS_200_OK, body),
+            None => (HTTP_STATUS_404_NOT_FOUND, &[]),
+        };
+
+        /* A new request discards what's left of the previous response */
+        state.response = Some(PendingResponse {
+            status_code,
+            content_length: body.len(),
+            body: if request.method == HTTP_METHOD_GET {
+                body.to_vec()
+            } else {
+                Vec::new()
+            },
+            headers_received: false,
+        });
+
+        complete(firmware, token.event, &mut token.status, EFI_SUCCESS);
+
+        Ok(())
+    })
+}
+
+extern "efiapi" fn http_response(this: *const Http, token: *mut HttpToken) -> EfiStatus {
+    let this: &Http = unsafe { &*this };
+
+    service(|firmware: &mut Firmware| {
+        let mut state = this.state.borrow_mut();
+
+        if state.config.is_none() {
+            return Err(EFI_NOT_STARTED);
+        }
+
+        let (token, message): (&mut HttpToken, &mut HttpMessage) = validate_token(token)?;
+
+        /* There's no connection without a request */
+        let response: &mut PendingResponse = state.response.as_mut().ok_or(EFI_ACCESS_DENIED)?;
+
+        if !response.headers_received {
+            let response_data: &mut HttpResponseData =
+                unsafe { (message.data as *mut HttpResponseData).as_mut() }
+                    .ok_or(EFI_INVALID_PARAMETER)?;
+
+            message.headers = allocate_headers(
+                firmware,
+                &[("Content-Length", response.content_length.to_string())],
+            )?;
+            message.header_count = 1;
+            response_data.status_code = response.status_code;
+            response.headers_received = true;
+        }
+
+        let length: usize = message.body_length.min(response.body.len());
+
+        if length != 0 {
+            if message.body.is_null() {
+                return Err(EFI_INVALID_PARAMETER);
+            }
+
+            unsafe { copy_nonoverlapping(response.body.as_ptr(), message.body as *mut u8, length) };
+        }
+
+        response.body.drain(..length);
+        message.body_length = length;
+
+        if response.body.is_empty() {
+            state.response = None;
+        }
+
+        complete(firmware, token.event, &mut token.status, EFI_SUCCESS);
+
+        Ok(())
+    })
+}
+
+extern "efiapi" fn http_cancel(this: *const Http, token: *mut HttpToken) -> EfiStatus {
+    if unsafe { &*this }.state.borrow().config.is_none() {
+        EFI_NOT_STARTED
+    } else if token.is_null() {
+        EFI_SUCCESS
+    } else {
+        EFI_NOT_FOUND
+    }
+}
+
+extern "efiapi" fn http_poll(this: *const Http) -> EfiStatus {
+    if unsafe { &*this }.state.borrow().config.is_none() {
+        EFI_NOT_STARTED
+    } else {
+        EFI_SUCCESS
+    }
+}
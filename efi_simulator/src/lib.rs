@@ -0,0 +1,54 @@
+/* The simulator runs on the host; When building for UEFI the crate is empty */
+#![cfg_attr(target_os = "uefi", no_std)]
+#![cfg(not(target_os = "uefi"))]
+/* Handles, events and registrations are boxed so their addresses can identify them */
+#![allow(clippy::vec_box, clippy::borrowed_box)]
+#![doc(html_no_source)]
+#![forbid(warnings)]
+/* Enables 'extern "efiapi"' */
+#![feature(abi_efiapi)]
+/* Enables "!" (never) type */
+#![feature(never_type)]
+
+mod boot_services;
+mod console;
+mod disk;
+mod display;
+mod events;
+mod firmware;
+mod firmware_management;
+mod handles;
+mod http;
+mod memory;
+mod network;
+mod nvdimm;
+mod nvme;
+mod pci;
+mod pointer;
+mod pxe;
+mod ram_disk;
+mod rng;
+mod runtime_services;
+mod serial;
+mod simulator;
+mod status;
+mod tables;
+mod tcp4;
+mod timestamp;
+mod udp4;
+mod usb;
+mod variables;
+
+pub use disk::SimulatedDisk;
+pub use firmware_management::SimulatedFirmwareImage;
+pub use network::SimulatedNetwork;
+pub use nvdimm::SimulatedNvdimm;
+pub use nvme::SimulatedNvmeController;
+pub use pci::SimulatedPciFunction;
+pub use pxe::SimulatedPxeServer;
+pub use simulator::{Simulator, SimulatorBuilder};
+pub use usb::SimulatedUsbDevice;
+pub use variables::{
+    EFI_VARIABLE_APPEND_WRITE, EFI_VARIABLE_BOOTSERVICE_ACCESS, EFI_VARIABLE_NON_VOLATILE,
+    EFI_VARIABLE_RUNTIME_ACCESS,
+};
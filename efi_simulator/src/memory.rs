@@ -0,0 +1,228 @@
+use {
+    crate::status::{EFI_INVALID_PARAMETER, EFI_NOT_FOUND, EFI_OUT_OF_RESOURCES},
+    efi::EfiStatus,
+    std::{
+        alloc::{alloc_zeroed, dealloc, Layout},
+        mem::{size_of, size_of_val},
+    },
+};
+
+pub(crate) const EFI_PAGE_SIZE: usize = 0x1000;
+
+/* Larger than the structure itself so consumers have to honour the reported descriptor size */
+pub(crate) const MEMORY_DESCRIPTOR_SIZE: usize = size_of::<MemoryDescriptor>() + 8;
+pub(crate) const MEMORY_DESCRIPTOR_VERSION: u32 = 1;
+
+const ALLOCATE_ANY_PAGES: u32 = 0;
+const ALLOCATE_MAX_ADDRESS: u32 = 1;
+const ALLOCATE_ADDRESS: u32 = 2;
+
+const EFI_CONVENTIONAL_MEMORY: u32 = 7;
+const EFI_PERSISTENT_MEMORY: u32 = 14;
+const EFI_MAX_MEMORY_TYPE: u32 = 15;
+const EFI_OEM_MEMORY_TYPE_START: u32 = 0x7000_0000;
+
+const EFI_MEMORY_WB: u64 = 0x8;
+
+const POOL_ALIGNMENT: usize = 8;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub(crate) struct MemoryDescriptor {
+    memory_type: u32,
+    physical_start: u64,
+    virtual_start: u64,
+    number_of_pages: u64,
+    attributes: u64,
+}
+
+struct Allocation {
+    address: *mut u8,
+    layout: Layout,
+    memory_type: u32,
+    pool: bool,
+}
+
+/// Page and pool allocations backed by the host's allocator.
+///
+/// Physical addresses are the host addresses of the allocations.
+pub(crate) struct Memory {
+    allocations: Vec<Allocation>,
+    map_key: usize,
+}
+
+impl Memory {
+    pub(crate) fn new() -> Self {
+        Self {
+            allocations: Vec::new(),
+            map_key: 1,
+        }
+    }
+
+    fn validate_type(memory_type: u32, pages: bool) -> Result<(), EfiStatus> {
+        if (EFI_MAX_MEMORY_TYPE..EFI_OEM_MEMORY_TYPE_START).contains(&memory_type)
+            || (pages
+                && (memory_type == EFI_CONVENTIONAL_MEMORY || memory_type == EFI_PERSISTENT_MEMORY))
+        {
+            Err(EFI_INVALID_PARAMETER)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn allocate(
+        &mut self,
+        layout: Layout,
+        memory_type: u32,
+        pool: bool,
+    ) -> Result<*mut u8, EfiStatus> {
+        let address: *mut u8 = unsafe { alloc_zeroed(layout) };
+
+        if address.is_null() {
+            return Err(EFI_OUT_OF_RESOURCES);
+        }
+
+        self.allocations.push(Allocation {
+            address,
+            layout,
+            memory_type,
+            pool,
+        });
+        self.map_key += 1;
+
+        Ok(address)
+    }
+
+    fn free(&mut self, address: *mut u8, pool: bool, size: Option<usize>) -> Result<(), EfiStatus> {
+        let index: usize = self
+            .allocations
+            .iter()
+            .position(|allocation: &Allocation| {
+                allocation.address == address
+                    && allocation.pool == pool
+                    && size
+                        .filter(|&size: &usize| allocation.layout.size() != size)
+                        .is_none()
+            })
+            .ok_or(if pool {
+                EFI_INVALID_PARAMETER
+            } else {
+                EFI_NOT_FOUND
+            })?;
+
+        let allocation: Allocation = self.allocations.swap_remove(index);
+
+        unsafe { dealloc(allocation.address, allocation.layout) };
+        self.map_key += 1;
+
+        Ok(())
+    }
+
+    pub(crate) fn allocate_pages(
+        &mut self,
+        allocate_type: u32,
+        memory_type: u32,
+        pages: usize,
+        address: u64,
+    ) -> Result<u64, EfiStatus> {
+        Self::validate_type(memory_type, true)?;
+
+        if pages == 0 {
+            return Err(EFI_INVALID_PARAMETER);
+        }
+
+        let layout: Layout = pages
+            .checked_mul(EFI_PAGE_SIZE)
+            .and_then(|size: usize| Layout::from_size_align(size, EFI_PAGE_SIZE).ok())
+            .ok_or(EFI_OUT_OF_RESOURCES)?;
+
+        match allocate_type {
+            ALLOCATE_ANY_PAGES => self
+                .allocate(layout, memory_type, false)
+                .map(|allocation: *mut u8| allocation as u64),
+            ALLOCATE_MAX_ADDRESS => {
+                let allocation: *mut u8 = self.allocate(layout, memory_type, false)?;
+
+                if allocation as u64 + (layout.size() as u64 - 1) > address {
+                    let _ = self.free(allocation, false, None);
+
+                    Err(EFI_NOT_FOUND)
+                } else {
+                    Ok(allocation as u64)
+                }
+            }
+            /* Placing allocations at fixed addresses isn't possible on the host */
+            ALLOCATE_ADDRESS => Err(EFI_NOT_FOUND),
+            _ => Err(EFI_INVALID_PARAMETER),
+        }
+    }
+
+    pub(crate) fn free_pages(&mut self, address: u64, pages: usize) -> Result<(), EfiStatus> {
+        self.free(
+            address as *mut u8,
+            false,
+            Some(pages.saturating_mul(EFI_PAGE_SIZE)),
+        )
+    }
+
+    pub(crate) fn allocate_pool(
+        &mut self,
+        memory_type: u32,
+        size: usize,
+    ) -> Result<*mut u8, EfiStatus> {
+        Self::validate_type(memory_type, false)?;
+
+        let layout: Layout = Layout::from_size_align(size.max(1), POOL_ALIGNMENT)
+            .map_err(|_| EFI_OUT_OF_RESOURCES)?;
+
+        self.allocate(layout, memory_type, true)
+    }
+
+    pub(crate) fn free_pool(&mut self, buffer: *mut u8) -> Result<(), EfiStatus> {
+        self.free(buffer, true, None)
+    }
+
+    /// Copies `data` into a new pool allocation, as services returning buffers do.
+    pub(crate) fn allocate_pool_from<T: Copy>(
+        &mut self,
+        memory_type: u32,
+        data: &[T],
+    ) -> Result<*mut T, EfiStatus> {
+        let buffer: *mut T = self.allocate_pool(memory_type, size_of_val(data))?.cast();
+
+        unsafe { buffer.copy_from_nonoverlapping(data.as_ptr(), data.len()) };
+
+        Ok(buffer)
+    }
+
+    /// The key changes every time an allocation is made or released.
+    pub(crate) fn map_key(&self) -> usize {
+        self.map_key
+    }
+
+    pub(crate) fn descriptors(&self) -> Vec<MemoryDescriptor> {
+        let mut descriptors: Vec<MemoryDescriptor> = self
+            .allocations
+            .iter()
+            .map(|allocation: &Allocation| MemoryDescriptor {
+                memory_type: allocation.memory_type,
+                physical_start: allocation.address as u64,
+                virtual_start: 0,
+                number_of_pages: allocation.layout.size().div_ceil(EFI_PAGE_SIZE) as u64,
+                attributes: EFI_MEMORY_WB,
+            })
+            .collect();
+
+        descriptors.sort_by_key(|descriptor: &MemoryDescriptor| descriptor.physical_start);
+
+        descriptors
+    }
+}
+
+impl Drop for Memory {
+    fn drop(&mut self) {
+        for allocation in self.allocations.drain(..) {
+            unsafe { dealloc(allocation.address, allocation.layout) };
+        }
+    }
+}
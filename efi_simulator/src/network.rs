@@ -0,0 +1,323 @@
+use {
+    crate::{
+        boot_services::{service, signal_all},
+        firmware::Firmware,
+        http::Http,
+        status::{EFI_ACCESS_DENIED, EFI_INVALID_PARAMETER, EFI_NOT_FOUND, EFI_UNSUPPORTED},
+        tcp4::Tcp4,
+        udp4::Udp4,
+    },
+    efi::{guids, EfiEvent, EfiGuid, EfiHandle, EfiStatus, VoidMutPtr, VoidPtr},
+    std::{
+        cell::RefCell,
+        ptr::copy_nonoverlapping,
+        rc::Rc,
+        slice::{from_raw_parts, from_raw_parts_mut},
+    },
+};
+
+/// First port assigned to instances configured with port 0.
+pub(crate) const EPHEMERAL_PORT: u16 = 49152;
+
+/// Network reached through the simulated UDP4, TCP4 and HTTP service bindings.
+///
+/// Echo hosts send UDP datagrams and the data of TCP connections back to the sender, while HTTP requests are answered from the attached resources.
+#[derive(Clone)]
+pub struct SimulatedNetwork {
+    station_ip: [u8; 4],
+    subnet_mask: [u8; 4],
+    echo_hosts: Vec<[u8; 4]>,
+    resources: Vec<(String, Vec<u8>)>,
+}
+
+impl SimulatedNetwork {
+    /// Creates an empty network, where instances using the default address get the given station address and subnet mask.
+    pub fn new(station_ip: [u8; 4], subnet_mask: [u8; 4]) -> Self {
+        Self {
+            station_ip,
+            subnet_mask,
+            echo_hosts: Vec::new(),
+            resources: Vec::new(),
+        }
+    }
+
+    /// Adds a host echoing UDP datagrams and TCP data on every port.
+    pub fn echo_host(mut self, address: [u8; 4]) -> Self {
+        self.echo_hosts.push(address);
+
+        self
+    }
+
+    /// Adds a resource served to `GET` requests of the URL. Requests of other URLs are answered with 404.
+    pub fn http_resource(mut self, url: &str, body: &[u8]) -> Self {
+        self.resources.push((url.to_string(), body.to_vec()));
+
+        self
+    }
+
+    pub(crate) fn station_ip(&self) -> [u8; 4] {
+        self.station_ip
+    }
+
+    pub(crate) fn subnet_mask(&self) -> [u8; 4] {
+        self.subnet_mask
+    }
+
+    pub(crate) fn is_echo_host(&self, address: [u8; 4]) -> bool {
+        self.echo_hosts.contains(&address)
+    }
+
+    pub(crate) fn resource(&self, url: &str) -> Option<&[u8]> {
+        self.resources
+            .iter()
+            .find(|(resource, _): &&(String, Vec<u8>)| resource == url)
+            .map(|(_, body): &(String, Vec<u8>)| body.as_slice())
+    }
+}
+
+/// `EFI_UDP4_FRAGMENT_DATA` and `EFI_TCP4_FRAGMENT_DATA`.
+#[repr(C)]
+pub(crate) struct FragmentData {
+    pub(crate) length: u32,
+    pub(crate) buffer: VoidMutPtr,
+}
+
+/// Collects the data of the fragments, which has to add up to `data_length`.
+pub(crate) fn gather(
+    fragments: *const FragmentData,
+    fragment_count: u32,
+    data_length: u32,
+) -> Result<Vec<u8>, EfiStatus> {
+    let fragments: &[FragmentData] = unsafe { from_raw_parts(fragments, fragment_count as usize) };
+
+    let mut data: Vec<u8> = Vec::with_capacity(data_length as usize);
+
+    for fragment in fragments {
+        if fragment.buffer.is_null() && fragment.length != 0 {
+            return Err(EFI_INVALID_PARAMETER);
+        }
+
+        if fragment.length != 0 {
+            data.extend_from_slice(unsafe {
+                from_raw_parts(fragment.buffer as *const u8, fragment.length as usize)
+            });
+        }
+    }
+
+    if data.len() == data_length as usize {
+        Ok(data)
+    } else {
+        Err(EFI_INVALID_PARAMETER)
+    }
+}
+
+/// Copies as much of the data as fits into the fragments, shortening them to the copied length.
+///
+/// Returns the number of bytes copied.
+pub(crate) fn scatter(fragments: *mut FragmentData, fragment_count: u32, data: &[u8]) -> usize {
+    let fragments: &mut [FragmentData] =
+        unsafe { from_raw_parts_mut(fragments, fragment_count as usize) };
+
+    let mut copied: usize = 0;
+
+    for fragment in fragments {
+        let length: usize = (fragment.length as usize).min(data.len() - copied);
+
+        if length != 0 {
+            unsafe {
+                copy_nonoverlapping(data[copied..].as_ptr(), fragment.buffer as *mut u8, length)
+            };
+        }
+
+        fragment.length = length as u32;
+        copied += length;
+    }
+
+    copied
+}
+
+/// Stores the status in a completion token and signals it's event.
+pub(crate) fn complete(
+    firmware: &mut Firmware,
+    event: EfiEvent,
+    status_field: *mut EfiStatus,
+    status: EfiStatus,
+) {
+    unsafe { *status_field = status };
+
+    let _ = firmware.events.signal(event);
+}
+
+/// Adds or deletes a route of a UDP4 or TCP4 instance.
+pub(crate) fn update_route(
+    routes: &mut Vec<[[u8; 4]; 3]>,
+    delete: bool,
+    subnet_address: *const [u8; 4],
+    subnet_mask: *const [u8; 4],
+    gateway_address: *const [u8; 4],
+) -> Result<(), EfiStatus> {
+    if subnet_address.is_null() || subnet_mask.is_null() || gateway_address.is_null() {
+        return Err(EFI_INVALID_PARAMETER);
+    }
+
+    let route: [[u8; 4]; 3] = unsafe { [*subnet_address, *subnet_mask, *gateway_address] };
+
+    let existing: Option<usize> = routes
+        .iter()
+        .position(|existing: &[[u8; 4]; 3]| *existing == route);
+
+    match (delete, existing) {
+        (false, None) => routes.push(route),
+        (false, Some(_)) => return Err(EFI_ACCESS_DENIED),
+        (true, Some(index)) => {
+            routes.remove(index);
+        }
+        (true, None) => return Err(EFI_NOT_FOUND),
+    }
+
+    Ok(())
+}
+
+#[derive(Clone, Copy)]
+pub(crate) enum ServiceKind {
+    Udp4,
+    Tcp4,
+    Http,
+}
+
+impl ServiceKind {
+    fn service_binding_guid(self) -> &'static EfiGuid {
+        match self {
+            Self::Udp4 => &guids::EFI_UDP4_SERVICE_BINDING_PROTOCOL,
+            Self::Tcp4 => &guids::EFI_TCP4_SERVICE_BINDING_PROTOCOL,
+            Self::Http => &guids::EFI_HTTP_SERVICE_BINDING_PROTOCOL,
+        }
+    }
+
+    fn protocol_guid(self) -> &'static EfiGuid {
+        match self {
+            Self::Udp4 => &guids::EFI_UDP4_PROTOCOL,
+            Self::Tcp4 => &guids::EFI_TCP4_PROTOCOL,
+            Self::Http => &guids::EFI_HTTP_PROTOCOL,
+        }
+    }
+}
+
+enum Child {
+    Udp4(Box<Udp4>),
+    Tcp4(Box<Tcp4>),
+    Http(Box<Http>),
+}
+
+impl Child {
+    fn interface(&self) -> VoidPtr {
+        match self {
+            Self::Udp4(udp4) => &**udp4 as *const Udp4 as VoidPtr,
+            Self::Tcp4(tcp4) => &**tcp4 as *const Tcp4 as VoidPtr,
+            Self::Http(http) => &**http as *const Http as VoidPtr,
+        }
+    }
+
+    /// Completes the child's outstanding tokens with `EFI_ABORTED` and releases what it delivered.
+    fn abort(&self, firmware: &mut Firmware) {
+        match self {
+            Self::Udp4(udp4) => udp4.reset(firmware),
+            Self::Tcp4(tcp4) => tcp4.reset(firmware),
+            Self::Http(http) => http.reset(),
+        }
+    }
+}
+
+/// Simulated `EFI_SERVICE_BINDING_PROTOCOL` creating UDP4, TCP4 or HTTP instances on the network.
+#[repr(C)]
+pub(crate) struct ServiceBinding {
+    create_child: extern "efiapi" fn(*const Self, *mut EfiHandle) -> EfiStatus,
+    destroy_child: extern "efiapi" fn(*const Self, EfiHandle) -> EfiStatus,
+    kind: ServiceKind,
+    network: Rc<SimulatedNetwork>,
+    children: RefCell<Vec<(EfiHandle, Child)>>,
+}
+
+impl ServiceBinding {
+    pub(crate) fn new(kind: ServiceKind, network: Rc<SimulatedNetwork>) -> Box<Self> {
+        Box::new(Self {
+            create_child: service_binding_create_child,
+            destroy_child: service_binding_destroy_child,
+            kind,
+            network,
+            children: RefCell::new(Vec::new()),
+        })
+    }
+
+    pub(crate) fn guid(&self) -> &'static EfiGuid {
+        self.kind.service_binding_guid()
+    }
+}
+
+extern "efiapi" fn service_binding_create_child(
+    this: *const ServiceBinding,
+    handle: *mut EfiHandle,
+) -> EfiStatus {
+    if handle.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    let this: &ServiceBinding = unsafe { &*this };
+
+    let network: Rc<SimulatedNetwork> = Rc::clone(&this.network);
+
+    let child: Child = match this.kind {
+        ServiceKind::Udp4 => Child::Udp4(Udp4::new(network)),
+        ServiceKind::Tcp4 => Child::Tcp4(Tcp4::new(network)),
+        ServiceKind::Http => Child::Http(Http::new(network)),
+    };
+
+    service(|firmware: &mut Firmware| {
+        let (child_handle, events): (EfiHandle, Vec<EfiEvent>) = firmware.handles.install(
+            unsafe { *handle },
+            this.kind.protocol_guid(),
+            child.interface(),
+        )?;
+
+        signal_all(firmware, events);
+
+        unsafe { *handle = child_handle };
+
+        this.children.borrow_mut().push((child_handle, child));
+
+        Ok(())
+    })
+}
+
+extern "efiapi" fn service_binding_destroy_child(
+    this: *const ServiceBinding,
+    handle: EfiHandle,
+) -> EfiStatus {
+    if handle.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    let this: &ServiceBinding = unsafe { &*this };
+
+    service(|firmware: &mut Firmware| {
+        let mut children = this.children.borrow_mut();
+
+        /* Handles without a child created by this service binding don't support it */
+        let index: usize = children
+            .iter()
+            .position(|(child_handle, _): &(EfiHandle, Child)| *child_handle == handle)
+            .ok_or(EFI_UNSUPPORTED)?;
+
+        firmware.handles.uninstall(
+            handle,
+            this.kind.protocol_guid(),
+            children[index].1.interface(),
+        )?;
+
+        let (_, child): (EfiHandle, Child) = children.remove(index);
+
+        child.abort(firmware);
+
+        Ok(())
+    })
+}
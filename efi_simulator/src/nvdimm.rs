@@ -0,0 +1,173 @@
+use {
+    crate::status::{EFI_BAD_BUFFER_SIZE, EFI_INVALID_PARAMETER, EFI_SUCCESS},
+    efi::EfiStatus,
+    std::{
+        cell::RefCell,
+        slice::{from_raw_parts, from_raw_parts_mut},
+    },
+};
+
+/// NVDIMM exposed through `EFI_NVDIMM_LABEL_PROTOCOL`, whose device path holds it's NFIT device handle.
+#[derive(Clone)]
+pub struct SimulatedNvdimm {
+    device_handle: u32,
+    label_storage: Vec<u8>,
+    max_transfer_length: u32,
+}
+
+impl SimulatedNvdimm {
+    /// Creates an NVDIMM with the given label storage area, which can be transferred at once.
+    pub fn new(device_handle: u32, label_storage: &[u8]) -> Self {
+        Self {
+            device_handle,
+            label_storage: label_storage.to_vec(),
+            max_transfer_length: 0,
+        }
+    }
+
+    /// Limits the number of bytes a single read or write can transfer, where zero means there's no limit.
+    pub fn max_transfer_length(mut self, max_transfer_length: u32) -> Self {
+        self.max_transfer_length = max_transfer_length;
+
+        self
+    }
+
+    pub(crate) fn label_storage_size(&self) -> usize {
+        self.label_storage.len()
+    }
+}
+
+#[repr(C)]
+pub(crate) struct NvdimmLabel {
+    label_storage_information: extern "efiapi" fn(*const Self, *mut u32, *mut u32) -> EfiStatus,
+    label_storage_read: extern "efiapi" fn(*const Self, u32, u32, *mut u8) -> EfiStatus,
+    label_storage_write: extern "efiapi" fn(*const Self, u32, u32, *const u8) -> EfiStatus,
+    label_storage: RefCell<Vec<u8>>,
+    max_transfer_length: u32,
+}
+
+impl NvdimmLabel {
+    /// Returns the current contents of the label storage area.
+    pub(crate) fn label_storage(&self) -> Vec<u8> {
+        self.label_storage.borrow().clone()
+    }
+
+    /// Checks the transfer's length and bounds, returning the range it covers.
+    fn range(&self, offset: u32, length: u32) -> Result<std::ops::Range<usize>, EfiStatus> {
+        if self.max_transfer_length != 0 && length > self.max_transfer_length {
+            return Err(EFI_BAD_BUFFER_SIZE);
+        }
+
+        let start: usize = offset as usize;
+        let end: usize = start + length as usize;
+
+        if end > self.label_storage.borrow().len() {
+            return Err(EFI_INVALID_PARAMETER);
+        }
+
+        Ok(start..end)
+    }
+}
+
+extern "efiapi" fn nvdimm_label_storage_information(
+    this: *const NvdimmLabel,
+    size_of_label_storage_area: *mut u32,
+    max_transfer_length: *mut u32,
+) -> EfiStatus {
+    if size_of_label_storage_area.is_null() || max_transfer_length.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    let this: &NvdimmLabel = unsafe { &*this };
+
+    unsafe {
+        *size_of_label_storage_area = this.label_storage.borrow().len() as u32;
+        *max_transfer_length = this.max_transfer_length;
+    }
+
+    EFI_SUCCESS
+}
+
+extern "efiapi" fn nvdimm_label_storage_read(
+    this: *const NvdimmLabel,
+    offset: u32,
+    length: u32,
+    buffer: *mut u8,
+) -> EfiStatus {
+    let this: &NvdimmLabel = unsafe { &*this };
+
+    if buffer.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    match this.range(offset, length) {
+        Ok(range) => {
+            let buffer: &mut [u8] = unsafe { from_raw_parts_mut(buffer, length as usize) };
+
+            buffer.copy_from_slice(&this.label_storage.borrow()[range]);
+
+            EFI_SUCCESS
+        }
+        Err(status) => status,
+    }
+}
+
+extern "efiapi" fn nvdimm_label_storage_write(
+    this: *const NvdimmLabel,
+    offset: u32,
+    length: u32,
+    buffer: *const u8,
+) -> EfiStatus {
+    let this: &NvdimmLabel = unsafe { &*this };
+
+    if buffer.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    match this.range(offset, length) {
+        Ok(range) => {
+            let buffer: &[u8] = unsafe { from_raw_parts(buffer, length as usize) };
+
+            this.label_storage.borrow_mut()[range].copy_from_slice(buffer);
+
+            EFI_SUCCESS
+        }
+        Err(status) => status,
+    }
+}
+
+/// The protocols installed on a simulated NVDIMM's handle.
+pub(crate) struct NvdimmProtocols {
+    pub(crate) label: Box<NvdimmLabel>,
+    pub(crate) device_path: Box<[u8]>,
+}
+
+impl NvdimmProtocols {
+    pub(crate) fn new(nvdimm: &SimulatedNvdimm) -> Self {
+        Self {
+            label: Box::new(NvdimmLabel {
+                label_storage_information: nvdimm_label_storage_information,
+                label_storage_read: nvdimm_label_storage_read,
+                label_storage_write: nvdimm_label_storage_write,
+                label_storage: RefCell::new(nvdimm.label_storage.clone()),
+                max_transfer_length: nvdimm.max_transfer_length,
+            }),
+            device_path: device_path(nvdimm.device_handle),
+        }
+    }
+}
+
+/// Builds an ACPI `_ADR` node holding the NFIT device handle, followed by an end node.
+fn device_path(device_handle: u32) -> Box<[u8]> {
+    const ACPI_DEVICE_PATH: u8 = 0x02;
+    const ACPI_ADR_DP: u8 = 0x03;
+    const ADR_NODE_LENGTH: u16 = 4 + 4;
+
+    let mut device_path: Vec<u8> = vec![ACPI_DEVICE_PATH, ACPI_ADR_DP];
+
+    device_path.extend_from_slice(&ADR_NODE_LENGTH.to_le_bytes());
+    device_path.extend_from_slice(&device_handle.to_le_bytes());
+    device_path.extend_from_slice(&[0x7F, 0xFF, 0x04, 0x00]);
+
+    device_path.into_boxed_slice()
+}
@@ -0,0 +1,466 @@
+use {
+    crate::{
+        boot_services::EFI_BOOT_SERVICES_DATA,
+        firmware::{with_firmware, Firmware},
+        status::{
+            EFI_BAD_BUFFER_SIZE, EFI_DEVICE_ERROR, EFI_INVALID_PARAMETER, EFI_NOT_FOUND,
+            EFI_SUCCESS, EFI_UNSUPPORTED,
+        },
+    },
+    efi::{EfiEvent, EfiStatus, VoidMutPtr, VoidPtr},
+    std::{
+        io,
+        slice::{from_raw_parts, from_raw_parts_mut},
+    },
+};
+
+const ATTRIBUTES_PHYSICAL: u32 = 0x01;
+const ATTRIBUTES_LOGICAL: u32 = 0x02;
+const ATTRIBUTES_CMD_SET_NVM: u32 = 0x08;
+
+const IO_ALIGN: u32 = 4;
+/* NVMe 1.4 */
+const NVME_VERSION: u32 = 0x0001_0400;
+
+const ALL_NAMESPACES: u32 = 0xFFFF_FFFF;
+
+const QUEUE_TYPE_ADMIN: u8 = 0;
+
+const OPCODE_GET_LOG_PAGE: u8 = 0x02;
+const OPCODE_IDENTIFY: u8 = 0x06;
+
+const CNS_NAMESPACE: u32 = 0x00;
+const CNS_CONTROLLER: u32 = 0x01;
+const CNS_ACTIVE_NAMESPACES: u32 = 0x02;
+
+const LOG_SMART_HEALTH_INFORMATION: u32 = 0x02;
+
+const IDENTIFY_SIZE: usize = 4096;
+const SMART_HEALTH_INFORMATION_SIZE: usize = 512;
+
+/* Status code types and codes reported in the completion's DW3 */
+const SCT_GENERIC: u32 = 0x0;
+const SCT_COMMAND_SPECIFIC: u32 = 0x1;
+const SC_INVALID_OPCODE: u32 = 0x01;
+const SC_INVALID_FIELD: u32 = 0x02;
+const SC_INVALID_NAMESPACE: u32 = 0x0B;
+const SC_INVALID_LOG_PAGE: u32 = 0x09;
+
+const MESSAGING_DEVICE_PATH: u8 = 0x03;
+const MSG_NVME_NAMESPACE_DP: u8 = 0x17;
+const NVME_NAMESPACE_NODE_LENGTH: u16 = 4 + 4 + 8;
+
+/* IEEE OUI the namespaces' EUI-64s are derived from */
+const EUI64_BASE: u64 = 0x0050_C2FF_FE00_0000;
+
+/// NVMe controller exposed through `EFI_NVM_EXPRESS_PASS_THRU_PROTOCOL`.
+///
+/// The controller answers the admin commands Identify and Get Log Page (SMART / Health Information);
+/// Namespaces have no backing storage.
+#[derive(Clone)]
+pub struct SimulatedNvmeController {
+    serial_number: String,
+    model_number: String,
+    firmware_revision: String,
+    namespaces: Vec<(u64, u32)>,
+    temperature: u16,
+    power_on_hours: u64,
+}
+
+impl SimulatedNvmeController {
+    /// Creates a controller without namespaces, at 35 °C.
+    pub fn new(serial_number: &str, model_number: &str) -> Self {
+        Self {
+            serial_number: String::from(serial_number),
+            model_number: String::from(model_number),
+            firmware_revision: String::from("1.0"),
+            namespaces: Vec::new(),
+            temperature: 308,
+            power_on_hours: 0,
+        }
+    }
+
+    pub fn firmware_revision(mut self, revision: &str) -> Self {
+        self.firmware_revision = String::from(revision);
+
+        self
+    }
+
+    /// Adds a namespace of the given number of blocks. Namespace IDs are assigned in the order they're added, starting from 1.
+    pub fn namespace(mut self, blocks: u64, block_size: u32) -> Self {
+        self.namespaces.push((blocks, block_size));
+
+        self
+    }
+
+    /// Sets the composite temperature reported in the SMART log, in Kelvin.
+    pub fn temperature(mut self, kelvin: u16) -> Self {
+        self.temperature = kelvin;
+
+        self
+    }
+
+    pub fn power_on_hours(mut self, hours: u64) -> Self {
+        self.power_on_hours = hours;
+
+        self
+    }
+
+    fn namespace_exists(&self, namespace_id: u32) -> bool {
+        namespace_id != 0 && namespace_id as usize <= self.namespaces.len()
+    }
+
+    fn identify_controller(&self) -> Vec<u8> {
+        let mut data: Vec<u8> = vec![0; IDENTIFY_SIZE];
+
+        /* PCI vendor and subsystem vendor IDs */
+        data[0..2].copy_from_slice(&0x1B36_u16.to_le_bytes());
+        data[2..4].copy_from_slice(&0x1AF4_u16.to_le_bytes());
+        ascii_field(&mut data[4..24], &self.serial_number);
+        ascii_field(&mut data[24..64], &self.model_number);
+        ascii_field(&mut data[64..72], &self.firmware_revision);
+        data[80..84].copy_from_slice(&NVME_VERSION.to_le_bytes());
+        data[516..520].copy_from_slice(&(self.namespaces.len() as u32).to_le_bytes());
+
+        data
+    }
+
+    fn identify_namespace(&self, namespace_id: u32) -> Vec<u8> {
+        let (blocks, block_size): (u64, u32) = self.namespaces[namespace_id as usize - 1];
+        let mut data: Vec<u8> = vec![0; IDENTIFY_SIZE];
+
+        /* Size, capacity and utilization */
+        for offset in (0..24).step_by(8) {
+            data[offset..offset + 8].copy_from_slice(&blocks.to_le_bytes());
+        }
+
+        /* A single LBA format without metadata, which is the formatted one */
+        data[25] = 0;
+        data[26] = 0;
+        data[130] = block_size.trailing_zeros() as u8;
+
+        data
+    }
+
+    fn active_namespaces(&self, namespace_id: u32) -> Vec<u8> {
+        let mut data: Vec<u8> = vec![0; IDENTIFY_SIZE];
+
+        for (index, id) in (namespace_id.saturating_add(1)..=self.namespaces.len() as u32)
+            .take(IDENTIFY_SIZE / 4)
+            .enumerate()
+        {
+            data[index * 4..index * 4 + 4].copy_from_slice(&id.to_le_bytes());
+        }
+
+        data
+    }
+
+    fn smart_log(&self) -> Vec<u8> {
+        let mut data: Vec<u8> = vec![0; SMART_HEALTH_INFORMATION_SIZE];
+
+        data[1..3].copy_from_slice(&self.temperature.to_le_bytes());
+        /* Available spare and it's threshold */
+        data[3] = 100;
+        data[4] = 10;
+        data[112..128].copy_from_slice(&1_u128.to_le_bytes());
+        data[128..144].copy_from_slice(&u128::from(self.power_on_hours).to_le_bytes());
+
+        data
+    }
+}
+
+/// Pads the string with spaces, as Identify's ASCII fields are.
+fn ascii_field(field: &mut [u8], string: &str) {
+    field.fill(b' ');
+
+    let length: usize = string.len().min(field.len());
+
+    field[..length].copy_from_slice(&string.as_bytes()[..length]);
+}
+
+#[repr(C)]
+struct NvmePassThruMode {
+    attributes: u32,
+    io_align: u32,
+    nvme_version: u32,
+}
+
+#[repr(C)]
+struct NvmeCommand {
+    cdw0: u32,
+    flags: u8,
+    nsid: u32,
+    cdw2: u32,
+    cdw3: u32,
+    cdw10: u32,
+    cdw11: u32,
+    cdw12: u32,
+    cdw13: u32,
+    cdw14: u32,
+    cdw15: u32,
+}
+
+#[repr(C)]
+struct NvmeCompletion {
+    dw0: u32,
+    dw1: u32,
+    dw2: u32,
+    dw3: u32,
+}
+
+#[repr(C)]
+struct NvmePassThruCommandPacket {
+    command_timeout: u64,
+    transfer_buffer: VoidMutPtr,
+    transfer_length: u32,
+    metadata_buffer: VoidMutPtr,
+    metadata_length: u32,
+    queue_type: u8,
+    nvme_cmd: *const NvmeCommand,
+    nvme_completion: *mut NvmeCompletion,
+}
+
+/// Simulated `EFI_NVM_EXPRESS_PASS_THRU_PROTOCOL` followed by the controller it passes commands to.
+#[repr(C)]
+pub(crate) struct NvmePassThru {
+    mode: *const NvmePassThruMode,
+    pass_thru:
+        extern "efiapi" fn(*const Self, u32, *mut NvmePassThruCommandPacket, EfiEvent) -> EfiStatus,
+    get_next_namespace: extern "efiapi" fn(*const Self, *mut u32) -> EfiStatus,
+    build_device_path: extern "efiapi" fn(*const Self, u32, *mut VoidMutPtr) -> EfiStatus,
+    get_namespace: extern "efiapi" fn(*const Self, VoidPtr, *mut u32) -> EfiStatus,
+    mode_data: NvmePassThruMode,
+    controller: SimulatedNvmeController,
+}
+
+impl NvmePassThru {
+    pub(crate) fn new(controller: &SimulatedNvmeController) -> io::Result<Box<Self>> {
+        if controller
+            .namespaces
+            .iter()
+            .any(|&(_, block_size): &(u64, u32)| block_size < 512 || !block_size.is_power_of_two())
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Block size has to be a power of two of at least 512!",
+            ));
+        }
+
+        let mut protocol: Box<Self> = Box::new(Self {
+            mode: 0 as _,
+            pass_thru: nvme_pass_thru,
+            get_next_namespace: nvme_get_next_namespace,
+            build_device_path: nvme_build_device_path,
+            get_namespace: nvme_get_namespace,
+            mode_data: NvmePassThruMode {
+                attributes: ATTRIBUTES_PHYSICAL | ATTRIBUTES_LOGICAL | ATTRIBUTES_CMD_SET_NVM,
+                io_align: IO_ALIGN,
+                nvme_version: NVME_VERSION,
+            },
+            controller: controller.clone(),
+        });
+
+        protocol.mode = &protocol.mode_data;
+
+        Ok(protocol)
+    }
+
+    /// Executes an admin command, returning the data it transfers or it's status code type and code.
+    fn admin_command(&self, command: &NvmeCommand) -> Result<Vec<u8>, (u32, u32)> {
+        let controller: &SimulatedNvmeController = &self.controller;
+
+        match command.cdw0 as u8 {
+            OPCODE_IDENTIFY => match command.cdw10 & 0xFF {
+                CNS_NAMESPACE if controller.namespace_exists(command.nsid) => {
+                    Ok(controller.identify_namespace(command.nsid))
+                }
+                CNS_NAMESPACE => Err((SCT_GENERIC, SC_INVALID_NAMESPACE)),
+                CNS_CONTROLLER => Ok(controller.identify_controller()),
+                CNS_ACTIVE_NAMESPACES => Ok(controller.active_namespaces(command.nsid)),
+                _ => Err((SCT_GENERIC, SC_INVALID_FIELD)),
+            },
+            OPCODE_GET_LOG_PAGE => {
+                /* Zero-based number of dwords, split across both dwords */
+                let dwords: usize = ((command.cdw10 >> 16) | (command.cdw11 << 16)) as usize + 1;
+
+                match command.cdw10 & 0xFF {
+                    LOG_SMART_HEALTH_INFORMATION => {
+                        let mut log: Vec<u8> = controller.smart_log();
+
+                        log.resize(dwords * 4, 0);
+
+                        Ok(log)
+                    }
+                    _ => Err((SCT_COMMAND_SPECIFIC, SC_INVALID_LOG_PAGE)),
+                }
+            }
+            _ => Err((SCT_GENERIC, SC_INVALID_OPCODE)),
+        }
+    }
+}
+
+extern "efiapi" fn nvme_pass_thru(
+    this: *const NvmePassThru,
+    _: u32,
+    packet: *mut NvmePassThruCommandPacket,
+    _: EfiEvent,
+) -> EfiStatus {
+    let this: &NvmePassThru = unsafe { &*this };
+
+    if packet.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    let packet: &mut NvmePassThruCommandPacket = unsafe { &mut *packet };
+
+    if packet.nvme_cmd.is_null()
+        || packet.nvme_completion.is_null()
+        || packet.transfer_buffer as usize & (IO_ALIGN as usize - 1) != 0
+    {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    let command: &NvmeCommand = unsafe { &*packet.nvme_cmd };
+
+    /* The namespaces have no backing storage, so there are no I/O commands to execute */
+    let result: Result<Vec<u8>, (u32, u32)> = if packet.queue_type == QUEUE_TYPE_ADMIN {
+        this.admin_command(command)
+    } else {
+        Err((SCT_GENERIC, SC_INVALID_OPCODE))
+    };
+
+    let (status, status_field): (EfiStatus, u32) = match result {
+        Ok(data) => {
+            let transfer_length: usize = packet.transfer_length as usize;
+
+            if transfer_length < data.len() || packet.transfer_buffer.is_null() {
+                packet.transfer_length = data.len() as u32;
+
+                return EFI_BAD_BUFFER_SIZE;
+            }
+
+            unsafe { from_raw_parts_mut(packet.transfer_buffer as *mut u8, data.len()) }
+                .copy_from_slice(&data);
+
+            packet.transfer_length = data.len() as u32;
+
+            (EFI_SUCCESS, 0)
+        }
+        Err((status_code_type, status_code)) => {
+            packet.transfer_length = 0;
+
+            (EFI_DEVICE_ERROR, status_code_type << 25 | status_code << 17)
+        }
+    };
+
+    unsafe {
+        *packet.nvme_completion = NvmeCompletion {
+            dw0: 0,
+            dw1: 0,
+            dw2: 0,
+            dw3: status_field,
+        }
+    };
+
+    status
+}
+
+extern "efiapi" fn nvme_get_next_namespace(
+    this: *const NvmePassThru,
+    namespace_id: *mut u32,
+) -> EfiStatus {
+    let controller: &SimulatedNvmeController = &unsafe { &*this }.controller;
+
+    if namespace_id.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    let namespace_id: &mut u32 = unsafe { &mut *namespace_id };
+
+    let next: u32 = match *namespace_id {
+        ALL_NAMESPACES => 1,
+        current if controller.namespace_exists(current) => current + 1,
+        _ => return EFI_INVALID_PARAMETER,
+    };
+
+    if controller.namespace_exists(next) {
+        *namespace_id = next;
+
+        EFI_SUCCESS
+    } else {
+        EFI_NOT_FOUND
+    }
+}
+
+extern "efiapi" fn nvme_build_device_path(
+    this: *const NvmePassThru,
+    namespace_id: u32,
+    device_path: *mut VoidMutPtr,
+) -> EfiStatus {
+    let controller: &SimulatedNvmeController = &unsafe { &*this }.controller;
+
+    if device_path.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    if !controller.namespace_exists(namespace_id) {
+        return EFI_NOT_FOUND;
+    }
+
+    let mut node: Vec<u8> = vec![MESSAGING_DEVICE_PATH, MSG_NVME_NAMESPACE_DP];
+
+    node.extend_from_slice(&NVME_NAMESPACE_NODE_LENGTH.to_le_bytes());
+    node.extend_from_slice(&namespace_id.to_le_bytes());
+    node.extend_from_slice(&(EUI64_BASE | u64::from(namespace_id)).to_le_bytes());
+
+    /* The caller is responsible for releasing the node */
+    match with_firmware(|firmware: &mut Firmware| {
+        firmware
+            .memory
+            .allocate_pool_from(EFI_BOOT_SERVICES_DATA, &node)
+    }) {
+        Ok(buffer) => {
+            unsafe { *device_path = buffer as VoidMutPtr };
+
+            EFI_SUCCESS
+        }
+        Err(status) => status,
+    }
+}
+
+extern "efiapi" fn nvme_get_namespace(
+    this: *const NvmePassThru,
+    device_path: VoidPtr,
+    namespace_id: *mut u32,
+) -> EfiStatus {
+    let controller: &SimulatedNvmeController = &unsafe { &*this }.controller;
+
+    if device_path.is_null() || namespace_id.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    let header: &[u8] = unsafe { from_raw_parts(device_path as *const u8, 4) };
+
+    if header[0] != MESSAGING_DEVICE_PATH
+        || header[1] != MSG_NVME_NAMESPACE_DP
+        || u16::from_le_bytes([header[2], header[3]]) != NVME_NAMESPACE_NODE_LENGTH
+    {
+        return EFI_UNSUPPORTED;
+    }
+
+    let node: &[u8] = unsafe {
+        from_raw_parts(
+            device_path as *const u8,
+            NVME_NAMESPACE_NODE_LENGTH as usize,
+        )
+    };
+    let id: u32 = u32::from_le_bytes([node[4], node[5], node[6], node[7]]);
+
+    if controller.namespace_exists(id) {
+        unsafe { *namespace_id = id };
+
+        EFI_SUCCESS
+    } else {
+        EFI_NOT_FOUND
+    }
+}
@@ -0,0 +1,1370 @@
+use {
+    crate::{
+        boot_services::EFI_BOOT_SERVICES_DATA,
+        firmware::{with_firmware, Firmware},
+        status::{into_status, EFI_INVALID_PARAMETER, EFI_SUCCESS, EFI_TIMEOUT, EFI_UNSUPPORTED},
+    },
+    efi::{EfiHandle, EfiStatus, VoidMutPtr, VoidPtr},
+    std::{
+        cell::{Cell, RefCell},
+        convert::TryFrom,
+        io,
+        ops::Range,
+        ptr::{copy_nonoverlapping, write_bytes},
+        rc::Rc,
+    },
+};
+
+/* Memory window of the root bridge, where the BARs are assigned */
+const MEMORY_WINDOW_BASE: u64 = 0xC000_0000;
+const MEMORY_WINDOW_SIZE: u64 = 0x1000_0000;
+
+/* Functions are the first function of consecutive devices on bus 0 */
+const MAX_DEVICES: usize = 32;
+
+/* Functions only have the conventional configuration space */
+const CONFIGURATION_SPACE_SIZE: usize = 0x100;
+
+const CONFIGURATION_VENDOR_ID: usize = 0x00;
+const CONFIGURATION_DEVICE_ID: usize = 0x02;
+const CONFIGURATION_COMMAND: usize = 0x04;
+const CONFIGURATION_CLASS_CODE: usize = 0x09;
+const CONFIGURATION_BAR_0: usize = 0x10;
+
+/* Command register bits */
+const COMMAND_MEMORY: u16 = 0x0002;
+const COMMAND_BUS_MASTER: u16 = 0x0004;
+
+/* EFI_PCI_ATTRIBUTE_* bits */
+const ATTRIBUTE_MEMORY_WRITE_COMBINE: u64 = 0x0080;
+const ATTRIBUTE_MEMORY: u64 = 0x0200;
+const ATTRIBUTE_BUS_MASTER: u64 = 0x0400;
+const ATTRIBUTE_MEMORY_CACHED: u64 = 0x0800;
+const ATTRIBUTE_DUAL_ADDRESS_CYCLE: u64 = 0x8000;
+
+/// Attributes `AllocateBuffer` accepts.
+const BUFFER_ATTRIBUTES: u64 =
+    ATTRIBUTE_MEMORY_WRITE_COMBINE | ATTRIBUTE_MEMORY_CACHED | ATTRIBUTE_DUAL_ADDRESS_CYCLE;
+
+/* EFI_PCI_IO_PROTOCOL_ATTRIBUTE_OPERATION */
+const ATTRIBUTE_OPERATION_GET: u32 = 0;
+const ATTRIBUTE_OPERATION_SET: u32 = 1;
+const ATTRIBUTE_OPERATION_ENABLE: u32 = 2;
+const ATTRIBUTE_OPERATION_DISABLE: u32 = 3;
+const ATTRIBUTE_OPERATION_SUPPORTED: u32 = 4;
+
+/* Last EFI_PCI_IO_PROTOCOL_OPERATION of functions and root bridges, which also map above 4 GiB */
+const OPERATION_BUS_MASTER_COMMON_BUFFER: u32 = 2;
+const OPERATION_BUS_MASTER_COMMON_BUFFER_64: u32 = 5;
+
+const ALLOCATE_ANY_PAGES: u32 = 0;
+const EFI_RUNTIME_SERVICES_DATA: u32 = 6;
+
+/* ACPI resource descriptors */
+const ACPI_ADDRESS_SPACE_DESCRIPTOR: u8 = 0x8A;
+const ACPI_END_TAG_DESCRIPTOR: u8 = 0x79;
+const RESOURCE_TYPE_MEMORY: u8 = 0;
+const RESOURCE_TYPE_BUS_NUMBER: u8 = 2;
+
+/// PCI function behind the simulated root bridge, exposed through `EFI_PCI_IO_PROTOCOL`.
+///
+/// The function has a single 32-bit memory BAR, BAR 0, backed by RAM and assigned an address in the root bridge's memory window.
+/// Memory decoding and bus mastering are disabled until they're enabled through the attributes.
+#[derive(Clone)]
+pub struct SimulatedPciFunction {
+    vendor_id: u16,
+    device_id: u16,
+    class_code: u32,
+    bar_size: usize,
+    option_rom: Option<Vec<u8>>,
+}
+
+impl SimulatedPciFunction {
+    /// Creates a function with a 4 KiB BAR and no option ROM. The class code holds the base class, sub-class and programming interface, from the highest byte.
+    pub fn new(vendor_id: u16, device_id: u16, class_code: u32) -> Self {
+        Self {
+            vendor_id,
+            device_id,
+            class_code,
+            bar_size: 0x1000,
+            option_rom: None,
+        }
+    }
+
+    /// Sets the size of BAR 0, which has to be a power of two of at least 16 bytes.
+    pub fn bar_size(mut self, size: usize) -> Self {
+        self.bar_size = size;
+
+        self
+    }
+
+    pub fn option_rom(mut self, image: &[u8]) -> Self {
+        self.option_rom = Some(image.to_vec());
+
+        self
+    }
+}
+
+/// Configuration space and BAR memory of a function, shared by it's `EFI_PCI_IO_PROTOCOL` and the root bridge.
+struct PciFunctionState {
+    configuration: [u8; CONFIGURATION_SPACE_SIZE],
+    bar: Vec<u8>,
+}
+
+impl PciFunctionState {
+    fn new(function: &SimulatedPciFunction, bar_address: u32) -> Self {
+        let mut configuration: [u8; CONFIGURATION_SPACE_SIZE] = [0; CONFIGURATION_SPACE_SIZE];
+
+        configuration[CONFIGURATION_VENDOR_ID..CONFIGURATION_VENDOR_ID + 2]
+            .copy_from_slice(&function.vendor_id.to_le_bytes());
+        configuration[CONFIGURATION_DEVICE_ID..CONFIGURATION_DEVICE_ID + 2]
+            .copy_from_slice(&function.device_id.to_le_bytes());
+        configuration[CONFIGURATION_CLASS_CODE..CONFIGURATION_CLASS_CODE + 3]
+            .copy_from_slice(&function.class_code.to_le_bytes()[..3]);
+        configuration[CONFIGURATION_BAR_0..CONFIGURATION_BAR_0 + 4]
+            .copy_from_slice(&bar_address.to_le_bytes());
+
+        Self {
+            configuration,
+            bar: vec![0; function.bar_size],
+        }
+    }
+
+    fn command(&self) -> u16 {
+        u16::from_le_bytes([
+            self.configuration[CONFIGURATION_COMMAND],
+            self.configuration[CONFIGURATION_COMMAND + 1],
+        ])
+    }
+
+    fn set_command(&mut self, command: u16) {
+        self.configuration[CONFIGURATION_COMMAND..CONFIGURATION_COMMAND + 2]
+            .copy_from_slice(&command.to_le_bytes());
+    }
+
+    fn bar_address(&self) -> u64 {
+        let mut register: [u8; 4] = [0; 4];
+
+        register.copy_from_slice(&self.configuration[CONFIGURATION_BAR_0..CONFIGURATION_BAR_0 + 4]);
+
+        u64::from(u32::from_le_bytes(register))
+    }
+
+    /// Returns the BAR memory when it's decoding the range of the given size.
+    fn decoded_bar(&mut self, address: u64, size: u64) -> Option<(&mut [u8], u64)> {
+        let base: u64 = self.bar_address();
+
+        (self.command() & COMMAND_MEMORY != 0
+            && address >= base
+            && address - base + size <= self.bar.len() as u64)
+            .then_some((self.bar.as_mut_slice(), address - base))
+    }
+
+    /// Writes the configuration space, ignoring the read-only registers.
+    ///
+    /// As on hardware, the address written to the BAR is aligned to it's size, so writing all ones reads back the size's mask.
+    fn write_configuration(&mut self, written: &[u8; CONFIGURATION_SPACE_SIZE]) {
+        let writable: Range<usize> = CONFIGURATION_COMMAND..CONFIGURATION_COMMAND + 2;
+        let bar: Range<usize> = CONFIGURATION_BAR_0..CONFIGURATION_BAR_0 + 4;
+
+        self.configuration[writable.clone()].copy_from_slice(&written[writable]);
+        self.configuration[bar.clone()].copy_from_slice(&written[bar]);
+
+        let bar_address: u32 = self.bar_address() as u32 & !(self.bar.len() as u32 - 1);
+
+        self.configuration[CONFIGURATION_BAR_0..CONFIGURATION_BAR_0 + 4]
+            .copy_from_slice(&bar_address.to_le_bytes());
+    }
+}
+
+/// Accesses performed for an `EFI_PCI_IO_PROTOCOL_WIDTH`.
+struct Access {
+    size: usize,
+    /// Bytes the address advances by after each access, 0 for the `Fifo` widths.
+    address_step: usize,
+    /// Bytes the buffer advances by after each access, 0 for the `Fill` widths.
+    buffer_step: usize,
+}
+
+impl Access {
+    fn new(width: u32) -> Result<Self, EfiStatus> {
+        if width > 11 {
+            return Err(EFI_INVALID_PARAMETER);
+        }
+
+        let size: usize = 1 << (width & 3);
+
+        Ok(match width >> 2 {
+            0 => Self {
+                size,
+                address_step: size,
+                buffer_step: size,
+            },
+            1 => Self {
+                size,
+                address_step: 0,
+                buffer_step: size,
+            },
+            _ => Self {
+                size,
+                address_step: size,
+                buffer_step: 0,
+            },
+        })
+    }
+
+    /// Accepts only the widths accessing consecutive units.
+    fn new_plain(width: u32) -> Result<Self, EfiStatus> {
+        if width > 3 {
+            return Err(EFI_INVALID_PARAMETER);
+        }
+
+        Self::new(width)
+    }
+
+    /// Returns the number of bytes spanned by `count` accesses at the address.
+    fn span(&self, count: usize) -> Option<usize> {
+        match count {
+            0 => Some(0),
+            _ => (count - 1)
+                .checked_mul(self.address_step)?
+                .checked_add(self.size),
+        }
+    }
+
+    /// Returns the number of buffer bytes used by `count` accesses.
+    fn buffer_span(&self, count: usize) -> usize {
+        match count {
+            0 => 0,
+            _ => (count - 1) * self.buffer_step + self.size,
+        }
+    }
+
+    /// Returns where the accesses start within a space of the given size, failing with `out_of_range` if they don't fit.
+    fn range(
+        &self,
+        space: usize,
+        offset: u64,
+        count: usize,
+        out_of_range: EfiStatus,
+    ) -> Result<usize, EfiStatus> {
+        match (usize::try_from(offset), self.span(count)) {
+            (Ok(offset), Some(span))
+                if offset
+                    .checked_add(span)
+                    .is_some_and(|end: usize| end <= space) =>
+            {
+                Ok(offset)
+            }
+            _ => Err(out_of_range),
+        }
+    }
+
+    fn read(
+        &self,
+        space: &[u8],
+        offset: usize,
+        count: usize,
+        buffer: VoidMutPtr,
+    ) -> Result<(), EfiStatus> {
+        if buffer.is_null() {
+            return Err(EFI_INVALID_PARAMETER);
+        }
+
+        for index in 0..count {
+            unsafe {
+                copy_nonoverlapping(
+                    space[offset + index * self.address_step..].as_ptr(),
+                    (buffer as *mut u8).add(index * self.buffer_step),
+                    self.size,
+                )
+            };
+        }
+
+        Ok(())
+    }
+
+    fn write(
+        &self,
+        space: &mut [u8],
+        offset: usize,
+        count: usize,
+        buffer: VoidPtr,
+    ) -> Result<(), EfiStatus> {
+        if buffer.is_null() {
+            return Err(EFI_INVALID_PARAMETER);
+        }
+
+        for index in 0..count {
+            unsafe {
+                copy_nonoverlapping(
+                    (buffer as *const u8).add(index * self.buffer_step),
+                    space[offset + index * self.address_step..].as_mut_ptr(),
+                    self.size,
+                )
+            };
+        }
+
+        Ok(())
+    }
+}
+
+/// Reads the polled value once; Nothing changes the memory while waiting, so the poll times out unless it matches right away.
+fn poll(
+    read: impl FnOnce(u32, *mut u64) -> EfiStatus,
+    width: u32,
+    mask: u64,
+    value: u64,
+    delay: u64,
+    result: *mut u64,
+) -> EfiStatus {
+    if result.is_null() || Access::new_plain(width).is_err() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    unsafe { *result = 0 };
+
+    let status: EfiStatus = read(width, result);
+
+    if status != EFI_SUCCESS {
+        return status;
+    }
+
+    /* The value is read once without delay */
+    if delay == 0 || unsafe { *result } & mask == value {
+        EFI_SUCCESS
+    } else {
+        EFI_TIMEOUT
+    }
+}
+
+/// DMA mappings. Host memory is directly accessible by the devices, so the device address is the host one.
+struct DmaMappings {
+    next: Cell<usize>,
+    active: RefCell<Vec<usize>>,
+}
+
+impl DmaMappings {
+    fn new() -> Self {
+        Self {
+            next: Cell::new(1),
+            active: RefCell::new(Vec::new()),
+        }
+    }
+
+    fn map(
+        &self,
+        operation: u32,
+        last_operation: u32,
+        host_address: VoidPtr,
+        size: *mut usize,
+        device_address: *mut u64,
+        mapping: *mut VoidMutPtr,
+    ) -> EfiStatus {
+        if operation > last_operation
+            || host_address.is_null()
+            || size.is_null()
+            || device_address.is_null()
+            || mapping.is_null()
+        {
+            return EFI_INVALID_PARAMETER;
+        }
+
+        let token: usize = self.next.get();
+
+        self.next.set(token + 1);
+        self.active.borrow_mut().push(token);
+
+        unsafe {
+            *device_address = host_address as u64;
+            *mapping = token as VoidMutPtr;
+        }
+
+        EFI_SUCCESS
+    }
+
+    fn unmap(&self, mapping: VoidMutPtr) -> EfiStatus {
+        let mut active = self.active.borrow_mut();
+
+        match active
+            .iter()
+            .position(|&token: &usize| token == mapping as usize)
+        {
+            Some(index) => {
+                active.remove(index);
+
+                EFI_SUCCESS
+            }
+            None => EFI_INVALID_PARAMETER,
+        }
+    }
+}
+
+fn allocate_buffer(
+    allocate_type: u32,
+    memory_type: u32,
+    pages: usize,
+    host_address: *mut VoidMutPtr,
+    attributes: u64,
+) -> EfiStatus {
+    if allocate_type != ALLOCATE_ANY_PAGES
+        || (memory_type != EFI_BOOT_SERVICES_DATA && memory_type != EFI_RUNTIME_SERVICES_DATA)
+        || host_address.is_null()
+    {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    if attributes & !BUFFER_ATTRIBUTES != 0 {
+        return EFI_UNSUPPORTED;
+    }
+
+    into_status(with_firmware(|firmware: &mut Firmware| {
+        let address: u64 =
+            firmware
+                .memory
+                .allocate_pages(ALLOCATE_ANY_PAGES, memory_type, pages, 0)?;
+
+        unsafe { *host_address = address as VoidMutPtr };
+
+        Ok(())
+    }))
+}
+
+fn free_buffer(pages: usize, host_address: VoidMutPtr) -> EfiStatus {
+    into_status(with_firmware(|firmware: &mut Firmware| {
+        firmware.memory.free_pages(host_address as u64, pages)
+    }))
+}
+
+/// Encodes an ACPI QWORD address space descriptor.
+fn address_space_descriptor(
+    resource_type: u8,
+    granularity: u64,
+    minimum: u64,
+    length: u64,
+) -> Vec<u8> {
+    let mut descriptor: Vec<u8> = vec![ACPI_ADDRESS_SPACE_DESCRIPTOR];
+
+    /* The length doesn't include the type and length fields */
+    descriptor.extend_from_slice(&43_u16.to_le_bytes());
+    descriptor.extend_from_slice(&[resource_type, 0, 0]);
+
+    for field in [granularity, minimum, minimum + length - 1, 0, length] {
+        descriptor.extend_from_slice(&field.to_le_bytes());
+    }
+
+    descriptor
+}
+
+/// Creates the functions, assigning their BARs consecutively in the memory window.
+pub(crate) fn functions(functions: &[SimulatedPciFunction]) -> io::Result<Vec<Box<PciIo>>> {
+    if functions.len() > MAX_DEVICES {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "At most 32 PCI functions can be attached!",
+        ));
+    }
+
+    let mut next_address: u64 = MEMORY_WINDOW_BASE;
+
+    functions
+        .iter()
+        .enumerate()
+        .map(|(device, function): (usize, &SimulatedPciFunction)| {
+            let size: u64 = function.bar_size as u64;
+
+            if size < 16 || !size.is_power_of_two() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "BAR size has to be a power of two of at least 16!",
+                ));
+            }
+
+            let address: u64 = next_address.next_multiple_of(size);
+
+            if address + size > MEMORY_WINDOW_BASE + MEMORY_WINDOW_SIZE {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "BARs don't fit in the root bridge's memory window!",
+                ));
+            }
+
+            next_address = address + size;
+
+            Ok(PciIo::new(function, device, address as u32))
+        })
+        .collect()
+}
+
+/// Simulated `EFI_PCI_IO_PROTOCOL` of a function.
+///
+/// Only BAR 0 can be accessed, I/O space accesses aren't supported.
+#[repr(C)]
+pub(crate) struct PciIo {
+    poll_mem: extern "efiapi" fn(*const Self, u32, u8, u64, u64, u64, u64, *mut u64) -> EfiStatus,
+    poll_io: extern "efiapi" fn(*const Self, u32, u8, u64, u64, u64, u64, *mut u64) -> EfiStatus,
+    mem_read: extern "efiapi" fn(*const Self, u32, u8, u64, usize, VoidMutPtr) -> EfiStatus,
+    mem_write: extern "efiapi" fn(*const Self, u32, u8, u64, usize, VoidPtr) -> EfiStatus,
+    io_read: extern "efiapi" fn(*const Self, u32, u8, u64, usize, VoidMutPtr) -> EfiStatus,
+    io_write: extern "efiapi" fn(*const Self, u32, u8, u64, usize, VoidPtr) -> EfiStatus,
+    pci_read: extern "efiapi" fn(*const Self, u32, u32, usize, VoidMutPtr) -> EfiStatus,
+    pci_write: extern "efiapi" fn(*const Self, u32, u32, usize, VoidPtr) -> EfiStatus,
+    copy_mem: extern "efiapi" fn(*const Self, u32, u8, u64, u8, u64, usize) -> EfiStatus,
+    map: extern "efiapi" fn(
+        *const Self,
+        u32,
+        VoidPtr,
+        *mut usize,
+        *mut u64,
+        *mut VoidMutPtr,
+    ) -> EfiStatus,
+    unmap: extern "efiapi" fn(*const Self, VoidMutPtr) -> EfiStatus,
+    allocate_buffer:
+        extern "efiapi" fn(*const Self, u32, u32, usize, *mut VoidMutPtr, u64) -> EfiStatus,
+    free_buffer: extern "efiapi" fn(*const Self, usize, VoidMutPtr) -> EfiStatus,
+    flush: extern "efiapi" fn(*const Self) -> EfiStatus,
+    get_location: extern "efiapi" fn(
+        *const Self,
+        *mut usize,
+        *mut usize,
+        *mut usize,
+        *mut usize,
+    ) -> EfiStatus,
+    attributes: extern "efiapi" fn(*const Self, u32, u64, *mut u64) -> EfiStatus,
+    get_bar_attributes: extern "efiapi" fn(*const Self, u8, *mut u64, *mut VoidPtr) -> EfiStatus,
+    set_bar_attributes: extern "efiapi" fn(*const Self, u64, u8, *mut u64, *mut u64) -> EfiStatus,
+    rom_size: u64,
+    rom_image: VoidPtr,
+    state: Rc<RefCell<PciFunctionState>>,
+    device: usize,
+    dma: DmaMappings,
+    /* Only reached through `rom_image`, which points into it */
+    #[allow(dead_code)]
+    option_rom: Option<Vec<u8>>,
+}
+
+impl PciIo {
+    fn new(function: &SimulatedPciFunction, device: usize, bar_address: u32) -> Box<Self> {
+        let option_rom: Option<Vec<u8>> = function.option_rom.clone();
+
+        Box::new(Self {
+            poll_mem: pci_io_poll_mem,
+            poll_io: pci_io_poll_io,
+            mem_read: pci_io_mem_read,
+            mem_write: pci_io_mem_write,
+            io_read: pci_io_io_read,
+            io_write: pci_io_io_write,
+            pci_read: pci_io_pci_read,
+            pci_write: pci_io_pci_write,
+            copy_mem: pci_io_copy_mem,
+            map: pci_io_map,
+            unmap: pci_io_unmap,
+            allocate_buffer: pci_io_allocate_buffer,
+            free_buffer: pci_io_free_buffer,
+            flush: pci_io_flush,
+            get_location: pci_io_get_location,
+            attributes: pci_io_attributes,
+            get_bar_attributes: pci_io_get_bar_attributes,
+            set_bar_attributes: pci_io_set_bar_attributes,
+            rom_size: option_rom
+                .as_ref()
+                .map_or(0, |rom: &Vec<u8>| rom.len() as u64),
+            rom_image: option_rom
+                .as_ref()
+                .map_or(0 as _, |rom: &Vec<u8>| rom.as_ptr() as VoidPtr),
+            state: Rc::new(RefCell::new(PciFunctionState::new(function, bar_address))),
+            device,
+            dma: DmaMappings::new(),
+            option_rom,
+        })
+    }
+
+    pub(crate) fn bar(&self) -> Vec<u8> {
+        self.state.borrow().bar.clone()
+    }
+}
+
+extern "efiapi" fn pci_io_poll_mem(
+    this: *const PciIo,
+    width: u32,
+    bar: u8,
+    offset: u64,
+    mask: u64,
+    value: u64,
+    delay: u64,
+    result: *mut u64,
+) -> EfiStatus {
+    poll(
+        |width: u32, result: *mut u64| pci_io_mem_read(this, width, bar, offset, 1, result as _),
+        width,
+        mask,
+        value,
+        delay,
+        result,
+    )
+}
+
+extern "efiapi" fn pci_io_poll_io(
+    _: *const PciIo,
+    _: u32,
+    _: u8,
+    _: u64,
+    _: u64,
+    _: u64,
+    _: u64,
+    _: *mut u64,
+) -> EfiStatus {
+    EFI_UNSUPPORTED
+}
+
+extern "efiapi" fn pci_io_mem_read(
+    this: *const PciIo,
+    width: u32,
+    bar: u8,
+    offset: u64,
+    count: usize,
+    buffer: VoidMutPtr,
+) -> EfiStatus {
+    let state = unsafe { &*this }.state.borrow();
+
+    into_status(Access::new(width).and_then(|access: Access| {
+        if bar != 0 {
+            return Err(EFI_UNSUPPORTED);
+        }
+
+        let offset: usize = access.range(state.bar.len(), offset, count, EFI_UNSUPPORTED)?;
+
+        access.read(&state.bar, offset, count, buffer)
+    }))
+}
+
+extern "efiapi" fn pci_io_mem_write(
+    this: *const PciIo,
+    width: u32,
+    bar: u8,
+    offset: u64,
+    count: usize,
+    buffer: VoidPtr,
+) -> EfiStatus {
+    let mut state = unsafe { &*this }.state.borrow_mut();
+
+    into_status(Access::new(width).and_then(|access: Access| {
+        if bar != 0 {
+            return Err(EFI_UNSUPPORTED);
+        }
+
+        let offset: usize = access.range(state.bar.len(), offset, count, EFI_UNSUPPORTED)?;
+
+        access.write(&mut state.bar, offset, count, buffer)
+    }))
+}
+
+extern "efiapi" fn pci_io_io_read(
+    _: *const PciIo,
+    _: u32,
+    _: u8,
+    _: u64,
+    _: usize,
+    _: VoidMutPtr,
+) -> EfiStatus {
+    EFI_UNSUPPORTED
+}
+
+extern "efiapi" fn pci_io_io_write(
+    _: *const PciIo,
+    _: u32,
+    _: u8,
+    _: u64,
+    _: usize,
+    _: VoidPtr,
+) -> EfiStatus {
+    EFI_UNSUPPORTED
+}
+
+extern "efiapi" fn pci_io_pci_read(
+    this: *const PciIo,
+    width: u32,
+    offset: u32,
+    count: usize,
+    buffer: VoidMutPtr,
+) -> EfiStatus {
+    let state = unsafe { &*this }.state.borrow();
+
+    into_status(Access::new(width).and_then(|access: Access| {
+        let offset: usize = access.range(
+            CONFIGURATION_SPACE_SIZE,
+            u64::from(offset),
+            count,
+            EFI_UNSUPPORTED,
+        )?;
+
+        access.read(&state.configuration, offset, count, buffer)
+    }))
+}
+
+extern "efiapi" fn pci_io_pci_write(
+    this: *const PciIo,
+    width: u32,
+    offset: u32,
+    count: usize,
+    buffer: VoidPtr,
+) -> EfiStatus {
+    let mut state = unsafe { &*this }.state.borrow_mut();
+
+    into_status(Access::new(width).and_then(|access: Access| {
+        let offset: usize = access.range(
+            CONFIGURATION_SPACE_SIZE,
+            u64::from(offset),
+            count,
+            EFI_UNSUPPORTED,
+        )?;
+        let mut written: [u8; CONFIGURATION_SPACE_SIZE] = state.configuration;
+
+        access.write(&mut written, offset, count, buffer)?;
+        state.write_configuration(&written);
+
+        Ok(())
+    }))
+}
+
+extern "efiapi" fn pci_io_copy_mem(
+    this: *const PciIo,
+    width: u32,
+    destination_bar: u8,
+    destination_offset: u64,
+    source_bar: u8,
+    source_offset: u64,
+    count: usize,
+) -> EfiStatus {
+    let mut state = unsafe { &*this }.state.borrow_mut();
+
+    into_status(Access::new_plain(width).and_then(|access: Access| {
+        if destination_bar != 0 || source_bar != 0 {
+            return Err(EFI_UNSUPPORTED);
+        }
+
+        let size: usize = state.bar.len();
+        let destination: usize = access.range(size, destination_offset, count, EFI_UNSUPPORTED)?;
+        let source: usize = access.range(size, source_offset, count, EFI_UNSUPPORTED)?;
+        let span: usize = access.span(count).unwrap_or(0);
+
+        /* Overlapping ranges are copied as if through an intermediate buffer */
+        state.bar.copy_within(source..source + span, destination);
+
+        Ok(())
+    }))
+}
+
+extern "efiapi" fn pci_io_map(
+    this: *const PciIo,
+    operation: u32,
+    host_address: VoidPtr,
+    size: *mut usize,
+    device_address: *mut u64,
+    mapping: *mut VoidMutPtr,
+) -> EfiStatus {
+    unsafe { &*this }.dma.map(
+        operation,
+        OPERATION_BUS_MASTER_COMMON_BUFFER,
+        host_address,
+        size,
+        device_address,
+        mapping,
+    )
+}
+
+extern "efiapi" fn pci_io_unmap(this: *const PciIo, mapping: VoidMutPtr) -> EfiStatus {
+    unsafe { &*this }.dma.unmap(mapping)
+}
+
+extern "efiapi" fn pci_io_allocate_buffer(
+    _: *const PciIo,
+    allocate_type: u32,
+    memory_type: u32,
+    pages: usize,
+    host_address: *mut VoidMutPtr,
+    attributes: u64,
+) -> EfiStatus {
+    allocate_buffer(allocate_type, memory_type, pages, host_address, attributes)
+}
+
+extern "efiapi" fn pci_io_free_buffer(
+    _: *const PciIo,
+    pages: usize,
+    host_address: VoidMutPtr,
+) -> EfiStatus {
+    free_buffer(pages, host_address)
+}
+
+extern "efiapi" fn pci_io_flush(_: *const PciIo) -> EfiStatus {
+    EFI_SUCCESS
+}
+
+extern "efiapi" fn pci_io_get_location(
+    this: *const PciIo,
+    segment: *mut usize,
+    bus: *mut usize,
+    device: *mut usize,
+    function: *mut usize,
+) -> EfiStatus {
+    if segment.is_null() || bus.is_null() || device.is_null() || function.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    unsafe {
+        *segment = 0;
+        *bus = 0;
+        *device = (*this).device;
+        *function = 0;
+    }
+
+    EFI_SUCCESS
+}
+
+extern "efiapi" fn pci_io_attributes(
+    this: *const PciIo,
+    operation: u32,
+    attributes: u64,
+    result: *mut u64,
+) -> EfiStatus {
+    const SUPPORTED: u64 = ATTRIBUTE_MEMORY | ATTRIBUTE_BUS_MASTER;
+
+    let mut state = unsafe { &*this }.state.borrow_mut();
+
+    let command: u16 = state.command();
+    let current: u64 = if command & COMMAND_MEMORY != 0 {
+        ATTRIBUTE_MEMORY
+    } else {
+        0
+    } | if command & COMMAND_BUS_MASTER != 0 {
+        ATTRIBUTE_BUS_MASTER
+    } else {
+        0
+    };
+
+    let updated: u64 = match operation {
+        ATTRIBUTE_OPERATION_GET | ATTRIBUTE_OPERATION_SUPPORTED => {
+            if result.is_null() {
+                return EFI_INVALID_PARAMETER;
+            }
+
+            unsafe {
+                *result = if operation == ATTRIBUTE_OPERATION_GET {
+                    current
+                } else {
+                    SUPPORTED
+                }
+            };
+
+            return EFI_SUCCESS;
+        }
+        _ if attributes & !SUPPORTED != 0 => return EFI_UNSUPPORTED,
+        ATTRIBUTE_OPERATION_SET => attributes,
+        ATTRIBUTE_OPERATION_ENABLE => current | attributes,
+        ATTRIBUTE_OPERATION_DISABLE => current & !attributes,
+        _ => return EFI_INVALID_PARAMETER,
+    };
+
+    let mut command: u16 = command & !(COMMAND_MEMORY | COMMAND_BUS_MASTER);
+
+    if updated & ATTRIBUTE_MEMORY != 0 {
+        command |= COMMAND_MEMORY;
+    }
+
+    if updated & ATTRIBUTE_BUS_MASTER != 0 {
+        command |= COMMAND_BUS_MASTER;
+    }
+
+    state.set_command(command);
+
+    EFI_SUCCESS
+}
+
+extern "efiapi" fn pci_io_get_bar_attributes(
+    this: *const PciIo,
+    bar: u8,
+    supports: *mut u64,
+    resources: *mut VoidPtr,
+) -> EfiStatus {
+    if supports.is_null() && resources.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    if bar != 0 {
+        return EFI_UNSUPPORTED;
+    }
+
+    let state = unsafe { &*this }.state.borrow();
+
+    if !supports.is_null() {
+        unsafe { *supports = ATTRIBUTE_MEMORY_WRITE_COMBINE };
+    }
+
+    if resources.is_null() {
+        return EFI_SUCCESS;
+    }
+
+    let mut descriptors: Vec<u8> = address_space_descriptor(
+        RESOURCE_TYPE_MEMORY,
+        32,
+        state.bar_address(),
+        state.bar.len() as u64,
+    );
+
+    descriptors.extend_from_slice(&[ACPI_END_TAG_DESCRIPTOR, 0]);
+
+    /* The caller is responsible for releasing the descriptors */
+    into_status(with_firmware(|firmware: &mut Firmware| {
+        let buffer: *mut u8 = firmware
+            .memory
+            .allocate_pool_from(EFI_BOOT_SERVICES_DATA, &descriptors)?;
+
+        unsafe { *resources = buffer as VoidPtr };
+
+        Ok(())
+    }))
+}
+
+extern "efiapi" fn pci_io_set_bar_attributes(
+    this: *const PciIo,
+    attributes: u64,
+    bar: u8,
+    offset: *mut u64,
+    length: *mut u64,
+) -> EfiStatus {
+    if offset.is_null() || length.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    if bar != 0 || attributes & !ATTRIBUTE_MEMORY_WRITE_COMBINE != 0 {
+        return EFI_UNSUPPORTED;
+    }
+
+    let size: u64 = unsafe { &*this }.state.borrow().bar.len() as u64;
+
+    match unsafe { (*offset).checked_add(*length) } {
+        Some(end) if end <= size => EFI_SUCCESS,
+        _ => EFI_UNSUPPORTED,
+    }
+}
+
+/// Simulated `EFI_PCI_ROOT_BRIDGE_IO_PROTOCOL` of segment 0, whose bus 0 holds the functions.
+///
+/// Configuration reads of absent functions, and memory reads in the window which no BAR decodes, return all ones; The writes are dropped.
+/// I/O space accesses aren't supported. There's no host bridge resource allocation protocol, so the parent handle is null.
+#[repr(C)]
+pub(crate) struct PciRootBridgeIo {
+    parent_handle: EfiHandle,
+    poll_mem: extern "efiapi" fn(*const Self, u32, u64, u64, u64, u64, *mut u64) -> EfiStatus,
+    poll_io: extern "efiapi" fn(*const Self, u32, u64, u64, u64, u64, *mut u64) -> EfiStatus,
+    mem_read: extern "efiapi" fn(*const Self, u32, u64, usize, VoidMutPtr) -> EfiStatus,
+    mem_write: extern "efiapi" fn(*const Self, u32, u64, usize, VoidPtr) -> EfiStatus,
+    io_read: extern "efiapi" fn(*const Self, u32, u64, usize, VoidMutPtr) -> EfiStatus,
+    io_write: extern "efiapi" fn(*const Self, u32, u64, usize, VoidPtr) -> EfiStatus,
+    pci_read: extern "efiapi" fn(*const Self, u32, u64, usize, VoidMutPtr) -> EfiStatus,
+    pci_write: extern "efiapi" fn(*const Self, u32, u64, usize, VoidPtr) -> EfiStatus,
+    copy_mem: extern "efiapi" fn(*const Self, u32, u64, u64, usize) -> EfiStatus,
+    map: extern "efiapi" fn(
+        *const Self,
+        u32,
+        VoidPtr,
+        *mut usize,
+        *mut u64,
+        *mut VoidMutPtr,
+    ) -> EfiStatus,
+    unmap: extern "efiapi" fn(*const Self, VoidMutPtr) -> EfiStatus,
+    allocate_buffer:
+        extern "efiapi" fn(*const Self, u32, u32, usize, *mut VoidMutPtr, u64) -> EfiStatus,
+    free_buffer: extern "efiapi" fn(*const Self, usize, VoidMutPtr) -> EfiStatus,
+    flush: extern "efiapi" fn(*const Self) -> EfiStatus,
+    get_attributes: extern "efiapi" fn(*const Self, *mut u64, *mut u64) -> EfiStatus,
+    set_attributes: extern "efiapi" fn(*const Self, u64, *mut u64, *mut u64) -> EfiStatus,
+    configuration: extern "efiapi" fn(*const Self, *mut VoidPtr) -> EfiStatus,
+    segment_number: u32,
+    functions: Vec<Rc<RefCell<PciFunctionState>>>,
+    attributes: Cell<u64>,
+    dma: DmaMappings,
+    resources: Vec<u8>,
+}
+
+impl PciRootBridgeIo {
+    pub(crate) fn new(functions: &[Box<PciIo>]) -> Box<Self> {
+        let mut resources: Vec<u8> = address_space_descriptor(
+            RESOURCE_TYPE_MEMORY,
+            32,
+            MEMORY_WINDOW_BASE,
+            MEMORY_WINDOW_SIZE,
+        );
+
+        resources.extend(address_space_descriptor(RESOURCE_TYPE_BUS_NUMBER, 0, 0, 1));
+        resources.extend_from_slice(&[ACPI_END_TAG_DESCRIPTOR, 0]);
+
+        Box::new(Self {
+            parent_handle: 0 as _,
+            poll_mem: root_bridge_poll_mem,
+            poll_io: root_bridge_poll_io,
+            mem_read: root_bridge_mem_read,
+            mem_write: root_bridge_mem_write,
+            io_read: root_bridge_io_read,
+            io_write: root_bridge_io_write,
+            pci_read: root_bridge_pci_read,
+            pci_write: root_bridge_pci_write,
+            copy_mem: root_bridge_copy_mem,
+            map: root_bridge_map,
+            unmap: root_bridge_unmap,
+            allocate_buffer: root_bridge_allocate_buffer,
+            free_buffer: root_bridge_free_buffer,
+            flush: root_bridge_flush,
+            get_attributes: root_bridge_get_attributes,
+            set_attributes: root_bridge_set_attributes,
+            configuration: root_bridge_configuration,
+            segment_number: 0,
+            functions: functions
+                .iter()
+                .map(|function: &Box<PciIo>| function.state.clone())
+                .collect(),
+            attributes: Cell::new(0),
+            dma: DmaMappings::new(),
+            resources,
+        })
+    }
+
+    /// Returns the function at the configuration address, which has to be within the conventional configuration space.
+    fn function(
+        &self,
+        access: &Access,
+        address: u64,
+        count: usize,
+    ) -> Result<(Option<&RefCell<PciFunctionState>>, usize), EfiStatus> {
+        let register: u64 = match address >> 32 {
+            0 => address & 0xFF,
+            extended => extended,
+        };
+        let register: usize = access.range(
+            CONFIGURATION_SPACE_SIZE,
+            register,
+            count,
+            EFI_INVALID_PARAMETER,
+        )?;
+
+        let (bus, device, function): (u8, u8, u8) = (
+            (address >> 24) as u8,
+            (address >> 16) as u8,
+            (address >> 8) as u8,
+        );
+
+        Ok((
+            match (bus, function) {
+                (0, 0) => self.functions.get(usize::from(device)).map(Rc::as_ref),
+                _ => None,
+            },
+            register,
+        ))
+    }
+
+    /// Runs `f` on the BAR memory decoding the accessed range, or `None` when no BAR decodes it.
+    fn memory<R>(
+        &self,
+        access: &Access,
+        address: u64,
+        count: usize,
+        f: impl FnOnce(Option<(&mut [u8], usize)>) -> Result<R, EfiStatus>,
+    ) -> Result<R, EfiStatus> {
+        let span: u64 = access.span(count).ok_or(EFI_INVALID_PARAMETER)? as u64;
+
+        if address < MEMORY_WINDOW_BASE || address - MEMORY_WINDOW_BASE + span > MEMORY_WINDOW_SIZE
+        {
+            return Err(EFI_INVALID_PARAMETER);
+        }
+
+        for function in &self.functions {
+            let mut state = function.borrow_mut();
+
+            if let Some((bar, offset)) = state.decoded_bar(address, span) {
+                return f(Some((bar, offset as usize)));
+            }
+        }
+
+        f(None)
+    }
+}
+
+extern "efiapi" fn root_bridge_poll_mem(
+    this: *const PciRootBridgeIo,
+    width: u32,
+    address: u64,
+    mask: u64,
+    value: u64,
+    delay: u64,
+    result: *mut u64,
+) -> EfiStatus {
+    poll(
+        |width: u32, result: *mut u64| root_bridge_mem_read(this, width, address, 1, result as _),
+        width,
+        mask,
+        value,
+        delay,
+        result,
+    )
+}
+
+extern "efiapi" fn root_bridge_poll_io(
+    _: *const PciRootBridgeIo,
+    _: u32,
+    _: u64,
+    _: u64,
+    _: u64,
+    _: u64,
+    _: *mut u64,
+) -> EfiStatus {
+    EFI_UNSUPPORTED
+}
+
+extern "efiapi" fn root_bridge_mem_read(
+    this: *const PciRootBridgeIo,
+    width: u32,
+    address: u64,
+    count: usize,
+    buffer: VoidMutPtr,
+) -> EfiStatus {
+    let this: &PciRootBridgeIo = unsafe { &*this };
+
+    into_status(Access::new(width).and_then(|access: Access| {
+        this.memory(
+            &access,
+            address,
+            count,
+            |bar: Option<(&mut [u8], usize)>| match bar {
+                Some((bar, offset)) => access.read(bar, offset, count, buffer),
+                None if buffer.is_null() => Err(EFI_INVALID_PARAMETER),
+                None => {
+                    unsafe { write_bytes(buffer as *mut u8, 0xFF, access.buffer_span(count)) };
+
+                    Ok(())
+                }
+            },
+        )
+    }))
+}
+
+extern "efiapi" fn root_bridge_mem_write(
+    this: *const PciRootBridgeIo,
+    width: u32,
+    address: u64,
+    count: usize,
+    buffer: VoidPtr,
+) -> EfiStatus {
+    let this: &PciRootBridgeIo = unsafe { &*this };
+
+    into_status(Access::new(width).and_then(|access: Access| {
+        this.memory(
+            &access,
+            address,
+            count,
+            |bar: Option<(&mut [u8], usize)>| match bar {
+                Some((bar, offset)) => access.write(bar, offset, count, buffer),
+                None if buffer.is_null() => Err(EFI_INVALID_PARAMETER),
+                None => Ok(()),
+            },
+        )
+    }))
+}
+
+extern "efiapi" fn root_bridge_io_read(
+    _: *const PciRootBridgeIo,
+    _: u32,
+    _: u64,
+    _: usize,
+    _: VoidMutPtr,
+) -> EfiStatus {
+    EFI_UNSUPPORTED
+}
+
+extern "efiapi" fn root_bridge_io_write(
+    _: *const PciRootBridgeIo,
+    _: u32,
+    _: u64,
+    _: usize,
+    _: VoidPtr,
+) -> EfiStatus {
+    EFI_UNSUPPORTED
+}
+
+extern "efiapi" fn root_bridge_pci_read(
+    this: *const PciRootBridgeIo,
+    width: u32,
+    address: u64,
+    count: usize,
+    buffer: VoidMutPtr,
+) -> EfiStatus {
+    let this: &PciRootBridgeIo = unsafe { &*this };
+
+    into_status(Access::new(width).and_then(|access: Access| {
+        match this.function(&access, address, count)? {
+            (Some(function), register) => {
+                access.read(&function.borrow().configuration, register, count, buffer)
+            }
+            (None, _) if buffer.is_null() => Err(EFI_INVALID_PARAMETER),
+            (None, _) => {
+                unsafe { write_bytes(buffer as *mut u8, 0xFF, access.buffer_span(count)) };
+
+                Ok(())
+            }
+        }
+    }))
+}
+
+extern "efiapi" fn root_bridge_pci_write(
+    this: *const PciRootBridgeIo,
+    width: u32,
+    address: u64,
+    count: usize,
+    buffer: VoidPtr,
+) -> EfiStatus {
+    let this: &PciRootBridgeIo = unsafe { &*this };
+
+    into_status(Access::new(width).and_then(|access: Access| {
+        match this.function(&access, address, count)? {
+            (Some(function), register) => {
+                let mut state = function.borrow_mut();
+                let mut written: [u8; CONFIGURATION_SPACE_SIZE] = state.configuration;
+
+                access.write(&mut written, register, count, buffer)?;
+                state.write_configuration(&written);
+
+                Ok(())
+            }
+            (None, _) if buffer.is_null() => Err(EFI_INVALID_PARAMETER),
+            (None, _) => Ok(()),
+        }
+    }))
+}
+
+extern "efiapi" fn root_bridge_copy_mem(
+    this: *const PciRootBridgeIo,
+    width: u32,
+    destination: u64,
+    source: u64,
+    count: usize,
+) -> EfiStatus {
+    let span: usize = match Access::new_plain(width)
+        .ok()
+        .and_then(|access: Access| access.span(count))
+    {
+        Some(span) => span,
+        None => return EFI_INVALID_PARAMETER,
+    };
+
+    /* Overlapping ranges are copied as if through an intermediate buffer */
+    let mut data: Vec<u8> = vec![0; span];
+
+    match root_bridge_mem_read(this, width, source, count, data.as_mut_ptr() as VoidMutPtr) {
+        EFI_SUCCESS => {
+            root_bridge_mem_write(this, width, destination, count, data.as_ptr() as VoidPtr)
+        }
+        status => status,
+    }
+}
+
+extern "efiapi" fn root_bridge_map(
+    this: *const PciRootBridgeIo,
+    operation: u32,
+    host_address: VoidPtr,
+    size: *mut usize,
+    device_address: *mut u64,
+    mapping: *mut VoidMutPtr,
+) -> EfiStatus {
+    unsafe { &*this }.dma.map(
+        operation,
+        OPERATION_BUS_MASTER_COMMON_BUFFER_64,
+        host_address,
+        size,
+        device_address,
+        mapping,
+    )
+}
+
+extern "efiapi" fn root_bridge_unmap(
+    this: *const PciRootBridgeIo,
+    mapping: VoidMutPtr,
+) -> EfiStatus {
+    unsafe { &*this }.dma.unmap(mapping)
+}
+
+extern "efiapi" fn root_bridge_allocate_buffer(
+    _: *const PciRootBridgeIo,
+    allocate_type: u32,
+    memory_type: u32,
+    pages: usize,
+    host_address: *mut VoidMutPtr,
+    attributes: u64,
+) -> EfiStatus {
+    allocate_buffer(allocate_type, memory_type, pages, host_address, attributes)
+}
+
+extern "efiapi" fn root_bridge_free_buffer(
+    _: *const PciRootBridgeIo,
+    pages: usize,
+    host_address: VoidMutPtr,
+) -> EfiStatus {
+    free_buffer(pages, host_address)
+}
+
+extern "efiapi" fn root_bridge_flush(_: *const PciRootBridgeIo) -> EfiStatus {
+    EFI_SUCCESS
+}
+
+extern "efiapi" fn root_bridge_get_attributes(
+    this: *const PciRootBridgeIo,
+    supports: *mut u64,
+    attributes: *mut u64,
+) -> EfiStatus {
+    if supports.is_null() && attributes.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    unsafe {
+        if !supports.is_null() {
+            *supports = ATTRIBUTE_MEMORY_WRITE_COMBINE | ATTRIBUTE_MEMORY_CACHED;
+        }
+
+        if !attributes.is_null() {
+            *attributes = (*this).attributes.get();
+        }
+    }
+
+    EFI_SUCCESS
+}
+
+extern "efiapi" fn root_bridge_set_attributes(
+    this: *const PciRootBridgeIo,
+    attributes: u64,
+    base: *mut u64,
+    length: *mut u64,
+) -> EfiStatus {
+    if attributes & !(ATTRIBUTE_MEMORY_WRITE_COMBINE | ATTRIBUTE_MEMORY_CACHED) != 0 {
+        return EFI_UNSUPPORTED;
+    }
+
+    /* Memory attributes apply to a range */
+    if attributes != 0 && (base.is_null() || length.is_null()) {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    unsafe { &*this }.attributes.set(attributes);
+
+    EFI_SUCCESS
+}
+
+extern "efiapi" fn root_bridge_configuration(
+    this: *const PciRootBridgeIo,
+    resources: *mut VoidPtr,
+) -> EfiStatus {
+    if resources.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    unsafe { *resources = (*this).resources.as_ptr() as VoidPtr };
+
+    EFI_SUCCESS
+}
@@ -0,0 +1,213 @@
+use {
+    crate::{
+        firmware::with_firmware,
+        status::{into_status, EFI_INVALID_PARAMETER, EFI_NOT_READY, EFI_SUCCESS},
+    },
+    efi::{EfiEvent, EfiStatus, VoidPtr},
+};
+
+/// Pointer input waiting to be read through `GetState`.
+#[derive(Default)]
+pub(crate) struct PointerInput {
+    /// Movement accumulated since the last read, with the current buttons.
+    pub(crate) movement: Option<SimplePointerState>,
+    /// Latest position, if it changed since the last read.
+    pub(crate) position: Option<AbsolutePointerState>,
+}
+
+impl PointerInput {
+    pub(crate) fn push_movement(&mut self, x: i32, y: i32, z: i32, left: bool, right: bool) {
+        let movement: &mut SimplePointerState =
+            self.movement.get_or_insert(SimplePointerState::default());
+
+        movement.relative_movement_x = movement.relative_movement_x.saturating_add(x);
+        movement.relative_movement_y = movement.relative_movement_y.saturating_add(y);
+        movement.relative_movement_z = movement.relative_movement_z.saturating_add(z);
+        movement.left_button = left;
+        movement.right_button = right;
+    }
+}
+
+#[repr(C)]
+struct SimplePointerMode {
+    resolution_x: u64,
+    resolution_y: u64,
+    resolution_z: u64,
+    left_button: bool,
+    right_button: bool,
+}
+
+#[repr(C)]
+#[derive(Default, Clone, Copy)]
+pub(crate) struct SimplePointerState {
+    relative_movement_x: i32,
+    relative_movement_y: i32,
+    relative_movement_z: i32,
+    left_button: bool,
+    right_button: bool,
+}
+
+/// Simulated `EFI_SIMPLE_POINTER_PROTOCOL` of a two button mouse with a wheel.
+#[repr(C)]
+pub(crate) struct SimplePointer {
+    reset: extern "efiapi" fn(*const Self, bool) -> EfiStatus,
+    get_state: extern "efiapi" fn(*const Self, *mut SimplePointerState) -> EfiStatus,
+    wait_for_input: EfiEvent,
+    mode: *const SimplePointerMode,
+    mode_data: SimplePointerMode,
+}
+
+impl SimplePointer {
+    pub(crate) fn new(wait_for_input: EfiEvent) -> Box<Self> {
+        let mut pointer: Box<Self> = Box::new(Self {
+            reset: simple_pointer_reset,
+            get_state: simple_pointer_get_state,
+            wait_for_input,
+            mode: 0 as _,
+            mode_data: SimplePointerMode {
+                resolution_x: 8,
+                resolution_y: 8,
+                resolution_z: 1,
+                left_button: true,
+                right_button: true,
+            },
+        });
+
+        pointer.mode = &pointer.mode_data;
+
+        pointer
+    }
+}
+
+/// Notification function of the simple pointer's `WaitForInput` event.
+pub(crate) extern "efiapi" fn simple_pointer_wait_for_input(event: EfiEvent, _: VoidPtr) {
+    with_firmware(|firmware| {
+        if firmware.pointer.movement.is_some() {
+            let _ = firmware.events.signal(event);
+        }
+    });
+}
+
+extern "efiapi" fn simple_pointer_reset(_: *const SimplePointer, _: bool) -> EfiStatus {
+    with_firmware(|firmware| firmware.pointer.movement = None);
+
+    EFI_SUCCESS
+}
+
+extern "efiapi" fn simple_pointer_get_state(
+    _: *const SimplePointer,
+    state: *mut SimplePointerState,
+) -> EfiStatus {
+    if state.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    into_status(with_firmware(|firmware| {
+        let movement: SimplePointerState = firmware.pointer.movement.take().ok_or(EFI_NOT_READY)?;
+
+        unsafe { *state = movement };
+
+        Ok(())
+    }))
+}
+
+#[repr(C)]
+struct AbsolutePointerMode {
+    absolute_min_x: u64,
+    absolute_min_y: u64,
+    absolute_min_z: u64,
+    absolute_max_x: u64,
+    absolute_max_y: u64,
+    absolute_max_z: u64,
+    attributes: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub(crate) struct AbsolutePointerState {
+    current_x: u64,
+    current_y: u64,
+    current_z: u64,
+    active_buttons: u32,
+}
+
+impl AbsolutePointerState {
+    pub(crate) fn new(x: u64, y: u64, active_buttons: u32) -> Self {
+        Self {
+            current_x: x,
+            current_y: y,
+            current_z: 0,
+            active_buttons,
+        }
+    }
+}
+
+/// Simulated `EFI_ABSOLUTE_POINTER_PROTOCOL` of a touch screen without a Z axis.
+#[repr(C)]
+pub(crate) struct AbsolutePointer {
+    reset: extern "efiapi" fn(*const Self, bool) -> EfiStatus,
+    get_state: extern "efiapi" fn(*const Self, *mut AbsolutePointerState) -> EfiStatus,
+    wait_for_input: EfiEvent,
+    mode: *const AbsolutePointerMode,
+    mode_data: AbsolutePointerMode,
+}
+
+impl AbsolutePointer {
+    /* Alternate button supported */
+    const ATTRIBUTES: u32 = 0x01;
+
+    pub(crate) fn new(wait_for_input: EfiEvent, max_x: u64, max_y: u64) -> Box<Self> {
+        let mut pointer: Box<Self> = Box::new(Self {
+            reset: absolute_pointer_reset,
+            get_state: absolute_pointer_get_state,
+            wait_for_input,
+            mode: 0 as _,
+            mode_data: AbsolutePointerMode {
+                absolute_min_x: 0,
+                absolute_min_y: 0,
+                absolute_min_z: 0,
+                absolute_max_x: max_x,
+                absolute_max_y: max_y,
+                absolute_max_z: 0,
+                attributes: Self::ATTRIBUTES,
+            },
+        });
+
+        pointer.mode = &pointer.mode_data;
+
+        pointer
+    }
+}
+
+/// Notification function of the absolute pointer's `WaitForInput` event.
+pub(crate) extern "efiapi" fn absolute_pointer_wait_for_input(event: EfiEvent, _: VoidPtr) {
+    with_firmware(|firmware| {
+        if firmware.pointer.position.is_some() {
+            let _ = firmware.events.signal(event);
+        }
+    });
+}
+
+extern "efiapi" fn absolute_pointer_reset(_: *const AbsolutePointer, _: bool) -> EfiStatus {
+    with_firmware(|firmware| firmware.pointer.position = None);
+
+    EFI_SUCCESS
+}
+
+extern "efiapi" fn absolute_pointer_get_state(
+    _: *const AbsolutePointer,
+    state: *mut AbsolutePointerState,
+) -> EfiStatus {
+    if state.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    into_status(with_firmware(|firmware| {
+        let position: AbsolutePointerState =
+            firmware.pointer.position.take().ok_or(EFI_NOT_READY)?;
+
+        unsafe { *state = position };
+
+        Ok(())
+    }))
+}
@@ -0,0 +1,600 @@
+use {
+    crate::status::{
+        EFI_ALREADY_STARTED, EFI_BUFFER_TOO_SMALL, EFI_INVALID_PARAMETER, EFI_NOT_STARTED,
+        EFI_SUCCESS, EFI_TFTP_ERROR, EFI_TIMEOUT, EFI_UNSUPPORTED,
+    },
+    efi::{EfiStatus, VoidMutPtr, VoidPtr},
+    std::{
+        cell::UnsafeCell, convert::TryFrom, ffi::CStr, os::raw::c_char, ptr::copy_nonoverlapping,
+    },
+};
+
+const PXE_BASE_CODE_REVISION: u64 = 0x0001_0000;
+
+const DEFAULT_TTL: u8 = 16;
+const DEFAULT_TOS: u8 = 0;
+
+const MAX_ARP_ENTRIES: usize = 8;
+const MAX_ROUTE_ENTRIES: usize = 8;
+const MAX_IP_COUNT: usize = 8;
+
+const PACKET_SIZE: usize = 1472;
+
+/* Locally administered MAC addresses of the client and the server */
+const STATION_MAC: [u8; 6] = [0x52, 0x54, 0x00, 0x12, 0x34, 0x56];
+const SERVER_MAC: [u8; 6] = [0x52, 0x54, 0x00, 0x00, 0x00, 0x01];
+
+const TRANSACTION_ID: u32 = 0x5058_4531;
+
+/* EFI_PXE_BASE_CODE_DHCPV4_PACKET field offsets */
+const BOOTP_OPCODE: usize = 0;
+const BOOTP_HW_TYPE: usize = 1;
+const BOOTP_HW_ADDRESS_LENGTH: usize = 2;
+const BOOTP_IDENT: usize = 4;
+const BOOTP_YOUR_IP: usize = 16;
+const BOOTP_SERVER_IP: usize = 20;
+const BOOTP_HW_ADDRESS: usize = 28;
+const BOOTP_BOOT_FILE: usize = 108;
+const BOOTP_BOOT_FILE_SIZE: usize = 128;
+const DHCP_MAGIC: usize = 236;
+const DHCP_OPTIONS: usize = 240;
+
+const BOOTP_REQUEST: u8 = 1;
+const BOOTP_REPLY: u8 = 2;
+const HW_TYPE_ETHERNET: u8 = 1;
+const DHCP_MAGIC_COOKIE: u32 = 0x6382_5363;
+
+/* DHCP options */
+const OPTION_SUBNET_MASK: u8 = 1;
+const OPTION_MESSAGE_TYPE: u8 = 53;
+const OPTION_SERVER_IDENTIFIER: u8 = 54;
+const OPTION_END: u8 = 255;
+const MESSAGE_DISCOVER: u8 = 1;
+const MESSAGE_ACK: u8 = 5;
+
+/* EFI_PXE_BASE_CODE_TFTP_OPCODE */
+const TFTP_GET_FILE_SIZE: u32 = 1;
+const TFTP_READ_FILE: u32 = 2;
+
+const TFTP_ERROR_FILE_NOT_FOUND: u8 = 1;
+
+/// TFTP server reached through the simulated `EFI_PXE_BASE_CODE_PROTOCOL`, which also acts as the DHCP server.
+///
+/// The DHCP cycle assigns the station address and subnet mask and names the boot file, while TFTP serves the attached files.
+#[derive(Clone)]
+pub struct SimulatedPxeServer {
+    server_ip: [u8; 4],
+    station_ip: [u8; 4],
+    subnet_mask: [u8; 4],
+    boot_file: String,
+    files: Vec<(String, Vec<u8>)>,
+}
+
+impl SimulatedPxeServer {
+    /// Creates a server without files, which assigns the given station address and subnet mask.
+    pub fn new(server_ip: [u8; 4], station_ip: [u8; 4], subnet_mask: [u8; 4]) -> Self {
+        Self {
+            server_ip,
+            station_ip,
+            subnet_mask,
+            boot_file: String::new(),
+            files: Vec::new(),
+        }
+    }
+
+    /// Sets the boot file named in the DHCP acknowledgement, which has to fit it's 128 bytes with the null-terminator.
+    pub fn boot_file(mut self, name: &str) -> Self {
+        self.boot_file = name.to_string();
+
+        self
+    }
+
+    /// Adds a file served over TFTP.
+    pub fn file(mut self, name: &str, contents: &[u8]) -> Self {
+        self.files.push((name.to_string(), contents.to_vec()));
+
+        self
+    }
+
+    pub(crate) fn validate(&self) -> bool {
+        self.boot_file.len() < BOOTP_BOOT_FILE_SIZE && !self.boot_file.contains('\0')
+    }
+}
+
+/// `EFI_IP_ADDRESS`, of which only IPv4 addresses are used.
+#[repr(C, align(4))]
+#[derive(Clone, Copy)]
+struct IpAddress([u8; 16]);
+
+impl IpAddress {
+    const fn zeroed() -> Self {
+        Self([0; 16])
+    }
+
+    fn v4(address: [u8; 4]) -> Self {
+        let mut raw: Self = Self::zeroed();
+
+        raw.0[..4].copy_from_slice(&address);
+
+        raw
+    }
+
+    fn as_v4(&self) -> [u8; 4] {
+        [self.0[0], self.0[1], self.0[2], self.0[3]]
+    }
+}
+
+#[repr(C, align(4))]
+#[derive(Clone, Copy)]
+struct Packet([u8; PACKET_SIZE]);
+
+impl Packet {
+    /// Builds a DHCPv4 message from the client's hardware address, with the given options.
+    fn dhcp(opcode: u8, options: &[u8]) -> Self {
+        let mut packet: [u8; PACKET_SIZE] = [0; PACKET_SIZE];
+
+        packet[BOOTP_OPCODE] = opcode;
+        packet[BOOTP_HW_TYPE] = HW_TYPE_ETHERNET;
+        packet[BOOTP_HW_ADDRESS_LENGTH] = STATION_MAC.len() as u8;
+        packet[BOOTP_IDENT..BOOTP_IDENT + 4].copy_from_slice(&TRANSACTION_ID.to_be_bytes());
+        packet[BOOTP_HW_ADDRESS..BOOTP_HW_ADDRESS + STATION_MAC.len()]
+            .copy_from_slice(&STATION_MAC);
+        packet[DHCP_MAGIC..DHCP_MAGIC + 4].copy_from_slice(&DHCP_MAGIC_COOKIE.to_be_bytes());
+        packet[DHCP_OPTIONS..DHCP_OPTIONS + options.len()].copy_from_slice(options);
+
+        Self(packet)
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct IpFilter {
+    filters: u8,
+    ip_count: u8,
+    reserved: u16,
+    ip_list: [IpAddress; MAX_IP_COUNT],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct ArpEntry {
+    ip_address: IpAddress,
+    mac_address: [u8; 32],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct RouteEntry {
+    ip_address: IpAddress,
+    subnet_mask: IpAddress,
+    gateway_address: IpAddress,
+}
+
+#[repr(C)]
+struct IcmpError {
+    icmp_type: u8,
+    code: u8,
+    checksum: u16,
+    data_header: u32,
+    data: [u8; 494],
+}
+
+#[repr(C)]
+struct TftpError {
+    error_code: u8,
+    error_string: [u8; 127],
+}
+
+/// `EFI_PXE_BASE_CODE_MODE`. Only the DHCP discover and acknowledgement packets are ever cached.
+#[repr(C)]
+struct PxeBaseCodeMode {
+    started: bool,
+    ip_v6_available: bool,
+    ip_v6_supported: bool,
+    using_ip_v6: bool,
+    bis_supported: bool,
+    bis_detected: bool,
+    auto_arp: bool,
+    send_guid: bool,
+    dhcp_discover_valid: bool,
+    dhcp_ack_received: bool,
+    proxy_offer_received: bool,
+    pxe_discover_valid: bool,
+    pxe_reply_received: bool,
+    pxe_bis_reply_received: bool,
+    icmp_error_received: bool,
+    tftp_error_received: bool,
+    make_callbacks: bool,
+    ttl: u8,
+    tos: u8,
+    station_ip: IpAddress,
+    subnet_mask: IpAddress,
+    dhcp_discover: Packet,
+    dhcp_ack: Packet,
+    proxy_offer: Packet,
+    pxe_discover: Packet,
+    pxe_reply: Packet,
+    pxe_bis_reply: Packet,
+    ip_filter: IpFilter,
+    arp_cache_entries: u32,
+    arp_cache: [ArpEntry; MAX_ARP_ENTRIES],
+    route_table_entries: u32,
+    route_table: [RouteEntry; MAX_ROUTE_ENTRIES],
+    icmp_error: IcmpError,
+    tftp_error: TftpError,
+}
+
+impl PxeBaseCodeMode {
+    fn new() -> Self {
+        let empty: Packet = Packet([0; PACKET_SIZE]);
+
+        Self {
+            started: false,
+            ip_v6_available: false,
+            ip_v6_supported: false,
+            using_ip_v6: false,
+            bis_supported: false,
+            bis_detected: false,
+            auto_arp: false,
+            send_guid: false,
+            dhcp_discover_valid: false,
+            dhcp_ack_received: false,
+            proxy_offer_received: false,
+            pxe_discover_valid: false,
+            pxe_reply_received: false,
+            pxe_bis_reply_received: false,
+            icmp_error_received: false,
+            tftp_error_received: false,
+            make_callbacks: false,
+            ttl: DEFAULT_TTL,
+            tos: DEFAULT_TOS,
+            station_ip: IpAddress::zeroed(),
+            subnet_mask: IpAddress::zeroed(),
+            dhcp_discover: empty,
+            dhcp_ack: empty,
+            proxy_offer: empty,
+            pxe_discover: empty,
+            pxe_reply: empty,
+            pxe_bis_reply: empty,
+            ip_filter: IpFilter {
+                filters: 0,
+                ip_count: 0,
+                reserved: 0,
+                ip_list: [IpAddress::zeroed(); MAX_IP_COUNT],
+            },
+            arp_cache_entries: 0,
+            arp_cache: [ArpEntry {
+                ip_address: IpAddress::zeroed(),
+                mac_address: [0; 32],
+            }; MAX_ARP_ENTRIES],
+            route_table_entries: 0,
+            route_table: [RouteEntry {
+                ip_address: IpAddress::zeroed(),
+                subnet_mask: IpAddress::zeroed(),
+                gateway_address: IpAddress::zeroed(),
+            }; MAX_ROUTE_ENTRIES],
+            icmp_error: IcmpError {
+                icmp_type: 0,
+                code: 0,
+                checksum: 0,
+                data_header: 0,
+                data: [0; 494],
+            },
+            tftp_error: TftpError {
+                error_code: 0,
+                error_string: [0; 127],
+            },
+        }
+    }
+
+    fn set_tftp_error(&mut self, code: u8, message: &[u8]) {
+        self.tftp_error_received = true;
+        self.tftp_error.error_code = code;
+        self.tftp_error.error_string = [0; 127];
+        self.tftp_error.error_string[..message.len()].copy_from_slice(message);
+    }
+}
+
+/// Simulated IPv4-only `EFI_PXE_BASE_CODE_PROTOCOL`, talking to a single server answering DHCP, ARP and TFTP.
+///
+/// Boot server discovery always times out, while UDP, IP filters, parameters and packet replacement aren't provided.
+#[repr(C)]
+pub(crate) struct PxeBaseCode {
+    revision: u64,
+    start: extern "efiapi" fn(*const Self, bool) -> EfiStatus,
+    stop: extern "efiapi" fn(*const Self) -> EfiStatus,
+    dhcp: extern "efiapi" fn(*const Self, bool) -> EfiStatus,
+    discover: extern "efiapi" fn(*const Self, u16, *mut u16, bool, VoidPtr) -> EfiStatus,
+    mtftp: extern "efiapi" fn(
+        *const Self,
+        u32,
+        VoidMutPtr,
+        bool,
+        *mut u64,
+        *const usize,
+        *const IpAddress,
+        *const u8,
+        VoidPtr,
+        bool,
+    ) -> EfiStatus,
+    udp_write: VoidPtr,
+    udp_read: VoidPtr,
+    set_ip_filter: VoidPtr,
+    arp: extern "efiapi" fn(*const Self, *const IpAddress, *mut [u8; 32]) -> EfiStatus,
+    set_parameters: VoidPtr,
+    set_station_ip:
+        extern "efiapi" fn(*const Self, *const IpAddress, *const IpAddress) -> EfiStatus,
+    set_packets: VoidPtr,
+    mode: *const PxeBaseCodeMode,
+    mode_data: UnsafeCell<PxeBaseCodeMode>,
+    server: SimulatedPxeServer,
+}
+
+impl PxeBaseCode {
+    pub(crate) fn new(server: SimulatedPxeServer) -> Box<Self> {
+        let mut pxe_base_code: Box<Self> = Box::new(Self {
+            revision: PXE_BASE_CODE_REVISION,
+            start: pxe_start,
+            stop: pxe_stop,
+            dhcp: pxe_dhcp,
+            discover: pxe_discover,
+            mtftp: pxe_mtftp,
+            udp_write: 0 as _,
+            udp_read: 0 as _,
+            set_ip_filter: 0 as _,
+            arp: pxe_arp,
+            set_parameters: 0 as _,
+            set_station_ip: pxe_set_station_ip,
+            set_packets: 0 as _,
+            mode: 0 as _,
+            mode_data: UnsafeCell::new(PxeBaseCodeMode::new()),
+            server,
+        });
+
+        pxe_base_code.mode = pxe_base_code.mode_data.get();
+
+        pxe_base_code
+    }
+
+    fn mode_data(&self) -> *mut PxeBaseCodeMode {
+        self.mode_data.get()
+    }
+}
+
+extern "efiapi" fn pxe_start(this: *const PxeBaseCode, use_ip_v6: bool) -> EfiStatus {
+    let mode: &mut PxeBaseCodeMode = unsafe { &mut *(*this).mode_data() };
+
+    if mode.started {
+        return EFI_ALREADY_STARTED;
+    }
+
+    if use_ip_v6 {
+        return EFI_UNSUPPORTED;
+    }
+
+    mode.started = true;
+    mode.auto_arp = true;
+
+    EFI_SUCCESS
+}
+
+extern "efiapi" fn pxe_stop(this: *const PxeBaseCode) -> EfiStatus {
+    let mode: &mut PxeBaseCodeMode = unsafe { &mut *(*this).mode_data() };
+
+    if !mode.started {
+        return EFI_NOT_STARTED;
+    }
+
+    *mode = PxeBaseCodeMode::new();
+
+    EFI_SUCCESS
+}
+
+extern "efiapi" fn pxe_dhcp(this: *const PxeBaseCode, _sort_offers: bool) -> EfiStatus {
+    let this: &PxeBaseCode = unsafe { &*this };
+    let server: &SimulatedPxeServer = &this.server;
+
+    let mode: &mut PxeBaseCodeMode = unsafe { &mut *this.mode_data() };
+
+    if !mode.started {
+        return EFI_NOT_STARTED;
+    }
+
+    mode.dhcp_discover = Packet::dhcp(
+        BOOTP_REQUEST,
+        &[OPTION_MESSAGE_TYPE, 1, MESSAGE_DISCOVER, OPTION_END],
+    );
+    mode.dhcp_discover_valid = true;
+
+    let mut options: Vec<u8> = vec![OPTION_MESSAGE_TYPE, 1, MESSAGE_ACK, OPTION_SUBNET_MASK, 4];
+
+    options.extend_from_slice(&server.subnet_mask);
+    options.extend_from_slice(&[OPTION_SERVER_IDENTIFIER, 4]);
+    options.extend_from_slice(&server.server_ip);
+    options.push(OPTION_END);
+
+    let mut acknowledgement: Packet = Packet::dhcp(BOOTP_REPLY, &options);
+
+    acknowledgement.0[BOOTP_YOUR_IP..BOOTP_YOUR_IP + 4].copy_from_slice(&server.station_ip);
+    acknowledgement.0[BOOTP_SERVER_IP..BOOTP_SERVER_IP + 4].copy_from_slice(&server.server_ip);
+    acknowledgement.0[BOOTP_BOOT_FILE..BOOTP_BOOT_FILE + server.boot_file.len()]
+        .copy_from_slice(server.boot_file.as_bytes());
+
+    mode.dhcp_ack = acknowledgement;
+    mode.dhcp_ack_received = true;
+    mode.station_ip = IpAddress::v4(server.station_ip);
+    mode.subnet_mask = IpAddress::v4(server.subnet_mask);
+
+    EFI_SUCCESS
+}
+
+extern "efiapi" fn pxe_discover(
+    this: *const PxeBaseCode,
+    _boot_type: u16,
+    _layer: *mut u16,
+    _use_bis: bool,
+    _info: VoidPtr,
+) -> EfiStatus {
+    if !unsafe { &*(*this).mode_data() }.started {
+        return EFI_NOT_STARTED;
+    }
+
+    /* No boot server answers discovery */
+    EFI_TIMEOUT
+}
+
+extern "efiapi" fn pxe_mtftp(
+    this: *const PxeBaseCode,
+    operation: u32,
+    buffer: VoidMutPtr,
+    _overwrite: bool,
+    buffer_size: *mut u64,
+    _block_size: *const usize,
+    server_ip: *const IpAddress,
+    file_name: *const u8,
+    _info: VoidPtr,
+    dont_use_buffer: bool,
+) -> EfiStatus {
+    let this: &PxeBaseCode = unsafe { &*this };
+
+    let mode: &mut PxeBaseCodeMode = unsafe { &mut *this.mode_data() };
+
+    if !mode.started {
+        return EFI_NOT_STARTED;
+    }
+
+    if buffer_size.is_null() || server_ip.is_null() || file_name.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    /* Multicast TFTP, writes and directory listings aren't served */
+    if operation != TFTP_GET_FILE_SIZE && operation != TFTP_READ_FILE {
+        return EFI_UNSUPPORTED;
+    }
+
+    mode.tftp_error_received = false;
+
+    if unsafe { (*server_ip).as_v4() } != this.server.server_ip {
+        return EFI_TIMEOUT;
+    }
+
+    let file_name: &[u8] = unsafe { CStr::from_ptr(file_name as *const c_char) }.to_bytes();
+
+    let contents: &[u8] = match this
+        .server
+        .files
+        .iter()
+        .find(|(name, _): &&(String, Vec<u8>)| name.as_bytes() == file_name)
+    {
+        Some((_, contents)) => contents,
+        None => {
+            mode.set_tftp_error(TFTP_ERROR_FILE_NOT_FOUND, b"File not found");
+
+            return EFI_TFTP_ERROR;
+        }
+    };
+
+    let size: u64 = contents.len() as u64;
+
+    if operation == TFTP_GET_FILE_SIZE || dont_use_buffer {
+        unsafe { *buffer_size = size };
+
+        return EFI_SUCCESS;
+    }
+
+    let available: u64 = unsafe { *buffer_size };
+
+    unsafe { *buffer_size = size };
+
+    if available < size {
+        return EFI_BUFFER_TOO_SMALL;
+    }
+
+    if buffer.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    unsafe { copy_nonoverlapping(contents.as_ptr(), buffer as *mut u8, contents.len()) };
+
+    EFI_SUCCESS
+}
+
+extern "efiapi" fn pxe_arp(
+    this: *const PxeBaseCode,
+    ip_address: *const IpAddress,
+    mac_address: *mut [u8; 32],
+) -> EfiStatus {
+    let this: &PxeBaseCode = unsafe { &*this };
+
+    let mode: &mut PxeBaseCodeMode = unsafe { &mut *this.mode_data() };
+
+    if !mode.started {
+        return EFI_NOT_STARTED;
+    }
+
+    if ip_address.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    let ip_address: IpAddress = unsafe { *ip_address };
+
+    /* Only the server answers */
+    if ip_address.as_v4() != this.server.server_ip {
+        return EFI_TIMEOUT;
+    }
+
+    let mut mac: [u8; 32] = [0; 32];
+
+    mac[..SERVER_MAC.len()].copy_from_slice(&SERVER_MAC);
+
+    let entries: usize = usize::try_from(mode.arp_cache_entries).unwrap_or(MAX_ARP_ENTRIES);
+
+    if entries < MAX_ARP_ENTRIES
+        && !mode.arp_cache[..entries]
+            .iter()
+            .any(|entry: &ArpEntry| entry.ip_address.as_v4() == ip_address.as_v4())
+    {
+        mode.arp_cache[entries] = ArpEntry {
+            ip_address,
+            mac_address: mac,
+        };
+        mode.arp_cache_entries += 1;
+    }
+
+    if !mac_address.is_null() {
+        unsafe { *mac_address = mac };
+    }
+
+    EFI_SUCCESS
+}
+
+extern "efiapi" fn pxe_set_station_ip(
+    this: *const PxeBaseCode,
+    station_ip: *const IpAddress,
+    subnet_mask: *const IpAddress,
+) -> EfiStatus {
+    let this: &PxeBaseCode = unsafe { &*this };
+
+    let mode: &mut PxeBaseCodeMode = unsafe { &mut *this.mode_data() };
+
+    if !mode.started {
+        return EFI_NOT_STARTED;
+    }
+
+    if !subnet_mask.is_null() {
+        let mask: u32 = u32::from_be_bytes(unsafe { (*subnet_mask).as_v4() });
+
+        /* The mask's bits have to be contiguous */
+        if mask.leading_ones() + mask.trailing_zeros() != 32 {
+            return EFI_INVALID_PARAMETER;
+        }
+
+        mode.subnet_mask = unsafe { *subnet_mask };
+    }
+
+    if !station_ip.is_null() {
+        mode.station_ip = unsafe { *station_ip };
+    }
+
+    EFI_SUCCESS
+}
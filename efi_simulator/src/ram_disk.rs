@@ -0,0 +1,287 @@
+use {
+    crate::{
+        boot_services::{device_path_size, service, signal_all, EFI_BOOT_SERVICES_DATA},
+        disk::{abort_requests, BlockIo, DiskProtocols},
+        firmware::Firmware,
+        status::{EFI_ALREADY_STARTED, EFI_INVALID_PARAMETER, EFI_NOT_FOUND, EFI_UNSUPPORTED},
+    },
+    efi::{guids, EfiEvent, EfiGuid, EfiHandle, EfiStatus, VoidPtr},
+    std::{mem::size_of, slice::from_raw_parts},
+};
+
+const DISK_BLOCK_SIZE: u32 = 512;
+const CD_BLOCK_SIZE: u32 = 2048;
+
+/// Simulated `EFI_RAM_DISK_PROTOCOL`.
+///
+/// The services don't take the protocol as a parameter, so the registered RAM disks are kept by the firmware.
+/// Virtual disks have 512 byte blocks, while virtual CDs have 2048 byte ones and are read-only.
+/// Registering returns a pool allocated copy of the device path, which is matched by content when unregistering.
+#[repr(C)]
+pub(crate) struct RamDisk {
+    register: extern "efiapi" fn(u64, u64, *const EfiGuid, *const u8, *mut *const u8) -> EfiStatus,
+    unregister: extern "efiapi" fn(*const u8) -> EfiStatus,
+}
+
+impl RamDisk {
+    pub(crate) fn new() -> Box<Self> {
+        Box::new(Self {
+            register: ram_disk_register,
+            unregister: ram_disk_unregister,
+        })
+    }
+}
+
+/// Returns the block size and whether the disk is read-only, for the supported disk types.
+fn geometry(disk_type: &EfiGuid) -> Option<(u32, bool)> {
+    if *disk_type == guids::EFI_VIRTUAL_DISK_GUID
+        || *disk_type == guids::EFI_PERSISTENT_VIRTUAL_DISK_GUID
+    {
+        Some((DISK_BLOCK_SIZE, false))
+    } else if *disk_type == guids::EFI_VIRTUAL_CD_GUID
+        || *disk_type == guids::EFI_PERSISTENT_VIRTUAL_CD_GUID
+    {
+        Some((CD_BLOCK_SIZE, true))
+    } else {
+        None
+    }
+}
+
+extern "efiapi" fn ram_disk_register(
+    base: u64,
+    size: u64,
+    disk_type: *const EfiGuid,
+    parent_device_path: *const u8,
+    device_path: *mut *const u8,
+) -> EfiStatus {
+    if base == 0 || disk_type.is_null() || device_path.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    let disk_type: EfiGuid = unsafe { *disk_type };
+
+    let (block_size, read_only): (u32, bool) = match geometry(&disk_type) {
+        Some(geometry) => geometry,
+        None => return EFI_UNSUPPORTED,
+    };
+
+    /* Trailing bytes which don't form a whole block aren't exposed */
+    let blocks: u64 = size / u64::from(block_size);
+
+    if blocks == 0 {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    let parent: &[u8] = if parent_device_path.is_null() {
+        &[]
+    } else {
+        unsafe { from_raw_parts(parent_device_path, device_path_size(parent_device_path)) }
+    };
+
+    service(|firmware: &mut Firmware| {
+        if firmware
+            .ram_disks
+            .iter()
+            .any(|(_, protocols): &(EfiHandle, DiskProtocols)| {
+                node(&protocols.device_path).starting_address == base
+            })
+        {
+            return Err(EFI_ALREADY_STARTED);
+        }
+
+        /* The first instance number unused by the RAM disks of the same type */
+        let instance: u16 = (0..=u16::MAX)
+            .find(|&instance: &u16| {
+                !firmware
+                    .ram_disks
+                    .iter()
+                    .any(|(_, protocols): &(EfiHandle, DiskProtocols)| {
+                        let node: RamDiskNode = node(&protocols.device_path);
+
+                        node.disk_type == disk_type && node.instance == instance
+                    })
+            })
+            .ok_or(EFI_INVALID_PARAMETER)?;
+
+        let protocols: DiskProtocols = DiskProtocols::ram_disk(
+            base as *mut u8,
+            blocks,
+            block_size,
+            read_only,
+            ram_disk_device_path(
+                parent,
+                &RamDiskNode {
+                    starting_address: base,
+                    ending_address: base + size - 1,
+                    disk_type,
+                    instance,
+                },
+            ),
+        );
+
+        /* The caller is responsible for releasing the copy */
+        let copy: *mut u8 = firmware
+            .memory
+            .allocate_pool_from(EFI_BOOT_SERVICES_DATA, &protocols.device_path)?;
+
+        let (handle, mut events): (EfiHandle, Vec<EfiEvent>) = firmware.handles.install(
+            0 as _,
+            &guids::EFI_DEVICE_PATH_PROTOCOL,
+            protocols.device_path.as_ptr() as VoidPtr,
+        )?;
+
+        events.extend(
+            firmware
+                .handles
+                .install(
+                    handle,
+                    &guids::EFI_BLOCK_IO_PROTOCOL,
+                    &*protocols.block_io as *const _ as VoidPtr,
+                )?
+                .1,
+        );
+        events.extend(
+            firmware
+                .handles
+                .install(
+                    handle,
+                    &guids::EFI_DISK_IO_PROTOCOL,
+                    &*protocols.disk_io as *const _ as VoidPtr,
+                )?
+                .1,
+        );
+        events.extend(
+            firmware
+                .handles
+                .install(
+                    handle,
+                    &guids::EFI_BLOCK_IO2_PROTOCOL,
+                    &*protocols.block_io2 as *const _ as VoidPtr,
+                )?
+                .1,
+        );
+        events.extend(
+            firmware
+                .handles
+                .install(
+                    handle,
+                    &guids::EFI_DISK_IO2_PROTOCOL,
+                    &*protocols.disk_io2 as *const _ as VoidPtr,
+                )?
+                .1,
+        );
+
+        signal_all(firmware, events);
+
+        unsafe { *device_path = copy };
+
+        firmware.ram_disks.push((handle, protocols));
+
+        Ok(())
+    })
+}
+
+extern "efiapi" fn ram_disk_unregister(device_path: *const u8) -> EfiStatus {
+    if device_path.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    let device_path: &[u8] =
+        unsafe { from_raw_parts(device_path, device_path_size(device_path) + 4) };
+
+    service(|firmware: &mut Firmware| {
+        let index: usize = firmware
+            .ram_disks
+            .iter()
+            .position(|(_, protocols): &(EfiHandle, DiskProtocols)| {
+                *protocols.device_path == *device_path
+            })
+            .ok_or(EFI_NOT_FOUND)?;
+
+        let (handle, protocols): &(EfiHandle, DiskProtocols) = &firmware.ram_disks[index];
+
+        for (guid, interface) in [
+            (
+                &guids::EFI_DISK_IO2_PROTOCOL,
+                &*protocols.disk_io2 as *const _ as VoidPtr,
+            ),
+            (
+                &guids::EFI_BLOCK_IO2_PROTOCOL,
+                &*protocols.block_io2 as *const _ as VoidPtr,
+            ),
+            (
+                &guids::EFI_DISK_IO_PROTOCOL,
+                &*protocols.disk_io as *const _ as VoidPtr,
+            ),
+            (
+                &guids::EFI_BLOCK_IO_PROTOCOL,
+                &*protocols.block_io as *const _ as VoidPtr,
+            ),
+            (
+                &guids::EFI_DEVICE_PATH_PROTOCOL,
+                protocols.device_path.as_ptr() as VoidPtr,
+            ),
+        ] {
+            firmware.handles.uninstall(*handle, guid, interface)?;
+        }
+
+        let block_io: *const BlockIo = &*firmware.ram_disks[index].1.block_io;
+
+        abort_requests(firmware, block_io);
+
+        firmware.ram_disks.remove(index);
+
+        Ok(())
+    })
+}
+
+/// Data of a RAM disk media device path node.
+struct RamDiskNode {
+    starting_address: u64,
+    ending_address: u64,
+    disk_type: EfiGuid,
+    instance: u16,
+}
+
+const MEDIA_DEVICE_PATH: u8 = 0x04;
+const MEDIA_RAM_DISK_DP: u8 = 0x09;
+const RAM_DISK_NODE_LENGTH: u16 = 4 + 8 + 8 + 16 + 2;
+
+/// Parses the RAM disk node, which the simulator's RAM disks' device paths end with.
+fn node(device_path: &[u8]) -> RamDiskNode {
+    let end: usize = device_path.len() - 4;
+    let data: &[u8] = &device_path[end - usize::from(RAM_DISK_NODE_LENGTH) + 4..end];
+
+    let mut starting_address: [u8; 8] = [0; 8];
+    let mut ending_address: [u8; 8] = [0; 8];
+
+    starting_address.copy_from_slice(&data[..8]);
+    ending_address.copy_from_slice(&data[8..16]);
+
+    RamDiskNode {
+        starting_address: u64::from_le_bytes(starting_address),
+        ending_address: u64::from_le_bytes(ending_address),
+        disk_type: unsafe { (data[16..32].as_ptr() as *const EfiGuid).read_unaligned() },
+        instance: u16::from_le_bytes([data[32], data[33]]),
+    }
+}
+
+/// Builds the parent's device path followed by a RAM disk node and an end node.
+fn ram_disk_device_path(parent: &[u8], node: &RamDiskNode) -> Box<[u8]> {
+    let mut device_path: Vec<u8> = parent.to_vec();
+
+    device_path.extend_from_slice(&[MEDIA_DEVICE_PATH, MEDIA_RAM_DISK_DP]);
+    device_path.extend_from_slice(&RAM_DISK_NODE_LENGTH.to_le_bytes());
+    device_path.extend_from_slice(&node.starting_address.to_le_bytes());
+    device_path.extend_from_slice(&node.ending_address.to_le_bytes());
+    device_path.extend_from_slice(unsafe {
+        from_raw_parts(
+            &node.disk_type as *const EfiGuid as *const u8,
+            size_of::<EfiGuid>(),
+        )
+    });
+    device_path.extend_from_slice(&node.instance.to_le_bytes());
+    device_path.extend_from_slice(&[0x7F, 0xFF, 0x04, 0x00]);
+
+    device_path.into_boxed_slice()
+}
@@ -0,0 +1,99 @@
+use {
+    crate::status::{EFI_BUFFER_TOO_SMALL, EFI_INVALID_PARAMETER, EFI_SUCCESS, EFI_UNSUPPORTED},
+    efi::{guids, EfiGuid, EfiStatus},
+    std::{
+        cell::Cell,
+        mem::{size_of, size_of_val},
+        slice::from_raw_parts_mut,
+    },
+};
+
+/// Supported algorithms, the first one being the default.
+const ALGORITHMS: [EfiGuid; 2] = [
+    guids::EFI_RNG_ALGORITHM_SP800_90_CTR_256,
+    guids::EFI_RNG_ALGORITHM_RAW,
+];
+
+/// Simulated `EFI_RNG_PROTOCOL`, producing a reproducible `xorshift64*` stream from the seed.
+#[repr(C)]
+pub(crate) struct Rng {
+    get_info: extern "efiapi" fn(*const Self, *mut usize, *mut EfiGuid) -> EfiStatus,
+    get_rng: extern "efiapi" fn(*const Self, *const EfiGuid, usize, *mut u8) -> EfiStatus,
+    state: Cell<u64>,
+}
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Box<Self> {
+        Box::new(Self {
+            get_info: rng_get_info,
+            get_rng: rng_get_rng,
+            /* Zero is the only state xorshift can't leave */
+            state: Cell::new(seed.max(1)),
+        })
+    }
+
+    fn next(&self) -> u64 {
+        let mut state: u64 = self.state.get();
+
+        state ^= state >> 12;
+        state ^= state << 25;
+        state ^= state >> 27;
+
+        self.state.set(state);
+
+        state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+}
+
+extern "efiapi" fn rng_get_info(
+    _: *const Rng,
+    size: *mut usize,
+    algorithms: *mut EfiGuid,
+) -> EfiStatus {
+    if size.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    let required: usize = size_of_val(&ALGORITHMS);
+
+    if unsafe { *size } < required {
+        unsafe { *size = required };
+
+        return EFI_BUFFER_TOO_SMALL;
+    }
+
+    if algorithms.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    unsafe {
+        from_raw_parts_mut(algorithms, ALGORITHMS.len()).copy_from_slice(&ALGORITHMS);
+
+        *size = required;
+    }
+
+    EFI_SUCCESS
+}
+
+extern "efiapi" fn rng_get_rng(
+    this: *const Rng,
+    algorithm: *const EfiGuid,
+    length: usize,
+    buffer: *mut u8,
+) -> EfiStatus {
+    if length == 0 || buffer.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    if !algorithm.is_null() && !ALGORITHMS.contains(unsafe { &*algorithm }) {
+        return EFI_UNSUPPORTED;
+    }
+
+    let this: &Rng = unsafe { &*this };
+
+    for chunk in unsafe { from_raw_parts_mut(buffer, length) }.chunks_mut(size_of::<u64>()) {
+        chunk.copy_from_slice(&this.next().to_le_bytes()[..chunk.len()]);
+    }
+
+    EFI_SUCCESS
+}
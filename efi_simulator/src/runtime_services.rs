@@ -0,0 +1,551 @@
+use {
+    crate::{
+        events::{EFI_EVENT_GROUP_VIRTUAL_ADDRESS_CHANGE, EVT_SIGNAL_VIRTUAL_ADDRESS_CHANGE},
+        firmware::{dispatch_notifications, with_firmware, Firmware, ResetHandler},
+        status::{
+            into_status, EFI_BUFFER_TOO_SMALL, EFI_INVALID_PARAMETER, EFI_NOT_FOUND, EFI_SUCCESS,
+            EFI_UNSUPPORTED,
+        },
+        tables::{TableHeader, EFI_RUNTIME_SERVICES_SIGNATURE, EFI_SIMULATED_REVISION},
+        variables::{
+            Variable, EFI_VARIABLE_BOOTSERVICE_ACCESS, EFI_VARIABLE_NON_VOLATILE,
+            EFI_VARIABLE_RUNTIME_ACCESS,
+        },
+    },
+    efi::{
+        runtime_services::{
+            capsule::{EfiCapsuleBlockDescriptor, EfiCapsuleFlags, EfiCapsuleHeader},
+            miscellaneous::EfiResetType,
+            time::EfiTimeRepresentation,
+        },
+        utilities::string_length,
+        EfiGuid, EfiStatus, VoidMutPtr, VoidPtr,
+    },
+    std::{mem::size_of, slice::from_raw_parts},
+};
+
+/* Limits reported by `QueryCapsuleCapabilities` and `QueryVariableInfo` */
+const MAXIMUM_CAPSULE_SIZE: u64 = 0x0100_0000;
+const VARIABLE_STORAGE_SIZE: u64 = 0x0001_0000;
+const MAXIMUM_VARIABLE_SIZE: u64 = 0x0000_8000;
+
+#[repr(C)]
+struct TimeCapabilities {
+    resolution: u32,
+    accuracy: u32,
+    sets_to_zero: bool,
+}
+
+/// Simulated `EFI_RUNTIME_SERVICES`.
+#[repr(C)]
+pub(crate) struct RuntimeServicesTable {
+    header: TableHeader,
+
+    /*~~~~~~~~~~*/
+    get_time: extern "efiapi" fn(*mut EfiTimeRepresentation, *mut TimeCapabilities) -> EfiStatus,
+    set_time: extern "efiapi" fn(*const EfiTimeRepresentation) -> EfiStatus,
+    get_wakeup_time:
+        extern "efiapi" fn(*mut bool, *mut bool, *mut EfiTimeRepresentation) -> EfiStatus,
+    set_wakeup_time: extern "efiapi" fn(bool, *const EfiTimeRepresentation) -> EfiStatus,
+
+    /*~~~~~~~~~~*/
+    set_virtual_address_map: extern "efiapi" fn(usize, usize, u32, VoidPtr) -> EfiStatus,
+    convert_pointer: extern "efiapi" fn(usize, *mut VoidPtr) -> EfiStatus,
+
+    /*~~~~~~~~~~*/
+    get_variable: extern "efiapi" fn(
+        *const u16,
+        *const EfiGuid,
+        *mut u32,
+        *mut usize,
+        VoidMutPtr,
+    ) -> EfiStatus,
+    get_next_variable_name: extern "efiapi" fn(*mut usize, *mut u16, *mut EfiGuid) -> EfiStatus,
+    set_variable: extern "efiapi" fn(*const u16, *const EfiGuid, u32, usize, VoidPtr) -> EfiStatus,
+
+    /*~~~~~~~~~~*/
+    get_next_high_monotonic_count: extern "efiapi" fn(*mut u32) -> EfiStatus,
+    reset_system: extern "efiapi" fn(EfiResetType, EfiStatus, usize, VoidPtr) -> !,
+
+    /*~~~~~~~~~~*/
+    update_capsule: extern "efiapi" fn(*const *const EfiCapsuleHeader, usize, u64) -> EfiStatus,
+    query_capsule_capabilities:
+        extern "efiapi" fn(*const *const EfiCapsuleHeader, usize, *mut u64, *mut u32) -> EfiStatus,
+
+    /*~~~~~~~~~~*/
+    query_variable_info: extern "efiapi" fn(u32, *mut u64, *mut u64, *mut u64) -> EfiStatus,
+}
+
+impl RuntimeServicesTable {
+    pub(crate) fn new() -> Box<Self> {
+        let mut table: Box<Self> = Box::new(Self {
+            header: TableHeader::new::<Self>(
+                EFI_RUNTIME_SERVICES_SIGNATURE,
+                EFI_SIMULATED_REVISION,
+            ),
+            get_time,
+            set_time,
+            get_wakeup_time,
+            set_wakeup_time,
+            set_virtual_address_map,
+            convert_pointer,
+            get_variable,
+            get_next_variable_name,
+            set_variable,
+            get_next_high_monotonic_count,
+            reset_system,
+            update_capsule,
+            query_capsule_capabilities,
+            query_variable_info,
+        });
+
+        table.header.update_crc32();
+
+        table
+    }
+}
+
+/* TIME */
+
+fn validate_time(time: &EfiTimeRepresentation) -> bool {
+    (1900..=9999).contains(&time.year)
+        && (1..=12).contains(&time.month)
+        && (1..=31).contains(&time.day)
+        && time.hour < 24
+        && time.minute < 60
+        && time.second < 60
+        && time.nanosecond < 1_000_000_000
+}
+
+extern "efiapi" fn get_time(
+    time: *mut EfiTimeRepresentation,
+    capabilities: *mut TimeCapabilities,
+) -> EfiStatus {
+    if time.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    with_firmware(|firmware: &mut Firmware| unsafe { *time = firmware.time });
+
+    if !capabilities.is_null() {
+        unsafe {
+            *capabilities = TimeCapabilities {
+                resolution: 1,
+                accuracy: 0,
+                sets_to_zero: false,
+            }
+        };
+    }
+
+    EFI_SUCCESS
+}
+
+extern "efiapi" fn set_time(time: *const EfiTimeRepresentation) -> EfiStatus {
+    match unsafe { time.as_ref() } {
+        Some(time) if validate_time(time) => {
+            with_firmware(|firmware: &mut Firmware| firmware.time = *time);
+
+            EFI_SUCCESS
+        }
+        _ => EFI_INVALID_PARAMETER,
+    }
+}
+
+extern "efiapi" fn get_wakeup_time(
+    enabled: *mut bool,
+    pending: *mut bool,
+    time: *mut EfiTimeRepresentation,
+) -> EfiStatus {
+    if enabled.is_null() || pending.is_null() || time.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    with_firmware(|firmware: &mut Firmware| unsafe {
+        *enabled = firmware.wakeup.0;
+        *pending = false;
+        *time = firmware.wakeup.1;
+    });
+
+    EFI_SUCCESS
+}
+
+extern "efiapi" fn set_wakeup_time(enable: bool, time: *const EfiTimeRepresentation) -> EfiStatus {
+    match (enable, unsafe { time.as_ref() }) {
+        (true, Some(time)) if validate_time(time) => {
+            with_firmware(|firmware: &mut Firmware| firmware.wakeup = (true, *time));
+
+            EFI_SUCCESS
+        }
+        (true, _) => EFI_INVALID_PARAMETER,
+        (false, _) => {
+            with_firmware(|firmware: &mut Firmware| firmware.wakeup.0 = false);
+
+            EFI_SUCCESS
+        }
+    }
+}
+
+/* VIRTUAL MEMORY */
+
+/// Accepted once after `ExitBootServices`. Runtime services stay identity mapped.
+extern "efiapi" fn set_virtual_address_map(_: usize, _: usize, _: u32, _: VoidPtr) -> EfiStatus {
+    let status: EfiStatus = with_firmware(|firmware: &mut Firmware| {
+        if !firmware.boot_services_exited || firmware.virtual_address_map_set {
+            return EFI_UNSUPPORTED;
+        }
+
+        firmware.virtual_address_map_set = true;
+        firmware
+            .events
+            .signal_type(EVT_SIGNAL_VIRTUAL_ADDRESS_CHANGE);
+        firmware
+            .events
+            .signal_group(&EFI_EVENT_GROUP_VIRTUAL_ADDRESS_CHANGE);
+
+        EFI_SUCCESS
+    });
+
+    dispatch_notifications();
+
+    status
+}
+
+extern "efiapi" fn convert_pointer(_: usize, address: *mut VoidPtr) -> EfiStatus {
+    if address.is_null() {
+        EFI_INVALID_PARAMETER
+    } else {
+        EFI_SUCCESS
+    }
+}
+
+/* VARIABLE */
+
+unsafe fn variable_name<'a>(name: *const u16) -> Option<&'a [u16]> {
+    string_length(name)
+        .ok()
+        .map(|length: usize| from_raw_parts(name, length))
+}
+
+extern "efiapi" fn get_variable(
+    name: *const u16,
+    guid: *const EfiGuid,
+    attributes: *mut u32,
+    data_size: *mut usize,
+    data: VoidMutPtr,
+) -> EfiStatus {
+    let name: &[u16] = match unsafe { variable_name(name) } {
+        Some(name) if !guid.is_null() && !data_size.is_null() => name,
+        _ => return EFI_INVALID_PARAMETER,
+    };
+
+    into_status(with_firmware(|firmware: &mut Firmware| {
+        let variable: &Variable = firmware
+            .variables
+            .get(name, unsafe { &*guid }, firmware.boot_services_exited)
+            .ok_or(EFI_NOT_FOUND)?;
+
+        unsafe {
+            if !attributes.is_null() {
+                *attributes = variable.attributes;
+            }
+
+            if *data_size < variable.data.len() {
+                *data_size = variable.data.len();
+
+                return Err(EFI_BUFFER_TOO_SMALL);
+            }
+
+            if data.is_null() {
+                return Err(EFI_INVALID_PARAMETER);
+            }
+
+            (data as *mut u8).copy_from_nonoverlapping(variable.data.as_ptr(), variable.data.len());
+            *data_size = variable.data.len();
+        }
+
+        Ok(())
+    }))
+}
+
+extern "efiapi" fn get_next_variable_name(
+    name_size: *mut usize,
+    name: *mut u16,
+    guid: *mut EfiGuid,
+) -> EfiStatus {
+    if name_size.is_null() || name.is_null() || guid.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    /* The previous name has to be terminated within the buffer */
+    let buffer: &[u16] = unsafe { from_raw_parts(name, *name_size / 2) };
+
+    let previous: &[u16] = match buffer.iter().position(|&character: &u16| character == 0) {
+        Some(length) => &buffer[..length],
+        None => return EFI_INVALID_PARAMETER,
+    };
+
+    into_status(with_firmware(|firmware: &mut Firmware| {
+        let variable: &Variable =
+            firmware
+                .variables
+                .next(previous, unsafe { &*guid }, firmware.boot_services_exited)?;
+
+        let required: usize = (variable.name.len() + 1) * 2;
+
+        unsafe {
+            if *name_size < required {
+                *name_size = required;
+
+                return Err(EFI_BUFFER_TOO_SMALL);
+            }
+
+            name.copy_from_nonoverlapping(variable.name.as_ptr(), variable.name.len());
+            *name.add(variable.name.len()) = 0;
+            *name_size = required;
+            *guid = variable.guid;
+        }
+
+        Ok(())
+    }))
+}
+
+extern "efiapi" fn set_variable(
+    name: *const u16,
+    guid: *const EfiGuid,
+    attributes: u32,
+    data_size: usize,
+    data: VoidPtr,
+) -> EfiStatus {
+    let name: &[u16] = match unsafe { variable_name(name) } {
+        Some(name) if !guid.is_null() && (data_size == 0 || !data.is_null()) => name,
+        _ => return EFI_INVALID_PARAMETER,
+    };
+
+    let data: &[u8] = if data_size == 0 {
+        &[]
+    } else {
+        unsafe { from_raw_parts(data as *const u8, data_size) }
+    };
+
+    into_status(with_firmware(|firmware: &mut Firmware| {
+        let runtime: bool = firmware.boot_services_exited;
+
+        firmware
+            .variables
+            .set(name, unsafe { &*guid }, attributes, data, runtime)
+    }))
+}
+
+extern "efiapi" fn query_variable_info(
+    attributes: u32,
+    maximum_storage_size: *mut u64,
+    remaining_storage_size: *mut u64,
+    maximum_variable_size: *mut u64,
+) -> EfiStatus {
+    if maximum_storage_size.is_null()
+        || remaining_storage_size.is_null()
+        || maximum_variable_size.is_null()
+        || attributes & EFI_VARIABLE_BOOTSERVICE_ACCESS == 0
+    {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    into_status(with_firmware(|firmware: &mut Firmware| {
+        if firmware.boot_services_exited && attributes & EFI_VARIABLE_RUNTIME_ACCESS == 0 {
+            return Err(EFI_INVALID_PARAMETER);
+        }
+
+        /* Volatile and non-volatile variables are accounted separately */
+        let used: u64 = firmware
+            .variables
+            .storage_size(attributes & EFI_VARIABLE_NON_VOLATILE != 0)
+            as u64;
+
+        unsafe {
+            *maximum_storage_size = VARIABLE_STORAGE_SIZE;
+            *remaining_storage_size = VARIABLE_STORAGE_SIZE.saturating_sub(used);
+            *maximum_variable_size = MAXIMUM_VARIABLE_SIZE;
+        }
+
+        Ok(())
+    }))
+}
+
+/* MISCELLANEOUS */
+
+extern "efiapi" fn get_next_high_monotonic_count(high_count: *mut u32) -> EfiStatus {
+    if high_count.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    with_firmware(|firmware: &mut Firmware| {
+        firmware.monotonic_count = (firmware.monotonic_count & !0xFFFF_FFFF).wrapping_add(1 << 32);
+
+        unsafe { *high_count = (firmware.monotonic_count >> 32) as u32 };
+    });
+
+    EFI_SUCCESS
+}
+
+extern "efiapi" fn reset_system(
+    reset_type: EfiResetType,
+    reset_status: EfiStatus,
+    data_size: usize,
+    reset_data: VoidPtr,
+) -> ! {
+    /* The handler runs without the firmware borrowed, so it may call services itself */
+    let handler: ResetHandler =
+        with_firmware(|firmware: &mut Firmware| firmware.reset_handler.clone());
+
+    let data: &[u8] = if data_size == 0 || reset_data.is_null() {
+        &[]
+    } else {
+        unsafe { from_raw_parts(reset_data as *const u8, data_size) }
+    };
+
+    handler(reset_type, reset_status, data)
+}
+
+/* CAPSULE */
+
+unsafe fn capsule_headers<'a>(
+    headers: *const *const EfiCapsuleHeader,
+    count: usize,
+) -> Option<Vec<&'a EfiCapsuleHeader>> {
+    if headers.is_null() || count == 0 {
+        return None;
+    }
+
+    from_raw_parts(headers, count)
+        .iter()
+        .map(|&header: &*const EfiCapsuleHeader| {
+            header.as_ref().filter(|header: &&EfiCapsuleHeader| {
+                header.header_size() as usize >= size_of::<EfiCapsuleHeader>()
+                    && header.capsule_image_size() >= header.header_size()
+            })
+        })
+        .collect()
+}
+
+/// Concatenates the data blocks described by a scatter-gather list, following continuation pointers.
+unsafe fn scatter_gather_data(mut descriptor: *const EfiCapsuleBlockDescriptor) -> Vec<u8> {
+    let mut data: Vec<u8> = Vec::new();
+
+    while let Some(block) = descriptor.as_ref() {
+        match (block.length(), block.address()) {
+            (0, 0) => break,
+            (0, address) => descriptor = address as *const EfiCapsuleBlockDescriptor,
+            (length, address) => {
+                data.extend_from_slice(from_raw_parts(address as *const u8, length as usize));
+                descriptor = descriptor.add(1);
+            }
+        }
+    }
+
+    data
+}
+
+/// Records the capsules, as firmware would before processing them on the next boot.
+extern "efiapi" fn update_capsule(
+    headers: *const *const EfiCapsuleHeader,
+    count: usize,
+    scatter_gather_list: u64,
+) -> EfiStatus {
+    let headers: Vec<&EfiCapsuleHeader> = match unsafe { capsule_headers(headers, count) } {
+        Some(headers) => headers,
+        None => return EFI_INVALID_PARAMETER,
+    };
+
+    let mut reset: bool = false;
+
+    for header in &headers {
+        let flags: EfiCapsuleFlags = header.flags();
+
+        if flags.contains(EfiCapsuleFlags::PERSIST_ACROSS_RESET) {
+            if scatter_gather_list == 0 {
+                return EFI_INVALID_PARAMETER;
+            }
+
+            reset |= flags.contains(EfiCapsuleFlags::INITIATE_RESET);
+        } else if flags.contains(EfiCapsuleFlags::INITIATE_RESET)
+            || flags.contains(EfiCapsuleFlags::POPULATE_SYSTEM_TABLE)
+        {
+            return EFI_INVALID_PARAMETER;
+        }
+    }
+
+    let capsules: Vec<Vec<u8>> = if scatter_gather_list == 0 {
+        headers
+            .iter()
+            .map(|&header: &&EfiCapsuleHeader| unsafe {
+                from_raw_parts(
+                    header as *const EfiCapsuleHeader as *const u8,
+                    header.capsule_image_size() as usize,
+                )
+                .to_vec()
+            })
+            .collect()
+    } else {
+        /* The list describes the capsules back to back, in the order of the headers */
+        let data: Vec<u8> =
+            unsafe { scatter_gather_data(scatter_gather_list as *const EfiCapsuleBlockDescriptor) };
+        let mut remaining: &[u8] = &data;
+        let mut capsules: Vec<Vec<u8>> = Vec::with_capacity(headers.len());
+
+        for header in &headers {
+            let size: usize = header.capsule_image_size() as usize;
+
+            if remaining.len() < size {
+                return EFI_INVALID_PARAMETER;
+            }
+
+            capsules.push(remaining[..size].to_vec());
+            remaining = &remaining[size..];
+        }
+
+        capsules
+    };
+
+    let handler: Result<Option<ResetHandler>, EfiStatus> =
+        with_firmware(|firmware: &mut Firmware| {
+            if !firmware.capsule_updates {
+                return Err(EFI_UNSUPPORTED);
+            }
+
+            firmware.capsules.extend(capsules);
+
+            Ok(reset.then(|| firmware.reset_handler.clone()))
+        });
+
+    match handler {
+        /* The handler runs without the firmware borrowed, like in `ResetSystem` */
+        Ok(Some(handler)) => handler(EfiResetType::Warm, EFI_SUCCESS, &[]),
+        Ok(None) => EFI_SUCCESS,
+        Err(status) => status,
+    }
+}
+
+extern "efiapi" fn query_capsule_capabilities(
+    headers: *const *const EfiCapsuleHeader,
+    count: usize,
+    maximum_capsule_size: *mut u64,
+    reset_type: *mut u32,
+) -> EfiStatus {
+    if unsafe { capsule_headers(headers, count) }.is_none()
+        || maximum_capsule_size.is_null()
+        || reset_type.is_null()
+    {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    if !with_firmware(|firmware: &mut Firmware| firmware.capsule_updates) {
+        return EFI_UNSUPPORTED;
+    }
+
+    unsafe {
+        *maximum_capsule_size = MAXIMUM_CAPSULE_SIZE;
+        *reset_type = EfiResetType::Warm as u32;
+    }
+
+    EFI_SUCCESS
+}
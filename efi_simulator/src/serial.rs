@@ -0,0 +1,280 @@
+use {
+    crate::status::{EFI_INVALID_PARAMETER, EFI_SUCCESS, EFI_TIMEOUT, EFI_UNSUPPORTED},
+    efi::{EfiGuid, EfiStatus, VoidMutPtr, VoidPtr},
+    std::{
+        cell::{RefCell, UnsafeCell},
+        collections::VecDeque,
+        slice::{from_raw_parts, from_raw_parts_mut},
+    },
+};
+
+const SERIAL_IO_REVISION: u32 = 0x0001_0001;
+
+const EFI_SERIAL_TERMINAL_DEVICE_TYPE_GUID: EfiGuid = EfiGuid::from_tuple((
+    0x6AD9_A60F,
+    0x5815,
+    0x4C7C,
+    [0x8A, 0x10, 0x50, 0x53, 0xD2, 0xBF, 0x7A, 0x1B],
+));
+
+/* Defaults chosen for attributes passed as 0 */
+const DEFAULT_BAUD_RATE: u64 = 115_200;
+const DEFAULT_TIMEOUT: u32 = 1_000_000;
+/* Bytes accepted by a single write */
+const FIFO_DEPTH: u32 = 16;
+const DEFAULT_DATA_BITS: u8 = 8;
+/* EFI_PARITY_TYPE and EFI_STOP_BITS_TYPE */
+const NO_PARITY: u32 = 1;
+const MAX_PARITY: u32 = 5;
+const ONE_STOP_BIT: u32 = 1;
+const MAX_STOP_BITS: u32 = 3;
+
+/* Control bits */
+const DATA_TERMINAL_READY: u32 = 0x0001;
+const REQUEST_TO_SEND: u32 = 0x0002;
+const CLEAR_TO_SEND: u32 = 0x0010;
+const DATA_SET_READY: u32 = 0x0020;
+const INPUT_BUFFER_EMPTY: u32 = 0x0100;
+const OUTPUT_BUFFER_EMPTY: u32 = 0x0200;
+const SOFTWARE_LOOPBACK_ENABLE: u32 = 0x2000;
+const SETTABLE: u32 = DATA_TERMINAL_READY | REQUEST_TO_SEND | SOFTWARE_LOOPBACK_ENABLE;
+const CONTROL_MASK: u32 =
+    SETTABLE | CLEAR_TO_SEND | DATA_SET_READY | INPUT_BUFFER_EMPTY | OUTPUT_BUFFER_EMPTY;
+
+#[repr(C)]
+struct SerialIoMode {
+    control_mask: u32,
+    timeout: u32,
+    baud_rate: u64,
+    receive_fifo_depth: u32,
+    data_bits: u32,
+    parity: u32,
+    stop_bits: u32,
+}
+
+impl SerialIoMode {
+    fn new() -> Self {
+        Self {
+            control_mask: CONTROL_MASK,
+            timeout: DEFAULT_TIMEOUT,
+            baud_rate: DEFAULT_BAUD_RATE,
+            receive_fifo_depth: FIFO_DEPTH,
+            data_bits: u32::from(DEFAULT_DATA_BITS),
+            parity: NO_PARITY,
+            stop_bits: ONE_STOP_BIT,
+        }
+    }
+}
+
+/// Data on the line and the state of the port's control bits.
+#[derive(Default)]
+struct SerialLine {
+    transmitted: Vec<u8>,
+    received: VecDeque<u8>,
+    control: u32,
+    stalled: bool,
+}
+
+/// Simulated `EFI_SERIAL_IO_PROTOCOL` of a terminal port, capturing the transmitted bytes.
+///
+/// Writes accept at most the FIFO's depth before timing out, while reads time out once the received bytes run out.
+#[repr(C)]
+pub(crate) struct SerialIo {
+    revision: u32,
+    reset: extern "efiapi" fn(*const Self) -> EfiStatus,
+    set_attributes: extern "efiapi" fn(*const Self, u64, u32, u32, u32, u8, u32) -> EfiStatus,
+    set_control: extern "efiapi" fn(*const Self, u32) -> EfiStatus,
+    get_control: extern "efiapi" fn(*const Self, *mut u32) -> EfiStatus,
+    write: extern "efiapi" fn(*const Self, *mut usize, VoidPtr) -> EfiStatus,
+    read: extern "efiapi" fn(*const Self, *mut usize, VoidMutPtr) -> EfiStatus,
+    mode: *const SerialIoMode,
+    device_type_guid: *const EfiGuid,
+    mode_data: UnsafeCell<SerialIoMode>,
+    line: RefCell<SerialLine>,
+}
+
+impl SerialIo {
+    pub(crate) fn new() -> Box<Self> {
+        let mut serial_io: Box<Self> = Box::new(Self {
+            revision: SERIAL_IO_REVISION,
+            reset: serial_io_reset,
+            set_attributes: serial_io_set_attributes,
+            set_control: serial_io_set_control,
+            get_control: serial_io_get_control,
+            write: serial_io_write,
+            read: serial_io_read,
+            mode: 0 as _,
+            device_type_guid: &EFI_SERIAL_TERMINAL_DEVICE_TYPE_GUID,
+            mode_data: UnsafeCell::new(SerialIoMode::new()),
+            line: RefCell::new(SerialLine::default()),
+        });
+
+        serial_io.mode = serial_io.mode_data.get();
+
+        serial_io
+    }
+
+    fn mode_data(&self) -> *mut SerialIoMode {
+        self.mode_data.get()
+    }
+
+    pub(crate) fn transmitted(&self) -> Vec<u8> {
+        self.line.borrow().transmitted.clone()
+    }
+
+    pub(crate) fn receive(&self, data: &[u8]) {
+        self.line.borrow_mut().received.extend(data);
+    }
+
+    pub(crate) fn set_stalled(&self, stalled: bool) {
+        self.line.borrow_mut().stalled = stalled;
+    }
+}
+
+extern "efiapi" fn serial_io_reset(this: *const SerialIo) -> EfiStatus {
+    let this: &SerialIo = unsafe { &*this };
+
+    unsafe { *this.mode_data() = SerialIoMode::new() };
+
+    let mut line = this.line.borrow_mut();
+
+    line.received.clear();
+    line.control = 0;
+
+    EFI_SUCCESS
+}
+
+extern "efiapi" fn serial_io_set_attributes(
+    this: *const SerialIo,
+    baud_rate: u64,
+    receive_fifo_depth: u32,
+    timeout: u32,
+    parity: u32,
+    data_bits: u8,
+    stop_bits: u32,
+) -> EfiStatus {
+    if parity > MAX_PARITY
+        || stop_bits > MAX_STOP_BITS
+        || (data_bits != 0 && !(5..=8).contains(&data_bits))
+    {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    /* The FIFO can't be resized */
+    if receive_fifo_depth != 0 && receive_fifo_depth != FIFO_DEPTH {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    let mode: &mut SerialIoMode = unsafe { &mut *(*this).mode_data() };
+
+    let or_default = |value: u32, default: u32| if value == 0 { default } else { value };
+
+    mode.baud_rate = if baud_rate == 0 {
+        DEFAULT_BAUD_RATE
+    } else {
+        baud_rate
+    };
+    mode.timeout = or_default(timeout, DEFAULT_TIMEOUT);
+    mode.parity = or_default(parity, NO_PARITY);
+    mode.data_bits = or_default(u32::from(data_bits), u32::from(DEFAULT_DATA_BITS));
+    mode.stop_bits = or_default(stop_bits, ONE_STOP_BIT);
+
+    EFI_SUCCESS
+}
+
+extern "efiapi" fn serial_io_set_control(this: *const SerialIo, control: u32) -> EfiStatus {
+    if control & !SETTABLE != 0 {
+        return EFI_UNSUPPORTED;
+    }
+
+    unsafe { &*this }.line.borrow_mut().control = control;
+
+    EFI_SUCCESS
+}
+
+extern "efiapi" fn serial_io_get_control(this: *const SerialIo, control: *mut u32) -> EfiStatus {
+    if control.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    let line = unsafe { &*this }.line.borrow();
+
+    /* The other end is always ready */
+    let mut bits: u32 = line.control | CLEAR_TO_SEND | DATA_SET_READY | OUTPUT_BUFFER_EMPTY;
+
+    if line.received.is_empty() {
+        bits |= INPUT_BUFFER_EMPTY;
+    }
+
+    unsafe { *control = bits };
+
+    EFI_SUCCESS
+}
+
+extern "efiapi" fn serial_io_write(
+    this: *const SerialIo,
+    size: *mut usize,
+    buffer: VoidPtr,
+) -> EfiStatus {
+    if size.is_null() || (buffer.is_null() && unsafe { *size } != 0) {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    let mut line = unsafe { &*this }.line.borrow_mut();
+
+    let requested: usize = unsafe { *size };
+    let accepted: usize = if line.stalled {
+        0
+    } else {
+        requested.min(FIFO_DEPTH as usize)
+    };
+
+    if accepted != 0 {
+        let data: &[u8] = unsafe { from_raw_parts(buffer as *const u8, accepted) };
+
+        if line.control & SOFTWARE_LOOPBACK_ENABLE == 0 {
+            line.transmitted.extend_from_slice(data);
+        } else {
+            line.received.extend(data);
+        }
+    }
+
+    unsafe { *size = accepted };
+
+    if accepted == requested {
+        EFI_SUCCESS
+    } else {
+        EFI_TIMEOUT
+    }
+}
+
+extern "efiapi" fn serial_io_read(
+    this: *const SerialIo,
+    size: *mut usize,
+    buffer: VoidMutPtr,
+) -> EfiStatus {
+    if size.is_null() || (buffer.is_null() && unsafe { *size } != 0) {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    let mut line = unsafe { &*this }.line.borrow_mut();
+
+    let requested: usize = unsafe { *size };
+    let read: usize = requested.min(line.received.len());
+
+    if read != 0 {
+        let buffer: &mut [u8] = unsafe { from_raw_parts_mut(buffer as *mut u8, read) };
+
+        for (byte, received) in buffer.iter_mut().zip(line.received.drain(..read)) {
+            *byte = received;
+        }
+    }
+
+    unsafe { *size = read };
+
+    if read == requested {
+        EFI_SUCCESS
+    } else {
+        EFI_TIMEOUT
+    }
+}
@@ -0,0 +1,1071 @@
+use {
+    crate::{
+        boot_services::BootServicesTable,
+        console::{text_input_wait_for_key, Console, ConsoleStream, TextInput, TextOutput},
+        disk::{DiskProtocols, SimulatedDisk},
+        display::{DisplayProtocols, EdidProtocol},
+        events::{EventTable, EVT_NOTIFY_WAIT, TPL_NOTIFY},
+        firmware::{self, dispatch_notifications, Firmware, ResetHandler},
+        firmware_management::{FirmwareManagement, SimulatedFirmwareImage},
+        handles::HandleDatabase,
+        memory::Memory,
+        network::{ServiceBinding, ServiceKind, SimulatedNetwork},
+        nvdimm::{NvdimmProtocols, SimulatedNvdimm},
+        nvme::{NvmePassThru, SimulatedNvmeController},
+        pci::{self, PciIo, PciRootBridgeIo, SimulatedPciFunction},
+        pointer::{
+            absolute_pointer_wait_for_input, simple_pointer_wait_for_input, AbsolutePointer,
+            AbsolutePointerState, PointerInput, SimplePointer,
+        },
+        pxe::{PxeBaseCode, SimulatedPxeServer},
+        ram_disk::RamDisk,
+        rng::Rng,
+        runtime_services::RuntimeServicesTable,
+        serial::SerialIo,
+        tables::{
+            ConfigurationTableEntry, SystemTable, TableHeader, EFI_SIMULATED_REVISION,
+            EFI_SYSTEM_TABLE_SIGNATURE,
+        },
+        timestamp::Timestamp,
+        usb::{SimulatedUsbDevice, Usb2Hc, UsbIo},
+        variables::{Variable, VariableStore},
+    },
+    efi::{
+        boot_services::EfiBootServices,
+        guids,
+        runtime_services::{
+            miscellaneous::EfiResetType,
+            time::{EfiDaylight, EfiTimeRepresentation, EfiTimeZone},
+            EfiRuntimeServices,
+        },
+        EfiEvent, EfiGuid, EfiHandle, EfiStatus, EfiSystemTable, VoidPtr,
+    },
+    std::{cell::RefCell, convert::TryFrom, io, rc::Rc},
+};
+
+/// Configures the firmware a [`Simulator`] presents.
+pub struct SimulatorBuilder {
+    firmware_vendor: String,
+    firmware_revision: u32,
+    variables: Vec<(String, EfiGuid, u32, Vec<u8>)>,
+    disks: Vec<SimulatedDisk>,
+    displays: Vec<Vec<u8>>,
+    simple_pointer: bool,
+    absolute_pointer: Option<(u64, u64)>,
+    nvme_controller: Option<SimulatedNvmeController>,
+    usb_devices: Vec<SimulatedUsbDevice>,
+    timestamp: bool,
+    nvdimms: Vec<SimulatedNvdimm>,
+    ram_disk: bool,
+    firmware_images: Vec<SimulatedFirmwareImage>,
+    capsule_updates: bool,
+    network: Option<SimulatedNetwork>,
+    pxe_server: Option<SimulatedPxeServer>,
+    pci_functions: Vec<SimulatedPciFunction>,
+    serial_ports: usize,
+    rng: Option<u64>,
+    time: EfiTimeRepresentation,
+    reset_handler: ResetHandler,
+}
+
+impl SimulatorBuilder {
+    pub fn new() -> Self {
+        Self {
+            firmware_vendor: String::from("NautilOS Simulator"),
+            firmware_revision: 1,
+            variables: Vec::new(),
+            disks: Vec::new(),
+            displays: Vec::new(),
+            simple_pointer: false,
+            absolute_pointer: None,
+            nvme_controller: None,
+            usb_devices: Vec::new(),
+            timestamp: false,
+            nvdimms: Vec::new(),
+            ram_disk: false,
+            firmware_images: Vec::new(),
+            capsule_updates: true,
+            network: None,
+            pxe_server: None,
+            pci_functions: Vec::new(),
+            serial_ports: 0,
+            rng: None,
+            time: EfiTimeRepresentation::new(
+                (1, 1, 2021),
+                (0, 0, 0, 0),
+                EfiTimeZone::unspecified_time_zone(),
+                EfiDaylight::new(),
+            ),
+            reset_handler: Rc::new(|_: EfiResetType, status: EfiStatus, _: &[u8]| -> ! {
+                panic!("ResetSystem called with status {}!", status)
+            }),
+        }
+    }
+
+    pub fn firmware_vendor(mut self, vendor: &str) -> Self {
+        self.firmware_vendor = String::from(vendor);
+
+        self
+    }
+
+    pub fn firmware_revision(mut self, revision: u32) -> Self {
+        self.firmware_revision = revision;
+
+        self
+    }
+
+    /// Adds a variable present before the simulated image runs.
+    pub fn variable(mut self, name: &str, guid: EfiGuid, attributes: u32, data: &[u8]) -> Self {
+        self.variables
+            .push((String::from(name), guid, attributes, data.to_vec()));
+
+        self
+    }
+
+    /// Attaches a disk. Disks are numbered in the order they're added.
+    pub fn disk(mut self, disk: SimulatedDisk) -> Self {
+        self.disks.push(disk);
+
+        self
+    }
+
+    /// Attaches a display reporting the given EDID. Displays are numbered in the order they're added.
+    pub fn display(mut self, edid: &[u8]) -> Self {
+        self.displays.push(edid.to_vec());
+
+        self
+    }
+
+    /// Attaches a mouse, producing `EFI_SIMPLE_POINTER_PROTOCOL`.
+    pub fn simple_pointer(mut self) -> Self {
+        self.simple_pointer = true;
+
+        self
+    }
+
+    /// Attaches a touch screen with coordinates up to the given ones, producing `EFI_ABSOLUTE_POINTER_PROTOCOL`.
+    pub fn absolute_pointer(mut self, max_x: u64, max_y: u64) -> Self {
+        self.absolute_pointer = Some((max_x, max_y));
+
+        self
+    }
+
+    /// Attaches an NVMe controller, producing `EFI_NVM_EXPRESS_PASS_THRU_PROTOCOL`.
+    pub fn nvme_controller(mut self, controller: SimulatedNvmeController) -> Self {
+        self.nvme_controller = Some(controller);
+
+        self
+    }
+
+    /// Attaches a USB device to a root hub port of the host controller, producing `EFI_USB_IO_PROTOCOL`.
+    ///
+    /// Devices are numbered in the order they're added, which is also the number of their port.
+    /// The host controller, producing `EFI_USB2_HC_PROTOCOL`, is present once a device is attached.
+    pub fn usb_device(mut self, device: SimulatedUsbDevice) -> Self {
+        self.usb_devices.push(device);
+
+        self
+    }
+
+    /// Provides `EFI_TIMESTAMP_PROTOCOL`, whose counter follows the virtual clock at 10 MHz.
+    pub fn timestamp(mut self) -> Self {
+        self.timestamp = true;
+
+        self
+    }
+
+    /// Provides `EFI_RAM_DISK_PROTOCOL`, which exposes registered RAM disks through Block IO and Disk IO.
+    pub fn ram_disk(mut self) -> Self {
+        self.ram_disk = true;
+
+        self
+    }
+
+    /// Attaches an NVDIMM, producing `EFI_NVDIMM_LABEL_PROTOCOL` and a device path ending with it's NFIT device handle.
+    ///
+    /// NVDIMMs are numbered in the order they're added.
+    pub fn nvdimm(mut self, nvdimm: SimulatedNvdimm) -> Self {
+        self.nvdimms.push(nvdimm);
+
+        self
+    }
+
+    /// Adds an image to the device managed through `EFI_FIRMWARE_MANAGEMENT_PROTOCOL`.
+    ///
+    /// Images are numbered in the order they're added, starting from 1, and are listed in the ESRT.
+    pub fn firmware_image(mut self, image: SimulatedFirmwareImage) -> Self {
+        self.firmware_images.push(image);
+
+        self
+    }
+
+    /// Sets whether `UpdateCapsule` accepts capsules. When disabled, the capsule services return `EFI_UNSUPPORTED`.
+    pub fn capsule_updates(mut self, supported: bool) -> Self {
+        self.capsule_updates = supported;
+
+        self
+    }
+
+    /// Provides `EFI_RNG_PROTOCOL`, whose output is reproducible from the seed.
+    pub fn rng(mut self, seed: u64) -> Self {
+        self.rng = Some(seed);
+
+        self
+    }
+
+    /// Attaches a serial terminal port, producing `EFI_SERIAL_IO_PROTOCOL`.
+    ///
+    /// Ports are numbered in the order they're added. Each write accepts at most 16 bytes, the depth of the port's FIFO.
+    pub fn serial_port(mut self) -> Self {
+        self.serial_ports += 1;
+
+        self
+    }
+
+    /// Attaches a PCI function, producing `EFI_PCI_IO_PROTOCOL`.
+    ///
+    /// Functions are numbered in the order they're added, which is also their device number on bus 0.
+    /// The root bridge, producing `EFI_PCI_ROOT_BRIDGE_IO_PROTOCOL`, is present once a function is attached.
+    pub fn pci_function(mut self, function: SimulatedPciFunction) -> Self {
+        self.pci_functions.push(function);
+
+        self
+    }
+
+    /// Provides `EFI_PXE_BASE_CODE_PROTOCOL`, talking to the given server.
+    pub fn pxe(mut self, server: SimulatedPxeServer) -> Self {
+        self.pxe_server = Some(server);
+
+        self
+    }
+
+    /// Attaches a network, producing the service binding protocols of `EFI_UDP4_PROTOCOL`, `EFI_TCP4_PROTOCOL` and `EFI_HTTP_PROTOCOL` on a single handle.
+    pub fn network(mut self, network: SimulatedNetwork) -> Self {
+        self.network = Some(network);
+
+        self
+    }
+
+    pub fn time(mut self, time: EfiTimeRepresentation) -> Self {
+        self.time = time;
+
+        self
+    }
+
+    /// Sets what happens when `ResetSystem` is called.
+    ///
+    /// The handler is called from within a firmware service and thus must not unwind.
+    /// By default it panics, which aborts the process.
+    pub fn on_reset<F: Fn(EfiResetType, EfiStatus, &[u8]) -> ! + 'static>(
+        mut self,
+        handler: F,
+    ) -> Self {
+        self.reset_handler = Rc::new(handler);
+
+        self
+    }
+
+    /// Builds the tables and makes the simulator active on the current thread.
+    ///
+    /// Only one simulator can be active per thread at a time.
+    pub fn build(self) -> io::Result<Simulator> {
+        if self.usb_devices.len() > usize::from(u8::MAX) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "The root hub has at most 255 ports!",
+            ));
+        }
+
+        if self
+            .nvdimms
+            .iter()
+            .any(|nvdimm: &SimulatedNvdimm| u32::try_from(nvdimm.label_storage_size()).is_err())
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Label storage area can't be larger than 4 GiB!",
+            ));
+        }
+
+        if self
+            .pxe_server
+            .as_ref()
+            .is_some_and(|server: &SimulatedPxeServer| !server.validate())
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "The PXE boot file name doesn't fit the DHCP packet!",
+            ));
+        }
+
+        let mut variables: VariableStore = VariableStore::default();
+
+        for (name, guid, attributes, data) in &self.variables {
+            let name: Vec<u16> = name.encode_utf16().collect();
+
+            variables
+                .set(&name, guid, *attributes, data, false)
+                .map_err(|status: EfiStatus| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("Variable rejected with status {}!", status),
+                    )
+                })?;
+        }
+
+        let mut events: EventTable = EventTable::new();
+
+        let wait_for_key: EfiEvent = events
+            .create(
+                EVT_NOTIFY_WAIT,
+                TPL_NOTIFY,
+                Some(text_input_wait_for_key),
+                0 as _,
+                None,
+            )
+            .unwrap_or_else(|status: EfiStatus| {
+                panic!("Creating the WaitForKey event failed with {}!", status)
+            });
+
+        let mut create_wait_event = |notify_function: extern "efiapi" fn(EfiEvent, VoidPtr)| {
+            events
+                .create(
+                    EVT_NOTIFY_WAIT,
+                    TPL_NOTIFY,
+                    Some(notify_function),
+                    0 as _,
+                    None,
+                )
+                .unwrap_or_else(|status: EfiStatus| {
+                    panic!("Creating the WaitForInput event failed with {}!", status)
+                })
+        };
+
+        let simple_pointer: Option<Box<SimplePointer>> = if self.simple_pointer {
+            Some(SimplePointer::new(create_wait_event(
+                simple_pointer_wait_for_input,
+            )))
+        } else {
+            None
+        };
+        let absolute_pointer: Option<Box<AbsolutePointer>> =
+            self.absolute_pointer.map(|(max_x, max_y): (u64, u64)| {
+                AbsolutePointer::new(
+                    create_wait_event(absolute_pointer_wait_for_input),
+                    max_x,
+                    max_y,
+                )
+            });
+
+        let con_in: Box<TextInput> = TextInput::new(wait_for_key);
+        let con_out: Box<TextOutput> = TextOutput::new(ConsoleStream::Output);
+        let std_err: Box<TextOutput> = TextOutput::new(ConsoleStream::Error);
+
+        let mut handles: HandleDatabase = HandleDatabase::default();
+
+        let mut install = |handle: EfiHandle, guid: &EfiGuid, interface: VoidPtr| -> EfiHandle {
+            handles
+                .install(handle, guid, interface)
+                .unwrap_or_else(|status: EfiStatus| {
+                    panic!("Installing a simulated protocol failed with {}!", status)
+                })
+                .0
+        };
+
+        let console_in_handle: EfiHandle = install(
+            0 as _,
+            &guids::EFI_SIMPLE_TEXT_INPUT_PROTOCOL,
+            &*con_in as *const TextInput as VoidPtr,
+        );
+        let console_out_handle: EfiHandle = install(
+            0 as _,
+            &guids::EFI_SIMPLE_TEXT_OUTPUT_PROTOCOL,
+            &*con_out as *const TextOutput as VoidPtr,
+        );
+        let standard_error_handle: EfiHandle = install(
+            0 as _,
+            &guids::EFI_SIMPLE_TEXT_OUTPUT_PROTOCOL,
+            &*std_err as *const TextOutput as VoidPtr,
+        );
+
+        let mut disks: Vec<(EfiHandle, DiskProtocols)> = Vec::with_capacity(self.disks.len());
+
+        for (index, disk) in self.disks.iter().enumerate() {
+            let protocols: DiskProtocols = DiskProtocols::open(disk, index as u32)?;
+
+            let handle: EfiHandle = install(
+                0 as _,
+                &guids::EFI_DEVICE_PATH_PROTOCOL,
+                protocols.device_path.as_ptr() as VoidPtr,
+            );
+            install(
+                handle,
+                &guids::EFI_BLOCK_IO_PROTOCOL,
+                &*protocols.block_io as *const _ as VoidPtr,
+            );
+            install(
+                handle,
+                &guids::EFI_DISK_IO_PROTOCOL,
+                &*protocols.disk_io as *const _ as VoidPtr,
+            );
+            install(
+                handle,
+                &guids::EFI_BLOCK_IO2_PROTOCOL,
+                &*protocols.block_io2 as *const _ as VoidPtr,
+            );
+            install(
+                handle,
+                &guids::EFI_DISK_IO2_PROTOCOL,
+                &*protocols.disk_io2 as *const _ as VoidPtr,
+            );
+
+            disks.push((handle, protocols));
+        }
+
+        let mut displays: Vec<(EfiHandle, DisplayProtocols)> =
+            Vec::with_capacity(self.displays.len());
+
+        for edid in &self.displays {
+            let protocols: DisplayProtocols = DisplayProtocols::new(edid);
+
+            let handle: EfiHandle = install(
+                0 as _,
+                &guids::EFI_EDID_DISCOVERED_PROTOCOL,
+                &*protocols.discovered as *const EdidProtocol as VoidPtr,
+            );
+            install(
+                handle,
+                &guids::EFI_EDID_ACTIVE_PROTOCOL,
+                &*protocols.active as *const EdidProtocol as VoidPtr,
+            );
+
+            displays.push((handle, protocols));
+        }
+
+        let simple_pointer: Option<(EfiHandle, Box<SimplePointer>)> =
+            simple_pointer.map(|protocol: Box<SimplePointer>| {
+                let handle: EfiHandle = install(
+                    0 as _,
+                    &guids::EFI_SIMPLE_POINTER_PROTOCOL,
+                    &*protocol as *const SimplePointer as VoidPtr,
+                );
+
+                (handle, protocol)
+            });
+        let absolute_pointer: Option<(EfiHandle, Box<AbsolutePointer>)> =
+            absolute_pointer.map(|protocol: Box<AbsolutePointer>| {
+                let handle: EfiHandle = install(
+                    0 as _,
+                    &guids::EFI_ABSOLUTE_POINTER_PROTOCOL,
+                    &*protocol as *const AbsolutePointer as VoidPtr,
+                );
+
+                (handle, protocol)
+            });
+
+        let nvme: Option<(EfiHandle, Box<NvmePassThru>)> = match &self.nvme_controller {
+            Some(controller) => {
+                let protocol: Box<NvmePassThru> = NvmePassThru::new(controller)?;
+
+                let handle: EfiHandle = install(
+                    0 as _,
+                    &guids::EFI_NVM_EXPRESS_PASS_THRU_PROTOCOL,
+                    &*protocol as *const NvmePassThru as VoidPtr,
+                );
+
+                Some((handle, protocol))
+            }
+            None => None,
+        };
+
+        let usb_host_controller: Option<(EfiHandle, Box<Usb2Hc>)> = if self.usb_devices.is_empty() {
+            None
+        } else {
+            let protocol: Box<Usb2Hc> = Usb2Hc::new(self.usb_devices.len());
+
+            let handle: EfiHandle = install(
+                0 as _,
+                &guids::EFI_USB2_HC_PROTOCOL,
+                &*protocol as *const Usb2Hc as VoidPtr,
+            );
+
+            Some((handle, protocol))
+        };
+        let usb_devices: Vec<(EfiHandle, Box<UsbIo>)> = self
+            .usb_devices
+            .iter()
+            .map(|device: &SimulatedUsbDevice| {
+                let protocol: Box<UsbIo> = UsbIo::new(device);
+
+                let handle: EfiHandle = install(
+                    0 as _,
+                    &guids::EFI_USB_IO_PROTOCOL,
+                    &*protocol as *const UsbIo as VoidPtr,
+                );
+
+                (handle, protocol)
+            })
+            .collect();
+
+        let timestamp: Option<(EfiHandle, Box<Timestamp>)> = if self.timestamp {
+            let protocol: Box<Timestamp> = Timestamp::new();
+
+            let handle: EfiHandle = install(
+                0 as _,
+                &guids::EFI_TIMESTAMP_PROTOCOL,
+                &*protocol as *const Timestamp as VoidPtr,
+            );
+
+            Some((handle, protocol))
+        } else {
+            None
+        };
+
+        let nvdimms: Vec<(EfiHandle, NvdimmProtocols)> = self
+            .nvdimms
+            .iter()
+            .map(|nvdimm: &SimulatedNvdimm| {
+                let protocols: NvdimmProtocols = NvdimmProtocols::new(nvdimm);
+
+                let handle: EfiHandle = install(
+                    0 as _,
+                    &guids::EFI_DEVICE_PATH_PROTOCOL,
+                    protocols.device_path.as_ptr() as VoidPtr,
+                );
+                install(
+                    handle,
+                    &guids::EFI_NVDIMM_LABEL_PROTOCOL,
+                    &*protocols.label as *const _ as VoidPtr,
+                );
+
+                (handle, protocols)
+            })
+            .collect();
+
+        let ram_disk: Option<(EfiHandle, Box<RamDisk>)> = if self.ram_disk {
+            let protocol: Box<RamDisk> = RamDisk::new();
+
+            let handle: EfiHandle = install(
+                0 as _,
+                &guids::EFI_RAM_DISK_PROTOCOL,
+                &*protocol as *const RamDisk as VoidPtr,
+            );
+
+            Some((handle, protocol))
+        } else {
+            None
+        };
+
+        let firmware_management: Option<(EfiHandle, Box<FirmwareManagement>)> =
+            if self.firmware_images.is_empty() {
+                None
+            } else {
+                let protocol: Box<FirmwareManagement> =
+                    FirmwareManagement::new(&self.firmware_images);
+
+                let handle: EfiHandle = install(
+                    0 as _,
+                    &guids::EFI_FIRMWARE_MANAGEMENT_PROTOCOL,
+                    &*protocol as *const FirmwareManagement as VoidPtr,
+                );
+
+                Some((handle, protocol))
+            };
+
+        let rng: Option<(EfiHandle, Box<Rng>)> = self.rng.map(|seed: u64| {
+            let protocol: Box<Rng> = Rng::new(seed);
+
+            let handle: EfiHandle = install(
+                0 as _,
+                &guids::EFI_RNG_PROTOCOL,
+                &*protocol as *const Rng as VoidPtr,
+            );
+
+            (handle, protocol)
+        });
+
+        let serial_ports: Vec<(EfiHandle, Box<SerialIo>)> = (0..self.serial_ports)
+            .map(|_| {
+                let protocol: Box<SerialIo> = SerialIo::new();
+
+                let handle: EfiHandle = install(
+                    0 as _,
+                    &guids::EFI_SERIAL_IO_PROTOCOL,
+                    &*protocol as *const SerialIo as VoidPtr,
+                );
+
+                (handle, protocol)
+            })
+            .collect();
+
+        let pci_functions: Vec<Box<PciIo>> = pci::functions(&self.pci_functions)?;
+        let pci_root_bridge: Option<(EfiHandle, Box<PciRootBridgeIo>)> = if pci_functions.is_empty()
+        {
+            None
+        } else {
+            let protocol: Box<PciRootBridgeIo> = PciRootBridgeIo::new(&pci_functions);
+
+            let handle: EfiHandle = install(
+                0 as _,
+                &guids::EFI_PCI_ROOT_BRIDGE_IO_PROTOCOL,
+                &*protocol as *const PciRootBridgeIo as VoidPtr,
+            );
+
+            Some((handle, protocol))
+        };
+        let pci_functions: Vec<(EfiHandle, Box<PciIo>)> = pci_functions
+            .into_iter()
+            .map(|protocol: Box<PciIo>| {
+                let handle: EfiHandle = install(
+                    0 as _,
+                    &guids::EFI_PCI_IO_PROTOCOL,
+                    &*protocol as *const PciIo as VoidPtr,
+                );
+
+                (handle, protocol)
+            })
+            .collect();
+
+        let pxe_base_code: Option<(EfiHandle, Box<PxeBaseCode>)> =
+            self.pxe_server.as_ref().map(|server: &SimulatedPxeServer| {
+                let protocol: Box<PxeBaseCode> = PxeBaseCode::new(server.clone());
+
+                let handle: EfiHandle = install(
+                    0 as _,
+                    &guids::EFI_PXE_BASE_CODE_PROTOCOL,
+                    &*protocol as *const PxeBaseCode as VoidPtr,
+                );
+
+                (handle, protocol)
+            });
+
+        let network: Option<(EfiHandle, Vec<Box<ServiceBinding>>)> =
+            self.network.as_ref().map(|network: &SimulatedNetwork| {
+                let network: Rc<SimulatedNetwork> = Rc::new(network.clone());
+
+                let service_bindings: Vec<Box<ServiceBinding>> =
+                    [ServiceKind::Udp4, ServiceKind::Tcp4, ServiceKind::Http]
+                        .iter()
+                        .map(|&kind: &ServiceKind| ServiceBinding::new(kind, Rc::clone(&network)))
+                        .collect();
+
+                let handle: EfiHandle = service_bindings.iter().fold(
+                    0 as _,
+                    |handle: EfiHandle, service_binding: &Box<ServiceBinding>| {
+                        install(
+                            handle,
+                            service_binding.guid(),
+                            &**service_binding as *const ServiceBinding as VoidPtr,
+                        )
+                    },
+                );
+
+                (handle, service_bindings)
+            });
+
+        let boot_services: Box<BootServicesTable> = BootServicesTable::new();
+        let runtime_services: Box<RuntimeServicesTable> = RuntimeServicesTable::new();
+        let firmware_vendor: Vec<u16> =
+            self.firmware_vendor.encode_utf16().chain(Some(0)).collect();
+
+        let mut system_table: Box<SystemTable> = Box::new(SystemTable {
+            header: TableHeader::new::<SystemTable>(
+                EFI_SYSTEM_TABLE_SIGNATURE,
+                EFI_SIMULATED_REVISION,
+            ),
+            firmware_vendor: firmware_vendor.as_ptr(),
+            firmware_revision: self.firmware_revision,
+            console_in_handle,
+            con_in: &*con_in,
+            console_out_handle,
+            con_out: &*con_out,
+            standard_error_handle,
+            std_err: &*std_err,
+            runtime_services: &*runtime_services,
+            boot_services: &*boot_services,
+            configuration_tables_count: 0,
+            configuration_tables: 0 as _,
+        });
+
+        let mut firmware: Firmware = Firmware {
+            variables,
+            handles,
+            memory: Memory::new(),
+            events,
+            console: Console::default(),
+            pointer: PointerInput::default(),
+            configuration_tables: Vec::new(),
+            system_table: &mut *system_table,
+            time: self.time,
+            wakeup: (false, self.time),
+            monotonic_count: 0,
+            watchdog: Some(300),
+            boot_services_exited: false,
+            virtual_address_map_set: false,
+            capsule_updates: self.capsule_updates,
+            capsules: Vec::new(),
+            ram_disks: Vec::new(),
+            io2_requests: Vec::new(),
+            reset_handler: self.reset_handler,
+        };
+
+        if let Some((_, protocol)) = &firmware_management {
+            firmware.configuration_tables.push(ConfigurationTableEntry {
+                guid: guids::EFI_SYSTEM_RESOURCE_TABLE,
+                table: protocol.esrt(),
+            });
+        }
+
+        firmware.update_configuration_tables();
+
+        let firmware: Rc<RefCell<Firmware>> = Rc::new(RefCell::new(firmware));
+
+        firmware::install(firmware.clone());
+
+        Ok(Simulator {
+            firmware,
+            system_table,
+            boot_services,
+            runtime_services,
+            con_in,
+            con_out,
+            std_err,
+            firmware_vendor,
+            disks,
+            displays,
+            simple_pointer,
+            absolute_pointer,
+            nvme,
+            usb_host_controller,
+            usb_devices,
+            timestamp,
+            nvdimms,
+            ram_disk,
+            firmware_management,
+            network,
+            pxe_base_code,
+            pci_root_bridge,
+            pci_functions,
+            serial_ports,
+            rng,
+        })
+    }
+}
+
+impl Default for SimulatorBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Firmware simulated in-process, for running code written against the `efi` crate on the host.
+///
+/// The tables it hands out stay valid until the simulator is dropped.
+pub struct Simulator {
+    firmware: Rc<RefCell<Firmware>>,
+    system_table: Box<SystemTable>,
+    /* Only reached through the system table, which points into them */
+    #[allow(dead_code)]
+    boot_services: Box<BootServicesTable>,
+    #[allow(dead_code)]
+    runtime_services: Box<RuntimeServicesTable>,
+    #[allow(dead_code)]
+    con_in: Box<TextInput>,
+    con_out: Box<TextOutput>,
+    #[allow(dead_code)]
+    std_err: Box<TextOutput>,
+    #[allow(dead_code)]
+    firmware_vendor: Vec<u16>,
+    disks: Vec<(EfiHandle, DiskProtocols)>,
+    displays: Vec<(EfiHandle, DisplayProtocols)>,
+    simple_pointer: Option<(EfiHandle, Box<SimplePointer>)>,
+    absolute_pointer: Option<(EfiHandle, Box<AbsolutePointer>)>,
+    nvme: Option<(EfiHandle, Box<NvmePassThru>)>,
+    usb_host_controller: Option<(EfiHandle, Box<Usb2Hc>)>,
+    usb_devices: Vec<(EfiHandle, Box<UsbIo>)>,
+    /* Only reached through it's handle */
+    #[allow(dead_code)]
+    timestamp: Option<(EfiHandle, Box<Timestamp>)>,
+    nvdimms: Vec<(EfiHandle, NvdimmProtocols)>,
+    ram_disk: Option<(EfiHandle, Box<RamDisk>)>,
+    firmware_management: Option<(EfiHandle, Box<FirmwareManagement>)>,
+    network: Option<(EfiHandle, Vec<Box<ServiceBinding>>)>,
+    pxe_base_code: Option<(EfiHandle, Box<PxeBaseCode>)>,
+    pci_root_bridge: Option<(EfiHandle, Box<PciRootBridgeIo>)>,
+    pci_functions: Vec<(EfiHandle, Box<PciIo>)>,
+    serial_ports: Vec<(EfiHandle, Box<SerialIo>)>,
+    rng: Option<(EfiHandle, Box<Rng>)>,
+}
+
+impl Simulator {
+    pub fn builder() -> SimulatorBuilder {
+        SimulatorBuilder::new()
+    }
+
+    /// Returns the system table, which is only valid as long as the simulator is.
+    pub fn system_table(&self) -> &EfiSystemTable {
+        unsafe { &*(&*self.system_table as *const SystemTable as *const EfiSystemTable) }
+    }
+
+    /// Returns the boot services, which unlike through the system table can't outlive the simulator.
+    pub fn boot_services(&self) -> &EfiBootServices {
+        self.system_table().boot_services()
+    }
+
+    /// Returns the runtime services, which unlike through the system table can't outlive the simulator.
+    pub fn runtime_services(&self) -> &EfiRuntimeServices {
+        self.system_table().runtime_services()
+    }
+
+    /// Returns everything written to the console output so far.
+    pub fn console_output(&self) -> String {
+        self.firmware.borrow().console.output.clone()
+    }
+
+    /// Returns the rows currently displayed on the console output, without trailing spaces.
+    pub fn console_screen(&self) -> Vec<String> {
+        self.con_out.screen()
+    }
+
+    /// Returns the attribute of the console output's cell, if it's within the screen.
+    pub fn console_attribute(&self, column: usize, row: usize) -> Option<u8> {
+        self.con_out.attribute(column, row)
+    }
+
+    /// Returns everything written to the standard error console so far.
+    pub fn standard_error(&self) -> String {
+        self.firmware.borrow().console.error.clone()
+    }
+
+    /// Queues a key stroke for the console input.
+    pub fn push_key(&self, scan_code: u16, unicode_char: u16) {
+        self.firmware
+            .borrow_mut()
+            .console
+            .keys
+            .push_back((scan_code, unicode_char));
+    }
+
+    /// Queues the string's characters for the console input.
+    pub fn push_str(&self, string: &str) {
+        for character in string.encode_utf16() {
+            self.push_key(0, character);
+        }
+    }
+
+    /// Moves the mouse by the given counts and sets it's buttons' state.
+    pub fn move_pointer(&self, x: i32, y: i32, z: i32, left_button: bool, right_button: bool) {
+        self.firmware
+            .borrow_mut()
+            .pointer
+            .push_movement(x, y, z, left_button, right_button);
+    }
+
+    /// Moves the touch screen's pointer to the given coordinates, with the `EFI_ABSP_*` buttons active.
+    pub fn touch(&self, x: u64, y: u64, active_buttons: u32) {
+        self.firmware.borrow_mut().pointer.position =
+            Some(AbsolutePointerState::new(x, y, active_buttons));
+    }
+
+    /// Returns a variable's attributes and data, as boot services would see it.
+    pub fn variable(&self, name: &str, guid: &EfiGuid) -> Option<(u32, Vec<u8>)> {
+        let name: Vec<u16> = name.encode_utf16().collect();
+
+        self.firmware
+            .borrow()
+            .variables
+            .get(&name, guid, false)
+            .map(|variable: &Variable| (variable.attributes, variable.data.clone()))
+    }
+
+    pub fn exited_boot_services(&self) -> bool {
+        self.firmware.borrow().boot_services_exited
+    }
+
+    /// Returns the timeout in seconds of the armed watchdog timer, if any.
+    pub fn watchdog_timeout(&self) -> Option<usize> {
+        self.firmware.borrow().watchdog
+    }
+
+    /// Returns the handle carrying the protocols of the disk with the given index.
+    pub fn disk_handle(&self, index: usize) -> Option<EfiHandle> {
+        self.disks
+            .get(index)
+            .map(|&(handle, _): &(EfiHandle, DiskProtocols)| handle)
+    }
+
+    /// Returns the handle carrying the EDID protocols of the display with the given index.
+    pub fn display_handle(&self, index: usize) -> Option<EfiHandle> {
+        self.displays
+            .get(index)
+            .map(|&(handle, _): &(EfiHandle, DisplayProtocols)| handle)
+    }
+
+    /// Returns the handle carrying the simple pointer protocol, if a mouse was attached.
+    pub fn simple_pointer_handle(&self) -> Option<EfiHandle> {
+        self.simple_pointer
+            .as_ref()
+            .map(|&(handle, _): &(EfiHandle, Box<SimplePointer>)| handle)
+    }
+
+    /// Returns the handle carrying the absolute pointer protocol, if a touch screen was attached.
+    pub fn absolute_pointer_handle(&self) -> Option<EfiHandle> {
+        self.absolute_pointer
+            .as_ref()
+            .map(|&(handle, _): &(EfiHandle, Box<AbsolutePointer>)| handle)
+    }
+
+    /// Returns the handle carrying the NVMe pass-through protocol, if a controller was attached.
+    pub fn nvme_handle(&self) -> Option<EfiHandle> {
+        self.nvme
+            .as_ref()
+            .map(|&(handle, _): &(EfiHandle, Box<NvmePassThru>)| handle)
+    }
+
+    /// Returns the handle carrying the USB2 host controller protocol, if any USB device was attached.
+    pub fn usb_host_controller_handle(&self) -> Option<EfiHandle> {
+        self.usb_host_controller
+            .as_ref()
+            .map(|&(handle, _): &(EfiHandle, Box<Usb2Hc>)| handle)
+    }
+
+    /// Returns the handle carrying the USB I/O protocol of the device with the given index.
+    pub fn usb_device_handle(&self, index: usize) -> Option<EfiHandle> {
+        self.usb_devices
+            .get(index)
+            .map(|&(handle, _): &(EfiHandle, Box<UsbIo>)| handle)
+    }
+
+    /// Returns the handle carrying the protocols of the NVDIMM with the given index.
+    pub fn nvdimm_handle(&self, index: usize) -> Option<EfiHandle> {
+        self.nvdimms
+            .get(index)
+            .map(|&(handle, _): &(EfiHandle, NvdimmProtocols)| handle)
+    }
+
+    /// Returns the current contents of the label storage area of the NVDIMM with the given index.
+    pub fn nvdimm_label_storage(&self, index: usize) -> Option<Vec<u8>> {
+        self.nvdimms
+            .get(index)
+            .map(|(_, protocols): &(EfiHandle, NvdimmProtocols)| protocols.label.label_storage())
+    }
+
+    /// Returns the handle carrying the RAM disk protocol, if it's provided.
+    pub fn ram_disk_handle(&self) -> Option<EfiHandle> {
+        self.ram_disk
+            .as_ref()
+            .map(|&(handle, _): &(EfiHandle, Box<RamDisk>)| handle)
+    }
+
+    /// Returns the number of RAM disks currently registered.
+    pub fn ram_disk_count(&self) -> usize {
+        self.firmware.borrow().ram_disks.len()
+    }
+
+    /// Returns the handle carrying the firmware management protocol, if any image was added.
+    pub fn firmware_management_handle(&self) -> Option<EfiHandle> {
+        self.firmware_management
+            .as_ref()
+            .map(|&(handle, _): &(EfiHandle, Box<FirmwareManagement>)| handle)
+    }
+
+    /// Returns the version and data of the firmware image with the given index, starting from 0.
+    pub fn firmware_image(&self, index: usize) -> Option<(u32, Vec<u8>)> {
+        self.firmware_management
+            .as_ref()
+            .and_then(|(_, protocol): &(EfiHandle, Box<FirmwareManagement>)| protocol.image(index))
+    }
+
+    /// Returns the handle carrying the random number generator protocol, if it's provided.
+    pub fn rng_handle(&self) -> Option<EfiHandle> {
+        self.rng
+            .as_ref()
+            .map(|&(handle, _): &(EfiHandle, Box<Rng>)| handle)
+    }
+
+    /// Returns the handle carrying the serial I/O protocol of the port with the given index.
+    pub fn serial_port_handle(&self, index: usize) -> Option<EfiHandle> {
+        self.serial_ports
+            .get(index)
+            .map(|&(handle, _): &(EfiHandle, Box<SerialIo>)| handle)
+    }
+
+    /// Returns the bytes transmitted so far by the port with the given index.
+    pub fn serial_output(&self, index: usize) -> Option<Vec<u8>> {
+        self.serial_ports
+            .get(index)
+            .map(|(_, protocol): &(EfiHandle, Box<SerialIo>)| protocol.transmitted())
+    }
+
+    /// Queues bytes to be read from the port with the given index.
+    ///
+    /// # Panics
+    /// Panics if there's no port with the given index.
+    pub fn push_serial_input(&self, index: usize, data: &[u8]) {
+        self.serial_ports[index].1.receive(data);
+    }
+
+    /// Sets whether the port with the given index stops accepting bytes, as if the other end stopped reading them.
+    ///
+    /// # Panics
+    /// Panics if there's no port with the given index.
+    pub fn stall_serial_port(&self, index: usize, stalled: bool) {
+        self.serial_ports[index].1.set_stalled(stalled);
+    }
+
+    /// Returns the handle carrying the PCI root bridge I/O protocol, if any PCI function was attached.
+    pub fn pci_root_bridge_handle(&self) -> Option<EfiHandle> {
+        self.pci_root_bridge
+            .as_ref()
+            .map(|&(handle, _): &(EfiHandle, Box<PciRootBridgeIo>)| handle)
+    }
+
+    /// Returns the handle carrying the PCI I/O protocol of the function with the given index.
+    pub fn pci_function_handle(&self, index: usize) -> Option<EfiHandle> {
+        self.pci_functions
+            .get(index)
+            .map(|&(handle, _): &(EfiHandle, Box<PciIo>)| handle)
+    }
+
+    /// Returns the current contents of BAR 0 of the function with the given index.
+    pub fn pci_bar(&self, index: usize) -> Option<Vec<u8>> {
+        self.pci_functions
+            .get(index)
+            .map(|(_, protocol): &(EfiHandle, Box<PciIo>)| protocol.bar())
+    }
+
+    /// Returns the handle carrying the PXE base code protocol, if a PXE server was given.
+    pub fn pxe_handle(&self) -> Option<EfiHandle> {
+        self.pxe_base_code
+            .as_ref()
+            .map(|&(handle, _): &(EfiHandle, Box<PxeBaseCode>)| handle)
+    }
+
+    /// Returns the handle carrying the network's service binding protocols, if a network was attached.
+    pub fn network_handle(&self) -> Option<EfiHandle> {
+        self.network
+            .as_ref()
+            .map(|(handle, _): &(EfiHandle, Vec<Box<ServiceBinding>>)| *handle)
+    }
+
+    /// Returns the capsules passed to `UpdateCapsule` so far, as contiguous buffers.
+    pub fn capsules(&self) -> Vec<Vec<u8>> {
+        self.firmware.borrow().capsules.clone()
+    }
+
+    /// Advances the virtual clock, firing any timer which expires in the meantime.
+    pub fn advance_time(&self, microseconds: u64) {
+        self.firmware
+            .borrow_mut()
+            .events
+            .advance(microseconds.saturating_mul(10));
+
+        dispatch_notifications();
+    }
+}
+
+impl Drop for Simulator {
+    fn drop(&mut self) {
+        firmware::uninstall();
+    }
+}
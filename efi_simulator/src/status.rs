@@ -0,0 +1,27 @@
+use efi::EfiStatus;
+
+pub(crate) const EFI_SUCCESS: EfiStatus = EfiStatus::success();
+pub(crate) const EFI_INVALID_PARAMETER: EfiStatus = EfiStatus::error(2);
+pub(crate) const EFI_UNSUPPORTED: EfiStatus = EfiStatus::error(3);
+pub(crate) const EFI_BAD_BUFFER_SIZE: EfiStatus = EfiStatus::error(4);
+pub(crate) const EFI_BUFFER_TOO_SMALL: EfiStatus = EfiStatus::error(5);
+pub(crate) const EFI_NOT_READY: EfiStatus = EfiStatus::error(6);
+pub(crate) const EFI_DEVICE_ERROR: EfiStatus = EfiStatus::error(7);
+pub(crate) const EFI_WRITE_PROTECTED: EfiStatus = EfiStatus::error(8);
+pub(crate) const EFI_OUT_OF_RESOURCES: EfiStatus = EfiStatus::error(9);
+pub(crate) const EFI_MEDIA_CHANGED: EfiStatus = EfiStatus::error(13);
+pub(crate) const EFI_NOT_FOUND: EfiStatus = EfiStatus::error(14);
+pub(crate) const EFI_ACCESS_DENIED: EfiStatus = EfiStatus::error(15);
+pub(crate) const EFI_TIMEOUT: EfiStatus = EfiStatus::error(18);
+pub(crate) const EFI_NOT_STARTED: EfiStatus = EfiStatus::error(19);
+pub(crate) const EFI_ALREADY_STARTED: EfiStatus = EfiStatus::error(20);
+pub(crate) const EFI_ABORTED: EfiStatus = EfiStatus::error(21);
+pub(crate) const EFI_TFTP_ERROR: EfiStatus = EfiStatus::error(23);
+
+/// Collapses the result of a simulated service into the status returned to the caller.
+pub(crate) fn into_status(result: Result<(), EfiStatus>) -> EfiStatus {
+    match result {
+        Ok(()) => EFI_SUCCESS,
+        Err(status) => status,
+    }
+}
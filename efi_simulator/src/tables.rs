@@ -0,0 +1,67 @@
+use {
+    crate::{
+        boot_services::BootServicesTable,
+        console::{TextInput, TextOutput},
+        runtime_services::RuntimeServicesTable,
+    },
+    efi::{EfiGuid, EfiHandle, EfiTableHeader, VoidPtr},
+    std::mem::size_of,
+};
+
+pub(crate) const EFI_SYSTEM_TABLE_SIGNATURE: u64 = 0x5453_5953_2049_4249;
+pub(crate) const EFI_BOOT_SERVICES_SIGNATURE: u64 = 0x5652_4553_544F_4F42;
+pub(crate) const EFI_RUNTIME_SERVICES_SIGNATURE: u64 = 0x5652_4553_544E_5552;
+
+/* Revision 2.70 */
+pub(crate) const EFI_SIMULATED_REVISION: u32 = (2 << 16) | 70;
+
+#[repr(C)]
+pub(crate) struct TableHeader {
+    signature: u64,
+    revision: u32,
+    header_size: u32,
+    crc32: u32,
+    reserved: u32,
+}
+
+impl TableHeader {
+    /// Creates a header describing a table of type `T`, which has to start with the header.
+    pub(crate) fn new<T>(signature: u64, revision: u32) -> Self {
+        Self {
+            signature,
+            revision,
+            header_size: size_of::<T>() as u32,
+            crc32: 0,
+            reserved: 0,
+        }
+    }
+
+    /// Recalculates the checksum with the same algorithm `EfiTableHeader::verify_table` uses.
+    pub(crate) fn update_crc32(&mut self) {
+        unsafe { &mut *(self as *mut Self as *mut EfiTableHeader) }.update_crc32()
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub(crate) struct ConfigurationTableEntry {
+    pub(crate) guid: EfiGuid,
+    pub(crate) table: VoidPtr,
+}
+
+#[repr(C)]
+pub(crate) struct SystemTable {
+    pub(crate) header: TableHeader,
+    pub(crate) firmware_vendor: *const u16,
+    pub(crate) firmware_revision: u32,
+    pub(crate) console_in_handle: EfiHandle,
+    pub(crate) con_in: *const TextInput,
+    pub(crate) console_out_handle: EfiHandle,
+    pub(crate) con_out: *const TextOutput,
+    pub(crate) standard_error_handle: EfiHandle,
+    pub(crate) std_err: *const TextOutput,
+    pub(crate) runtime_services: *const RuntimeServicesTable,
+    pub(crate) boot_services: *const BootServicesTable,
+    pub(crate) configuration_tables_count: usize,
+    pub(crate) configuration_tables: *const ConfigurationTableEntry,
+}
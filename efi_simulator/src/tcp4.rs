@@ -0,0 +1,547 @@
+use {
+    crate::{
+        boot_services::service,
+        firmware::Firmware,
+        network::{
+            complete, gather, scatter, update_route, FragmentData, SimulatedNetwork, EPHEMERAL_PORT,
+        },
+        status::{
+            EFI_ABORTED, EFI_ACCESS_DENIED, EFI_INVALID_PARAMETER, EFI_NOT_FOUND, EFI_NOT_STARTED,
+            EFI_SUCCESS, EFI_TIMEOUT,
+        },
+    },
+    efi::{EfiEvent, EfiHandle, EfiStatus, VoidMutPtr, VoidPtr},
+    std::{cell::RefCell, collections::VecDeque, rc::Rc},
+};
+
+/* EFI_TCP4_CONNECTION_STATE */
+const STATE_CLOSED: u32 = 0;
+const STATE_LISTEN: u32 = 1;
+const STATE_ESTABLISHED: u32 = 4;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct Tcp4AccessPoint {
+    use_default_address: bool,
+    station_address: [u8; 4],
+    subnet_mask: [u8; 4],
+    station_port: u16,
+    remote_address: [u8; 4],
+    remote_port: u16,
+    active_flag: bool,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct Tcp4ConfigData {
+    type_of_service: u8,
+    time_to_live: u8,
+    access_point: Tcp4AccessPoint,
+    control_option: VoidPtr,
+}
+
+#[repr(C)]
+struct Tcp4CompletionToken {
+    event: EfiEvent,
+    status: EfiStatus,
+}
+
+#[repr(C)]
+struct Tcp4ListenToken {
+    completion_token: Tcp4CompletionToken,
+    new_child_handle: EfiHandle,
+}
+
+#[repr(C)]
+struct Tcp4CloseToken {
+    completion_token: Tcp4CompletionToken,
+    abort_on_close: bool,
+}
+
+#[repr(C)]
+struct Tcp4IoToken {
+    completion_token: Tcp4CompletionToken,
+    packet: VoidMutPtr,
+}
+
+#[repr(C)]
+struct Tcp4TransmitData {
+    push: bool,
+    urgent: bool,
+    data_length: u32,
+    fragment_count: u32,
+    fragment_table: [FragmentData; 0],
+}
+
+#[repr(C)]
+struct Tcp4ReceiveData {
+    urgent: bool,
+    data_length: u32,
+    fragment_count: u32,
+    fragment_table: [FragmentData; 0],
+}
+
+struct Tcp4State {
+    config: Option<Tcp4ConfigData>,
+    connection_state: u32,
+    routes: Vec<[[u8; 4]; 3]>,
+    receives: VecDeque<*mut Tcp4IoToken>,
+    accepts: Vec<*mut Tcp4ListenToken>,
+    received: VecDeque<u8>,
+}
+
+impl Tcp4State {
+    fn new() -> Self {
+        Self {
+            config: None,
+            connection_state: STATE_CLOSED,
+            routes: Vec::new(),
+            receives: VecDeque::new(),
+            accepts: Vec::new(),
+            received: VecDeque::new(),
+        }
+    }
+
+    /// Aborts the queued receive and accept tokens.
+    fn abort(&mut self, firmware: &mut Firmware) {
+        let receives = self
+            .receives
+            .drain(..)
+            .map(|token: *mut Tcp4IoToken| unsafe {
+                &mut (*token).completion_token as *mut Tcp4CompletionToken
+            });
+        let accepts = self
+            .accepts
+            .drain(..)
+            .map(|token: *mut Tcp4ListenToken| unsafe {
+                &mut (*token).completion_token as *mut Tcp4CompletionToken
+            });
+
+        for token in receives
+            .chain(accepts)
+            .collect::<Vec<*mut Tcp4CompletionToken>>()
+        {
+            let token: &mut Tcp4CompletionToken = unsafe { &mut *token };
+
+            complete(firmware, token.event, &mut token.status, EFI_ABORTED);
+        }
+    }
+
+    /// Fills the queued receive tokens with the data received so far.
+    fn deliver(&mut self, firmware: &mut Firmware) {
+        while !self.received.is_empty() {
+            let token: &mut Tcp4IoToken = match self.receives.pop_front() {
+                Some(token) => unsafe { &mut *token },
+                None => break,
+            };
+
+            let receive_data: &mut Tcp4ReceiveData =
+                unsafe { &mut *(token.packet as *mut Tcp4ReceiveData) };
+
+            let data: Vec<u8> = self
+                .received
+                .iter()
+                .take(receive_data.data_length as usize)
+                .copied()
+                .collect();
+
+            let copied: usize = scatter(
+                receive_data.fragment_table.as_mut_ptr(),
+                receive_data.fragment_count,
+                &data,
+            );
+
+            self.received.drain(..copied);
+
+            receive_data.urgent = false;
+            receive_data.data_length = copied as u32;
+
+            let completion_token: &mut Tcp4CompletionToken = &mut token.completion_token;
+
+            complete(
+                firmware,
+                completion_token.event,
+                &mut completion_token.status,
+                EFI_SUCCESS,
+            );
+        }
+    }
+}
+
+/// Simulated `EFI_TCP4_PROTOCOL` instance.
+///
+/// Active instances connect to echo hosts, which send the transmitted data back. As hosts never connect, accept tokens only complete when they're cancelled.
+#[repr(C)]
+pub(crate) struct Tcp4 {
+    get_mode_data: extern "efiapi" fn(
+        *const Self,
+        *mut u32,
+        *mut Tcp4ConfigData,
+        VoidMutPtr,
+        VoidMutPtr,
+        VoidMutPtr,
+    ) -> EfiStatus,
+    configure: extern "efiapi" fn(*const Self, *const Tcp4ConfigData) -> EfiStatus,
+    routes: extern "efiapi" fn(
+        *const Self,
+        bool,
+        *const [u8; 4],
+        *const [u8; 4],
+        *const [u8; 4],
+    ) -> EfiStatus,
+    connect: extern "efiapi" fn(*const Self, *mut Tcp4CompletionToken) -> EfiStatus,
+    accept: extern "efiapi" fn(*const Self, *mut Tcp4ListenToken) -> EfiStatus,
+    transmit: extern "efiapi" fn(*const Self, *mut Tcp4IoToken) -> EfiStatus,
+    receive: extern "efiapi" fn(*const Self, *mut Tcp4IoToken) -> EfiStatus,
+    close: extern "efiapi" fn(*const Self, *mut Tcp4CloseToken) -> EfiStatus,
+    cancel: extern "efiapi" fn(*const Self, *mut Tcp4CompletionToken) -> EfiStatus,
+    poll: extern "efiapi" fn(*const Self) -> EfiStatus,
+    network: Rc<SimulatedNetwork>,
+    state: RefCell<Tcp4State>,
+}
+
+impl Tcp4 {
+    pub(crate) fn new(network: Rc<SimulatedNetwork>) -> Box<Self> {
+        Box::new(Self {
+            get_mode_data: tcp4_get_mode_data,
+            configure: tcp4_configure,
+            routes: tcp4_routes,
+            connect: tcp4_connect,
+            accept: tcp4_accept,
+            transmit: tcp4_transmit,
+            receive: tcp4_receive,
+            close: tcp4_close,
+            cancel: tcp4_cancel,
+            poll: tcp4_poll,
+            network,
+            state: RefCell::new(Tcp4State::new()),
+        })
+    }
+
+    /// Aborts the connection and the queued tokens, and forgets the configuration.
+    pub(crate) fn reset(&self, firmware: &mut Firmware) {
+        let mut state = self.state.borrow_mut();
+
+        state.abort(firmware);
+
+        *state = Tcp4State::new();
+    }
+}
+
+/// Returns the completion token of a token which can be queued, which needs an event.
+fn validate_token(
+    token: *mut Tcp4CompletionToken,
+) -> Result<&'static mut Tcp4CompletionToken, EfiStatus> {
+    match unsafe { token.as_mut() } {
+        Some(token) if !token.event.is_null() => Ok(token),
+        _ => Err(EFI_INVALID_PARAMETER),
+    }
+}
+
+/// Returns the token of a transmission or a reception on the established connection.
+fn validate_io_token(
+    state: &Tcp4State,
+    token: *mut Tcp4IoToken,
+) -> Result<&'static mut Tcp4IoToken, EfiStatus> {
+    if state.config.is_none() {
+        return Err(EFI_NOT_STARTED);
+    }
+
+    validate_token(token as *mut Tcp4CompletionToken)?;
+
+    let token: &mut Tcp4IoToken = unsafe { &mut *token };
+
+    if token.packet.is_null() {
+        return Err(EFI_INVALID_PARAMETER);
+    }
+
+    if state.connection_state != STATE_ESTABLISHED {
+        return Err(EFI_ACCESS_DENIED);
+    }
+
+    Ok(token)
+}
+
+extern "efiapi" fn tcp4_get_mode_data(
+    this: *const Tcp4,
+    connection_state: *mut u32,
+    config_data: *mut Tcp4ConfigData,
+    _ip4_mode_data: VoidMutPtr,
+    _mnp_config_data: VoidMutPtr,
+    _snp_mode_data: VoidMutPtr,
+) -> EfiStatus {
+    let state = unsafe { &*this }.state.borrow();
+
+    let config: Tcp4ConfigData = match state.config {
+        Some(config) => config,
+        None => return EFI_NOT_STARTED,
+    };
+
+    if !connection_state.is_null() {
+        unsafe { *connection_state = state.connection_state };
+    }
+
+    if !config_data.is_null() {
+        unsafe { *config_data = config };
+    }
+
+    EFI_SUCCESS
+}
+
+extern "efiapi" fn tcp4_configure(
+    this: *const Tcp4,
+    config_data: *const Tcp4ConfigData,
+) -> EfiStatus {
+    let this: &Tcp4 = unsafe { &*this };
+
+    if config_data.is_null() {
+        return service(|firmware: &mut Firmware| {
+            this.reset(firmware);
+
+            Ok(())
+        });
+    }
+
+    let mut state = this.state.borrow_mut();
+
+    /* The instance has to be reset first */
+    if state.config.is_some() {
+        return EFI_ACCESS_DENIED;
+    }
+
+    let mut config: Tcp4ConfigData = unsafe { *config_data };
+    let access_point: &mut Tcp4AccessPoint = &mut config.access_point;
+
+    if access_point.use_default_address {
+        access_point.station_address = this.network.station_ip();
+        access_point.subnet_mask = this.network.subnet_mask();
+    }
+
+    if access_point.active_flag {
+        if access_point.remote_address == [0; 4] || access_point.remote_port == 0 {
+            return EFI_INVALID_PARAMETER;
+        }
+
+        if access_point.station_port == 0 {
+            access_point.station_port = EPHEMERAL_PORT;
+        }
+    } else if access_point.station_port == 0 {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    /* The default options are used */
+    config.control_option = 0 as _;
+
+    state.connection_state = if config.access_point.active_flag {
+        STATE_CLOSED
+    } else {
+        STATE_LISTEN
+    };
+    state.config = Some(config);
+
+    EFI_SUCCESS
+}
+
+extern "efiapi" fn tcp4_routes(
+    this: *const Tcp4,
+    delete: bool,
+    subnet_address: *const [u8; 4],
+    subnet_mask: *const [u8; 4],
+    gateway_address: *const [u8; 4],
+) -> EfiStatus {
+    let mut state = unsafe { &*this }.state.borrow_mut();
+
+    if state.config.is_none() {
+        return EFI_NOT_STARTED;
+    }
+
+    match update_route(
+        &mut state.routes,
+        delete,
+        subnet_address,
+        subnet_mask,
+        gateway_address,
+    ) {
+        Ok(()) => EFI_SUCCESS,
+        Err(status) => status,
+    }
+}
+
+extern "efiapi" fn tcp4_connect(this: *const Tcp4, token: *mut Tcp4CompletionToken) -> EfiStatus {
+    let this: &Tcp4 = unsafe { &*this };
+
+    service(|firmware: &mut Firmware| {
+        let mut state = this.state.borrow_mut();
+
+        let access_point: Tcp4AccessPoint = state.config.ok_or(EFI_NOT_STARTED)?.access_point;
+
+        let token: &mut Tcp4CompletionToken = validate_token(token)?;
+
+        if !access_point.active_flag || state.connection_state != STATE_CLOSED {
+            return Err(EFI_ACCESS_DENIED);
+        }
+
+        /* Nothing answers at other addresses */
+        let status: EfiStatus = if this.network.is_echo_host(access_point.remote_address) {
+            state.connection_state = STATE_ESTABLISHED;
+
+            EFI_SUCCESS
+        } else {
+            EFI_TIMEOUT
+        };
+
+        complete(firmware, token.event, &mut token.status, status);
+
+        Ok(())
+    })
+}
+
+extern "efiapi" fn tcp4_accept(this: *const Tcp4, token: *mut Tcp4ListenToken) -> EfiStatus {
+    let mut state = unsafe { &*this }.state.borrow_mut();
+
+    let access_point: Tcp4AccessPoint = match state.config {
+        Some(config) => config.access_point,
+        None => return EFI_NOT_STARTED,
+    };
+
+    if let Err(status) = validate_token(token as *mut Tcp4CompletionToken) {
+        return status;
+    }
+
+    if access_point.active_flag || state.accepts.contains(&token) {
+        return EFI_ACCESS_DENIED;
+    }
+
+    state.accepts.push(token);
+
+    EFI_SUCCESS
+}
+
+extern "efiapi" fn tcp4_transmit(this: *const Tcp4, token: *mut Tcp4IoToken) -> EfiStatus {
+    let this: &Tcp4 = unsafe { &*this };
+
+    service(|firmware: &mut Firmware| {
+        let mut state = this.state.borrow_mut();
+
+        let token: &mut Tcp4IoToken = validate_io_token(&state, token)?;
+
+        let transmit_data: &Tcp4TransmitData =
+            unsafe { &*(token.packet as *const Tcp4TransmitData) };
+
+        let data: Vec<u8> = gather(
+            transmit_data.fragment_table.as_ptr(),
+            transmit_data.fragment_count,
+            transmit_data.data_length,
+        )?;
+
+        let completion_token: &mut Tcp4CompletionToken = &mut token.completion_token;
+
+        complete(
+            firmware,
+            completion_token.event,
+            &mut completion_token.status,
+            EFI_SUCCESS,
+        );
+
+        /* Connections are only established with echo hosts */
+        state.received.extend(data);
+        state.deliver(firmware);
+
+        Ok(())
+    })
+}
+
+extern "efiapi" fn tcp4_receive(this: *const Tcp4, token: *mut Tcp4IoToken) -> EfiStatus {
+    let this: &Tcp4 = unsafe { &*this };
+
+    service(|firmware: &mut Firmware| {
+        let mut state = this.state.borrow_mut();
+
+        let token: &mut Tcp4IoToken = validate_io_token(&state, token)?;
+
+        if state.receives.contains(&(token as *mut Tcp4IoToken)) {
+            return Err(EFI_ACCESS_DENIED);
+        }
+
+        state.receives.push_back(token);
+        state.deliver(firmware);
+
+        Ok(())
+    })
+}
+
+extern "efiapi" fn tcp4_close(this: *const Tcp4, token: *mut Tcp4CloseToken) -> EfiStatus {
+    let this: &Tcp4 = unsafe { &*this };
+
+    service(|firmware: &mut Firmware| {
+        let mut state = this.state.borrow_mut();
+
+        if state.config.is_none() {
+            return Err(EFI_NOT_STARTED);
+        }
+
+        let token: &mut Tcp4CompletionToken = validate_token(token as *mut Tcp4CompletionToken)?;
+
+        if state.connection_state == STATE_CLOSED {
+            return Err(EFI_ACCESS_DENIED);
+        }
+
+        /* Closing gracefully and aborting are the same, as the echo host never has data in flight */
+        state.abort(firmware);
+        state.received.clear();
+        state.connection_state = STATE_CLOSED;
+
+        complete(firmware, token.event, &mut token.status, EFI_SUCCESS);
+
+        Ok(())
+    })
+}
+
+extern "efiapi" fn tcp4_cancel(this: *const Tcp4, token: *mut Tcp4CompletionToken) -> EfiStatus {
+    let this: &Tcp4 = unsafe { &*this };
+
+    service(|firmware: &mut Firmware| {
+        let mut state = this.state.borrow_mut();
+
+        if state.config.is_none() {
+            return Err(EFI_NOT_STARTED);
+        }
+
+        if token.is_null() {
+            state.abort(firmware);
+
+            return Ok(());
+        }
+
+        /* The completion token is the first field of every token */
+        if let Some(index) = state
+            .receives
+            .iter()
+            .position(|&queued: &*mut Tcp4IoToken| queued as *mut Tcp4CompletionToken == token)
+        {
+            state.receives.remove(index);
+        } else if let Some(index) = state
+            .accepts
+            .iter()
+            .position(|&queued: &*mut Tcp4ListenToken| queued as *mut Tcp4CompletionToken == token)
+        {
+            state.accepts.remove(index);
+        } else {
+            return Err(EFI_NOT_FOUND);
+        }
+
+        let token: &mut Tcp4CompletionToken = unsafe { &mut *token };
+
+        complete(firmware, token.event, &mut token.status, EFI_ABORTED);
+
+        Ok(())
+    })
+}
+
+extern "efiapi" fn tcp4_poll(this: *const Tcp4) -> EfiStatus {
+    if unsafe { &*this }.state.borrow().config.is_none() {
+        EFI_NOT_STARTED
+    } else {
+        EFI_SUCCESS
+    }
+}
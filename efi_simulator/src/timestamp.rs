@@ -0,0 +1,51 @@
+use {
+    crate::{
+        firmware::{with_firmware, Firmware},
+        status::{EFI_INVALID_PARAMETER, EFI_SUCCESS},
+    },
+    efi::EfiStatus,
+};
+
+/// The counter follows the virtual clock, which advances in 100ns units.
+const FREQUENCY: u64 = 10_000_000;
+
+#[repr(C)]
+struct TimestampProperties {
+    frequency: u64,
+    end_value: u64,
+}
+
+/// Simulated `EFI_TIMESTAMP_PROTOCOL`, counting the virtual time advanced through [`Simulator::advance_time`](crate::Simulator::advance_time).
+#[repr(C)]
+pub(crate) struct Timestamp {
+    get_timestamp: extern "efiapi" fn() -> u64,
+    get_properties: extern "efiapi" fn(*mut TimestampProperties) -> EfiStatus,
+}
+
+impl Timestamp {
+    pub(crate) fn new() -> Box<Self> {
+        Box::new(Self {
+            get_timestamp: timestamp_get_timestamp,
+            get_properties: timestamp_get_properties,
+        })
+    }
+}
+
+extern "efiapi" fn timestamp_get_timestamp() -> u64 {
+    with_firmware(|firmware: &mut Firmware| firmware.events.time())
+}
+
+extern "efiapi" fn timestamp_get_properties(properties: *mut TimestampProperties) -> EfiStatus {
+    if properties.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    unsafe {
+        *properties = TimestampProperties {
+            frequency: FREQUENCY,
+            end_value: u64::MAX,
+        }
+    };
+
+    EFI_SUCCESS
+}
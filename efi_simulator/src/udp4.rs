@@ -0,0 +1,465 @@
+use {
+    crate::{
+        boot_services::service,
+        events::{EVT_NOTIFY_SIGNAL, TPL_CALLBACK},
+        firmware::{with_firmware, Firmware},
+        network::{complete, gather, update_route, FragmentData, SimulatedNetwork, EPHEMERAL_PORT},
+        status::{
+            EFI_ABORTED, EFI_ACCESS_DENIED, EFI_ALREADY_STARTED, EFI_INVALID_PARAMETER,
+            EFI_NOT_FOUND, EFI_NOT_STARTED, EFI_SUCCESS,
+        },
+    },
+    efi::{
+        runtime_services::time::EfiTimeRepresentation, EfiEvent, EfiStatus, VoidMutPtr, VoidPtr,
+    },
+    std::{cell::RefCell, collections::VecDeque, rc::Rc},
+};
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct Udp4ConfigData {
+    accept_broadcast: bool,
+    accept_promiscuous: bool,
+    accept_any_port: bool,
+    allow_duplicate_port: bool,
+    type_of_service: u8,
+    time_to_live: u8,
+    do_not_fragment: bool,
+    receive_timeout: u32,
+    transmit_timeout: u32,
+    use_default_address: bool,
+    station_address: [u8; 4],
+    subnet_mask: [u8; 4],
+    station_port: u16,
+    remote_address: [u8; 4],
+    remote_port: u16,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct Udp4SessionData {
+    source_address: [u8; 4],
+    source_port: u16,
+    destination_address: [u8; 4],
+    destination_port: u16,
+}
+
+#[repr(C)]
+struct Udp4CompletionToken {
+    event: EfiEvent,
+    status: EfiStatus,
+    packet: VoidMutPtr,
+}
+
+#[repr(C)]
+struct Udp4TransmitData {
+    session_data: *const Udp4SessionData,
+    gateway_address: *const [u8; 4],
+    data_length: u32,
+    fragment_count: u32,
+    fragment_table: [FragmentData; 0],
+}
+
+#[repr(C)]
+struct Udp4ReceiveData {
+    time_stamp: EfiTimeRepresentation,
+    recycle_signal: EfiEvent,
+    session_data: Udp4SessionData,
+    data_length: u32,
+    fragment_count: u32,
+    fragment_table: [FragmentData; 1],
+}
+
+/// A datagram handed to the caller, kept until it's recycle signal is signalled.
+struct DeliveredDatagram {
+    receive_data: Udp4ReceiveData,
+    /* Only reached through the receive data's fragment */
+    #[allow(dead_code)]
+    payload: Vec<u8>,
+}
+
+#[derive(Default)]
+struct Udp4State {
+    config: Option<Udp4ConfigData>,
+    groups: Vec<[u8; 4]>,
+    routes: Vec<[[u8; 4]; 3]>,
+    receives: VecDeque<*mut Udp4CompletionToken>,
+    received: VecDeque<(Udp4SessionData, Vec<u8>)>,
+    delivered: Vec<Box<DeliveredDatagram>>,
+}
+
+/// Simulated `EFI_UDP4_PROTOCOL` instance.
+///
+/// Transmissions complete immediately, while receive tokens complete once a datagram arrives from an echo host.
+#[repr(C)]
+pub(crate) struct Udp4 {
+    get_mode_data: extern "efiapi" fn(
+        *const Self,
+        *mut Udp4ConfigData,
+        VoidMutPtr,
+        VoidMutPtr,
+        VoidMutPtr,
+    ) -> EfiStatus,
+    configure: extern "efiapi" fn(*const Self, *const Udp4ConfigData) -> EfiStatus,
+    groups: extern "efiapi" fn(*const Self, bool, *const [u8; 4]) -> EfiStatus,
+    routes: extern "efiapi" fn(
+        *const Self,
+        bool,
+        *const [u8; 4],
+        *const [u8; 4],
+        *const [u8; 4],
+    ) -> EfiStatus,
+    transmit: extern "efiapi" fn(*const Self, *mut Udp4CompletionToken) -> EfiStatus,
+    receive: extern "efiapi" fn(*const Self, *mut Udp4CompletionToken) -> EfiStatus,
+    cancel: extern "efiapi" fn(*const Self, *mut Udp4CompletionToken) -> EfiStatus,
+    poll: extern "efiapi" fn(*const Self) -> EfiStatus,
+    network: Rc<SimulatedNetwork>,
+    state: RefCell<Udp4State>,
+}
+
+impl Udp4 {
+    pub(crate) fn new(network: Rc<SimulatedNetwork>) -> Box<Self> {
+        Box::new(Self {
+            get_mode_data: udp4_get_mode_data,
+            configure: udp4_configure,
+            groups: udp4_groups,
+            routes: udp4_routes,
+            transmit: udp4_transmit,
+            receive: udp4_receive,
+            cancel: udp4_cancel,
+            poll: udp4_poll,
+            network,
+            state: RefCell::new(Udp4State::default()),
+        })
+    }
+
+    /// Aborts the queued receive tokens and forgets the configuration and the received datagrams.
+    pub(crate) fn reset(&self, firmware: &mut Firmware) {
+        let mut state = self.state.borrow_mut();
+
+        for token in state.receives.drain(..) {
+            let token: &mut Udp4CompletionToken = unsafe { &mut *token };
+
+            complete(firmware, token.event, &mut token.status, EFI_ABORTED);
+        }
+
+        for datagram in state.delivered.drain(..) {
+            let _ = firmware.events.close(datagram.receive_data.recycle_signal);
+        }
+
+        *state = Udp4State::default();
+    }
+
+    /// Hands received datagrams to the queued receive tokens.
+    fn deliver(&self, firmware: &mut Firmware, state: &mut Udp4State) -> Result<(), EfiStatus> {
+        while !state.receives.is_empty() && !state.received.is_empty() {
+            let recycle_signal: EfiEvent = firmware.events.create(
+                EVT_NOTIFY_SIGNAL,
+                TPL_CALLBACK,
+                Some(udp4_recycle),
+                self as *const Self as VoidPtr,
+                None,
+            )?;
+
+            let token: &mut Udp4CompletionToken =
+                unsafe { &mut *state.receives.pop_front().unwrap() };
+            let (session_data, mut payload): (Udp4SessionData, Vec<u8>) =
+                state.received.pop_front().unwrap();
+
+            let datagram: Box<DeliveredDatagram> = Box::new(DeliveredDatagram {
+                receive_data: Udp4ReceiveData {
+                    time_stamp: firmware.time,
+                    recycle_signal,
+                    session_data,
+                    data_length: payload.len() as u32,
+                    fragment_count: 1,
+                    fragment_table: [FragmentData {
+                        length: payload.len() as u32,
+                        buffer: payload.as_mut_ptr() as VoidMutPtr,
+                    }],
+                },
+                payload,
+            });
+
+            token.packet = &datagram.receive_data as *const Udp4ReceiveData as VoidMutPtr;
+
+            state.delivered.push(datagram);
+
+            complete(firmware, token.event, &mut token.status, EFI_SUCCESS);
+        }
+
+        Ok(())
+    }
+}
+
+/// Checks that the token can be queued, which needs an event.
+fn validate_token(
+    token: *mut Udp4CompletionToken,
+) -> Result<&'static mut Udp4CompletionToken, EfiStatus> {
+    match unsafe { token.as_mut() } {
+        Some(token) if !token.event.is_null() => Ok(token),
+        _ => Err(EFI_INVALID_PARAMETER),
+    }
+}
+
+extern "efiapi" fn udp4_get_mode_data(
+    this: *const Udp4,
+    config_data: *mut Udp4ConfigData,
+    _ip4_mode_data: VoidMutPtr,
+    _mnp_config_data: VoidMutPtr,
+    _snp_mode_data: VoidMutPtr,
+) -> EfiStatus {
+    let state = unsafe { &*this }.state.borrow();
+
+    let config: Udp4ConfigData = match state.config {
+        Some(config) => config,
+        None => return EFI_NOT_STARTED,
+    };
+
+    if !config_data.is_null() {
+        unsafe { *config_data = config };
+    }
+
+    EFI_SUCCESS
+}
+
+extern "efiapi" fn udp4_configure(
+    this: *const Udp4,
+    config_data: *const Udp4ConfigData,
+) -> EfiStatus {
+    let this: &Udp4 = unsafe { &*this };
+
+    if config_data.is_null() {
+        return service(|firmware: &mut Firmware| {
+            this.reset(firmware);
+
+            Ok(())
+        });
+    }
+
+    let mut state = this.state.borrow_mut();
+
+    if state.config.is_some() {
+        return EFI_ALREADY_STARTED;
+    }
+
+    let mut config: Udp4ConfigData = unsafe { *config_data };
+
+    if config.use_default_address {
+        config.station_address = this.network.station_ip();
+        config.subnet_mask = this.network.subnet_mask();
+    }
+
+    if config.station_port == 0 {
+        config.station_port = EPHEMERAL_PORT;
+    }
+
+    state.config = Some(config);
+
+    EFI_SUCCESS
+}
+
+extern "efiapi" fn udp4_groups(
+    this: *const Udp4,
+    join: bool,
+    multicast_address: *const [u8; 4],
+) -> EfiStatus {
+    let mut state = unsafe { &*this }.state.borrow_mut();
+
+    if state.config.is_none() {
+        return EFI_NOT_STARTED;
+    }
+
+    if multicast_address.is_null() {
+        if join {
+            return EFI_INVALID_PARAMETER;
+        }
+
+        state.groups.clear();
+
+        return EFI_SUCCESS;
+    }
+
+    let address: [u8; 4] = unsafe { *multicast_address };
+
+    /* Class D addresses */
+    if !(224..=239).contains(&address[0]) {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    let joined: Option<usize> = state
+        .groups
+        .iter()
+        .position(|&group: &[u8; 4]| group == address);
+
+    match (join, joined) {
+        (true, None) => state.groups.push(address),
+        (true, Some(_)) => return EFI_ALREADY_STARTED,
+        (false, Some(index)) => {
+            state.groups.remove(index);
+        }
+        (false, None) => return EFI_NOT_FOUND,
+    }
+
+    EFI_SUCCESS
+}
+
+extern "efiapi" fn udp4_routes(
+    this: *const Udp4,
+    delete: bool,
+    subnet_address: *const [u8; 4],
+    subnet_mask: *const [u8; 4],
+    gateway_address: *const [u8; 4],
+) -> EfiStatus {
+    let mut state = unsafe { &*this }.state.borrow_mut();
+
+    if state.config.is_none() {
+        return EFI_NOT_STARTED;
+    }
+
+    match update_route(
+        &mut state.routes,
+        delete,
+        subnet_address,
+        subnet_mask,
+        gateway_address,
+    ) {
+        Ok(()) => EFI_SUCCESS,
+        Err(status) => status,
+    }
+}
+
+extern "efiapi" fn udp4_transmit(this: *const Udp4, token: *mut Udp4CompletionToken) -> EfiStatus {
+    let this: &Udp4 = unsafe { &*this };
+
+    service(|firmware: &mut Firmware| {
+        let mut state = this.state.borrow_mut();
+
+        let config: Udp4ConfigData = state.config.ok_or(EFI_NOT_STARTED)?;
+
+        let token: &mut Udp4CompletionToken = validate_token(token)?;
+
+        let transmit_data: &Udp4TransmitData =
+            unsafe { (token.packet as *const Udp4TransmitData).as_ref() }
+                .ok_or(EFI_INVALID_PARAMETER)?;
+
+        let (remote_address, remote_port): ([u8; 4], u16) =
+            match unsafe { transmit_data.session_data.as_ref() } {
+                Some(session_data) => (
+                    session_data.destination_address,
+                    session_data.destination_port,
+                ),
+                None => (config.remote_address, config.remote_port),
+            };
+
+        if remote_address == [0; 4] || remote_port == 0 {
+            return Err(EFI_INVALID_PARAMETER);
+        }
+
+        let data: Vec<u8> = gather(
+            transmit_data.fragment_table.as_ptr(),
+            transmit_data.fragment_count,
+            transmit_data.data_length,
+        )?;
+
+        complete(firmware, token.event, &mut token.status, EFI_SUCCESS);
+
+        /* Datagrams to other addresses are lost */
+        if this.network.is_echo_host(remote_address) {
+            state.received.push_back((
+                Udp4SessionData {
+                    source_address: remote_address,
+                    source_port: remote_port,
+                    destination_address: config.station_address,
+                    destination_port: config.station_port,
+                },
+                data,
+            ));
+
+            this.deliver(firmware, &mut state)?;
+        }
+
+        Ok(())
+    })
+}
+
+extern "efiapi" fn udp4_receive(this: *const Udp4, token: *mut Udp4CompletionToken) -> EfiStatus {
+    let this: &Udp4 = unsafe { &*this };
+
+    service(|firmware: &mut Firmware| {
+        let mut state = this.state.borrow_mut();
+
+        if state.config.is_none() {
+            return Err(EFI_NOT_STARTED);
+        }
+
+        let token: &mut Udp4CompletionToken = validate_token(token)?;
+
+        if state
+            .receives
+            .contains(&(token as *mut Udp4CompletionToken))
+        {
+            return Err(EFI_ACCESS_DENIED);
+        }
+
+        token.packet = 0 as _;
+
+        state.receives.push_back(token);
+
+        this.deliver(firmware, &mut state)
+    })
+}
+
+extern "efiapi" fn udp4_cancel(this: *const Udp4, token: *mut Udp4CompletionToken) -> EfiStatus {
+    let this: &Udp4 = unsafe { &*this };
+
+    service(|firmware: &mut Firmware| {
+        let mut state = this.state.borrow_mut();
+
+        if state.config.is_none() {
+            return Err(EFI_NOT_STARTED);
+        }
+
+        let cancelled: Vec<*mut Udp4CompletionToken> = if token.is_null() {
+            state.receives.drain(..).collect()
+        } else {
+            let index: usize = state
+                .receives
+                .iter()
+                .position(|&queued: &*mut Udp4CompletionToken| queued == token)
+                .ok_or(EFI_NOT_FOUND)?;
+
+            state.receives.remove(index).into_iter().collect()
+        };
+
+        for token in cancelled {
+            let token: &mut Udp4CompletionToken = unsafe { &mut *token };
+
+            complete(firmware, token.event, &mut token.status, EFI_ABORTED);
+        }
+
+        Ok(())
+    })
+}
+
+extern "efiapi" fn udp4_poll(this: *const Udp4) -> EfiStatus {
+    if unsafe { &*this }.state.borrow().config.is_none() {
+        EFI_NOT_STARTED
+    } else {
+        EFI_SUCCESS
+    }
+}
+
+/// Releases a delivered datagram once the caller signals it's recycle event.
+extern "efiapi" fn udp4_recycle(event: EfiEvent, context: VoidPtr) {
+    let this: &Udp4 = unsafe { &*(context as *const Udp4) };
+
+    this.state
+        .borrow_mut()
+        .delivered
+        .retain(|datagram: &Box<DeliveredDatagram>| datagram.receive_data.recycle_signal != event);
+
+    with_firmware(|firmware: &mut Firmware| {
+        let _ = firmware.events.close(event);
+    });
+}
@@ -0,0 +1,930 @@
+use {
+    crate::{
+        boot_services::EFI_BOOT_SERVICES_DATA,
+        firmware::{with_firmware, Firmware},
+        status::{
+            EFI_DEVICE_ERROR, EFI_INVALID_PARAMETER, EFI_NOT_FOUND, EFI_SUCCESS, EFI_UNSUPPORTED,
+        },
+    },
+    efi::{EfiStatus, VoidMutPtr, VoidPtr},
+    std::{
+        cell::{Cell, RefCell},
+        ptr::copy_nonoverlapping,
+        slice::{from_raw_parts, from_raw_parts_mut},
+    },
+};
+
+const LANGUAGE_ENGLISH_US: u16 = 0x0409;
+
+const DESCRIPTOR_DEVICE: u8 = 0x01;
+const DESCRIPTOR_CONFIGURATION: u8 = 0x02;
+const DESCRIPTOR_STRING: u8 = 0x03;
+const DESCRIPTOR_INTERFACE: u8 = 0x04;
+const DESCRIPTOR_ENDPOINT: u8 = 0x05;
+
+const DEVICE_DESCRIPTOR_LENGTH: usize = 18;
+const CONFIG_DESCRIPTOR_LENGTH: usize = 9;
+const INTERFACE_DESCRIPTOR_LENGTH: usize = 9;
+const ENDPOINT_DESCRIPTOR_LENGTH: usize = 7;
+
+const REQUEST_DEVICE_TO_HOST: u8 = 0x80;
+const REQUEST_GET_DESCRIPTOR: u8 = 0x06;
+const REQUEST_GET_CONFIGURATION: u8 = 0x08;
+const REQUEST_SET_CONFIGURATION: u8 = 0x09;
+
+/* Bulk endpoints, which echo the data written to them */
+const ENDPOINT_BULK_IN: u8 = 0x81;
+const ENDPOINT_BULK_OUT: u8 = 0x02;
+const ENDPOINT_ATTRIBUTES_BULK: u8 = 0x02;
+const MAX_PACKET_SIZE: u16 = 512;
+
+const CONFIGURATION_VALUE: u8 = 1;
+
+/* Data directions of EFI_USB_DATA_DIRECTION */
+const DATA_IN: u32 = 0;
+const NO_DATA: u32 = 2;
+
+const TRANSFER_STALL: u32 = 0x0002;
+
+/* Port status bits */
+const PORT_CONNECTION: u16 = 0x0001;
+const PORT_ENABLE: u16 = 0x0002;
+const PORT_POWER: u16 = 0x0100;
+const PORT_HIGH_SPEED: u16 = 0x0400;
+const PORT_CONNECTION_CHANGE: u16 = 0x0001;
+const PORT_ENABLE_CHANGE: u16 = 0x0002;
+const PORT_RESET_CHANGE: u16 = 0x0010;
+
+/* Port features */
+const FEATURE_RESET: u32 = 4;
+const FEATURE_CONNECT_CHANGE: u32 = 16;
+const FEATURE_ENABLE_CHANGE: u32 = 17;
+const FEATURE_RESET_CHANGE: u32 = 20;
+
+const SPEED_HIGH: u8 = 2;
+const HC_STATE_OPERATIONAL: u32 = 1;
+
+/// High speed USB device exposed through `EFI_USB_IO_PROTOCOL`, with a single interface.
+///
+/// The interface has a bulk IN and a bulk OUT endpoint; Data written to the OUT endpoint is read back from the IN one.
+/// Strings are provided in US English only.
+#[derive(Clone)]
+pub struct SimulatedUsbDevice {
+    vendor_id: u16,
+    product_id: u16,
+    interface_class: u8,
+    manufacturer: Option<String>,
+    product: Option<String>,
+    serial_number: Option<String>,
+}
+
+impl SimulatedUsbDevice {
+    /// Creates a device without strings.
+    pub fn new(vendor_id: u16, product_id: u16, interface_class: u8) -> Self {
+        Self {
+            vendor_id,
+            product_id,
+            interface_class,
+            manufacturer: None,
+            product: None,
+            serial_number: None,
+        }
+    }
+
+    pub fn manufacturer(mut self, manufacturer: &str) -> Self {
+        self.manufacturer = Some(String::from(manufacturer));
+
+        self
+    }
+
+    pub fn product(mut self, product: &str) -> Self {
+        self.product = Some(String::from(product));
+
+        self
+    }
+
+    pub fn serial_number(mut self, serial_number: &str) -> Self {
+        self.serial_number = Some(String::from(serial_number));
+
+        self
+    }
+
+    /// Returns the strings in the order of their indices, starting from 1.
+    fn strings(&self) -> Vec<&str> {
+        [&self.manufacturer, &self.product, &self.serial_number]
+            .iter()
+            .filter_map(|string: &&Option<String>| string.as_deref())
+            .collect()
+    }
+
+    /// Returns the indices of the manufacturer, product and serial number strings, 0 for the ones the device doesn't report.
+    fn string_indices(&self) -> [u8; 3] {
+        let mut next: u8 = 0;
+
+        [&self.manufacturer, &self.product, &self.serial_number].map(|string: &Option<String>| {
+            match string {
+                Some(_) => {
+                    next += 1;
+
+                    next
+                }
+                None => 0,
+            }
+        })
+    }
+
+    fn device_descriptor(&self) -> Vec<u8> {
+        let mut data: Vec<u8> = vec![DEVICE_DESCRIPTOR_LENGTH as u8, DESCRIPTOR_DEVICE];
+
+        /* USB 2.0, with the class defined by the interface */
+        data.extend_from_slice(&0x0200_u16.to_le_bytes());
+        data.extend_from_slice(&[0, 0, 0, 64]);
+        data.extend_from_slice(&self.vendor_id.to_le_bytes());
+        data.extend_from_slice(&self.product_id.to_le_bytes());
+        data.extend_from_slice(&0x0100_u16.to_le_bytes());
+        data.extend_from_slice(&self.string_indices());
+        /* A single configuration */
+        data.push(1);
+
+        data
+    }
+
+    /// Returns the configuration descriptor followed by the interface and endpoint descriptors.
+    fn config_descriptors(&self) -> Vec<u8> {
+        let total_length: u16 = (CONFIG_DESCRIPTOR_LENGTH
+            + INTERFACE_DESCRIPTOR_LENGTH
+            + 2 * ENDPOINT_DESCRIPTOR_LENGTH) as u16;
+        let mut data: Vec<u8> = vec![CONFIG_DESCRIPTOR_LENGTH as u8, DESCRIPTOR_CONFIGURATION];
+
+        data.extend_from_slice(&total_length.to_le_bytes());
+        /* Bus powered, drawing up to 100 mA */
+        data.extend_from_slice(&[1, CONFIGURATION_VALUE, 0, 0x80, 50]);
+        data.extend_from_slice(&[
+            INTERFACE_DESCRIPTOR_LENGTH as u8,
+            DESCRIPTOR_INTERFACE,
+            0,
+            0,
+            2,
+            self.interface_class,
+            0,
+            0,
+            0,
+        ]);
+
+        for endpoint_address in [ENDPOINT_BULK_IN, ENDPOINT_BULK_OUT] {
+            data.extend_from_slice(&[
+                ENDPOINT_DESCRIPTOR_LENGTH as u8,
+                DESCRIPTOR_ENDPOINT,
+                endpoint_address,
+                ENDPOINT_ATTRIBUTES_BULK,
+            ]);
+            data.extend_from_slice(&MAX_PACKET_SIZE.to_le_bytes());
+            data.push(0);
+        }
+
+        data
+    }
+
+    fn string_descriptor(&self, index: u8, language_id: u16) -> Option<Vec<u8>> {
+        let string: Vec<u16> = if index == 0 {
+            vec![LANGUAGE_ENGLISH_US]
+        } else if language_id == LANGUAGE_ENGLISH_US {
+            self.strings()
+                .get(usize::from(index) - 1)?
+                .encode_utf16()
+                .collect()
+        } else {
+            return None;
+        };
+
+        let mut data: Vec<u8> = vec![(2 + string.len() * 2) as u8, DESCRIPTOR_STRING];
+
+        for character in string {
+            data.extend_from_slice(&character.to_le_bytes());
+        }
+
+        Some(data)
+    }
+}
+
+#[repr(C)]
+struct UsbDeviceRequest {
+    request_type: u8,
+    request: u8,
+    value: u16,
+    index: u16,
+    length: u16,
+}
+
+/// Simulated `EFI_USB_IO_PROTOCOL` followed by the device it talks to.
+#[repr(C)]
+pub(crate) struct UsbIo {
+    control_transfer: extern "efiapi" fn(
+        *const Self,
+        *const UsbDeviceRequest,
+        u32,
+        u32,
+        VoidMutPtr,
+        usize,
+        *mut u32,
+    ) -> EfiStatus,
+    bulk_transfer:
+        extern "efiapi" fn(*const Self, u8, VoidMutPtr, *mut usize, usize, *mut u32) -> EfiStatus,
+    async_interrupt_transfer: extern "efiapi" fn(
+        *const Self,
+        u8,
+        bool,
+        usize,
+        usize,
+        VoidMutPtr,
+        VoidMutPtr,
+    ) -> EfiStatus,
+    sync_interrupt_transfer:
+        extern "efiapi" fn(*const Self, u8, VoidMutPtr, *mut usize, usize, *mut u32) -> EfiStatus,
+    isochronous_transfer:
+        extern "efiapi" fn(*const Self, u8, VoidMutPtr, usize, *mut u32) -> EfiStatus,
+    async_isochronous_transfer:
+        extern "efiapi" fn(*const Self, u8, VoidMutPtr, usize, VoidMutPtr, VoidMutPtr) -> EfiStatus,
+    get_device_descriptor: extern "efiapi" fn(*const Self, *mut u8) -> EfiStatus,
+    get_config_descriptor: extern "efiapi" fn(*const Self, *mut u8) -> EfiStatus,
+    get_interface_descriptor: extern "efiapi" fn(*const Self, *mut u8) -> EfiStatus,
+    get_endpoint_descriptor: extern "efiapi" fn(*const Self, u8, *mut u8) -> EfiStatus,
+    get_string_descriptor: extern "efiapi" fn(*const Self, u16, u8, *mut *mut u16) -> EfiStatus,
+    get_supported_languages:
+        extern "efiapi" fn(*const Self, *mut *const u16, *mut u16) -> EfiStatus,
+    port_reset: extern "efiapi" fn(*const Self) -> EfiStatus,
+    languages: [u16; 1],
+    device: SimulatedUsbDevice,
+    configuration: Cell<u8>,
+    echo: RefCell<Vec<u8>>,
+}
+
+impl UsbIo {
+    pub(crate) fn new(device: &SimulatedUsbDevice) -> Box<Self> {
+        Box::new(Self {
+            control_transfer: usb_io_control_transfer,
+            bulk_transfer: usb_io_bulk_transfer,
+            async_interrupt_transfer: usb_io_async_interrupt_transfer,
+            sync_interrupt_transfer: usb_io_sync_interrupt_transfer,
+            isochronous_transfer: usb_io_isochronous_transfer,
+            async_isochronous_transfer: usb_io_async_isochronous_transfer,
+            get_device_descriptor: usb_io_get_device_descriptor,
+            get_config_descriptor: usb_io_get_config_descriptor,
+            get_interface_descriptor: usb_io_get_interface_descriptor,
+            get_endpoint_descriptor: usb_io_get_endpoint_descriptor,
+            get_string_descriptor: usb_io_get_string_descriptor,
+            get_supported_languages: usb_io_get_supported_languages,
+            port_reset: usb_io_port_reset,
+            languages: [LANGUAGE_ENGLISH_US],
+            device: device.clone(),
+            configuration: Cell::new(CONFIGURATION_VALUE),
+            echo: RefCell::new(Vec::new()),
+        })
+    }
+
+    /// Answers a standard request, returning the data it transfers, or `None` if the device stalls.
+    fn standard_request(&self, request: &UsbDeviceRequest) -> Option<Vec<u8>> {
+        match (request.request_type, request.request) {
+            (REQUEST_DEVICE_TO_HOST, REQUEST_GET_DESCRIPTOR) => {
+                let [index, descriptor_type]: [u8; 2] = request.value.to_le_bytes();
+
+                match descriptor_type {
+                    DESCRIPTOR_DEVICE => Some(self.device.device_descriptor()),
+                    DESCRIPTOR_CONFIGURATION if index == 0 => {
+                        Some(self.device.config_descriptors())
+                    }
+                    DESCRIPTOR_STRING => self.device.string_descriptor(index, request.index),
+                    _ => None,
+                }
+            }
+            (REQUEST_DEVICE_TO_HOST, REQUEST_GET_CONFIGURATION) => {
+                Some(vec![self.configuration.get()])
+            }
+            (0, REQUEST_SET_CONFIGURATION) if request.value <= u16::from(CONFIGURATION_VALUE) => {
+                self.configuration.set(request.value as u8);
+
+                Some(Vec::new())
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Copies the descriptor into the caller's structure, which has the same layout.
+fn write_descriptor(descriptor: &[u8], destination: *mut u8) -> EfiStatus {
+    if destination.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    unsafe { copy_nonoverlapping(descriptor.as_ptr(), destination, descriptor.len()) };
+
+    EFI_SUCCESS
+}
+
+extern "efiapi" fn usb_io_control_transfer(
+    this: *const UsbIo,
+    request: *const UsbDeviceRequest,
+    direction: u32,
+    _: u32,
+    data: VoidMutPtr,
+    length: usize,
+    status: *mut u32,
+) -> EfiStatus {
+    let this: &UsbIo = unsafe { &*this };
+
+    if request.is_null() || status.is_null() || direction > NO_DATA {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    let request: &UsbDeviceRequest = unsafe { &*request };
+    let is_in: bool = request.request_type & REQUEST_DEVICE_TO_HOST != 0;
+
+    if (direction == DATA_IN) != is_in || (direction != NO_DATA && data.is_null()) {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    match this.standard_request(request) {
+        Some(response) => {
+            if direction == DATA_IN {
+                /* The device returns no more than requested */
+                let length: usize = response.len().min(length).min(usize::from(request.length));
+
+                unsafe { from_raw_parts_mut(data as *mut u8, length) }
+                    .copy_from_slice(&response[..length]);
+            }
+
+            unsafe { *status = 0 };
+
+            EFI_SUCCESS
+        }
+        None => {
+            unsafe { *status = TRANSFER_STALL };
+
+            EFI_DEVICE_ERROR
+        }
+    }
+}
+
+extern "efiapi" fn usb_io_bulk_transfer(
+    this: *const UsbIo,
+    endpoint_address: u8,
+    data: VoidMutPtr,
+    length: *mut usize,
+    _: usize,
+    status: *mut u32,
+) -> EfiStatus {
+    let this: &UsbIo = unsafe { &*this };
+
+    if data.is_null() || length.is_null() || status.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    let length: &mut usize = unsafe { &mut *length };
+
+    match endpoint_address {
+        ENDPOINT_BULK_OUT => {
+            *this.echo.borrow_mut() =
+                unsafe { from_raw_parts(data as *const u8, *length) }.to_vec();
+        }
+        ENDPOINT_BULK_IN => {
+            let mut echo = this.echo.borrow_mut();
+            let transferred: usize = echo.len().min(*length);
+
+            unsafe { from_raw_parts_mut(data as *mut u8, transferred) }
+                .copy_from_slice(&echo[..transferred]);
+            echo.drain(..transferred);
+
+            *length = transferred;
+        }
+        _ => return EFI_INVALID_PARAMETER,
+    }
+
+    unsafe { *status = 0 };
+
+    EFI_SUCCESS
+}
+
+/* The interface has no interrupt or isochronous endpoints */
+extern "efiapi" fn usb_io_async_interrupt_transfer(
+    _: *const UsbIo,
+    _: u8,
+    _: bool,
+    _: usize,
+    _: usize,
+    _: VoidMutPtr,
+    _: VoidMutPtr,
+) -> EfiStatus {
+    EFI_INVALID_PARAMETER
+}
+
+extern "efiapi" fn usb_io_sync_interrupt_transfer(
+    _: *const UsbIo,
+    _: u8,
+    _: VoidMutPtr,
+    _: *mut usize,
+    _: usize,
+    _: *mut u32,
+) -> EfiStatus {
+    EFI_INVALID_PARAMETER
+}
+
+extern "efiapi" fn usb_io_isochronous_transfer(
+    _: *const UsbIo,
+    _: u8,
+    _: VoidMutPtr,
+    _: usize,
+    _: *mut u32,
+) -> EfiStatus {
+    EFI_UNSUPPORTED
+}
+
+extern "efiapi" fn usb_io_async_isochronous_transfer(
+    _: *const UsbIo,
+    _: u8,
+    _: VoidMutPtr,
+    _: usize,
+    _: VoidMutPtr,
+    _: VoidMutPtr,
+) -> EfiStatus {
+    EFI_UNSUPPORTED
+}
+
+extern "efiapi" fn usb_io_get_device_descriptor(
+    this: *const UsbIo,
+    descriptor: *mut u8,
+) -> EfiStatus {
+    write_descriptor(&unsafe { &*this }.device.device_descriptor(), descriptor)
+}
+
+extern "efiapi" fn usb_io_get_config_descriptor(
+    this: *const UsbIo,
+    descriptor: *mut u8,
+) -> EfiStatus {
+    let this: &UsbIo = unsafe { &*this };
+
+    if this.configuration.get() == 0 {
+        return EFI_NOT_FOUND;
+    }
+
+    write_descriptor(
+        &this.device.config_descriptors()[..CONFIG_DESCRIPTOR_LENGTH],
+        descriptor,
+    )
+}
+
+extern "efiapi" fn usb_io_get_interface_descriptor(
+    this: *const UsbIo,
+    descriptor: *mut u8,
+) -> EfiStatus {
+    let this: &UsbIo = unsafe { &*this };
+
+    if this.configuration.get() == 0 {
+        return EFI_NOT_FOUND;
+    }
+
+    write_descriptor(
+        &this.device.config_descriptors()
+            [CONFIG_DESCRIPTOR_LENGTH..CONFIG_DESCRIPTOR_LENGTH + INTERFACE_DESCRIPTOR_LENGTH],
+        descriptor,
+    )
+}
+
+extern "efiapi" fn usb_io_get_endpoint_descriptor(
+    this: *const UsbIo,
+    endpoint_index: u8,
+    descriptor: *mut u8,
+) -> EfiStatus {
+    let this: &UsbIo = unsafe { &*this };
+
+    if this.configuration.get() == 0 || endpoint_index >= 2 {
+        return EFI_NOT_FOUND;
+    }
+
+    let offset: usize = CONFIG_DESCRIPTOR_LENGTH
+        + INTERFACE_DESCRIPTOR_LENGTH
+        + usize::from(endpoint_index) * ENDPOINT_DESCRIPTOR_LENGTH;
+
+    write_descriptor(
+        &this.device.config_descriptors()[offset..offset + ENDPOINT_DESCRIPTOR_LENGTH],
+        descriptor,
+    )
+}
+
+extern "efiapi" fn usb_io_get_string_descriptor(
+    this: *const UsbIo,
+    language_id: u16,
+    string_index: u8,
+    string: *mut *mut u16,
+) -> EfiStatus {
+    let device: &SimulatedUsbDevice = &unsafe { &*this }.device;
+
+    if string.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    if string_index == 0 {
+        return EFI_NOT_FOUND;
+    }
+
+    let descriptor: Vec<u8> = match device.string_descriptor(string_index, language_id) {
+        Some(descriptor) => descriptor,
+        None => return EFI_NOT_FOUND,
+    };
+
+    /* The descriptor's characters, null terminated */
+    let mut data: Vec<u8> = descriptor[2..].to_vec();
+
+    data.extend_from_slice(&[0, 0]);
+
+    /* The caller is responsible for releasing the string */
+    match with_firmware(|firmware: &mut Firmware| {
+        firmware
+            .memory
+            .allocate_pool_from(EFI_BOOT_SERVICES_DATA, &data)
+    }) {
+        Ok(buffer) => {
+            unsafe { *string = buffer as *mut u16 };
+
+            EFI_SUCCESS
+        }
+        Err(status) => status,
+    }
+}
+
+extern "efiapi" fn usb_io_get_supported_languages(
+    this: *const UsbIo,
+    table: *mut *const u16,
+    table_size: *mut u16,
+) -> EfiStatus {
+    let this: &UsbIo = unsafe { &*this };
+
+    if table.is_null() || table_size.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    unsafe {
+        *table = this.languages.as_ptr();
+        *table_size = (this.languages.len() * 2) as u16;
+    }
+
+    EFI_SUCCESS
+}
+
+extern "efiapi" fn usb_io_port_reset(this: *const UsbIo) -> EfiStatus {
+    let this: &UsbIo = unsafe { &*this };
+
+    /* The bus driver restores the configuration after the reset */
+    this.configuration.set(CONFIGURATION_VALUE);
+    this.echo.borrow_mut().clear();
+
+    EFI_SUCCESS
+}
+
+#[repr(C)]
+struct UsbPortStatus {
+    port_status: u16,
+    port_change_status: u16,
+}
+
+/// Simulated `EFI_USB2_HC_PROTOCOL`, with a root hub port for each attached device.
+///
+/// Transfers aren't supported, as they're performed through the devices' `EFI_USB_IO_PROTOCOL` instead.
+#[repr(C)]
+pub(crate) struct Usb2Hc {
+    get_capability: extern "efiapi" fn(*const Self, *mut u8, *mut u8, *mut u8) -> EfiStatus,
+    reset: extern "efiapi" fn(*const Self, u16) -> EfiStatus,
+    get_state: extern "efiapi" fn(*const Self, *mut u32) -> EfiStatus,
+    set_state: extern "efiapi" fn(*const Self, u32) -> EfiStatus,
+    control_transfer: extern "efiapi" fn(
+        *const Self,
+        u8,
+        u8,
+        usize,
+        VoidPtr,
+        u32,
+        VoidMutPtr,
+        *mut usize,
+        usize,
+        VoidPtr,
+        *mut u32,
+    ) -> EfiStatus,
+    bulk_transfer: extern "efiapi" fn(
+        *const Self,
+        u8,
+        u8,
+        u8,
+        usize,
+        u8,
+        *mut VoidMutPtr,
+        *mut usize,
+        *mut u8,
+        usize,
+        VoidPtr,
+        *mut u32,
+    ) -> EfiStatus,
+    async_interrupt_transfer: extern "efiapi" fn(
+        *const Self,
+        u8,
+        u8,
+        u8,
+        usize,
+        bool,
+        *mut u8,
+        usize,
+        usize,
+        VoidPtr,
+        VoidMutPtr,
+        VoidMutPtr,
+    ) -> EfiStatus,
+    sync_interrupt_transfer: extern "efiapi" fn(
+        *const Self,
+        u8,
+        u8,
+        u8,
+        usize,
+        VoidMutPtr,
+        *mut usize,
+        *mut u8,
+        usize,
+        VoidPtr,
+        *mut u32,
+    ) -> EfiStatus,
+    isochronous_transfer: extern "efiapi" fn(
+        *const Self,
+        u8,
+        u8,
+        u8,
+        usize,
+        u8,
+        *mut VoidMutPtr,
+        usize,
+        VoidPtr,
+        *mut u32,
+    ) -> EfiStatus,
+    async_isochronous_transfer: extern "efiapi" fn(
+        *const Self,
+        u8,
+        u8,
+        u8,
+        usize,
+        u8,
+        *mut VoidMutPtr,
+        usize,
+        VoidPtr,
+        VoidMutPtr,
+        VoidMutPtr,
+    ) -> EfiStatus,
+    get_root_hub_port_status: extern "efiapi" fn(*const Self, u8, *mut UsbPortStatus) -> EfiStatus,
+    set_root_hub_port_feature: extern "efiapi" fn(*const Self, u8, u32) -> EfiStatus,
+    clear_root_hub_port_feature: extern "efiapi" fn(*const Self, u8, u32) -> EfiStatus,
+    major_revision: u16,
+    minor_revision: u16,
+    state: Cell<u32>,
+    /* Pending change bits of each port */
+    port_changes: RefCell<Vec<u16>>,
+}
+
+impl Usb2Hc {
+    pub(crate) fn new(port_count: usize) -> Box<Self> {
+        Box::new(Self {
+            get_capability: usb2_hc_get_capability,
+            reset: usb2_hc_reset,
+            get_state: usb2_hc_get_state,
+            set_state: usb2_hc_set_state,
+            control_transfer: usb2_hc_control_transfer,
+            bulk_transfer: usb2_hc_bulk_transfer,
+            async_interrupt_transfer: usb2_hc_async_interrupt_transfer,
+            sync_interrupt_transfer: usb2_hc_sync_interrupt_transfer,
+            isochronous_transfer: usb2_hc_isochronous_transfer,
+            async_isochronous_transfer: usb2_hc_async_isochronous_transfer,
+            get_root_hub_port_status: usb2_hc_get_root_hub_port_status,
+            set_root_hub_port_feature: usb2_hc_set_root_hub_port_feature,
+            clear_root_hub_port_feature: usb2_hc_clear_root_hub_port_feature,
+            major_revision: 2,
+            minor_revision: 0,
+            state: Cell::new(HC_STATE_OPERATIONAL),
+            /* Devices are attached before the image runs */
+            port_changes: RefCell::new(vec![PORT_CONNECTION_CHANGE; port_count]),
+        })
+    }
+
+    fn port_exists(&self, port_number: u8) -> bool {
+        usize::from(port_number) < self.port_changes.borrow().len()
+    }
+}
+
+extern "efiapi" fn usb2_hc_get_capability(
+    this: *const Usb2Hc,
+    max_speed: *mut u8,
+    port_count: *mut u8,
+    is_64_bit_capable: *mut u8,
+) -> EfiStatus {
+    let this: &Usb2Hc = unsafe { &*this };
+
+    if max_speed.is_null() || port_count.is_null() || is_64_bit_capable.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    unsafe {
+        *max_speed = SPEED_HIGH;
+        *port_count = this.port_changes.borrow().len() as u8;
+        *is_64_bit_capable = 1;
+    }
+
+    EFI_SUCCESS
+}
+
+extern "efiapi" fn usb2_hc_reset(_: *const Usb2Hc, attributes: u16) -> EfiStatus {
+    /* Global and host controller resets, without debug ports */
+    match attributes {
+        0x0001 | 0x0002 => EFI_SUCCESS,
+        0x0004 | 0x0008 => EFI_UNSUPPORTED,
+        _ => EFI_INVALID_PARAMETER,
+    }
+}
+
+extern "efiapi" fn usb2_hc_get_state(this: *const Usb2Hc, state: *mut u32) -> EfiStatus {
+    let this: &Usb2Hc = unsafe { &*this };
+
+    if state.is_null() {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    unsafe { *state = this.state.get() };
+
+    EFI_SUCCESS
+}
+
+extern "efiapi" fn usb2_hc_set_state(this: *const Usb2Hc, state: u32) -> EfiStatus {
+    let this: &Usb2Hc = unsafe { &*this };
+
+    /* Halt, operational and suspend */
+    if state > 2 {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    this.state.set(state);
+
+    EFI_SUCCESS
+}
+
+extern "efiapi" fn usb2_hc_control_transfer(
+    _: *const Usb2Hc,
+    _: u8,
+    _: u8,
+    _: usize,
+    _: VoidPtr,
+    _: u32,
+    _: VoidMutPtr,
+    _: *mut usize,
+    _: usize,
+    _: VoidPtr,
+    _: *mut u32,
+) -> EfiStatus {
+    EFI_UNSUPPORTED
+}
+
+extern "efiapi" fn usb2_hc_bulk_transfer(
+    _: *const Usb2Hc,
+    _: u8,
+    _: u8,
+    _: u8,
+    _: usize,
+    _: u8,
+    _: *mut VoidMutPtr,
+    _: *mut usize,
+    _: *mut u8,
+    _: usize,
+    _: VoidPtr,
+    _: *mut u32,
+) -> EfiStatus {
+    EFI_UNSUPPORTED
+}
+
+extern "efiapi" fn usb2_hc_async_interrupt_transfer(
+    _: *const Usb2Hc,
+    _: u8,
+    _: u8,
+    _: u8,
+    _: usize,
+    _: bool,
+    _: *mut u8,
+    _: usize,
+    _: usize,
+    _: VoidPtr,
+    _: VoidMutPtr,
+    _: VoidMutPtr,
+) -> EfiStatus {
+    EFI_UNSUPPORTED
+}
+
+extern "efiapi" fn usb2_hc_sync_interrupt_transfer(
+    _: *const Usb2Hc,
+    _: u8,
+    _: u8,
+    _: u8,
+    _: usize,
+    _: VoidMutPtr,
+    _: *mut usize,
+    _: *mut u8,
+    _: usize,
+    _: VoidPtr,
+    _: *mut u32,
+) -> EfiStatus {
+    EFI_UNSUPPORTED
+}
+
+extern "efiapi" fn usb2_hc_isochronous_transfer(
+    _: *const Usb2Hc,
+    _: u8,
+    _: u8,
+    _: u8,
+    _: usize,
+    _: u8,
+    _: *mut VoidMutPtr,
+    _: usize,
+    _: VoidPtr,
+    _: *mut u32,
+) -> EfiStatus {
+    EFI_UNSUPPORTED
+}
+
+extern "efiapi" fn usb2_hc_async_isochronous_transfer(
+    _: *const Usb2Hc,
+    _: u8,
+    _: u8,
+    _: u8,
+    _: usize,
+    _: u8,
+    _: *mut VoidMutPtr,
+    _: usize,
+    _: VoidPtr,
+    _: VoidMutPtr,
+    _: VoidMutPtr,
+) -> EfiStatus {
+    EFI_UNSUPPORTED
+}
+
+extern "efiapi" fn usb2_hc_get_root_hub_port_status(
+    this: *const Usb2Hc,
+    port_number: u8,
+    status: *mut UsbPortStatus,
+) -> EfiStatus {
+    let this: &Usb2Hc = unsafe { &*this };
+
+    if status.is_null() || !this.port_exists(port_number) {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    unsafe {
+        *status = UsbPortStatus {
+            port_status: PORT_CONNECTION | PORT_ENABLE | PORT_POWER | PORT_HIGH_SPEED,
+            port_change_status: this.port_changes.borrow()[usize::from(port_number)],
+        }
+    };
+
+    EFI_SUCCESS
+}
+
+extern "efiapi" fn usb2_hc_set_root_hub_port_feature(
+    this: *const Usb2Hc,
+    port_number: u8,
+    feature: u32,
+) -> EfiStatus {
+    let this: &Usb2Hc = unsafe { &*this };
+
+    if !this.port_exists(port_number) {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    /* Resets complete immediately, re-enabling the port */
+    if feature == FEATURE_RESET {
+        this.port_changes.borrow_mut()[usize::from(port_number)] |=
+            PORT_RESET_CHANGE | PORT_ENABLE_CHANGE;
+    }
+
+    EFI_SUCCESS
+}
+
+extern "efiapi" fn usb2_hc_clear_root_hub_port_feature(
+    this: *const Usb2Hc,
+    port_number: u8,
+    feature: u32,
+) -> EfiStatus {
+    let this: &Usb2Hc = unsafe { &*this };
+
+    if !this.port_exists(port_number) {
+        return EFI_INVALID_PARAMETER;
+    }
+
+    let change: u16 = match feature {
+        FEATURE_CONNECT_CHANGE => PORT_CONNECTION_CHANGE,
+        FEATURE_ENABLE_CHANGE => PORT_ENABLE_CHANGE,
+        FEATURE_RESET_CHANGE => PORT_RESET_CHANGE,
+        _ => 0,
+    };
+
+    this.port_changes.borrow_mut()[usize::from(port_number)] &= !change;
+
+    EFI_SUCCESS
+}
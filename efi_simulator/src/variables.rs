@@ -0,0 +1,157 @@
+use {
+    crate::status::{EFI_INVALID_PARAMETER, EFI_NOT_FOUND, EFI_UNSUPPORTED},
+    efi::{EfiGuid, EfiStatus},
+};
+
+pub const EFI_VARIABLE_NON_VOLATILE: u32 = 0x01;
+pub const EFI_VARIABLE_BOOTSERVICE_ACCESS: u32 = 0x02;
+pub const EFI_VARIABLE_RUNTIME_ACCESS: u32 = 0x04;
+pub(crate) const EFI_VARIABLE_AUTHENTICATED_WRITE_ACCESS: u32 = 0x10;
+pub(crate) const EFI_VARIABLE_TIME_BASED_AUTHENTICATED_WRITE_ACCESS: u32 = 0x20;
+pub const EFI_VARIABLE_APPEND_WRITE: u32 = 0x40;
+pub(crate) const EFI_VARIABLE_ENHANCED_AUTHENTICATED_ACCESS: u32 = 0x80;
+
+pub(crate) struct Variable {
+    pub(crate) name: Vec<u16>,
+    pub(crate) guid: EfiGuid,
+    pub(crate) attributes: u32,
+    pub(crate) data: Vec<u8>,
+}
+
+/// In-memory variable store following `SetVariable`'s create, append and delete rules.
+#[derive(Default)]
+pub(crate) struct VariableStore {
+    variables: Vec<Variable>,
+}
+
+impl VariableStore {
+    fn is_visible(variable: &Variable, runtime: bool) -> bool {
+        !runtime || variable.attributes & EFI_VARIABLE_RUNTIME_ACCESS != 0
+    }
+
+    fn position(&self, name: &[u16], guid: &EfiGuid, runtime: bool) -> Option<usize> {
+        self.variables.iter().position(|variable: &Variable| {
+            variable.name == name && variable.guid == *guid && Self::is_visible(variable, runtime)
+        })
+    }
+
+    /// Looks up a variable. After `ExitBootServices` only runtime variables are visible.
+    pub(crate) fn get(&self, name: &[u16], guid: &EfiGuid, runtime: bool) -> Option<&Variable> {
+        self.position(name, guid, runtime)
+            .map(|index: usize| &self.variables[index])
+    }
+
+    /// Returns the variable following the given one. An empty name starts the enumeration.
+    pub(crate) fn next(
+        &self,
+        name: &[u16],
+        guid: &EfiGuid,
+        runtime: bool,
+    ) -> Result<&Variable, EfiStatus> {
+        let start: usize = if name.is_empty() {
+            0
+        } else {
+            self.position(name, guid, runtime)
+                .ok_or(EFI_INVALID_PARAMETER)?
+                + 1
+        };
+
+        self.variables[start..]
+            .iter()
+            .find(|variable: &&Variable| Self::is_visible(variable, runtime))
+            .ok_or(EFI_NOT_FOUND)
+    }
+
+    /// Returns the bytes taken by the volatile or non-volatile variables' names and data.
+    pub(crate) fn storage_size(&self, non_volatile: bool) -> usize {
+        self.variables
+            .iter()
+            .filter(|variable: &&Variable| {
+                (variable.attributes & EFI_VARIABLE_NON_VOLATILE != 0) == non_volatile
+            })
+            .map(|variable: &Variable| (variable.name.len() + 1) * 2 + variable.data.len())
+            .sum()
+    }
+
+    pub(crate) fn set(
+        &mut self,
+        name: &[u16],
+        guid: &EfiGuid,
+        attributes: u32,
+        data: &[u8],
+        runtime: bool,
+    ) -> Result<(), EfiStatus> {
+        const ACCESS: u32 = EFI_VARIABLE_BOOTSERVICE_ACCESS | EFI_VARIABLE_RUNTIME_ACCESS;
+        const AUTHENTICATED: u32 = EFI_VARIABLE_AUTHENTICATED_WRITE_ACCESS
+            | EFI_VARIABLE_TIME_BASED_AUTHENTICATED_WRITE_ACCESS
+            | EFI_VARIABLE_ENHANCED_AUTHENTICATED_ACCESS;
+
+        if name.is_empty() {
+            return Err(EFI_INVALID_PARAMETER);
+        }
+
+        if attributes & AUTHENTICATED != 0 {
+            return Err(EFI_UNSUPPORTED);
+        }
+
+        if attributes & EFI_VARIABLE_RUNTIME_ACCESS != 0
+            && attributes & EFI_VARIABLE_BOOTSERVICE_ACCESS == 0
+        {
+            return Err(EFI_INVALID_PARAMETER);
+        }
+
+        let append: bool = attributes & EFI_VARIABLE_APPEND_WRITE != 0;
+        let attributes: u32 = attributes & !EFI_VARIABLE_APPEND_WRITE;
+        let existing: Option<usize> = self.position(name, guid, runtime);
+
+        if !append && (data.is_empty() || attributes & ACCESS == 0) {
+            /* Deletion */
+            return match existing {
+                Some(index) => {
+                    self.variables.remove(index);
+
+                    Ok(())
+                }
+                None => Err(EFI_NOT_FOUND),
+            };
+        }
+
+        if runtime
+            && attributes & (EFI_VARIABLE_RUNTIME_ACCESS | EFI_VARIABLE_NON_VOLATILE)
+                != EFI_VARIABLE_RUNTIME_ACCESS | EFI_VARIABLE_NON_VOLATILE
+        {
+            return Err(EFI_INVALID_PARAMETER);
+        }
+
+        match existing {
+            Some(index) => {
+                let variable: &mut Variable = &mut self.variables[index];
+
+                if variable.attributes != attributes {
+                    return Err(EFI_INVALID_PARAMETER);
+                }
+
+                if append {
+                    variable.data.extend_from_slice(data);
+                } else {
+                    variable.data = data.to_vec();
+                }
+            }
+            None => {
+                if data.is_empty() {
+                    /* Appending nothing to a missing variable doesn't create it */
+                    return Ok(());
+                }
+
+                self.variables.push(Variable {
+                    name: name.to_vec(),
+                    guid: *guid,
+                    attributes,
+                    data: data.to_vec(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
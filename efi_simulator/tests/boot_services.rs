@@ -0,0 +1,519 @@
+mod common;
+
+use {
+    common::{disk_image, utf16, VENDOR_GUID},
+    efi::{
+        boot_services::{
+            child_handle::ChildHandle,
+            event::{Event, EventFuture, EventWaker},
+            handle_database::{HandleDatabase, HandleInfo},
+            protocol_notify::ProtocolNotify,
+            sync::{Mutex, TplGuard},
+            types::{
+                event_and_timer::{EfiEventType, EfiTimerDelay},
+                protocol_handler::EfiInterfaceType,
+                task_priority::EfiTaskPriorityLevel,
+            },
+            EfiBootServices,
+        },
+        guids,
+        protocols::{
+            device_path::{EfiDevicePathNode, EfiDevicePathProtocolRaw},
+            driver::{
+                EfiComponentName, EfiComponentName2Protocol, EfiDriver, EfiDriverBindingProtocol,
+            },
+            media::{EfiBlockIOProtocol, EfiDiskIOProtocol, EfiNvdimmLabelProtocol},
+            timestamp::{EfiTimestampProperties, EfiTimestampProtocol},
+            EfiProtocol,
+        },
+        EfiEvent, EfiGuid, EfiHandle, EfiStatusEnum, EfiStatusError, NonNullVoidPtr,
+    },
+    efi_simulator::{SimulatedDisk, Simulator},
+    std::{
+        cell::{Cell, RefCell},
+        future::Future,
+        pin::Pin,
+        rc::Rc,
+        sync::Arc,
+        task::{Context, Poll, Waker},
+        time::Duration,
+    },
+};
+
+#[test]
+fn timers_drive_wait_for_event() {
+    let simulator: Simulator = Simulator::builder().build().unwrap();
+    let boot_services = simulator.boot_services();
+
+    let event: EfiEvent = match boot_services.create_event(
+        EfiEventType::Timer,
+        EfiTaskPriorityLevel::APPLICATION,
+        None,
+    ) {
+        EfiStatusEnum::Success(event) => event,
+        _ => panic!("Creating the timer failed!"),
+    };
+
+    assert!(boot_services.check_event(event).is_error());
+    assert!(boot_services
+        .set_timer(event, EfiTimerDelay::Relative, 1_000)
+        .is_success());
+    assert_eq!(
+        boot_services.wait_for_event(&[event]),
+        EfiStatusEnum::Success(0)
+    );
+    assert!(boot_services.check_event(event).is_error());
+    assert!(boot_services.close_event(event).is_success());
+}
+
+#[test]
+fn event_closures_and_futures() {
+    let simulator: Simulator = Simulator::builder().build().unwrap();
+    /* Events, wakers and drivers keep static boot services, so they're declared after, and dropped before, the simulator */
+    let boot_services = simulator.system_table().boot_services();
+
+    let notified: Rc<Cell<usize>> = Rc::new(Cell::new(0));
+    let counter: Rc<Cell<usize>> = notified.clone();
+
+    let event: Event = match Event::with_notify(
+        boot_services,
+        EfiEventType::NotifySignal,
+        EfiTaskPriorityLevel::CALLBACK,
+        move || counter.set(counter.get() + 1),
+    ) {
+        EfiStatusEnum::Success(event) => event,
+        _ => panic!("Creating the event failed!"),
+    };
+
+    assert!(event.signal().is_success());
+    assert_eq!(notified.get(), 1);
+
+    drop(event);
+
+    /* The closure is released together with the event */
+    assert_eq!(Rc::strong_count(&notified), 1);
+
+    let waker: Arc<EventWaker> = match EventWaker::new(boot_services) {
+        EfiStatusEnum::Success(waker) => waker,
+        _ => panic!("Creating the waker failed!"),
+    };
+
+    let context_waker: Waker = waker.waker();
+    let mut context: Context = Context::from_waker(&context_waker);
+
+    let mut future: EventFuture =
+        match EventFuture::periodic(boot_services, Duration::from_millis(1)) {
+            EfiStatusEnum::Success(future) => future,
+            _ => panic!("Creating the periodic timer failed!"),
+        };
+
+    for _ in 0..3 {
+        let mut parks: usize = 0;
+
+        while Pin::new(&mut future).poll(&mut context).is_pending() {
+            assert!(waker.park().is_success());
+
+            parks += 1;
+        }
+
+        assert_eq!(parks, 1);
+    }
+
+    assert!(future.event().cancel_timer().is_success());
+}
+
+#[test]
+fn mutex_defers_notify_functions() {
+    static SHARED: Mutex<usize> = Mutex::new(EfiTaskPriorityLevel::CALLBACK, 0);
+
+    let simulator: Simulator = Simulator::builder().build().unwrap();
+    /* Events, wakers and drivers keep static boot services, so they're declared after, and dropped before, the simulator */
+    let boot_services = simulator.system_table().boot_services();
+
+    let event: Event = match Event::with_notify(
+        boot_services,
+        EfiEventType::NotifySignal,
+        EfiTaskPriorityLevel::CALLBACK,
+        move || *SHARED.lock(boot_services) += 1,
+    ) {
+        EfiStatusEnum::Success(event) => event,
+        _ => panic!("Creating the event failed!"),
+    };
+
+    {
+        let mut shared = SHARED.lock(boot_services);
+
+        assert!(event.signal().is_success());
+
+        /* The notify function runs once the lock is released */
+        assert_eq!(*shared, 0);
+
+        *shared = 10;
+
+        assert!(SHARED.try_lock(boot_services).is_none());
+    }
+
+    assert_eq!(*SHARED.lock(boot_services), 11);
+
+    {
+        let guard: TplGuard = TplGuard::notify(boot_services);
+
+        assert_eq!(guard.old_level(), EfiTaskPriorityLevel::APPLICATION);
+
+        /* Raising to a lower level keeps the current one */
+        assert_eq!(
+            TplGuard::callback(boot_services).old_level(),
+            EfiTaskPriorityLevel::NOTIFY
+        );
+    }
+}
+
+#[test]
+fn protocol_notify_reports_new_handles() {
+    let simulator: Simulator = Simulator::builder().build().unwrap();
+    /* Events, wakers and drivers keep static boot services, so they're declared after, and dropped before, the simulator */
+    let boot_services = simulator.system_table().boot_services();
+
+    let waker: Arc<EventWaker> = match EventWaker::new(boot_services) {
+        EfiStatusEnum::Success(waker) => waker,
+        _ => panic!("Creating the waker failed!"),
+    };
+
+    let context_waker: Waker = waker.waker();
+    let mut context: Context = Context::from_waker(&context_waker);
+
+    let mut notify: ProtocolNotify<EfiBlockIOProtocol> = match ProtocolNotify::new(boot_services) {
+        EfiStatusEnum::Success(notify) => notify,
+        _ => panic!("Registering the notification failed!"),
+    };
+
+    assert_eq!(notify.try_next(), EfiStatusEnum::Success(None));
+
+    let mut next = notify.next_handle();
+
+    assert!(Pin::new(&mut next).poll(&mut context).is_pending());
+
+    let interface: [u8; 8] = [0; 8];
+    let mut handle: EfiHandle = 0 as _;
+
+    assert!(boot_services
+        .install_protocol_interface(
+            &mut handle,
+            &EfiBlockIOProtocol::guid(),
+            EfiInterfaceType::NativeInterface,
+            Some(&interface),
+        )
+        .is_success());
+
+    /* The installation woke the executor */
+    assert!(waker.park().is_success());
+
+    assert_eq!(
+        Pin::new(&mut next).poll(&mut context),
+        Poll::Ready(EfiStatusEnum::Success(handle))
+    );
+    assert_eq!(notify.try_next(), EfiStatusEnum::Success(None));
+}
+
+#[test]
+fn handle_database_lists_protocols() {
+    let simulator: Simulator = Simulator::builder()
+        .disk(SimulatedDisk::new(disk_image("handle_database", 4)))
+        .build()
+        .unwrap();
+    let boot_services = simulator.boot_services();
+
+    let database: HandleDatabase = match HandleDatabase::inspect(boot_services, "en") {
+        EfiStatusEnum::Success(database) => database,
+        _ => panic!("Inspecting the handle database failed!"),
+    };
+
+    let disk: &HandleInfo = database
+        .find(simulator.disk_handle(0).unwrap())
+        .expect("Disk's handle is missing!");
+
+    assert!(disk.protocols().contains(&EfiBlockIOProtocol::guid()));
+    assert!(disk.protocols().contains(&EfiDiskIOProtocol::guid()));
+
+    /* The vendor device path is printed in the generic form, as there is no device path to text protocol */
+    assert!(disk.device_path().unwrap().starts_with("Path(1,4,"));
+
+    let report: String = database.to_string();
+
+    assert!(report.contains("  EFI_BLOCK_IO_PROTOCOL\n"));
+    assert!(report.contains("  EFI_DISK_IO_PROTOCOL\n"));
+}
+
+#[test]
+fn drivers_manage_child_handles() {
+    struct VendorProtocol {
+        value: u32,
+    }
+
+    impl EfiProtocol for VendorProtocol {
+        type Parsed = &'static Self;
+        type Error = !;
+
+        fn guid() -> EfiGuid {
+            VENDOR_GUID
+        }
+
+        unsafe fn parse(
+            ptr: NonNullVoidPtr,
+        ) -> Result<<Self as EfiProtocol>::Parsed, <Self as EfiProtocol>::Error> {
+            Ok(&*ptr.cast().as_ptr())
+        }
+    }
+
+    static VENDOR_PROTOCOL: VendorProtocol = VendorProtocol { value: 0x4E41_5554 };
+
+    struct PartitionDriver {
+        boot_services: &'static EfiBootServices,
+        children: RefCell<Vec<ChildHandle>>,
+    }
+
+    impl EfiDriver for PartitionDriver {
+        const VERSION: u32 = 0x10;
+
+        fn supported(
+            &self,
+            _: &EfiDriverBindingProtocol,
+            controller_handle: EfiHandle,
+            _: Option<EfiDevicePathProtocolRaw>,
+        ) -> EfiStatusEnum {
+            match self
+                .boot_services
+                .handle_protocol::<EfiBlockIOProtocol>(controller_handle)
+            {
+                Ok(EfiStatusEnum::Success(_)) => EfiStatusEnum::Success(()),
+                _ => EfiStatusEnum::Error(EfiStatusError::EfiUnsupported, ()),
+            }
+        }
+
+        fn start(
+            &self,
+            binding: &EfiDriverBindingProtocol,
+            controller_handle: EfiHandle,
+            _: Option<EfiDevicePathProtocolRaw>,
+        ) -> EfiStatusEnum {
+            let parent_device_path: EfiDevicePathProtocolRaw = match self
+                .boot_services
+                .handle_protocol::<EfiDevicePathProtocolRaw>(
+                controller_handle,
+            ) {
+                Ok(EfiStatusEnum::Success(Ok(device_path))) => device_path,
+                _ => return EfiStatusEnum::Error(EfiStatusError::EfiDeviceError, ()),
+            };
+
+            /* Hard drive media device path node */
+            let node: EfiDevicePathNode = EfiDevicePathNode::new(4, 1, &[0; 38]);
+
+            let mut child: ChildHandle =
+                match ChildHandle::new(self.boot_services, &parent_device_path, &node) {
+                    EfiStatusEnum::Success(child) => child,
+                    _ => return EfiStatusEnum::Error(EfiStatusError::EfiDeviceError, ()),
+                };
+
+            assert!(child
+                .install_interface(self.boot_services, &VENDOR_PROTOCOL)
+                .is_success());
+            assert!(matches!(
+                child.open_parent::<EfiBlockIOProtocol>(
+                    self.boot_services,
+                    controller_handle,
+                    binding.driver_binding_handle(),
+                ),
+                Ok(EfiStatusEnum::Success(_))
+            ));
+
+            /* Protocols the controller lacks are reported through the status */
+            assert!(matches!(
+                child.open_parent::<EfiNvdimmLabelProtocol>(
+                    self.boot_services,
+                    controller_handle,
+                    binding.driver_binding_handle(),
+                ),
+                Ok(EfiStatusEnum::Error(EfiStatusError::EfiUnsupported, ()))
+            ));
+
+            self.children.borrow_mut().push(child);
+
+            EfiStatusEnum::Success(())
+        }
+
+        fn stop(
+            &self,
+            _: &EfiDriverBindingProtocol,
+            _: EfiHandle,
+            children: &[EfiHandle],
+        ) -> EfiStatusEnum {
+            let mut owned: std::cell::RefMut<Vec<ChildHandle>> = self.children.borrow_mut();
+
+            while let Some(index) = owned
+                .iter()
+                .position(|child: &ChildHandle| children.contains(&child.handle()))
+            {
+                if let EfiStatusEnum::Error(error, child) =
+                    owned.remove(index).destroy(self.boot_services)
+                {
+                    owned.push(child);
+
+                    return EfiStatusEnum::Error(error, ());
+                }
+            }
+
+            EfiStatusEnum::Success(())
+        }
+    }
+
+    struct PartitionDriverName {
+        name: Vec<u16>,
+    }
+
+    impl EfiComponentName for PartitionDriverName {
+        const SUPPORTED_LANGUAGES: &'static [u8] = b"en\0";
+
+        fn driver_name(&self, _: &str) -> Option<&[u16]> {
+            Some(&self.name)
+        }
+
+        fn controller_name(&self, _: EfiHandle, _: Option<EfiHandle>, _: &str) -> Option<&[u16]> {
+            None
+        }
+    }
+
+    let simulator: Simulator = Simulator::builder()
+        .disk(SimulatedDisk::new(disk_image("drivers", 4)))
+        .build()
+        .unwrap();
+    /* Events, wakers and drivers keep static boot services, so they're declared after, and dropped before, the simulator */
+    let boot_services = simulator.system_table().boot_services();
+    let disk: EfiHandle = simulator.disk_handle(0).unwrap();
+
+    /* Stands in for the driver's image handle */
+    let interface: [u8; 8] = [0; 8];
+    let mut image_handle: EfiHandle = 0 as _;
+
+    assert!(boot_services
+        .install_protocol_interface(
+            &mut image_handle,
+            &guids::EFI_LOADED_IMAGE_PROTOCOL,
+            EfiInterfaceType::NativeInterface,
+            Some(&interface),
+        )
+        .is_success());
+
+    let binding: &EfiDriverBindingProtocol = match EfiDriverBindingProtocol::install(
+        boot_services,
+        image_handle,
+        PartitionDriver {
+            boot_services,
+            children: RefCell::new(Vec::new()),
+        },
+    ) {
+        EfiStatusEnum::Success(binding) => binding,
+        _ => panic!("Installing the driver failed!"),
+    };
+
+    assert!(EfiComponentName2Protocol::install(
+        boot_services,
+        binding.driver_binding_handle(),
+        PartitionDriverName {
+            name: utf16("NautilOS Partition Driver"),
+        },
+    )
+    .is_success());
+
+    /* Firmware finds the driver through the protocol, as it would while connecting controllers */
+    let found: &EfiDriverBindingProtocol =
+        match boot_services.handle_protocol::<EfiDriverBindingProtocol>(image_handle) {
+            Ok(EfiStatusEnum::Success(Ok(binding))) => binding,
+            _ => panic!("Driver binding protocol is missing!"),
+        };
+
+    assert_eq!(found.version(), 0x10);
+    assert!(found.supported(image_handle, None).is_error());
+    assert!(found.supported(disk, None).is_success());
+    assert!(found.start(disk, None).is_success());
+
+    let database: HandleDatabase = match HandleDatabase::inspect(boot_services, "en-US") {
+        EfiStatusEnum::Success(database) => database,
+        _ => panic!("Inspecting the handle database failed!"),
+    };
+
+    assert_eq!(
+        database.find(image_handle).unwrap().driver_name(),
+        Some("NautilOS Partition Driver")
+    );
+
+    let children: &[EfiHandle] = database.find(disk).unwrap().children();
+
+    assert_eq!(children.len(), 1);
+
+    let child: &HandleInfo = database.find(children[0]).unwrap();
+
+    assert!(child.protocols().contains(&VENDOR_GUID));
+    assert!(child
+        .device_path()
+        .unwrap()
+        .ends_with(&format!("/Path(4,1,{})", "00".repeat(38))));
+
+    match boot_services.handle_protocol::<VendorProtocol>(children[0]) {
+        Ok(EfiStatusEnum::Success(Ok(protocol))) => assert_eq!(protocol.value, 0x4E41_5554),
+        _ => panic!("Vendor protocol is missing!"),
+    }
+
+    assert!(found.stop(disk, children).is_success());
+    assert!(boot_services
+        .handle_protocol::<VendorProtocol>(children[0])
+        .map_or(true, |result| result.is_error()));
+}
+
+#[test]
+fn timestamps_follow_the_virtual_clock() {
+    let simulator: Simulator = Simulator::builder().build().unwrap();
+
+    /* A protocol nobody installed isn't found, rather than being a firmware fault */
+    assert!(matches!(
+        simulator
+            .boot_services()
+            .revision_1_1()
+            .unwrap()
+            .locate_protocol::<EfiTimestampProtocol>(None),
+        Ok(EfiStatusEnum::Error(EfiStatusError::EfiNotFound, ()))
+    ));
+
+    drop(simulator);
+
+    let simulator: Simulator = Simulator::builder().timestamp().build().unwrap();
+
+    let timestamp: &EfiTimestampProtocol = simulator
+        .boot_services()
+        .revision_1_1()
+        .unwrap()
+        .locate_protocol::<EfiTimestampProtocol>(None)
+        .unwrap()
+        .unfold()
+        .ok()
+        .unwrap()
+        .1
+        .unwrap();
+    let properties: EfiTimestampProperties = timestamp.get_properties().unfold().unwrap().1;
+
+    assert_eq!(properties.frequency(), 10_000_000);
+    assert_eq!(properties.end_value(), u64::MAX);
+
+    let start: u64 = timestamp.get_timestamp();
+
+    simulator.advance_time(250);
+
+    let end: u64 = timestamp.get_timestamp();
+
+    assert_eq!(properties.elapsed(start, end), 2500);
+    assert_eq!(
+        properties.to_nanoseconds(properties.elapsed(start, end)),
+        Some(250_000)
+    );
+    /* The counter wraps around past the end value */
+    assert_eq!(properties.elapsed(u64::MAX - 1, 1), 3);
+}
@@ -0,0 +1,31 @@
+/* Each test binary uses only some of the helpers */
+#![allow(dead_code)]
+
+use {
+    efi::EfiGuid,
+    std::{fs, path::PathBuf},
+};
+
+pub const VENDOR_GUID: EfiGuid = EfiGuid::from_tuple((
+    0x8BE4_DF61,
+    0x93CA,
+    0x11D2,
+    [0xAA, 0x0D, 0x00, 0xE0, 0x98, 0x03, 0x2B, 0x8C],
+));
+
+pub fn utf16(string: &str) -> Vec<u16> {
+    string.encode_utf16().chain(Some(0)).collect()
+}
+
+pub fn disk_image(name: &str, blocks: usize) -> PathBuf {
+    let path: PathBuf =
+        std::env::temp_dir().join(format!("efi_simulator_{}_{}.img", name, std::process::id()));
+
+    let data: Vec<u8> = (0..blocks * 512)
+        .map(|index: usize| (index / 512) as u8)
+        .collect();
+
+    fs::write(&path, data).unwrap();
+
+    path
+}
@@ -0,0 +1,282 @@
+use {
+    efi::{
+        protocols::console::{
+            EfiAbsolutePointerMode, EfiAbsolutePointerProtocol, EfiAbsolutePointerState,
+            EfiAnsiTextOutput, EfiBackgroundColor, EfiEdidActiveProtocol,
+            EfiEdidDiscoveredProtocol, EfiForegroundColor, EfiSimplePointerProtocol,
+            EfiSimplePointerState, EfiSimpleTextOutputProtocol, EfiTextAttribute,
+        },
+        structures::edid::{Edid, EdidDetailedTiming, EdidTiming},
+        EfiHandle, EfiStatusEnum, EfiStatusError, EfiSystemTable,
+    },
+    efi_simulator::Simulator,
+    std::fmt::Write,
+};
+
+#[test]
+fn console_output_is_captured() {
+    let simulator: Simulator = Simulator::builder().build().unwrap();
+    let system_table: &EfiSystemTable = simulator.system_table();
+
+    assert!(system_table
+        .con_out()
+        .unwrap()
+        .output_string("Hello\n")
+        .is_success());
+    assert!(system_table
+        .std_err()
+        .unwrap()
+        .output_string("Oops")
+        .is_success());
+
+    assert_eq!(simulator.console_output(), "Hello\n\r");
+    assert_eq!(simulator.standard_error(), "Oops");
+}
+
+#[test]
+fn ansi_escapes_are_interpreted() {
+    let simulator: Simulator = Simulator::builder().build().unwrap();
+    let con_out: &EfiSimpleTextOutputProtocol = simulator.system_table().con_out().unwrap();
+
+    let mut output: EfiAnsiTextOutput = EfiAnsiTextOutput::new(con_out);
+
+    /* The sequence is split across writes */
+    write!(output, "\u{1B}[1;3").unwrap();
+    write!(output, "1;44mError\u{1B}[0m!").unwrap();
+
+    assert_eq!(simulator.console_output(), "Error!");
+    assert_eq!(con_out.get_mode().attribute(), EfiTextAttribute::default());
+
+    write!(output, "\u{1B}[33;41m").unwrap();
+
+    assert_eq!(
+        con_out.get_mode().attribute(),
+        EfiTextAttribute::new(EfiForegroundColor::Brown, EfiBackgroundColor::Red)
+    );
+
+    write!(output, "\u{1B}[5;10H\u{1B}[2A\u{1B}[3C\u{1B}[?25l").unwrap();
+
+    assert_eq!(con_out.get_mode().cursor_row(), 2);
+    assert_eq!(con_out.get_mode().cursor_column(), 12);
+    assert!(!con_out.get_mode().cursor_visible());
+
+    write!(output, "\u{1B}[2K").unwrap();
+
+    assert_eq!(
+        simulator.console_output(),
+        format!("Error!{}", " ".repeat(80))
+    );
+    assert_eq!(con_out.get_mode().cursor_column(), 12);
+}
+
+/// Builds the base block of a 1920x1080 panel's EDID.
+fn panel_edid() -> Vec<u8> {
+    let mut edid: Vec<u8> = vec![0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x00];
+
+    /* "NOS", product 0x1234, serial 42, week 10 of 2021, version 1.4 */
+    edid.extend_from_slice(&[0x39, 0xF3, 0x34, 0x12, 42, 0, 0, 0, 10, 31, 1, 4]);
+    /* Digital, 60x34 cm, gamma and features */
+    edid.extend_from_slice(&[0xA5, 60, 34, 0x78, 0x06]);
+    edid.resize(0x23, 0);
+    /* 640x480@60 and 800x600@60 */
+    edid.extend_from_slice(&[0x21, 0x00, 0x00]);
+    /* 1920x1080@60, the rest unused */
+    edid.extend_from_slice(&[0xD1, 0xC0]);
+    edid.resize(edid.len() + 14, 0x01);
+    /* 1920x1080@60, 148.5 MHz */
+    edid.extend_from_slice(&[
+        0x02, 0x3A, 0x80, 0x18, 0x71, 0x38, 0x2D, 0x40, 0x58, 0x2C, 0x45, 0x00, 0x58, 0x54, 0x21,
+        0x00, 0x00, 0x1E,
+    ]);
+    edid.extend_from_slice(&[0x00, 0x00, 0x00, 0xFC, 0x00]);
+    edid.extend_from_slice(b"NautilOS\n    ");
+    edid.extend_from_slice(&[0x00, 0x00, 0x00, 0x10, 0x00]);
+    edid.resize(edid.len() + 13, 0);
+    edid.extend_from_slice(&[0x00, 0x00, 0x00, 0x10, 0x00]);
+    edid.resize(edid.len() + 13, 0);
+    edid.push(0);
+
+    assert_eq!(edid.len(), 127);
+
+    let sum: u8 = edid
+        .iter()
+        .fold(0, |sum: u8, &byte: &u8| sum.wrapping_add(byte));
+
+    edid.push(sum.wrapping_neg());
+
+    edid
+}
+
+#[test]
+fn displays_report_their_edid() {
+    let edid: Vec<u8> = panel_edid();
+    let simulator: Simulator = Simulator::builder().display(&edid).build().unwrap();
+    let boot_services = simulator.boot_services();
+    let handle: EfiHandle = simulator.display_handle(0).unwrap();
+
+    let discovered: &EfiEdidDiscoveredProtocol = boot_services
+        .handle_protocol::<EfiEdidDiscoveredProtocol>(handle)
+        .unwrap()
+        .unfold()
+        .ok()
+        .unwrap()
+        .1
+        .unwrap();
+    let active: &EfiEdidActiveProtocol = boot_services
+        .handle_protocol::<EfiEdidActiveProtocol>(handle)
+        .unwrap()
+        .unfold()
+        .ok()
+        .unwrap()
+        .1
+        .unwrap();
+
+    assert_eq!(discovered.edid(), Some(&edid[..]));
+
+    let parsed: Edid = active.parse_edid().unwrap();
+
+    assert_eq!(&parsed.manufacturer_id(), b"NOS");
+    assert_eq!(parsed.product_code(), 0x1234);
+    assert_eq!(parsed.serial_number(), 42);
+    assert_eq!(parsed.manufacture_year(), 2021);
+    assert_eq!(parsed.version(), (1, 4));
+    assert!(parsed.is_digital());
+    assert_eq!(parsed.physical_size(), Some((60, 34)));
+    assert_eq!(parsed.monitor_name(), Some("NautilOS"));
+    assert_eq!(parsed.extension_count(), 0);
+    assert_eq!(
+        parsed
+            .established_timings()
+            .map(|timing: EdidTiming| (timing.width(), timing.height(), timing.refresh_rate()))
+            .collect::<Vec<_>>(),
+        [(640, 480, 60), (800, 600, 60)]
+    );
+    assert_eq!(
+        parsed
+            .standard_timings()
+            .map(|timing: EdidTiming| (timing.width(), timing.height(), timing.refresh_rate()))
+            .collect::<Vec<_>>(),
+        [(1920, 1080, 60)]
+    );
+    assert_eq!(parsed.detailed_timings().count(), 1);
+
+    let preferred: EdidDetailedTiming = parsed.preferred_timing().unwrap();
+
+    assert_eq!(parsed.native_resolution(), Some((1920, 1080)));
+    assert_eq!(preferred.pixel_clock(), 14850);
+    assert_eq!(preferred.horizontal_blanking(), 280);
+    assert_eq!(preferred.vertical_blanking(), 45);
+    assert_eq!(preferred.horizontal_sync_offset(), 88);
+    assert_eq!(preferred.horizontal_sync_pulse_width(), 44);
+    assert_eq!(preferred.vertical_sync_offset(), 4);
+    assert_eq!(preferred.vertical_sync_pulse_width(), 5);
+    assert_eq!(
+        (
+            preferred.horizontal_image_size(),
+            preferred.vertical_image_size()
+        ),
+        (600, 340)
+    );
+    assert!(!preferred.is_interlaced());
+    assert_eq!(preferred.refresh_rate(), 60);
+
+    /* Corrupted blocks are rejected */
+    let mut corrupted: Vec<u8> = edid.clone();
+
+    corrupted[0x40] ^= 1;
+
+    assert!(Edid::parse(&corrupted).is_none());
+    assert!(Edid::parse(&edid[..100]).is_none());
+}
+
+#[test]
+fn pointers_report_input() {
+    let simulator: Simulator = Simulator::builder()
+        .simple_pointer()
+        .absolute_pointer(0x7FFF, 0x7FFF)
+        .build()
+        .unwrap();
+    let boot_services = simulator.boot_services();
+
+    let mouse: &EfiSimplePointerProtocol = boot_services
+        .handle_protocol::<EfiSimplePointerProtocol>(simulator.simple_pointer_handle().unwrap())
+        .unwrap()
+        .unfold()
+        .ok()
+        .unwrap()
+        .1
+        .unwrap();
+
+    assert!(mouse.mode().has_left_button() && mouse.mode().has_right_button());
+    assert_ne!(mouse.mode().resolution_x(), 0);
+    assert_eq!(
+        mouse.get_state(),
+        EfiStatusEnum::Error(EfiStatusError::EfiNotReady, ())
+    );
+    assert!(boot_services
+        .check_event(*mouse.wait_for_input())
+        .is_error());
+
+    /* Movement accumulates until it's read */
+    simulator.move_pointer(5, -3, 0, false, false);
+    simulator.move_pointer(2, 1, -1, true, false);
+
+    assert_eq!(
+        boot_services.wait_for_event(&[*mouse.wait_for_input()]),
+        EfiStatusEnum::Success(0)
+    );
+
+    let state: EfiSimplePointerState = mouse.get_state().unfold().ok().unwrap().1;
+
+    assert_eq!(
+        (
+            state.relative_movement_x(),
+            state.relative_movement_y(),
+            state.relative_movement_z()
+        ),
+        (7, -2, -1)
+    );
+    assert!(state.left_button() && !state.right_button());
+    assert!(mouse.get_state().is_error());
+
+    let touch_screen: &EfiAbsolutePointerProtocol = boot_services
+        .handle_protocol::<EfiAbsolutePointerProtocol>(simulator.absolute_pointer_handle().unwrap())
+        .unwrap()
+        .unfold()
+        .ok()
+        .unwrap()
+        .1
+        .unwrap();
+    let mode: &EfiAbsolutePointerMode = touch_screen.mode();
+
+    assert_eq!(
+        (mode.absolute_max_x(), mode.absolute_max_y()),
+        (0x7FFF, 0x7FFF)
+    );
+    assert!(mode.supports(EfiAbsolutePointerMode::SUPPORTS_ALT_ACTIVE));
+    assert!(!mode.supports(EfiAbsolutePointerMode::SUPPORTS_PRESSURE_AS_Z));
+
+    simulator.touch(0x4000, 0x7FFF, EfiAbsolutePointerState::TOUCH_ACTIVE);
+
+    assert!(boot_services
+        .check_event(*touch_screen.wait_for_input())
+        .is_success());
+
+    let state: EfiAbsolutePointerState = touch_screen.get_state().unfold().ok().unwrap().1;
+
+    assert!(state.is_active(EfiAbsolutePointerState::TOUCH_ACTIVE));
+    assert!(!state.is_active(EfiAbsolutePointerState::ALT_ACTIVE));
+    assert_eq!(
+        (
+            mode.scale_x(state.current_x(), 1024),
+            mode.scale_y(state.current_y(), 768)
+        ),
+        (511, 767)
+    );
+    assert!(touch_screen.get_state().is_error());
+
+    simulator.touch(1, 1, 0);
+
+    assert!(touch_screen.reset(false).is_success());
+    assert!(touch_screen.get_state().is_error());
+}
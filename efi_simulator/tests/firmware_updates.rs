@@ -0,0 +1,259 @@
+mod common;
+
+use {
+    common::{utf16, VENDOR_GUID},
+    efi::{
+        guids,
+        protocols::firmware_management::{
+            EfiFirmwareImageDescriptor, EfiFirmwareImageInfo, EfiFirmwareImageUpdatable,
+            EfiFirmwareManagementProtocol, EfiFirmwarePackageInfo,
+        },
+        runtime_services::{
+            capsule::{
+                EfiCapsuleBuffer, EfiCapsuleBuilder, EfiCapsuleFlags, EfiFmpCapsuleImage,
+                EfiFmpCapsulePayload,
+            },
+            capsule_delivery::{self, EfiCapsuleDelivery},
+            miscellaneous::EfiResetType,
+            variable::EfiVariableAttributes,
+            EfiRuntimeServices,
+        },
+        variables::{os_indications, EFI_OS_INDICATIONS_VARIABLE_NAME},
+        EfiFirmwareResourceType, EfiLastAttemptStatus, EfiStatusEnum, EfiStatusError,
+        EfiSystemResourceEntry, EfiSystemResourceTable, EfiSystemTable,
+    },
+    efi_simulator::{
+        SimulatedFirmwareImage, Simulator, EFI_VARIABLE_BOOTSERVICE_ACCESS,
+        EFI_VARIABLE_NON_VOLATILE, EFI_VARIABLE_RUNTIME_ACCESS,
+    },
+};
+
+#[test]
+fn firmware_updates_through_management_protocol() {
+    let simulator: Simulator = Simulator::builder()
+        .firmware_image(
+            SimulatedFirmwareImage::new(VENDOR_GUID, &[2, 0, 0, 0, 0xAA])
+                .name("System Firmware")
+                .lowest_supported_version(2),
+        )
+        .build()
+        .unwrap();
+    let system_table: &EfiSystemTable = simulator.system_table();
+    let boot_services = system_table.boot_services();
+
+    let resources = |system_table: &EfiSystemTable| -> Vec<EfiSystemResourceEntry> {
+        system_table
+            .configuration_tables()
+            .get_by_guid(guids::EFI_SYSTEM_RESOURCE_TABLE)
+            .next()
+            .and_then(|entry| entry.get_as::<EfiSystemResourceTable>())
+            .and_then(EfiSystemResourceTable::entries)
+            .unwrap()
+            .to_vec()
+    };
+
+    let entries: Vec<EfiSystemResourceEntry> = resources(system_table);
+
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].fw_class(), VENDOR_GUID);
+    assert_eq!(
+        entries[0].fw_type(),
+        EfiFirmwareResourceType::SystemFirmware
+    );
+    assert_eq!(entries[0].fw_version(), 2);
+    assert_eq!(entries[0].lowest_supported_fw_version(), 2);
+
+    let fmp: &EfiFirmwareManagementProtocol = boot_services
+        .handle_protocol::<EfiFirmwareManagementProtocol>(
+            simulator.firmware_management_handle().unwrap(),
+        )
+        .unwrap()
+        .unfold()
+        .ok()
+        .unwrap()
+        .1
+        .unwrap();
+
+    let info: EfiFirmwareImageInfo = fmp.get_image_info(boot_services).unfold().ok().unwrap().1;
+    let descriptors: Vec<EfiFirmwareImageDescriptor> = info.descriptors().collect();
+
+    assert_eq!(
+        info.package_version_name(),
+        &utf16("Simulated Package")[..17]
+    );
+    assert_eq!(descriptors.len(), 1);
+    assert_eq!(descriptors[0].image_index(), 1);
+    assert_eq!(descriptors[0].image_type_id(), VENDOR_GUID);
+    assert_eq!(
+        descriptors[0].image_id_name(),
+        Some(&utf16("System Firmware")[..15])
+    );
+    assert_eq!(descriptors[0].version(), 2);
+    assert_eq!(descriptors[0].size(), 5);
+    assert_eq!(descriptors[0].lowest_supported_image_version(), Some(2));
+    assert_eq!(descriptors[0].dependencies(), None);
+
+    assert_eq!(
+        fmp.get_image(1),
+        EfiStatusEnum::Success(vec![2, 0, 0, 0, 0xAA])
+    );
+
+    let old: EfiFirmwareImageUpdatable = fmp.check_image(1, &[1, 0, 0, 0]).unfold().ok().unwrap().1;
+
+    assert!(old.contains(EfiFirmwareImageUpdatable::INVALID_OLD));
+    assert!(!old.is_valid());
+    assert!(fmp
+        .check_image(1, &[3, 0, 0, 0])
+        .unfold()
+        .ok()
+        .unwrap()
+        .1
+        .is_valid());
+
+    /* Rejected updates report the reason and the attempt through the ESRT */
+    assert_eq!(
+        fmp.set_image(boot_services, 1, &[1, 0, 0, 0], None, None),
+        EfiStatusEnum::Error(
+            EfiStatusError::EfiAborted,
+            Some(utf16("Version too old")[..15].to_vec())
+        )
+    );
+    assert_eq!(
+        resources(system_table)[0].last_attempt_status(),
+        EfiLastAttemptStatus::IncorrectVersion
+    );
+    assert_eq!(resources(system_table)[0].last_attempt_version(), 1);
+
+    let mut completions: Vec<usize> = Vec::new();
+
+    assert!(fmp
+        .set_image(
+            boot_services,
+            1,
+            &[3, 0, 0, 0, 0xBB, 0xCC],
+            None,
+            Some(&mut |completion: usize| completions.push(completion)),
+        )
+        .is_success());
+    assert_eq!(completions, [50, 100]);
+    assert_eq!(
+        simulator.firmware_image(0),
+        Some((3, vec![3, 0, 0, 0, 0xBB, 0xCC]))
+    );
+
+    let entries: Vec<EfiSystemResourceEntry> = resources(system_table);
+
+    assert_eq!(entries[0].fw_version(), 3);
+    assert_eq!(
+        entries[0].last_attempt_status(),
+        EfiLastAttemptStatus::Success
+    );
+
+    assert!(fmp
+        .set_package_info(None, None, 7, &utf16("Package 7"))
+        .is_success());
+
+    let package: EfiFirmwarePackageInfo =
+        fmp.get_package_info(boot_services).unfold().ok().unwrap().1;
+
+    assert_eq!(package.version(), 7);
+    assert_eq!(package.version_name(), &utf16("Package 7")[..9]);
+    assert_eq!(
+        fmp.set_package_info(None, None, 8, &utf16("Package 8")[..9]),
+        EfiStatusEnum::Error(EfiStatusError::EfiInvalidParameter, ())
+    );
+}
+
+#[test]
+fn capsules_are_delivered_through_update_capsule() {
+    let capsule: EfiCapsuleBuffer = EfiCapsuleBuilder::firmware_management(
+        &EfiFmpCapsulePayload::new().image(EfiFmpCapsuleImage::new(
+            VENDOR_GUID,
+            1,
+            &[3, 0, 0, 0, 0xBB],
+        )),
+    )
+    .flags(EfiCapsuleFlags::new(EfiCapsuleFlags::PERSIST_ACROSS_RESET))
+    .build()
+    .unwrap();
+
+    assert_eq!(
+        capsule.header().capsule_guid(),
+        guids::EFI_FIRMWARE_MANAGEMENT_CAPSULE_ID
+    );
+    assert_eq!(
+        capsule.header().capsule_image_size() as usize,
+        capsule.as_bytes().len()
+    );
+
+    {
+        let simulator: Simulator = Simulator::builder().build().unwrap();
+        let runtime_services: &EfiRuntimeServices = simulator.runtime_services();
+
+        let storage = runtime_services
+            .revision_2_0()
+            .unwrap()
+            .query_variable_info(&EfiVariableAttributes::new(
+                EFI_VARIABLE_NON_VOLATILE | EFI_VARIABLE_BOOTSERVICE_ACCESS,
+            ))
+            .unfold()
+            .ok()
+            .unwrap()
+            .1;
+
+        assert!(storage.remaining_storage_size() <= storage.maximum_storage_size());
+        assert_ne!(storage.maximum_variable_size(), 0);
+
+        /* Persisting capsules are read back through the scatter-gather list */
+        match capsule_delivery::deliver(runtime_services, &[&capsule], None) {
+            EfiStatusEnum::Success(EfiCapsuleDelivery::UpdateCapsule {
+                reset_type,
+                scatter_gather_list,
+            }) => {
+                assert_eq!(reset_type, EfiResetType::Warm);
+                assert!(scatter_gather_list.is_some());
+            }
+            _ => panic!("Delivering the capsule failed!"),
+        }
+
+        assert_eq!(simulator.capsules(), [capsule.as_bytes()]);
+    }
+
+    let simulator: Simulator = Simulator::builder()
+        .capsule_updates(false)
+        .variable(
+            "OsIndicationsSupported",
+            guids::EFI_GLOBAL_VARIABLE,
+            EFI_VARIABLE_BOOTSERVICE_ACCESS | EFI_VARIABLE_RUNTIME_ACCESS,
+            &os_indications::FILE_CAPSULE_DELIVERY_SUPPORTED.to_le_bytes(),
+        )
+        .build()
+        .unwrap();
+    let runtime_services: &EfiRuntimeServices = simulator.runtime_services();
+
+    /* Without the system partition there's nothing to fall back to */
+    assert!(matches!(
+        capsule_delivery::deliver(runtime_services, &[&capsule], None),
+        EfiStatusEnum::Error(EfiStatusError::EfiUnsupported, ())
+    ));
+    assert!(simulator.capsules().is_empty());
+
+    assert!(capsule_delivery::file_capsule_delivery_supported(
+        runtime_services
+    ));
+    assert!(capsule_delivery::request_file_capsule_delivery(runtime_services).is_success());
+    assert_eq!(
+        simulator
+            .variable("OsIndications", &guids::EFI_GLOBAL_VARIABLE)
+            .map(|(_, data): (u32, Vec<u8>)| data),
+        Some(
+            os_indications::FILE_CAPSULE_DELIVERY_SUPPORTED
+                .to_le_bytes()
+                .to_vec()
+        )
+    );
+    assert_eq!(
+        EFI_OS_INDICATIONS_VARIABLE_NAME,
+        &utf16("OsIndications")[..]
+    );
+}
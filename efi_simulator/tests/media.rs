@@ -0,0 +1,1093 @@
+mod common;
+
+use {
+    acpi::{
+        devices::acpi_defined::NVDIMMDeviceHandle,
+        tables::{Nfit, RegionMapping, SdtHeader, SpaRange, NFIT_PERSISTENT_MEMORY_REGION},
+    },
+    common::{disk_image, VENDOR_GUID},
+    efi::{
+        boot_services::{event::EventWaker, sync::TplGuard},
+        guids,
+        protocols::{
+            device_path::EfiDevicePathProtocolRaw,
+            media::{
+                EfiAlignedBuffer, EfiBlockIO2Protocol, EfiBlockIOProtocol, EfiDiskIO2Protocol,
+                EfiDiskIOProtocol, EfiIO2Future, EfiNvdimmLabelProtocol,
+                EfiNvdimmLabelStorageInformation, EfiNvmeCommand, EfiNvmeCompletion,
+                EfiNvmeIdentifyController, EfiNvmeIdentifyNamespace, EfiNvmePassThruMode,
+                EfiNvmePassThruProtocol, EfiNvmeQueueType, EfiNvmeSmartLog,
+                EfiRamDiskDevicePathNode, EfiRamDiskProtocol, EfiScsiCdb, EfiScsiInquiryData,
+                EfiScsiReadCapacity,
+            },
+        },
+        structures::{
+            gpt::{EfiPartitionEntry, EfiPartitionTableHeader},
+            nvdimm_label::{EfiNvdimmLabelArea, EfiNvdimmNamespaceLabel},
+        },
+        EfiGuid, EfiHandle, EfiStatusEnum, EfiStatusError, NonNullVoidPtr,
+    },
+    efi_simulator::{SimulatedDisk, SimulatedNvdimm, SimulatedNvmeController, Simulator},
+    std::{
+        fs,
+        future::Future,
+        path::PathBuf,
+        pin::Pin,
+        process::{Command, Output},
+        sync::Arc,
+        task::{Context, Poll, Waker},
+    },
+};
+
+#[test]
+fn disks_are_readable() {
+    let path: PathBuf = disk_image("read", 4);
+    let simulator: Simulator = Simulator::builder()
+        .disk(SimulatedDisk::new(&path).read_only(true))
+        .build()
+        .unwrap();
+    let handle: EfiHandle = simulator.disk_handle(0).unwrap();
+    let boot_services = simulator.boot_services();
+
+    let block_io: &EfiBlockIOProtocol = boot_services
+        .handle_protocol::<EfiBlockIOProtocol>(handle)
+        .unwrap()
+        .unfold()
+        .ok()
+        .unwrap()
+        .1
+        .unwrap();
+
+    assert_eq!(block_io.media_revision_1().block_size(), 512);
+    assert_eq!(block_io.media_revision_1().last_block(), 3);
+
+    let mut buffer: Vec<u8> = vec![0; 1024];
+
+    assert!(block_io.read_blocks(0, 2, &mut buffer).is_success());
+    assert!(buffer[..512].iter().all(|&byte: &u8| byte == 2));
+    assert!(buffer[512..].iter().all(|&byte: &u8| byte == 3));
+    assert!(block_io.read_blocks(0, 3, &mut buffer).is_error());
+    assert!(block_io.write_blocks(0, 0, &buffer[..512]).is_error());
+
+    let disk_io: &EfiDiskIOProtocol = boot_services
+        .handle_protocol::<EfiDiskIOProtocol>(handle)
+        .unwrap()
+        .unfold()
+        .ok()
+        .unwrap()
+        .1
+        .unwrap();
+
+    let mut buffer: [u8; 4] = [0; 4];
+
+    assert!(disk_io.read_disk(0, 510, &mut buffer).is_success());
+    assert_eq!(buffer, [0, 0, 1, 1]);
+
+    drop(simulator);
+
+    fs::remove_file(path).unwrap();
+}
+
+/// Polls the future, parking on the waker while it's pending, and returns it's output with the number of parks.
+fn block_on_parking<F: Future>(waker: &Arc<EventWaker>, future: F) -> (F::Output, usize) {
+    let context_waker: Waker = waker.waker();
+    let mut context: Context = Context::from_waker(&context_waker);
+    let mut future: Pin<Box<F>> = Box::pin(future);
+    let mut parks: usize = 0;
+
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return (output, parks);
+        }
+
+        assert!(waker.park().is_success());
+
+        parks += 1;
+    }
+}
+
+#[test]
+fn io2_requests_complete_through_their_event() {
+    let path: PathBuf = disk_image("io2", 4);
+    let simulator: Simulator = Simulator::builder()
+        .disk(SimulatedDisk::new(&path))
+        .build()
+        .unwrap();
+
+    /* The simulator keeps the image open, while wakers have to be dropped before it */
+    fs::remove_file(path).unwrap();
+
+    let handle: EfiHandle = simulator.disk_handle(0).unwrap();
+    let boot_services = simulator.system_table().boot_services();
+
+    let block_io: &EfiBlockIO2Protocol = boot_services
+        .handle_protocol::<EfiBlockIO2Protocol>(handle)
+        .unwrap()
+        .unfold()
+        .ok()
+        .unwrap()
+        .1
+        .unwrap();
+    let disk_io: &EfiDiskIO2Protocol = boot_services
+        .handle_protocol::<EfiDiskIO2Protocol>(handle)
+        .unwrap()
+        .unfold()
+        .ok()
+        .unwrap()
+        .1
+        .unwrap();
+
+    let waker: Arc<EventWaker> = match EventWaker::new(boot_services) {
+        EfiStatusEnum::Success(waker) => waker,
+        _ => panic!("Creating the waker failed!"),
+    };
+
+    let mut buffer: Vec<u8> = vec![0xFF; 1024];
+
+    /* The executor only wakes up once the firmware completes the request */
+    let (result, parks): (EfiStatusEnum, usize) = block_on_parking(&waker, unsafe {
+        block_io.read_blocks(boot_services, 0, 1, &mut buffer)
+    });
+
+    assert_eq!(result, EfiStatusEnum::Success(()));
+    assert_eq!(parks, 1);
+    assert!(buffer[..512].iter().all(|&byte: &u8| byte == 1));
+    assert!(buffer[512..].iter().all(|&byte: &u8| byte == 2));
+
+    let data: [u8; 4] = [9; 4];
+
+    assert_eq!(
+        block_on_parking(&waker, unsafe {
+            disk_io.write_disk(boot_services, 0, 510, &data)
+        })
+        .0,
+        EfiStatusEnum::Success(())
+    );
+    assert_eq!(
+        block_on_parking(&waker, disk_io.flush_disk(boot_services)).0,
+        EfiStatusEnum::Success(())
+    );
+
+    let mut buffer: [u8; 6] = [0; 6];
+
+    assert_eq!(
+        block_on_parking(&waker, unsafe {
+            disk_io.read_disk(boot_services, 0, 509, &mut buffer)
+        })
+        .0,
+        EfiStatusEnum::Success(())
+    );
+    assert_eq!(buffer, [0, 9, 9, 9, 9, 1]);
+
+    /* Invalid requests fail when they're submitted */
+    let mut buffer: Vec<u8> = vec![0; 512];
+
+    assert_eq!(
+        block_on_parking(&waker, unsafe {
+            block_io.read_blocks(boot_services, 0, 4, &mut buffer)
+        }),
+        (
+            EfiStatusEnum::Error(EfiStatusError::EfiInvalidParameter, ()),
+            0
+        )
+    );
+
+    /* Cancelled requests complete with the error as their transaction status */
+    let context_waker: Waker = waker.waker();
+    let mut context: Context = Context::from_waker(&context_waker);
+
+    {
+        let mut future: Pin<Box<EfiIO2Future>> =
+            Box::pin(unsafe { disk_io.read_disk(boot_services, 0, 0, &mut buffer) });
+
+        assert!(future.as_mut().poll(&mut context).is_pending());
+        assert!(disk_io.cancel().is_success());
+        assert_eq!(
+            future.as_mut().poll(&mut context),
+            Poll::Ready(EfiStatusEnum::Error(EfiStatusError::EfiAborted, ()))
+        );
+    }
+
+    assert!(buffer.iter().all(|&byte: &u8| byte == 0));
+
+    /* Dropped Block IO 2 requests are waited for, as they can't be cancelled */
+    {
+        let mut future: Pin<Box<EfiIO2Future>> =
+            Box::pin(unsafe { block_io.read_blocks(boot_services, 0, 3, &mut buffer) });
+
+        assert!(future.as_mut().poll(&mut context).is_pending());
+    }
+
+    assert!(buffer.iter().all(|&byte: &u8| byte == 3));
+
+    /* Dropped Disk IO 2 requests are cancelled before being waited for */
+    buffer.fill(0);
+
+    {
+        let mut future: Pin<Box<EfiIO2Future>> =
+            Box::pin(unsafe { disk_io.read_disk(boot_services, 0, 512, &mut buffer) });
+
+        assert!(future.as_mut().poll(&mut context).is_pending());
+    }
+
+    assert!(buffer.iter().all(|&byte: &u8| byte == 0));
+}
+
+#[test]
+fn io2_futures_dropped_at_raised_tpl_panic() {
+    const CHILD_VARIABLE: &str = "EFI_SIMULATOR_IO2_PANIC_CHILD";
+
+    /* Panics abort the tests, so the panicking part runs in a child process */
+    if std::env::var_os(CHILD_VARIABLE).is_none() {
+        let output: Output = Command::new(std::env::current_exe().unwrap())
+            .args([
+                "--exact",
+                "--nocapture",
+                "io2_futures_dropped_at_raised_tpl_panic",
+            ])
+            .env(CHILD_VARIABLE, "1")
+            .output()
+            .unwrap();
+
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stderr).contains(
+            "Unfinished IO 2 request dropped at a task priority level blocking it's completion!"
+        ));
+
+        return;
+    }
+
+    let path: PathBuf = disk_image("io2_panic", 1);
+    let simulator: Simulator = Simulator::builder()
+        .disk(SimulatedDisk::new(&path))
+        .build()
+        .unwrap();
+    let boot_services = simulator.system_table().boot_services();
+
+    fs::remove_file(path).unwrap();
+
+    let block_io: &EfiBlockIO2Protocol = boot_services
+        .handle_protocol::<EfiBlockIO2Protocol>(simulator.disk_handle(0).unwrap())
+        .unwrap()
+        .unfold()
+        .ok()
+        .unwrap()
+        .1
+        .unwrap();
+
+    let waker: Arc<EventWaker> = match EventWaker::new(boot_services) {
+        EfiStatusEnum::Success(waker) => waker,
+        _ => panic!("Creating the waker failed!"),
+    };
+    let context_waker: Waker = waker.waker();
+    let mut context: Context = Context::from_waker(&context_waker);
+
+    let mut buffer: Vec<u8> = vec![0; 512];
+    let mut future: Pin<Box<EfiIO2Future>> =
+        Box::pin(unsafe { block_io.read_blocks(boot_services, 0, 0, &mut buffer) });
+
+    assert!(future.as_mut().poll(&mut context).is_pending());
+
+    /* The completion's notify function can't run, so the drop would wait forever */
+    let _guard: TplGuard = TplGuard::callback(boot_services);
+
+    drop(future);
+}
+
+const EFI_SYSTEM_PARTITION_GUID: EfiGuid = EfiGuid::from_tuple((
+    0xC12A_7328,
+    0xF81F,
+    0x11D2,
+    [0xBA, 0x4B, 0x00, 0xA0, 0xC9, 0x3E, 0xC9, 0x3B],
+));
+
+const LINUX_DATA_PARTITION_GUID: EfiGuid = EfiGuid::from_tuple((
+    0x0FC6_3DAF,
+    0x8483,
+    0x4772,
+    [0x8E, 0x79, 0x3D, 0x69, 0xD8, 0x47, 0x7D, 0xE4],
+));
+
+/// Starting and ending LBA, type and name of a partition entry.
+type GptPartitionFields = (u64, u64, EfiGuid, &'static str);
+
+const GPT_PARTITIONS: [Option<GptPartitionFields>; 4] = [
+    Some((3, 4, EFI_SYSTEM_PARTITION_GUID, "EFI system")),
+    None,
+    Some((5, 5, LINUX_DATA_PARTITION_GUID, "kernel")),
+    None,
+];
+
+/// Builds an 8 block disk with a protective MBR, the primary GPT at LBA 1 and 2 and the backup one at LBA 6 and 7.
+fn gpt_disk_image(name: &str) -> PathBuf {
+    let path: PathBuf = disk_image(name, 8);
+    let mut data: Vec<u8> = vec![0; 8 * 512];
+
+    /* Protective MBR spanning the whole disk */
+    data[446 + 4] = 0xEE;
+    data[446 + 8..446 + 12].copy_from_slice(&1u32.to_le_bytes());
+    data[446 + 12..446 + 16].copy_from_slice(&7u32.to_le_bytes());
+    data[510..512].copy_from_slice(&[0x55, 0xAA]);
+
+    let mut entries: Vec<u8> = vec![0; 4 * 128];
+
+    for (index, partition) in GPT_PARTITIONS.iter().enumerate() {
+        if let Some((starting_lba, ending_lba, type_guid, name)) = *partition {
+            let entry: &mut [u8] = &mut entries[index * 128..][..128];
+
+            entry[..16].copy_from_slice(&type_guid.to_array());
+            entry[16..32].copy_from_slice(&[index as u8 + 1; 16]);
+            entry[32..40].copy_from_slice(&starting_lba.to_le_bytes());
+            entry[40..48].copy_from_slice(&ending_lba.to_le_bytes());
+
+            for (unit, bytes) in name.encode_utf16().zip(entry[56..].chunks_mut(2)) {
+                bytes.copy_from_slice(&unit.to_le_bytes());
+            }
+        }
+    }
+
+    let entries_crc32: u32 = efi::crc32::checksum(&entries);
+
+    for &(my_lba, alternate_lba, partition_entry_lba) in &[(1u64, 7u64, 2u64), (7, 1, 6)] {
+        let header: &mut [u8] = &mut data[my_lba as usize * 512..][..92];
+
+        header[..8].copy_from_slice(b"EFI PART");
+        header[8..12].copy_from_slice(&0x0001_0000u32.to_le_bytes());
+        header[12..16].copy_from_slice(&92u32.to_le_bytes());
+        header[24..32].copy_from_slice(&my_lba.to_le_bytes());
+        header[32..40].copy_from_slice(&alternate_lba.to_le_bytes());
+        header[40..48].copy_from_slice(&3u64.to_le_bytes());
+        header[48..56].copy_from_slice(&5u64.to_le_bytes());
+        header[56..72].copy_from_slice(&VENDOR_GUID.to_array());
+        header[72..80].copy_from_slice(&partition_entry_lba.to_le_bytes());
+        header[80..84].copy_from_slice(&4u32.to_le_bytes());
+        header[84..88].copy_from_slice(&128u32.to_le_bytes());
+        header[88..92].copy_from_slice(&entries_crc32.to_le_bytes());
+
+        let header_crc32: u32 = efi::crc32::checksum(header);
+
+        header[16..20].copy_from_slice(&header_crc32.to_le_bytes());
+
+        data[partition_entry_lba as usize * 512..][..entries.len()].copy_from_slice(&entries);
+    }
+
+    fs::write(&path, data).unwrap();
+
+    path
+}
+
+#[test]
+fn partition_tables_are_parsed() {
+    let path: PathBuf = gpt_disk_image("gpt");
+    let simulator: Simulator = Simulator::builder()
+        .disk(SimulatedDisk::new(&path))
+        .build()
+        .unwrap();
+    let handle: EfiHandle = simulator.disk_handle(0).unwrap();
+
+    let block_io: &EfiBlockIOProtocol = simulator
+        .boot_services()
+        .handle_protocol::<EfiBlockIOProtocol>(handle)
+        .unwrap()
+        .unfold()
+        .ok()
+        .unwrap()
+        .1
+        .unwrap();
+
+    let read_table = |lba: u64| -> Option<(EfiPartitionTableHeader, Vec<EfiPartitionEntry>)> {
+        let mut block: Vec<u8> = vec![0; 512];
+
+        assert!(block_io.read_blocks(0, lba, &mut block).is_success());
+
+        let header: EfiPartitionTableHeader = EfiPartitionTableHeader::parse(&block)?;
+        let mut entries: Vec<u8> = vec![0; 512];
+
+        assert!(block_io
+            .read_blocks(0, header.partition_entry_lba(), &mut entries)
+            .is_success());
+
+        let entries: Vec<EfiPartitionEntry> = header.parse_entries(&entries)?.collect();
+
+        Some((header, entries))
+    };
+
+    let (primary, primary_entries): (EfiPartitionTableHeader, Vec<EfiPartitionEntry>) =
+        read_table(1).unwrap();
+
+    assert_eq!(primary.revision(), 0x0001_0000);
+    assert_eq!(primary.my_lba(), 1);
+    assert_eq!(primary.alternate_lba(), 7);
+    assert_eq!(primary.first_usable_lba(), 3);
+    assert_eq!(primary.last_usable_lba(), 5);
+    assert_eq!(primary.disk_guid(), VENDOR_GUID);
+    assert_eq!(primary.partition_entry_lba(), 2);
+    assert_eq!(primary.partition_entry_array_size(), 512);
+
+    /* Unused entries are skipped */
+    assert_eq!(primary_entries.len(), 2);
+
+    for (entry, (index, (starting_lba, ending_lba, type_guid, name))) in primary_entries.iter().zip(
+        GPT_PARTITIONS
+            .iter()
+            .enumerate()
+            .filter_map(|(index, partition)| Some((index, (*partition)?))),
+    ) {
+        assert_eq!(entry.partition_type_guid(), type_guid);
+        assert_eq!(
+            entry.unique_partition_guid(),
+            EfiGuid::from_array(&[index as u8 + 1; 16])
+        );
+        assert_eq!(entry.starting_lba(), starting_lba);
+        assert_eq!(entry.ending_lba(), ending_lba);
+        assert_eq!(entry.attributes(), 0);
+        assert_eq!(
+            entry.partition_name(),
+            &name.encode_utf16().collect::<Vec<u16>>()[..]
+        );
+    }
+
+    /* The backup header points back at the primary one */
+    let (backup, backup_entries): (EfiPartitionTableHeader, Vec<EfiPartitionEntry>) =
+        read_table(primary.alternate_lba()).unwrap();
+
+    assert_eq!(backup.my_lba(), 7);
+    assert_eq!(backup.alternate_lba(), primary.my_lba());
+    assert_eq!(backup.partition_entry_lba(), 6);
+    assert_eq!(backup.disk_guid(), primary.disk_guid());
+    assert_eq!(backup_entries.len(), primary_entries.len());
+
+    for (backup_entry, primary_entry) in backup_entries.iter().zip(&primary_entries) {
+        assert_eq!(
+            backup_entry.unique_partition_guid(),
+            primary_entry.unique_partition_guid()
+        );
+        assert_eq!(
+            backup_entry.partition_name(),
+            primary_entry.partition_name()
+        );
+    }
+
+    /* A corrupted primary header fails it's checksum, leaving the backup to be used */
+    let mut block: Vec<u8> = vec![0; 512];
+
+    assert!(block_io.read_blocks(0, 1, &mut block).is_success());
+
+    block[40] ^= 1;
+
+    assert!(block_io.write_blocks(0, 1, &block).is_success());
+    assert!(read_table(1).is_none());
+    assert!(read_table(7).is_some());
+
+    /* So does a corrupted entry in the backup's partition entry array */
+    assert!(block_io.read_blocks(0, 6, &mut block).is_success());
+
+    block[2 * 128 + 56] ^= 1;
+
+    assert!(block_io.write_blocks(0, 6, &block).is_success());
+    assert!(read_table(7).is_none());
+    assert!(block_io.read_blocks(0, 7, &mut block).is_success());
+    assert!(EfiPartitionTableHeader::parse(&block).is_some());
+
+    /* An array shorter than the header describes is rejected */
+    assert!(backup.parse_entries(&[0; 256]).is_none());
+
+    drop(simulator);
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn nvme_controllers_answer_admin_commands() {
+    let simulator: Simulator = Simulator::builder()
+        .nvme_controller(
+            SimulatedNvmeController::new("NAUT0001", "NautilOS Simulated SSD")
+                .namespace(0x10_0000, 512)
+                .namespace(0x2_0000, 4096)
+                .temperature(310)
+                .power_on_hours(42),
+        )
+        .build()
+        .unwrap();
+    let boot_services = simulator.boot_services();
+
+    let nvme: &EfiNvmePassThruProtocol = boot_services
+        .handle_protocol::<EfiNvmePassThruProtocol>(simulator.nvme_handle().unwrap())
+        .unwrap()
+        .unfold()
+        .ok()
+        .unwrap()
+        .1
+        .unwrap();
+    let mode: &EfiNvmePassThruMode = nvme.mode();
+
+    assert!(mode.contains(EfiNvmePassThruMode::PHYSICAL | EfiNvmePassThruMode::CMD_SET_NVM));
+    assert_eq!(mode.nvme_version(), 0x0001_0400);
+    assert_eq!(nvme.namespaces().collect::<Vec<u32>>(), [1, 2]);
+    assert_eq!(
+        nvme.get_next_namespace(2),
+        EfiStatusEnum::Error(EfiStatusError::EfiNotFound, ())
+    );
+
+    let mut buffer: EfiAlignedBuffer =
+        EfiAlignedBuffer::new(EfiNvmeCommand::IDENTIFY_SIZE, mode.io_align()).unwrap();
+
+    assert_eq!(buffer.as_ptr() as usize % mode.io_align() as usize, 0);
+
+    let completion: EfiNvmeCompletion = nvme
+        .pass_thru(
+            0,
+            EfiNvmeQueueType::Admin,
+            &EfiNvmeCommand::identify_controller(),
+            Some(&mut buffer),
+            0,
+        )
+        .unfold()
+        .ok()
+        .unwrap()
+        .1;
+
+    assert!(completion.is_success());
+
+    let controller: EfiNvmeIdentifyController = EfiNvmeIdentifyController::parse(&buffer).unwrap();
+
+    assert_eq!(controller.serial_number(), "NAUT0001");
+    assert_eq!(controller.model_number(), "NautilOS Simulated SSD");
+    assert_eq!(controller.firmware_revision(), "1.0");
+    assert_eq!(controller.number_of_namespaces(), 2);
+
+    assert!(nvme
+        .pass_thru(
+            2,
+            EfiNvmeQueueType::Admin,
+            &EfiNvmeCommand::identify_namespace(2),
+            Some(&mut buffer),
+            0,
+        )
+        .is_success());
+
+    let namespace: EfiNvmeIdentifyNamespace = EfiNvmeIdentifyNamespace::parse(&buffer).unwrap();
+
+    assert_eq!(namespace.size(), 0x2_0000);
+    assert_eq!(namespace.lba_formats_count(), 1);
+    assert_eq!(namespace.lba_format(0), Some((0, 4096)));
+    assert_eq!(namespace.block_size(), Some(4096));
+
+    /* Identify Namespace reports unknown namespaces through the completion */
+    match nvme.pass_thru(
+        3,
+        EfiNvmeQueueType::Admin,
+        &EfiNvmeCommand::identify_namespace(3),
+        Some(&mut buffer),
+        0,
+    ) {
+        EfiStatusEnum::Error(EfiStatusError::EfiDeviceError, completion) => {
+            assert_eq!(
+                (completion.status_code_type(), completion.status_code()),
+                (0, 0x0B)
+            );
+        }
+        status => panic!("Unexpected status {:?}!", status),
+    }
+
+    assert!(nvme
+        .pass_thru(
+            0,
+            EfiNvmeQueueType::Admin,
+            &EfiNvmeCommand::active_namespace_list(1),
+            Some(&mut buffer),
+            0,
+        )
+        .is_success());
+    assert_eq!(buffer[..8], [2, 0, 0, 0, 0, 0, 0, 0]);
+
+    let mut log: EfiAlignedBuffer = EfiAlignedBuffer::new(
+        EfiNvmeCommand::SMART_HEALTH_INFORMATION_SIZE,
+        mode.io_align(),
+    )
+    .unwrap();
+
+    assert!(nvme
+        .pass_thru(
+            EfiNvmePassThruProtocol::ALL_NAMESPACES,
+            EfiNvmeQueueType::Admin,
+            &EfiNvmeCommand::get_log_page(
+                EfiNvmeCommand::LOG_SMART_HEALTH_INFORMATION,
+                EfiNvmePassThruProtocol::ALL_NAMESPACES,
+                EfiNvmeCommand::SMART_HEALTH_INFORMATION_SIZE,
+            ),
+            Some(&mut log),
+            0,
+        )
+        .is_success());
+
+    let smart: EfiNvmeSmartLog = EfiNvmeSmartLog::parse(&log).unwrap();
+
+    assert_eq!(smart.critical_warning(), 0);
+    assert_eq!(smart.composite_temperature(), 310);
+    assert_eq!(smart.available_spare(), 100);
+    assert_eq!(smart.power_on_hours(), 42);
+
+    let device_path = nvme.build_device_path(2).unfold().ok().unwrap().1;
+    let node: &[u8] = unsafe { std::slice::from_raw_parts(device_path.as_ptr() as *const u8, 16) };
+
+    assert_eq!(node[..8], [0x03, 0x17, 16, 0, 2, 0, 0, 0]);
+    assert_eq!(nvme.get_namespace(&device_path), EfiStatusEnum::Success(2));
+    assert!(boot_services
+        .free_pool(device_path.as_ptr() as _)
+        .is_success());
+    assert!(nvme.build_device_path(3).is_error());
+}
+
+#[test]
+fn scsi_commands_are_built_and_parsed() {
+    assert_eq!(EfiScsiCdb::inquiry(96).as_bytes(), [0x12, 0, 0, 0, 96, 0]);
+    assert_eq!(
+        EfiScsiCdb::read_capacity_10().as_bytes(),
+        [0x25, 0, 0, 0, 0, 0, 0, 0, 0, 0]
+    );
+    assert_eq!(
+        EfiScsiCdb::read_capacity_16(32).as_bytes(),
+        [0x9E, 0x10, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 32, 0, 0]
+    );
+    assert!(EfiScsiCdb::new(&[0; 17]).is_none());
+    assert_eq!(
+        EfiScsiCdb::new(&[0; 6]),
+        Some(EfiScsiCdb::test_unit_ready())
+    );
+
+    let mut inquiry: Vec<u8> = vec![0x05, 0x80, 0x05, 0x02, 31, 0, 0, 0];
+
+    inquiry.extend_from_slice(b"NAUTILOS");
+    inquiry.extend_from_slice(b"Virtual DVD     ");
+    inquiry.extend_from_slice(b"1.0 ");
+
+    let data: EfiScsiInquiryData = EfiScsiInquiryData::parse(&inquiry).unwrap();
+
+    assert!(EfiScsiInquiryData::parse(&inquiry[..35]).is_none());
+    assert_eq!(data.peripheral_qualifier(), 0);
+    assert_eq!(
+        data.peripheral_device_type(),
+        EfiScsiInquiryData::CD_DVD_DEVICE
+    );
+    assert!(data.is_removable());
+    assert_eq!(
+        (
+            data.vendor_identification(),
+            data.product_identification(),
+            data.product_revision_level()
+        ),
+        ("NAUTILOS", "Virtual DVD", "1.0")
+    );
+
+    let capacity: EfiScsiReadCapacity =
+        EfiScsiReadCapacity::parse_10(&[0x00, 0x0F, 0xFF, 0xFF, 0x00, 0x00, 0x02, 0x00]).unwrap();
+
+    assert_eq!(
+        (capacity.last_lba(), capacity.block_length()),
+        (0xF_FFFF, 512)
+    );
+    assert_eq!(capacity.capacity(), 512 << 20);
+
+    let mut data: [u8; 32] = [0; 32];
+
+    data[..8].copy_from_slice(&0x1_0000_0000_u64.to_be_bytes());
+    data[8..12].copy_from_slice(&4096_u32.to_be_bytes());
+
+    let capacity: EfiScsiReadCapacity = EfiScsiReadCapacity::parse_16(&data).unwrap();
+
+    assert_eq!(capacity.last_lba(), 0x1_0000_0000);
+    assert_eq!(capacity.capacity(), (0x1_0000_0001_u128) * 4096);
+    assert!(EfiScsiReadCapacity::parse_16(&data[..11]).is_none());
+}
+
+/// Fletcher's 64-bit checksum, as used by the NVDIMM labels.
+fn fletcher64(data: &[u8]) -> u64 {
+    let (mut low, mut high): (u32, u32) = (0, 0);
+
+    for word in data.chunks(4) {
+        low = low.wrapping_add(u32::from_le_bytes([word[0], word[1], word[2], word[3]]));
+        high = high.wrapping_add(low);
+    }
+
+    u64::from(high) << 32 | u64::from(low)
+}
+
+/// Slot, UUID, flags, number of labels, position and DPA of a namespace label.
+type NvdimmLabelFields = (u32, [u8; 16], u32, u16, u16, u64);
+
+/// Builds a label storage area with two index blocks, the second one being current, and four 256 byte slots.
+fn nvdimm_label_area(free: [u8; 2], labels: &[NvdimmLabelFields]) -> Vec<u8> {
+    let mut area: Vec<u8> = vec![0; 512 + 4 * 256];
+
+    for (index, &free) in free.iter().enumerate() {
+        let block: &mut [u8] = &mut area[index * 256..][..256];
+
+        block[..16].copy_from_slice(b"NAMESPACE_INDEX\0");
+        block[19] = 1;
+        block[20..24].copy_from_slice(&(index as u32 + 1).to_le_bytes());
+        block[24..32].copy_from_slice(&(index as u64 * 256).to_le_bytes());
+        block[32..40].copy_from_slice(&256u64.to_le_bytes());
+        block[40..48].copy_from_slice(&((1 - index) as u64 * 256).to_le_bytes());
+        block[48..56].copy_from_slice(&512u64.to_le_bytes());
+        block[56..60].copy_from_slice(&4u32.to_le_bytes());
+        block[60..64].copy_from_slice(&[1, 0, 2, 0]);
+        block[72] = free;
+
+        let checksum: u64 = fletcher64(block);
+
+        block[64..72].copy_from_slice(&checksum.to_le_bytes());
+    }
+
+    for &(slot, uuid, flags, n_label, position, dpa) in labels {
+        let label: &mut [u8] = &mut area[512 + slot as usize * 256..][..256];
+
+        label[..16].copy_from_slice(&uuid);
+        label[16..21].copy_from_slice(b"pmem0");
+        label[80..84].copy_from_slice(&flags.to_le_bytes());
+        label[84..86].copy_from_slice(&n_label.to_le_bytes());
+        label[86..88].copy_from_slice(&position.to_le_bytes());
+        label[88..96].copy_from_slice(&0x1122_3344_5566_7788u64.to_le_bytes());
+        label[104..112].copy_from_slice(&dpa.to_le_bytes());
+        label[112..120].copy_from_slice(&0x10_0000u64.to_le_bytes());
+        label[120..124].copy_from_slice(&slot.to_le_bytes());
+
+        let checksum: u64 = fletcher64(label);
+
+        label[248..256].copy_from_slice(&checksum.to_le_bytes());
+    }
+
+    area
+}
+
+#[test]
+fn nvdimm_labels_describe_namespaces() {
+    const UUID: [u8; 16] = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16];
+
+    /* The first index block marks every slot as free, but it's superseded by the second one */
+    let area: Vec<u8> = nvdimm_label_area(
+        [0x0F, 0x0A],
+        &[
+            (0, UUID, 0, 2, 1, 0x2000),
+            (
+                2,
+                [0xAA; 16],
+                EfiNvdimmNamespaceLabel::FLAGS_ROLABEL,
+                1,
+                0,
+                0x20_2000,
+            ),
+            (3, [0xBB; 16], 0, 1, 0, 0x40_2000),
+        ],
+    );
+
+    let simulator: Simulator = Simulator::builder()
+        .nvdimm(SimulatedNvdimm::new(0x0000_1001, &area).max_transfer_length(128))
+        .build()
+        .unwrap();
+    let boot_services = simulator.boot_services();
+    let handle: EfiHandle = simulator.nvdimm_handle(0).unwrap();
+
+    let protocol: &EfiNvdimmLabelProtocol = boot_services
+        .handle_protocol::<EfiNvdimmLabelProtocol>(handle)
+        .unwrap()
+        .unfold()
+        .ok()
+        .unwrap()
+        .1
+        .unwrap();
+
+    let information: EfiNvdimmLabelStorageInformation =
+        protocol.label_storage_information().unfold().unwrap().1;
+
+    assert_eq!(information.size_of_label_storage_area(), area.len() as u32);
+    assert_eq!(information.max_transfer_length(), 128);
+
+    /* Transfers are limited to the maximum length and the area's bounds */
+    assert!(matches!(
+        protocol.label_storage_read(0, &mut [0; 256]),
+        EfiStatusEnum::Error(EfiStatusError::EfiBadBufferSize, ())
+    ));
+    assert!(matches!(
+        protocol.label_storage_read(area.len() as u32 - 64, &mut [0; 128]),
+        EfiStatusEnum::Error(EfiStatusError::EfiInvalidParameter, ())
+    ));
+
+    let read: Vec<u8> = protocol.read_label_storage_area().unfold().unwrap().1;
+
+    assert_eq!(read, area);
+
+    let labels: EfiNvdimmLabelArea = EfiNvdimmLabelArea::parse(&read).unwrap();
+
+    assert_eq!(labels.index().seq(), 2);
+    assert_eq!(labels.index().label_size(), 256);
+    assert_eq!(labels.index().version(), (1, 2));
+
+    /* Slot 3 is marked as free, thus ignored */
+    let labels: Vec<EfiNvdimmNamespaceLabel> = labels.labels().collect();
+
+    assert_eq!(labels.len(), 2);
+    assert_eq!(
+        labels[0].uuid(),
+        EfiGuid::from_tuple((0x0403_0201, 0x0605, 0x0807, [9, 10, 11, 12, 13, 14, 15, 16]))
+    );
+    assert_eq!(labels[0].name(), b"pmem0");
+    assert_eq!((labels[0].n_label(), labels[0].position()), (2, 1));
+    assert_eq!(labels[0].set_cookie(), 0x1122_3344_5566_7788);
+    assert_eq!((labels[0].dpa(), labels[0].raw_size()), (0x2000, 0x10_0000));
+    assert!(!labels[0].contains(EfiNvdimmNamespaceLabel::FLAGS_ROLABEL));
+    assert_eq!(labels[1].slot(), 2);
+    assert!(labels[1].contains(EfiNvdimmNamespaceLabel::FLAGS_ROLABEL));
+
+    /* A corrupted label is skipped */
+    protocol
+        .label_storage_write(512 + 100, &[0xFF])
+        .unfold()
+        .unwrap();
+
+    let corrupted: Vec<u8> = simulator.nvdimm_label_storage(0).unwrap();
+
+    assert_eq!(corrupted[512 + 100], 0xFF);
+    assert_eq!(
+        EfiNvdimmLabelArea::parse(&corrupted)
+            .unwrap()
+            .labels()
+            .map(|label: EfiNvdimmNamespaceLabel| label.slot())
+            .collect::<Vec<u32>>(),
+        [2]
+    );
+
+    /* Changes both index blocks and updates their checksums */
+    let reindex = |change: &dyn Fn(&mut [u8])| -> Vec<u8> {
+        let mut area: Vec<u8> = area.clone();
+
+        for block in area[..512].chunks_mut(256) {
+            change(block);
+            block[64..72].fill(0);
+
+            let checksum: u64 = fletcher64(block);
+
+            block[64..72].copy_from_slice(&checksum.to_le_bytes());
+        }
+
+        area
+    };
+
+    /* Labels are either 128 or 256 bytes big */
+    assert!(EfiNvdimmLabelArea::parse(&reindex(&|block: &mut [u8]| block[19] = 2)).is_none());
+
+    /* Slots past the end of the address space aren't read */
+    assert_eq!(
+        EfiNvdimmLabelArea::parse(&reindex(&|block: &mut [u8]| {
+            block[48..56].copy_from_slice(&(u64::MAX - 255).to_le_bytes())
+        }))
+        .unwrap()
+        .labels()
+        .count(),
+        0
+    );
+
+    /* The device path leads to the NVDIMM's NFIT device handle */
+    let device_path: EfiDevicePathProtocolRaw = boot_services
+        .handle_protocol::<EfiDevicePathProtocolRaw>(handle)
+        .unwrap()
+        .unfold()
+        .ok()
+        .unwrap()
+        .1
+        .unwrap();
+    let device_handle: NVDIMMDeviceHandle =
+        unsafe { device_path.nvdimm_device_handle::<NVDIMMDeviceHandle>() }.unwrap();
+
+    assert_eq!(device_handle.raw(), 0x0000_1001);
+
+    /* The NFIT maps the label's part of the NVDIMM to a persistent memory range */
+    const NFIT_LENGTH: u32 = 36 + 4 + 56 + 48;
+
+    let mut table: Vec<u8> = vec![0; NFIT_LENGTH as usize];
+
+    table[..4].copy_from_slice(&Nfit::SIGNATURE);
+    table[4..8].copy_from_slice(&NFIT_LENGTH.to_le_bytes());
+
+    let range: &mut [u8] = &mut table[40..96];
+
+    range[2..4].copy_from_slice(&56u16.to_le_bytes());
+    range[4..6].copy_from_slice(&1u16.to_le_bytes());
+    range[16..32].copy_from_slice(&NFIT_PERSISTENT_MEMORY_REGION);
+    range[32..40].copy_from_slice(&0x1_0000_0000u64.to_le_bytes());
+    range[40..48].copy_from_slice(&0x4000_0000u64.to_le_bytes());
+
+    let mapping: &mut [u8] = &mut table[96..];
+
+    mapping[..2].copy_from_slice(&1u16.to_le_bytes());
+    mapping[2..4].copy_from_slice(&48u16.to_le_bytes());
+    mapping[4..8].copy_from_slice(&0x0000_1001u32.to_le_bytes());
+    mapping[12..14].copy_from_slice(&1u16.to_le_bytes());
+    mapping[16..24].copy_from_slice(&0x4000_0000u64.to_le_bytes());
+
+    let checksum: u8 = table
+        .iter()
+        .fold(0, |sum: u8, &byte: &u8| sum.wrapping_sub(byte));
+
+    table[9] = checksum;
+
+    let nfit: Nfit = Nfit::parse(SdtHeader::parse(&table).unwrap()).unwrap();
+    let mappings: Vec<RegionMapping> = nfit.region_mappings(device_handle).collect();
+
+    assert_eq!(mappings.len(), 1);
+    assert_eq!(mappings[0].region_size(), 0x4000_0000);
+
+    let range: SpaRange = nfit.spa_range(mappings[0].spa_range_index()).unwrap();
+
+    assert!(range.is_persistent_memory());
+    assert_eq!(range.base() + labels[0].dpa(), 0x1_0000_2000);
+    assert!(nfit
+        .region_mappings(NVDIMMDeviceHandle::new(0x0000_1002))
+        .next()
+        .is_none());
+}
+
+#[test]
+fn ram_disks_are_exposed_as_block_devices() {
+    let simulator: Simulator = Simulator::builder().ram_disk().build().unwrap();
+    let boot_services = simulator.boot_services();
+
+    let ram_disk: &EfiRamDiskProtocol = boot_services
+        .revision_1_1()
+        .unwrap()
+        .locate_protocol::<EfiRamDiskProtocol>(None)
+        .unwrap()
+        .unfold()
+        .ok()
+        .unwrap()
+        .1
+        .unwrap();
+
+    /* ISO 9660 image, with the primary volume descriptor in the 17th block */
+    let mut image: Vec<u8> = vec![0; 20 * 2048];
+
+    image[0x8000..0x8006].copy_from_slice(b"\x01CD001");
+
+    let base: u64 = image.as_ptr() as u64;
+    let size: u64 = image.len() as u64;
+
+    let device_path: EfiDevicePathProtocolRaw =
+        unsafe { ram_disk.register(base, size, &guids::EFI_VIRTUAL_CD_GUID, None) }
+            .unwrap()
+            .unfold()
+            .unwrap()
+            .1;
+    let node: EfiRamDiskDevicePathNode = unsafe { device_path.ram_disk() }.unwrap();
+
+    assert_eq!(node.starting_address(), base);
+    assert_eq!(node.ending_address(), base + size - 1);
+    assert_eq!(node.disk_type(), guids::EFI_VIRTUAL_CD_GUID);
+    assert_eq!(node.instance(), 0);
+    assert_eq!(simulator.ram_disk_count(), 1);
+
+    /* The same memory can't be registered twice, nor as an unknown type */
+    assert!(matches!(
+        unsafe { ram_disk.register(base, size, &guids::EFI_VIRTUAL_DISK_GUID, None) },
+        Ok(EfiStatusEnum::Error(EfiStatusError::EfiAlreadyStarted, ()))
+    ));
+    assert!(matches!(
+        unsafe { ram_disk.register(base + 2048, 2048, &VENDOR_GUID, None) },
+        Ok(EfiStatusEnum::Error(EfiStatusError::EfiUnsupported, ()))
+    ));
+
+    let handle: EfiHandle = boot_services
+        .locate_device_path(
+            &guids::EFI_BLOCK_IO_PROTOCOL,
+            &mut EfiDevicePathProtocolRaw::new(*device_path),
+        )
+        .unfold()
+        .unwrap()
+        .1;
+
+    let block_io: &EfiBlockIOProtocol = boot_services
+        .handle_protocol::<EfiBlockIOProtocol>(handle)
+        .unwrap()
+        .unfold()
+        .ok()
+        .unwrap()
+        .1
+        .unwrap();
+
+    assert_eq!(block_io.media_revision_1().block_size(), 2048);
+    assert_eq!(block_io.media_revision_1().last_block(), 19);
+    assert!(block_io.media_revision_1().read_only());
+
+    let mut buffer: Vec<u8> = vec![0; 2048];
+
+    assert!(block_io.read_blocks(0, 16, &mut buffer).is_success());
+    assert_eq!(&buffer[1..6], b"CD001");
+    assert!(block_io.write_blocks(0, 16, &buffer).is_error());
+
+    /* Virtual disks are writable, with writes landing in the registered memory */
+    let mut disk: Vec<u8> = vec![0; 4 * 512];
+
+    let disk_path: EfiDevicePathProtocolRaw = unsafe {
+        ram_disk.register(
+            disk.as_mut_ptr() as u64,
+            disk.len() as u64,
+            &guids::EFI_VIRTUAL_DISK_GUID,
+            Some(&device_path),
+        )
+    }
+    .unwrap()
+    .unfold()
+    .unwrap()
+    .1;
+
+    /* The parent's nodes come first, so the new RAM disk node is the last one */
+    assert_eq!(unsafe { disk_path.nodes() }.count(), 2);
+    assert_eq!(
+        unsafe { disk_path.ram_disk() }.unwrap().disk_type(),
+        guids::EFI_VIRTUAL_DISK_GUID
+    );
+
+    let disk_handle: EfiHandle = boot_services
+        .locate_device_path(
+            &guids::EFI_DISK_IO_PROTOCOL,
+            &mut EfiDevicePathProtocolRaw::new(*disk_path),
+        )
+        .unfold()
+        .unwrap()
+        .1;
+    let disk_io: &EfiDiskIOProtocol = boot_services
+        .handle_protocol::<EfiDiskIOProtocol>(disk_handle)
+        .unwrap()
+        .unfold()
+        .ok()
+        .unwrap()
+        .1
+        .unwrap();
+
+    assert!(disk_io.write_disk(0, 510, &[0xAA, 0x55]).is_success());
+    assert_eq!(disk[510..512], [0xAA, 0x55]);
+
+    /* Unknown device paths aren't unregistered */
+    let mut end_node: [u8; 4] = [0x7F, 0xFF, 0x04, 0x00];
+
+    assert!(matches!(
+        ram_disk.unregister(&EfiDevicePathProtocolRaw::new(
+            NonNullVoidPtr::new(end_node.as_mut_ptr().cast()).unwrap()
+        )),
+        EfiStatusEnum::Error(EfiStatusError::EfiNotFound, ())
+    ));
+    assert!(ram_disk.unregister(&device_path).is_success());
+    assert!(ram_disk.unregister(&disk_path).is_success());
+    assert_eq!(simulator.ram_disk_count(), 0);
+
+    /* The returned device paths are allocated from pool for the caller to free */
+    assert!(boot_services
+        .free_pool(device_path.as_ptr() as _)
+        .is_success());
+    assert!(boot_services
+        .free_pool(disk_path.as_ptr() as _)
+        .is_success());
+    assert!(matches!(
+        boot_services.handle_protocol::<EfiBlockIOProtocol>(handle),
+        Err(_) | Ok(EfiStatusEnum::Error(..))
+    ));
+}
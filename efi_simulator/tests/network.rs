@@ -0,0 +1,983 @@
+mod common;
+
+use {
+    common::utf16,
+    efi::{
+        boot_services::{
+            types::{event_and_timer::EfiEventType, task_priority::EfiTaskPriorityLevel},
+            EfiBootServices,
+        },
+        protocols::{
+            network::{
+                common::structs::{
+                    EfiFragmentData, EfiIPAddress, EfiIPv4AddressRaw, EfiIPv6AddressRaw,
+                    EfiMacAddress,
+                },
+                EfiHttpAccessPoint, EfiHttpConfigData, EfiHttpHeader, EfiHttpMessage,
+                EfiHttpMethod, EfiHttpProtocol, EfiHttpRequestData, EfiHttpResponseData,
+                EfiHttpToken, EfiHttpV4AccessPoint, EfiHttpV6AccessPoint, EfiHttpVersion,
+                EfiPxeBaseCodeDhcpV4Packet, EfiPxeBaseCodeMode, EfiPxeBaseCodeProtocol,
+                EfiPxeBaseCodeTftpError, EfiTcp4AccessPoint, EfiTcp4CloseToken, EfiTcp4ConfigData,
+                EfiTcp4ConnectionState, EfiTcp4ConnectionToken, EfiTcp4IOToken, EfiTcp4ListenToken,
+                EfiTcp4Protocol, EfiTcp4ReceiveData, EfiTcp4TransmitData, EfiUdp4CompletionToken,
+                EfiUdp4ConfigData, EfiUdp4Protocol, EfiUdp4ReceiveData, EfiUdp4SessionData,
+                EfiUdp4TransmitData,
+            },
+            service_binding::{EfiServiceBinding, EfiServiceBindingProtocol, EfiServiceChild},
+        },
+        EfiEvent, EfiHandle, EfiStatusEnum, EfiStatusError,
+    },
+    efi_simulator::{SimulatedNetwork, SimulatedPxeServer, Simulator},
+};
+
+const SERVER_IP: [u8; 4] = [192, 168, 1, 1];
+const STATION_IP: [u8; 4] = [192, 168, 1, 20];
+const ECHO_HOST: [u8; 4] = [192, 168, 1, 7];
+const BOOT_URL: &str = "http://192.168.1.1/boot/loader.efi";
+
+fn ip(address: [u8; 4]) -> EfiIPAddress {
+    EfiIPAddress::IPv4(EfiIPv4AddressRaw::new(address))
+}
+
+fn raw(address: [u8; 4]) -> EfiIPv4AddressRaw {
+    EfiIPv4AddressRaw::new(address)
+}
+
+fn event(boot_services: &EfiBootServices) -> EfiEvent {
+    match boot_services.create_event(EfiEventType::None, EfiTaskPriorityLevel::APPLICATION, None) {
+        EfiStatusEnum::Success(event) => event,
+        _ => panic!("Creating the event failed!"),
+    }
+}
+
+fn pxe_simulator() -> Simulator {
+    Simulator::builder()
+        .pxe(
+            SimulatedPxeServer::new(SERVER_IP, [192, 168, 1, 20], [255, 255, 255, 0])
+                .boot_file("boot/loader.efi")
+                .file("boot/loader.efi", b"MZ loader image")
+                .file("boot/config", b"timeout=5"),
+        )
+        .build()
+        .unwrap()
+}
+
+fn pxe_base_code(simulator: &Simulator) -> &'static EfiPxeBaseCodeProtocol {
+    let boot_services: &EfiBootServices = simulator.boot_services();
+
+    boot_services
+        .handle_protocol::<EfiPxeBaseCodeProtocol>(simulator.pxe_handle().unwrap())
+        .unwrap()
+        .unfold()
+        .ok()
+        .unwrap()
+        .1
+        .unwrap()
+}
+
+#[test]
+fn pxe_dhcp_configures_the_station() {
+    let simulator: Simulator = pxe_simulator();
+    let pxe: &EfiPxeBaseCodeProtocol = pxe_base_code(&simulator);
+    let mode: &EfiPxeBaseCodeMode = pxe.mode();
+
+    assert_eq!(
+        pxe.dhcp(false),
+        EfiStatusEnum::Error(EfiStatusError::EfiNotStarted, ())
+    );
+    assert!(!mode.started());
+
+    assert_eq!(
+        pxe.start(true),
+        EfiStatusEnum::Error(EfiStatusError::EfiUnsupported, ())
+    );
+    assert!(pxe.start(false).is_success());
+    assert_eq!(
+        pxe.start(false),
+        EfiStatusEnum::Error(EfiStatusError::EfiAlreadyStarted, ())
+    );
+    assert!(mode.started() && !mode.using_ip_v6() && mode.auto_arp());
+    assert!(mode.dhcp_ack().is_none());
+
+    assert!(pxe.dhcp(false).is_success());
+
+    assert_eq!(mode.station_ip().to_string(), "192.168.1.20");
+    assert_eq!(mode.subnet_mask().to_string(), "255.255.255.0");
+
+    let discover: &EfiPxeBaseCodeDhcpV4Packet = mode.dhcp_discover().unwrap().dhcp_v4();
+    let ack: &EfiPxeBaseCodeDhcpV4Packet = mode.dhcp_ack().unwrap().dhcp_v4();
+
+    assert_eq!((discover.opcode(), ack.opcode()), (1, 2));
+    assert_eq!(discover.transaction_id(), ack.transaction_id());
+    assert_eq!(ack.hw_address(), discover.hw_address());
+    assert_eq!(ack.hw_address().len(), 6);
+    assert_eq!(*ack.your_ip(), [192, 168, 1, 20]);
+    assert_eq!(*ack.next_server(), SERVER_IP);
+    assert_eq!(ack.boot_file(), b"boot/loader.efi");
+    assert_eq!(ack.magic(), 0x6382_5363);
+    assert_eq!(&ack.options()[..3], &[53, 1, 5]);
+    assert!(mode.proxy_offer().is_none() && mode.pxe_reply().is_none());
+
+    assert!(pxe
+        .set_station_ip(Some(ip([10, 0, 0, 2])), None)
+        .is_success());
+    assert_eq!(mode.station_ip().to_string(), "10.0.0.2");
+    assert_eq!(mode.subnet_mask().to_string(), "255.255.255.0");
+    assert_eq!(
+        pxe.set_station_ip(None, Some(ip([255, 0, 255, 0]))),
+        EfiStatusEnum::Error(EfiStatusError::EfiInvalidParameter, ())
+    );
+
+    assert!(pxe.stop().is_success());
+    assert!(!mode.started() && mode.dhcp_ack().is_none());
+    assert_eq!(
+        pxe.stop(),
+        EfiStatusEnum::Error(EfiStatusError::EfiNotStarted, ())
+    );
+}
+
+#[test]
+fn pxe_tftp_downloads_files() {
+    let simulator: Simulator = pxe_simulator();
+    let pxe: &EfiPxeBaseCodeProtocol = pxe_base_code(&simulator);
+
+    assert!(pxe.start(false).is_success());
+    assert!(pxe.dhcp(false).is_success());
+
+    let boot_file: Vec<u8> = [pxe.mode().dhcp_ack().unwrap().dhcp_v4().boot_file(), b"\0"].concat();
+
+    assert_eq!(
+        pxe.get_file_size(ip(SERVER_IP), &boot_file, None),
+        EfiStatusEnum::Success(15)
+    );
+
+    let mut small: [u8; 4] = [0; 4];
+
+    assert_eq!(
+        pxe.read_file(ip(SERVER_IP), &boot_file, &mut small, None),
+        EfiStatusEnum::Error(EfiStatusError::EfiBufferTooSmall, 15)
+    );
+
+    let mut buffer: [u8; 32] = [0; 32];
+
+    assert_eq!(
+        pxe.read_file(ip(SERVER_IP), b"boot/config\0", &mut buffer, None),
+        EfiStatusEnum::Success(9)
+    );
+    assert_eq!(&buffer[..9], b"timeout=5");
+
+    /* File names have to be null-terminated */
+    assert_eq!(
+        pxe.read_file(ip(SERVER_IP), b"boot/config", &mut buffer, None),
+        EfiStatusEnum::Error(EfiStatusError::EfiInvalidParameter, 0)
+    );
+
+    assert!(pxe.mode().tftp_error().is_none());
+    assert_eq!(
+        pxe.read_file(ip(SERVER_IP), b"missing\0", &mut buffer, None),
+        EfiStatusEnum::Error(EfiStatusError::EfiTftpError, 32)
+    );
+
+    let tftp_error: &EfiPxeBaseCodeTftpError = pxe.mode().tftp_error().unwrap();
+
+    assert_eq!(tftp_error.error_code(), 1);
+    assert_eq!(tftp_error.error_string(), b"File not found");
+
+    /* Nothing answers at other addresses */
+    assert_eq!(
+        pxe.get_file_size(ip([192, 168, 1, 2]), &boot_file, None),
+        EfiStatusEnum::Error(EfiStatusError::EfiTimeout, ())
+    );
+    assert_eq!(
+        pxe.read_directory(ip(SERVER_IP), b"boot\0", &mut buffer, None),
+        EfiStatusEnum::Error(EfiStatusError::EfiUnsupported, 32)
+    );
+}
+
+#[test]
+fn pxe_arp_resolves_the_server() {
+    let simulator: Simulator = pxe_simulator();
+    let pxe: &EfiPxeBaseCodeProtocol = pxe_base_code(&simulator);
+
+    assert!(matches!(
+        pxe.arp(ip(SERVER_IP)),
+        EfiStatusEnum::Error(EfiStatusError::EfiNotStarted, _)
+    ));
+    assert!(pxe.start(false).is_success());
+
+    let mac: EfiMacAddress = match pxe.arp(ip(SERVER_IP)) {
+        EfiStatusEnum::Success(mac) => mac,
+        _ => panic!("Resolving the server's address failed!"),
+    };
+
+    assert_eq!(&mac[..6], &[0x52, 0x54, 0x00, 0x00, 0x00, 0x01]);
+    assert!(mac[6..].iter().all(|&byte: &u8| byte == 0));
+
+    /* Resolving again doesn't duplicate the cache entry */
+    assert!(pxe.arp(ip(SERVER_IP)).is_success());
+
+    let cache: Vec<(EfiIPAddress, EfiMacAddress)> = pxe.mode().arp_cache().collect();
+
+    assert_eq!(cache.len(), 1);
+    assert!(cache[0] == (ip(SERVER_IP), mac));
+
+    assert!(matches!(
+        pxe.arp(ip([192, 168, 1, 77])),
+        EfiStatusEnum::Error(EfiStatusError::EfiTimeout, _)
+    ));
+}
+
+#[test]
+fn pxe_is_only_provided_with_a_server() {
+    let simulator: Simulator = Simulator::builder().build().unwrap();
+
+    assert_eq!(simulator.pxe_handle(), None);
+    assert!(matches!(
+        simulator
+            .boot_services()
+            .revision_1_1()
+            .unwrap()
+            .locate_protocol::<EfiPxeBaseCodeProtocol>(None),
+        Ok(EfiStatusEnum::Error(EfiStatusError::EfiNotFound, ()))
+    ));
+
+    assert!(Simulator::builder()
+        .pxe(
+            SimulatedPxeServer::new(SERVER_IP, [192, 168, 1, 20], [255, 255, 255, 0])
+                .boot_file(&"a".repeat(128))
+        )
+        .build()
+        .is_err());
+}
+
+fn network_simulator() -> Simulator {
+    Simulator::builder()
+        .network(
+            SimulatedNetwork::new(STATION_IP, [255, 255, 255, 0])
+                .echo_host(ECHO_HOST)
+                .http_resource(BOOT_URL, b"MZ loader image"),
+        )
+        .build()
+        .unwrap()
+}
+
+fn service_binding<T: EfiServiceBinding>(
+    simulator: &Simulator,
+) -> &'static EfiServiceBindingProtocol<T> {
+    simulator
+        .boot_services()
+        .handle_protocol::<EfiServiceBindingProtocol<T>>(simulator.network_handle().unwrap())
+        .unwrap()
+        .unfold()
+        .ok()
+        .unwrap()
+        .1
+        .unwrap()
+}
+
+fn open_child<T: EfiServiceBinding>(simulator: &Simulator) -> EfiServiceChild<'static, T> {
+    service_binding::<T>(simulator)
+        .open_child(
+            simulator.boot_services().revision_1_1().unwrap(),
+            simulator.network_handle().unwrap(),
+        )
+        .unwrap()
+        .unfold()
+        .ok()
+        .unwrap()
+        .1
+}
+
+#[test]
+fn udp4_datagrams_are_echoed() {
+    let simulator: Simulator = network_simulator();
+    let boot_services: &EfiBootServices = simulator.boot_services();
+    let udp4: EfiServiceChild<EfiUdp4Protocol> = open_child(&simulator);
+
+    assert!(matches!(
+        udp4.get_mode_data(),
+        EfiStatusEnum::Error(EfiStatusError::EfiNotStarted, ())
+    ));
+    assert_eq!(
+        udp4.poll(),
+        EfiStatusEnum::Error(EfiStatusError::EfiNotStarted, ())
+    );
+
+    let config_data: EfiUdp4ConfigData = EfiUdp4ConfigData::new_default(0, raw(ECHO_HOST), 7);
+
+    assert!(udp4.configure(Some(&config_data)).is_success());
+    assert_eq!(
+        udp4.configure(Some(&config_data)),
+        EfiStatusEnum::Error(EfiStatusError::EfiAlreadyStarted, ())
+    );
+
+    let mode_data: EfiUdp4ConfigData = match udp4.get_mode_data() {
+        EfiStatusEnum::Success(mode_data) => mode_data,
+        _ => panic!("Reading the configuration failed!"),
+    };
+
+    /* The default address comes from the network, while port 0 picks an ephemeral port */
+    assert_eq!(*mode_data.station_address(), STATION_IP);
+    assert_eq!(*mode_data.subnet_mask(), [255, 255, 255, 0]);
+    assert_eq!(mode_data.station_port(), 49152);
+    assert_eq!(
+        (*mode_data.remote_address(), mode_data.remote_port()),
+        (ECHO_HOST, 7)
+    );
+
+    let mut receive_token: EfiUdp4CompletionToken =
+        EfiUdp4CompletionToken::new_receive(event(boot_services));
+
+    assert!(unsafe { udp4.receive(&mut receive_token) }.is_success());
+    assert_eq!(
+        unsafe { udp4.receive(&mut receive_token) },
+        EfiStatusEnum::Error(EfiStatusError::EfiAccessDenied, ())
+    );
+    assert!(boot_services.check_event(receive_token.event()).is_error());
+
+    let transmit_data: EfiUdp4TransmitData<2> = EfiUdp4TransmitData::new(
+        None,
+        None,
+        [
+            EfiFragmentData::new_transmit(b"pi"),
+            EfiFragmentData::new_transmit(b"ng"),
+        ],
+    );
+    let mut transmit_token: EfiUdp4CompletionToken =
+        EfiUdp4CompletionToken::new_transmit(event(boot_services), &transmit_data);
+
+    assert!(unsafe { udp4.transmit(&mut transmit_token) }.is_success());
+    assert!(boot_services
+        .check_event(transmit_token.event())
+        .is_success());
+    assert!(transmit_token.status().is_success());
+
+    /* The echo host sends the datagram back */
+    assert!(boot_services
+        .check_event(receive_token.event())
+        .is_success());
+    assert!(receive_token.status().is_success());
+
+    let receive_data: &EfiUdp4ReceiveData = receive_token.receive_data().unwrap();
+    let session_data: &EfiUdp4SessionData = receive_data.session_data();
+
+    assert_eq!(
+        (*session_data.source_address(), session_data.source_port()),
+        (ECHO_HOST, 7)
+    );
+    assert_eq!(
+        (
+            *session_data.destination_address(),
+            session_data.destination_port()
+        ),
+        (STATION_IP, 49152)
+    );
+    assert_eq!(receive_data.data_length(), 4);
+    assert_eq!(receive_data.fragments().len(), 1);
+    assert_eq!(receive_data.fragments()[0].data(), b"ping");
+
+    /* Signalling the recycle event releases the datagram and closes the event */
+    let recycle_signal: EfiEvent = receive_data.recycle_signal();
+
+    assert!(boot_services.signal_event(recycle_signal).is_success());
+    assert!(boot_services.signal_event(recycle_signal).is_error());
+
+    /* Datagrams to other hosts are lost */
+    let session_data: EfiUdp4SessionData =
+        EfiUdp4SessionData::new(raw(STATION_IP), 0, raw([192, 168, 1, 8]), 7);
+    let transmit_data: EfiUdp4TransmitData<1> = EfiUdp4TransmitData::new(
+        Some(&session_data),
+        None,
+        [EfiFragmentData::new_transmit(b"lost")],
+    );
+    let mut transmit_token: EfiUdp4CompletionToken =
+        EfiUdp4CompletionToken::new_transmit(event(boot_services), &transmit_data);
+    let mut receive_token: EfiUdp4CompletionToken =
+        EfiUdp4CompletionToken::new_receive(event(boot_services));
+
+    assert!(unsafe { udp4.receive(&mut receive_token) }.is_success());
+    assert!(unsafe { udp4.transmit(&mut transmit_token) }.is_success());
+    assert!(transmit_token.status().is_success());
+    assert!(boot_services.check_event(receive_token.event()).is_error());
+
+    assert!(udp4.cancel(Some(&mut receive_token)).is_success());
+    assert!(boot_services
+        .check_event(receive_token.event())
+        .is_success());
+    assert_eq!(
+        receive_token.status(),
+        EfiStatusEnum::Error(EfiStatusError::EfiAborted, ())
+    );
+    assert!(receive_token.receive_data().is_none());
+    assert_eq!(
+        udp4.cancel(Some(&mut receive_token)),
+        EfiStatusEnum::Error(EfiStatusError::EfiNotFound, ())
+    );
+}
+
+#[test]
+fn udp4_groups_and_routes() {
+    let simulator: Simulator = network_simulator();
+    let udp4: EfiServiceChild<EfiUdp4Protocol> = open_child(&simulator);
+
+    assert_eq!(
+        udp4.join_group(raw([224, 0, 0, 251])),
+        EfiStatusEnum::Error(EfiStatusError::EfiNotStarted, ())
+    );
+    assert!(udp4
+        .configure(Some(&EfiUdp4ConfigData::new(
+            raw([10, 0, 0, 2]),
+            raw([255, 0, 0, 0]),
+            68,
+            raw([0; 4]),
+            0,
+        )))
+        .is_success());
+
+    /* Only class D addresses are multicast groups */
+    assert_eq!(
+        udp4.join_group(raw([192, 168, 1, 255])),
+        EfiStatusEnum::Error(EfiStatusError::EfiInvalidParameter, ())
+    );
+    assert!(udp4.join_group(raw([224, 0, 0, 251])).is_success());
+    assert_eq!(
+        udp4.join_group(raw([224, 0, 0, 251])),
+        EfiStatusEnum::Error(EfiStatusError::EfiAlreadyStarted, ())
+    );
+    assert!(udp4.leave_group(Some(raw([224, 0, 0, 251]))).is_success());
+    assert_eq!(
+        udp4.leave_group(Some(raw([224, 0, 0, 251]))),
+        EfiStatusEnum::Error(EfiStatusError::EfiNotFound, ())
+    );
+    assert!(udp4.join_group(raw([239, 1, 1, 1])).is_success());
+    assert!(udp4.leave_group(None).is_success());
+    assert_eq!(
+        udp4.leave_group(Some(raw([239, 1, 1, 1]))),
+        EfiStatusEnum::Error(EfiStatusError::EfiNotFound, ())
+    );
+
+    let route: [EfiIPv4AddressRaw; 3] = [
+        raw([10, 1, 0, 0]),
+        raw([255, 255, 0, 0]),
+        raw([10, 0, 0, 1]),
+    ];
+
+    assert!(udp4.add_route(route[0], route[1], route[2]).is_success());
+    assert_eq!(
+        udp4.add_route(route[0], route[1], route[2]),
+        EfiStatusEnum::Error(EfiStatusError::EfiAccessDenied, ())
+    );
+    assert!(udp4.delete_route(route[0], route[1], route[2]).is_success());
+    assert_eq!(
+        udp4.delete_route(route[0], route[1], route[2]),
+        EfiStatusEnum::Error(EfiStatusError::EfiNotFound, ())
+    );
+
+    /* Without a remote address configured, transmissions need session data */
+    let transmit_data: EfiUdp4TransmitData<1> =
+        EfiUdp4TransmitData::new(None, None, [EfiFragmentData::new_transmit(b"data")]);
+    let mut transmit_token: EfiUdp4CompletionToken =
+        EfiUdp4CompletionToken::new_transmit(event(simulator.boot_services()), &transmit_data);
+
+    assert_eq!(
+        unsafe { udp4.transmit(&mut transmit_token) },
+        EfiStatusEnum::Error(EfiStatusError::EfiInvalidParameter, ())
+    );
+
+    let mode_data: EfiUdp4ConfigData = match udp4.get_mode_data() {
+        EfiStatusEnum::Success(mode_data) => mode_data,
+        _ => panic!("Reading the configuration failed!"),
+    };
+
+    assert!(!mode_data.use_default_address());
+    assert_eq!(
+        (*mode_data.station_address(), mode_data.station_port()),
+        ([10, 0, 0, 2], 68)
+    );
+
+    /* Resetting forgets the configuration */
+    assert!(udp4.configure(None).is_success());
+    assert!(matches!(
+        udp4.get_mode_data(),
+        EfiStatusEnum::Error(EfiStatusError::EfiNotStarted, ())
+    ));
+}
+
+#[test]
+fn tcp4_connections_echo_data() {
+    let simulator: Simulator = network_simulator();
+    let boot_services: &EfiBootServices = simulator.boot_services();
+    let tcp4: EfiServiceChild<EfiTcp4Protocol> = open_child(&simulator);
+
+    assert!(matches!(
+        tcp4.get_mode_data(),
+        EfiStatusEnum::Error(EfiStatusError::EfiNotStarted, ())
+    ));
+
+    /* Active instances need a remote address */
+    assert_eq!(
+        tcp4.configure(Some(&EfiTcp4ConfigData::new(
+            EfiTcp4AccessPoint::new_default(0, raw([0; 4]), 0, true)
+        ))),
+        EfiStatusEnum::Error(EfiStatusError::EfiInvalidParameter, ())
+    );
+
+    let config_data: EfiTcp4ConfigData =
+        EfiTcp4ConfigData::new(EfiTcp4AccessPoint::new_default(0, raw(ECHO_HOST), 7, true));
+
+    assert!(tcp4.configure(Some(&config_data)).is_success());
+    assert_eq!(
+        tcp4.configure(Some(&config_data)),
+        EfiStatusEnum::Error(EfiStatusError::EfiAccessDenied, ())
+    );
+
+    match tcp4.get_mode_data() {
+        EfiStatusEnum::Success((connection_state, mode_data)) => {
+            let access_point: &EfiTcp4AccessPoint = mode_data.access_point();
+
+            assert_eq!(connection_state, EfiTcp4ConnectionState::Closed);
+            assert_eq!(*access_point.station_address(), STATION_IP);
+            assert_eq!(access_point.station_port(), 49152);
+            assert!(access_point.active());
+        }
+        _ => panic!("Reading the configuration failed!"),
+    }
+
+    let mut connection_token: EfiTcp4ConnectionToken =
+        EfiTcp4ConnectionToken::new(event(boot_services));
+
+    assert!(unsafe { tcp4.connect(&mut connection_token) }.is_success());
+    assert!(boot_services
+        .check_event(connection_token.completion_token().event())
+        .is_success());
+    assert!(connection_token.completion_token().status().is_success());
+    assert!(matches!(
+        tcp4.get_mode_data(),
+        EfiStatusEnum::Success((EfiTcp4ConnectionState::Established, _))
+    ));
+    assert_eq!(
+        unsafe { tcp4.connect(&mut connection_token) },
+        EfiStatusEnum::Error(EfiStatusError::EfiAccessDenied, ())
+    );
+
+    let mut buffer: [u8; 8] = [0; 8];
+    let mut rest: [u8; 8] = [0; 8];
+    let mut receive_data: EfiTcp4ReceiveData<2> = EfiTcp4ReceiveData::new([
+        EfiFragmentData::new(&mut buffer),
+        EfiFragmentData::new(&mut rest),
+    ]);
+
+    {
+        let mut receive_token: EfiTcp4IOToken =
+            EfiTcp4IOToken::new_receive(event(boot_services), &mut receive_data);
+
+        assert!(unsafe { tcp4.receive(&mut receive_token) }.is_success());
+        assert!(boot_services
+            .check_event(receive_token.completion_token().event())
+            .is_error());
+
+        let mut transmit_data: EfiTcp4TransmitData<2> = EfiTcp4TransmitData::new(
+            true,
+            false,
+            [
+                EfiFragmentData::new_transmit(b"hello"),
+                EfiFragmentData::new_transmit(b" world"),
+            ],
+        );
+        let mut transmit_token: EfiTcp4IOToken =
+            EfiTcp4IOToken::new_transmit(event(boot_services), &mut transmit_data);
+
+        assert!(unsafe { tcp4.transmit(&mut transmit_token) }.is_success());
+        assert!(transmit_token.completion_token().status().is_success());
+
+        /* The echo host sends the data back into the queued buffers */
+        assert!(boot_services
+            .check_event(receive_token.completion_token().event())
+            .is_success());
+        assert!(receive_token.completion_token().status().is_success());
+    }
+
+    assert!(!receive_data.urgent());
+    assert_eq!(receive_data.data_length(), 11);
+    assert_eq!(receive_data.fragments()[0].data(), b"hello wo");
+    assert_eq!(receive_data.fragments()[1].data(), b"rld");
+
+    let mut close_token: EfiTcp4CloseToken = EfiTcp4CloseToken::new(event(boot_services), false);
+
+    assert!(unsafe { tcp4.close(&mut close_token) }.is_success());
+    assert!(close_token.completion_token().status().is_success());
+    assert!(matches!(
+        tcp4.get_mode_data(),
+        EfiStatusEnum::Success((EfiTcp4ConnectionState::Closed, _))
+    ));
+    assert_eq!(
+        unsafe { tcp4.close(&mut close_token) },
+        EfiStatusEnum::Error(EfiStatusError::EfiAccessDenied, ())
+    );
+
+    /* Nothing answers at other addresses */
+    let other: EfiServiceChild<EfiTcp4Protocol> = open_child(&simulator);
+
+    assert!(other
+        .configure(Some(&EfiTcp4ConfigData::new(
+            EfiTcp4AccessPoint::new_default(0, raw([192, 168, 1, 8]), 80, true)
+        )))
+        .is_success());
+
+    let mut connection_token: EfiTcp4ConnectionToken =
+        EfiTcp4ConnectionToken::new(event(boot_services));
+
+    assert!(unsafe { other.connect(&mut connection_token) }.is_success());
+    assert_eq!(
+        connection_token.completion_token().status(),
+        EfiStatusEnum::Error(EfiStatusError::EfiTimeout, ())
+    );
+    assert!(matches!(
+        other.get_mode_data(),
+        EfiStatusEnum::Success((EfiTcp4ConnectionState::Closed, _))
+    ));
+}
+
+#[test]
+fn tcp4_accept_waits_until_cancelled() {
+    let simulator: Simulator = network_simulator();
+    let boot_services: &EfiBootServices = simulator.boot_services();
+    let tcp4: EfiServiceChild<EfiTcp4Protocol> = open_child(&simulator);
+
+    /* Passive instances need a station port */
+    assert_eq!(
+        tcp4.configure(Some(&EfiTcp4ConfigData::new(
+            EfiTcp4AccessPoint::new_default(0, raw([0; 4]), 0, false)
+        ))),
+        EfiStatusEnum::Error(EfiStatusError::EfiInvalidParameter, ())
+    );
+    assert!(tcp4
+        .configure(Some(&EfiTcp4ConfigData::new(EfiTcp4AccessPoint::new(
+            raw([10, 0, 0, 2]),
+            raw([255, 0, 0, 0]),
+            80,
+            raw([0; 4]),
+            0,
+            false,
+        ))))
+        .is_success());
+    assert!(matches!(
+        tcp4.get_mode_data(),
+        EfiStatusEnum::Success((EfiTcp4ConnectionState::Listen, _))
+    ));
+
+    let mut connection_token: EfiTcp4ConnectionToken =
+        EfiTcp4ConnectionToken::new(event(boot_services));
+
+    assert_eq!(
+        unsafe { tcp4.connect(&mut connection_token) },
+        EfiStatusEnum::Error(EfiStatusError::EfiAccessDenied, ())
+    );
+
+    let mut listen_token: EfiTcp4ListenToken = EfiTcp4ListenToken::new(event(boot_services));
+
+    assert!(unsafe { tcp4.accept(&mut listen_token) }.is_success());
+    assert_eq!(
+        unsafe { tcp4.accept(&mut listen_token) },
+        EfiStatusEnum::Error(EfiStatusError::EfiAccessDenied, ())
+    );
+    assert!(boot_services
+        .check_event(listen_token.completion_token().event())
+        .is_error());
+
+    assert!(tcp4
+        .cancel(Some(listen_token.completion_token()))
+        .is_success());
+    assert!(boot_services
+        .check_event(listen_token.completion_token().event())
+        .is_success());
+    assert_eq!(
+        listen_token.completion_token().status(),
+        EfiStatusEnum::Error(EfiStatusError::EfiAborted, ())
+    );
+    assert!(listen_token.new_child_handle().is_null());
+    assert_eq!(
+        tcp4.cancel(Some(listen_token.completion_token())),
+        EfiStatusEnum::Error(EfiStatusError::EfiNotFound, ())
+    );
+
+    let route: [EfiIPv4AddressRaw; 3] = [
+        raw([10, 1, 0, 0]),
+        raw([255, 255, 0, 0]),
+        raw([10, 0, 0, 1]),
+    ];
+
+    assert!(tcp4.add_route(route[0], route[1], route[2]).is_success());
+    assert_eq!(
+        tcp4.add_route(route[0], route[1], route[2]),
+        EfiStatusEnum::Error(EfiStatusError::EfiAccessDenied, ())
+    );
+    assert!(tcp4.delete_route(route[0], route[1], route[2]).is_success());
+
+    /* Resetting aborts queued tokens */
+    let mut listen_token: EfiTcp4ListenToken = EfiTcp4ListenToken::new(event(boot_services));
+
+    assert!(unsafe { tcp4.accept(&mut listen_token) }.is_success());
+    assert!(tcp4.configure(None).is_success());
+    assert_eq!(
+        listen_token.completion_token().status(),
+        EfiStatusEnum::Error(EfiStatusError::EfiAborted, ())
+    );
+    assert_eq!(
+        tcp4.poll(),
+        EfiStatusEnum::Error(EfiStatusError::EfiNotStarted, ())
+    );
+}
+
+fn http_request(
+    boot_services: &EfiBootServices,
+    http: &EfiHttpProtocol,
+    method: EfiHttpMethod,
+    url: &str,
+) -> EfiStatusEnum {
+    let url: Vec<u16> = utf16(url);
+    let request_data: EfiHttpRequestData = EfiHttpRequestData::new(method, &url).unwrap();
+    let mut message: EfiHttpMessage = EfiHttpMessage::new_request(&request_data, &[], None);
+    let mut token: EfiHttpToken = EfiHttpToken::new(event(boot_services), &mut message);
+
+    match unsafe { http.request(&mut token) } {
+        EfiStatusEnum::Success(()) => {
+            assert!(boot_services.check_event(token.event()).is_success());
+
+            token.status()
+        }
+        status => status,
+    }
+}
+
+#[test]
+fn http_serves_resources() {
+    let simulator: Simulator = network_simulator();
+    let boot_services: &EfiBootServices = simulator.boot_services();
+    let http: EfiServiceChild<EfiHttpProtocol> = open_child(&simulator);
+
+    let mut access_point: EfiHttpV4AccessPoint = EfiHttpV4AccessPoint::new_default(0);
+
+    assert!(matches!(
+        http.get_mode_data(EfiHttpAccessPoint::IPv4(&mut access_point)),
+        EfiStatusEnum::Error(EfiStatusError::EfiNotStarted, ())
+    ));
+
+    /* Only IPv4 is simulated */
+    let mut v6_access_point: EfiHttpV6AccessPoint =
+        EfiHttpV6AccessPoint::new(EfiIPv6AddressRaw::from_segments([0; 8]), 0);
+
+    assert_eq!(
+        http.configure(Some(&EfiHttpConfigData::new(
+            EfiHttpVersion::Http11,
+            0,
+            EfiHttpAccessPoint::IPv6(&mut v6_access_point),
+        ))),
+        EfiStatusEnum::Error(EfiStatusError::EfiUnsupported, ())
+    );
+
+    let mut config_access_point: EfiHttpV4AccessPoint = EfiHttpV4AccessPoint::new_default(0);
+    let config_data: EfiHttpConfigData = EfiHttpConfigData::new(
+        EfiHttpVersion::Http11,
+        5_000,
+        EfiHttpAccessPoint::IPv4(&mut config_access_point),
+    );
+
+    assert!(http.configure(Some(&config_data)).is_success());
+    assert_eq!(
+        http.configure(Some(&config_data)),
+        EfiStatusEnum::Error(EfiStatusError::EfiAlreadyStarted, ())
+    );
+
+    match http.get_mode_data(EfiHttpAccessPoint::IPv4(&mut access_point)) {
+        EfiStatusEnum::Success(mode_data) => {
+            assert_eq!(mode_data.http_version(), EfiHttpVersion::Http11);
+            assert_eq!(mode_data.timeout_milliseconds(), 5_000);
+            assert!(!mode_data.local_address_is_ip_v6());
+        }
+        _ => panic!("Reading the configuration failed!"),
+    }
+
+    assert_eq!(*access_point.local_address(), STATION_IP);
+    assert_eq!(*access_point.local_subnet(), [255, 255, 255, 0]);
+
+    let mut response_data: EfiHttpResponseData = EfiHttpResponseData::new();
+    let mut body: [u8; 8] = [0; 8];
+
+    /* There's nothing to receive before a request */
+    {
+        let mut message: EfiHttpMessage =
+            EfiHttpMessage::new_response(Some(&mut response_data), &mut body);
+        let mut token: EfiHttpToken = EfiHttpToken::new(event(boot_services), &mut message);
+
+        assert_eq!(
+            unsafe { http.response(&mut token) },
+            EfiStatusEnum::Error(EfiStatusError::EfiAccessDenied, ())
+        );
+    }
+
+    assert!(http_request(boot_services, &http, EfiHttpMethod::Get, BOOT_URL).is_success());
+
+    /* The first response receives the status code and the headers */
+    {
+        let mut message: EfiHttpMessage =
+            EfiHttpMessage::new_response(Some(&mut response_data), &mut body);
+        let mut token: EfiHttpToken = EfiHttpToken::new(event(boot_services), &mut message);
+
+        assert!(unsafe { http.response(&mut token) }.is_success());
+        assert!(boot_services.check_event(token.event()).is_success());
+        assert!(token.status().is_success());
+
+        let headers: &[EfiHttpHeader] = token.message().headers();
+
+        assert_eq!(headers.len(), 1);
+        assert_eq!(headers[0].field_name(), b"Content-Length");
+        assert_eq!(headers[0].field_value(), b"15");
+        assert_eq!(token.message().body_length(), 8);
+        assert!(boot_services
+            .free_pool(token.message().headers_ptr())
+            .is_success());
+    }
+
+    assert_eq!(response_data.status_code(), Some(200));
+    assert_eq!(&body, b"MZ loade");
+
+    /* Later ones only receive what's left of the body */
+    let mut rest: [u8; 16] = [0; 16];
+
+    {
+        let mut message: EfiHttpMessage = EfiHttpMessage::new_response(None, &mut rest);
+        let mut token: EfiHttpToken = EfiHttpToken::new(event(boot_services), &mut message);
+
+        assert!(unsafe { http.response(&mut token) }.is_success());
+        assert!(token.message().headers().is_empty());
+        assert_eq!(token.message().body_length(), 7);
+    }
+
+    assert_eq!(&rest[..7], b"r image");
+
+    /* HEAD only receives the headers */
+    assert!(http_request(boot_services, &http, EfiHttpMethod::Head, BOOT_URL).is_success());
+
+    let mut response_data: EfiHttpResponseData = EfiHttpResponseData::new();
+
+    {
+        let mut message: EfiHttpMessage =
+            EfiHttpMessage::new_response(Some(&mut response_data), &mut body);
+        let mut token: EfiHttpToken = EfiHttpToken::new(event(boot_services), &mut message);
+
+        assert!(unsafe { http.response(&mut token) }.is_success());
+        assert_eq!(token.message().headers()[0].field_value(), b"15");
+        assert_eq!(token.message().body_length(), 0);
+        assert!(boot_services
+            .free_pool(token.message().headers_ptr())
+            .is_success());
+    }
+
+    assert_eq!(response_data.status_code(), Some(200));
+
+    assert!(http_request(
+        boot_services,
+        &http,
+        EfiHttpMethod::Get,
+        "http://192.168.1.1/missing"
+    )
+    .is_success());
+
+    let mut response_data: EfiHttpResponseData = EfiHttpResponseData::new();
+
+    {
+        let mut message: EfiHttpMessage =
+            EfiHttpMessage::new_response(Some(&mut response_data), &mut body);
+        let mut token: EfiHttpToken = EfiHttpToken::new(event(boot_services), &mut message);
+
+        assert!(unsafe { http.response(&mut token) }.is_success());
+        assert_eq!(token.message().headers()[0].field_value(), b"0");
+        assert!(boot_services
+            .free_pool(token.message().headers_ptr())
+            .is_success());
+    }
+
+    assert_eq!(response_data.status_code(), Some(404));
+
+    assert_eq!(
+        http_request(boot_services, &http, EfiHttpMethod::Post, BOOT_URL),
+        EfiStatusEnum::Error(EfiStatusError::EfiUnsupported, ())
+    );
+    assert!(http.cancel(None).is_success());
+    assert!(http.poll().is_success());
+
+    assert!(http.configure(None).is_success());
+    assert_eq!(
+        http_request(boot_services, &http, EfiHttpMethod::Get, BOOT_URL),
+        EfiStatusEnum::Error(EfiStatusError::EfiNotStarted, ())
+    );
+}
+
+#[test]
+fn network_service_bindings_manage_children() {
+    let simulator: Simulator = network_simulator();
+    let boot_services: &EfiBootServices = simulator.boot_services();
+    let udp4_binding: &EfiServiceBindingProtocol<EfiUdp4Protocol> = service_binding(&simulator);
+    let tcp4_binding: &EfiServiceBindingProtocol<EfiTcp4Protocol> = service_binding(&simulator);
+
+    let handle: EfiHandle = match udp4_binding.create_child(None) {
+        EfiStatusEnum::Success(handle) => handle,
+        _ => panic!("Creating the UDP4 child failed!"),
+    };
+
+    assert!(boot_services
+        .handle_protocol::<EfiUdp4Protocol>(handle)
+        .unwrap()
+        .is_success());
+
+    /* Children can share an existing handle */
+    assert_eq!(
+        tcp4_binding.create_child(Some(handle)),
+        EfiStatusEnum::Success(handle)
+    );
+    assert!(boot_services
+        .handle_protocol::<EfiTcp4Protocol>(handle)
+        .unwrap()
+        .is_success());
+
+    assert!(udp4_binding.destroy_child(handle).is_success());
+    assert!(matches!(
+        boot_services.handle_protocol::<EfiUdp4Protocol>(handle),
+        Err(_) | Ok(EfiStatusEnum::Error(..))
+    ));
+    assert_eq!(
+        udp4_binding.destroy_child(handle),
+        EfiStatusEnum::Error(EfiStatusError::EfiUnsupported, ())
+    );
+    assert!(tcp4_binding.destroy_child(handle).is_success());
+
+    /* Dropping an opened child destroys it */
+    let child: EfiHandle = {
+        let http: EfiServiceChild<EfiHttpProtocol> = open_child(&simulator);
+
+        assert!(http.poll().is_error());
+
+        http.handle()
+    };
+
+    assert_eq!(
+        service_binding::<EfiHttpProtocol>(&simulator).destroy_child(child),
+        EfiStatusEnum::Error(EfiStatusError::EfiUnsupported, ())
+    );
+}
+
+#[test]
+fn network_is_only_provided_when_attached() {
+    let simulator: Simulator = Simulator::builder().build().unwrap();
+
+    assert_eq!(simulator.network_handle(), None);
+    assert!(matches!(
+        simulator
+            .boot_services()
+            .revision_1_1()
+            .unwrap()
+            .locate_protocol::<EfiServiceBindingProtocol<EfiUdp4Protocol>>(None),
+        Ok(EfiStatusEnum::Error(EfiStatusError::EfiNotFound, ()))
+    ));
+}
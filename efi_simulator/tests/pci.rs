@@ -0,0 +1,368 @@
+use {
+    efi::{
+        boot_services::{types::memory::EfiMemoryType, EfiBootServices},
+        protocols::pci::{
+            EfiAcpiAddressSpaceDescriptor, EfiAcpiResourceType, EfiPciAttributes,
+            EfiPciConfigurationAddress, EfiPciIoAttributeOperation, EfiPciIoMapping,
+            EfiPciIoOperation, EfiPciIoProtocol, EfiPciIoWidth, EfiPciLocation,
+            EfiPciRootBridgeIoProtocol,
+        },
+        EfiStatusEnum, EfiStatusError, VoidMutPtr, VoidPtr,
+    },
+    efi_simulator::{SimulatedPciFunction, Simulator},
+};
+
+const BAR_ADDRESS: u64 = 0xC000_0000;
+
+fn simulator() -> Simulator {
+    Simulator::builder()
+        .pci_function(SimulatedPciFunction::new(0x8086, 0x100E, 0x02_00_00))
+        .pci_function(
+            SimulatedPciFunction::new(0x1AF4, 0x1001, 0x01_00_00)
+                .bar_size(0x100)
+                .option_rom(&[0x55, 0xAA, 0x01]),
+        )
+        .build()
+        .unwrap()
+}
+
+fn root_bridge(simulator: &Simulator) -> &'static EfiPciRootBridgeIoProtocol {
+    let boot_services: &EfiBootServices = simulator.boot_services();
+
+    boot_services
+        .handle_protocol::<EfiPciRootBridgeIoProtocol>(simulator.pci_root_bridge_handle().unwrap())
+        .unwrap()
+        .unfold()
+        .ok()
+        .unwrap()
+        .1
+        .unwrap()
+}
+
+fn pci_io(simulator: &Simulator, index: usize) -> &'static EfiPciIoProtocol {
+    let boot_services: &EfiBootServices = simulator.boot_services();
+
+    boot_services
+        .handle_protocol::<EfiPciIoProtocol>(simulator.pci_function_handle(index).unwrap())
+        .unwrap()
+        .unfold()
+        .ok()
+        .unwrap()
+        .1
+        .unwrap()
+}
+
+fn configuration(device: u8, register: u32) -> EfiPciConfigurationAddress {
+    EfiPciConfigurationAddress::new(0, device, 0, register)
+}
+
+#[test]
+fn pci_root_bridge_enumerates_the_functions() {
+    let simulator: Simulator = simulator();
+    let root_bridge: &EfiPciRootBridgeIoProtocol = root_bridge(&simulator);
+
+    assert_eq!(root_bridge.segment_number(), 0);
+
+    let resources: Vec<EfiAcpiAddressSpaceDescriptor> = match root_bridge.configuration() {
+        EfiStatusEnum::Success(resources) => resources.collect(),
+        _ => panic!("Getting the root bridge's resources failed!"),
+    };
+
+    assert_eq!(resources.len(), 2);
+    assert_eq!(resources[0].resource_type(), EfiAcpiResourceType::Memory);
+    assert_eq!(resources[0].range_minimum(), BAR_ADDRESS);
+    assert_eq!(resources[0].address_length(), 0x1000_0000);
+    assert_eq!(resources[1].resource_type(), EfiAcpiResourceType::BusNumber);
+    assert_eq!(
+        (resources[1].range_minimum(), resources[1].range_maximum()),
+        (0, 0)
+    );
+
+    let mut ids: [u16; 2] = [0; 2];
+
+    for (device, expected) in [(0, [0x8086, 0x100E]), (1, [0x1AF4, 0x1001])] {
+        assert!(root_bridge
+            .pci_read(configuration(device, 0), &mut ids)
+            .is_success());
+        assert_eq!(ids, expected);
+    }
+
+    /* Absent functions read as all ones */
+    assert!(root_bridge
+        .pci_read(configuration(2, 0), &mut ids)
+        .is_success());
+    assert_eq!(ids, [0xFFFF; 2]);
+    assert!(root_bridge
+        .pci_read(EfiPciConfigurationAddress::new(0, 0, 1, 0), &mut ids)
+        .is_success());
+    assert_eq!(ids, [0xFFFF; 2]);
+
+    /* The BARs are assigned consecutively, aligned to their size */
+    let mut bars: [u32; 1] = [0];
+
+    assert!(root_bridge
+        .pci_read(configuration(1, 0x10), &mut bars)
+        .is_success());
+    assert_eq!(u64::from(bars[0]), BAR_ADDRESS + 0x1000);
+
+    /* Writing all ones reads back the BAR's size mask */
+    assert!(root_bridge
+        .pci_write(configuration(1, 0x10), &[u32::MAX])
+        .is_success());
+    assert!(root_bridge
+        .pci_read(configuration(1, 0x10), &mut bars)
+        .is_success());
+    assert_eq!(bars[0], !0xFF);
+
+    /* Identification registers are read-only */
+    assert!(root_bridge
+        .pci_write(configuration(1, 0), &[0u32])
+        .is_success());
+    assert!(root_bridge
+        .pci_read(configuration(1, 0), &mut ids)
+        .is_success());
+    assert_eq!(ids, [0x1AF4, 0x1001]);
+
+    /* Functions only have the conventional configuration space */
+    assert_eq!(
+        root_bridge.pci_read(configuration(0, 0x100), &mut ids),
+        EfiStatusEnum::Error(EfiStatusError::EfiInvalidParameter, ())
+    );
+}
+
+#[test]
+fn pci_io_accesses_the_function() {
+    let simulator: Simulator = simulator();
+    let root_bridge: &EfiPciRootBridgeIoProtocol = root_bridge(&simulator);
+    let without_rom: &EfiPciIoProtocol = pci_io(&simulator, 0);
+    let pci_io: &EfiPciIoProtocol = pci_io(&simulator, 1);
+
+    match pci_io.get_location() {
+        EfiStatusEnum::Success(location) => assert_eq!(
+            location,
+            EfiPciLocation {
+                segment: 0,
+                bus: 0,
+                device: 1,
+                function: 0,
+            }
+        ),
+        _ => panic!("Getting the location failed!"),
+    }
+
+    let mut class_code: [u8; 3] = [0; 3];
+
+    assert!(pci_io.pci_read(0x09, &mut class_code).is_success());
+    assert_eq!(class_code, [0x00, 0x00, 0x01]);
+    assert_eq!(pci_io.rom_image(), Some(&[0x55, 0xAA, 0x01][..]));
+    assert_eq!(without_rom.rom_image(), None);
+
+    assert!(pci_io
+        .mem_write(0, 0x10, &[0x1122_3344_u32, 0x5566_7788])
+        .is_success());
+    assert_eq!(
+        &simulator.pci_bar(1).unwrap()[0x10..0x18],
+        &[0x44, 0x33, 0x22, 0x11, 0x88, 0x77, 0x66, 0x55]
+    );
+
+    /* The BAR isn't decoded until memory accesses are enabled */
+    let mut data: [u32; 2] = [0; 2];
+    let address: u64 = BAR_ADDRESS + 0x1000 + 0x10;
+
+    assert!(root_bridge.mem_read(address, &mut data).is_success());
+    assert_eq!(data, [u32::MAX; 2]);
+
+    assert!(pci_io
+        .attributes(
+            EfiPciIoAttributeOperation::Enable,
+            EfiPciAttributes::new(EfiPciAttributes::MEMORY)
+        )
+        .is_success());
+    assert!(root_bridge.mem_read(address, &mut data).is_success());
+    assert_eq!(data, [0x1122_3344, 0x5566_7788]);
+
+    /* Which is reflected by the command register */
+    let mut command: [u16; 1] = [0];
+
+    assert!(pci_io.pci_read(0x04, &mut command).is_success());
+    assert_eq!(command[0], 0x0002);
+
+    match pci_io.attributes(EfiPciIoAttributeOperation::Get, EfiPciAttributes::new(0)) {
+        EfiStatusEnum::Success(attributes) => {
+            assert_eq!(attributes.bits(), EfiPciAttributes::MEMORY)
+        }
+        _ => panic!("Getting the attributes failed!"),
+    }
+    assert_eq!(
+        pci_io.attributes(
+            EfiPciIoAttributeOperation::Enable,
+            EfiPciAttributes::new(EfiPciAttributes::IO)
+        ),
+        EfiStatusEnum::Error(EfiStatusError::EfiUnsupported, ())
+    );
+
+    /* Overlapping copies behave as if through an intermediate buffer */
+    assert!(pci_io
+        .copy_mem(EfiPciIoWidth::Uint32, (0, 0x14), (0, 0x10), 2)
+        .is_success());
+    assert!(pci_io.mem_read(0, 0x10, &mut data).is_success());
+    assert_eq!(data, [0x1122_3344, 0x1122_3344]);
+
+    match pci_io.poll_mem(EfiPciIoWidth::Uint32, 0, 0x10, 0xFFFF, 0x3344, 10) {
+        EfiStatusEnum::Success(value) => assert_eq!(value, 0x1122_3344),
+        _ => panic!("Polling the memory failed!"),
+    }
+    assert_eq!(
+        pci_io.poll_mem(EfiPciIoWidth::Uint32, 0, 0x10, 0xFFFF, 0, 10),
+        EfiStatusEnum::Error(EfiStatusError::EfiTimeout, ())
+    );
+
+    assert_eq!(
+        pci_io.mem_read(0, 0xFC, &mut data),
+        EfiStatusEnum::Error(EfiStatusError::EfiUnsupported, ())
+    );
+    assert_eq!(
+        pci_io.mem_read(1, 0, &mut data),
+        EfiStatusEnum::Error(EfiStatusError::EfiUnsupported, ())
+    );
+    assert_eq!(
+        pci_io.io_read(0, 0, &mut data),
+        EfiStatusEnum::Error(EfiStatusError::EfiUnsupported, ())
+    );
+}
+
+#[test]
+fn pci_io_reports_the_bar_resources() {
+    let simulator: Simulator = simulator();
+    let boot_services: &EfiBootServices = simulator.boot_services();
+    let pci_io: &EfiPciIoProtocol = pci_io(&simulator, 0);
+
+    let (supports, resources): (EfiPciAttributes, Vec<EfiAcpiAddressSpaceDescriptor>) =
+        match pci_io.get_bar_attributes(0) {
+            EfiStatusEnum::Success((supports, resources)) => {
+                let buffer: VoidPtr = resources.as_ptr() as VoidPtr;
+                let resources: Vec<EfiAcpiAddressSpaceDescriptor> = resources.collect();
+
+                assert!(boot_services.free_pool(buffer).is_success());
+
+                (supports, resources)
+            }
+            _ => panic!("Getting the BAR's attributes failed!"),
+        };
+
+    assert!(supports.contains(EfiPciAttributes::MEMORY_WRITE_COMBINE));
+    assert_eq!(resources.len(), 1);
+    assert_eq!(resources[0].resource_type(), EfiAcpiResourceType::Memory);
+    assert_eq!(resources[0].granularity(), 32);
+    assert_eq!(resources[0].range_minimum(), BAR_ADDRESS);
+    assert_eq!(resources[0].address_length(), 0x1000);
+
+    let (mut offset, mut length): (u64, u64) = (0x800, 0x800);
+
+    assert!(pci_io
+        .set_bar_attributes(
+            EfiPciAttributes::new(EfiPciAttributes::MEMORY_WRITE_COMBINE),
+            0,
+            &mut offset,
+            &mut length
+        )
+        .is_success());
+
+    length += 1;
+
+    assert_eq!(
+        pci_io.set_bar_attributes(
+            EfiPciAttributes::new(EfiPciAttributes::MEMORY_WRITE_COMBINE),
+            0,
+            &mut offset,
+            &mut length
+        ),
+        EfiStatusEnum::Error(EfiStatusError::EfiUnsupported, ())
+    );
+    assert!(matches!(
+        pci_io.get_bar_attributes(1),
+        EfiStatusEnum::Error(EfiStatusError::EfiUnsupported, _)
+    ));
+}
+
+#[test]
+fn pci_io_maps_and_allocates_dma_buffers() {
+    let simulator: Simulator = simulator();
+    let pci_io: &EfiPciIoProtocol = pci_io(&simulator, 0);
+
+    let host: VoidMutPtr = match pci_io.allocate_buffer(
+        EfiMemoryType::EfiBootServicesData,
+        2,
+        EfiPciAttributes::new(EfiPciAttributes::MEMORY_CACHED),
+    ) {
+        EfiStatusEnum::Success(host) => host,
+        _ => panic!("Allocating the buffer failed!"),
+    };
+
+    let mapping: EfiPciIoMapping =
+        match unsafe { pci_io.map(EfiPciIoOperation::BusMasterCommonBuffer, host, 0x2000) } {
+            EfiStatusEnum::Success(mapping) => mapping,
+            _ => panic!("Mapping the buffer failed!"),
+        };
+
+    assert_eq!(mapping.device_address(), host as u64);
+    assert_eq!(mapping.length(), 0x2000);
+    assert!(pci_io.unmap(mapping).is_success());
+    assert!(unsafe { pci_io.free_buffer(2, host) }.is_success());
+
+    /* Only root bridges map above 4 GiB */
+    assert!(matches!(
+        unsafe { pci_io.map(EfiPciIoOperation::BusMasterRead64, host, 0x10) },
+        EfiStatusEnum::Error(EfiStatusError::EfiInvalidParameter, _)
+    ));
+    assert_eq!(
+        pci_io.allocate_buffer(
+            EfiMemoryType::EfiBootServicesData,
+            1,
+            EfiPciAttributes::new(EfiPciAttributes::BUS_MASTER)
+        ),
+        EfiStatusEnum::Error(EfiStatusError::EfiUnsupported, ())
+    );
+    assert_eq!(
+        pci_io.allocate_buffer(EfiMemoryType::EfiLoaderData, 1, EfiPciAttributes::new(0)),
+        EfiStatusEnum::Error(EfiStatusError::EfiInvalidParameter, ())
+    );
+
+    let root_bridge: &EfiPciRootBridgeIoProtocol = root_bridge(&simulator);
+    let data: [u8; 4] = [1, 2, 3, 4];
+
+    let mapping: EfiPciIoMapping = match unsafe {
+        root_bridge.map(
+            EfiPciIoOperation::BusMasterRead64,
+            data.as_ptr() as VoidPtr,
+            data.len(),
+        )
+    } {
+        EfiStatusEnum::Success(mapping) => mapping,
+        _ => panic!("Mapping the buffer failed!"),
+    };
+
+    assert_eq!(mapping.device_address(), data.as_ptr() as u64);
+    assert!(root_bridge.unmap(mapping).is_success());
+}
+
+#[test]
+fn pci_is_only_provided_when_functions_are_attached() {
+    let simulator: Simulator = Simulator::builder().build().unwrap();
+
+    assert_eq!(simulator.pci_root_bridge_handle(), None);
+    assert_eq!(simulator.pci_function_handle(0), None);
+    assert!(matches!(
+        simulator
+            .boot_services()
+            .revision_1_1()
+            .unwrap()
+            .locate_protocol::<EfiPciRootBridgeIoProtocol>(None),
+        Ok(EfiStatusEnum::Error(EfiStatusError::EfiNotFound, ()))
+    ));
+
+    assert!(Simulator::builder()
+        .pci_function(SimulatedPciFunction::new(0x8086, 0x100E, 0x02_00_00).bar_size(0x300))
+        .build()
+        .is_err());
+}
@@ -0,0 +1,112 @@
+use {
+    efi::{guids, protocols::rng::EfiRngProtocol, EfiGuid, EfiStatusEnum, EfiStatusError},
+    efi_simulator::Simulator,
+};
+
+fn random_bytes(seed: u64) -> Vec<u8> {
+    let simulator: Simulator = Simulator::builder().rng(seed).build().unwrap();
+
+    let rng: &EfiRngProtocol = match simulator
+        .boot_services()
+        .revision_1_1()
+        .unwrap()
+        .locate_protocol::<EfiRngProtocol>(None)
+    {
+        Ok(EfiStatusEnum::Success(Ok(rng))) => rng,
+        _ => panic!("Locating the RNG protocol failed!"),
+    };
+
+    let mut bytes: Vec<u8> = vec![0; 13];
+
+    assert!(rng.get_rng(None, &mut bytes).is_success());
+
+    bytes
+}
+
+#[test]
+fn rng_reports_the_supported_algorithms() {
+    let simulator: Simulator = Simulator::builder().rng(1).build().unwrap();
+    let boot_services = simulator.boot_services();
+
+    let rng: &EfiRngProtocol = boot_services
+        .handle_protocol::<EfiRngProtocol>(simulator.rng_handle().unwrap())
+        .unwrap()
+        .unfold()
+        .ok()
+        .unwrap()
+        .1
+        .unwrap();
+
+    let mut algorithms: [EfiGuid; 4] = [EfiGuid::from_tuple((0, 0, 0, [0; 8])); 4];
+
+    match rng.get_info(&mut algorithms[..1]) {
+        EfiStatusEnum::Error(EfiStatusError::EfiBufferTooSmall, count) => assert_eq!(count, 2),
+        _ => panic!("A single algorithm's buffer should be too small!"),
+    }
+
+    match rng.get_info(&mut algorithms) {
+        EfiStatusEnum::Success(algorithms) => assert_eq!(
+            algorithms,
+            [
+                guids::EFI_RNG_ALGORITHM_SP800_90_CTR_256,
+                guids::EFI_RNG_ALGORITHM_RAW
+            ]
+        ),
+        _ => panic!("Getting the algorithms failed!"),
+    }
+}
+
+#[test]
+fn rng_fills_buffers_reproducibly() {
+    let first: Vec<u8> = random_bytes(0x5EED);
+
+    assert_ne!(first, vec![0; 13]);
+    assert_eq!(random_bytes(0x5EED), first);
+    assert_ne!(random_bytes(0x5EEE), first);
+
+    let simulator: Simulator = Simulator::builder().rng(0x5EED).build().unwrap();
+
+    let rng: &EfiRngProtocol = simulator
+        .boot_services()
+        .handle_protocol::<EfiRngProtocol>(simulator.rng_handle().unwrap())
+        .unwrap()
+        .unfold()
+        .ok()
+        .unwrap()
+        .1
+        .unwrap();
+
+    let mut bytes: [u8; 13] = [0; 13];
+
+    /* The algorithms share the stream, which continues across calls */
+    assert!(rng
+        .get_rng(Some(&guids::EFI_RNG_ALGORITHM_RAW), &mut bytes)
+        .is_success());
+    assert_eq!(&bytes[..8], &first[..8]);
+    assert!(rng.get_rng(None, &mut bytes).is_success());
+    assert_ne!(&bytes[..8], &first[..8]);
+
+    assert_eq!(
+        rng.get_rng(Some(&guids::EFI_RNG_ALGORITHM_X9_31_AES), &mut bytes),
+        EfiStatusEnum::Error(EfiStatusError::EfiUnsupported, ())
+    );
+    assert_eq!(
+        rng.get_rng(None, &mut []),
+        EfiStatusEnum::Error(EfiStatusError::EfiInvalidParameter, ())
+    );
+}
+
+#[test]
+fn rng_is_only_provided_when_requested() {
+    let simulator: Simulator = Simulator::builder().build().unwrap();
+
+    assert_eq!(simulator.rng_handle(), None);
+    assert!(matches!(
+        simulator
+            .boot_services()
+            .revision_1_1()
+            .unwrap()
+            .locate_protocol::<EfiRngProtocol>(None),
+        Ok(EfiStatusEnum::Error(EfiStatusError::EfiNotFound, ()))
+    ));
+}
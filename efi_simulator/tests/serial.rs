@@ -0,0 +1,196 @@
+use {
+    core::{fmt::Write, ptr::from_ref},
+    efi::{
+        boot_services::EfiBootServices,
+        protocols::console::{
+            EfiParityType, EfiSerialControlBits, EfiSerialIoMode, EfiSerialIoProtocol,
+            EfiStopBitsType,
+        },
+        EfiGuid, EfiStatusEnum, EfiStatusError,
+    },
+    efi_simulator::Simulator,
+};
+
+/// `EFI_SERIAL_TERMINAL_DEVICE_TYPE_GUID`.
+const SERIAL_TERMINAL: EfiGuid = EfiGuid::from_tuple((
+    0x6AD9_A60F,
+    0x5815,
+    0x4C7C,
+    [0x8A, 0x10, 0x50, 0x53, 0xD2, 0xBF, 0x7A, 0x1B],
+));
+
+fn serial_io(simulator: &Simulator, index: usize) -> &'static EfiSerialIoProtocol {
+    let boot_services: &EfiBootServices = simulator.boot_services();
+
+    boot_services
+        .handle_protocol::<EfiSerialIoProtocol>(simulator.serial_port_handle(index).unwrap())
+        .unwrap()
+        .unfold()
+        .ok()
+        .unwrap()
+        .1
+        .unwrap()
+}
+
+#[test]
+fn serial_writes_are_retried_until_complete() {
+    let simulator: Simulator = Simulator::builder()
+        .serial_port()
+        .serial_port()
+        .build()
+        .unwrap();
+    let serial_io: &EfiSerialIoProtocol = serial_io(&simulator, 1);
+
+    let data: Vec<u8> = (0..40).collect();
+
+    /* A single write only fills the FIFO */
+    assert_eq!(
+        serial_io.write(&data),
+        EfiStatusEnum::Error(EfiStatusError::EfiTimeout, 16)
+    );
+    assert!(serial_io.write_all(&data[16..]).is_success());
+    assert_eq!(simulator.serial_output(1), Some(data));
+    assert_eq!(simulator.serial_output(0), Some(Vec::new()));
+    assert_eq!(simulator.serial_output(2), None);
+
+    simulator.stall_serial_port(1, true);
+
+    assert_eq!(
+        serial_io.write_all(b"lost"),
+        EfiStatusEnum::Error(EfiStatusError::EfiTimeout, ())
+    );
+
+    simulator.stall_serial_port(1, false);
+
+    assert!(serial_io.write_all(&[]).is_success());
+    assert_eq!(simulator.serial_output(1).unwrap().len(), 40);
+}
+
+#[test]
+fn serial_formatting_ends_lines_with_carriage_returns() {
+    let simulator: Simulator = Simulator::builder().serial_port().build().unwrap();
+
+    /* Formatting needs a mutable interface, which the runtime's logger also gets from a stored pointer */
+    let serial_out: *mut EfiSerialIoProtocol = from_ref(serial_io(&simulator, 0)).cast_mut();
+    let serial_io: &mut EfiSerialIoProtocol = unsafe { serial_out.as_mut() }.unwrap();
+
+    write!(
+        serial_io,
+        "Booting {}\nfrom a line long enough to need a retry\n",
+        1
+    )
+    .unwrap();
+
+    assert_eq!(
+        simulator.serial_output(0).unwrap(),
+        b"Booting 1\r\nfrom a line long enough to need a retry\r\n"
+    );
+}
+
+#[test]
+fn serial_reads_time_out_once_the_input_runs_out() {
+    let simulator: Simulator = Simulator::builder().serial_port().build().unwrap();
+    let serial_io: &EfiSerialIoProtocol = serial_io(&simulator, 0);
+
+    let mut buffer: [u8; 8] = [0; 8];
+
+    assert_eq!(
+        serial_io.read(&mut buffer),
+        EfiStatusEnum::Error(EfiStatusError::EfiTimeout, 0)
+    );
+
+    simulator.push_serial_input(0, b"hello");
+
+    match serial_io.get_control() {
+        EfiStatusEnum::Success(control) => assert!(!control.input_buffer_empty()),
+        _ => panic!("Getting the control bits failed!"),
+    }
+    assert_eq!(serial_io.read(&mut buffer[..3]), EfiStatusEnum::Success(3));
+    assert_eq!(
+        serial_io.read(&mut buffer[3..]),
+        EfiStatusEnum::Error(EfiStatusError::EfiTimeout, 2)
+    );
+    assert_eq!(&buffer[..5], b"hello");
+
+    /* With software loopback the written bytes come back instead of being transmitted */
+    assert!(serial_io
+        .set_control(EfiSerialControlBits::new(
+            EfiSerialControlBits::SOFTWARE_LOOPBACK_ENABLE
+        ))
+        .is_success());
+    assert!(serial_io.write_all(b"echo").is_success());
+    assert_eq!(serial_io.read(&mut buffer[..4]), EfiStatusEnum::Success(4));
+    assert_eq!(&buffer[..4], b"echo");
+    assert_eq!(simulator.serial_output(0), Some(Vec::new()));
+}
+
+#[test]
+fn serial_attributes_and_control_bits_are_applied() {
+    let simulator: Simulator = Simulator::builder().serial_port().build().unwrap();
+    let serial_io: &EfiSerialIoProtocol = serial_io(&simulator, 0);
+
+    assert_eq!(serial_io.device_type_guid(), Some(&SERIAL_TERMINAL));
+    assert_eq!(serial_io.mode().receive_fifo_depth(), 16);
+
+    assert!(serial_io
+        .set_attributes(
+            9600,
+            0,
+            500,
+            EfiParityType::EvenParity,
+            7,
+            EfiStopBitsType::TwoStopBits
+        )
+        .is_success());
+
+    let mode: &EfiSerialIoMode = serial_io.mode();
+
+    assert_eq!(mode.baud_rate(), 9600);
+    assert_eq!(mode.timeout(), 500);
+    assert_eq!(mode.parity(), Some(EfiParityType::EvenParity));
+    assert_eq!(mode.data_bits(), 7);
+    assert_eq!(mode.stop_bits(), Some(EfiStopBitsType::TwoStopBits));
+
+    /* Defaults are selected again by passing 0 */
+    assert!(serial_io
+        .set_attributes(0, 0, 0, EfiParityType::Default, 0, EfiStopBitsType::Default)
+        .is_success());
+    assert_eq!(mode.baud_rate(), 115_200);
+    assert_eq!(mode.parity(), Some(EfiParityType::NoParity));
+    assert_eq!(mode.data_bits(), 8);
+    assert_eq!(mode.stop_bits(), Some(EfiStopBitsType::OneStopBit));
+
+    assert_eq!(
+        serial_io.set_attributes(0, 0, 0, EfiParityType::Default, 9, EfiStopBitsType::Default),
+        EfiStatusEnum::Error(EfiStatusError::EfiInvalidParameter, ())
+    );
+
+    assert!(!mode.control_mask().hardware_flow_control_enable());
+    assert!(serial_io
+        .set_control(EfiSerialControlBits::new(
+            EfiSerialControlBits::DATA_TERMINAL_READY | EfiSerialControlBits::REQUEST_TO_SEND
+        ))
+        .is_success());
+    assert_eq!(
+        serial_io.set_control(EfiSerialControlBits::new(
+            EfiSerialControlBits::HARDWARE_FLOW_CONTROL_ENABLE
+        )),
+        EfiStatusEnum::Error(EfiStatusError::EfiUnsupported, ())
+    );
+
+    match serial_io.get_control() {
+        EfiStatusEnum::Success(control) => {
+            assert!(control.data_terminal_ready() && control.request_to_send());
+            assert!(control.clear_to_send() && control.data_set_ready());
+            assert!(control.input_buffer_empty() && control.output_buffer_empty());
+        }
+        _ => panic!("Getting the control bits failed!"),
+    }
+
+    assert!(serial_io.reset().is_success());
+
+    match serial_io.get_control() {
+        EfiStatusEnum::Success(control) => assert!(!control.data_terminal_ready()),
+        _ => panic!("Getting the control bits failed!"),
+    }
+}
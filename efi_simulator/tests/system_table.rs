@@ -0,0 +1,113 @@
+use {
+    efi::{
+        boot_services::types::memory::{EfiAllocateType, EfiMemoryType},
+        EfiRevision, EfiStatusEnum, EfiSystemTable, EfiTableHeader,
+    },
+    efi_simulator::Simulator,
+    std::ptr::copy_nonoverlapping,
+};
+
+#[test]
+fn tables_verify() {
+    let simulator: Simulator = Simulator::builder().firmware_revision(7).build().unwrap();
+    let system_table: &EfiSystemTable = simulator.system_table();
+
+    assert!(system_table.verify_table());
+    assert_eq!(system_table.firmware_revision(), 7);
+    assert!(system_table.revision() >= EfiRevision::EFI_2_00);
+
+    let crc32: EfiStatusEnum<u32> = system_table
+        .boot_services()
+        .revision_1_1()
+        .unwrap()
+        .calculate_crc32(b"123456789");
+
+    assert_eq!(crc32, EfiStatusEnum::Success(0xCBF4_3926));
+
+    /* Table sizes which don't cover the header fail verification, even with a matching checksum */
+    for &table_size in &[0u64, 12, 20] {
+        let mut header: [u64; 3] = [0; 3];
+
+        unsafe {
+            copy_nonoverlapping(
+                system_table.header() as *const EfiTableHeader as *const u64,
+                header.as_mut_ptr(),
+                header.len(),
+            );
+        }
+
+        /* "table_size" follows "revision" in the second quadword */
+        header[1] = header[1] & 0xFFFF_FFFF | table_size << 32;
+
+        let header: &mut EfiTableHeader = unsafe { &mut *header.as_mut_ptr().cast() };
+
+        header.update_crc32();
+
+        assert_eq!(u64::from(header.table_size()), table_size);
+        assert!(!header.verify_table());
+    }
+}
+
+#[test]
+fn crc32_matches_firmware() {
+    let simulator: Simulator = Simulator::builder().build().unwrap();
+    let system_table: &EfiSystemTable = simulator.system_table();
+
+    let data: Vec<u8> = (0..1021_u32)
+        .map(|index: u32| (index.wrapping_mul(0x9E37_79B9) >> 24) as u8)
+        .collect();
+
+    for length in [1, 7, 8, 9, 64, 1021] {
+        let mut crc: efi::crc32::Crc32 = efi::crc32::Crc32::new();
+
+        /* Stream in uneven pieces to cover the partial chunks */
+        for piece in data[..length].chunks(5) {
+            crc.update(piece);
+        }
+
+        assert_eq!(
+            EfiStatusEnum::Success(crc.finish()),
+            system_table
+                .boot_services()
+                .revision_1_1()
+                .unwrap()
+                .calculate_crc32(&data[..length])
+        );
+        assert_eq!(crc.finish(), efi::crc32::checksum(&data[..length]));
+    }
+}
+
+#[test]
+fn exit_boot_services_requires_current_map_key() {
+    let simulator: Simulator = Simulator::builder().build().unwrap();
+    let boot_services = simulator.boot_services();
+
+    let mut address: u64 = 0;
+
+    assert!(boot_services
+        .allocate_pages(
+            EfiAllocateType::AllocateAnyPages,
+            EfiMemoryType::EfiLoaderData,
+            2,
+            &mut address,
+        )
+        .is_success());
+    assert_ne!(address, 0);
+
+    let mut buffer: Vec<u8> = vec![0; 4096];
+
+    let map_key: usize = match boot_services.get_memory_map(&mut buffer) {
+        EfiStatusEnum::Success(map) => map.key(),
+        _ => panic!("Getting the memory map failed!"),
+    };
+
+    assert!(boot_services
+        .exit_boot_services(0 as _, map_key + 1)
+        .is_error());
+    assert!(!simulator.exited_boot_services());
+    assert!(boot_services
+        .exit_boot_services(0 as _, map_key)
+        .is_success());
+    assert!(simulator.exited_boot_services());
+    assert!(simulator.system_table().con_out().is_none());
+}
@@ -0,0 +1,186 @@
+use {
+    efi::{
+        protocols::usb::{
+            EfiUsb2HcProtocol, EfiUsbData, EfiUsbDeviceDescriptor, EfiUsbDeviceRequest,
+            EfiUsbEndpointDescriptor, EfiUsbHcState, EfiUsbInterfaceDescriptor, EfiUsbIoProtocol,
+            EfiUsbPortFeature, EfiUsbPortStatus, EfiUsbSpeed, EfiUsbTransferStatus,
+            EfiUsbTransferType, EFI_USB_LANGUAGE_ENGLISH_US,
+        },
+        EfiStatusEnum, EfiStatusError,
+    },
+    efi_simulator::{SimulatedUsbDevice, Simulator},
+};
+
+#[test]
+fn usb_devices_report_their_descriptors() {
+    let simulator: Simulator = Simulator::builder()
+        .usb_device(
+            SimulatedUsbDevice::new(0x1050, 0x0407, EfiUsbInterfaceDescriptor::CLASS_SMART_CARD)
+                .manufacturer("Yubico")
+                .product("YubiKey OTP+FIDO+CCID")
+                .serial_number("12345678"),
+        )
+        .usb_device(SimulatedUsbDevice::new(
+            0x0781,
+            0x5581,
+            EfiUsbInterfaceDescriptor::CLASS_MASS_STORAGE,
+        ))
+        .build()
+        .unwrap();
+    let boot_services = simulator.boot_services();
+
+    let usb_io: &EfiUsbIoProtocol = boot_services
+        .handle_protocol::<EfiUsbIoProtocol>(simulator.usb_device_handle(0).unwrap())
+        .unwrap()
+        .unfold()
+        .ok()
+        .unwrap()
+        .1
+        .unwrap();
+
+    let device: EfiUsbDeviceDescriptor = usb_io.get_device_descriptor().unfold().unwrap().1;
+
+    assert_eq!((device.vendor_id(), device.product_id()), (0x1050, 0x0407));
+    assert_eq!(device.usb_version(), 0x0200);
+    assert_eq!(
+        (
+            device.manufacturer_index(),
+            device.product_index(),
+            device.serial_number_index()
+        ),
+        (1, 2, 3)
+    );
+
+    let interface: EfiUsbInterfaceDescriptor =
+        usb_io.get_interface_descriptor().unfold().unwrap().1;
+
+    assert_eq!(
+        interface.interface_class(),
+        EfiUsbInterfaceDescriptor::CLASS_SMART_CARD
+    );
+    assert_eq!(interface.num_endpoints(), 2);
+
+    let endpoints: Vec<EfiUsbEndpointDescriptor> = usb_io.endpoint_descriptors().collect();
+
+    assert_eq!(endpoints.len(), 2);
+    assert!(endpoints[0].is_in() && !endpoints[1].is_in());
+    assert!(endpoints.iter().all(
+        |endpoint: &EfiUsbEndpointDescriptor| endpoint.transfer_type() == EfiUsbTransferType::Bulk
+            && endpoint.max_packet_size() == 512
+    ));
+
+    assert_eq!(
+        usb_io.get_supported_languages().unfold().unwrap().1,
+        [EFI_USB_LANGUAGE_ENGLISH_US]
+    );
+    assert_eq!(
+        String::from_utf16(
+            &usb_io
+                .get_string_descriptor(boot_services, EFI_USB_LANGUAGE_ENGLISH_US, 2)
+                .unfold()
+                .unwrap()
+                .1
+        )
+        .unwrap(),
+        "YubiKey OTP+FIDO+CCID"
+    );
+    assert_eq!(
+        usb_io.get_string_descriptor(boot_services, 0x0407, 2),
+        EfiStatusEnum::Error(EfiStatusError::EfiNotFound, ())
+    );
+
+    /* The configuration descriptor is followed by the interface and endpoint ones */
+    let mut buffer: [u8; 64] = [0; 64];
+
+    assert!(usb_io
+        .get_descriptor(
+            EfiUsbDeviceRequest::DESCRIPTOR_CONFIGURATION,
+            0,
+            0,
+            &mut buffer,
+            1000
+        )
+        .is_success());
+    assert_eq!(u16::from_le_bytes([buffer[2], buffer[3]]), 32);
+    assert_eq!(buffer[9 + 5], EfiUsbInterfaceDescriptor::CLASS_SMART_CARD);
+
+    assert_eq!(
+        usb_io.control_transfer(
+            &EfiUsbDeviceRequest::new(0, 0x7F, 0, 0, 0),
+            EfiUsbData::None,
+            1000
+        ),
+        EfiStatusEnum::Error(
+            EfiStatusError::EfiDeviceError,
+            EfiUsbTransferStatus::new(EfiUsbTransferStatus::STALL)
+        )
+    );
+
+    let mut data: [u8; 5] = *b"hello";
+
+    assert!(usb_io
+        .bulk_transfer(endpoints[1].endpoint_address(), &mut data, 1000)
+        .is_success());
+
+    let mut echo: [u8; 8] = [0; 8];
+
+    assert_eq!(
+        usb_io
+            .bulk_transfer(endpoints[0].endpoint_address(), &mut echo, 1000)
+            .unfold()
+            .unwrap()
+            .1
+             .0,
+        5
+    );
+    assert_eq!(&echo[..5], b"hello");
+
+    let host_controller: &EfiUsb2HcProtocol = boot_services
+        .handle_protocol::<EfiUsb2HcProtocol>(simulator.usb_host_controller_handle().unwrap())
+        .unwrap()
+        .unfold()
+        .ok()
+        .unwrap()
+        .1
+        .unwrap();
+
+    assert_eq!(host_controller.revision(), (2, 0));
+    assert_eq!(
+        host_controller.get_state().unfold().unwrap().1,
+        EfiUsbHcState::Operational
+    );
+
+    let capability = host_controller.get_capability().unfold().unwrap().1;
+
+    assert_eq!(capability.max_speed(), Some(EfiUsbSpeed::High));
+    assert_eq!(capability.port_count(), 2);
+
+    let ports: Vec<(u8, EfiUsbPortStatus)> = host_controller.root_hub_ports().collect();
+
+    assert_eq!(ports.len(), 2);
+    assert!(ports.iter().all(|(_, status): &(u8, EfiUsbPortStatus)| {
+        status.is_enabled() && status.speed() == Some(EfiUsbSpeed::High)
+    }));
+    assert!(host_controller
+        .clear_root_hub_port_feature(0, EfiUsbPortFeature::ConnectChange)
+        .is_success());
+    assert_eq!(
+        host_controller
+            .get_root_hub_port_status(0)
+            .unfold()
+            .unwrap()
+            .1
+            .port_change_status(),
+        0
+    );
+    assert_eq!(
+        host_controller
+            .get_root_hub_port_status(1)
+            .unfold()
+            .unwrap()
+            .1
+            .port_change_status(),
+        EfiUsbPortStatus::CONNECTION_CHANGE
+    );
+    assert!(host_controller.get_root_hub_port_status(2).is_error());
+}
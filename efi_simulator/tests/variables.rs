@@ -0,0 +1,173 @@
+mod common;
+
+use {
+    common::{disk_image, utf16, VENDOR_GUID},
+    efi::{
+        boot_services::EfiBootServices,
+        guids,
+        protocols::device_path::{EfiDevicePathNode, EfiDevicePathProtocolRaw},
+        runtime_services::variable::{EfiVariable, EfiVariableAttributes},
+        structures::load_option::EfiLoadOption,
+        variables::EFI_BOOT_CURRENT_VARIABLE_NAME,
+        EfiHandle, EfiStatusEnum, NonNullVoidPtr,
+    },
+    efi_simulator::{
+        SimulatedDisk, Simulator, EFI_VARIABLE_BOOTSERVICE_ACCESS, EFI_VARIABLE_NON_VOLATILE,
+        EFI_VARIABLE_RUNTIME_ACCESS,
+    },
+    std::{fs, path::PathBuf},
+};
+
+#[test]
+fn variables() {
+    let simulator: Simulator = Simulator::builder()
+        .variable(
+            "Boot0000",
+            VENDOR_GUID,
+            EFI_VARIABLE_NON_VOLATILE | EFI_VARIABLE_BOOTSERVICE_ACCESS,
+            &[1, 2, 3],
+        )
+        .build()
+        .unwrap();
+    let runtime_services: &dyn EfiVariable = simulator.runtime_services().revision_1_0();
+
+    let mut data: [u8; 2] = [0; 2];
+
+    match runtime_services.get_variable(&utf16("Boot0000"), &VENDOR_GUID, Some(&mut data)) {
+        EfiStatusEnum::Error(_, (size, _)) => assert_eq!(size, 3),
+        _ => panic!("Expected the buffer to be too small!"),
+    }
+
+    let mut data: [u8; 3] = [0; 3];
+
+    match runtime_services.get_variable(&utf16("Boot0000"), &VENDOR_GUID, Some(&mut data)) {
+        EfiStatusEnum::Success((size, attributes)) => {
+            assert_eq!(size, 3);
+            assert!(attributes.non_volatile());
+            assert!(!attributes.runtime_access());
+        }
+        _ => panic!("Expected the variable to be found!"),
+    }
+
+    assert_eq!(data, [1, 2, 3]);
+    assert_eq!(
+        simulator.variable("Boot0000", &VENDOR_GUID),
+        Some((
+            EFI_VARIABLE_NON_VOLATILE | EFI_VARIABLE_BOOTSERVICE_ACCESS,
+            vec![1, 2, 3]
+        ))
+    );
+    assert!(simulator.variable("Boot0001", &VENDOR_GUID).is_none());
+    assert!(Simulator::builder()
+        .variable("Invalid", VENDOR_GUID, EFI_VARIABLE_RUNTIME_ACCESS, &[0])
+        .build()
+        .is_err());
+}
+
+#[test]
+fn boot_option_leads_to_boot_device() {
+    let path: PathBuf = disk_image("boot_option", 4);
+    let simulator: Simulator = Simulator::builder()
+        .disk(SimulatedDisk::new(&path))
+        .variable(
+            "BootCurrent",
+            guids::EFI_GLOBAL_VARIABLE,
+            EFI_VARIABLE_BOOTSERVICE_ACCESS | EFI_VARIABLE_RUNTIME_ACCESS,
+            &0x0001u16.to_le_bytes(),
+        )
+        .build()
+        .unwrap();
+    let boot_services: &EfiBootServices = simulator.boot_services();
+    let runtime_services: &dyn EfiVariable = simulator.runtime_services().revision_1_0();
+    let disk: EfiHandle = simulator.disk_handle(0).unwrap();
+
+    /* The boot option points at a file on the disk */
+    let disk_path: EfiDevicePathProtocolRaw = boot_services
+        .handle_protocol::<EfiDevicePathProtocolRaw>(disk)
+        .unwrap()
+        .unfold()
+        .ok()
+        .unwrap()
+        .1
+        .unwrap();
+    let file_name: Vec<u8> = utf16("\\EFI\\BOOT\\BOOTX64.EFI")
+        .iter()
+        .flat_map(|unit: &u16| unit.to_le_bytes())
+        .collect();
+    let file_path: Vec<u8> =
+        unsafe { disk_path.append_node(&EfiDevicePathNode::new(4, 4, &file_name)) }.unwrap();
+
+    /* Active, with the file path list following the description */
+    let mut boot_option: Vec<u8> = 1u32.to_le_bytes().to_vec();
+
+    boot_option.extend_from_slice(&(file_path.len() as u16).to_le_bytes());
+    boot_option.extend(
+        utf16("Nautilos")
+            .iter()
+            .flat_map(|unit: &u16| unit.to_le_bytes()),
+    );
+    boot_option.extend_from_slice(&file_path);
+
+    assert!(runtime_services
+        .set_variable(
+            &utf16("Boot0001"),
+            &guids::EFI_GLOBAL_VARIABLE,
+            &EfiVariableAttributes::new(
+                EFI_VARIABLE_NON_VOLATILE
+                    | EFI_VARIABLE_BOOTSERVICE_ACCESS
+                    | EFI_VARIABLE_RUNTIME_ACCESS
+            ),
+            &boot_option,
+        )
+        .is_success());
+
+    /* Resolved the way the loader finds it's boot device */
+    let mut boot_current: [u8; 2] = [0; 2];
+
+    assert!(runtime_services
+        .get_variable(
+            EFI_BOOT_CURRENT_VARIABLE_NAME,
+            &guids::EFI_GLOBAL_VARIABLE,
+            Some(&mut boot_current),
+        )
+        .is_success());
+
+    let mut data: Vec<u8> = vec![0; boot_option.len()];
+
+    assert!(runtime_services
+        .get_variable(
+            &utf16(&format!("Boot{:04X}", u16::from_le_bytes(boot_current))),
+            &guids::EFI_GLOBAL_VARIABLE,
+            Some(&mut data),
+        )
+        .is_success());
+
+    let load_option: EfiLoadOption = EfiLoadOption::parse(&data).unwrap();
+
+    assert_eq!(load_option.attributes(), 1);
+    assert_eq!(load_option.description(), &utf16("Nautilos")[..]);
+    assert!(load_option.optional_data().is_empty());
+
+    let mut device_path: EfiDevicePathProtocolRaw = EfiDevicePathProtocolRaw::new(
+        NonNullVoidPtr::new(load_option.file_path_list().as_ptr() as _).unwrap(),
+    );
+
+    assert_eq!(
+        boot_services
+            .locate_device_path(&guids::EFI_DISK_IO_PROTOCOL, &mut device_path)
+            .unfold()
+            .unwrap()
+            .1,
+        disk
+    );
+
+    /* What's left of the device path leads to the file */
+    let node: EfiDevicePathNode = unsafe { device_path.nodes() }.next().unwrap();
+
+    assert_eq!((node.node_type(), node.sub_type()), (4, 4));
+    assert_eq!(node.data(), &file_name[..]);
+
+    drop(simulator);
+
+    fs::remove_file(path).unwrap();
+}
@@ -39,3 +39,4 @@ pub use result::{Error, Result};
 
 pub mod features;
 pub mod input_output;
+pub mod random;
@@ -0,0 +1,190 @@
+//! Provides access to the platform's hardware random number generators and cycle counter.
+
+use crate::{
+    features::detection::{available as detection_available, FeatureState},
+    result::{Error, Result},
+};
+
+/// Number of times a hardware random number generator is retried before giving up.
+///
+/// The generators can temporarily run out of entropy, in which case they report failure.
+pub const RETRIES: usize = 10;
+
+/* Tests a bit of CPUID's result; "register" selects EBX (0) or ECX (1) */
+global_target_arch! {
+    ["x86", "x86_64"] fn cpuid_bit(leaf: u32, register: usize, bit: u32) -> Result<FeatureState> {
+        use Error::*;
+        use FeatureState::*;
+
+        match detection_available() {
+            Ok(Enabled) => {
+                let max_leaf: u32;
+
+                unsafe {
+                    asm!(
+                        "cpuid",
+                        inout("eax") 0 => max_leaf,
+                        out("ebx") _,
+                        out("ecx") _,
+                        out("edx") _,
+                        options(nomem, nostack)
+                    );
+                }
+
+                if max_leaf < leaf {
+                    return Err(Unavailable);
+                }
+
+                let b: u32;
+                let c: u32;
+
+                unsafe {
+                    asm!(
+                        "cpuid",
+                        inout("eax") leaf => _,
+                        out("ebx") b,
+                        inout("ecx") 0 => c,
+                        out("edx") _,
+                        options(nomem, nostack)
+                    );
+                }
+
+                if [b, c][register] >> bit & 1 == 1 {
+                    Ok(Enabled)
+                } else {
+                    Err(Unavailable)
+                }
+            },
+            Ok(Disabled) => Err(FeatureDisabled),
+            Err(error) => Err(error),
+        }
+    }
+}
+
+/// Checks whether the hardware random number generator is available.
+///
+/// Returns `Err` with [`FeatureDisabled`] when feature detection mechanism is required but is disabled.
+/// Returns error value returned by [`available`] when it returns an error.
+///
+/// [`available`]: fn@crate::features::detection::available
+/// [`FeatureDisabled`]: enum@crate::features::detection::FeatureState
+pub fn random_available() -> Result<FeatureState> {
+    target_arch_else_unimplemented_error! {
+        ["x86", "x86_64"] {
+            /* RDRAND: CPUID[1].C[30] */
+            cpuid_bit(1, 1, 30)
+        }
+    }
+}
+
+/// Checks whether the hardware entropy source (used for seeding) is available.
+///
+/// Returns `Err` with [`FeatureDisabled`] when feature detection mechanism is required but is disabled.
+/// Returns error value returned by [`available`] when it returns an error.
+///
+/// [`available`]: fn@crate::features::detection::available
+/// [`FeatureDisabled`]: enum@crate::features::detection::FeatureState
+pub fn seed_available() -> Result<FeatureState> {
+    target_arch_else_unimplemented_error! {
+        ["x86", "x86_64"] {
+            /* RDSEED: CPUID[7:0].B[18] */
+            cpuid_bit(7, 0, 18)
+        }
+    }
+}
+
+/// Returns a random value from the hardware random number generator.
+///
+/// Returns `Err` with [`Unclassified`] when the generator failed [`RETRIES`] times in a row.
+///
+/// **Note**: Availability has to be checked with [`random_available`] beforehand.
+///
+/// [`Unclassified`]: variant@crate::result::Error::Unclassified
+pub fn random() -> Result<usize> {
+    target_arch_else_unimplemented_error! {
+        ["x86", "x86_64"] {
+            for _ in 0..RETRIES {
+                let value: usize;
+                let success: u8;
+
+                unsafe {
+                    asm!(
+                        "rdrand {value}",
+                        "setc {success}",
+                        value = lateout(reg) value,
+                        success = lateout(reg_byte) success,
+                        options(nomem, nostack)
+                    );
+                }
+
+                if success == 1 {
+                    return Ok(value);
+                }
+            }
+
+            Err(Error::Unclassified)
+        }
+    }
+}
+
+/// Returns a value from the hardware entropy source.
+///
+/// Unlike [`random`], the values are not expanded by a generator, which makes them suitable for seeding.
+///
+/// Returns `Err` with [`Unclassified`] when the entropy source failed [`RETRIES`] times in a row.
+///
+/// **Note**: Availability has to be checked with [`seed_available`] beforehand.
+///
+/// [`Unclassified`]: variant@crate::result::Error::Unclassified
+pub fn seed() -> Result<usize> {
+    target_arch_else_unimplemented_error! {
+        ["x86", "x86_64"] {
+            for _ in 0..RETRIES {
+                let value: usize;
+                let success: u8;
+
+                unsafe {
+                    asm!(
+                        "rdseed {value}",
+                        "setc {success}",
+                        value = lateout(reg) value,
+                        success = lateout(reg_byte) success,
+                        options(nomem, nostack)
+                    );
+                }
+
+                if success == 1 {
+                    return Ok(value);
+                }
+
+                core::hint::spin_loop();
+            }
+
+            Err(Error::Unclassified)
+        }
+    }
+}
+
+/// Returns the value of the processor's cycle counter.
+///
+/// # Notes
+/// On IA-32 (x86) and AMD64 (x86_64) it *should* always returns [`core::result::Result::Ok`].
+pub fn cycle_counter() -> Result<u64> {
+    target_arch_else_unimplemented_error! {
+        ["x86", "x86_64"] {
+            let low: u32;
+            let high: u32;
+
+            unsafe {
+                asm!(
+                    "rdtsc",
+                    lateout("eax") low,
+                    lateout("edx") high,
+                    options(nomem, nostack)
+                );
+            }
+
+            Ok(u64::from(high) << 32 | u64::from(low))
+        }
+    }
+}
@@ -1,13 +1,21 @@
-use core::{
-    cell::UnsafeCell,
-    marker::PhantomData,
-    ops::{Deref, DerefMut},
-    sync::atomic::{AtomicBool, Ordering},
+use {
+    core::{
+        cell::UnsafeCell,
+        marker::PhantomData,
+        ops::{Deref, DerefMut},
+        ptr::{from_ref, null_mut},
+        sync::atomic::{AtomicBool, AtomicPtr, Ordering},
+    },
+    efi::boot_services::{sync::TplGuard, EfiBootServices1x0},
 };
 
+/// Spin lock which also raises the task priority level to `TPL_NOTIFY` while boot services are set.
+///
+/// Notify functions can't interrupt the lock's owner then, so allocating from them doesn't spin forever.
 #[derive(Debug, Default)]
 pub struct CoreMutex<T> {
     lock: AtomicBool,
+    boot_services: AtomicPtr<EfiBootServices1x0>,
     value: UnsafeCell<T>,
 }
 
@@ -15,18 +23,33 @@ impl<T> CoreMutex<T> {
     pub const fn new(value: T) -> Self {
         Self {
             lock: AtomicBool::new(false),
+            boot_services: AtomicPtr::new(null_mut()),
             value: UnsafeCell::new(value),
         }
     }
 
+    /// Sets the boot services used to raise the task priority level, `None` once they're exited.
+    pub fn set_boot_services(&self, boot_services: Option<&'static EfiBootServices1x0>) {
+        self.boot_services.store(
+            boot_services.map_or(null_mut(), |boot_services: &EfiBootServices1x0| {
+                from_ref(boot_services).cast_mut()
+            }),
+            Ordering::SeqCst,
+        );
+    }
+
     pub fn lock(&self) -> Lock<T> {
+        /* Raised before spinning, so the owner can't be interrupted once it has the lock */
+        let tpl_guard: Option<TplGuard<'static>> =
+            unsafe { self.boot_services.load(Ordering::SeqCst).as_ref() }.map(TplGuard::notify);
+
         while self
             .lock
             .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
             .is_err()
         {}
 
-        Lock::new(self)
+        Lock::new(self, tpl_guard)
     }
 }
 
@@ -36,13 +59,16 @@ unsafe impl<T> Sync for CoreMutex<T> where T: Send {}
 
 pub struct Lock<'a, T> {
     mutex: &'a CoreMutex<T>,
+    /* Dropped after the mutex is unlocked */
+    _tpl_guard: Option<TplGuard<'a>>,
     _phantom_data: PhantomData<&'a mut T>,
 }
 
 impl<'a, T> Lock<'a, T> {
-    const fn new(mutex: &'a CoreMutex<T>) -> Self {
+    fn new(mutex: &'a CoreMutex<T>, tpl_guard: Option<TplGuard<'a>>) -> Self {
         Self {
             mutex,
+            _tpl_guard: tpl_guard,
             _phantom_data: PhantomData,
         }
     }
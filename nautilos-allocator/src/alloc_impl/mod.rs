@@ -13,6 +13,7 @@ use {
         ptr::{null_mut, NonNull},
     },
     core_mutex::{CoreMutex, Lock},
+    efi::boot_services::EfiBootServices1x0,
 };
 
 #[global_allocator]
@@ -173,3 +174,10 @@ fn alloc_error_handler(layout: Layout) -> ! {
 pub fn initialize(memory: &'static mut [u8]) -> Result<(), AllocatorInitializationError> {
     ALLOC_IMPL.initialize(memory)
 }
+
+/// Sets the boot services used to raise the task priority level while the heap is locked.
+///
+/// **Note**: Setting it to `None` before exiting boot services is mandatory, as the level can't be changed afterwards.
+pub fn set_boot_services(boot_services: Option<&'static EfiBootServices1x0>) {
+    ALLOC_IMPL.mutex.set_boot_services(boot_services);
+}
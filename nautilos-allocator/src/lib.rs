@@ -19,4 +19,4 @@ pub use {
 mod alloc_impl;
 
 #[cfg(feature = "alloc_impl")]
-pub use alloc_impl::{initialize, AllocatorInitializationError};
+pub use alloc_impl::{initialize, set_boot_services, AllocatorInitializationError};
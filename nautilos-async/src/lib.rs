@@ -58,9 +58,20 @@ where
     block_on_with(&mut Context::from_waker(&NEW_WAKER()), future)
 }
 
-pub fn block_on_with<T>(context: &mut Context<'_>, mut future: T) -> T::Output
+pub fn block_on_with<T>(context: &mut Context<'_>, future: T) -> T::Output
 where
     T: Future,
+{
+    block_on_with_park(context, future, || ())
+}
+
+/// Polls the future until it's ready, calling `park` every time it's pending.
+///
+/// `park` is expected to block until the context's waker is woken (e.g.: `efi::boot_services::event::EventWaker::park`).
+pub fn block_on_with_park<T, P>(context: &mut Context<'_>, mut future: T, mut park: P) -> T::Output
+where
+    T: Future,
+    P: FnMut(),
 {
     loop {
         let pinned: Pin<&mut T> = unsafe { Pin::new_unchecked(&mut future) };
@@ -68,6 +79,8 @@ where
         if let Poll::Ready(result) = pinned.poll(context) {
             break result;
         }
+
+        park();
     }
 }
 
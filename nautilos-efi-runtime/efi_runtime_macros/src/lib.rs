@@ -0,0 +1,113 @@
+use proc_macro::*;
+
+fn configuration(attr: TokenStream) -> String {
+    let mut output: String = String::new();
+
+    let mut options: Vec<Vec<TokenTree>> = vec![Vec::new()];
+
+    for token in attr {
+        match &token {
+            TokenTree::Punct(punct) if punct.as_char() == ',' => options.push(Vec::new()),
+            _ => options.last_mut().unwrap().push(token),
+        }
+    }
+
+    for option in options
+        .iter()
+        .filter(|option: &&Vec<TokenTree>| !option.is_empty())
+    {
+        let key: String = if let TokenTree::Ident(key) = &option[0] {
+            key.to_string()
+        } else {
+            panic!(
+                "Error: Expected option's name instead got \"{}\"!",
+                option[0]
+            );
+        };
+
+        let value: Option<String> = match option.get(1) {
+            None => None,
+            Some(TokenTree::Punct(punct)) if punct.as_char() == '=' && option.len() > 2 => Some(
+                option[2..]
+                    .iter()
+                    .cloned()
+                    .collect::<TokenStream>()
+                    .to_string(),
+            ),
+            Some(token) => panic!("Error: Expected '=' instead got \"{}\"!", token),
+        };
+
+        output += &match (key.as_ref(), value) {
+            ("heap_size", Some(value)) => format!(".heap_size({})", value),
+            ("memory_type", Some(value)) => format!(".memory_type({})", value),
+            ("clear_screen", None) => ".clear_screen(true)".to_string(),
+            ("clear_screen", Some(value)) => format!(".clear_screen({})", value),
+            ("serial", None) => ".serial(true)".to_string(),
+            ("serial", Some(value)) => format!(".serial({})", value),
+            ("heap_size", None) | ("memory_type", None) => {
+                panic!("Error: Option \"{}\" requires a value!", key)
+            }
+            (key, _) => panic!("Error: Unknown option \"{}\" used!", key),
+        };
+    }
+
+    output
+}
+
+fn function_name(item: &TokenStream) -> String {
+    let mut tokens = item.clone().into_iter();
+
+    while let Some(token) = tokens.next() {
+        if let TokenTree::Ident(ident) = token {
+            if ident.to_string() == "fn" {
+                if let Some(TokenTree::Ident(name)) = tokens.next() {
+                    return name.to_string();
+                }
+
+                break;
+            }
+        }
+    }
+
+    panic!("Error: The entry point attribute can only be applied to functions!");
+}
+
+/// Defines the function as the image's entry point, setting up the runtime before calling it.
+///
+/// The function takes a `nautilos_efi_runtime::Context` and returns a type implementing `nautilos_efi_runtime::Termination`.
+///
+/// Options:
+/// * `heap_size = <expression>`: Size of the heap in bytes, besides the allocator's own structures.
+/// * `memory_type = <expression>`: `EfiMemoryType` of the heap's pool allocation.
+/// * `clear_screen`: Clears the console before calling the function.
+/// * `serial`: Mirrors the output to the first serial port found. Other ports can be chosen through `Context::set_serial_output`.
+#[proc_macro_attribute]
+pub fn efi_entry(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let configuration: String = configuration(attr);
+    let name: String = function_name(&item);
+
+    format!(
+        "{item}
+
+#[no_mangle]
+extern \"efiapi\" fn efi_main(
+    image_handle: ::nautilos_efi_runtime::efi::EfiHandle,
+    system_table: *mut ::nautilos_efi_runtime::efi::EfiSystemTable,
+) -> ::nautilos_efi_runtime::efi::EfiStatus {{
+    /* The firmware passes a pointer to the system table, which gets verified before use */
+    unsafe {{
+        ::nautilos_efi_runtime::start(
+            image_handle,
+            system_table,
+            ::nautilos_efi_runtime::Configuration::new(){configuration},
+            {name},
+        )
+    }}
+}}",
+        item = item,
+        configuration = configuration,
+        name = name,
+    )
+    .parse()
+    .unwrap()
+}
@@ -0,0 +1,196 @@
+use {
+    crate::{
+        output::{Logger, SERIAL_OUT},
+        timestamps::SetupTimestamps,
+    },
+    core::{
+        ptr::{from_ref, null_mut},
+        sync::atomic::Ordering,
+    },
+    efi::{
+        boot_services::{types::memory::EfiMemoryType, EfiBootServices},
+        protocols::console::EfiSerialIoProtocol,
+        runtime_services::EfiRuntimeServices,
+        EfiHandle, EfiStatusEnum, EfiStatusError, EfiSystemTable,
+    },
+};
+
+/// Configuration of the runtime, set through [`efi_entry`](crate::efi_entry)'s options.
+#[derive(Debug, Clone, Copy)]
+pub struct Configuration {
+    heap_size: usize,
+    memory_type: EfiMemoryType,
+    clear_screen: bool,
+    serial: bool,
+}
+
+impl Configuration {
+    /// Returns the default configuration: 16 MB heap in loader data memory, without clearing the screen or mirroring the output to a serial port.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            heap_size: 0x0100_0000,
+            memory_type: EfiMemoryType::EfiLoaderData,
+            clear_screen: false,
+            serial: false,
+        }
+    }
+
+    /// Sets the size of the heap, excluding the allocator's own structures.
+    #[must_use]
+    pub const fn heap_size(mut self, heap_size: usize) -> Self {
+        self.heap_size = heap_size;
+
+        self
+    }
+
+    /// Sets the memory type of the heap's pool allocation.
+    #[must_use]
+    pub const fn memory_type(mut self, memory_type: EfiMemoryType) -> Self {
+        self.memory_type = memory_type;
+
+        self
+    }
+
+    /// Sets whether the console is cleared before the entry point is called.
+    #[must_use]
+    pub const fn clear_screen(mut self, clear_screen: bool) -> Self {
+        self.clear_screen = clear_screen;
+
+        self
+    }
+
+    /// Sets whether the output is mirrored to the first serial port found.
+    ///
+    /// Where the console output is already redirected to the serial port (e.g.: OVMF), this duplicates every line.
+    #[must_use]
+    pub const fn serial(mut self, serial: bool) -> Self {
+        self.serial = serial;
+
+        self
+    }
+
+    #[cfg(feature = "global_allocator")]
+    pub(crate) const fn get_heap_size(&self) -> usize {
+        self.heap_size
+    }
+
+    #[cfg(feature = "global_allocator")]
+    pub(crate) const fn get_memory_type(&self) -> EfiMemoryType {
+        self.memory_type
+    }
+
+    pub(crate) const fn get_clear_screen(&self) -> bool {
+        self.clear_screen
+    }
+
+    pub(crate) const fn get_serial(&self) -> bool {
+        self.serial
+    }
+}
+
+impl Default for Configuration {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Passed to the image's entry point, once the runtime is set up.
+pub struct Context {
+    image_handle: EfiHandle,
+    system_table: &'static mut EfiSystemTable,
+    logger: Logger,
+    state_storing: bool,
+    setup_timestamps: SetupTimestamps,
+}
+
+impl Context {
+    pub(crate) fn new(
+        image_handle: EfiHandle,
+        system_table: &'static mut EfiSystemTable,
+        state_storing: bool,
+        setup_timestamps: SetupTimestamps,
+    ) -> Self {
+        Self {
+            image_handle,
+            system_table,
+            logger: Logger::new(),
+            state_storing,
+            setup_timestamps,
+        }
+    }
+
+    /// Returns the handle of the image.
+    #[must_use]
+    pub fn image_handle(&self) -> EfiHandle {
+        self.image_handle
+    }
+
+    /// Returns the verified system table.
+    #[must_use]
+    pub fn system_table(&self) -> &EfiSystemTable {
+        self.system_table
+    }
+
+    /// Returns the verified system table.
+    pub fn system_table_mut(&mut self) -> &mut EfiSystemTable {
+        self.system_table
+    }
+
+    /// Returns the verified boot services table.
+    #[must_use]
+    pub fn boot_services(&self) -> &'static EfiBootServices {
+        self.system_table.boot_services()
+    }
+
+    /// Returns the verified runtime services table.
+    #[must_use]
+    pub fn runtime_services(&self) -> &'static EfiRuntimeServices {
+        self.system_table.runtime_services()
+    }
+
+    /// Returns the logger writing to the console output and the serial port.
+    #[must_use]
+    pub fn logger(&self) -> &Logger {
+        &self.logger
+    }
+
+    /// Returns whether the state storing mechanism is enabled.
+    ///
+    /// Images which depend on it have to check it, as the runtime only warns when it's unavailable.
+    #[must_use]
+    pub fn state_storing_enabled(&self) -> bool {
+        self.state_storing
+    }
+
+    /// Mirrors the output to the serial port of the handle, or stops mirroring it when `None` is given.
+    ///
+    /// # Errors
+    /// Returns the status of retrieving the handle's serial I/O protocol; `EfiDeviceError` when the firmware returns no interface.
+    pub fn set_serial_output(&self, handle: Option<EfiHandle>) -> Result<(), EfiStatusError> {
+        let serial_io: *mut EfiSerialIoProtocol = match handle {
+            None => null_mut(),
+            Some(handle) => match self
+                .boot_services()
+                .handle_protocol::<EfiSerialIoProtocol>(handle)
+            {
+                Ok(
+                    EfiStatusEnum::Success(Ok(serial_io))
+                    | EfiStatusEnum::Warning(_, Ok(serial_io)),
+                ) => from_ref(serial_io).cast_mut(),
+                Ok(EfiStatusEnum::Error(status, ())) => return Err(status),
+                Err(_) => return Err(EfiStatusError::EfiDeviceError),
+            },
+        };
+
+        SERIAL_OUT.store(serial_io, Ordering::Relaxed);
+
+        Ok(())
+    }
+
+    /// Returns when each step of setting up the runtime completed, for images profiling their start up.
+    #[must_use]
+    pub fn setup_timestamps(&self) -> &SetupTimestamps {
+        &self.setup_timestamps
+    }
+}
@@ -0,0 +1,43 @@
+//! This crate sets up the runtime shared by all EFI images.
+//!
+//! Before the image's entry point (marked with [`efi_entry`]) is called, it handles:
+//! 1. Verifying the EFI tables passed by the firmware.
+//! 1. Publishing the console output (and the serial port, when requested) for the [`Logger`] and the panic handler.
+//! 1. Enabling the feature detection and state storing mechanisms.
+//! 1. Allocating and initializing the heap backing the global allocator, with the `global_allocator` feature.
+//!
+//! When the firmware provides EFI's timestamp protocol, the steps following the tables' verification are timed through [`SetupTimestamps`].
+//!
+//! The entry point receives a [`Context`] and it's returned value is converted to the image's exit status through [`Termination`].
+//!
+//! ```ignore
+//! #[nautilos_efi_runtime::efi_entry(heap_size = 0x0040_0000)]
+//! fn main(context: Context) -> Result<(), EfiStatusError> {
+//!     context.logger().log(format_args!("Hello from {:?}!", context.image_handle()));
+//!
+//!     Ok(())
+//! }
+//! ```
+
+#![no_std]
+#![doc(html_no_source)]
+#![feature(panic_info_message, never_type)]
+#![forbid(warnings, missing_docs, clippy::pedantic)]
+
+mod context;
+mod output;
+#[cfg(feature = "panic_handler")]
+mod panic_handling;
+mod setup;
+mod termination;
+mod timestamps;
+
+pub use {
+    context::{Configuration, Context},
+    efi,
+    efi_runtime_macros::efi_entry,
+    output::{Logger, BOOT_SERVICES, CON_OUT, SERIAL_OUT},
+    setup::start,
+    termination::Termination,
+    timestamps::{SetupStep, SetupTimestamps},
+};
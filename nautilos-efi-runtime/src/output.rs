@@ -0,0 +1,108 @@
+use {
+    core::{
+        fmt::{Arguments, Result as FmtResult, Write},
+        mem::align_of,
+        sync::atomic::{AtomicPtr, Ordering},
+    },
+    efi::{
+        boot_services::{
+            sync::{Mutex, MutexGuard},
+            types::task_priority::EfiTaskPriorityLevel,
+            EfiBootServices1x0,
+        },
+        protocols::console::{
+            EfiAnsiTextOutput, EfiSerialIoProtocol, EfiSimpleTextOutputProtocol, EfiTextAttribute,
+        },
+    },
+};
+
+/// Stores pointer to EFI's console output protocol interface.
+///
+/// **Note**: Since dangling pointers **can not** be validated, setting it to a null pointer while doing any memory map changes is mandatory.
+pub static CON_OUT: AtomicPtr<EfiSimpleTextOutputProtocol> = AtomicPtr::new(core::ptr::null_mut());
+
+/// Stores pointer to EFI's serial I/O protocol interface the output is mirrored to.
+///
+/// Headless machines only expose their output through the serial port.
+pub static SERIAL_OUT: AtomicPtr<EfiSerialIoProtocol> = AtomicPtr::new(core::ptr::null_mut());
+
+/// Stores pointer to EFI's boot services, which the [`Logger`]'s writes are serialized through.
+///
+/// **Note**: Setting it to a null pointer before exiting boot services is mandatory, as the task priority level can't be changed afterwards.
+pub static BOOT_SERVICES: AtomicPtr<EfiBootServices1x0> = AtomicPtr::new(core::ptr::null_mut());
+
+/* Serial I/O can't be used above "TPL_CALLBACK" */
+#[doc(hidden)]
+static OUTPUT: Mutex<()> = Mutex::new(EfiTaskPriorityLevel::CALLBACK, ());
+
+/// Writes formatted strings on the console output stored in [`CON_OUT`] and the serial port stored in [`SERIAL_OUT`].
+///
+/// ANSI escape sequences are interpreted for the console and passed as they are to the serial port.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Logger {
+    _private: (),
+}
+
+impl Logger {
+    /// Returns logger writing to the outputs set up by the runtime.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { _private: () }
+    }
+
+    /// Prints the formatted string.
+    ///
+    /// While boot services are available, notify functions can't interleave their output with the string's.
+    pub fn print(&self, args: Arguments<'_>) {
+        /* Notify functions above "TPL_CALLBACK" can still interrupt the owner and write unserialized */
+        let _guard: Option<MutexGuard<'_, ()>> =
+            unsafe { BOOT_SERVICES.load(Ordering::Relaxed).as_ref() }
+                .and_then(|boot_services: &EfiBootServices1x0| OUTPUT.try_lock(boot_services));
+
+        let con_out: *mut EfiSimpleTextOutputProtocol = CON_OUT.load(Ordering::Relaxed);
+
+        if con_out.align_offset(align_of::<EfiSimpleTextOutputProtocol>()) == 0 {
+            if let Some(con_out) = unsafe { con_out.as_ref() } {
+                let _ =
+                    EfiAnsiTextOutput::with_default_attribute(con_out, EfiTextAttribute::default())
+                        .write_fmt(args);
+            }
+        }
+
+        let serial_out: *mut EfiSerialIoProtocol = SERIAL_OUT.load(Ordering::Relaxed);
+
+        if serial_out.align_offset(align_of::<EfiSerialIoProtocol>()) == 0 {
+            if let Some(serial_out) = unsafe { serial_out.as_mut() } {
+                let _ = serial_out.write_fmt(args);
+            }
+        }
+    }
+
+    /// Prints the formatted string prefixed with `[DEBUG] ` on a new line.
+    pub fn debug(&self, args: Arguments<'_>) {
+        self.print(format_args!("[DEBUG] {args}\n"));
+    }
+
+    /// Prints the formatted string prefixed with `[LOG] ` on a new line.
+    pub fn log(&self, args: Arguments<'_>) {
+        self.print(format_args!("[LOG] {args}\n"));
+    }
+
+    /// Prints the formatted string prefixed with `[WARN] ` on a new line.
+    pub fn warn(&self, args: Arguments<'_>) {
+        self.print(format_args!("[WARN] {args}\n"));
+    }
+
+    /// Prints the formatted string prefixed with `[ERROR] ` on a new line.
+    pub fn error(&self, args: Arguments<'_>) {
+        self.print(format_args!("[ERROR] {args}\n"));
+    }
+}
+
+impl Write for Logger {
+    fn write_str(&mut self, string: &str) -> FmtResult {
+        self.print(format_args!("{string}"));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,68 @@
+//! This module defines a basic EFI-compatible panic handler.
+
+use core::{
+    fmt::Write,
+    mem::align_of,
+    panic::{Location, PanicInfo},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use efi::protocols::console::{EfiSerialIoProtocol, EfiSimpleTextOutputProtocol};
+
+use crate::output::{CON_OUT, SERIAL_OUT};
+
+#[doc(hidden)]
+static IN_PANIC: AtomicBool = AtomicBool::new(false);
+
+/// Panic handler's implementation.
+///
+/// Acquires the pointer to the console output protocol's interface from [`CON_OUT`] and the serial I/O protocol's one from [`SERIAL_OUT`].
+/// It checks whether the pointers are non-null & properly aligned.
+#[panic_handler]
+fn panic_handler(panic_info: &PanicInfo) -> ! {
+    /* Stops recursive panics and allows multi-threading */
+    while IN_PANIC
+        .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+        .is_err()
+    {}
+
+    let (file, line, column): (&str, u32, u32) = panic_info
+        .location()
+        .map_or(("", 0, 0), |location: &Location| {
+            (location.file(), location.line(), location.column())
+        });
+
+    let message: core::fmt::Arguments = if let Some(message) = panic_info.message() {
+        *message
+    } else {
+        format_args!("(No message)")
+    };
+
+    let report = |output: &mut dyn Write| {
+        let _ = output.write_fmt(format_args!(
+            "\nPanic [{} -> Line {} : Column {}]\nError message: {}",
+            file, line, column, message
+        ));
+    };
+
+    let con_out: *mut EfiSimpleTextOutputProtocol = CON_OUT.load(Ordering::SeqCst);
+
+    if con_out.align_offset(align_of::<EfiSimpleTextOutputProtocol>()) == 0 {
+        if let Some(con_out) = unsafe { con_out.as_mut() } {
+            report(con_out);
+        }
+    }
+
+    let serial_out: *mut EfiSerialIoProtocol = SERIAL_OUT.load(Ordering::SeqCst);
+
+    if serial_out.align_offset(align_of::<EfiSerialIoProtocol>()) == 0 {
+        if let Some(serial_out) = unsafe { serial_out.as_mut() } {
+            report(serial_out);
+        }
+    }
+
+    /* Reset state in case of multithreading */
+    IN_PANIC.store(false, Ordering::SeqCst);
+
+    loop {}
+}
@@ -0,0 +1,223 @@
+use {
+    crate::{
+        context::{Configuration, Context},
+        output::{Logger, BOOT_SERVICES, CON_OUT, SERIAL_OUT},
+        termination::Termination,
+        timestamps::{SetupStep, SetupTimestamps},
+    },
+    core::sync::atomic::Ordering,
+    efi::{
+        boot_services::{EfiBootServices, EfiBootServices1x0},
+        protocols::console::EfiSerialIoProtocol,
+        EfiHandle, EfiStatus, EfiStatusEnum, EfiStatusError, EfiSystemTable,
+    },
+};
+
+#[cfg(feature = "global_allocator")]
+use {
+    core::slice::from_raw_parts_mut,
+    efi::VoidPtr,
+    nautilos_allocator::{
+        initialize as initialize_allocator, set_boot_services as set_allocator_boot_services, Heap,
+    },
+};
+
+/// Sets up the runtime and calls the entry point, converting it's returned value to the image's exit status.
+///
+/// Called by the `efi_main` function generated by [`efi_entry`](crate::efi_entry).
+/// # Panics
+/// Panics when the services' tables are invalid or when setting up the heap fails.
+/// # Safety
+/// Must be called once, with the arguments passed by the firmware to the image's entry point.
+pub unsafe fn start<F, R>(
+    image_handle: EfiHandle,
+    system_table: *mut EfiSystemTable,
+    configuration: Configuration,
+    main: F,
+) -> EfiStatus
+where
+    F: FnOnce(Context) -> R,
+    R: Termination,
+{
+    let system_table: &'static mut EfiSystemTable = match system_table.as_mut() {
+        Some(system_table) => system_table,
+        None => return EfiStatusError::EfiInvalidParameter.into(),
+    };
+
+    /* Verify that the system table is valid, as the console can't be trusted otherwise */
+    if !system_table.header().verify_table() {
+        return EfiStatusError::EfiCompromisedData.into();
+    }
+
+    let logger: Logger = Logger::new();
+
+    /* Set the output interface for the panic handler and the logger */
+    if let Some(con_out) = system_table.con_out_mut() {
+        CON_OUT.store(con_out, Ordering::Relaxed);
+
+        if configuration.get_clear_screen() {
+            if let EfiStatusEnum::Error(status, ()) = con_out.clear_screen() {
+                logger.warn(format_args!(
+                    "(EFI) Clearing screen failed with error: {status:?}"
+                ));
+            }
+        }
+    }
+
+    /* Verify the services' tables separately to report which one is invalid */
+    assert!(
+        system_table.boot_services().header().verify_table(),
+        "Boot services table's checksum is invalid!"
+    );
+    assert!(
+        system_table.runtime_services().verify_table(),
+        "Runtime services table's checksum is invalid!"
+    );
+
+    /* Serialize the output, now that the boot services can be trusted */
+    BOOT_SERVICES.store(
+        core::ptr::from_ref::<EfiBootServices1x0>(system_table.boot_services()).cast_mut(),
+        Ordering::Relaxed,
+    );
+
+    let mut timestamps: SetupTimestamps = SetupTimestamps::new(system_table.boot_services());
+
+    timestamps.record(SetupStep::ServicesVerified);
+
+    /* Mirror the output to the serial port (used on headless machines), when requested and there is one */
+    if configuration.get_serial() {
+        if let Some(serial_io) = locate_serial_io(system_table.boot_services()) {
+            SERIAL_OUT.store(core::ptr::from_ref(serial_io).cast_mut(), Ordering::Relaxed);
+        }
+    }
+
+    timestamps.record(SetupStep::SerialOutputPublished);
+
+    setup_detection_mechanism(logger);
+
+    timestamps.record(SetupStep::FeatureDetectionSetUp);
+
+    let state_storing: bool = setup_state_storing(logger);
+
+    timestamps.record(SetupStep::StateStoringSetUp);
+
+    #[cfg(feature = "global_allocator")]
+    {
+        setup_allocator(system_table.boot_services(), configuration);
+
+        timestamps.record(SetupStep::HeapInitialized);
+    }
+
+    main(Context::new(
+        image_handle,
+        system_table,
+        state_storing,
+        timestamps,
+    ))
+    .report(logger)
+}
+
+fn locate_serial_io(boot_services: &EfiBootServices) -> Option<&'static EfiSerialIoProtocol> {
+    if let Ok(EfiStatusEnum::Success(Ok(serial_io))) = boot_services
+        .revision_1_1()?
+        .locate_protocol::<EfiSerialIoProtocol>(None)
+    {
+        Some(serial_io)
+    } else {
+        None
+    }
+}
+
+fn setup_detection_mechanism(logger: Logger) {
+    use native::{
+        features::detection::{enable, FeatureState},
+        Error,
+    };
+
+    match enable() {
+        Ok(FeatureState::Enabled) => (),
+        Ok(FeatureState::Disabled) => logger.warn(format_args!(
+            "Feature detection mechanism couldn't be enabled. It may be required later."
+        )),
+        Err(Error::Unavailable) => logger.warn(format_args!(
+            "Feature detection mechanism unavailable. It may be required later."
+        )),
+        Err(error) => {
+            panic!(
+                "Error occured while enabling feature detection mechanism!\nError: {:?}",
+                error
+            )
+        }
+    }
+}
+
+/// Returns whether the state storing mechanism got enabled.
+fn setup_state_storing(logger: Logger) -> bool {
+    use native::{
+        features::detection::{
+            state_storing::{available, enable},
+            FeatureState,
+        },
+        Error,
+    };
+
+    match available() {
+        Ok(FeatureState::Enabled) => true,
+        Ok(FeatureState::Disabled) => match enable() {
+            Ok(FeatureState::Enabled) => true,
+            Ok(FeatureState::Disabled) => {
+                logger.warn(format_args!("State storing mechanism couldn't be enabled!"));
+
+                false
+            }
+            Err(error) => {
+                panic!(
+                    "Error occured while enabling state storing mechanism!\nError: {:?}",
+                    error
+                )
+            }
+        },
+        Err(Error::Unavailable) => {
+            logger.warn(format_args!("State storing mechanism unavailable!"));
+
+            false
+        }
+        Err(Error::FeatureDisabled) => {
+            logger.warn(format_args!("Feature detection mechanism required to determine whether state storing is available, but is disabled!"));
+
+            false
+        }
+        Err(error) => {
+            panic!(
+                "Error occured while testing for state storing mechanism!\nError: {:?}",
+                error
+            )
+        }
+    }
+}
+
+#[cfg(feature = "global_allocator")]
+fn setup_allocator(boot_services: &'static EfiBootServices1x0, configuration: Configuration) {
+    let size: usize = Heap::UNALIGNED_REQUIRED_INITIAL_SIZE + configuration.get_heap_size();
+
+    let address: VoidPtr = match boot_services
+        .allocate_pool(configuration.get_memory_type(), size)
+        .unfold()
+    {
+        Ok((_, address)) => address,
+        Err((status, ())) => {
+            panic!(
+                "(EFI) Error occured while allocating the heap!\nError: {:?}",
+                status
+            )
+        }
+    };
+
+    if let Err(error) =
+        initialize_allocator(unsafe { from_raw_parts_mut(address as *mut u8, size) })
+    {
+        panic!("Error occured while initializing heap!\nError: {:?}", error);
+    }
+
+    set_allocator_boot_services(Some(boot_services));
+}
@@ -0,0 +1,57 @@
+use {
+    crate::output::Logger,
+    core::fmt::Debug,
+    efi::{EfiStatus, EfiStatusEnum},
+};
+
+/// Implemented by the types the entry point can return, converting them to the image's exit status.
+pub trait Termination {
+    /// Returns the exit status, reporting errors through the logger.
+    fn report(self, logger: Logger) -> EfiStatus;
+}
+
+impl Termination for () {
+    fn report(self, _: Logger) -> EfiStatus {
+        EfiStatus::success()
+    }
+}
+
+impl Termination for ! {
+    fn report(self, _: Logger) -> EfiStatus {
+        self
+    }
+}
+
+impl Termination for EfiStatus {
+    fn report(self, _: Logger) -> EfiStatus {
+        self
+    }
+}
+
+impl<T, E> Termination for EfiStatusEnum<T, E> {
+    fn report(self, logger: Logger) -> EfiStatus {
+        if let EfiStatusEnum::Error(status, _) = &self {
+            logger.error(format_args!("(EFI) Exiting with error: {status:?}"));
+        }
+
+        EfiStatus::from(&self)
+    }
+}
+
+/// Errors are converted with their [`Into<EfiStatus>`] implementation, after being logged.
+impl<T, E> Termination for Result<T, E>
+where
+    T: Termination,
+    E: Into<EfiStatus> + Debug,
+{
+    fn report(self, logger: Logger) -> EfiStatus {
+        match self {
+            Ok(value) => value.report(logger),
+            Err(error) => {
+                logger.error(format_args!("Exiting with error: {error:?}"));
+
+                error.into()
+            }
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use efi::{
+    boot_services::EfiBootServices, protocols::timestamp::EfiTimestampProtocol, EfiStatusEnum,
+};
+
+/// Defines the steps of setting up the runtime, in the order they complete.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SetupStep {
+    /// The system table and the services' tables are verified.
+    ServicesVerified,
+    /// The output is mirrored to the serial port, when requested and there is one.
+    SerialOutputPublished,
+    /// The feature detection mechanism is enabled, or found unavailable.
+    FeatureDetectionSetUp,
+    /// The state storing mechanism is enabled, or found unavailable.
+    StateStoringSetUp,
+    /// The heap is allocated and initialized. Only recorded with the `global_allocator` feature.
+    HeapInitialized,
+}
+
+impl SetupStep {
+    /// All the steps, in the order they complete.
+    pub const ALL: [Self; 5] = [
+        Self::ServicesVerified,
+        Self::SerialOutputPublished,
+        Self::FeatureDetectionSetUp,
+        Self::StateStoringSetUp,
+        Self::HeapInitialized,
+    ];
+}
+
+/// Values of EFI's timestamp counter when each setup step completed.
+///
+/// Nothing is recorded when the firmware doesn't provide the timestamp protocol.
+#[derive(Clone, Copy)]
+pub struct SetupTimestamps {
+    timestamp: Option<&'static EfiTimestampProtocol>,
+    timestamps: [Option<u64>; SetupStep::ALL.len()],
+}
+
+impl SetupTimestamps {
+    /// Locates the timestamp protocol, which requires the boot services' table to be verified.
+    pub(crate) fn new(boot_services: &EfiBootServices) -> Self {
+        let timestamp: Option<&'static EfiTimestampProtocol> = match boot_services
+            .revision_1_1()
+            .map(|services| services.locate_protocol::<EfiTimestampProtocol>(None))
+        {
+            Some(Ok(EfiStatusEnum::Success(Ok(timestamp)))) => Some(timestamp),
+            _ => None,
+        };
+
+        Self {
+            timestamp,
+            timestamps: [None; SetupStep::ALL.len()],
+        }
+    }
+
+    pub(crate) fn record(&mut self, step: SetupStep) {
+        self.timestamps[step as usize] = self
+            .timestamp
+            .map(|timestamp: &EfiTimestampProtocol| timestamp.get_timestamp());
+    }
+
+    /// Returns the timestamp of the step, `None` if it wasn't recorded.
+    #[must_use]
+    pub fn get(&self, step: SetupStep) -> Option<u64> {
+        self.timestamps[step as usize]
+    }
+
+    /// Returns the recorded steps with their timestamps, in the order they completed.
+    pub fn recorded(&self) -> impl Iterator<Item = (SetupStep, u64)> + '_ {
+        SetupStep::ALL
+            .iter()
+            .filter_map(move |&step: &SetupStep| Some((step, self.get(step)?)))
+    }
+}
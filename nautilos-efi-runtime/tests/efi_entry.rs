@@ -0,0 +1,138 @@
+/* The runtime's allocator and panic handler would replace the test harness' own, so this only runs with "--no-default-features" */
+#![cfg(not(any(feature = "global_allocator", feature = "panic_handler")))]
+
+use {
+    efi::{EfiHandle, EfiStatusEnum, EfiStatusError, EfiSystemTable},
+    efi_simulator::Simulator,
+    nautilos_efi_runtime::{efi_entry, Context, SetupStep},
+    std::{
+        cell::{Cell, RefCell},
+        ptr::{null_mut, read},
+    },
+};
+
+thread_local! {
+    static FAILURE: Cell<Option<EfiStatusError>> = const { Cell::new(None) };
+    static ENTERED: RefCell<Option<(EfiHandle, Vec<SetupStep>)>> = const { RefCell::new(None) };
+}
+
+#[efi_entry(clear_screen, serial, heap_size = 0x1000)]
+fn entry(context: Context) -> Result<(), EfiStatusError> {
+    context.logger().log(format_args!("Entered"));
+
+    ENTERED.with(|entered: &RefCell<Option<(EfiHandle, Vec<SetupStep>)>>| {
+        *entered.borrow_mut() = Some((
+            context.image_handle(),
+            context
+                .setup_timestamps()
+                .recorded()
+                .map(|(step, _): (SetupStep, u64)| step)
+                .collect(),
+        ))
+    });
+
+    match FAILURE.with(Cell::get) {
+        Some(status) => Err(status),
+        None => Ok(()),
+    }
+}
+
+fn run(simulator: &Simulator) -> EfiStatusEnum {
+    let system_table: *mut EfiSystemTable =
+        simulator.system_table() as *const EfiSystemTable as *mut EfiSystemTable;
+
+    efi_main(simulator.serial_port_handle(0).unwrap(), system_table).into_enum()
+}
+
+fn entered() -> Option<(EfiHandle, Vec<SetupStep>)> {
+    ENTERED
+        .with(|entered: &RefCell<Option<(EfiHandle, Vec<SetupStep>)>>| entered.borrow_mut().take())
+}
+
+/* The runtime publishes the outputs through statics, so the scenarios run one after another */
+#[test]
+fn efi_entry_sets_up_the_runtime() {
+    let simulator: Simulator = Simulator::builder()
+        .serial_port()
+        .timestamp()
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        efi_main(simulator.serial_port_handle(0).unwrap(), null_mut()).into_enum(),
+        EfiStatusEnum::Error(EfiStatusError::EfiInvalidParameter, ())
+    );
+
+    /* Tables failing the checksum aren't trusted, not even for reporting it */
+    let mut corrupted: EfiSystemTable = unsafe { read(simulator.system_table()) };
+
+    /* The header's CRC32 follows the signature, the revision and the header's size */
+    unsafe { *(&mut corrupted as *mut EfiSystemTable as *mut u32).add(4) ^= 1 };
+
+    assert_eq!(
+        efi_main(simulator.serial_port_handle(0).unwrap(), &mut corrupted).into_enum(),
+        EfiStatusEnum::Error(EfiStatusError::EfiCompromisedData, ())
+    );
+    assert!(entered().is_none());
+    assert!(simulator.console_output().is_empty());
+
+    assert!(simulator
+        .system_table()
+        .con_out()
+        .unwrap()
+        .output_string("Before the entry point\n")
+        .is_success());
+
+    assert_eq!(run(&simulator), EfiStatusEnum::Success(()));
+
+    let (image_handle, steps): (EfiHandle, Vec<SetupStep>) = entered().unwrap();
+
+    assert_eq!(image_handle, simulator.serial_port_handle(0).unwrap());
+    /* The heap is only set up with the global allocator */
+    assert_eq!(
+        steps,
+        [
+            SetupStep::ServicesVerified,
+            SetupStep::SerialOutputPublished,
+            SetupStep::FeatureDetectionSetUp,
+            SetupStep::StateStoringSetUp,
+        ]
+    );
+
+    /* The screen is cleared before the entry point is called */
+    let screen: Vec<String> = simulator.console_screen();
+
+    assert!(!screen
+        .iter()
+        .any(|row: &String| row.contains("Before the entry point")));
+    assert!(screen.iter().any(|row: &String| row == "[LOG] Entered"));
+
+    /* The output is mirrored to the serial port, with it's line endings */
+    let serial_output: String = String::from_utf8(simulator.serial_output(0).unwrap()).unwrap();
+
+    assert!(serial_output.contains("[LOG] Entered\r\n"));
+
+    /* Only one simulator can be active per thread */
+    drop(simulator);
+
+    /* Errors are logged and become the exit status */
+    let simulator: Simulator = Simulator::builder().serial_port().build().unwrap();
+
+    FAILURE.with(|failure: &Cell<Option<EfiStatusError>>| {
+        failure.set(Some(EfiStatusError::EfiAborted))
+    });
+
+    assert_eq!(
+        run(&simulator),
+        EfiStatusEnum::Error(EfiStatusError::EfiAborted, ())
+    );
+
+    /* Nothing is timed without the timestamp protocol */
+    assert!(entered().unwrap().1.is_empty());
+    assert!(String::from_utf8(simulator.serial_output(0).unwrap())
+        .unwrap()
+        .contains("[ERROR] Exiting with error: EfiAborted\r\n"));
+    assert!(simulator
+        .console_output()
+        .contains("[ERROR] Exiting with error: EfiAborted"));
+}
@@ -2,6 +2,7 @@
 pub enum OsMemoryType {
     LoaderHeap,
     HandlesBuffer,
+    KernelHandoff,
 }
 
 impl From<OsMemoryType> for [u8; core::mem::size_of::<OsMemoryType>()] {
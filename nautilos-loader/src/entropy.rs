@@ -0,0 +1,227 @@
+//! This module provides randomness until the kernel's own random number generator is available.
+//!
+//! The seed is taken from the first available source in the following order:
+//! 1. EFI's random number generator protocol.
+//! 1. The platform's hardware entropy source.
+//! 1. The platform's hardware random number generator.
+//! 1. Timing jitter measured through the processor's cycle counter.
+//!
+//! The seed is then expanded by [`Csprng`], which also derives seed material for the kernel's generator.
+
+use {
+    core::{hint::spin_loop, mem::size_of},
+    efi::{boot_services::EfiBootServices, protocols::rng::EfiRngProtocol, EfiStatusEnum},
+    native::{features::detection::FeatureState, random},
+};
+
+/// Size of seeds in bytes.
+pub const SEED_SIZE: usize = 32;
+
+/// Number of cycle counter samples mixed when falling back to timing jitter.
+const JITTER_SAMPLES: usize = 4096;
+
+/// "expand 32-byte k"
+const CHACHA_CONSTANTS: [u32; 4] = [0x6170_7865, 0x3320_646E, 0x7962_2D32, 0x6B20_6574];
+
+/// Defines the sources the seed can be taken from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    /// EFI's `EFI_RNG_PROTOCOL`.
+    RngProtocol,
+    /// The platform's hardware entropy source (e.g.: `RDSEED` on `x86`).
+    HardwareSeed,
+    /// The platform's hardware random number generator (e.g.: `RDRAND` on `x86`).
+    HardwareRandom,
+    /// Timing jitter of the processor's cycle counter.
+    CycleCounterJitter,
+}
+
+/// Gathers a seed from the first available source.
+#[must_use]
+pub fn gather(boot_services: &EfiBootServices) -> ([u8; SEED_SIZE], Source) {
+    let mut seed: [u8; SEED_SIZE] = [0; SEED_SIZE];
+
+    if from_rng_protocol(boot_services, &mut seed) {
+        (seed, Source::RngProtocol)
+    } else if matches!(random::seed_available(), Ok(FeatureState::Enabled))
+        && from_hardware(random::seed, &mut seed)
+    {
+        (seed, Source::HardwareSeed)
+    } else if matches!(random::random_available(), Ok(FeatureState::Enabled))
+        && from_hardware(random::random, &mut seed)
+    {
+        (seed, Source::HardwareRandom)
+    } else {
+        (from_jitter(), Source::CycleCounterJitter)
+    }
+}
+
+fn from_rng_protocol(boot_services: &EfiBootServices, seed: &mut [u8; SEED_SIZE]) -> bool {
+    if let Some(boot_services) = boot_services.revision_1_1() {
+        if let Ok(EfiStatusEnum::Success(Ok(rng))) =
+            boot_services.locate_protocol::<EfiRngProtocol>(None)
+        {
+            return rng.get_rng(None, seed).is_success();
+        }
+    }
+
+    false
+}
+
+fn from_hardware(source: fn() -> native::Result<usize>, seed: &mut [u8; SEED_SIZE]) -> bool {
+    for chunk in seed.chunks_mut(size_of::<usize>()) {
+        match source() {
+            Ok(value) => chunk.copy_from_slice(&value.to_le_bytes()[..chunk.len()]),
+            Err(_) => return false,
+        }
+    }
+
+    true
+}
+
+fn from_jitter() -> [u8; SEED_SIZE] {
+    let mut pool: [u32; 16] = [0; 16];
+
+    let mut previous: u64 = random::cycle_counter().unwrap_or(0);
+
+    for index in 0..JITTER_SAMPLES {
+        /* Makes the duration of the next sample depend on the current one */
+        for _ in 0..(previous & 0xF) {
+            spin_loop();
+        }
+
+        let current: u64 = random::cycle_counter().unwrap_or(0);
+
+        let mut delta: [u32; 2] = [0; 2];
+
+        read_words(&current.wrapping_sub(previous).to_le_bytes(), &mut delta);
+
+        pool[index % 16] = pool[index % 16].rotate_left(7) ^ delta[0] ^ delta[1];
+        pool[(index + 1) % 16] = pool[(index + 1) % 16].wrapping_add(pool[index % 16]);
+
+        previous = current;
+    }
+
+    let mut key: [u32; 8] = [0; 8];
+
+    for (index, word) in key.iter_mut().enumerate() {
+        *word = pool[index] ^ pool[index + 8];
+    }
+
+    let mut seed: [u8; SEED_SIZE] = [0; SEED_SIZE];
+
+    write_words(&block(&key, 0)[..8], &mut seed);
+
+    seed
+}
+
+/// `ChaCha20`-based cryptographically secure pseudo-random number generator.
+///
+/// The key is replaced after every request, so previously returned data can't be recovered from the generator's state.
+pub struct Csprng {
+    key: [u32; 8],
+}
+
+impl Csprng {
+    /// Creates new instance seeded with the given seed.
+    #[must_use]
+    pub fn new(seed: &[u8; SEED_SIZE]) -> Self {
+        let mut key: [u32; 8] = [0; 8];
+
+        read_words(seed, &mut key);
+
+        Self { key }
+    }
+
+    /// Creates new instance seeded through [`gather`] and returns the source of the seed.
+    #[must_use]
+    pub fn from_entropy(boot_services: &EfiBootServices) -> (Self, Source) {
+        let (seed, source): ([u8; SEED_SIZE], Source) = gather(boot_services);
+
+        (Self::new(&seed), source)
+    }
+
+    /// Fills the buffer with random data.
+    pub fn fill_bytes(&mut self, buffer: &mut [u8]) {
+        for (counter, chunk) in (1..).zip(buffer.chunks_mut(64)) {
+            write_words(&block(&self.key, counter), chunk);
+        }
+
+        /* Block 0 is reserved for the next key */
+        let next: [u32; 16] = block(&self.key, 0);
+
+        self.key.copy_from_slice(&next[..8]);
+    }
+
+    /// Returns a random 64-bit value.
+    pub fn next_u64(&mut self) -> u64 {
+        let mut bytes: [u8; 8] = [0; 8];
+
+        self.fill_bytes(&mut bytes);
+
+        u64::from_le_bytes(bytes)
+    }
+
+    /// Returns seed material for another generator (e.g.: the kernel's one).
+    pub fn seed_material(&mut self) -> [u8; SEED_SIZE] {
+        let mut seed: [u8; SEED_SIZE] = [0; SEED_SIZE];
+
+        self.fill_bytes(&mut seed);
+
+        seed
+    }
+}
+
+fn read_words(bytes: &[u8], words: &mut [u32]) {
+    for (word, bytes) in words.iter_mut().zip(bytes.chunks_exact(4)) {
+        *word = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    }
+}
+
+fn write_words(words: &[u32], buffer: &mut [u8]) {
+    for (bytes, word) in buffer.chunks_mut(4).zip(words) {
+        bytes.copy_from_slice(&word.to_le_bytes()[..bytes.len()]);
+    }
+}
+
+fn quarter_round(state: &mut [u32; 16], indices: [usize; 4]) {
+    let [first, second, third, fourth]: [usize; 4] = indices;
+
+    state[first] = state[first].wrapping_add(state[second]);
+    state[fourth] = (state[fourth] ^ state[first]).rotate_left(16);
+    state[third] = state[third].wrapping_add(state[fourth]);
+    state[second] = (state[second] ^ state[third]).rotate_left(12);
+    state[first] = state[first].wrapping_add(state[second]);
+    state[fourth] = (state[fourth] ^ state[first]).rotate_left(8);
+    state[third] = state[third].wrapping_add(state[fourth]);
+    state[second] = (state[second] ^ state[third]).rotate_left(7);
+}
+
+/// `ChaCha20` block function with a 64-bit counter and zero nonce.
+fn block(key: &[u32; 8], counter: u64) -> [u32; 16] {
+    let mut input: [u32; 16] = [0; 16];
+
+    input[..4].copy_from_slice(&CHACHA_CONSTANTS);
+    input[4..12].copy_from_slice(key);
+
+    read_words(&counter.to_le_bytes(), &mut input[12..14]);
+
+    let mut state: [u32; 16] = input;
+
+    for _ in 0..10 {
+        quarter_round(&mut state, [0, 4, 8, 12]);
+        quarter_round(&mut state, [1, 5, 9, 13]);
+        quarter_round(&mut state, [2, 6, 10, 14]);
+        quarter_round(&mut state, [3, 7, 11, 15]);
+        quarter_round(&mut state, [0, 5, 10, 15]);
+        quarter_round(&mut state, [1, 6, 11, 12]);
+        quarter_round(&mut state, [2, 7, 8, 13]);
+        quarter_round(&mut state, [3, 4, 9, 14]);
+    }
+
+    for (word, input) in state.iter_mut().zip(&input) {
+        *word = word.wrapping_add(*input);
+    }
+
+    state
+}
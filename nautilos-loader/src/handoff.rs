@@ -0,0 +1,83 @@
+//! This module builds the structure handed over to the kernel, describing what the loader gathered while booting.
+//!
+//! The structure is allocated together with the data it points to as `KernelHandoff` memory (one of the OS defined memory types),
+//! so the kernel can tell it apart from the memory it reclaims once it takes the ownership from the firmware.
+
+use {
+    crate::{
+        defs::OsMemoryType,
+        profiler::{Profiler, Record},
+    },
+    core::{
+        mem::{size_of, size_of_val},
+        ptr::copy_nonoverlapping,
+    },
+    efi::{
+        boot_services::{types::memory::EfiMemoryType, EfiBootServices},
+        protocols::timestamp::EfiTimestampProperties,
+        EfiStatusEnum, EfiStatusError, VoidPtr,
+    },
+};
+
+/// Revision of the handoff's layout, increased whenever it changes.
+pub const REVISION: u32 = 1;
+
+/// Records of the loader's stages, taken by the [`Profiler`].
+#[repr(C)]
+pub struct BootStages {
+    /// Properties of the timestamp counter the records' timestamps are in. The frequency is zero when nothing was recorded.
+    pub properties: EfiTimestampProperties,
+    /// Number of records.
+    pub count: usize,
+    /// Records in the order they were taken.
+    pub records: *const Record,
+}
+
+/// Structure handed over to the kernel.
+#[repr(C)]
+pub struct KernelHandoff {
+    /// Revision of the layout, see [`REVISION`].
+    pub revision: u32,
+    /// Records of the loader's stages.
+    pub boot_stages: BootStages,
+}
+
+impl KernelHandoff {
+    /// Allocates the handoff, followed by copies of the profiler's records.
+    ///
+    /// # Errors
+    /// Returns the status of allocating the memory.
+    pub fn new(
+        boot_services: &EfiBootServices,
+        profiler: &Profiler,
+    ) -> Result<&'static Self, EfiStatusError> {
+        let records: &[Record] = profiler.records();
+
+        let base: VoidPtr = match boot_services.allocate_pool(
+            EfiMemoryType::custom(OsMemoryType::KernelHandoff.into()),
+            size_of::<Self>() + size_of_val(records),
+        ) {
+            EfiStatusEnum::Success(base) | EfiStatusEnum::Warning(_, base) => base,
+            EfiStatusEnum::Error(status, ()) => return Err(status),
+        };
+
+        unsafe {
+            let handoff: *mut Self = base.cast_mut().cast::<Self>();
+            /* The handoff's size is a multiple of it's alignment, which is at least the records' one */
+            let copies: *mut Record = handoff.add(1).cast::<Record>();
+
+            copy_nonoverlapping(records.as_ptr(), copies, records.len());
+
+            handoff.write(Self {
+                revision: REVISION,
+                boot_stages: BootStages {
+                    properties: profiler.properties(),
+                    count: records.len(),
+                    records: copies,
+                },
+            });
+
+            Ok(&*handoff)
+        }
+    }
+}
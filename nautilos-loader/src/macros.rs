@@ -1,20 +1,16 @@
 /// Macro for printing formatted strings on the general console output.
-/// It uses [`panic_handling`]'s [`CON_OUT`] to acquire pointer to the console output protocol's interface.
+/// It uses the runtime's [`Logger`], which writes to the console output stored in [`CON_OUT`].
 ///
-/// [`panic_handling`]: module@crate::panic_handling
-/// [`CON_OUT`]: static@crate::panic_handling::CON_OUT
+/// The output is also written to the serial port stored in [`SERIAL_OUT`], when one is set.
+/// ANSI escape sequences are interpreted for the console and passed as they are to the serial port.
+///
+/// [`Logger`]: struct@nautilos_efi_runtime::Logger
+/// [`CON_OUT`]: static@nautilos_efi_runtime::CON_OUT
+/// [`SERIAL_OUT`]: static@nautilos_efi_runtime::SERIAL_OUT
 #[macro_export]
 macro_rules! print {
 	($($args:tt)+) => {
-		{
-			let con_out:
-				*mut efi::protocols::console::EfiSimpleTextOutputProtocol =
-				crate::panic_handling::CON_OUT.load(core::sync::atomic::Ordering::Relaxed);
-
-			if !con_out.is_null() && (con_out as usize) % core::mem::align_of::<efi::protocols::console::EfiSimpleTextOutputProtocol>() == 0 {
-				match core::fmt::write(unsafe { &mut *con_out }, format_args!($($args)+)) { _ => () }
-			}
-		}
+		nautilos_efi_runtime::Logger::new().print(format_args!($($args)+));
 	};
 }
 
@@ -1,14 +1,21 @@
 //! This crate represents the kernel loader.
 //!
 //! It handles the following functionality:
-//! 1. Verifying the EFI tables passed by the firmware.
+//! 1. Setting up the runtime (verifying the EFI tables, console output, heap, etc.) through [`nautilos_efi_runtime`].
 //! 1. Verifying the system meets the minimum requirements.
+//! 1. Recording when each stage completes through [`profiler`], to catch regressions in boot time.
+//! 1. Seeding the random number generator (also used to seed the kernel's one).
+//! 1. Recording the display and mass storage controllers' PCI topology.
+//! 1. Identifying the hardware security tokens attached over USB.
+//! 1. Discovering the persistent memory namespaces described by the NVDIMMs' labels.
+//! 1. Printing the handle database, when built with the `diagnostics` feature.
 //! 1. Finding kernel's partition.
 //! 1. Finding configuration file in kernel's partition.
 //!     * If it exists, loads configuration.
 //!     * If it doesn't exist, uses default configuration.
 //! 1. Setting desired (stated in the configuration) video mode (resolution, color map, etc.).
 //! 1. Finding and loading suitable kernel binaries (kernel, core/generic drivers, etc.).
+//! 1. Building the kernel handoff through [`handoff`], carrying the boot stages' records.
 //! 1. Taking the ownership from the EFI firmware.
 //! 1. Building memory map using EFI's one.
 //! 1. Transfering control to the kernel initializer.
@@ -16,7 +23,7 @@
 #![no_std]
 #![no_main]
 #![doc(html_no_source)]
-#![feature(panic_info_message, never_type)]
+#![feature(never_type)]
 #![forbid(warnings, missing_docs, clippy::pedantic)]
 
 extern crate alloc;
@@ -24,162 +31,183 @@ extern crate alloc;
 mod defs;
 mod macros;
 
-pub mod panic_handling;
+pub mod entropy;
+pub mod handoff;
+pub mod pci;
+pub mod pmem;
+pub mod profiler;
+pub mod usb;
 
 use {
-    core::{mem::size_of, sync::atomic::Ordering},
+    core::mem::size_of,
+    defs::OsMemoryType,
     efi::{
-        boot_services::EfiBootServices, runtime_services::EfiRuntimeServices, EfiHandle, EfiStatus,
-        EfiSystemTable,
+        boot_services::{types::memory::EfiMemoryType, EfiBootServices},
+        runtime_services::EfiRuntimeServices,
+        EfiHandle,
     },
-    panic_handling::CON_OUT,
+    nautilos_efi_runtime::{efi_entry, Context},
 };
 
 /// Loader's main function.
 ///
-/// This function acts as EFI's entry point.
-#[no_mangle]
-fn efi_main(_image_handle: EfiHandle, system_table: &mut EfiSystemTable) -> EfiStatus {
-    /* Verify that the system table is valid */
-    if !system_table.verify_table() {
-        return EfiStatus::error(0);
+/// This function acts as EFI's entry point, called once the runtime is set up.
+#[efi_entry(
+    heap_size = 0x0100_0000,
+    memory_type = EfiMemoryType::custom(OsMemoryType::LoaderHeap.into()),
+    clear_screen,
+    serial = cfg!(feature = "serial_console")
+)]
+fn main(mut context: Context) -> ! {
+    /* Requirement: State storing mechanism */
+    if !context.state_storing_enabled() {
+        panic!("State storing mechanism unavailable!");
     }
 
-    /* Set the output interface for the panic handler (also used by "print!" and "println!") */
-    if let Some(con_out) = system_table.con_out_mut() {
-        CON_OUT.store(con_out, Ordering::Relaxed);
+    log!("EFI revision: {}", context.system_table().revision());
 
-        if let efi::EfiStatusEnum::Error(status, _) = con_out.clear_screen() {
-            efi_warn!("Clearing screen failed with error: {:?}", status);
-        }
+    let boot_services: &mut EfiBootServices = context.system_table_mut().boot_services_mut();
+    let runtime_services: &mut EfiRuntimeServices =
+        context.system_table_mut().runtime_services_mut();
+
+    let mut profiler: profiler::Profiler =
+        profiler::Profiler::new(boot_services, context.setup_timestamps());
+
+    if !profiler.is_enabled() {
+        warn!("Timestamp protocol unavailable; Boot stages won't be recorded!");
     }
 
-    let boot_services: &mut EfiBootServices = system_table.boot_services_mut();
-    let runtime_services: &mut EfiRuntimeServices = system_table.runtime_services_mut();
+    let (mut random, source): (entropy::Csprng, entropy::Source) =
+        entropy::Csprng::from_entropy(boot_services);
 
-    stages::start_up(boot_services);
+    profiler.record(profiler::Stage::EntropySeeded);
 
-    let boot_device_handle: EfiHandle =
-        stages::get_boot_device_handle(boot_services, runtime_services);
+    log!("Random number generator seeded from: {:?}", source);
 
-    debug_info!(
-        "Boot Device Handle: 0x{:0>width$X}",
-        boot_device_handle as usize,
-        width = size_of::<usize>() * 2
-    );
+    /* The kernel handoff doesn't carry it yet */
+    let _kernel_seed: [u8; entropy::SEED_SIZE] = random.seed_material();
 
-    loop {
-        core::hint::spin_loop();
-    }
-}
+    /* The hardware descriptions below are only collected and logged for now */
+    let pci_controllers: alloc::vec::Vec<pci::Controller> = pci::topology(boot_services);
 
-mod stages {
-    use efi::{boot_services::EfiBootServices1x0, runtime_services::EfiRuntimeServices};
+    profiler.record(profiler::Stage::PciTopologyRecorded);
 
-    use crate::{efi_panic, efi_warn, log, warn};
+    for controller in &pci_controllers {
+        log!(
+            "PCI {:0>4X}:{:0>2X}:{:0>2X}.{:X} [{:0>4X}:{:0>4X}] {:?}",
+            controller.location.segment,
+            controller.location.bus,
+            controller.location.device,
+            controller.location.function,
+            controller.vendor_id,
+            controller.device_id,
+            controller.class
+        );
+    }
 
-    pub fn start_up(boot_services: &mut EfiBootServices1x0) {
-        setup_detection_mechanism();
+    let security_tokens: alloc::vec::Vec<usb::SecurityToken> = usb::security_tokens(boot_services);
 
-        setup_state_storing();
+    profiler.record(profiler::Stage::SecurityTokensIdentified);
 
-        setup_allocator(boot_services);
+    if security_tokens.is_empty() {
+        warn!("No USB security token attached!");
     }
 
-    fn setup_detection_mechanism() {
-        use native::{
-            features::detection::{enable, FeatureState},
-            Error,
-        };
+    for token in &security_tokens {
+        log!(
+            "USB security token [{:0>4X}:{:0>4X}] {} {} (serial number: {})",
+            token.vendor_id,
+            token.product_id,
+            token.manufacturer,
+            token.product,
+            token.serial_number
+        );
+    }
 
-        match enable() {
-            Ok(FeatureState::Enabled) => log!("Feature detection mechanism enabled."),
-            Ok(FeatureState::Disabled) => {
-                warn!("Feature detection mechanism couldn't be enabled. It may be required later.")
-            }
-            Err(Error::Unavailable) => {
-                warn!("Feature detection mechanism unavailable. It may be required later.")
+    let namespaces: alloc::vec::Vec<pmem::Namespace> = pmem::namespaces(
+        boot_services,
+        &context.system_table().configuration_tables(),
+    );
+
+    profiler.record(profiler::Stage::NamespacesDiscovered);
+
+    for namespace in &namespaces {
+        log!(
+            "Persistent memory namespace {} \"{}\" ({} bytes, {} of {} labels{})",
+            namespace.uuid,
+            namespace.name,
+            namespace.size(),
+            namespace.labels.len(),
+            namespace.label_count,
+            if namespace.read_only {
+                ", read-only"
+            } else {
+                ""
             }
-            Err(error) => panic!(
-                "Error occured while enabling feature detection mechanism!\nError: {:?}",
-                error
-            ),
+        );
+
+        if !namespace.is_complete() {
+            warn!(
+                "Persistent memory namespace {} is incomplete!",
+                namespace.uuid
+            );
         }
     }
 
-    fn setup_state_storing() {
-        use native::{
-            features::detection::{
-                state_storing::{available, enable},
-                FeatureState,
-            },
-            Error,
-        };
+    #[cfg(feature = "diagnostics")]
+    {
+        stages::print_handle_database(boot_services);
 
-        /* Requirement: State storing mechanism */
-        match available() {
-            Ok(FeatureState::Enabled) => log!("State storing mechanism available."),
-            Ok(FeatureState::Disabled) => {
-                warn!("State storing mechanism available but is disabled.");
-                log!("Enabling state storing mechanism...");
-                match enable() {
-                    Ok(FeatureState::Enabled) => log!("State storing mechanism enabled."),
-                    Ok(FeatureState::Disabled) => panic!("State storing mechanism couldn't be enabled!"),
-                    Err(error) => panic!("Error occured while enabling feature detection mechanism!\nError: {:?}", error),
-                }
-            },
-            Err(Error::Unavailable) => panic!("State storing mechanism unavailable!"),
-            Err(Error::FeatureDisabled) => panic!("Feature detection mechanism required to determine whether state storing is available, but is disabled!"),
-            Err(error) => panic!("Error occured while testing for state storing mechanism!\nError: {:?}", error),
-        }
+        profiler.record(profiler::Stage::HandleDatabasePrinted);
     }
 
-    fn setup_allocator(boot_services: &EfiBootServices1x0) {
-        use {
-            crate::defs::OsMemoryType,
-            core::slice::from_raw_parts_mut,
-            efi::{
-                boot_services::types::memory::EfiMemoryType, EfiPhysicalAddress, EfiStatusError,
-                EfiStatusWarning, VoidPtr,
-            },
-            nautilos_allocator::{initialize as initialize_allocator, Heap},
+    let boot_device_handle: EfiHandle =
+        stages::get_boot_device_handle(boot_services, runtime_services);
+
+    profiler.record(profiler::Stage::BootDeviceFound);
+
+    debug_info!(
+        "Boot Device Handle: 0x{:0>width$X}",
+        boot_device_handle as usize,
+        width = size_of::<usize>() * 2
+    );
+
+    print!("{}", profiler);
+
+    let kernel_handoff: &handoff::KernelHandoff =
+        match handoff::KernelHandoff::new(boot_services, &profiler) {
+            Ok(kernel_handoff) => kernel_handoff,
+            Err(status) => panic!("Allocating the kernel handoff failed with: {:?}!", status),
         };
 
-        const REQUIRED_HEAP_SIZE: usize = Heap::UNALIGNED_REQUIRED_INITIAL_SIZE;
-        const ADDITIONAL_HEAP_SIZE: usize = 0x0100_0000; // 16 MB
-        const TOTAL_HEAP_SIZE: usize = REQUIRED_HEAP_SIZE + ADDITIONAL_HEAP_SIZE;
+    debug_info!("Kernel Handoff: {:p}", kernel_handoff);
+
+    loop {
+        core::hint::spin_loop();
+    }
+}
 
-        let address: EfiPhysicalAddress;
+mod stages {
+    #[cfg(feature = "diagnostics")]
+    use efi::boot_services::EfiBootServices;
+    use efi::{boot_services::EfiBootServices1x0, runtime_services::EfiRuntimeServices};
 
-        let result: Result<(Option<EfiStatusWarning>, VoidPtr), (EfiStatusError, ())> =
-            boot_services
-                .allocate_pool(
-                    EfiMemoryType::custom(OsMemoryType::LoaderHeap.into()),
-                    TOTAL_HEAP_SIZE,
-                )
-                .unfold();
+    use crate::{efi_panic, efi_warn};
 
-        match result {
-            Ok((status, allocation_address)) => {
-                if let Some(status) = status {
-                    efi_warn!(
-                        "Warning status returned while allocating memory pages.\tWarning: {:?}",
-                        status
-                    );
-                }
+    #[cfg(feature = "diagnostics")]
+    pub fn print_handle_database(boot_services: &EfiBootServices) {
+        use {
+            crate::print,
+            efi::{boot_services::handle_database::HandleDatabase, EfiStatusEnum},
+        };
 
-                address = allocation_address as u64;
+        match HandleDatabase::inspect(boot_services, "en") {
+            EfiStatusEnum::Success(database) | EfiStatusEnum::Warning(_, database) => {
+                print!("{}", database);
+            }
+            EfiStatusEnum::Error(status, ()) => {
+                efi_warn!("Inspecting handle database failed with error: {:?}", status);
             }
-            Err((status, ())) => efi_panic!(
-                "Error occured while retrieving disk I/O device handles!\nError: {:?}",
-                status
-            ),
-        }
-
-        if let Err(error) =
-            initialize_allocator(unsafe { from_raw_parts_mut(address as *mut u8, TOTAL_HEAP_SIZE) })
-        {
-            panic!("Error occured while initializing heap!\nError: {:?}", error);
         }
     }
 
@@ -0,0 +1,112 @@
+//! This module records the PCI controllers the kernel needs before it has it's own PCI driver.
+//!
+//! Only display and mass storage controllers are recorded, as they're required for early output and for loading drivers.
+
+use {
+    alloc::vec::Vec,
+    efi::{
+        boot_services::{types::protocol_handler::EfiLocateSearchType, EfiBootServices},
+        guids::EFI_PCI_IO_PROTOCOL,
+        protocols::pci::{EfiPciIoProtocol, EfiPciLocation},
+        EfiHandle, EfiStatusEnum, Void,
+    },
+};
+
+/// Offset of the vendor and device identifiers within the configuration space.
+const IDENTIFIERS_OFFSET: u32 = 0x00;
+
+/// Offset of the revision identifier and class code within the configuration space.
+const CLASS_CODE_OFFSET: u32 = 0x08;
+
+/// Defines the recorded controller classes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Class {
+    /// Mass storage controller (base class `0x01`).
+    MassStorage,
+    /// Display controller (base class `0x03`).
+    Display,
+}
+
+impl Class {
+    fn from_base_class(base_class: u8) -> Option<Self> {
+        match base_class {
+            0x01 => Some(Self::MassStorage),
+            0x03 => Some(Self::Display),
+            _ => None,
+        }
+    }
+}
+
+/// Describes a PCI controller.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Controller {
+    /// Segment, bus, device and function numbers.
+    pub location: EfiPciLocation,
+    /// Vendor identifier.
+    pub vendor_id: u16,
+    /// Device identifier.
+    pub device_id: u16,
+    /// Controller class.
+    pub class: Class,
+    /// Class specific subclass.
+    pub subclass: u8,
+    /// Subclass specific programming interface.
+    pub programming_interface: u8,
+}
+
+impl Controller {
+    fn read(pci_io: &EfiPciIoProtocol) -> Option<Self> {
+        let (_, location): (_, EfiPciLocation) = pci_io.get_location().unfold().ok()?;
+
+        let identifiers: &mut [u16; 2] = &mut [0; 2];
+        let class_code: &mut [u8; 4] = &mut [0; 4];
+
+        if pci_io.pci_read(IDENTIFIERS_OFFSET, identifiers).is_error()
+            || pci_io.pci_read(CLASS_CODE_OFFSET, class_code).is_error()
+        {
+            return None;
+        }
+
+        Some(Self {
+            location,
+            vendor_id: identifiers[0],
+            device_id: identifiers[1],
+            class: Class::from_base_class(class_code[3])?,
+            subclass: class_code[2],
+            programming_interface: class_code[1],
+        })
+    }
+}
+
+/// Enumerates the PCI controllers exposed by the firmware and returns the display and mass storage ones.
+#[must_use]
+pub fn topology(boot_services: &EfiBootServices) -> Vec<Controller> {
+    let handles: &[EfiHandle] = match boot_services.revision_1_1().map(|services| {
+        services.locate_handle_buffer(
+            EfiLocateSearchType::ByProtocol,
+            Some(&EFI_PCI_IO_PROTOCOL),
+            None,
+        )
+    }) {
+        Some(EfiStatusEnum::Success(handles) | EfiStatusEnum::Warning(_, handles)) => handles,
+        Some(EfiStatusEnum::Error(..)) | None => return Vec::new(),
+    };
+
+    let controllers: Vec<Controller> = handles
+        .iter()
+        .filter_map(|&handle: &EfiHandle| {
+            if let Ok(EfiStatusEnum::Success(Ok(pci_io))) =
+                boot_services.handle_protocol::<EfiPciIoProtocol>(handle)
+            {
+                Controller::read(pci_io)
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    /* The buffer is allocated by the firmware */
+    let _ = boot_services.free_pool(handles.as_ptr().cast::<Void>());
+
+    controllers
+}
@@ -0,0 +1,213 @@
+//! This module discovers the persistent memory namespaces, before the kernel is loaded, for the storage stack.
+//!
+//! Namespaces are described by the labels stored on each NVDIMM, read through EFI's NVDIMM label protocol.
+//! The labels of a namespace spread across several NVDIMMs share it's UUID and form a label set.
+//! Each label is tied back to it's NVDIMM through the NFIT device handle in the NVDIMM's device path,
+//! and to the address range it's mapped to through the NFIT's region mappings.
+
+use {
+    acpi::{
+        devices::acpi_defined::NVDIMMDeviceHandle,
+        tables::{Nfit, RegionMapping, Rsdp, SpaRange},
+    },
+    alloc::{string::String, vec::Vec},
+    efi::{
+        boot_services::{types::protocol_handler::EfiLocateSearchType, EfiBootServices},
+        guids::EFI_NVDIMM_LABEL_PROTOCOL,
+        protocols::{device_path::EfiDevicePathProtocolRaw, media::EfiNvdimmLabelProtocol},
+        structures::nvdimm_label::{EfiNvdimmLabelArea, EfiNvdimmNamespaceLabel},
+        EfiConfigurationTable, EfiConfigurationTableEntry, EfiGuid, EfiHandle, EfiStatusEnum, Void,
+    },
+};
+
+/// Describes the address range part of a namespace is mapped to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Region {
+    /// Index of the NFIT's SPA range structure.
+    pub spa_range_index: u16,
+    /// Physical address of the namespace's part.
+    pub address: u64,
+    /// Whether the range's type is persistent memory.
+    pub is_persistent_memory: bool,
+}
+
+/// Describes the part of a namespace held by a single NVDIMM.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Label {
+    /// NFIT device handle of the NVDIMM.
+    pub device_handle: u32,
+    /// Position of the label within the label set.
+    pub position: u16,
+    /// NVDIMM physical address of the part.
+    pub dpa: u64,
+    /// Size of the part in bytes.
+    pub raw_size: u64,
+    /// Address range the part is mapped to, `None` if the NFIT doesn't map it or maps it past the address space.
+    pub region: Option<Region>,
+}
+
+/// Describes a namespace assembled from it's label set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Namespace {
+    /// UUID shared by the namespace's labels.
+    pub uuid: EfiGuid,
+    /// Name of the namespace, empty if it has none.
+    pub name: String,
+    /// Cookie identifying the interleave set the namespace belongs to.
+    pub set_cookie: u64,
+    /// Logical block size, where zero means the namespace is byte addressable.
+    pub lba_size: u64,
+    /// Whether the namespace is read-only.
+    pub read_only: bool,
+    /// Number of labels the label set consists of.
+    pub label_count: u16,
+    /// Labels found so far, ordered by their position.
+    pub labels: Vec<Label>,
+}
+
+impl Namespace {
+    /// Returns whether the labels of all the NVDIMMs the namespace spans were found.
+    #[must_use]
+    pub fn is_complete(&self) -> bool {
+        self.labels.len() == usize::from(self.label_count)
+    }
+
+    /// Returns the namespace's size in bytes.
+    #[must_use]
+    pub fn size(&self) -> u64 {
+        self.labels.iter().map(|label: &Label| label.raw_size).sum()
+    }
+
+    fn new(label: &EfiNvdimmNamespaceLabel<'_>) -> Self {
+        Self {
+            uuid: label.uuid(),
+            name: String::from_utf8_lossy(label.name()).into_owned(),
+            set_cookie: label.set_cookie(),
+            lba_size: label.lba_size(),
+            read_only: label.contains(EfiNvdimmNamespaceLabel::FLAGS_ROLABEL),
+            label_count: label.n_label(),
+            labels: Vec::new(),
+        }
+    }
+}
+
+/// Reads the labels of every NVDIMM and returns the namespaces they describe.
+#[must_use]
+pub fn namespaces(
+    boot_services: &EfiBootServices,
+    configuration_tables: &EfiConfigurationTable,
+) -> Vec<Namespace> {
+    let handles: &[EfiHandle] = match boot_services.revision_1_1().map(|services| {
+        services.locate_handle_buffer(
+            EfiLocateSearchType::ByProtocol,
+            Some(&EFI_NVDIMM_LABEL_PROTOCOL),
+            None,
+        )
+    }) {
+        Some(EfiStatusEnum::Success(handles) | EfiStatusEnum::Warning(_, handles)) => handles,
+        Some(EfiStatusEnum::Error(..)) | None => return Vec::new(),
+    };
+
+    let nfit: Option<Nfit<'static>> = find_nfit(configuration_tables);
+    let mut namespaces: Vec<Namespace> = Vec::new();
+
+    for &handle in handles {
+        let device_handle: NVDIMMDeviceHandle =
+            match boot_services.handle_protocol::<EfiDevicePathProtocolRaw>(handle) {
+                Ok(EfiStatusEnum::Success(Ok(device_path))) => {
+                    match unsafe { device_path.nvdimm_device_handle::<NVDIMMDeviceHandle>() } {
+                        Some(device_handle) => device_handle,
+                        None => continue,
+                    }
+                }
+                _ => continue,
+            };
+
+        let area: Vec<u8> = match boot_services.handle_protocol::<EfiNvdimmLabelProtocol>(handle) {
+            Ok(EfiStatusEnum::Success(Ok(protocol))) => match protocol.read_label_storage_area() {
+                EfiStatusEnum::Success(area) | EfiStatusEnum::Warning(_, area) => area,
+                EfiStatusEnum::Error(..) => continue,
+            },
+            _ => continue,
+        };
+
+        /* NVDIMMs without labels don't contribute to any namespace */
+        let area: EfiNvdimmLabelArea<'_> = match EfiNvdimmLabelArea::parse(&area) {
+            Some(area) => area,
+            None => continue,
+        };
+
+        for label in area.labels() {
+            /* The label set is incomplete while it's being updated */
+            if label.contains(EfiNvdimmNamespaceLabel::FLAGS_UPDATING) {
+                continue;
+            }
+
+            let index: usize = namespaces
+                .iter()
+                .position(|namespace: &Namespace| namespace.uuid == label.uuid())
+                .unwrap_or_else(|| {
+                    namespaces.push(Namespace::new(&label));
+
+                    namespaces.len() - 1
+                });
+
+            namespaces[index].labels.push(Label {
+                device_handle: device_handle.raw(),
+                position: label.position(),
+                dpa: label.dpa(),
+                raw_size: label.raw_size(),
+                region: nfit.and_then(|nfit: Nfit<'static>| region(nfit, device_handle, &label)),
+            });
+        }
+    }
+
+    for namespace in &mut namespaces {
+        namespace.labels.sort_by_key(|label: &Label| label.position);
+    }
+
+    /* The buffer is allocated by the firmware */
+    let _ = boot_services.free_pool(handles.as_ptr().cast::<Void>());
+
+    namespaces
+}
+
+fn find_nfit(configuration_tables: &EfiConfigurationTable) -> Option<Nfit<'static>> {
+    let rsdp: &Rsdp = configuration_tables
+        .get_all()
+        .iter()
+        .find_map(EfiConfigurationTableEntry::get_as::<Rsdp>)?;
+
+    if !rsdp.is_valid() {
+        return None;
+    }
+
+    Nfit::parse(unsafe { rsdp.find_table(Nfit::SIGNATURE) }?)
+}
+
+/// Finds the region mapping which covers the label's part of the NVDIMM.
+fn region(
+    nfit: Nfit<'static>,
+    device_handle: NVDIMMDeviceHandle,
+    label: &EfiNvdimmNamespaceLabel<'_>,
+) -> Option<Region> {
+    let mapping: RegionMapping =
+        nfit.region_mappings(device_handle)
+            .find(|mapping: &RegionMapping| {
+                let base: u64 = mapping.physical_address_region_base();
+
+                (base..base.saturating_add(mapping.region_size())).contains(&label.dpa())
+            })?;
+
+    let range: SpaRange = nfit.spa_range(mapping.spa_range_index())?;
+
+    Some(Region {
+        spa_range_index: range.index(),
+        /* Interleaving isn't accounted for, as the kernel maps the whole set */
+        address: range
+            .base()
+            .checked_add(mapping.region_offset())?
+            .checked_add(label.dpa() - mapping.physical_address_region_base())?,
+        is_persistent_memory: range.is_persistent_memory(),
+    })
+}
@@ -0,0 +1,195 @@
+//! This module records when each of the loader's stages completes, to catch regressions in boot time across firmware builds.
+//!
+//! Timestamps are taken through EFI's timestamp protocol, so they're monotonic and don't depend on the processor's frequency.
+//! When the firmware doesn't provide the protocol, nothing is recorded.
+//!
+//! The runtime's setup steps are recorded from the timestamps the runtime took, before the loader's own stages.
+//! The records are printed and handed over to the kernel through [`KernelHandoff`](crate::handoff::KernelHandoff).
+
+use {
+    alloc::vec::Vec,
+    core::fmt::{Display, Formatter, Result},
+    efi::{
+        boot_services::EfiBootServices,
+        protocols::timestamp::{EfiTimestampProperties, EfiTimestampProtocol},
+        EfiStatusEnum,
+    },
+    nautilos_efi_runtime::{SetupStep, SetupTimestamps},
+};
+
+/// Defines the stages of the loader, in the order they complete.
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    /// The runtime verified the system table and the services' tables.
+    ServicesVerified,
+    /// The runtime mirrored the output to the serial port, if there is one.
+    SerialOutputPublished,
+    /// The runtime set up the feature detection mechanism.
+    FeatureDetectionSetUp,
+    /// The runtime set up the state storing mechanism.
+    StateStoringSetUp,
+    /// The runtime initialized the heap.
+    HeapInitialized,
+    /// The runtime is set up and the system meets the minimum requirements.
+    RuntimeSetUp,
+    /// The random number generator is seeded.
+    EntropySeeded,
+    /// The PCI topology is recorded.
+    PciTopologyRecorded,
+    /// The USB security tokens are identified.
+    SecurityTokensIdentified,
+    /// The persistent memory namespaces are discovered.
+    NamespacesDiscovered,
+    /// The handle database is printed, when built with the `diagnostics` feature.
+    HandleDatabasePrinted,
+    /// The kernel's partition is found.
+    BootDeviceFound,
+}
+
+impl From<SetupStep> for Stage {
+    fn from(step: SetupStep) -> Self {
+        match step {
+            SetupStep::ServicesVerified => Self::ServicesVerified,
+            SetupStep::SerialOutputPublished => Self::SerialOutputPublished,
+            SetupStep::FeatureDetectionSetUp => Self::FeatureDetectionSetUp,
+            SetupStep::StateStoringSetUp => Self::StateStoringSetUp,
+            SetupStep::HeapInitialized => Self::HeapInitialized,
+        }
+    }
+}
+
+impl Display for Stage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        f.pad(match self {
+            Self::ServicesVerified => "Services verified",
+            Self::SerialOutputPublished => "Serial output published",
+            Self::FeatureDetectionSetUp => "Feature detection set up",
+            Self::StateStoringSetUp => "State storing set up",
+            Self::HeapInitialized => "Heap initialized",
+            Self::RuntimeSetUp => "Runtime set up",
+            Self::EntropySeeded => "Entropy seeded",
+            Self::PciTopologyRecorded => "PCI topology recorded",
+            Self::SecurityTokensIdentified => "Security tokens identified",
+            Self::NamespacesDiscovered => "Namespaces discovered",
+            Self::HandleDatabasePrinted => "Handle database printed",
+            Self::BootDeviceFound => "Boot device found",
+        })
+    }
+}
+
+/// Describes when a stage completed.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Record {
+    /// Stage which completed.
+    pub stage: Stage,
+    /// Value of the timestamp counter when the stage completed.
+    pub timestamp: u64,
+}
+
+/// Records the stages' timestamps.
+pub struct Profiler {
+    timestamp: Option<&'static EfiTimestampProtocol>,
+    properties: EfiTimestampProperties,
+    records: Vec<Record>,
+}
+
+impl Profiler {
+    /// Locates the timestamp protocol, records the runtime's setup steps and then [`Stage::RuntimeSetUp`].
+    #[must_use]
+    pub fn new(boot_services: &EfiBootServices, setup_timestamps: &SetupTimestamps) -> Self {
+        let mut profiler: Self = Self {
+            timestamp: None,
+            properties: EfiTimestampProperties::default(),
+            records: Vec::new(),
+        };
+
+        if let Some(boot_services) = boot_services.revision_1_1() {
+            if let Ok(EfiStatusEnum::Success(Ok(timestamp))) =
+                boot_services.locate_protocol::<EfiTimestampProtocol>(None)
+            {
+                if let EfiStatusEnum::Success(properties) = timestamp.get_properties() {
+                    /* Ticks can't be converted without the frequency */
+                    if properties.frequency() != 0 {
+                        profiler.timestamp = Some(timestamp);
+                        profiler.properties = properties;
+                    }
+                }
+            }
+        }
+
+        if profiler.is_enabled() {
+            profiler.records.extend(setup_timestamps.recorded().map(
+                |(step, timestamp): (SetupStep, u64)| Record {
+                    stage: step.into(),
+                    timestamp,
+                },
+            ));
+        }
+
+        profiler.record(Stage::RuntimeSetUp);
+
+        profiler
+    }
+
+    /// Returns whether the firmware provides the timestamp protocol.
+    #[must_use]
+    pub fn is_enabled(&self) -> bool {
+        self.timestamp.is_some()
+    }
+
+    /// Records that the stage completed.
+    pub fn record(&mut self, stage: Stage) {
+        if let Some(timestamp) = self.timestamp {
+            self.records.push(Record {
+                stage,
+                timestamp: timestamp.get_timestamp(),
+            });
+        }
+    }
+
+    /// Returns the properties of the timestamp counter, which the records' timestamps are in.
+    #[must_use]
+    pub fn properties(&self) -> EfiTimestampProperties {
+        self.properties
+    }
+
+    /// Returns the records in the order they were taken.
+    #[must_use]
+    pub fn records(&self) -> &[Record] {
+        &self.records
+    }
+
+    /// Returns the time between the two timestamps in microseconds.
+    fn microseconds(&self, start: u64, end: u64) -> u64 {
+        self.properties
+            .to_nanoseconds(self.properties.elapsed(start, end))
+            .map_or(0, |nanoseconds: u64| nanoseconds / 1000)
+    }
+}
+
+impl Display for Profiler {
+    /// Prints each stage with the time since the first record and since the previous stage.
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        let first: u64 = match self.records.first() {
+            Some(record) => record.timestamp,
+            None => return writeln!(f, "Boot stages weren't recorded."),
+        };
+        let mut previous: u64 = first;
+
+        for record in &self.records {
+            writeln!(
+                f,
+                "{:<26} {:>10} us (+{} us)",
+                record.stage,
+                self.microseconds(first, record.timestamp),
+                self.microseconds(previous, record.timestamp)
+            )?;
+
+            previous = record.timestamp;
+        }
+
+        Ok(())
+    }
+}
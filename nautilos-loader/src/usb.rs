@@ -0,0 +1,119 @@
+//! This module identifies the hardware security tokens attached over USB, before the kernel is loaded.
+//!
+//! Tokens are recognized by their smart card (CCID) interface, which they expose regardless of their vendor.
+//! Each token is reported once, even when it exposes several such interfaces.
+
+use {
+    alloc::{string::String, vec::Vec},
+    efi::{
+        boot_services::{
+            types::protocol_handler::EfiLocateSearchType, EfiBootServices, EfiBootServices1x0,
+        },
+        guids::EFI_USB_IO_PROTOCOL,
+        protocols::usb::{
+            EfiUsbDeviceDescriptor, EfiUsbInterfaceDescriptor, EfiUsbIoProtocol,
+            EFI_USB_LANGUAGE_ENGLISH_US,
+        },
+        EfiHandle, EfiStatusEnum, Void,
+    },
+};
+
+/// Describes a hardware security token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SecurityToken {
+    /// USB vendor identifier.
+    pub vendor_id: u16,
+    /// USB product identifier.
+    pub product_id: u16,
+    /// Manufacturer's name, empty if the token doesn't report one.
+    pub manufacturer: String,
+    /// Product's name, empty if the token doesn't report one.
+    pub product: String,
+    /// Serial number, empty if the token doesn't report one.
+    pub serial_number: String,
+}
+
+impl SecurityToken {
+    fn read(boot_services: &EfiBootServices1x0, usb_io: &EfiUsbIoProtocol) -> Option<Self> {
+        let (_, interface): (_, EfiUsbInterfaceDescriptor) =
+            usb_io.get_interface_descriptor().unfold().ok()?;
+
+        if interface.interface_class() != EfiUsbInterfaceDescriptor::CLASS_SMART_CARD {
+            return None;
+        }
+
+        let (_, device): (_, EfiUsbDeviceDescriptor) =
+            usb_io.get_device_descriptor().unfold().ok()?;
+
+        /* Prefer English, as it's what most tokens provide */
+        let language_id: u16 = match usb_io.get_supported_languages() {
+            EfiStatusEnum::Success(languages) | EfiStatusEnum::Warning(_, languages) => {
+                if languages.contains(&EFI_USB_LANGUAGE_ENGLISH_US) {
+                    EFI_USB_LANGUAGE_ENGLISH_US
+                } else {
+                    languages
+                        .first()
+                        .copied()
+                        .unwrap_or(EFI_USB_LANGUAGE_ENGLISH_US)
+                }
+            }
+            EfiStatusEnum::Error(..) => EFI_USB_LANGUAGE_ENGLISH_US,
+        };
+
+        let string = |index: u8| -> String {
+            if index == 0 {
+                return String::new();
+            }
+
+            match usb_io.get_string_descriptor(boot_services, language_id, index) {
+                EfiStatusEnum::Success(string) | EfiStatusEnum::Warning(_, string) => {
+                    String::from_utf16_lossy(&string)
+                }
+                EfiStatusEnum::Error(..) => String::new(),
+            }
+        };
+
+        Some(Self {
+            vendor_id: device.vendor_id(),
+            product_id: device.product_id(),
+            manufacturer: string(device.manufacturer_index()),
+            product: string(device.product_index()),
+            serial_number: string(device.serial_number_index()),
+        })
+    }
+}
+
+/// Enumerates the USB interfaces exposed by the firmware and returns the security tokens they belong to.
+#[must_use]
+pub fn security_tokens(boot_services: &EfiBootServices) -> Vec<SecurityToken> {
+    let handles: &[EfiHandle] = match boot_services.revision_1_1().map(|services| {
+        services.locate_handle_buffer(
+            EfiLocateSearchType::ByProtocol,
+            Some(&EFI_USB_IO_PROTOCOL),
+            None,
+        )
+    }) {
+        Some(EfiStatusEnum::Success(handles) | EfiStatusEnum::Warning(_, handles)) => handles,
+        Some(EfiStatusEnum::Error(..)) | None => return Vec::new(),
+    };
+
+    let mut tokens: Vec<SecurityToken> = Vec::new();
+
+    for &handle in handles {
+        if let Ok(EfiStatusEnum::Success(Ok(usb_io))) =
+            boot_services.handle_protocol::<EfiUsbIoProtocol>(handle)
+        {
+            if let Some(token) = SecurityToken::read(boot_services, usb_io) {
+                /* Composite tokens expose an interface per application */
+                if !tokens.contains(&token) {
+                    tokens.push(token);
+                }
+            }
+        }
+    }
+
+    /* The buffer is allocated by the firmware */
+    let _ = boot_services.free_pool(handles.as_ptr().cast::<Void>());
+
+    tokens
+}
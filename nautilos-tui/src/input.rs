@@ -0,0 +1,124 @@
+use efi::{
+    boot_services::EfiBootServices1x0,
+    protocols::console::{EfiInputKey, EfiSimpleTextInputProtocol},
+    EfiStatusEnum, EfiStatusError,
+};
+
+/// Decoded key stroke.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    /// Printable character.
+    Character(char),
+    /// `Enter` key.
+    Enter,
+    /// `Backspace` key.
+    Backspace,
+    /// `Tab` key.
+    Tab,
+    /// `Esc` key.
+    Escape,
+    /// Up arrow key.
+    Up,
+    /// Down arrow key.
+    Down,
+    /// Left arrow key.
+    Left,
+    /// Right arrow key.
+    Right,
+    /// `Home` key.
+    Home,
+    /// `End` key.
+    End,
+    /// `Insert` key.
+    Insert,
+    /// `Delete` key.
+    Delete,
+    /// `Page Up` key.
+    PageUp,
+    /// `Page Down` key.
+    PageDown,
+    /// Function key with the given number (`1` to `10`).
+    Function(u16),
+    /// Key with unknown scan code or non-printable character.
+    Unknown(u16),
+}
+
+impl From<EfiInputKey> for Key {
+    fn from(key: EfiInputKey) -> Self {
+        match (key.scan_code(), key.unicode_char()) {
+            (EfiInputKey::SCAN_NULL, 0x0D) => Self::Enter,
+            (EfiInputKey::SCAN_NULL, 0x08) => Self::Backspace,
+            (EfiInputKey::SCAN_NULL, 0x09) => Self::Tab,
+            (EfiInputKey::SCAN_NULL, character) => char::from_u32(u32::from(character))
+                .filter(|character: &char| !character.is_control())
+                .map_or(Self::Unknown(character), Self::Character),
+            (EfiInputKey::SCAN_UP, _) => Self::Up,
+            (EfiInputKey::SCAN_DOWN, _) => Self::Down,
+            (EfiInputKey::SCAN_RIGHT, _) => Self::Right,
+            (EfiInputKey::SCAN_LEFT, _) => Self::Left,
+            (EfiInputKey::SCAN_HOME, _) => Self::Home,
+            (EfiInputKey::SCAN_END, _) => Self::End,
+            (EfiInputKey::SCAN_INSERT, _) => Self::Insert,
+            (EfiInputKey::SCAN_DELETE, _) => Self::Delete,
+            (EfiInputKey::SCAN_PAGE_UP, _) => Self::PageUp,
+            (EfiInputKey::SCAN_PAGE_DOWN, _) => Self::PageDown,
+            (EfiInputKey::SCAN_ESC, _) => Self::Escape,
+            (scan_code @ EfiInputKey::SCAN_F1..=EfiInputKey::SCAN_F10, _) => {
+                Self::Function(scan_code - EfiInputKey::SCAN_F1 + 1)
+            }
+            (scan_code, _) => Self::Unknown(scan_code),
+        }
+    }
+}
+
+/// Reads key strokes from the simple text input protocol.
+pub struct Keyboard<'a> {
+    input: &'a EfiSimpleTextInputProtocol,
+    boot_services: &'a EfiBootServices1x0,
+}
+
+impl<'a> Keyboard<'a> {
+    /// Creates new keyboard reading from the given input.
+    #[must_use]
+    pub fn new(
+        input: &'a EfiSimpleTextInputProtocol,
+        boot_services: &'a EfiBootServices1x0,
+    ) -> Self {
+        Self {
+            input,
+            boot_services,
+        }
+    }
+
+    /// Returns the pending key stroke, if there is one.
+    #[must_use]
+    pub fn poll_key(&self) -> Option<Key> {
+        self.input
+            .read_key_stroke()
+            .unfold()
+            .ok()
+            .map(|(_, key): (_, EfiInputKey)| Key::from(key))
+    }
+
+    /// Waits for a key stroke and returns it.
+    pub fn read_key(&self) -> EfiStatusEnum<Key> {
+        loop {
+            if let EfiStatusEnum::Error(status, ()) = self
+                .boot_services
+                .wait_for_event(&[*self.input.wait_for_key()])
+            {
+                return EfiStatusEnum::Error(status, ());
+            }
+
+            match self.input.read_key_stroke() {
+                EfiStatusEnum::Success(key) => return EfiStatusEnum::Success(Key::from(key)),
+                EfiStatusEnum::Warning(status, key) => {
+                    return EfiStatusEnum::Warning(status, Key::from(key))
+                }
+                /* Another consumer may have read the key after the event was signalled */
+                EfiStatusEnum::Error(EfiStatusError::EfiNotReady, ()) => (),
+                EfiStatusEnum::Error(status, ()) => return EfiStatusEnum::Error(status, ()),
+            }
+        }
+    }
+}
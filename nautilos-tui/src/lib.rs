@@ -0,0 +1,24 @@
+//! This crate provides a text-mode user interface on top of EFI's simple text protocols.
+//!
+//! It consists of:
+//! 1. [`Screen`], which selects the largest text mode and redraws only the cells changed since the last [`Screen::present`].
+//! 1. [`Keyboard`], which blocks on the input's `wait_for_key` event and decodes the pressed keys.
+//! 1. Widgets drawn on the screen (boxes, lists, menus, progress bars, line edits and dialogs).
+
+#![no_std]
+#![forbid(warnings, missing_docs, clippy::pedantic)]
+
+extern crate alloc;
+
+mod input;
+mod screen;
+mod widgets;
+
+pub use {
+    input::{Key, Keyboard},
+    screen::{Cell, Rect, Screen},
+    widgets::{
+        draw_box, message_dialog, LineEdit, LineEditAction, List, Menu, MenuAction, ProgressBar,
+        Theme,
+    },
+};
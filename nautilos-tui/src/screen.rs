@@ -0,0 +1,330 @@
+use {
+    alloc::{string::String, vec, vec::Vec},
+    efi::{
+        protocols::console::{EfiSimpleTextOutputProtocol, EfiTextAttribute},
+        EfiStatusEnum,
+    },
+};
+
+/// Character cell of the screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cell {
+    /// Displayed character.
+    pub character: char,
+    /// Colors of the cell.
+    pub attribute: EfiTextAttribute,
+}
+
+impl Cell {
+    /// Creates new cell. Control characters are replaced with spaces.
+    #[must_use]
+    pub fn new(character: char, attribute: EfiTextAttribute) -> Self {
+        Self {
+            character: if character.is_control() {
+                ' '
+            } else {
+                character
+            },
+            attribute,
+        }
+    }
+}
+
+/// Rectangular area of the screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rect {
+    /// Left-most column.
+    pub column: usize,
+    /// Top-most row.
+    pub row: usize,
+    /// Width in columns.
+    pub width: usize,
+    /// Height in rows.
+    pub height: usize,
+}
+
+impl Rect {
+    /// Creates new rectangle.
+    #[must_use]
+    pub const fn new(column: usize, row: usize, width: usize, height: usize) -> Self {
+        Self {
+            column,
+            row,
+            width,
+            height,
+        }
+    }
+
+    /// Returns a rectangle of the given size centered within this one (clamped to it's size).
+    #[must_use]
+    pub fn centered(&self, width: usize, height: usize) -> Self {
+        let (width, height): (usize, usize) = (width.min(self.width), height.min(self.height));
+
+        Self::new(
+            self.column + (self.width - width) / 2,
+            self.row + (self.height - height) / 2,
+            width,
+            height,
+        )
+    }
+
+    /// Returns the rectangle shrunk by one cell on every side (e.g.: the area inside a box).
+    #[must_use]
+    pub fn inner(&self) -> Self {
+        Self::new(
+            self.column + 1,
+            self.row + 1,
+            self.width.saturating_sub(2),
+            self.height.saturating_sub(2),
+        )
+    }
+}
+
+/// Double-buffered screen.
+///
+/// Drawing functions modify the back buffer, while [`present`](Self::present) rewrites only the cells which differ from the displayed ones.
+pub struct Screen<'a> {
+    output: &'a EfiSimpleTextOutputProtocol,
+    columns: usize,
+    rows: usize,
+    displayed: Vec<Option<Cell>>,
+    cells: Vec<Cell>,
+    cursor: Option<(usize, usize)>,
+}
+
+impl<'a> Screen<'a> {
+    /// Switches to the text mode with the most cells and creates blank screen.
+    pub fn new(output: &'a EfiSimpleTextOutputProtocol) -> EfiStatusEnum<Self> {
+        let dimensions = |mode: usize| -> Option<(usize, usize)> {
+            let (mut columns, mut rows): (usize, usize) = (0, 0);
+
+            output
+                .query_mode(mode, &mut columns, &mut rows)
+                .unfold()
+                .ok()
+                .map(|_| (columns, rows))
+        };
+
+        let current_mode: usize = output.get_mode().mode() as usize;
+
+        let best: Option<(usize, (usize, usize))> = (0..output.get_mode().max_mode() as usize)
+            .filter_map(|mode: usize| dimensions(mode).map(|dimensions| (mode, dimensions)))
+            .max_by_key(|&(_, (columns, rows)): &(usize, (usize, usize))| columns * rows);
+
+        let (columns, rows): (usize, usize) = match best {
+            Some((mode, dimensions)) if mode == current_mode => dimensions,
+            Some((mode, dimensions)) if output.set_mode(mode).is_success() => dimensions,
+            /* Keep the current mode if switching fails */
+            _ => match dimensions(current_mode) {
+                Some(dimensions) => dimensions,
+                None => return EfiStatusEnum::Error(efi::EfiStatusError::EfiUnsupported, ()),
+            },
+        };
+
+        let attribute: EfiTextAttribute = EfiTextAttribute::default();
+
+        if let EfiStatusEnum::Error(status, ()) = output.set_attribute(attribute) {
+            return EfiStatusEnum::Error(status, ());
+        }
+
+        if let EfiStatusEnum::Error(status, ()) = output.clear_screen() {
+            return EfiStatusEnum::Error(status, ());
+        }
+
+        let blank: Cell = Cell::new(' ', attribute);
+
+        EfiStatusEnum::Success(Self {
+            output,
+            columns,
+            rows,
+            displayed: vec![Some(blank); columns * rows],
+            cells: vec![blank; columns * rows],
+            cursor: None,
+        })
+    }
+
+    /// Returns the number of columns.
+    #[must_use]
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    /// Returns the number of rows.
+    #[must_use]
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Returns the whole screen's area.
+    #[must_use]
+    pub fn area(&self) -> Rect {
+        Rect::new(0, 0, self.columns, self.rows)
+    }
+
+    /// Returns the cell at the given position, if it's within the screen.
+    #[must_use]
+    pub fn get(&self, column: usize, row: usize) -> Option<Cell> {
+        if column < self.columns && row < self.rows {
+            Some(self.cells[row * self.columns + column])
+        } else {
+            None
+        }
+    }
+
+    /// Sets the cell at the given position. Positions outside of the screen are ignored.
+    pub fn put(&mut self, column: usize, row: usize, character: char, attribute: EfiTextAttribute) {
+        if column < self.columns && row < self.rows {
+            self.cells[row * self.columns + column] = Cell::new(character, attribute);
+        }
+    }
+
+    /// Prints the text starting at the given position without wrapping and returns the number of printed characters.
+    pub fn print(
+        &mut self,
+        column: usize,
+        row: usize,
+        text: &str,
+        attribute: EfiTextAttribute,
+    ) -> usize {
+        let mut printed: usize = 0;
+
+        for character in text.chars().take(self.columns.saturating_sub(column)) {
+            self.put(column + printed, row, character, attribute);
+
+            printed += 1;
+        }
+
+        printed
+    }
+
+    /// Prints at most `width` characters of the text and fills the rest of the width with spaces.
+    ///
+    /// Returns the number of written cells.
+    pub fn print_padded(
+        &mut self,
+        column: usize,
+        row: usize,
+        text: &str,
+        width: usize,
+        attribute: EfiTextAttribute,
+    ) -> usize {
+        let width: usize = width.min(self.columns.saturating_sub(column));
+
+        for (offset, character) in text
+            .chars()
+            .chain(core::iter::repeat(' '))
+            .take(width)
+            .enumerate()
+        {
+            self.put(column + offset, row, character, attribute);
+        }
+
+        width
+    }
+
+    /// Fills the area with the character.
+    pub fn fill(&mut self, area: Rect, character: char, attribute: EfiTextAttribute) {
+        for row in area.row..area.row + area.height {
+            for column in area.column..area.column + area.width {
+                self.put(column, row, character, attribute);
+            }
+        }
+    }
+
+    /// Fills the whole screen with spaces.
+    pub fn clear(&mut self, attribute: EfiTextAttribute) {
+        self.fill(self.area(), ' ', attribute);
+    }
+
+    /// Sets where the cursor is shown after presenting, or hides it.
+    pub fn set_cursor(&mut self, cursor: Option<(usize, usize)>) {
+        self.cursor = cursor;
+    }
+
+    /// Forces the next [`present`](Self::present) to rewrite every cell (e.g.: after something else wrote to the console).
+    pub fn invalidate(&mut self) {
+        self.displayed
+            .iter_mut()
+            .for_each(|cell: &mut Option<Cell>| *cell = None);
+    }
+
+    /// Writes the changed cells to the console.
+    pub fn present(&mut self) -> EfiStatusEnum {
+        /* Failing to hide the cursor only causes flickering */
+        let _ = self.output.enable_cursor(false);
+
+        let mut attribute: Option<EfiTextAttribute> = None;
+        let mut run: String = String::new();
+
+        for row in 0..self.rows {
+            let mut column: usize = 0;
+
+            while column < self.columns {
+                if !self.is_changed(column, row) {
+                    column += 1;
+
+                    continue;
+                }
+
+                let start: usize = column;
+                let run_attribute: EfiTextAttribute =
+                    self.cells[row * self.columns + column].attribute;
+
+                run.clear();
+
+                while column < self.columns
+                    && self.is_changed(column, row)
+                    && self.cells[row * self.columns + column].attribute == run_attribute
+                {
+                    let index: usize = row * self.columns + column;
+
+                    run.push(self.cells[index].character);
+
+                    self.displayed[index] = Some(self.cells[index]);
+
+                    column += 1;
+                }
+
+                if let EfiStatusEnum::Error(status, ()) =
+                    self.output.set_cursor_position(start, row)
+                {
+                    return EfiStatusEnum::Error(status, ());
+                }
+
+                if attribute != Some(run_attribute) {
+                    if let EfiStatusEnum::Error(status, ()) =
+                        self.output.set_attribute(run_attribute)
+                    {
+                        return EfiStatusEnum::Error(status, ());
+                    }
+
+                    attribute = Some(run_attribute);
+                }
+
+                if let EfiStatusEnum::Error(status, ()) = self.output.output_string(&run) {
+                    return EfiStatusEnum::Error(status, ());
+                }
+            }
+        }
+
+        match self.cursor {
+            Some((column, row)) => {
+                let result: EfiStatusEnum = self.output.set_cursor_position(column, row);
+
+                if result.is_error() {
+                    result
+                } else {
+                    self.output.enable_cursor(true)
+                }
+            }
+            None => EfiStatusEnum::Success(()),
+        }
+    }
+
+    fn is_changed(&self, column: usize, row: usize) -> bool {
+        let index: usize = row * self.columns + column;
+
+        /* Writing the last cell scrolls the screen on most consoles */
+        index + 1 != self.cells.len() && self.displayed[index] != Some(self.cells[index])
+    }
+}
@@ -0,0 +1,487 @@
+use {
+    crate::{
+        input::{Key, Keyboard},
+        screen::{Rect, Screen},
+    },
+    alloc::string::String,
+    core::convert::TryFrom,
+    efi::{
+        protocols::console::{EfiBackgroundColor, EfiForegroundColor, EfiTextAttribute},
+        EfiStatusEnum,
+    },
+};
+
+/// Colors used by the widgets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    /// Text and background.
+    pub normal: EfiTextAttribute,
+    /// Selected list entries, progress and edited text.
+    pub highlight: EfiTextAttribute,
+    /// Box borders and titles.
+    pub border: EfiTextAttribute,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            normal: EfiTextAttribute::new(EfiForegroundColor::LightGray, EfiBackgroundColor::Blue),
+            highlight: EfiTextAttribute::new(
+                EfiForegroundColor::Blue,
+                EfiBackgroundColor::LightGray,
+            ),
+            border: EfiTextAttribute::new(EfiForegroundColor::White, EfiBackgroundColor::Blue),
+        }
+    }
+}
+
+/// Draws a box with a single-line border around the area and clears it's inside.
+///
+/// The title is centered within the top border.
+pub fn draw_box(screen: &mut Screen, area: Rect, title: Option<&str>, theme: &Theme) {
+    if area.width < 2 || area.height < 2 {
+        return;
+    }
+
+    let (right, bottom): (usize, usize) =
+        (area.column + area.width - 1, area.row + area.height - 1);
+
+    screen.fill(area.inner(), ' ', theme.normal);
+
+    for column in area.column + 1..right {
+        screen.put(column, area.row, '\u{2500}', theme.border);
+        screen.put(column, bottom, '\u{2500}', theme.border);
+    }
+
+    for row in area.row + 1..bottom {
+        screen.put(area.column, row, '\u{2502}', theme.border);
+        screen.put(right, row, '\u{2502}', theme.border);
+    }
+
+    screen.put(area.column, area.row, '\u{250C}', theme.border);
+    screen.put(right, area.row, '\u{2510}', theme.border);
+    screen.put(area.column, bottom, '\u{2514}', theme.border);
+    screen.put(right, bottom, '\u{2518}', theme.border);
+
+    if let Some(title) = title {
+        let length: usize = (title.chars().count() + 2).min(area.width - 2);
+        let column: usize = area.column + (area.width - length) / 2;
+
+        screen.print_padded(column, area.row, "", length, theme.border);
+        screen.print_padded(
+            column + 1,
+            area.row,
+            title,
+            length.saturating_sub(2),
+            theme.border,
+        );
+    }
+}
+
+/// Scrollable list with a selected entry.
+pub struct List<'a, T: AsRef<str>> {
+    items: &'a [T],
+    selected: usize,
+    offset: usize,
+    visible: usize,
+}
+
+impl<'a, T: AsRef<str>> List<'a, T> {
+    /// Creates new list with the first entry selected.
+    #[must_use]
+    pub fn new(items: &'a [T]) -> Self {
+        Self {
+            items,
+            selected: 0,
+            offset: 0,
+            visible: 1,
+        }
+    }
+
+    /// Returns the listed entries.
+    #[must_use]
+    pub fn items(&self) -> &'a [T] {
+        self.items
+    }
+
+    /// Returns the index of the selected entry, unless the list is empty.
+    #[must_use]
+    pub fn selected(&self) -> Option<usize> {
+        if self.items.is_empty() {
+            None
+        } else {
+            Some(self.selected)
+        }
+    }
+
+    /// Selects the entry, clamping the index to the last one.
+    pub fn select(&mut self, index: usize) {
+        self.selected = index.min(self.items.len().saturating_sub(1));
+    }
+
+    /// Moves the selection with the arrow, `Home`, `End`, `Page Up` and `Page Down` keys.
+    ///
+    /// Returns whether the key was handled.
+    pub fn handle_key(&mut self, key: Key) -> bool {
+        let page: usize = self.visible.max(1);
+
+        match key {
+            Key::Up => self.select(self.selected.saturating_sub(1)),
+            Key::Down => self.select(self.selected + 1),
+            Key::Home => self.select(0),
+            Key::End => self.select(usize::MAX),
+            Key::PageUp => self.select(self.selected.saturating_sub(page)),
+            Key::PageDown => self.select(self.selected.saturating_add(page)),
+            _ => return false,
+        }
+
+        true
+    }
+
+    /// Draws the visible entries, scrolling so the selected one is shown.
+    ///
+    /// Arrows in the right-most column show whether there are more entries above or below.
+    pub fn draw(&mut self, screen: &mut Screen, area: Rect, theme: &Theme) {
+        self.visible = area.height.max(1);
+
+        if self.selected < self.offset {
+            self.offset = self.selected;
+        } else if self.selected >= self.offset + self.visible {
+            self.offset = self.selected + 1 - self.visible;
+        }
+
+        for line in 0..area.height {
+            let index: usize = self.offset + line;
+
+            let (text, attribute): (&str, EfiTextAttribute) = match self.items.get(index) {
+                Some(item) if index == self.selected => (item.as_ref(), theme.highlight),
+                Some(item) => (item.as_ref(), theme.normal),
+                None => ("", theme.normal),
+            };
+
+            screen.print_padded(area.column, area.row + line, text, area.width, attribute);
+        }
+
+        if area.width != 0 && area.height != 0 {
+            let column: usize = area.column + area.width - 1;
+
+            if self.offset != 0 {
+                screen.put(column, area.row, '\u{2191}', theme.border);
+            }
+
+            if self.offset + area.height < self.items.len() {
+                screen.put(column, area.row + area.height - 1, '\u{2193}', theme.border);
+            }
+        }
+    }
+}
+
+/// Result of passing a key to a [`Menu`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuAction {
+    /// The key only moved the selection or was ignored.
+    None,
+    /// The entry with the given index was chosen with `Enter`.
+    Selected(usize),
+    /// The menu was closed with `Esc`.
+    Cancelled,
+}
+
+/// Boxed list of entries which can be chosen.
+pub struct Menu<'a, T: AsRef<str>> {
+    title: &'a str,
+    list: List<'a, T>,
+}
+
+impl<'a, T: AsRef<str>> Menu<'a, T> {
+    /// Creates new menu with the first entry selected.
+    #[must_use]
+    pub fn new(title: &'a str, items: &'a [T]) -> Self {
+        Self {
+            title,
+            list: List::new(items),
+        }
+    }
+
+    /// Returns the menu's list (e.g.: to preselect an entry).
+    pub fn list_mut(&mut self) -> &mut List<'a, T> {
+        &mut self.list
+    }
+
+    /// Handles the key.
+    pub fn handle_key(&mut self, key: Key) -> MenuAction {
+        match key {
+            Key::Enter => self
+                .list
+                .selected()
+                .map_or(MenuAction::None, MenuAction::Selected),
+            Key::Escape => MenuAction::Cancelled,
+            key => {
+                self.list.handle_key(key);
+
+                MenuAction::None
+            }
+        }
+    }
+
+    /// Draws the menu's box and entries.
+    pub fn draw(&mut self, screen: &mut Screen, area: Rect, theme: &Theme) {
+        draw_box(screen, area, Some(self.title), theme);
+
+        self.list.draw(screen, area.inner(), theme);
+    }
+}
+
+/// Horizontal progress bar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProgressBar {
+    /// Current progress.
+    pub value: u64,
+    /// Value at which the progress is complete.
+    pub maximum: u64,
+}
+
+impl ProgressBar {
+    /// Creates new progress bar.
+    #[must_use]
+    pub fn new(value: u64, maximum: u64) -> Self {
+        Self { value, maximum }
+    }
+
+    /// Draws the bar with the percentage centered in it.
+    pub fn draw(
+        &self,
+        screen: &mut Screen,
+        column: usize,
+        row: usize,
+        width: usize,
+        theme: &Theme,
+    ) {
+        use core::fmt::Write;
+
+        let value: u64 = self.value.min(self.maximum);
+        let width_u64: u64 = u64::try_from(width).unwrap_or(u64::MAX);
+
+        /* Empty range is displayed as complete */
+        let filled: u64 = width_u64
+            .saturating_mul(value)
+            .checked_div(self.maximum)
+            .unwrap_or(width_u64);
+        let percentage: u64 = value
+            .saturating_mul(100)
+            .checked_div(self.maximum)
+            .unwrap_or(100);
+
+        let filled: usize = usize::try_from(filled).unwrap_or(width);
+
+        let mut label: String = String::new();
+
+        let _ = write!(label, "{percentage}%");
+
+        let label_start: usize = width.saturating_sub(label.len()) / 2;
+
+        for (offset, position) in (column..column + width).enumerate() {
+            let character: char = label
+                .chars()
+                .nth(offset.wrapping_sub(label_start))
+                .unwrap_or(if offset < filled {
+                    '\u{2588}'
+                } else {
+                    '\u{2591}'
+                });
+
+            let attribute: EfiTextAttribute = if offset < filled {
+                theme.highlight
+            } else {
+                theme.normal
+            };
+
+            screen.put(position, row, character, attribute);
+        }
+    }
+}
+
+/// Result of passing a key to a [`LineEdit`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineEditAction {
+    /// The key was ignored or only moved the cursor.
+    None,
+    /// The text was changed.
+    Changed,
+    /// Editing was finished with `Enter`.
+    Submitted,
+    /// Editing was cancelled with `Esc`.
+    Cancelled,
+}
+
+/// Single-line text input.
+pub struct LineEdit {
+    text: String,
+    cursor: usize,
+    offset: usize,
+    max_length: usize,
+}
+
+impl LineEdit {
+    /// Creates new empty input accepting up to `max_length` characters.
+    #[must_use]
+    pub fn new(max_length: usize) -> Self {
+        Self {
+            text: String::new(),
+            cursor: 0,
+            offset: 0,
+            max_length,
+        }
+    }
+
+    /// Replaces the text and moves the cursor after it's end.
+    pub fn set_text(&mut self, text: &str) {
+        self.text = text.chars().take(self.max_length).collect();
+        self.cursor = self.text.chars().count();
+    }
+
+    /// Returns the edited text.
+    #[must_use]
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    fn byte_index(&self, index: usize) -> usize {
+        self.text
+            .char_indices()
+            .nth(index)
+            .map_or(self.text.len(), |(byte_index, _): (usize, char)| byte_index)
+    }
+
+    /// Handles the key.
+    pub fn handle_key(&mut self, key: Key) -> LineEditAction {
+        let length: usize = self.text.chars().count();
+
+        match key {
+            Key::Character(character) if length < self.max_length => {
+                let index: usize = self.byte_index(self.cursor);
+
+                self.text.insert(index, character);
+                self.cursor += 1;
+
+                LineEditAction::Changed
+            }
+            Key::Backspace if self.cursor != 0 => {
+                self.cursor -= 1;
+
+                let index: usize = self.byte_index(self.cursor);
+
+                self.text.remove(index);
+
+                LineEditAction::Changed
+            }
+            Key::Delete if self.cursor < length => {
+                let index: usize = self.byte_index(self.cursor);
+
+                self.text.remove(index);
+
+                LineEditAction::Changed
+            }
+            Key::Left => {
+                self.cursor = self.cursor.saturating_sub(1);
+
+                LineEditAction::None
+            }
+            Key::Right => {
+                self.cursor = (self.cursor + 1).min(length);
+
+                LineEditAction::None
+            }
+            Key::Home => {
+                self.cursor = 0;
+
+                LineEditAction::None
+            }
+            Key::End => {
+                self.cursor = length;
+
+                LineEditAction::None
+            }
+            Key::Enter => LineEditAction::Submitted,
+            Key::Escape => LineEditAction::Cancelled,
+            _ => LineEditAction::None,
+        }
+    }
+
+    /// Draws the visible part of the text and places the screen's cursor.
+    pub fn draw(
+        &mut self,
+        screen: &mut Screen,
+        column: usize,
+        row: usize,
+        width: usize,
+        theme: &Theme,
+    ) {
+        if width == 0 {
+            return;
+        }
+
+        /* Scroll horizontally to keep the cursor visible */
+        if self.cursor < self.offset {
+            self.offset = self.cursor;
+        } else if self.cursor >= self.offset + width {
+            self.offset = self.cursor + 1 - width;
+        }
+
+        let visible: &str = &self.text[self.byte_index(self.offset)..];
+
+        screen.print_padded(column, row, visible, width, theme.highlight);
+        screen.set_cursor(Some((column + self.cursor - self.offset, row)));
+    }
+}
+
+/// Shows a message in a centered box until `Enter` or `Esc` is pressed and returns the pressed key.
+///
+/// The area below the dialog isn't restored, so the caller should redraw it afterwards.
+pub fn message_dialog(
+    screen: &mut Screen,
+    keyboard: &Keyboard,
+    title: &str,
+    message: &str,
+    theme: &Theme,
+) -> EfiStatusEnum<Key> {
+    let width: usize = message
+        .lines()
+        .map(|line: &str| line.chars().count())
+        .chain(Some(title.chars().count() + 2))
+        .max()
+        .unwrap_or(0)
+        + 4;
+    let height: usize = message.lines().count() + 4;
+
+    let area: Rect = screen.area().centered(width, height);
+
+    draw_box(screen, area, Some(title), theme);
+
+    for (line, text) in message.lines().enumerate() {
+        screen.print_padded(
+            area.column + 2,
+            area.row + 2 + line,
+            text,
+            area.width.saturating_sub(4),
+            theme.normal,
+        );
+    }
+
+    screen.set_cursor(None);
+
+    if let EfiStatusEnum::Error(status, ()) = screen.present() {
+        return EfiStatusEnum::Error(status, ());
+    }
+
+    loop {
+        match keyboard.read_key() {
+            EfiStatusEnum::Success(key @ (Key::Enter | Key::Escape))
+            | EfiStatusEnum::Warning(_, key @ (Key::Enter | Key::Escape)) => {
+                return EfiStatusEnum::Success(key)
+            }
+            EfiStatusEnum::Error(status, ()) => return EfiStatusEnum::Error(status, ()),
+            _ => (),
+        }
+    }
+}
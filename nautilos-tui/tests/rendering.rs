@@ -0,0 +1,299 @@
+use {
+    efi::{
+        protocols::console::{EfiInputKey, EfiSimpleTextOutputProtocol},
+        EfiStatusEnum, EfiSystemTable,
+    },
+    efi_simulator::Simulator,
+    nautilos_tui::{
+        draw_box, message_dialog, Key, Keyboard, LineEdit, LineEditAction, List, Menu, MenuAction,
+        ProgressBar, Rect, Screen, Theme,
+    },
+};
+
+fn screen(con_out: &EfiSimpleTextOutputProtocol) -> Screen<'_> {
+    match Screen::new(con_out) {
+        EfiStatusEnum::Success(screen) => screen,
+        _ => panic!("Creating the screen failed!"),
+    }
+}
+
+fn attribute(simulator: &Simulator, column: usize, row: usize) -> usize {
+    simulator.console_attribute(column, row).unwrap() as usize
+}
+
+#[test]
+fn screen_switches_to_the_largest_mode() {
+    let simulator: Simulator = Simulator::builder().build().unwrap();
+    let con_out: &EfiSimpleTextOutputProtocol = simulator.system_table().con_out().unwrap();
+
+    assert!(con_out.output_string("Firmware logo").is_success());
+
+    let screen: Screen = screen(con_out);
+
+    assert_eq!((screen.columns(), screen.rows()), (80, 50));
+    assert_eq!(con_out.get_mode().mode(), 1);
+    assert_eq!(simulator.console_screen(), vec![String::new(); 50]);
+}
+
+#[test]
+fn boxes_are_drawn_with_their_title() {
+    let simulator: Simulator = Simulator::builder().build().unwrap();
+    let con_out: &EfiSimpleTextOutputProtocol = simulator.system_table().con_out().unwrap();
+    let theme: Theme = Theme::default();
+
+    let mut screen: Screen = screen(con_out);
+
+    /* The borders are longer than the protocol's buffer, so they're written in several parts */
+    draw_box(&mut screen, Rect::new(0, 0, 80, 4), Some("Boot"), &theme);
+
+    assert!(screen.present().is_success());
+
+    let rows: Vec<String> = simulator.console_screen();
+
+    assert_eq!(
+        rows[0],
+        format!(
+            "\u{250C}{}{}{}\u{2510}",
+            "\u{2500}".repeat(36),
+            " Boot ",
+            "\u{2500}".repeat(36)
+        )
+    );
+    assert_eq!(rows[1], format!("\u{2502}{}\u{2502}", " ".repeat(78)));
+    assert_eq!(rows[2], rows[1]);
+    assert_eq!(
+        rows[3],
+        format!("\u{2514}{}\u{2518}", "\u{2500}".repeat(78))
+    );
+    assert!(rows[4..].iter().all(String::is_empty));
+
+    assert_eq!(attribute(&simulator, 0, 0), theme.border.raw());
+    assert_eq!(attribute(&simulator, 38, 0), theme.border.raw());
+    assert_eq!(attribute(&simulator, 1, 1), theme.normal.raw());
+}
+
+#[test]
+fn present_rewrites_only_the_changed_cells() {
+    let simulator: Simulator = Simulator::builder().build().unwrap();
+    let con_out: &EfiSimpleTextOutputProtocol = simulator.system_table().con_out().unwrap();
+    let theme: Theme = Theme::default();
+
+    let mut screen: Screen = screen(con_out);
+
+    /* Nothing changed since the screen was cleared */
+    assert!(screen.present().is_success());
+    assert_eq!(simulator.console_output(), "");
+
+    screen.print(5, 3, "Hi", theme.normal);
+    screen.put(10, 3, '!', theme.highlight);
+
+    assert!(screen.present().is_success());
+    assert_eq!(simulator.console_output(), "Hi!");
+    assert_eq!(simulator.console_screen()[3], "     Hi   !");
+    assert_eq!(attribute(&simulator, 6, 3), theme.normal.raw());
+    assert_eq!(attribute(&simulator, 10, 3), theme.highlight.raw());
+    assert!(!con_out.get_mode().cursor_visible());
+
+    assert!(screen.present().is_success());
+    assert_eq!(simulator.console_output(), "Hi!");
+
+    /* Writing the last cell would scroll the console */
+    screen.put(79, 49, '#', theme.normal);
+
+    assert!(screen.present().is_success());
+    assert_eq!(simulator.console_output(), "Hi!");
+
+    screen.invalidate();
+
+    assert!(screen.present().is_success());
+    assert_eq!(simulator.console_output().chars().count(), 3 + 80 * 50 - 1);
+    assert_eq!(simulator.console_screen()[3], "     Hi   !");
+    assert_eq!(simulator.console_screen()[49], "");
+}
+
+#[test]
+fn menus_scroll_to_the_selection() {
+    let simulator: Simulator = Simulator::builder().build().unwrap();
+    let con_out: &EfiSimpleTextOutputProtocol = simulator.system_table().con_out().unwrap();
+    let theme: Theme = Theme::default();
+
+    let entries: Vec<String> = (0..10).map(|entry| format!("Entry {}", entry)).collect();
+
+    let mut screen: Screen = screen(con_out);
+    let mut menu: Menu<String> = Menu::new("Boot", &entries);
+
+    menu.draw(&mut screen, Rect::new(0, 0, 20, 6), &theme);
+
+    assert!(screen.present().is_success());
+
+    let rows: Vec<String> = simulator.console_screen();
+
+    assert_eq!(
+        rows[1],
+        format!("\u{2502}Entry 0{}\u{2502}", " ".repeat(11))
+    );
+    assert_eq!(
+        rows[4],
+        format!("\u{2502}Entry 3{}\u{2193}\u{2502}", " ".repeat(10))
+    );
+    assert_eq!(attribute(&simulator, 1, 1), theme.highlight.raw());
+    assert_eq!(attribute(&simulator, 1, 2), theme.normal.raw());
+
+    assert_eq!(menu.handle_key(Key::End), MenuAction::None);
+
+    menu.draw(&mut screen, Rect::new(0, 0, 20, 6), &theme);
+
+    assert!(screen.present().is_success());
+
+    let rows: Vec<String> = simulator.console_screen();
+
+    assert_eq!(
+        rows[1],
+        format!("\u{2502}Entry 6{}\u{2191}\u{2502}", " ".repeat(10))
+    );
+    assert_eq!(
+        rows[4],
+        format!("\u{2502}Entry 9{}\u{2502}", " ".repeat(11))
+    );
+    assert_eq!(attribute(&simulator, 1, 4), theme.highlight.raw());
+    assert_eq!(attribute(&simulator, 1, 1), theme.normal.raw());
+
+    /* A page up moves the selection by the number of visible entries */
+    assert_eq!(menu.handle_key(Key::PageUp), MenuAction::None);
+    assert_eq!(menu.handle_key(Key::Enter), MenuAction::Selected(5));
+    assert_eq!(menu.handle_key(Key::Escape), MenuAction::Cancelled);
+
+    let empty: [&str; 0] = [];
+    let mut list: List<&str> = List::new(&empty);
+
+    assert!(list.handle_key(Key::Down));
+    assert_eq!(list.selected(), None);
+}
+
+#[test]
+fn progress_bars_show_the_percentage() {
+    let simulator: Simulator = Simulator::builder().build().unwrap();
+    let con_out: &EfiSimpleTextOutputProtocol = simulator.system_table().con_out().unwrap();
+    let theme: Theme = Theme::default();
+
+    let mut screen: Screen = screen(con_out);
+
+    ProgressBar::new(1, 4).draw(&mut screen, 0, 0, 20, &theme);
+    /* Empty range is complete */
+    ProgressBar::new(0, 0).draw(&mut screen, 0, 1, 10, &theme);
+
+    assert!(screen.present().is_success());
+
+    let rows: Vec<String> = simulator.console_screen();
+
+    assert_eq!(
+        rows[0],
+        format!(
+            "{}{}25%{}",
+            "\u{2588}".repeat(5),
+            "\u{2591}".repeat(3),
+            "\u{2591}".repeat(9)
+        )
+    );
+    assert_eq!(
+        rows[1],
+        format!("{}100%{}", "\u{2588}".repeat(3), "\u{2588}".repeat(3))
+    );
+    assert_eq!(attribute(&simulator, 4, 0), theme.highlight.raw());
+    assert_eq!(attribute(&simulator, 5, 0), theme.normal.raw());
+}
+
+#[test]
+fn line_edits_scroll_and_place_the_cursor() {
+    let simulator: Simulator = Simulator::builder().build().unwrap();
+    let con_out: &EfiSimpleTextOutputProtocol = simulator.system_table().con_out().unwrap();
+    let theme: Theme = Theme::default();
+
+    let mut screen: Screen = screen(con_out);
+    let mut line_edit: LineEdit = LineEdit::new(12);
+
+    for character in "h\u{E9}llo world!?".chars() {
+        line_edit.handle_key(Key::Character(character));
+    }
+
+    /* The last character exceeds the maximum length */
+    assert_eq!(line_edit.text(), "h\u{E9}llo world!");
+
+    line_edit.draw(&mut screen, 2, 1, 8, &theme);
+
+    assert!(screen.present().is_success());
+    assert_eq!(simulator.console_screen()[1], "   world!");
+    assert_eq!(attribute(&simulator, 9, 1), theme.highlight.raw());
+    assert_eq!(con_out.get_mode().cursor_column(), 9);
+    assert_eq!(con_out.get_mode().cursor_row(), 1);
+    assert!(con_out.get_mode().cursor_visible());
+
+    assert_eq!(line_edit.handle_key(Key::Home), LineEditAction::None);
+
+    line_edit.draw(&mut screen, 2, 1, 8, &theme);
+
+    assert!(screen.present().is_success());
+    assert_eq!(simulator.console_screen()[1], "  h\u{E9}llo wo");
+    assert_eq!(con_out.get_mode().cursor_column(), 2);
+
+    assert_eq!(line_edit.handle_key(Key::Delete), LineEditAction::Changed);
+    assert_eq!(line_edit.handle_key(Key::Right), LineEditAction::None);
+
+    line_edit.draw(&mut screen, 2, 1, 8, &theme);
+
+    assert!(screen.present().is_success());
+    assert_eq!(simulator.console_screen()[1], "  \u{E9}llo wor");
+    assert_eq!(con_out.get_mode().cursor_column(), 3);
+
+    assert_eq!(
+        line_edit.handle_key(Key::Backspace),
+        LineEditAction::Changed
+    );
+    assert_eq!(line_edit.handle_key(Key::Enter), LineEditAction::Submitted);
+    assert_eq!(line_edit.text(), "llo world!");
+}
+
+#[test]
+fn message_dialogs_wait_for_enter_or_escape() {
+    let simulator: Simulator = Simulator::builder().build().unwrap();
+    let system_table: &EfiSystemTable = simulator.system_table();
+    let theme: Theme = Theme::default();
+
+    let mut screen: Screen = screen(system_table.con_out().unwrap());
+    let keyboard: Keyboard =
+        Keyboard::new(system_table.con_in().unwrap(), system_table.boot_services());
+
+    assert_eq!(keyboard.poll_key(), None);
+
+    simulator.push_key(EfiInputKey::SCAN_UP, 0);
+    simulator.push_str("x\r");
+    simulator.push_key(EfiInputKey::SCAN_F1 + 1, 0);
+
+    assert_eq!(keyboard.poll_key(), Some(Key::Up));
+
+    match message_dialog(&mut screen, &keyboard, "Info", "Hello", &theme) {
+        EfiStatusEnum::Success(key) => assert_eq!(key, Key::Enter),
+        _ => panic!("The dialog failed!"),
+    }
+
+    let rows: Vec<String> = simulator.console_screen();
+
+    assert_eq!(
+        rows[22],
+        format!("{}\u{250C}\u{2500} Info \u{2500}\u{2510}", " ".repeat(35))
+    );
+    assert_eq!(
+        rows[24],
+        format!("{}\u{2502} Hello  \u{2502}", " ".repeat(35))
+    );
+    assert_eq!(
+        rows[26],
+        format!("{}\u{2514}{}\u{2518}", " ".repeat(35), "\u{2500}".repeat(8))
+    );
+
+    /* The keys after the closing one are left for the caller */
+    match keyboard.read_key() {
+        EfiStatusEnum::Success(key) => assert_eq!(key, Key::Function(2)),
+        _ => panic!("Reading the key failed!"),
+    }
+}